@@ -0,0 +1,277 @@
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::expr::{Expr, Operand};
+use crate::weather::WeatherData;
+
+/// Chance of precipitation in % above which an umbrella is advised.
+pub const RAIN_CHANCE: f64 = 50.0;
+
+/// UV index above which sunscreen is advised.
+pub const UV_INDEX: f64 = 6.0;
+
+/// Wind or gust speed in km/h above which loose items should be secured.
+pub const WIND_KPH: f64 = 50.0;
+
+/// Temperature in °C below which precipitation brings a risk of ice.
+pub const ICE_BELOW_C: f64 = 0.0;
+
+/// Fields an advice condition can compare, see [`field`].
+pub const FIELDS: &[&str] = &[
+    "temp_c",
+    "feels_like_c",
+    "min_temp_c",
+    "max_temp_c",
+    "humidity",
+    "wind_kph",
+    "gust_kph",
+    "uv",
+    "pressure_mb",
+    "visibility_km",
+    "cloud",
+    "precip_chance",
+    "precip_mm",
+];
+
+/// One advice rule: a condition on the weather and the line shown when it
+/// holds.
+///
+/// ```json
+/// { "id": "umbrella", "when": "precip_chance > 50", "message": "Take an umbrella" }
+/// ```
+///
+/// # Fields
+/// - `id`: name of the rule, listed in JSON output when it fires,
+/// - `when`: condition over [`FIELDS`], see [`Expr`],
+/// - `message`: the advice shown.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdviceRule {
+    pub id: String,
+    pub when: String,
+    pub message: String,
+}
+
+impl AdviceRule {
+    fn new(id: &str, when: String, message: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            when,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// The `advice` section of the config: thresholds of the built-in rules,
+/// each falling back to its default when not set, and rules of one's own.
+///
+/// ```json
+/// { "advice": { "uv": 4, "wind_kph": 40, "rules": [
+///     { "id": "scarf", "when": "feels_like_c < -10", "message": "Wear a scarf" }
+/// ] } }
+/// ```
+///
+/// # Fields
+/// - `rain_chance`: above it, `umbrella` fires (default [`RAIN_CHANCE`]),
+/// - `uv`: above it, `sunscreen` fires (default [`UV_INDEX`]),
+/// - `wind_kph`: wind or gusts above it fire `wind` (default [`WIND_KPH`]),
+/// - `ice_below_c`: precipitation below it fires `ice` (default
+///   [`ICE_BELOW_C`]),
+/// - `rules`: more rules, after the built-in ones; one with the `id` of a
+///   built-in rule replaces it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AdviceSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rain_chance: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uv: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_kph: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ice_below_c: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AdviceRule>,
+}
+
+impl AdviceSettings {
+    /// The rules of these settings: the built-in ones with their
+    /// thresholds, in order, then the configured ones.
+    pub fn rules(&self) -> Vec<AdviceRule> {
+        let builtin = [
+            AdviceRule::new(
+                "umbrella",
+                format!(
+                    "precip_chance > {}",
+                    self.rain_chance.unwrap_or(RAIN_CHANCE)
+                ),
+                "Take an umbrella",
+            ),
+            AdviceRule::new(
+                "sunscreen",
+                format!("uv > {}", self.uv.unwrap_or(UV_INDEX)),
+                "High UV — sunscreen recommended",
+            ),
+            AdviceRule::new(
+                "wind",
+                format!(
+                    "wind_kph > {limit} || gust_kph > {limit}",
+                    limit = self.wind_kph.unwrap_or(WIND_KPH)
+                ),
+                "Strong wind — secure loose items",
+            ),
+            AdviceRule::new(
+                "ice",
+                format!(
+                    "precip_mm > 0 && min_temp_c < {}",
+                    self.ice_below_c.unwrap_or(ICE_BELOW_C)
+                ),
+                "Ice risk",
+            ),
+        ];
+
+        let mut rules: Vec<AdviceRule> = builtin
+            .into_iter()
+            .filter(|rule| !self.rules.iter().any(|own| own.id == rule.id))
+            .collect();
+        rules.extend(self.rules.iter().cloned());
+        rules
+    }
+}
+
+/// A rule that fired, as listed in JSON output.
+///
+/// # Fields
+/// - `id`: the rule's id, e.g. `umbrella`,
+/// - `message`: its advice, e.g. `Take an umbrella`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Advice {
+    pub id: String,
+    pub message: String,
+}
+
+/// Evaluates advice rules against weather data.
+///
+/// Conditions are parsed once, up front; a field the data lacks makes its
+/// condition false, so rules on forecast values stay quiet for data
+/// without a forecast.
+#[derive(Debug, Clone)]
+pub struct AdviceEngine {
+    rules: Vec<(AdviceRule, Expr)>,
+}
+
+impl AdviceEngine {
+    /// An engine evaluating `rules` in order.
+    ///
+    /// # Errors
+    /// Returns an error naming the first rule whose condition cannot be
+    /// parsed or compares an unknown field.
+    pub fn new(rules: Vec<AdviceRule>) -> Result<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let expr = Expr::parse(&rule.when)
+                    .map_err(|e| anyhow!("advice rule '{}': {}", rule.id, e))?;
+                // A rule on an unknown field would never fire.
+                if let Some(name) = unknown_field(&expr) {
+                    return Err(anyhow!(
+                        "advice rule '{}': unknown field '{}', expected one of: {}",
+                        rule.id,
+                        name,
+                        FIELDS.join(", ")
+                    ));
+                }
+                Ok((rule, expr))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// The advice for `data`: every rule whose condition holds, in order.
+    pub fn evaluate(&self, data: &WeatherData) -> Vec<Advice> {
+        let lookup = |name: &str| field(data, name);
+
+        self.rules
+            .iter()
+            .filter(|(_, expr)| expr.eval(&lookup).unwrap_or(false))
+            .map(|(rule, _)| Advice {
+                id: rule.id.clone(),
+                message: rule.message.clone(),
+            })
+            .collect()
+    }
+}
+
+/// The value of the advice field `name` in `data`, one of [`FIELDS`]:
+///
+/// - current values as reported, `uv` being the UV index,
+/// - `min_temp_c`, `max_temp_c`: the lowest and highest temperature of the
+///   hours, else of the first forecast day, else the current one,
+/// - `precip_chance`: the highest chance of precipitation in % of the
+///   hours, else of the first forecast day,
+/// - `precip_mm`: the precipitation in mm over the hours, else of the
+///   first forecast day.
+pub fn field(data: &WeatherData, name: &str) -> Option<f64> {
+    let day = data.forecast.first();
+    let hours = || data.hourly.iter();
+    let max = |values: Vec<f64>| values.into_iter().reduce(f64::max);
+    let min = |values: Vec<f64>| values.into_iter().reduce(f64::min);
+
+    match name {
+        "temp_c" => data.temp_c,
+        "feels_like_c" => data.feels_like_c,
+        "min_temp_c" => min(hours().filter_map(|h| h.temp_c).collect())
+            .or(day.and_then(|d| d.min_c))
+            .or(data.temp_c),
+        "max_temp_c" => max(hours().filter_map(|h| h.temp_c).collect())
+            .or(day.and_then(|d| d.max_c))
+            .or(data.temp_c),
+        "humidity" => data.humidity,
+        "wind_kph" => data.wind_kph,
+        "gust_kph" => data.gust_kph,
+        "uv" => data.uv_index,
+        "pressure_mb" => data.pressure_mb,
+        "visibility_km" => data.visibility_km,
+        "cloud" => data.cloud,
+        "precip_chance" => max(hours().filter_map(|h| h.precip_chance).collect())
+            .or(day.and_then(|d| d.precip_chance)),
+        "precip_mm" => hours()
+            .filter_map(|h| h.precip_mm)
+            .reduce(|a, b| a + b)
+            .or(day.and_then(|d| d.precip_mm)),
+        _ => None,
+    }
+}
+
+/// The first field of `expr` not in [`FIELDS`].
+fn unknown_field(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Or(a, b) | Expr::And(a, b) => unknown_field(a).or_else(|| unknown_field(b)),
+        Expr::Not(a) => unknown_field(a),
+        Expr::Compare(left, _, right) => {
+            [left, right].into_iter().find_map(|operand| match operand {
+                Operand::Field(name) if !FIELDS.contains(&name.as_str()) => Some(name.as_str()),
+                _ => None,
+            })
+        }
+    }
+}
+
+/// Engine set by [`use_advice`].
+static ENGINE: OnceLock<AdviceEngine> = OnceLock::new();
+
+/// Makes `get` of this process add the advice of `engine` to its output
+/// (`get --advice`); the first call wins.
+pub fn use_advice(engine: AdviceEngine) {
+    let _ = ENGINE.set(engine);
+}
+
+/// The engine set with [`use_advice`]; `None` without `--advice`.
+pub fn engine() -> Option<&'static AdviceEngine> {
+    ENGINE.get()
+}
@@ -0,0 +1,458 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// Air pollutants tracked by the normalized air-quality model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pollutant {
+    Pm2_5,
+    Pm10,
+    O3,
+    No2,
+    So2,
+    Co,
+}
+
+impl Pollutant {
+    /// The usual chemical or size name, e.g. `PM2.5` or `NO2`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Pollutant::Pm2_5 => "PM2.5",
+            Pollutant::Pm10 => "PM10",
+            Pollutant::O3 => "O3",
+            Pollutant::No2 => "NO2",
+            Pollutant::So2 => "SO2",
+            Pollutant::Co => "CO",
+        }
+    }
+}
+
+/// Raw pollutant concentrations in µg/m³, as reported by the provider.
+///
+/// Every field is optional because providers report different subsets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Pollutants {
+    pub pm2_5: Option<f64>,
+    pub pm10: Option<f64>,
+    pub o3: Option<f64>,
+    pub no2: Option<f64>,
+    pub so2: Option<f64>,
+    pub co: Option<f64>,
+}
+
+impl Pollutants {
+    /// Returns the concentration of a single pollutant.
+    pub fn get(&self, pollutant: Pollutant) -> Option<f64> {
+        match pollutant {
+            Pollutant::Pm2_5 => self.pm2_5,
+            Pollutant::Pm10 => self.pm10,
+            Pollutant::O3 => self.o3,
+            Pollutant::No2 => self.no2,
+            Pollutant::So2 => self.so2,
+            Pollutant::Co => self.co,
+        }
+    }
+}
+
+/// Scale an air-quality index value is expressed in.
+///
+/// - `UsAqi`: US EPA Air Quality Index, 0–500,
+/// - `EuCaqi`: European Common Air Quality Index (hourly), 0–100+,
+/// - `UsEpaCategory`: WeatherAPI's `us-epa-index`, 1–6,
+/// - `UkDefra`: UK DEFRA Daily Air Quality Index, 1–10,
+/// - `OpenWeather`: OpenWeatherMap's own index, 1–5.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AqiScale {
+    UsAqi,
+    EuCaqi,
+    UsEpaCategory,
+    UkDefra,
+    OpenWeather,
+}
+
+impl AqiScale {
+    /// The name and range of the scale, e.g. `US AQI, 0–500`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UsAqi => "US AQI, 0–500",
+            Self::EuCaqi => "European CAQI, 0–100+",
+            Self::UsEpaCategory => "US EPA index, 1–6",
+            Self::UkDefra => "UK DEFRA index, 1–10",
+            Self::OpenWeather => "OpenWeatherMap index, 1–5",
+        }
+    }
+
+    /// The category name of `value` on this scale, e.g. `Fair` for 2 on
+    /// OpenWeatherMap's scale.
+    pub fn category(self, value: f64) -> &'static str {
+        let level = value.round().max(0.0) as u32;
+
+        match self {
+            Self::UsAqi => us_aqi_category(level),
+            Self::EuCaqi => caqi_category(level),
+            Self::UsEpaCategory => match level {
+                0..=1 => "Good",
+                2 => "Moderate",
+                3 => "Unhealthy for Sensitive Groups",
+                4 => "Unhealthy",
+                5 => "Very Unhealthy",
+                _ => "Hazardous",
+            },
+            Self::UkDefra => match level {
+                0..=3 => "Low",
+                4..=6 => "Moderate",
+                7..=9 => "High",
+                _ => "Very high",
+            },
+            Self::OpenWeather => match level {
+                0..=1 => "Good",
+                2 => "Fair",
+                3 => "Moderate",
+                4 => "Poor",
+                _ => "Very poor",
+            },
+        }
+    }
+}
+
+/// Whether an index value came from the provider or was computed by wapp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    Provider,
+    Computed,
+}
+
+/// A single index value together with its scale, category on that
+/// scale, and origin.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexReading {
+    pub scale: AqiScale,
+    pub value: f64,
+    pub category: &'static str,
+    pub source: ValueSource,
+}
+
+impl IndexReading {
+    fn new(scale: AqiScale, value: f64, source: ValueSource) -> Self {
+        Self {
+            scale,
+            value,
+            category: scale.category(value),
+            source,
+        }
+    }
+}
+
+/// Provider-agnostic air-quality report.
+///
+/// Carries the raw concentrations plus every index known for them: values
+/// supplied by the provider and the US AQI / European CAQI computed from the
+/// concentrations, so reports from different providers can be compared.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AirQuality {
+    pub pollutants: Pollutants,
+    pub indices: Vec<IndexReading>,
+}
+
+impl AirQuality {
+    /// Builds a report from concentrations and the provider's own indices.
+    ///
+    /// The US AQI and European CAQI are computed and appended when enough
+    /// pollutants are present.
+    pub fn normalize(pollutants: Pollutants, provider_indices: Vec<(AqiScale, f64)>) -> Self {
+        let mut indices: Vec<IndexReading> = provider_indices
+            .into_iter()
+            .map(|(scale, value)| IndexReading::new(scale, value, ValueSource::Provider))
+            .collect();
+
+        if let Some(value) = us_aqi(&pollutants) {
+            indices.push(IndexReading::new(
+                AqiScale::UsAqi,
+                value as f64,
+                ValueSource::Computed,
+            ));
+        }
+
+        if let Some(value) = eu_caqi(&pollutants) {
+            indices.push(IndexReading::new(
+                AqiScale::EuCaqi,
+                value as f64,
+                ValueSource::Computed,
+            ));
+        }
+
+        Self {
+            pollutants,
+            indices,
+        }
+    }
+
+    /// Returns the headline index: the provider's own, else the first
+    /// computed one.
+    pub fn primary(&self) -> Option<&IndexReading> {
+        self.indices
+            .iter()
+            .find(|i| i.source == ValueSource::Provider)
+            .or(self.indices.first())
+    }
+
+    /// Returns the first index reading on the given scale.
+    pub fn index(&self, scale: AqiScale) -> Option<&IndexReading> {
+        self.indices.iter().find(|i| i.scale == scale)
+    }
+
+    /// Maps WeatherAPI's `air_quality` object (from `aqi=yes`).
+    ///
+    /// Provider-supplied indices: `us-epa-index` and `gb-defra-index`.
+    pub fn from_weatherapi(air_quality: &Value) -> Option<Self> {
+        let obj = air_quality.as_object()?;
+
+        let pollutants = Pollutants {
+            pm2_5: obj.get("pm2_5").and_then(Value::as_f64),
+            pm10: obj.get("pm10").and_then(Value::as_f64),
+            o3: obj.get("o3").and_then(Value::as_f64),
+            no2: obj.get("no2").and_then(Value::as_f64),
+            so2: obj.get("so2").and_then(Value::as_f64),
+            co: obj.get("co").and_then(Value::as_f64),
+        };
+
+        let mut indices = Vec::new();
+        if let Some(v) = obj.get("us-epa-index").and_then(Value::as_f64) {
+            indices.push((AqiScale::UsEpaCategory, v));
+        }
+        if let Some(v) = obj.get("gb-defra-index").and_then(Value::as_f64) {
+            indices.push((AqiScale::UkDefra, v));
+        }
+
+        Some(Self::normalize(pollutants, indices))
+    }
+
+    /// Maps one entry of OpenWeatherMap's `air_pollution` `list` array.
+    ///
+    /// Provider-supplied index: `main.aqi` (1–5).
+    pub fn from_openweather(entry: &Value) -> Option<Self> {
+        let components = entry.get("components")?.as_object()?;
+        let field = |name: &str| components.get(name).and_then(Value::as_f64);
+
+        let pollutants = Pollutants {
+            pm2_5: field("pm2_5"),
+            pm10: field("pm10"),
+            o3: field("o3"),
+            no2: field("no2"),
+            so2: field("so2"),
+            co: field("co"),
+        };
+
+        let indices = entry["main"]["aqi"]
+            .as_f64()
+            .map(|v| vec![(AqiScale::OpenWeather, v)])
+            .unwrap_or_default();
+
+        Some(Self::normalize(pollutants, indices))
+    }
+}
+
+/// One row of a piecewise-linear breakpoint table:
+/// `(c_lo, c_hi, i_lo, i_hi)`.
+type Breakpoint = (f64, f64, f64, f64);
+
+/// US EPA PM2.5 (24-hour, µg/m³) breakpoints, 2024 revision.
+const US_PM25: &[Breakpoint] = &[
+    (0.0, 9.0, 0.0, 50.0),
+    (9.1, 35.4, 51.0, 100.0),
+    (35.5, 55.4, 101.0, 150.0),
+    (55.5, 125.4, 151.0, 200.0),
+    (125.5, 225.4, 201.0, 300.0),
+    (225.5, 325.4, 301.0, 500.0),
+];
+
+/// US EPA PM10 (24-hour, µg/m³) breakpoints.
+const US_PM10: &[Breakpoint] = &[
+    (0.0, 54.0, 0.0, 50.0),
+    (55.0, 154.0, 51.0, 100.0),
+    (155.0, 254.0, 101.0, 150.0),
+    (255.0, 354.0, 151.0, 200.0),
+    (355.0, 424.0, 201.0, 300.0),
+    (425.0, 604.0, 301.0, 500.0),
+];
+
+/// US EPA O3 breakpoints in ppm: the 8-hour table up to 0.200 ppm,
+/// continued by the 1-hour table's 301-400 and 401-500 bands for higher
+/// concentrations. The 8-hour table already reaches 300 at 0.200 ppm, so
+/// the 301-400 band starts right above it, at 0.201 ppm, rather than at
+/// 0.405 ppm, and the index keeps rising.
+const US_O3: &[Breakpoint] = &[
+    (0.000, 0.054, 0.0, 50.0),
+    (0.055, 0.070, 51.0, 100.0),
+    (0.071, 0.085, 101.0, 150.0),
+    (0.086, 0.105, 151.0, 200.0),
+    (0.106, 0.200, 201.0, 300.0),
+    (0.201, 0.504, 301.0, 400.0),
+    (0.505, 0.604, 401.0, 500.0),
+];
+
+/// US EPA NO2 (1-hour, ppb) breakpoints.
+const US_NO2: &[Breakpoint] = &[
+    (0.0, 53.0, 0.0, 50.0),
+    (54.0, 100.0, 51.0, 100.0),
+    (101.0, 360.0, 101.0, 150.0),
+    (361.0, 649.0, 151.0, 200.0),
+    (650.0, 1249.0, 201.0, 300.0),
+    (1250.0, 2049.0, 301.0, 500.0),
+];
+
+/// US EPA SO2 (1-hour, ppb) breakpoints.
+const US_SO2: &[Breakpoint] = &[
+    (0.0, 35.0, 0.0, 50.0),
+    (36.0, 75.0, 51.0, 100.0),
+    (76.0, 185.0, 101.0, 150.0),
+    (186.0, 304.0, 151.0, 200.0),
+    (305.0, 604.0, 201.0, 300.0),
+    (605.0, 1004.0, 301.0, 500.0),
+];
+
+/// US EPA CO (8-hour, ppm) breakpoints.
+const US_CO: &[Breakpoint] = &[
+    (0.0, 4.4, 0.0, 50.0),
+    (4.5, 9.4, 51.0, 100.0),
+    (9.5, 12.4, 101.0, 150.0),
+    (12.5, 15.4, 151.0, 200.0),
+    (15.5, 30.4, 201.0, 300.0),
+    (30.5, 50.4, 301.0, 500.0),
+];
+
+/// Converts a provider concentration (µg/m³) into the unit of the EPA table:
+/// µg/m³ for particulates, ppm for O3 and CO, ppb for NO2 and SO2.
+///
+/// Gas conversions assume 25 °C and 1 atm (molar volume 24.45 L).
+pub fn to_epa_units(pollutant: Pollutant, ugm3: f64) -> f64 {
+    let ppb = |molar_mass: f64| ugm3 * 24.45 / molar_mass;
+
+    match pollutant {
+        Pollutant::Pm2_5 | Pollutant::Pm10 => ugm3,
+        Pollutant::O3 => ppb(48.00) / 1000.0,
+        Pollutant::No2 => ppb(46.01),
+        Pollutant::So2 => ppb(64.07),
+        Pollutant::Co => ppb(28.01) / 1000.0,
+    }
+}
+
+/// Computes the US AQI sub-index for one pollutant.
+///
+/// `concentration` must already be in the EPA table unit (see
+/// [`to_epa_units`]). It is truncated to the table's precision first, as the
+/// EPA method requires. Values above the table are capped at 500.
+pub fn us_sub_index(pollutant: Pollutant, concentration: f64) -> Option<u32> {
+    let (table, precision) = match pollutant {
+        Pollutant::Pm2_5 => (US_PM25, 10.0),
+        Pollutant::Pm10 => (US_PM10, 1.0),
+        Pollutant::O3 => (US_O3, 1000.0),
+        Pollutant::No2 => (US_NO2, 1.0),
+        Pollutant::So2 => (US_SO2, 1.0),
+        Pollutant::Co => (US_CO, 10.0),
+    };
+
+    if !concentration.is_finite() || concentration < 0.0 {
+        return None;
+    }
+
+    let c = (concentration * precision + 1e-9).floor() / precision;
+
+    let Some(&(c_lo, c_hi, i_lo, i_hi)) = table.iter().find(|row| c >= row.0 && c <= row.1) else {
+        return Some(500);
+    };
+
+    Some(((i_hi - i_lo) / (c_hi - c_lo) * (c - c_lo) + i_lo).round() as u32)
+}
+
+/// Computes the US AQI as the maximum sub-index over the available pollutants.
+///
+/// Returns `None` if no pollutant concentration is known.
+pub fn us_aqi(pollutants: &Pollutants) -> Option<u32> {
+    ALL_POLLUTANTS
+        .iter()
+        .filter_map(|&p| us_sub_index(p, to_epa_units(p, pollutants.get(p)?)))
+        .max()
+}
+
+/// Returns the US AQI category name for an index value.
+pub fn us_aqi_category(aqi: u32) -> &'static str {
+    match aqi {
+        0..=50 => "Good",
+        51..=100 => "Moderate",
+        101..=150 => "Unhealthy for Sensitive Groups",
+        151..=200 => "Unhealthy",
+        201..=300 => "Very Unhealthy",
+        _ => "Hazardous",
+    }
+}
+
+/// European CAQI (hourly, background) grid upper bounds in µg/m³ for the
+/// index levels 25, 50, 75 and 100.
+fn caqi_grid(pollutant: Pollutant) -> [f64; 4] {
+    match pollutant {
+        Pollutant::No2 => [50.0, 100.0, 200.0, 400.0],
+        Pollutant::Pm10 => [25.0, 50.0, 90.0, 180.0],
+        Pollutant::Pm2_5 => [15.0, 30.0, 55.0, 110.0],
+        Pollutant::O3 => [60.0, 120.0, 180.0, 240.0],
+        Pollutant::So2 => [50.0, 100.0, 350.0, 500.0],
+        Pollutant::Co => [5000.0, 7500.0, 10000.0, 20000.0],
+    }
+}
+
+/// Computes the European CAQI sub-index for one pollutant (µg/m³).
+///
+/// The index is linear within each grid band. Above 100 ("very high") the
+/// slope of the last band is extended, so the value keeps growing with the
+/// concentration instead of saturating.
+pub fn caqi_sub_index(pollutant: Pollutant, ugm3: f64) -> Option<u32> {
+    if !ugm3.is_finite() || ugm3 < 0.0 {
+        return None;
+    }
+
+    let grid = caqi_grid(pollutant);
+    let mut c_lo = 0.0;
+
+    for (band, &c_hi) in grid.iter().enumerate() {
+        let i_lo = band as f64 * 25.0;
+        if ugm3 <= c_hi {
+            return Some((i_lo + (ugm3 - c_lo) / (c_hi - c_lo) * 25.0).round() as u32);
+        }
+        c_lo = c_hi;
+    }
+
+    let width = grid[3] - grid[2];
+    Some((100.0 + (ugm3 - grid[3]) / width * 25.0).round() as u32)
+}
+
+/// Computes the European CAQI as the maximum sub-index over the available
+/// pollutants. Returns `None` if no pollutant concentration is known.
+pub fn eu_caqi(pollutants: &Pollutants) -> Option<u32> {
+    ALL_POLLUTANTS
+        .iter()
+        .filter_map(|&p| caqi_sub_index(p, pollutants.get(p)?))
+        .max()
+}
+
+/// Returns the CAQI category name for an index value.
+pub fn caqi_category(caqi: u32) -> &'static str {
+    match caqi {
+        0..=25 => "Very low",
+        26..=50 => "Low",
+        51..=75 => "Medium",
+        76..=100 => "High",
+        _ => "Very high",
+    }
+}
+
+/// Every tracked pollutant, in the order they are listed to users.
+pub const ALL_POLLUTANTS: [Pollutant; 6] = [
+    Pollutant::Pm2_5,
+    Pollutant::Pm10,
+    Pollutant::O3,
+    Pollutant::No2,
+    Pollutant::So2,
+    Pollutant::Co,
+];
@@ -0,0 +1,842 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::fsutil::write_atomic;
+use crate::location::Location;
+use crate::paths::ensure_dir;
+use crate::providers::{is_rate_limited, is_unreachable, ApiProvider, DataKind, FetchResponse};
+use crate::quota::is_exhausted;
+use crate::transport::HttpRequest;
+
+/// Default lifetime of a cached `get` response: ten minutes.
+pub const DEFAULT_RESPONSE_TTL_SECS: u64 = 10 * 60;
+
+/// How long a response with validators is kept for revalidation after it
+/// was last fetched or confirmed: a week.
+pub const VALIDATED_TTL_SECS: u64 = 7 * 86_400;
+
+/// Extension of every file wapp writes into the cache directory.
+///
+/// Management commands only ever touch files named
+/// `<namespace>-<hash>.wcache` (plus [`STATS_FILE`]), so unrelated files in
+/// the same directory are left alone.
+pub const ENTRY_EXTENSION: &str = "wcache";
+
+/// Hit/miss counters kept next to the entries.
+pub const STATS_FILE: &str = "stats.wcache.json";
+
+/// Kind of data a cache entry holds.
+///
+/// - `response`: provider responses,
+/// - `geocode`: resolved locations,
+/// - `stale`: last known good responses kept for fallback,
+/// - `http`: provider responses as received, with their [`Validators`],
+///   kept for conditional requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Namespace {
+    Response,
+    Geocode,
+    Stale,
+    Http,
+}
+
+impl Namespace {
+    /// Name used in file names and listings.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Namespace::Response => "response",
+            Namespace::Geocode => "geocode",
+            Namespace::Stale => "stale",
+            Namespace::Http => "http",
+        }
+    }
+}
+
+/// One cached item, stored as JSON in its own file.
+///
+/// # Fields
+/// - `namespace`: kind of data,
+/// - `key`: lookup key, e.g. `weatherapi:now:kyiv`,
+/// - `provider`: provider that produced the data,
+/// - `city`: location the data belongs to,
+/// - `created`: Unix time the entry was written,
+/// - `expires`: Unix time after which the entry is expired, if any,
+/// - `body`: cached payload,
+/// - `validators`: the `ETag` and `Last-Modified` headers of an `http`
+///   entry; empty, and omitted, for other entries.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub namespace: Namespace,
+    pub key: String,
+    pub provider: String,
+    pub city: String,
+    pub created: u64,
+    pub expires: Option<u64>,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Validators::is_empty")]
+    pub validators: Validators,
+}
+
+impl CacheEntry {
+    /// Returns `true` if the entry has an expiry time at or before `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|at| at <= now)
+    }
+
+    /// Data kind of the entry: the second part of a `response` or `stale`
+    /// key such as `weatherapi:now:kyiv`, else the namespace name.
+    pub fn kind(&self) -> &str {
+        match self.namespace {
+            Namespace::Response | Namespace::Stale => self
+                .key
+                .split(':')
+                .nth(1)
+                .unwrap_or(self.namespace.as_str()),
+            Namespace::Geocode | Namespace::Http => self.namespace.as_str(),
+        }
+    }
+}
+
+/// The headers a provider identifies a version of a response with, sent
+/// back to ask whether it changed.
+///
+/// # Fields
+/// - `etag`: the `ETag` header, sent back as `If-None-Match`,
+/// - `last_modified`: the `Last-Modified` header, sent back as
+///   `If-Modified-Since`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// The validators of `response`.
+    pub fn of(response: &FetchResponse) -> Self {
+        Self {
+            etag: response.etag.clone(),
+            last_modified: response.last_modified.clone(),
+        }
+    }
+
+    /// Returns `true` without any validator, when a response cannot be
+    /// revalidated.
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// The headers of a conditional request: `If-None-Match` and
+    /// `If-Modified-Since`, for the validators present.
+    pub fn headers(&self) -> Vec<(&'static str, &str)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.etag {
+            headers.push(("If-None-Match", etag.as_str()));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.push(("If-Modified-Since", last_modified.as_str()));
+        }
+        headers
+    }
+}
+
+/// Whether `request` already asks whether a response changed, e.g. one of
+/// met.no made with its own `If-Modified-Since`.
+pub fn is_conditional(request: &HttpRequest) -> bool {
+    request.headers.iter().any(|(name, _)| {
+        name.eq_ignore_ascii_case("If-None-Match") || name.eq_ignore_ascii_case("If-Modified-Since")
+    })
+}
+
+/// How the response to a request of `get` was obtained, as logged with
+/// `--verbose`.
+///
+/// - `Fresh`: served from the cache, younger than the TTL; no request,
+/// - `Revalidated`: the provider answered `304 Not Modified` to a
+///   conditional request, and the stored body was served,
+/// - `Fetched`: the provider sent the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheOutcome {
+    Fresh,
+    Revalidated,
+    Fetched,
+}
+
+impl CacheOutcome {
+    /// Name in logs, e.g. `fresh hit`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CacheOutcome::Fresh => "fresh hit",
+            CacheOutcome::Revalidated => "revalidated",
+            CacheOutcome::Fetched => "fetched",
+        }
+    }
+}
+
+/// Summary reported by `wapp cache stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub total_bytes: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups served from the cache, if any lookups were made.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+/// Which entries `wapp cache clear` removes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClearFilter {
+    /// Every entry and the hit/miss counters.
+    All,
+    /// Entries whose expiry time has passed.
+    Expired,
+    /// Entries for one city (compared case-insensitively).
+    City(String),
+    /// Entries written at least this many seconds ago.
+    OlderThan(u64),
+    /// Geocoding entries.
+    Geocode,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Counters {
+    hits: u64,
+    misses: u64,
+}
+
+/// The on-disk cache directory.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    root: PathBuf,
+}
+
+impl Cache {
+    /// Opens the cache rooted at `root`; the directory is created on first write.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Opens the cache in the resolved cache directory, see [`Paths`](crate::paths::Paths).
+    pub fn open_default() -> Self {
+        Self::new(crate::paths::Paths::resolve().cache)
+    }
+
+    /// Directory holding the cache files.
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Writes an entry, replacing any entry with the same namespace and key.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or file cannot be written.
+    pub fn put(&self, entry: &CacheEntry) -> io::Result<()> {
+        ensure_dir(&self.root)?;
+        let json = serde_json::to_vec(entry)?;
+        write_atomic(&self.entry_path(entry.namespace, &entry.key), &json)
+    }
+
+    /// Reads an entry, if present and readable.
+    pub fn get(&self, namespace: Namespace, key: &str) -> Option<CacheEntry> {
+        self.try_get(namespace, key).ok().flatten()
+    }
+
+    /// Like [`get`](Self::get), telling a missing entry (`Ok(None)`) from
+    /// one that cannot be read or parsed.
+    ///
+    /// # Errors
+    /// Returns an error if the entry file exists but is unreadable or corrupt.
+    pub fn try_get(&self, namespace: Namespace, key: &str) -> anyhow::Result<Option<CacheEntry>> {
+        let path = self.entry_path(namespace, key);
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(anyhow::anyhow!("cannot read {}: {}", path.display(), e)),
+        };
+        let entry: CacheEntry = serde_json::from_slice(&data)
+            .map_err(|e| anyhow::anyhow!("corrupt cache entry {}: {}", path.display(), e))?;
+
+        // Guard against hash collisions.
+        Ok((entry.key == key).then_some(entry))
+    }
+
+    /// Returns every readable entry with its file path, ordered by key;
+    /// unreadable files, e.g. of another version, are skipped.
+    pub fn entries(&self) -> Vec<(PathBuf, CacheEntry)> {
+        let mut entries: Vec<(PathBuf, CacheEntry)> = self
+            .entry_files()
+            .into_iter()
+            .filter_map(|path| read_entry(&path).map(|entry| (path, entry)))
+            .collect();
+
+        entries.sort_by(|a, b| a.1.key.cmp(&b.1.key));
+        entries
+    }
+
+    /// Counts a lookup served from the cache.
+    pub fn record_hit(&self) {
+        crate::metrics::record_cache_hit();
+        self.update_counters(|c| c.hits += 1);
+    }
+
+    /// Counts a lookup that had to go to the provider.
+    pub fn record_miss(&self) {
+        self.update_counters(|c| c.misses += 1);
+    }
+
+    /// Returns the number and size of entries and the hit/miss counters.
+    pub fn stats(&self) -> CacheStats {
+        let files = self.entry_files();
+        let counters = self.counters();
+
+        CacheStats {
+            entries: files.len(),
+            total_bytes: files
+                .iter()
+                .filter_map(|path| fs::metadata(path).ok())
+                .map(|meta| meta.len())
+                .sum(),
+            hits: counters.hits,
+            misses: counters.misses,
+        }
+    }
+
+    /// Removes the entries selected by `filter` and returns how many were removed.
+    ///
+    /// Only files following the cache naming scheme are considered.
+    ///
+    /// # Errors
+    /// Returns an error if a selected file cannot be removed.
+    pub fn clear(&self, filter: &ClearFilter, now: u64) -> io::Result<usize> {
+        let mut removed = 0;
+
+        for path in self.entry_files() {
+            let selected = match filter {
+                ClearFilter::All => true,
+                // Unreadable entries are unusable, so they count as expired.
+                ClearFilter::Expired => read_entry(&path).is_none_or(|e| e.is_expired(now)),
+                ClearFilter::OlderThan(secs) => {
+                    read_entry(&path).is_none_or(|e| now.saturating_sub(e.created) >= *secs)
+                }
+                ClearFilter::City(city) => {
+                    read_entry(&path).is_some_and(|e| e.city.eq_ignore_ascii_case(city))
+                }
+                ClearFilter::Geocode => has_namespace(&path, Namespace::Geocode),
+            };
+
+            if selected {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+
+        if *filter == ClearFilter::All {
+            match fs::remove_file(self.root.join(STATS_FILE)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Path of the file for an entry, e.g. `response-1f0c…e2.wcache`.
+    fn entry_path(&self, namespace: Namespace, key: &str) -> PathBuf {
+        self.root.join(format!(
+            "{}-{:016x}.{}",
+            namespace.as_str(),
+            fnv1a(key),
+            ENTRY_EXTENSION
+        ))
+    }
+
+    /// Files in the cache directory that follow the entry naming scheme.
+    fn entry_files(&self) -> Vec<PathBuf> {
+        let Ok(dir) = fs::read_dir(&self.root) else {
+            return Vec::new();
+        };
+
+        dir.filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && is_entry_file(path))
+            .collect()
+    }
+
+    fn counters(&self) -> Counters {
+        fs::read(self.root.join(STATS_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Counter updates are best effort; a failure never breaks a fetch.
+    fn update_counters(&self, update: impl FnOnce(&mut Counters)) {
+        let mut counters = self.counters();
+        update(&mut counters);
+
+        if ensure_dir(&self.root).is_ok() {
+            if let Ok(json) = serde_json::to_vec(&counters) {
+                let _ = write_atomic(&self.root.join(STATS_FILE), &json);
+            }
+        }
+    }
+}
+
+/// How a [`ResponseCache`] treats stored responses.
+///
+/// - `use`: serve a fresh stored response, else fetch and store,
+/// - `refresh`: always fetch (`--refresh`), and store the result,
+/// - `offline`: never fetch (`--offline`); serve the stored response of
+///   any age, else fail with [`NoCachedData`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    Use,
+    Refresh,
+    Offline,
+}
+
+/// Exit code of `get --offline` when nothing is cached for the location.
+pub const NO_CACHED_DATA_EXIT_CODE: i32 = 4;
+
+/// Error returned by `get --offline` when no response is cached for the
+/// location; `main` maps it to [`NO_CACHED_DATA_EXIT_CODE`].
+#[derive(Debug)]
+pub struct NoCachedData {
+    pub location: String,
+}
+
+impl std::fmt::Display for NoCachedData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no cached data for {}", self.location)
+    }
+}
+
+impl std::error::Error for NoCachedData {}
+
+/// Provider responses of `get`, kept in the `response` namespace of a
+/// [`Cache`] and reused while younger than a TTL.
+///
+/// Entries are keyed by provider, data kind, location, forecast length,
+/// and a hash of the settings that shape a response (units, language,
+/// extra parameters), e.g. `weatherapi:now:city:kyiv#1a2b3c4d`. Cache
+/// failures are never fatal: an unreadable or corrupt entry is logged and
+/// fetched again, and an entry that cannot be written is logged and
+/// skipped.
+///
+/// A response served past its TTL, offline or as a fallback, is announced
+/// on stderr with its age, e.g. `cached 42 minutes ago`.
+///
+/// Raw provider responses with validators are kept too, in the `http`
+/// namespace, so that [`send`](crate::providers::send) can ask whether
+/// they changed once the TTL has passed; see [`validated`](Self::validated).
+///
+/// # Fields
+/// - `cache`: the cache directory,
+/// - `ttl_secs`: age below which a stored response is served,
+/// - `mode`: see [`CacheMode`],
+/// - `scope`: hash of the response settings, part of every key,
+/// - `stale_fallback`: serve the stored response of any age when the
+///   provider cannot be reached, see [`is_unreachable`],
+/// - `allow_stale`: serve the stored response of any age when the provider
+///   has used its daily quota, see [`is_exhausted`],
+/// - `now`: Unix time of the call, so tests can pick the clock; see
+///   [`at`](Self::at).
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    pub cache: Cache,
+    pub ttl_secs: u64,
+    pub mode: CacheMode,
+    pub scope: String,
+    pub stale_fallback: bool,
+    pub allow_stale: bool,
+    pub now: u64,
+}
+
+impl ResponseCache {
+    /// A cache for responses shaped by `settings`, any text that changes
+    /// when the units, language, or parameters of the requests change.
+    pub fn new(cache: Cache, ttl_secs: u64, mode: CacheMode, settings: &str, now: u64) -> Self {
+        Self {
+            cache,
+            ttl_secs,
+            mode,
+            scope: format!("{:08x}", fnv1a(settings) as u32),
+            stale_fallback: false,
+            allow_stale: false,
+            now,
+        }
+    }
+
+    /// The same cache as seen at `now`, so a long-running `get --watch`
+    /// judges the age of stored responses by the current time.
+    pub fn at(&self, now: u64) -> Self {
+        Self {
+            now,
+            ..self.clone()
+        }
+    }
+
+    /// Key of the response of `provider` for one request.
+    pub fn key(
+        &self,
+        provider: &str,
+        location: &Location,
+        kind: &str,
+        days: Option<u32>,
+    ) -> String {
+        let location = match location {
+            Location::City(city) => format!("city:{}", city.raw.to_lowercase()),
+            Location::Coords { lat, lon } => format!("coords:{},{}", lat, lon),
+            Location::Zip { .. } => format!("zip:{}", location.label().to_lowercase()),
+            Location::Iata(code) => format!("iata:{}", code),
+        };
+        let days = days.map(|days| format!(":{}d", days)).unwrap_or_default();
+
+        format!("{}:{}:{}{}#{}", provider, kind, location, days, self.scope)
+    }
+
+    /// The stored response for `key` if it is younger than the TTL and the
+    /// mode allows serving it.
+    pub fn lookup(&self, key: &str) -> Option<CacheEntry> {
+        if self.mode != CacheMode::Use {
+            return None;
+        }
+
+        match self.cache.try_get(Namespace::Response, key) {
+            Ok(Some(entry)) if self.now.saturating_sub(entry.created) < self.ttl_secs => {
+                self.cache.record_hit();
+                Some(entry)
+            }
+            Ok(_) => {
+                self.cache.record_miss();
+                None
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "ignoring the cached response");
+                self.cache.record_miss();
+                None
+            }
+        }
+    }
+
+    /// Stores `body` as the response for `key`; a failure is logged.
+    pub fn store(&self, key: &str, provider: &str, location: &Location, body: &str) {
+        let entry = CacheEntry {
+            namespace: Namespace::Response,
+            key: key.to_string(),
+            provider: provider.to_string(),
+            city: location.label(),
+            created: self.now,
+            expires: Some(self.now + self.ttl_secs),
+            body: body.to_string(),
+            validators: Validators::default(),
+        };
+
+        if let Err(e) = self.cache.put(&entry) {
+            tracing::warn!(error = %e, path = %self.cache.path().display(), "response not cached");
+        }
+    }
+
+    /// The stored response for `key` of any age; an unreadable entry is
+    /// logged and treated as missing.
+    pub fn last(&self, key: &str) -> Option<CacheEntry> {
+        self.cache
+            .try_get(Namespace::Response, key)
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "ignoring the cached response");
+                None
+            })
+    }
+
+    /// The response to `request` kept in the `http` namespace with its
+    /// validators, to ask the provider whether it changed; `None` offline,
+    /// for a request that is already conditional, see [`is_conditional`],
+    /// and for a response not fetched or confirmed in the last
+    /// [`VALIDATED_TTL_SECS`].
+    pub fn validated(&self, request: &HttpRequest) -> Option<CacheEntry> {
+        if self.mode == CacheMode::Offline || is_conditional(request) {
+            return None;
+        }
+
+        match self.cache.try_get(Namespace::Http, &request.describe()) {
+            Ok(Some(entry)) if !entry.is_expired(self.now) && !entry.validators.is_empty() => {
+                Some(entry)
+            }
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(error = %e, "ignoring the cached response");
+                None
+            }
+        }
+    }
+
+    /// Keeps `response` of `provider` to `request` in the `http` namespace
+    /// for [`VALIDATED_TTL_SECS`], if it succeeded with JSON and has
+    /// validators; a failure is logged.
+    ///
+    /// Entries are keyed by the request with its API key masked, see
+    /// [`HttpRequest::describe`]; the provider is not asked for a location,
+    /// so the entry has none.
+    pub fn keep_validated(&self, request: &HttpRequest, provider: &str, response: &FetchResponse) {
+        let validators = Validators::of(response);
+        if !(200..=299).contains(&response.status)
+            || validators.is_empty()
+            || serde_json::from_str::<serde::de::IgnoredAny>(&response.body).is_err()
+        {
+            return;
+        }
+
+        self.put_validated(CacheEntry {
+            namespace: Namespace::Http,
+            key: request.describe(),
+            provider: provider.to_string(),
+            city: String::new(),
+            created: self.now,
+            expires: Some(self.now + VALIDATED_TTL_SECS),
+            body: response.body.clone(),
+            validators,
+        });
+    }
+
+    /// Records that the provider confirmed `entry` unchanged at `now`,
+    /// keeping any new `validators` it sent; the body is kept for another
+    /// [`VALIDATED_TTL_SECS`].
+    pub fn revalidated(&self, entry: &CacheEntry, validators: Validators) {
+        self.put_validated(CacheEntry {
+            created: self.now,
+            expires: Some(self.now + VALIDATED_TTL_SECS),
+            validators: Validators {
+                etag: validators.etag.or(entry.validators.etag.clone()),
+                last_modified: validators
+                    .last_modified
+                    .or(entry.validators.last_modified.clone()),
+            },
+            ..entry.clone()
+        });
+    }
+
+    fn put_validated(&self, entry: CacheEntry) {
+        if let Err(e) = self.cache.put(&entry) {
+            tracing::warn!(error = %e, path = %self.cache.path().display(), "response not kept for revalidation");
+        }
+    }
+
+    /// The response of `provider` for `location`, `kind`, and `days`: the
+    /// stored one when [`lookup`](Self::lookup) finds it, else fetched
+    /// with [`get_data`](ApiProvider::get_data) and stored.
+    ///
+    /// Offline, only the [`last`](Self::last) stored response is served.
+    /// It also stands in for a request that was rate limited, with
+    /// `stale_fallback` for one that failed because the provider could not
+    /// be reached, and with `allow_stale` for one refused by the daily
+    /// quota.
+    ///
+    /// # Errors
+    /// Returns [`NoCachedData`] offline when nothing is stored, else the
+    /// error of the provider request; failed requests, and responses that are
+    /// not JSON, are not stored.
+    pub async fn fetch(
+        &self,
+        provider: &dyn ApiProvider,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> anyhow::Result<String> {
+        let key = self.key(provider.name(), &location, &kind.to_string(), days);
+
+        if self.mode == CacheMode::Offline {
+            let entry = self.last(&key).ok_or_else(|| NoCachedData {
+                location: location.label(),
+            })?;
+            tracing::warn!(
+                "offline: {} cached {}",
+                location.label(),
+                describe_age(self.now.saturating_sub(entry.created))
+            );
+            return Ok(entry.body);
+        }
+
+        if let Some(entry) = self.lookup(&key) {
+            tracing::info!(
+                provider = provider.name(),
+                key = %key,
+                age_secs = self.now.saturating_sub(entry.created),
+                cache_hit = true,
+                outcome = CacheOutcome::Fresh.as_str(),
+                "response served from cache"
+            );
+            return Ok(entry.body);
+        }
+
+        let body = match provider.get_data(location.clone(), kind, days).await {
+            Ok(body) => body,
+            Err(e)
+                if is_rate_limited(&e)
+                    || (self.stale_fallback && is_unreachable(&e))
+                    || (self.allow_stale && is_exhausted(&e)) =>
+            {
+                let Some(entry) = self.last(&key) else {
+                    return Err(e);
+                };
+                tracing::warn!(
+                    error = %e,
+                    "showing {} as cached {}",
+                    location.label(),
+                    describe_age(self.now.saturating_sub(entry.created))
+                );
+                return Ok(entry.body);
+            }
+            Err(e) => return Err(e),
+        };
+        // A page let through by `--raw` would be served as a response later.
+        if serde_json::from_str::<serde::de::IgnoredAny>(&body).is_ok() {
+            self.store(&key, provider.name(), &location, &body);
+        }
+        Ok(body)
+    }
+}
+
+/// Response cache set by [`use_response_cache`].
+static RESPONSE_CACHE: OnceLock<ResponseCache> = OnceLock::new();
+
+/// Makes every later [`execute`](crate::request::execute) of this process
+/// go through `cache`; the first call wins. Without a call, responses are
+/// never cached.
+pub fn use_response_cache(cache: ResponseCache) {
+    let _ = RESPONSE_CACHE.set(cache);
+}
+
+/// The cache set with [`use_response_cache`], if any.
+pub fn response_cache() -> Option<&'static ResponseCache> {
+    RESPONSE_CACHE.get()
+}
+
+/// Current Unix time in seconds.
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Describes an age in seconds for people, e.g. `just now`,
+/// `1 minute ago`, `42 minutes ago`, `3 hours ago`.
+pub fn describe_age(secs: u64) -> String {
+    let (count, unit) = match secs {
+        s if s < 60 => return "just now".into(),
+        s if s < 3600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3600, "hour"),
+        s => (s / 86_400, "day"),
+    };
+
+    match count {
+        1 => format!("1 {} ago", unit),
+        n => format!("{} {}s ago", n, unit),
+    }
+}
+
+/// Reads an age such as `90s`, `15m`, `2h`, `1d`, or `1h30m`, in seconds.
+///
+/// # Errors
+/// Returns an error unless `text` is one or more whole numbers, each
+/// followed by `s`, `m`, `h`, or `d`.
+pub fn parse_age(text: &str) -> anyhow::Result<u64> {
+    let invalid = || anyhow::anyhow!("invalid duration '{}': expected e.g. 90s, 15m, 2h", text);
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut secs: u64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86_400,
+            _ => return Err(invalid()),
+        };
+        let count: u64 = number.parse().map_err(|_| invalid())?;
+        secs = count
+            .checked_mul(unit)
+            .and_then(|part| secs.checked_add(part))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+
+    match number.is_empty() {
+        true => Ok(secs),
+        false => Err(invalid()),
+    }
+}
+
+/// Formats a size in bytes as e.g. `512 B`, `1.5 KB`, `2.0 MB`.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// Formats an age in seconds as e.g. `45s`, `12m`, `3h`, `2d`.
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86_400),
+    }
+}
+
+/// Returns `true` for names like `response-0123456789abcdef.wcache`.
+fn is_entry_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let Some(stem) = name.strip_suffix(&format!(".{}", ENTRY_EXTENSION)) else {
+        return false;
+    };
+    let Some((namespace, hash)) = stem.split_once('-') else {
+        return false;
+    };
+
+    ["response", "geocode", "stale", "http"].contains(&namespace)
+        && hash.len() == 16
+        && hash.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn has_namespace(path: &Path, namespace: Namespace) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with(&format!("{}-", namespace.as_str())))
+}
+
+fn read_entry(path: &Path) -> Option<CacheEntry> {
+    serde_json::from_slice(&fs::read(path).ok()?).ok()
+}
+
+/// 64-bit FNV-1a, used for file names because it is stable across builds.
+pub fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+
+use crate::condition::Condition;
+use crate::series::SeriesPoint;
+
+/// Exit code of `wapp check` when the condition does not hold; errors exit
+/// with [`CHECK_ERROR_EXIT_CODE`] or a more specific code above it.
+pub const CONDITION_NOT_MET_EXIT_CODE: i32 = 1;
+
+/// Exit code of a `wapp check` that failed without a more specific code.
+pub const CHECK_ERROR_EXIT_CODE: i32 = 2;
+
+/// What `wapp check` looks for in the forecast, in metric units.
+///
+/// Parsed from `rain`, `rain>N%`, `snow`, `temp-above:N`, `temp-below:N`
+/// (°C), and `wind-above:N` (km/h), ignoring case.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckCondition {
+    /// Precipitation other than snow, or a rain, drizzle, or thunderstorm
+    /// condition.
+    Rain,
+    /// A chance of precipitation above the given percentage; an hour
+    /// without a reported chance does not meet it.
+    RainChance(f64),
+    /// A snow or sleet condition.
+    Snow,
+    TempAbove(f64),
+    TempBelow(f64),
+    WindAbove(f64),
+}
+
+impl CheckCondition {
+    /// The value of `point` that meets the condition, formatted with its
+    /// unit, or `None` if the point does not meet it.
+    pub fn matches(self, point: &SeriesPoint) -> Option<String> {
+        let kind = point.condition.as_deref().map(Condition::from_text);
+        match self {
+            CheckCondition::Rain => {
+                let wet = matches!(
+                    kind,
+                    Some(Condition::Rain | Condition::Drizzle | Condition::Thunderstorm)
+                );
+                // Precipitation of a snow hour is snow, not rain.
+                let snow = kind == Some(Condition::Snow);
+                match point.precip_mm.filter(|mm| *mm > 0.0 && !snow) {
+                    Some(mm) => Some(format!("{} mm", mm)),
+                    None if wet => point.condition.clone(),
+                    None => None,
+                }
+            }
+            CheckCondition::RainChance(limit) => point
+                .precip_chance
+                .filter(|p| *p > limit)
+                .map(|p| format!("{:.0}% chance", p)),
+            CheckCondition::Snow => matches!(kind, Some(Condition::Snow | Condition::Sleet))
+                .then(|| point.condition.clone().unwrap_or_default()),
+            CheckCondition::TempAbove(limit) => point
+                .temp_c
+                .filter(|t| *t > limit)
+                .map(|t| format!("{} °C", t)),
+            CheckCondition::TempBelow(limit) => point
+                .temp_c
+                .filter(|t| *t < limit)
+                .map(|t| format!("{} °C", t)),
+            CheckCondition::WindAbove(limit) => point
+                .wind_kph
+                .filter(|w| *w > limit)
+                .map(|w| format!("{} km/h", w)),
+        }
+    }
+}
+
+impl fmt::Display for CheckCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckCondition::Rain => f.write_str("rain"),
+            CheckCondition::RainChance(n) => write!(f, "chance of rain above {}%", n),
+            CheckCondition::Snow => f.write_str("snow"),
+            CheckCondition::TempAbove(n) => write!(f, "temperature above {} °C", n),
+            CheckCondition::TempBelow(n) => write!(f, "temperature below {} °C", n),
+            CheckCondition::WindAbove(n) => write!(f, "wind above {} km/h", n),
+        }
+    }
+}
+
+impl FromStr for CheckCondition {
+    type Err = anyhow::Error;
+
+    /// # Errors
+    /// Returns an error naming the supported conditions for an unknown name,
+    /// and one for a missing or non-numeric threshold.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim().to_lowercase();
+        if let Some(("rain", value)) = text.split_once('>').map(|(n, v)| (n.trim(), v.trim())) {
+            let percent = value.strip_suffix('%').unwrap_or(value).trim();
+            return percent
+                .parse::<f64>()
+                .ok()
+                .filter(|p| (0.0..=100.0).contains(p))
+                .map(CheckCondition::RainChance)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "'{}' in condition '{}' is not a percentage from 0 to 100, e.g. rain>50%",
+                        value,
+                        s.trim()
+                    )
+                });
+        }
+        let (name, value) = match text.split_once(':') {
+            Some((name, value)) => (name, Some(value.trim())),
+            None => (text.as_str(), None),
+        };
+        let threshold = || -> anyhow::Result<f64> {
+            let value = value.filter(|v| !v.is_empty()).ok_or_else(|| {
+                anyhow!("condition '{}' needs a threshold, e.g. {}:25", name, name)
+            })?;
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| anyhow!("'{}' in condition '{}' is not a number", value, s.trim()))
+        };
+
+        let condition = match name {
+            "rain" | "snow" if value.is_some() => {
+                bail!("condition '{}' takes no threshold", name)
+            }
+            "rain" => CheckCondition::Rain,
+            "snow" => CheckCondition::Snow,
+            "temp-above" => CheckCondition::TempAbove(threshold()?),
+            "temp-below" => CheckCondition::TempBelow(threshold()?),
+            "wind-above" => CheckCondition::WindAbove(threshold()?),
+            _ => bail!(
+                "unknown condition '{}'. Supported: rain, rain>N%, snow, temp-above:N, temp-below:N, wind-above:N",
+                s.trim()
+            ),
+        };
+        Ok(condition)
+    }
+}
+
+/// Parses a `--within` window such as `90m`, `12h`, or `2d`; a bare
+/// number counts hours.
+///
+/// # Errors
+/// Returns an error for a missing or zero amount, or an unknown unit.
+pub fn parse_within(s: &str) -> anyhow::Result<Duration> {
+    let text = s.trim();
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (amount, unit) = text.split_at(split);
+    let invalid = || anyhow!("invalid window '{}'; expected e.g. 90m, 12h or 2d", text);
+
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    if amount == 0 {
+        return Err(invalid());
+    }
+    match unit {
+        "m" => Ok(Duration::minutes(amount)),
+        "" | "h" => Ok(Duration::hours(amount)),
+        "d" => Ok(Duration::days(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Forecast days to fetch so the hours of `within` from now are covered.
+pub fn days_for(within: Duration) -> u32 {
+    (within.num_hours().max(0) as u32).div_ceil(24) + 1
+}
+
+/// Outcome of `wapp check`: whether the condition holds, and the first
+/// forecast hour that meets it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub holds: bool,
+    pub line: String,
+}
+
+/// Evaluates `condition` against the forecast hours of `points` from the
+/// hour holding `now` until `within` later, and describes the outcome for
+/// `city`.
+pub fn evaluate(
+    condition: CheckCondition,
+    points: &[SeriesPoint],
+    city: &str,
+    now: DateTime<Utc>,
+    within: Duration,
+) -> Verdict {
+    let window = |time: DateTime<Utc>| time > now - Duration::hours(1) && time <= now + within;
+    let first = points
+        .iter()
+        .filter(|point| window(point.time))
+        .find_map(|point| Some((point.time, condition.matches(point)?)));
+
+    match first {
+        Some((time, value)) => Verdict {
+            holds: true,
+            line: format!(
+                "{}: {} expected at {} ({})",
+                city,
+                condition,
+                time.format("%Y-%m-%d %H:%M UTC"),
+                value
+            ),
+        },
+        None => Verdict {
+            holds: false,
+            line: format!(
+                "{}: no {} expected within {}",
+                city,
+                condition,
+                describe_window(within)
+            ),
+        },
+    }
+}
+
+/// A window in its largest whole unit, e.g. `12h` or `90m`.
+fn describe_window(within: Duration) -> String {
+    let minutes = within.num_minutes();
+    if minutes % (24 * 60) == 0 {
+        format!("{}d", minutes / (24 * 60))
+    } else if minutes % 60 == 0 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Error returned when the condition of `wapp check` does not hold; `main`
+/// exits with [`CONDITION_NOT_MET_EXIT_CODE`] without printing it, since
+/// the verdict was already printed.
+#[derive(Debug)]
+pub struct ConditionNotMet;
+
+impl fmt::Display for ConditionNotMet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("condition not met")
+    }
+}
+
+impl std::error::Error for ConditionNotMet {}
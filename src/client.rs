@@ -0,0 +1,224 @@
+//! A high-level API for programs embedding wapp, see [`Client`].
+
+use anyhow::Result;
+
+use crate::config::AppConfig;
+use crate::location::Location;
+use crate::providers::{provider_chain_with, ApiProvider, DataKind, ProviderOverrides};
+use crate::request::{execute_race, execute_with_fallback, Strategy, WeatherRequest};
+use crate::weather::WeatherData;
+
+/// The weather of a provider and its fallbacks, in the normalized
+/// [`WeatherData`] shape every provider maps onto.
+///
+/// A client takes the provider, its key, units, language and fallbacks
+/// from an [`AppConfig`], such as the one `wapp configure` saves (see
+/// [`load_config`](crate::config::load_config)) or one built in code.
+/// Requests go through the same validation, response cache, retries and
+/// fallbacks as the `wapp` commands, which are built on it. Nothing is
+/// printed and the process never exits: every failure is an error, see
+/// [`WappError`](crate::error::WappError).
+///
+/// ```no_run
+/// use wapp::config::AppConfig;
+/// use wapp::providers::ProviderKind;
+/// use wapp::Client;
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let client = Client::from_config(AppConfig {
+///     provider: ProviderKind::OpenMeteo,
+///     ..Default::default()
+/// })?;
+///
+/// let now = client.current("Kyiv").await?;
+/// println!("{:?} °C, {:?}", now.temp_c, now.condition);
+///
+/// for day in client.forecast("Kyiv", 3).await?.forecast {
+///     println!("{}: {:?}..{:?} °C", day.date, day.min_c, day.max_c);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Client {
+    config: AppConfig,
+    providers: Vec<Box<dyn ApiProvider>>,
+}
+
+impl Client {
+    /// A client of the provider configured in `config`, followed by its
+    /// fallback providers.
+    ///
+    /// # Errors
+    /// Returns a configuration error if the provider cannot be created,
+    /// e.g. without an API key, see
+    /// [`provider_factory_with`](crate::providers::provider_factory_with).
+    pub fn from_config(config: AppConfig) -> Result<Self> {
+        Self::with_overrides(config, &ProviderOverrides::default())
+    }
+
+    /// Like [`from_config`](Self::from_config), with `overrides` applied
+    /// on top of `config`, e.g. another provider, key or language, or
+    /// environment variables of their own, see [`ProviderOverrides`].
+    ///
+    /// # Errors
+    /// Same as [`from_config`](Self::from_config).
+    pub fn with_overrides(config: AppConfig, overrides: &ProviderOverrides) -> Result<Self> {
+        let providers = provider_chain_with(&config, overrides)?;
+
+        Ok(Self { config, providers })
+    }
+
+    /// A client of `provider` alone, e.g. one built with its `new`
+    /// constructor; `config` only serves the saved locations.
+    ///
+    /// ```no_run
+    /// use wapp::config::AppConfig;
+    /// use wapp::providers::WeatherApiProvider;
+    /// use wapp::Client;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> anyhow::Result<()> {
+    /// let provider = WeatherApiProvider::new("KEY").with_lang("uk");
+    /// let client = Client::with_provider(AppConfig::default(), Box::new(provider));
+    ///
+    /// let tomorrow = client.tomorrow("Lviv").await?;
+    /// assert!(tomorrow.forecast.len() <= 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_provider(config: AppConfig, provider: Box<dyn ApiProvider>) -> Self {
+        Self {
+            config,
+            providers: vec![provider],
+        }
+    }
+
+    /// The configuration of the client.
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    /// The provider asked first.
+    pub fn provider(&self) -> &dyn ApiProvider {
+        self.providers[0].as_ref()
+    }
+
+    /// The current weather at `location`: a city, `@name` of a saved
+    /// location, or any other [`Location`].
+    ///
+    /// # Errors
+    /// Returns an error if the location is unknown, every provider failed,
+    /// or the response cannot be read.
+    pub async fn current(&self, location: impl Into<Location>) -> Result<WeatherData> {
+        self.weather(WeatherRequest::at(
+            self.config.resolve_location(location.into())?,
+            DataKind::Now,
+            None,
+        ))
+        .await
+    }
+
+    /// The forecast of the next `days` at `location`, today included, as
+    /// [`forecast`](WeatherData::forecast) days.
+    ///
+    /// # Errors
+    /// Same as [`current`](Self::current), and also if the provider does
+    /// not forecast that many days.
+    pub async fn forecast(&self, location: impl Into<Location>, days: u32) -> Result<WeatherData> {
+        self.weather(WeatherRequest::at(
+            self.config.resolve_location(location.into())?,
+            DataKind::Forecast,
+            Some(days),
+        ))
+        .await
+    }
+
+    /// Tomorrow's forecast at `location`, as its one
+    /// [`forecast`](WeatherData::forecast) day.
+    ///
+    /// # Errors
+    /// Same as [`current`](Self::current).
+    pub async fn tomorrow(&self, location: impl Into<Location>) -> Result<WeatherData> {
+        self.weather(WeatherRequest::at(
+            self.config.resolve_location(location.into())?,
+            DataKind::Tomorrow,
+            None,
+        ))
+        .await
+    }
+
+    /// The forecast of the coming weekend at `location`, or of what is left
+    /// of it, with a verdict, see [`weekend`](crate::weekend).
+    ///
+    /// # Errors
+    /// Same as [`current`](Self::current).
+    pub async fn weekend(&self, location: impl Into<Location>) -> Result<WeatherData> {
+        self.weather(WeatherRequest::at(
+            self.config.resolve_location(location.into())?,
+            DataKind::Weekend,
+            None,
+        ))
+        .await
+    }
+
+    /// The data of any `request`, normalized, see
+    /// [`read`](crate::output::read).
+    ///
+    /// # Errors
+    /// Same as [`fetch`](Self::fetch), and also if the response cannot be
+    /// read.
+    pub async fn weather(&self, request: WeatherRequest) -> Result<WeatherData> {
+        let kind = request.kind.to_string();
+        let (provider, body) = self.fetch(request).await?;
+
+        crate::output::read(provider, &kind, &body).ok_or_else(|| {
+            anyhow::anyhow!("cannot read '{}' data from '{}'", kind, provider.name())
+        })
+    }
+
+    /// The response to `request` as the provider sent it, and the provider
+    /// that sent it, by the [`Strategy`] of the config: the first one, or a
+    /// fallback while the ones before it are unavailable, see
+    /// [`execute_with_fallback`], or with `race` the fastest, see
+    /// [`execute_race`].
+    ///
+    /// # Errors
+    /// Returns an error if the request is invalid for the provider, or
+    /// every provider failed.
+    pub async fn fetch(&self, request: WeatherRequest) -> Result<(&dyn ApiProvider, String)> {
+        match self.config.strategy.unwrap_or_default() {
+            Strategy::Single => execute_with_fallback(&self.providers[..1], request).await,
+            Strategy::Fallback => execute_with_fallback(&self.providers, request).await,
+            Strategy::Race => execute_race(&self.providers, request).await,
+        }
+    }
+
+    /// The responses to several `requests` of the first provider, fetched
+    /// concurrently, each with its kind, see
+    /// [`execute_many`](crate::request::execute_many).
+    ///
+    /// # Errors
+    /// Returns an error if the provider does not serve a requested kind; a
+    /// failed request only fails its own entry.
+    pub async fn fetch_many(
+        &self,
+        requests: Vec<WeatherRequest>,
+    ) -> Result<Vec<(String, Result<String>)>> {
+        crate::request::execute_many(self.provider(), requests).await
+    }
+
+    /// The responses to `requests` for several locations of the first
+    /// provider, at most `limit` at a time, each with its location's label,
+    /// see [`execute_locations`](crate::request::execute_locations).
+    ///
+    /// # Errors
+    /// Same as [`fetch_many`](Self::fetch_many).
+    pub async fn fetch_locations(
+        &self,
+        requests: Vec<WeatherRequest>,
+        limit: usize,
+    ) -> Result<Vec<(String, Result<String>)>> {
+        crate::request::execute_locations(self.provider(), requests, limit).await
+    }
+}
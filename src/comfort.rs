@@ -0,0 +1,193 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::units::{c_to_f, f_to_c, kph_to_mph};
+use crate::weather::{dew_point, WeatherData};
+
+/// Lowest temperature in °C, 80 °F, at which the heat index is used.
+pub const HEAT_INDEX_MIN_C: f64 = 26.7;
+
+/// Highest temperature in °C, 50 °F, at which the wind chill is used.
+pub const WIND_CHILL_MAX_C: f64 = 10.0;
+
+/// Wind speed in km/h, about 3 mph, above which the wind chill is used.
+pub const WIND_CHILL_MIN_KPH: f64 = 4.8;
+
+/// The heat index in °C of air at `temp_c` with `humidity` % relative
+/// humidity, by the US National Weather Service's method: Steadman's
+/// simple formula where it gives less than 80 °F, else the Rothfusz
+/// regression with its adjustments for humidity below 13% and above 85%.
+/// 90 °F at 70% is 106 °F.
+pub fn heat_index(temp_c: f64, humidity: f64) -> f64 {
+    let t = c_to_f(temp_c);
+    let rh = humidity;
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return f_to_c(simple);
+    }
+
+    let mut index = -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+    }
+    f_to_c(index)
+}
+
+/// The wind chill in °C of air at `temp_c` in a `wind_kph` km/h wind, by
+/// the formula the US and Canadian weather services adopted in 2001:
+/// 0 °F in a 15 mph wind is -19 °F.
+pub fn wind_chill(temp_c: f64, wind_kph: f64) -> f64 {
+    let t = c_to_f(temp_c);
+    let v = kph_to_mph(wind_kph).powf(0.16);
+
+    f_to_c(35.74 + 0.6215 * t - 35.75 * v + 0.4275 * t * v)
+}
+
+/// Where a [`FeelsLike`] temperature comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeelsLikeSource {
+    Provider,
+    HeatIndex,
+    WindChill,
+}
+
+impl FeelsLikeSource {
+    /// The note shown after a computed value, e.g. `heat index`; `None`
+    /// for the provider's own.
+    pub fn note(self) -> Option<&'static str> {
+        match self {
+            Self::Provider => None,
+            Self::HeatIndex => Some("heat index"),
+            Self::WindChill => Some("wind chill"),
+        }
+    }
+}
+
+/// An apparent temperature in °C, with its source.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FeelsLike {
+    pub temp_c: f64,
+    pub source: FeelsLikeSource,
+}
+
+/// The apparent temperature of air at `temp_c`: the [`heat_index`] from
+/// [`HEAT_INDEX_MIN_C`] up, with a known `humidity`, the [`wind_chill`]
+/// up to [`WIND_CHILL_MAX_C`] in a wind above [`WIND_CHILL_MIN_KPH`], and
+/// `None` in between, where the air feels as warm as it is.
+pub fn computed(temp_c: f64, humidity: Option<f64>, wind_kph: Option<f64>) -> Option<FeelsLike> {
+    if temp_c >= HEAT_INDEX_MIN_C {
+        return humidity.map(|humidity| FeelsLike {
+            temp_c: heat_index(temp_c, humidity),
+            source: FeelsLikeSource::HeatIndex,
+        });
+    }
+    if temp_c <= WIND_CHILL_MAX_C {
+        return wind_kph
+            .filter(|&kph| kph > WIND_CHILL_MIN_KPH)
+            .map(|kph| FeelsLike {
+                temp_c: wind_chill(temp_c, kph),
+                source: FeelsLikeSource::WindChill,
+            });
+    }
+    None
+}
+
+/// The feels-like temperature of `data`: the provider's where it reports
+/// one, else [`computed`] from the temperature, humidity and wind, so it
+/// is the same for every provider.
+pub fn feels_like(data: &WeatherData) -> Option<FeelsLike> {
+    if let Some(temp_c) = data.feels_like_c {
+        return Some(FeelsLike {
+            temp_c,
+            source: FeelsLikeSource::Provider,
+        });
+    }
+    computed(data.temp_c?, data.humidity, data.wind_kph)
+}
+
+/// Dew point in °C from which humid air is [`Comfort::Muggy`].
+pub const MUGGY_DEW_POINT_C: f64 = 16.0;
+
+/// How the weather feels, from the apparent temperature and the dew
+/// point, see [`classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Comfort {
+    Bitter,
+    Cold,
+    Cool,
+    Pleasant,
+    Muggy,
+    Hot,
+}
+
+impl Comfort {
+    /// The label shown in text output, e.g. `muggy`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bitter => "bitter",
+            Self::Cold => "cold",
+            Self::Cool => "cool",
+            Self::Pleasant => "pleasant",
+            Self::Muggy => "muggy",
+            Self::Hot => "hot",
+        }
+    }
+}
+
+impl fmt::Display for Comfort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The comfort of air that feels like `feels_like_c` °C, with a dew point
+/// of `dew_point_c` °C where known:
+/// - `bitter` at -10 °C and below,
+/// - `cold` below 10 °C,
+/// - `hot` at 32 °C and above,
+/// - `muggy` with a dew point of [`MUGGY_DEW_POINT_C`] and above,
+/// - `cool` below 18 °C,
+/// - `pleasant` otherwise.
+///
+/// The first that holds is taken: humid air that feels cold or hot is
+/// called so, not muggy.
+pub fn classify(feels_like_c: f64, dew_point_c: Option<f64>) -> Comfort {
+    if feels_like_c <= -10.0 {
+        Comfort::Bitter
+    } else if feels_like_c < 10.0 {
+        Comfort::Cold
+    } else if feels_like_c >= 32.0 {
+        Comfort::Hot
+    } else if dew_point_c.is_some_and(|d| d >= MUGGY_DEW_POINT_C) {
+        Comfort::Muggy
+    } else if feels_like_c < 18.0 {
+        Comfort::Cool
+    } else {
+        Comfort::Pleasant
+    }
+}
+
+/// The comfort of `data`, see [`classify`], from its [`feels_like`]
+/// temperature, else its temperature, and its dew point, the provider's
+/// or computed with [`dew_point`]; `None` without a temperature.
+pub fn comfort(data: &WeatherData) -> Option<Comfort> {
+    let temp_c = feels_like(data).map(|f| f.temp_c).or(data.temp_c)?;
+    let dew_point_c = data.dew_point_c.or_else(|| {
+        data.temp_c
+            .zip(data.humidity)
+            .and_then(|(t, h)| dew_point(t, h))
+    });
+    Some(classify(temp_c, dew_point_c))
+}
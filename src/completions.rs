@@ -0,0 +1,51 @@
+use std::ffi::OsStr;
+use std::io::Write;
+
+use clap::CommandFactory;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::Shell;
+
+use crate::config::AppConfig;
+
+/// Writes the completion script of `wapp` for `shell` to `out`.
+///
+/// The script completes subcommands, flags, and provider names; saved
+/// locations need the dynamic completions of [`complete_city`].
+pub fn generate(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut crate::cli::Cli::command(), "wapp", out);
+}
+
+/// The saved locations of `cfg` starting with `current`, as `@name`; empty
+/// unless `current` starts with `@`.
+pub fn location_candidates(cfg: &AppConfig, current: &str) -> Vec<String> {
+    let Some(prefix) = current.strip_prefix('@') else {
+        return Vec::new();
+    };
+
+    cfg.locations
+        .keys()
+        .filter(|name| name.starts_with(prefix))
+        .map(|name| format!("@{}", name))
+        .collect()
+}
+
+/// Completes a `--city` value with the saved locations, see
+/// [`location_candidates`]. Used by shells that call back into `wapp`
+/// (`COMPLETE=<shell> wapp`); without a readable config there is nothing
+/// to offer.
+pub fn complete_city(current: &OsStr) -> Vec<CompletionCandidate> {
+    let (Some(current), Ok(cfg)) = (current.to_str(), crate::config::load_config()) else {
+        return Vec::new();
+    };
+
+    location_candidates(&cfg, current)
+        .into_iter()
+        .map(|name| {
+            let help = cfg
+                .locations
+                .get(&name[1..])
+                .map(|entry| entry.to_string().into());
+            CompletionCandidate::new(name).help(help)
+        })
+        .collect()
+}
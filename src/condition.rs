@@ -0,0 +1,161 @@
+use serde::Serialize;
+
+/// Weather condition in a provider-independent category.
+///
+/// Providers report conditions as their own numeric codes and free text;
+/// both map onto this enum so output can show a matching glyph. Anything
+/// without a category is [`Unknown`](Condition::Unknown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Fog,
+    Drizzle,
+    Rain,
+    Sleet,
+    Snow,
+    Thunderstorm,
+    Unknown,
+}
+
+impl Condition {
+    /// Maps an OpenWeatherMap condition code (`weather[0].id`), see
+    /// <https://openweathermap.org/weather-conditions>.
+    pub fn from_openweather(code: i64) -> Self {
+        match code {
+            200..=299 => Self::Thunderstorm,
+            300..=399 => Self::Drizzle,
+            // Freezing rain
+            511 => Self::Sleet,
+            500..=599 => Self::Rain,
+            611..=616 => Self::Sleet,
+            600..=699 => Self::Snow,
+            // Squalls and tornadoes
+            771 | 781 => Self::Thunderstorm,
+            700..=799 => Self::Fog,
+            800 => Self::Clear,
+            801 | 802 => Self::PartlyCloudy,
+            803 | 804 => Self::Cloudy,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Maps a WeatherAPI condition code (`condition.code`), see
+    /// <https://www.weatherapi.com/docs/weather_conditions.json>.
+    pub fn from_weatherapi(code: i64) -> Self {
+        match code {
+            1000 => Self::Clear,
+            1003 => Self::PartlyCloudy,
+            1006 | 1009 => Self::Cloudy,
+            1030 | 1135 | 1147 => Self::Fog,
+            1063 | 1180..=1195 | 1240..=1246 => Self::Rain,
+            1066 | 1114 | 1117 | 1210..=1225 | 1255 | 1258 => Self::Snow,
+            1069 | 1198 | 1201 | 1204..=1207 | 1237 | 1249 | 1252 | 1261 | 1264 => Self::Sleet,
+            1072 | 1150..=1171 => Self::Drizzle,
+            1087 | 1273..=1282 => Self::Thunderstorm,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Maps a condition text by its keywords, for providers whose codes are
+    /// not mapped, e.g. "Patchy rain nearby" → [`Rain`](Condition::Rain).
+    pub fn from_text(text: &str) -> Self {
+        let text = text.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| text.contains(w));
+
+        if has(&["thunder", "storm"]) {
+            Self::Thunderstorm
+        } else if has(&["sleet", "freezing rain", "freezing drizzle", "ice pellets"]) {
+            Self::Sleet
+        } else if has(&["snow", "blizzard", "flurries"]) {
+            Self::Snow
+        } else if has(&["drizzle"]) {
+            Self::Drizzle
+        } else if has(&["rain", "shower"]) {
+            Self::Rain
+        } else if has(&["fog", "mist", "haze"]) {
+            Self::Fog
+        } else if has(&["partly", "few clouds", "scattered", "mostly clear"]) {
+            Self::PartlyCloudy
+        } else if has(&["cloud", "overcast"]) {
+            Self::Cloudy
+        } else if has(&["clear", "sunny", "sun"]) {
+            Self::Clear
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// How bad the weather is, from 0 for clear skies (and unknown
+    /// conditions) to 8 for thunderstorms: thunderstorm > sleet > snow >
+    /// rain > drizzle > fog > cloudy > partly cloudy > clear. Breaks the
+    /// ties of [`dominant`].
+    pub fn severity(self) -> u8 {
+        match self {
+            Self::Clear | Self::Unknown => 0,
+            Self::PartlyCloudy => 1,
+            Self::Cloudy => 2,
+            Self::Fog => 3,
+            Self::Drizzle => 4,
+            Self::Rain => 5,
+            Self::Snow => 6,
+            Self::Sleet => 7,
+            Self::Thunderstorm => 8,
+        }
+    }
+
+    /// Emoji for the condition; unknown conditions get a thermometer.
+    pub fn emoji(self) -> &'static str {
+        match self {
+            Self::Clear => "☀️",
+            Self::PartlyCloudy => "⛅",
+            Self::Cloudy => "☁️",
+            Self::Fog => "🌫",
+            Self::Drizzle => "🌦",
+            Self::Rain => "🌧",
+            Self::Sleet => "🌨",
+            Self::Snow => "❄️",
+            Self::Thunderstorm => "⛈",
+            Self::Unknown => "🌡",
+        }
+    }
+
+    /// ASCII replacement of [`emoji`](Self::emoji) for terminals without
+    /// emoji support.
+    pub fn ascii(self) -> &'static str {
+        match self {
+            Self::Clear => "O",
+            Self::PartlyCloudy => "O~",
+            Self::Cloudy => "~~",
+            Self::Fog => "==",
+            Self::Drizzle => ",,",
+            Self::Rain => "//",
+            Self::Sleet => "*/",
+            Self::Snow => "**",
+            Self::Thunderstorm => "/!",
+            Self::Unknown => "?",
+        }
+    }
+}
+
+/// The condition most frequent in `conditions`, e.g. of the 3-hour slots of
+/// a day; ties go to the worse weather, see [`Condition::severity`].
+///
+/// Unknown conditions are not counted, so a day of three unmapped slots and
+/// one of rain is rainy; [`Unknown`](Condition::Unknown) only when nothing
+/// else is left, including for no conditions at all.
+pub fn dominant(conditions: &[Condition]) -> Condition {
+    let mut counts: Vec<(Condition, usize)> = Vec::new();
+    for &condition in conditions.iter().filter(|c| **c != Condition::Unknown) {
+        match counts.iter_mut().find(|(c, _)| *c == condition) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((condition, 1)),
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(c, n)| (*n, c.severity()))
+        .map_or(Condition::Unknown, |(c, _)| c)
+}
@@ -1,6 +1,16 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::advice::AdviceSettings;
+use crate::error::WappError;
+use crate::location::{CityName, Location};
+use crate::logging::LogFormat;
+use crate::providers::ProviderKind;
+use crate::request::Strategy;
+use crate::rules::Rule;
 
 /// Application configuration structure.
 ///
@@ -8,52 +18,1032 @@ use std::path::Path;
 /// It is saved to and loaded from `config.json` in the application root.
 ///
 /// # Fields
-/// - `provider`: Name of the active weather provider (e.g., `"weatherapi"`, `"openweather"`).
+/// - `provider`: The active weather provider, saved by name (e.g., `"weatherapi"`,
+///   `"openweather"`); see [`ProviderKind`] for the accepted spellings.
+/// - `fallback_providers`: Providers tried in order when `provider` is unavailable.
+/// - `api_keys`: API keys saved by `configure --key`, keyed by provider name;
+///   used when no key is set in the environment or the OS keyring.
+/// - `rules`: Threshold rules evaluated after every successful fetch.
+/// - `pager`: Pager for long human-readable output; an empty string disables paging.
+/// - `providers`: Settings for individual providers, keyed by provider name.
+/// - `log_format`: Format of log events on stderr (`text` or `json`).
+/// - `auto_locate`: `false` stops `get` without a location from looking up
+///   the position of this machine by IP address.
+/// - `default_city`: City used by `get` when no location is given.
+/// - `locations`: Saved locations by name, used as `--city @name`.
+/// - `units`: Units of measurement used when `--units` is not given.
+/// - `lang`: Response language used when `--lang` is not given.
+/// - `profiles`: Named profiles by name, see [`Profile`]; the top-level
+///   settings are the [`DEFAULT_PROFILE`].
+/// - `active_profile`: Profile used when `--profile` is not given.
+/// - `cache_ttl`: Seconds a cached `get` response is reused, see
+///   [`ResponseCache`](crate::cache::ResponseCache).
+/// - `stale_fallback`: `true` makes `get` show the last cached response,
+///   with a warning, when the provider cannot be reached.
+/// - `notify`: `true` sends weather output as desktop notifications too,
+///   as `--notify` does.
+/// - `history`: `true` records every successful query in the local history,
+///   see [`record`](crate::history::record).
+/// - `retries`: Attempts per provider request, see
+///   [`RetryPolicy`](crate::retry::RetryPolicy).
+/// - `rate_limit_wait`: Longest `Retry-After`, in seconds, waited for
+///   before a rate limited request is repeated.
+/// - `timeout`: Seconds a provider request may take, see
+///   [`timeouts_for`](crate::timeout::timeouts_for).
+/// - `proxy`: Proxy URL of provider requests, see
+///   [`proxy_for`](crate::http::proxy_for).
+/// - `ca_bundle`: PEM file of extra trusted CA certificates, see
+///   [`tls_for`](crate::http::tls_for).
+/// - `strategy`: How `provider` and `fallback_providers` share a request,
+///   see [`Strategy`].
+/// - `advice`: Thresholds and rules of `get --advice`, see
+///   [`AdviceSettings`].
+/// - `quota`: Calls allowed per day by provider name, see
+///   [`Quota`](crate::quota::Quota).
 ///
 /// This struct is serializable and deserializable using Serde.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct AppConfig {
-    /// Name of the selected weather provider.
-    pub provider: String,
+    /// The selected weather provider, saved by name.
+    pub provider: ProviderKind,
+
+    /// Providers tried in order after `provider` when it is unavailable,
+    /// see [`provider_chain`](Self::provider_chain).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_providers: Vec<ProviderKind>,
+
+    /// API keys saved by `configure --key`, by provider name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub api_keys: BTreeMap<String, String>,
+
+    /// Threshold rules, see [`Rule`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<Rule>,
+
+    /// Pager command, overriding `$PAGER`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pager: Option<String>,
+
+    /// Per-provider settings, see [`ProviderSettings`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, ProviderSettings>,
+
+    /// Log format, overridden by `--log-format`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_format: Option<LogFormat>,
+
+    /// IP geolocation for `get` without a location; on when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_locate: Option<bool>,
+
+    /// City saved by `configure --default-city`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_city: Option<String>,
+
+    /// Locations saved by `location add`, see [`SavedLocation`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locations: BTreeMap<String, SavedLocation>,
+
+    /// Units saved by the `configure` wizard (`metric`, `imperial`, `standard`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<String>,
+
+    /// Response language code saved by the `configure` wizard.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+
+    /// Profiles saved by `configure --profile`, see [`Profile`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+
+    /// Profile set by `profile use`; the [`DEFAULT_PROFILE`] when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+
+    /// Response cache lifetime in seconds, overridden by `--cache-ttl`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+
+    /// Cached responses of any age for unreachable providers; off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_fallback: Option<bool>,
+
+    /// Desktop notifications of weather output; off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<bool>,
+
+    /// Recording of successful queries in the history; off when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<bool>,
+
+    /// Attempts per provider request, overridden by `--retries`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+
+    /// Longest `Retry-After` waited for, in seconds; 10 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit_wait: Option<u64>,
+
+    /// Provider request timeout in seconds, overridden by `WAPP_TIMEOUT`
+    /// and `get --timeout`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    /// Proxy of provider requests, above `HTTP_PROXY` and `HTTPS_PROXY`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// Extra trusted CA certificates, overridden by `WAPP_CA_BUNDLE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<PathBuf>,
+
+    /// Request strategy; `fallback` when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<Strategy>,
+
+    /// Advice thresholds and rules; the built-in defaults when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advice: Option<AdviceSettings>,
+
+    /// Daily call limits by provider name; no limit when unset.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub quota: BTreeMap<String, u32>,
+}
+
+impl AppConfig {
+    /// The selected provider followed by the fallback providers, without
+    /// repeats.
+    pub fn provider_chain(&self) -> Vec<ProviderKind> {
+        let mut chain = vec![self.provider];
+        for kind in &self.fallback_providers {
+            if !chain.contains(kind) {
+                chain.push(*kind);
+            }
+        }
+        chain
+    }
+
+    /// The value of the config `key` as it is written on the command line,
+    /// or `None` when it is not set. `auto_locate`, `stale_fallback`,
+    /// `notify`, and `history` are always set, to their defaults `on`, `off`,
+    /// `off`, and `off` when the config leaves them out.
+    ///
+    /// `api_key` is the key saved for the selected `provider`, returned as
+    /// it is; callers printing it redact it.
+    ///
+    /// # Errors
+    /// Returns an error listing [`CONFIG_KEYS`] for an unknown key.
+    pub fn get_key(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(match key {
+            "provider" => Some(self.provider.to_string()),
+            "fallback_providers" => Some(
+                self.fallback_providers
+                    .iter()
+                    .map(|kind| kind.name())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )
+            .filter(|list| !list.is_empty()),
+            "api_key" => self.api_keys.get(self.provider.name()).cloned(),
+            "default_city" => self.default_city.clone(),
+            "auto_locate" => Some(on_off(self.auto_locate.unwrap_or(true)).into()),
+            "stale_fallback" => Some(on_off(self.stale_fallback.unwrap_or(false)).into()),
+            "notify" => Some(on_off(self.notify.unwrap_or(false)).into()),
+            "history" => Some(on_off(self.history.unwrap_or(false)).into()),
+            "pager" => self.pager.clone(),
+            "units" => self.units.clone(),
+            "lang" => self.lang.clone(),
+            "log_format" => self.log_format.map(|format| match format {
+                LogFormat::Text => "text".into(),
+                LogFormat::Json => "json".into(),
+            }),
+            "cache_ttl" => self.cache_ttl.map(|secs| secs.to_string()),
+            "retries" => self.retries.map(|attempts| attempts.to_string()),
+            "rate_limit_wait" => self.rate_limit_wait.map(|secs| secs.to_string()),
+            "timeout" => self.timeout.map(|secs| secs.to_string()),
+            "proxy" => self.proxy.clone(),
+            "ca_bundle" => self
+                .ca_bundle
+                .as_ref()
+                .map(|path| path.display().to_string()),
+            "strategy" => self.strategy.map(|strategy| strategy.name().into()),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Sets the config `key` from its command line form, validated like the
+    /// matching `configure` option:
+    ///
+    /// - `provider`: a provider name, see [`ProviderKind`],
+    /// - `fallback_providers`: comma-separated provider names,
+    /// - `default_city`: a city name, see [`CityName`],
+    /// - `auto_locate`, `stale_fallback`, `notify`, `history`: `on`/`off`,
+    ///   `true`/`false`, or `yes`/`no`,
+    /// - `log_format`: `text` or `json`,
+    /// - `units`: see [`parse_units`], `lang`: see [`parse_lang`],
+    /// - `api_key`: the key saved for the selected `provider`,
+    /// - `pager`: any text; an empty pager disables paging,
+    /// - `cache_ttl`: whole seconds; `0` disables the response cache,
+    /// - `retries`: attempts per provider request, at least `1`,
+    /// - `rate_limit_wait`: whole seconds; `0` never waits for a `429`,
+    /// - `timeout`: whole seconds, at least `1`,
+    /// - `proxy`: an `http` or `https` URL, see
+    ///   [`parse_proxy`](crate::http::parse_proxy),
+    /// - `ca_bundle`: a readable PEM file, see
+    ///   [`read_ca_bundle`](crate::http::read_ca_bundle),
+    /// - `strategy`: `single`, `fallback`, or `race`, see [`Strategy`].
+    ///
+    /// # Errors
+    /// Returns an error for an unknown key or an invalid value.
+    pub fn set_key(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let invalid = |expected: &str| {
+            anyhow::anyhow!(
+                "invalid value '{}' for {}: expected {}",
+                value,
+                key,
+                expected
+            )
+        };
+
+        match key {
+            "provider" => self.provider = value.parse()?,
+            "fallback_providers" => {
+                self.fallback_providers = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::parse)
+                    .collect::<anyhow::Result<_>>()?
+            }
+            "api_key" if value.trim().is_empty() => return Err(invalid("a non-empty key")),
+            "api_key" => {
+                self.api_keys
+                    .insert(self.provider.name().to_string(), value.trim().to_string());
+            }
+            "default_city" => self.default_city = Some(CityName::new(value)?.as_str().to_string()),
+            "auto_locate" => {
+                self.auto_locate = Some(parse_on_off(value).ok_or_else(|| invalid("on or off"))?)
+            }
+            "stale_fallback" => {
+                self.stale_fallback = Some(parse_on_off(value).ok_or_else(|| invalid("on or off"))?)
+            }
+            "notify" => {
+                self.notify = Some(parse_on_off(value).ok_or_else(|| invalid("on or off"))?)
+            }
+            "history" => {
+                self.history = Some(parse_on_off(value).ok_or_else(|| invalid("on or off"))?)
+            }
+            "pager" => self.pager = Some(value.to_string()),
+            "units" => self.units = Some(parse_units(value)?),
+            "lang" => self.lang = Some(parse_lang(value)?),
+            "log_format" => {
+                self.log_format = Some(match value.trim().to_lowercase().as_str() {
+                    "text" => LogFormat::Text,
+                    "json" => LogFormat::Json,
+                    _ => return Err(invalid("text or json")),
+                })
+            }
+            "cache_ttl" => {
+                self.cache_ttl = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid("a number of seconds"))?,
+                )
+            }
+            "retries" => {
+                self.retries = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|attempts| *attempts >= 1)
+                        .ok_or_else(|| invalid("a number of attempts, at least 1"))?,
+                )
+            }
+            "rate_limit_wait" => {
+                self.rate_limit_wait = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid("a number of seconds"))?,
+                )
+            }
+            "timeout" => {
+                self.timeout = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|secs| *secs >= 1)
+                        .ok_or_else(|| invalid("a number of seconds, at least 1"))?,
+                )
+            }
+            "proxy" => {
+                crate::http::parse_proxy(value)?;
+                self.proxy = Some(value.trim().to_string());
+            }
+            "ca_bundle" => {
+                let path = PathBuf::from(value.trim());
+                crate::http::read_ca_bundle(&path)?;
+                self.ca_bundle = Some(path);
+            }
+            "strategy" => self.strategy = Some(value.parse()?),
+            _ => return Err(unknown_key(key)),
+        }
+
+        Ok(())
+    }
+
+    /// Removes the config `key`, so its default applies again.
+    ///
+    /// # Errors
+    /// Returns an error for an unknown key, and for `provider`, which has
+    /// to be set.
+    pub fn unset_key(&mut self, key: &str) -> anyhow::Result<()> {
+        match key {
+            "provider" => {
+                return Err(anyhow::anyhow!(
+                    "provider cannot be unset; choose another one with `wapp config set provider <name>`"
+                ))
+            }
+            "fallback_providers" => self.fallback_providers.clear(),
+            "api_key" => {
+                self.api_keys.remove(self.provider.name());
+            }
+            "default_city" => self.default_city = None,
+            "auto_locate" => self.auto_locate = None,
+            "pager" => self.pager = None,
+            "cache_ttl" => self.cache_ttl = None,
+            "stale_fallback" => self.stale_fallback = None,
+            "notify" => self.notify = None,
+            "history" => self.history = None,
+            "retries" => self.retries = None,
+            "rate_limit_wait" => self.rate_limit_wait = None,
+            "timeout" => self.timeout = None,
+            "proxy" => self.proxy = None,
+            "ca_bundle" => self.ca_bundle = None,
+            "strategy" => self.strategy = None,
+            "units" => self.units = None,
+            "lang" => self.lang = None,
+            "log_format" => self.log_format = None,
+            _ => return Err(unknown_key(key)),
+        }
+
+        Ok(())
+    }
+
+    /// Saves `location` as `name`, replacing an existing entry only with
+    /// `force`.
+    ///
+    /// # Errors
+    /// Returns an error if the name is invalid, see [`saved_name`], or
+    /// already taken without `force`.
+    pub fn add_location(
+        &mut self,
+        name: &str,
+        location: SavedLocation,
+        force: bool,
+    ) -> anyhow::Result<()> {
+        let name = saved_name(name)?;
+
+        if !force && self.locations.contains_key(&name) {
+            return Err(anyhow::anyhow!(
+                "location '{}' already exists; pass --force to replace it",
+                name
+            ));
+        }
+
+        self.locations.insert(name, location);
+        Ok(())
+    }
+
+    /// Removes the location saved as `name`.
+    ///
+    /// # Errors
+    /// Returns an error if no location has that name.
+    pub fn remove_location(&mut self, name: &str) -> anyhow::Result<SavedLocation> {
+        let name = name.strip_prefix('@').unwrap_or(name);
+
+        self.locations
+            .remove(name)
+            .ok_or_else(|| anyhow::anyhow!("no saved location '{}'", name))
+    }
+
+    /// Resolves a `@name` city to the location saved as `name`; other
+    /// locations are returned unchanged.
+    ///
+    /// # Errors
+    /// Returns an error if no location has that name or the saved one is
+    /// invalid.
+    pub fn resolve_location(&self, location: Location) -> anyhow::Result<Location> {
+        let Location::City(input) = &location else {
+            return Ok(location);
+        };
+        let Some(name) = input.raw.strip_prefix('@') else {
+            return Ok(location);
+        };
+
+        self.locations
+            .get(name)
+            .ok_or_else(|| {
+                anyhow::anyhow!("no saved location '{}'; see `wapp location list`", name)
+            })?
+            .to_location()
+    }
+
+    /// Name of the profile a call uses: `name` if given, else the active
+    /// profile, else the [`DEFAULT_PROFILE`].
+    pub fn profile_name<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.or(self.active_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Names of the [`DEFAULT_PROFILE`] and the saved profiles, in that order.
+    pub fn profile_names(&self) -> Vec<&str> {
+        std::iter::once(DEFAULT_PROFILE)
+            .chain(self.profiles.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// This config with the settings of the profile chosen by
+    /// [`profile_name`](Self::profile_name) in place of the top-level ones.
+    ///
+    /// # Errors
+    /// Returns an error if no profile has that name.
+    pub fn with_profile(&self, name: Option<&str>) -> anyhow::Result<AppConfig> {
+        let name = self.profile_name(name);
+        if name == DEFAULT_PROFILE {
+            return Ok(self.clone());
+        }
+
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| self.unknown_profile(name))?;
+        let mut cfg = self.clone();
+        if let Some(provider) = profile.provider {
+            cfg.provider = provider;
+        }
+        for (value, setting) in [
+            (&mut cfg.units, &profile.units),
+            (&mut cfg.lang, &profile.lang),
+            (&mut cfg.default_city, &profile.default_city),
+        ] {
+            if setting.is_some() {
+                value.clone_from(setting);
+            }
+        }
+        Ok(cfg)
+    }
+
+    /// Saves the provider, units, language and default city of `view`, a
+    /// config returned by [`with_profile`](Self::with_profile) and then
+    /// edited, to the profile `name`, and its other settings to this config.
+    ///
+    /// Only the profile settings that differ from the top-level ones are
+    /// stored in the profile, so the others keep following the top level.
+    pub fn store_profile(&mut self, name: &str, view: AppConfig) {
+        if name == DEFAULT_PROFILE {
+            *self = view;
+            return;
+        }
+
+        let differing = |value: &Option<String>, shared: &Option<String>| {
+            value.clone().filter(|_| value != shared)
+        };
+        let profile = Profile {
+            provider: Some(view.provider).filter(|provider| *provider != self.provider),
+            units: differing(&view.units, &self.units),
+            lang: differing(&view.lang, &self.lang),
+            default_city: differing(&view.default_city, &self.default_city),
+        };
+
+        let mut cfg = AppConfig {
+            provider: self.provider,
+            units: self.units.take(),
+            lang: self.lang.take(),
+            default_city: self.default_city.take(),
+            ..view
+        };
+        cfg.profiles.insert(name.to_string(), profile);
+        *self = cfg;
+    }
+
+    /// Makes `name` the profile used without `--profile`.
+    ///
+    /// # Errors
+    /// Returns an error if no profile has that name.
+    pub fn set_active_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        if name == DEFAULT_PROFILE {
+            self.active_profile = None;
+        } else if self.profiles.contains_key(name) {
+            self.active_profile = Some(name.to_string());
+        } else {
+            return Err(self.unknown_profile(name));
+        }
+
+        Ok(())
+    }
+
+    /// Removes the profile `name`; when it was active, the
+    /// [`DEFAULT_PROFILE`] becomes active again.
+    ///
+    /// # Errors
+    /// Returns an error for the default profile or an unknown name.
+    pub fn delete_profile(&mut self, name: &str) -> anyhow::Result<Profile> {
+        if name == DEFAULT_PROFILE {
+            return Err(anyhow::anyhow!(
+                "the {} profile cannot be deleted",
+                DEFAULT_PROFILE
+            ));
+        }
+
+        let profile = self
+            .profiles
+            .remove(name)
+            .ok_or_else(|| self.unknown_profile(name))?;
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+        Ok(profile)
+    }
+
+    fn unknown_profile(&self, name: &str) -> anyhow::Error {
+        anyhow::anyhow!(
+            "no profile '{}'. Profiles: {}",
+            name,
+            self.profile_names().join(", ")
+        )
+    }
+}
+
+/// Keys of `wapp config set` and `unset`, in the order `config show`
+/// prints them. Rules, saved locations and per-provider settings have no key;
+/// they are edited with their own commands or in `config.json`.
+pub const CONFIG_KEYS: [&str; 19] = [
+    "provider",
+    "fallback_providers",
+    "strategy",
+    "api_key",
+    "default_city",
+    "auto_locate",
+    "units",
+    "lang",
+    "pager",
+    "log_format",
+    "cache_ttl",
+    "stale_fallback",
+    "notify",
+    "history",
+    "retries",
+    "rate_limit_wait",
+    "timeout",
+    "proxy",
+    "ca_bundle",
+];
+
+/// `on` or `off`, how switches are written on the command line.
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Reads a switch written as `on`/`off`, `true`/`false`, or `yes`/`no`,
+/// ignoring case.
+fn parse_on_off(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "on" | "true" | "yes" => Some(true),
+        "off" | "false" | "no" => Some(false),
+        _ => None,
+    }
 }
 
-/// Path to the configuration file.
+/// Validates units of measurement, ignoring case.
 ///
-/// The file is stored next to the application binary.
-/// No directories are created automatically.
+/// # Errors
+/// Returns an error unless `value` is `metric`, `imperial` or `standard`.
+pub fn parse_units(value: &str) -> anyhow::Result<String> {
+    let units = value.trim().to_lowercase();
+
+    match units.as_str() {
+        "metric" | "imperial" | "standard" => Ok(units),
+        _ => Err(anyhow::anyhow!(
+            "invalid units '{}': expected metric, imperial or standard",
+            value.trim()
+        )),
+    }
+}
+
+/// Validates a response language code such as `en`, `uk` or `zh_tw`.
 ///
-/// Example: `config.json`
-const CONFIG_PATH: &str = "config.json";
+/// # Errors
+/// Returns an error unless `value` is 2 to 10 letters, digits, `-` or `_`.
+pub fn parse_lang(value: &str) -> anyhow::Result<String> {
+    let lang = value.trim();
+
+    if !(2..=10).contains(&lang.len())
+        || !lang
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "invalid language '{}': expected a code such as en or uk",
+            lang
+        ));
+    }
+
+    Ok(lang.to_string())
+}
 
-/// Saves the given configuration to `config.json`.
+fn unknown_key(key: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "unknown config key '{}'. Valid keys: {}",
+        key,
+        CONFIG_KEYS.join(", ")
+    )
+}
+
+/// A location saved under a name, stored under `locations.<name>` as a
+/// city or a pair of coordinates.
 ///
-/// The file is written using pretty JSON formatting for readability.
+/// ```json
+/// { "locations": { "home": { "city": "Kyiv" }, "office": { "lat": 50.45, "lon": 30.52 } } }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SavedLocation {
+    City { city: String },
+    Coords { lat: f64, lon: f64 },
+}
+
+impl SavedLocation {
+    /// The saved location as the `Location` a provider is asked for.
+    ///
+    /// # Errors
+    /// Returns an error if the city is not a valid city name or the
+    /// coordinates are out of range, e.g. after editing `config.json`.
+    pub fn to_location(&self) -> anyhow::Result<Location> {
+        match self {
+            Self::City { city } => Ok(Location::from(&CityName::new(city)?)),
+            Self::Coords { lat, lon } => Location::coords(*lat, *lon),
+        }
+    }
+}
+
+impl std::fmt::Display for SavedLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::City { city } => write!(f, "{}", city),
+            Self::Coords { lat, lon } => write!(f, "{},{}", lat, lon),
+        }
+    }
+}
+
+/// Validates the name of a saved location; a leading `@` is dropped.
 ///
 /// # Errors
-/// Returns an error if:
-/// - the file cannot be written,
-/// - serialization fails.
+/// Returns an error unless the name is non-empty and holds only letters,
+/// digits, `-` and `_`.
+pub fn saved_name(input: &str) -> anyhow::Result<String> {
+    let name = input.strip_prefix('@').unwrap_or(input);
+
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "invalid location name '{}': use letters, digits, '-' and '_'",
+            input
+        ));
+    }
+
+    Ok(name.to_string())
+}
+
+/// Settings for one provider, stored under `providers.<name>`.
+///
+/// ```json
+/// { "providers": { "weatherapi": { "extra_params": { "aqi": "yes" } } } }
+/// ```
+///
+/// # Fields
+/// - `extra_params`: query parameters appended to every request to the
+///   provider; `--param` flags with the same name take precedence.
+/// - `kind_overrides`: request parameters per data kind, see [`KindOverride`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderSettings {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_params: BTreeMap<String, String>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+/// Provider parameters used for one data kind instead of the built-in ones.
+///
+/// ```json
+/// { "kind_overrides": { "forecast": { "days": 7 }, "hourly": { "days": 2 } } }
+/// ```
+///
+/// # Fields
+/// - `days`: forecast length used when `--days` is not given; only the
+///   `forecast` and `hourly` kinds have a length.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KindOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+}
+
+/// Name of the profile made of the top-level settings of [`AppConfig`].
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Settings of one named profile, stored under `profiles.<name>`; a setting
+/// that is not set falls back to the top-level one.
+///
+/// ```json
+/// { "profiles": { "work": { "provider": "openweather", "units": "imperial", "lang": "en" } } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_city: Option<String>,
+}
+
+/// Validates a profile name given with `--profile`.
+///
+/// # Errors
+/// Returns an error unless the name is non-empty and holds only letters,
+/// digits, `-` and `_`.
+pub fn parse_profile_name(input: &str) -> anyhow::Result<String> {
+    if input.is_empty()
+        || !input
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "invalid profile name '{}': use letters, digits, '-' and '_'",
+            input
+        ));
+    }
+
+    Ok(input.to_string())
+}
+
+/// Debug output never includes the API key or the proxy password.
+impl std::fmt::Debug for AppConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppConfig")
+            .field("provider", &self.provider)
+            .field("fallback_providers", &self.fallback_providers)
+            .field(
+                "api_keys",
+                &self
+                    .api_keys
+                    .keys()
+                    .map(|name| (name, crate::providers::credentials::REDACTED))
+                    .collect::<BTreeMap<_, _>>(),
+            )
+            .field("rules", &self.rules)
+            .field("pager", &self.pager)
+            .field("providers", &self.providers)
+            .field("log_format", &self.log_format)
+            .field("auto_locate", &self.auto_locate)
+            .field("default_city", &self.default_city)
+            .field("locations", &self.locations)
+            .field("units", &self.units)
+            .field("lang", &self.lang)
+            .field("profiles", &self.profiles)
+            .field("active_profile", &self.active_profile)
+            .field("cache_ttl", &self.cache_ttl)
+            .field("stale_fallback", &self.stale_fallback)
+            .field("retries", &self.retries)
+            .field("rate_limit_wait", &self.rate_limit_wait)
+            .field("timeout", &self.timeout)
+            .field(
+                "proxy",
+                &self.proxy.as_deref().map(crate::http::redact_proxy),
+            )
+            .field("ca_bundle", &self.ca_bundle)
+            .field("strategy", &self.strategy)
+            .field("advice", &self.advice)
+            .field("quota", &self.quota)
+            .finish()
+    }
+}
+
+/// File name of the configuration, in the platform config directory, see
+/// [`Paths`](crate::paths::Paths); earlier versions kept it in the working
+/// directory.
+pub const CONFIG_FILE: &str = "config.json";
+
+/// Syntax of a config file, told by its extension: `.toml` is TOML, any
+/// other is JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+}
+
+impl ConfigFormat {
+    /// The format of the file at `path`.
+    pub fn of(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
+        }
+    }
+}
+
+/// Path set by the global `--config` flag, see [`use_config_path`].
+static CONFIG_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Makes every later [`load_config`] and [`save_config`] of this process
+/// use `path`; the first call wins.
+pub fn use_config_path(path: PathBuf) {
+    let _ = CONFIG_OVERRIDE.set(path);
+}
+
+/// Profile set by the global `--profile` flag, see [`use_profile`].
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Makes every later [`load_config`] of this process apply the profile
+/// `name` instead of the active one; the first call wins.
+pub fn use_profile(name: String) {
+    let _ = PROFILE.set(name);
+}
+
+/// The profile set with [`use_profile`], if any.
+pub fn selected_profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// Location of the config file: the `--config` path, else `$WAPP_CONFIG`,
+/// else `config.toml` in the platform config directory (`~/.config/wapp`
+/// on Linux) if it exists, else `config.json` there. TOML wins when both
+/// exist, with a warning. Relative paths are made absolute.
+pub fn config_path() -> PathBuf {
+    let path = match CONFIG_OVERRIDE.get() {
+        Some(path) => path.clone(),
+        None if location_chosen() => crate::paths::Paths::resolve().config,
+        None => {
+            let json = crate::paths::Paths::resolve().config;
+            let toml = json.with_extension("toml");
+            if !toml.exists() {
+                json
+            } else {
+                if json.exists() {
+                    static BOTH: std::sync::Once = std::sync::Once::new();
+                    BOTH.call_once(|| {
+                        tracing::warn!(
+                            used = %toml.display(),
+                            ignored = %json.display(),
+                            "both config.toml and config.json exist; using config.toml"
+                        )
+                    });
+                }
+                toml
+            }
+        }
+    };
+
+    std::path::absolute(&path).unwrap_or(path)
+}
+
+/// Whether the config file was chosen with `--config` or `$WAPP_CONFIG`.
+fn location_chosen() -> bool {
+    CONFIG_OVERRIDE.get().is_some() || std::env::var_os("WAPP_CONFIG").is_some()
+}
+
+/// The file [`load_config`] reads: [`config_path`] if it exists, else a
+/// `config.json` left in the working directory by an earlier version, unless
+/// the location was chosen with `--config` or `$WAPP_CONFIG`.
+pub fn find_config() -> Option<PathBuf> {
+    let path = config_path();
+    if path.exists() {
+        return Some(path);
+    }
+
+    let legacy = Path::new(CONFIG_FILE);
+    if location_chosen() || !legacy.is_file() {
+        return None;
+    }
+
+    static HINT: std::sync::Once = std::sync::Once::new();
+    HINT.call_once(|| {
+        tracing::warn!(
+            legacy = %legacy.display(),
+            path = %path.display(),
+            "using the config file in the working directory; move it with `wapp config migrate`"
+        )
+    });
+    Some(legacy.to_path_buf())
+}
+
+/// Saves the given configuration to [`config_path`], see [`save_config_to`].
+///
+/// # Errors
+/// Same as [`save_config_to`], as a [`WappError::Config`].
 ///
 /// # Example
 /// ```ignore
-/// let cfg = AppConfig { provider: "weatherapi".into() };
+/// let cfg = AppConfig { provider: ProviderKind::WeatherApi, ..Default::default() };
 /// save_config(&cfg)?;
 /// ```
 pub fn save_config(cfg: &AppConfig) -> anyhow::Result<()> {
-    fs::write(CONFIG_PATH, serde_json::to_string_pretty(cfg)?)?;
+    save_config_to(&config_path(), cfg).map_err(crate::error::config_error)
+}
+
+/// Saves `cfg` to `path`, creating its directory if needed.
+///
+/// The file is written atomically in the [`ConfigFormat`] of `path`, as
+/// pretty JSON or TOML for readability, so rewriting a TOML config keeps it
+/// TOML.
+///
+/// # Errors
+/// Returns an error if:
+/// - the directory or the file cannot be written,
+/// - serialization fails.
+pub fn save_config_to(path: &Path, cfg: &AppConfig) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        crate::paths::ensure_dir(dir)?;
+    }
+
+    let text = match ConfigFormat::of(path) {
+        ConfigFormat::Json => serde_json::to_string_pretty(cfg)?,
+        ConfigFormat::Toml => toml::to_string_pretty(cfg)?,
+    };
+    crate::fsutil::write_atomic(path, text.as_bytes())?;
     Ok(())
 }
 
-/// Loads the application configuration from `config.json`.
+/// An exclusive lock on a config file, released when dropped, see
+/// [`lock_config_at`].
+#[derive(Debug)]
+pub struct ConfigLock {
+    _file: fs::File,
+}
+
+/// Locks the config file of [`config_path`], see [`lock_config_at`].
 ///
-/// If the file does not exist, this function returns an instructional error
+/// # Errors
+/// Same as [`lock_config_at`], as a [`WappError::Config`].
+pub fn lock_config() -> anyhow::Result<ConfigLock> {
+    lock_config_at(&config_path()).map_err(crate::error::config_error)
+}
+
+/// Locks the config at `path` for a read-modify-write, e.g. `configure`
+/// changing one setting: another process or thread locking it waits until
+/// the lock is dropped, so neither loses the edit of the other.
+///
+/// The lock is advisory and held on a sibling `<file>.lock`, as saving
+/// replaces the config file itself. Loading takes no lock: saves are
+/// atomic, so a reader sees the old file or the new one.
+///
+/// # Errors
+/// Returns an error if the directory or the lock file cannot be created,
+/// or the file cannot be locked.
+pub fn lock_config_at(path: &Path) -> anyhow::Result<ConfigLock> {
+    let file = crate::fsutil::lock_sibling(path)?;
+    Ok(ConfigLock { _file: file })
+}
+
+/// `path` with `.suffix` appended to its file name, e.g. `config.json.lock`.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Loads the application configuration from the file of [`find_config`],
+/// with the settings of the [`selected_profile`] or the active one applied,
+/// see [`AppConfig::with_profile`].
+///
+/// If there is no file, this function returns an instructional error
 /// telling the user to run the `configure` command first.
 ///
 /// # Errors
 /// Returns an error if:
 /// - the config file is missing,
 /// - the JSON is malformed,
-/// - the file cannot be read.
+/// - the file cannot be read,
+/// - the profile does not exist,
+///
+/// each as a configuration [`WappError`].
 ///
 /// # Example
 /// ```ignore
@@ -61,11 +1051,249 @@ pub fn save_config(cfg: &AppConfig) -> anyhow::Result<()> {
 /// println!("Current provider: {}", cfg.provider);
 /// ```
 pub fn load_config() -> anyhow::Result<AppConfig> {
-    if !Path::new(CONFIG_PATH).exists() {
+    load_config_file()?
+        .with_profile(selected_profile())
+        .map_err(crate::error::config_error)
+}
+
+/// Like [`load_config`], without applying a profile: the config as saved,
+/// for commands that edit and save it.
+///
+/// # Errors
+/// Same as [`load_config`], except for the profile.
+pub fn load_config_file() -> anyhow::Result<AppConfig> {
+    match find_config() {
+        Some(path) => load_config_from(&path).map_err(crate::error::config_error),
+        None => Err(WappError::ConfigNotFound(config_path()).into()),
+    }
+}
+
+/// Like [`load_config_file`], with the default config when there is no
+/// file yet, for commands that create it.
+///
+/// # Errors
+/// Same as [`load_config_file`], except for a missing file.
+pub fn load_config_or_default() -> anyhow::Result<AppConfig> {
+    match load_config_file() {
+        Err(e) if matches!(WappError::of(&e), Some(WappError::ConfigNotFound(_))) => {
+            Ok(AppConfig::default())
+        }
+        result => result,
+    }
+}
+
+/// Loads the configuration saved at `path`, in the [`ConfigFormat`] of its
+/// extension.
+///
+/// An empty or truncated file, e.g. left by a crash while an earlier
+/// version wrote it in place, is moved aside to
+/// `<file>.corrupted-<date>-<time>`, so that `configure` starts afresh.
+///
+/// # Errors
+/// Returns an error if the file cannot be read, a
+/// [`WappError::ConfigCorrupted`] naming the backup if it was corrupted,
+/// or a [`WappError::ConfigParse`] if it is not a valid config.
+pub fn load_config_from(path: &Path) -> anyhow::Result<AppConfig> {
+    let _span = tracing::debug_span!("load_config", path = %path.display()).entered();
+    let text = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("cannot read {}: {}", path.display(), e))?;
+
+    if is_truncated(&text, ConfigFormat::of(path)) {
+        return Err(set_aside(path));
+    }
+    let cfg = match ConfigFormat::of(path) {
+        ConfigFormat::Json => parse_config(&text),
+        ConfigFormat::Toml => parse_toml_config(&text),
+    };
+    cfg.map_err(|e| {
+        WappError::ConfigParse {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        }
+        .into()
+    })
+}
+
+/// Whether `text` is an empty config, or only NUL bytes, or JSON cut short.
+/// A cut TOML file cannot be told from a mistake, so it is not detected.
+fn is_truncated(text: &str, format: ConfigFormat) -> bool {
+    if text
+        .trim_matches(|c: char| c.is_whitespace() || c == '\0')
+        .is_empty()
+    {
+        return true;
+    }
+
+    match format {
+        ConfigFormat::Json => {
+            serde_json::from_str::<serde_json::Value>(text).is_err_and(|e| e.is_eof())
+        }
+        ConfigFormat::Toml => false,
+    }
+}
+
+/// Moves the corrupted config at `path` aside, and returns the
+/// [`WappError::ConfigCorrupted`] naming the backup.
+fn set_aside(path: &Path) -> anyhow::Error {
+    let backup = sibling(
+        path,
+        &format!("corrupted-{}", chrono::Local::now().format("%Y%m%d-%H%M%S")),
+    );
+
+    match fs::rename(path, &backup) {
+        Ok(()) => {
+            tracing::warn!(path = %path.display(), backup = %backup.display(), "corrupted config moved aside");
+            WappError::ConfigCorrupted {
+                path: path.to_path_buf(),
+                backup,
+            }
+            .into()
+        }
+        Err(e) => anyhow::anyhow!(
+            "config {} appears corrupted, and cannot be moved aside: {}",
+            path.display(),
+            e
+        ),
+    }
+}
+
+/// Moves a `config.json` left in the working directory to `target`, and
+/// returns the working-directory file that was moved.
+///
+/// # Errors
+/// Returns an error if there is no such file, `target` already exists, or
+/// the file cannot be copied or removed.
+pub fn migrate_config(target: &Path) -> anyhow::Result<PathBuf> {
+    let legacy = std::path::absolute(CONFIG_FILE)?;
+    if !legacy.is_file() {
+        return Err(anyhow::anyhow!("no {} to migrate", legacy.display()));
+    }
+    if legacy == target {
+        return Err(anyhow::anyhow!(
+            "{} is already the config file",
+            legacy.display()
+        ));
+    }
+    if target.exists() {
         return Err(anyhow::anyhow!(
-            "config.json not found. Run: wapp configure <provider>"
+            "{} already exists; remove it or {} first",
+            target.display(),
+            legacy.display()
         ));
     }
 
-    Ok(serde_json::from_str(&fs::read_to_string(CONFIG_PATH)?)?)
+    // Checked on the way, so a broken file is not moved.
+    save_config_to(target, &load_config_from(&legacy)?)?;
+    fs::remove_file(&legacy)?;
+    Ok(legacy)
+}
+
+/// Reads the contents of a `config.json`.
+///
+/// Configs written before keys were saved per provider hold one `api_key`,
+/// which is read as the key of their `provider`.
+///
+/// # Errors
+/// Returns an error if the JSON is malformed or not a valid config.
+pub fn parse_config(text: &str) -> anyhow::Result<AppConfig> {
+    config_from_value(serde_json::from_str(text)?)
+}
+
+/// Like [`parse_config`], for the contents of a `config.toml`.
+///
+/// # Errors
+/// Returns an error if the TOML is malformed, naming the line and column,
+/// or not a valid config.
+pub fn parse_toml_config(text: &str) -> anyhow::Result<AppConfig> {
+    let table: toml::Table = toml::from_str(text)?;
+    config_from_value(serde_json::to_value(table)?)
+}
+
+/// Reads a parsed config, moving a legacy `api_key` to its provider.
+fn config_from_value(mut value: serde_json::Value) -> anyhow::Result<AppConfig> {
+    if let Some(fields) = value.as_object_mut() {
+        if let Some(serde_json::Value::String(key)) = fields.remove("api_key") {
+            let mut cfg: AppConfig = serde_json::from_value(value)?;
+            cfg.api_keys
+                .entry(cfg.provider.name().to_string())
+                .or_insert(key);
+            return Ok(cfg);
+        }
+    }
+
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Environment variable selecting the provider, below `--provider` and above
+/// the config file.
+pub const PROVIDER_ENV: &str = "WAPP_PROVIDER";
+
+/// Like [`load_config`], but the provider may also come from `--provider`
+/// (`provider`) or [`PROVIDER_ENV`], see [`config_for`].
+///
+/// # Errors
+/// Same as [`config_for`].
+pub fn load_config_for(provider: Option<ProviderKind>) -> anyhow::Result<AppConfig> {
+    let saved = match find_config() {
+        Some(path) => Some(
+            load_config_from(&path)
+                .and_then(|cfg| cfg.with_profile(selected_profile()))
+                .map_err(crate::error::config_error)?,
+        ),
+        None => None,
+    };
+
+    config_for(provider, std::env::var(PROVIDER_ENV).ok().as_deref(), saved)
+        .map_err(crate::error::config_error)
+}
+
+/// The config of a call whose provider comes from, in order: `flag`
+/// (`--provider`), `env` (the value of [`PROVIDER_ENV`]; empty counts as
+/// unset), or the `saved` config. Without a saved config, the default one
+/// with the chosen provider is used, so no config file is needed. The saved
+/// fallback providers still follow a provider from `env`. An info event
+/// names the source.
+///
+/// # Errors
+/// Returns an error if `env` is not a supported provider name, or no
+/// provider is given at all.
+pub fn config_for(
+    flag: Option<ProviderKind>,
+    env: Option<&str>,
+    saved: Option<AppConfig>,
+) -> anyhow::Result<AppConfig> {
+    let env = env
+        .filter(|value| !value.trim().is_empty())
+        .map(|value| {
+            value
+                .parse::<ProviderKind>()
+                .map_err(|e| anyhow::anyhow!("{}: {}", PROVIDER_ENV, e))
+        })
+        .transpose()?;
+
+    let (mut cfg, has_file) = match saved {
+        Some(cfg) => (cfg, true),
+        None => (AppConfig::default(), false),
+    };
+    let source = match (flag, env) {
+        (Some(provider), _) => {
+            cfg.provider = provider;
+            "--provider"
+        }
+        (None, Some(provider)) => {
+            cfg.provider = provider;
+            PROVIDER_ENV
+        }
+        (None, None) if has_file => "config",
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "{} not found. Run: wapp configure <provider>, or set {}",
+                config_path().display(),
+                PROVIDER_ENV
+            ))
+        }
+    };
+
+    tracing::info!(provider = %cfg.provider, source, "provider selected");
+    Ok(cfg)
 }
@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::fsutil::write_atomic;
+use crate::location::Location;
+use crate::providers::{ApiProvider, DataKind};
+use crate::rules::{Rule, RuleEngine};
+
+/// Format of the snapshot file written by the daemon.
+///
+/// - `json`: the provider response as returned,
+/// - `oneline`: the response compacted onto a single line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SnapshotFormat {
+    Json,
+    Oneline,
+}
+
+/// Settings for a daemon run.
+///
+/// # Fields
+/// - `location`: location to fetch,
+/// - `kind`: data kind passed to the provider (e.g. [`DataKind::Now`]),
+/// - `interval`: delay between fetches,
+/// - `output`: snapshot file, replaced atomically on every successful fetch,
+/// - `format`: snapshot format,
+/// - `stale_after`: age of the last successful fetch after which a
+///   `<output>.stale` marker is written,
+/// - `rules`: threshold rules evaluated after every successful fetch.
+#[derive(Debug, Clone)]
+pub struct DaemonOptions {
+    pub location: Location,
+    pub kind: DataKind,
+    pub interval: Duration,
+    pub output: PathBuf,
+    pub format: SnapshotFormat,
+    pub stale_after: Duration,
+    pub rules: Vec<Rule>,
+}
+
+/// Returns the path of the stale marker for a snapshot file,
+/// e.g. `current.json` → `current.json.stale`.
+pub fn stale_marker_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".stale");
+    PathBuf::from(name)
+}
+
+/// Renders a provider response in the requested snapshot format.
+///
+/// Non-JSON bodies in `oneline` mode are flattened by collapsing whitespace.
+pub fn render_snapshot(body: &str, format: SnapshotFormat) -> String {
+    match format {
+        SnapshotFormat::Json => body.to_string(),
+        SnapshotFormat::Oneline => {
+            let line = serde_json::from_str::<serde_json::Value>(body)
+                .map(|v| v.to_string())
+                .unwrap_or_else(|_| body.split_whitespace().collect::<Vec<_>>().join(" "));
+            format!("{}\n", line)
+        }
+    }
+}
+
+/// Runs the fetch-and-write loop until `shutdown` is cancelled.
+///
+/// Every `interval` the provider is queried and a successful response is
+/// written atomically to `output`. Failures are reported on stderr and keep
+/// the previous snapshot in place. Once the last successful fetch is older
+/// than `stale_after`, a `.stale` marker is written next to the snapshot and
+/// it is removed again after the next success.
+///
+/// Cancelling `shutdown` also cancels a fetch in progress (dropping its HTTP
+/// request) and keeps the previous snapshot. A snapshot write that has
+/// started always completes, so the file is never left half-written.
+///
+/// Threshold rules are evaluated after every successful write; an
+/// `exit-code` rule ends the loop.
+///
+/// # Errors
+/// Returns [`RuleExit`](crate::rules::RuleExit) when an `exit-code` rule
+/// fires; fetch, snapshot and stale marker errors are reported and retried
+/// on the next tick.
+pub async fn run(
+    provider: &dyn ApiProvider,
+    options: &DaemonOptions,
+    shutdown: &CancellationToken,
+) -> anyhow::Result<()> {
+    let marker = stale_marker_path(&options.output);
+    let started = Instant::now();
+    let mut last_success: Option<Instant> = None;
+    let mut engine = RuleEngine::new(options.rules.clone());
+
+    loop {
+        let fetched = tokio::select! {
+            result = provider.get_data(options.location.clone(), options.kind.fetched(), None) => result,
+            _ = shutdown.cancelled() => {
+                tracing::info!("fetch cancelled by shutdown");
+                break;
+            }
+        };
+
+        // Not cancellable: a started write always finishes.
+        let result = fetched.and_then(|body| {
+            let snapshot = render_snapshot(&body, options.format);
+            write_atomic(&options.output, snapshot.as_bytes())
+                .map(|()| body)
+                .map_err(|e| anyhow::anyhow!("cannot write {}: {}", options.output.display(), e))
+        });
+
+        match result {
+            Ok(body) => {
+                last_success = Some(Instant::now());
+                if marker.exists() {
+                    let _ = std::fs::remove_file(&marker);
+                }
+                tracing::info!(output = %options.output.display(), "snapshot written");
+                crate::rules::apply(&mut engine, &body, Instant::now()).await?;
+            }
+            Err(e) => tracing::error!(error = %e, "snapshot not updated"),
+        }
+
+        let age = last_success.unwrap_or(started).elapsed();
+        if age >= options.stale_after && !marker.exists() {
+            // Only bookkeeping: the daemon keeps fetching without it.
+            if let Err(e) = std::fs::write(&marker, stale_marker_contents(age)) {
+                tracing::warn!(marker = %marker.display(), error = %e, "cannot write stale marker");
+            }
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(options.interval) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Contents of the stale marker: when it was written and how old the data is.
+fn stale_marker_contents(age: Duration) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    format!("marked_at={}\nage_seconds={}\n", now, age.as_secs())
+}
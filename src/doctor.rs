@@ -0,0 +1,442 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::config::{AppConfig, PROVIDER_ENV};
+use crate::providers::credentials::redact_secrets;
+use crate::providers::{ApiProvider, DataKind, KeyRejected, ProviderKind, ProviderOverrides};
+
+/// Outcome of one check of `wapp doctor`.
+///
+/// - `pass`: the check succeeded,
+/// - `warn`: the check failed, but wapp can work without it,
+/// - `fail`: the check failed and wapp cannot fetch weather until it is
+///   fixed; `doctor` then exits non-zero,
+/// - `skip`: the check needs one that did not pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    /// The status as `doctor` prints it, e.g. `PASS`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pass => "PASS",
+            Self::Warn => "WARN",
+            Self::Fail => "FAIL",
+            Self::Skip => "SKIP",
+        }
+    }
+}
+
+/// The result of one check of `wapp doctor`.
+///
+/// # Fields
+/// - `name`: stable name of the check, e.g. `dns`,
+/// - `status`: see [`Status`],
+/// - `message`: what was found, on one line,
+/// - `hint`: how to fix a failed check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub status: Status,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn new(name: &str, status: Status, message: &str, hint: Option<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            // Errors may quote a request URL and with it the API key.
+            message: redact_secrets(message).replace('\n', " "),
+            hint,
+        }
+    }
+
+    /// A check that succeeded.
+    pub fn pass(name: &str, message: impl AsRef<str>) -> Self {
+        Self::new(name, Status::Pass, message.as_ref(), None)
+    }
+
+    /// A check wapp can work without that failed, with a `hint` to fix it.
+    pub fn warn(name: &str, message: impl AsRef<str>, hint: impl Into<String>) -> Self {
+        Self::new(name, Status::Warn, message.as_ref(), Some(hint.into()))
+    }
+
+    /// A critical check that failed, with a `hint` to fix it.
+    pub fn fail(name: &str, message: impl AsRef<str>, hint: impl Into<String>) -> Self {
+        Self::new(name, Status::Fail, message.as_ref(), Some(hint.into()))
+    }
+
+    /// A check that did not run, because of `reason`.
+    pub fn skip(name: &str, reason: impl AsRef<str>) -> Self {
+        Self::new(name, Status::Skip, reason.as_ref(), None)
+    }
+}
+
+/// Settings of a `wapp doctor` run.
+///
+/// # Fields
+/// - `provider`: the provider to check instead of the configured one,
+/// - `timeout`: longest wait for each network check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoctorOptions {
+    pub provider: Option<ProviderKind>,
+    pub timeout: Duration,
+}
+
+/// Runs every check, in order:
+///
+/// - `config`: the config file exists and can be read,
+/// - `provider`: a supported provider is chosen, see
+///   [`config_for`](crate::config::config_for),
+/// - `credentials`: the provider can be created, so its API key is set,
+/// - `dns`: the host of the provider's requests resolves,
+/// - `connect`: the host answers an HTTP(S) request,
+/// - `api_key`: the provider accepts the key, see
+///   [`verify_key`](crate::providers::verify_key),
+/// - `config_dir`, `cache_dir`, `data_dir`: the directories wapp writes to
+///   are writable; a failure is a warning.
+///
+/// A check whose prerequisite did not pass is skipped. Network checks give
+/// up after `options.timeout`, so the run never hangs.
+pub async fn run(options: &DoctorOptions) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    let env_provider = std::env::var(PROVIDER_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty());
+    let chosen = options.provider.is_some() || env_provider.is_some();
+    let config_path = crate::config::config_path();
+    let (saved, readable) = match crate::config::find_config() {
+        Some(path) => match crate::config::load_config_from(&path)
+            .and_then(|cfg| cfg.with_profile(crate::config::selected_profile()))
+        {
+            Ok(cfg) => {
+                checks.push(Check::pass("config", path.display().to_string()));
+                (Some(cfg), true)
+            }
+            Err(e) => {
+                checks.push(Check::fail(
+                    "config",
+                    format!("{:#}", e),
+                    "fix the file, or recreate it with `wapp configure <provider>`",
+                ));
+                (None, false)
+            }
+        },
+        None if chosen => {
+            checks.push(Check::warn(
+                "config",
+                format!("no config file at {}", config_path.display()),
+                "save the provider with `wapp configure <provider>`",
+            ));
+            (None, true)
+        }
+        None => {
+            checks.push(Check::fail(
+                "config",
+                format!("no config file at {}", config_path.display()),
+                "create it with `wapp configure <provider>`",
+            ));
+            (None, false)
+        }
+    };
+
+    let cfg = match readable {
+        false => {
+            checks.push(Check::skip("provider", "needs a readable config"));
+            None
+        }
+        true => match crate::config::config_for(options.provider, env_provider.as_deref(), saved) {
+            Ok(cfg) => {
+                checks.push(Check::pass("provider", cfg.provider.name()));
+                Some(cfg)
+            }
+            Err(e) => {
+                checks.push(Check::fail(
+                    "provider",
+                    e.to_string(),
+                    "`wapp providers` lists the supported names",
+                ));
+                None
+            }
+        },
+    };
+
+    let provider = match &cfg {
+        Some(cfg) => credentials(cfg, options.provider, &mut checks),
+        None => {
+            checks.push(Check::skip("credentials", "needs a provider"));
+            None
+        }
+    };
+
+    match &provider {
+        Some(provider) => network(provider.as_ref(), options.timeout, &mut checks).await,
+        None => {
+            for name in ["dns", "connect", "api_key"] {
+                checks.push(Check::skip(name, "needs the provider's credentials"));
+            }
+        }
+    }
+
+    let paths = crate::paths::Paths::resolve();
+    let config_dir = config_path.parent().unwrap_or(Path::new("."));
+    for (name, dir, hint) in [
+        (
+            "config_dir",
+            config_dir,
+            "make it writable, or choose another file with --config or WAPP_CONFIG",
+        ),
+        (
+            "cache_dir",
+            &paths.cache,
+            "make it writable, or choose another with WAPP_CACHE_DIR",
+        ),
+        (
+            "data_dir",
+            &paths.data,
+            "make it writable, or choose another with WAPP_DATA_DIR",
+        ),
+    ] {
+        checks.push(match writable(dir) {
+            Ok(()) => Check::pass(name, dir.display().to_string()),
+            Err(e) => Check::warn(name, format!("cannot write {}: {}", dir.display(), e), hint),
+        });
+    }
+
+    checks
+}
+
+/// Adds the `credentials` check of the provider of `cfg`, and returns the
+/// provider if it could be created.
+fn credentials(
+    cfg: &AppConfig,
+    provider: Option<ProviderKind>,
+    checks: &mut Vec<Check>,
+) -> Option<Box<dyn ApiProvider>> {
+    let kind = provider.unwrap_or(cfg.provider);
+    let overrides = ProviderOverrides {
+        provider,
+        ..Default::default()
+    };
+
+    match crate::providers::provider_factory_with(cfg, &overrides) {
+        Ok(provider) => {
+            let message = match kind.info().required_env {
+                [] => "no API key needed".to_string(),
+                vars => format!("{} found", vars.join(", ")),
+            };
+            checks.push(Check::pass("credentials", message));
+            Some(provider)
+        }
+        Err(e) => {
+            let hint = match kind.info().required_env {
+                [] => "`wapp providers` shows why the provider cannot be used".to_string(),
+                vars => format!(
+                    "set {}, or save a key with `wapp configure {} --key KEY`",
+                    vars.join(" and "),
+                    kind.name()
+                ),
+            };
+            checks.push(Check::fail("credentials", e.to_string(), hint));
+            None
+        }
+    }
+}
+
+/// Adds the `dns`, `connect`, and `api_key` checks of `provider`, each
+/// given up after `timeout`.
+async fn network(provider: &dyn ApiProvider, timeout: Duration, checks: &mut Vec<Check>) {
+    let limit = crate::timeout::describe_limit(timeout);
+    let url = provider
+        .build_request(&crate::providers::verify_location(), DataKind::Now, None)
+        .map_err(|e| e.to_string())
+        .and_then(|request| reqwest::Url::parse(&request.url).map_err(|e| e.to_string()));
+    let (url, host, port) = match url {
+        Ok(url) => match (url.host_str(), url.port_or_known_default()) {
+            (Some(host), Some(port)) => (url.clone(), host.to_string(), port),
+            _ => {
+                for name in ["dns", "connect", "api_key"] {
+                    checks.push(Check::skip(name, format!("no host in {}", url)));
+                }
+                return;
+            }
+        },
+        Err(e) => {
+            for name in ["dns", "connect", "api_key"] {
+                checks.push(Check::skip(name, format!("no request to check: {}", e)));
+            }
+            return;
+        }
+    };
+
+    let lookup = tokio::time::timeout(timeout, tokio::net::lookup_host((host.as_str(), port)));
+    let resolved = match lookup.await {
+        Ok(Ok(mut addrs)) => addrs
+            .next()
+            .ok_or_else(|| format!("{} has no address", host)),
+        Ok(Err(e)) => Err(format!("cannot resolve {}: {}", host, e)),
+        Err(_) => Err(format!("resolving {} timed out after {}", host, limit)),
+    };
+    let addr = match resolved {
+        Ok(addr) => addr,
+        Err(message) => {
+            checks.push(Check::fail(
+                "dns",
+                message,
+                "check the network connection and DNS settings",
+            ));
+            checks.push(Check::skip("connect", "needs the host to resolve"));
+            checks.push(Check::skip("api_key", "needs the host to resolve"));
+            return;
+        }
+    };
+    checks.push(Check::pass(
+        "dns",
+        format!("{} resolves to {}", host, addr.ip()),
+    ));
+
+    let origin = url.origin().ascii_serialization();
+    let connect_hint = "check the network connection, the `proxy` and `ca_bundle` settings, \
+                        and any firewall";
+    let connected = match crate::http::client() {
+        Ok(client) => match tokio::time::timeout(timeout, client.get(&origin).send()).await {
+            Ok(Ok(response)) => Ok(format!(
+                "{} answered HTTP {}",
+                origin,
+                response.status().as_u16()
+            )),
+            Ok(Err(e)) => Err(format!("cannot reach {}: {}", origin, e.without_url())),
+            Err(_) => Err(format!("{} did not answer within {}", origin, limit)),
+        },
+        Err(e) => Err(e.to_string()),
+    };
+    match connected {
+        Ok(message) => checks.push(Check::pass("connect", message)),
+        Err(message) => {
+            checks.push(Check::fail("connect", message, connect_hint));
+            checks.push(Check::skip("api_key", "needs a connection"));
+            return;
+        }
+    }
+
+    if provider.verify_url().is_none() {
+        checks.push(Check::pass("api_key", "no API key needed"));
+        return;
+    }
+    let key_hint = format!(
+        "check the key, then save it with `wapp configure {} --key KEY`",
+        provider.name()
+    );
+    checks.push(
+        match tokio::time::timeout(timeout, crate::providers::verify_key(provider)).await {
+            Ok(Ok(())) => Check::pass("api_key", "accepted"),
+            Ok(Err(e)) if e.is::<KeyRejected>() => Check::fail("api_key", e.to_string(), key_hint),
+            Ok(Err(e)) => Check::fail(
+                "api_key",
+                format!("{:#}", e),
+                "try again later, or check the provider's status page",
+            ),
+            Err(_) => Check::fail(
+                "api_key",
+                format!("the key check timed out after {}", limit),
+                "try again later, or raise --timeout",
+            ),
+        },
+    );
+}
+
+/// Creates `dir` if needed and writes and removes a file in it.
+fn writable(dir: &Path) -> std::io::Result<()> {
+    crate::paths::ensure_dir(dir)?;
+
+    let probe = dir.join(format!(".wapp-doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+/// The number of critical checks that failed; `doctor` exits non-zero
+/// unless it is `0`.
+pub fn failed(checks: &[Check]) -> usize {
+    checks
+        .iter()
+        .filter(|check| check.status == Status::Fail)
+        .count()
+}
+
+/// Renders the checks as one line each, with the hint of a failed check
+/// below it, followed by a summary line:
+///
+/// ```text
+/// PASS  config       /home/me/.config/wapp/config.json
+/// FAIL  credentials  WEATHERAPI_KEY environment variable is not set
+///                    hint: set WEATHERAPI_KEY, or save a key with `wapp configure weatherapi --key KEY`
+/// ```
+pub fn render_text(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for check in checks {
+        out.push_str(&format!(
+            "{}  {:<width$}  {}\n",
+            check.status.label(),
+            check.name,
+            check.message,
+            width = width
+        ));
+        if let Some(hint) = &check.hint {
+            out.push_str(&format!("{}hint: {}\n", " ".repeat(width + 8), hint));
+        }
+    }
+
+    let warnings = checks
+        .iter()
+        .filter(|check| check.status == Status::Warn)
+        .count();
+    out.push_str(&match (failed(checks), warnings) {
+        (0, 0) => "All checks passed\n".to_string(),
+        (0, n) => format!(
+            "All critical checks passed, {} {}\n",
+            n,
+            plural(n, "warning")
+        ),
+        (n, _) => format!("{} {} failed\n", n, plural(n, "check")),
+    });
+
+    out
+}
+
+/// Renders the checks as a JSON object, for CI:
+/// `{"ok": false, "checks": [{"name": "config", "status": "pass", ...}]}`.
+pub fn render_json(checks: &[Check]) -> String {
+    let json = serde_json::json!({
+        "ok": failed(checks) == 0,
+        "checks": checks,
+    });
+    format!(
+        "{}\n",
+        serde_json::to_string_pretty(&json).unwrap_or_default()
+    )
+}
+
+fn plural(n: usize, word: &str) -> String {
+    match n {
+        1 => word.to_string(),
+        _ => format!("{}s", word),
+    }
+}
@@ -0,0 +1,375 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::cache::NoCachedData;
+use crate::location::Location;
+use crate::providers::credentials::redact_secrets;
+use crate::providers::{DataKind, KeyRejected, ProviderKind};
+use crate::request::RaceFailed;
+use crate::rules::RuleExit;
+
+/// The failures callers tell apart, e.g. to pick an exit code, retry a
+/// request, or fall back to another provider.
+///
+/// Functions return them inside an [`anyhow::Error`], which may add
+/// context; [`WappError::of`] finds one again.
+///
+/// # Variants
+/// - `Usage`: a command used wrongly, e.g. `get` without a location,
+/// - `MissingEnvVar`: the variable of a provider's API key (or its `_FILE`
+///   variant) is not set,
+/// - `UnsupportedProvider`: an unknown provider name, with the closest
+///   known one if it is only a typo away,
+/// - `ConfigNotFound`: no config file, where one is needed,
+/// - `ConfigParse`: a config file that is not a valid config,
+/// - `ConfigCorrupted`: an empty or truncated config file, e.g. after a
+///   crash while it was written, moved aside to `backup`,
+/// - `Config`: any other invalid configuration, e.g. a reserved extra
+///   parameter, or a config file that cannot be read or saved,
+/// - `Http`: an unsuccessful status that another provider would answer the
+///   same way, e.g. `401` for an invalid API key; `message` is read from the
+///   error `body`, e.g. `WeatherAPI error 1006: No matching location found`,
+///   or names the status when the body has none,
+/// - `CityNotFound`: the provider does not know the location, e.g. a
+///   misspelt city; `suggestions` are close matches found by
+///   [`suggest_locations`](crate::providers::suggest_locations),
+/// - `RateLimited`: `429 Too Many Requests`, asking to wait `retry_after`
+///   when the provider sent a `Retry-After` header,
+/// - `Network`: no answer (`unreachable`, e.g. without a network
+///   connection or after a timeout), or a server error (`5xx`),
+/// - `UnsupportedDataKind`: a data kind the provider does not serve,
+/// - `NonJson`: a successful status with a body that is not JSON, e.g. the
+///   login page of a captive portal or an empty body; `excerpt` is the
+///   start of the body, see [`check_json`](crate::providers::check_json),
+/// - `QuotaExhausted`: the provider made its `limit` of calls for the day
+///   in the `quota` section of the config, until local midnight (`renews`),
+///   see [`Quota`](crate::quota::Quota).
+///
+/// Unlike the others, `RateLimited` and `Network` may pass with time or
+/// another provider, see [`is_unavailable`](crate::providers::is_unavailable).
+#[derive(Debug, thiserror::Error)]
+pub enum WappError {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("{0} (or {0}_FILE) is not set")]
+    MissingEnvVar(String),
+
+    #[error(
+        "provider '{name}' is not supported{} Supported providers: {}",
+        suggestion.map_or(".".into(), |kind| format!("; did you mean '{}'?", kind)),
+        supported_names().join(", ")
+    )]
+    UnsupportedProvider {
+        name: String,
+        suggestion: Option<ProviderKind>,
+    },
+
+    #[error("{} not found. Run: wapp configure <provider>", .0.display())]
+    ConfigNotFound(PathBuf),
+
+    #[error("invalid config {}: {message}", path.display())]
+    ConfigParse { path: PathBuf, message: String },
+
+    #[error(
+        "config {} appears corrupted, re-run configure (backup saved to {})",
+        path.display(),
+        backup.display()
+    )]
+    ConfigCorrupted { path: PathBuf, backup: PathBuf },
+
+    #[error(transparent)]
+    Config(anyhow::Error),
+
+    #[error("{message}")]
+    Http {
+        provider: String,
+        status: u16,
+        message: String,
+        body: String,
+    },
+
+    #[error("{}", not_found_message(location, suggestions))]
+    CityNotFound {
+        provider: String,
+        location: Location,
+        suggestions: Vec<String>,
+    },
+
+    #[error("rate limited by {provider}{}", wait_message(*retry_after))]
+    RateLimited {
+        provider: String,
+        retry_after: Option<Duration>,
+    },
+
+    #[error("{reason}")]
+    Network {
+        provider: String,
+        reason: String,
+        unreachable: bool,
+    },
+
+    #[error("{}", unsupported_message(*kind, provider))]
+    UnsupportedDataKind { kind: DataKind, provider: String },
+
+    #[error("{}", non_json_message(provider, *status, content_type.as_deref(), excerpt))]
+    NonJson {
+        provider: String,
+        status: u16,
+        content_type: Option<String>,
+        excerpt: String,
+    },
+
+    #[error("daily quota for {provider} exhausted (renews at {})", renews.format("%Y-%m-%d %H:%M"))]
+    QuotaExhausted {
+        provider: String,
+        limit: u32,
+        renews: chrono::NaiveDateTime,
+    },
+}
+
+impl WappError {
+    /// The first `WappError` of the chain of `error`, if any.
+    pub fn of(error: &anyhow::Error) -> Option<&WappError> {
+        error.chain().find_map(|cause| cause.downcast_ref())
+    }
+
+    /// The name of the variant in snake case, e.g. `city_not_found`; the
+    /// `kind` of an [`ErrorReport`], so it never changes.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WappError::Usage(_) => "usage",
+            WappError::MissingEnvVar(_) => "missing_env_var",
+            WappError::UnsupportedProvider { .. } => "unsupported_provider",
+            WappError::ConfigNotFound(_) => "config_not_found",
+            WappError::ConfigParse { .. } => "config_parse",
+            WappError::ConfigCorrupted { .. } => "config_corrupted",
+            WappError::Config(_) => "config",
+            WappError::Http { .. } => "http",
+            WappError::CityNotFound { .. } => "city_not_found",
+            WappError::RateLimited { .. } => "rate_limited",
+            WappError::Network { .. } => "network",
+            WappError::UnsupportedDataKind { .. } => "unsupported_data_kind",
+            WappError::NonJson { .. } => "non_json",
+            WappError::QuotaExhausted { .. } => "quota_exhausted",
+        }
+    }
+
+    /// The provider that failed, for the variants that name one.
+    pub fn provider(&self) -> Option<&str> {
+        match self {
+            WappError::Http { provider, .. }
+            | WappError::CityNotFound { provider, .. }
+            | WappError::RateLimited { provider, .. }
+            | WappError::Network { provider, .. }
+            | WappError::UnsupportedDataKind { provider, .. }
+            | WappError::NonJson { provider, .. }
+            | WappError::QuotaExhausted { provider, .. } => Some(provider),
+            _ => None,
+        }
+    }
+
+    /// Whether the same command may succeed later unchanged: `RateLimited`,
+    /// `Network`, and `QuotaExhausted`.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            WappError::RateLimited { .. }
+                | WappError::Network { .. }
+                | WappError::QuotaExhausted { .. }
+        )
+    }
+}
+
+/// How `main` prints the error a command failed with, see `--errors`.
+///
+/// - `text`: `Error: ` and the message with its causes,
+/// - `json`: one [`ErrorReport`] object on one line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// A failure as printed by `--errors json`, for scripts:
+///
+/// ```json
+/// {"error":{"kind":"city_not_found","provider":"weatherapi","message":"City 'Lodnon' not found","retryable":false}}
+/// ```
+///
+/// # Fields
+/// - `kind`: the [`WappError::kind`] of the error; for other errors
+///   `no_cached_data` (`get --offline`), `all_providers_failed` (a
+///   [`RaceFailed`]), `key_rejected` (a [`KeyRejected`]), `rule` (an
+///   `exit-code` rule fired), `internal` (a panic), or `other`,
+/// - `provider`: the provider that failed, `null` when none did,
+/// - `message`: the message with its causes, without API keys,
+/// - `retryable`: whether the same command may succeed later, see
+///   [`WappError::is_retryable`]; for a race, whether any provider's error
+///   is.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ErrorReport {
+    pub kind: &'static str,
+    pub provider: Option<String>,
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl ErrorReport {
+    /// The report of `error`.
+    pub fn of(error: &anyhow::Error) -> Self {
+        let message = redact_secrets(&format!("{:#}", error));
+        let report = |kind, provider: Option<&str>, retryable| Self {
+            kind,
+            provider: provider.map(String::from),
+            message: message.clone(),
+            retryable,
+        };
+
+        for cause in error.chain() {
+            if let Some(race) = cause.downcast_ref::<RaceFailed>() {
+                let retryable = race.errors.iter().any(|(_, e)| Self::of(e).retryable);
+                return report("all_providers_failed", None, retryable);
+            }
+            if cause.is::<NoCachedData>() {
+                return report("no_cached_data", None, false);
+            }
+            if let Some(rejected) = cause.downcast_ref::<KeyRejected>() {
+                return report("key_rejected", Some(&rejected.provider), false);
+            }
+            if cause.is::<RuleExit>() {
+                return report("rule", None, false);
+            }
+            if cause
+                .downcast_ref::<tokio::task::JoinError>()
+                .is_some_and(|e| e.is_panic())
+            {
+                return report("internal", None, false);
+            }
+        }
+
+        match WappError::of(error) {
+            Some(e) => report(e.kind(), e.provider(), e.is_retryable()),
+            None => report("other", None, false),
+        }
+    }
+
+    /// The report of a panic with `message`.
+    pub fn internal(message: &str) -> Self {
+        Self {
+            kind: "internal",
+            provider: None,
+            message: redact_secrets(message),
+            retryable: false,
+        }
+    }
+
+    /// The report as one line of JSON, under an `error` key, with the
+    /// fields in their order.
+    pub fn to_json(&self) -> String {
+        #[derive(serde::Serialize)]
+        struct Wrapped<'a> {
+            error: &'a ErrorReport,
+        }
+
+        serde_json::to_string(&Wrapped { error: self }).unwrap_or_default()
+    }
+}
+
+/// A [`WappError::Usage`] with `message`.
+pub fn usage(message: impl std::fmt::Display) -> anyhow::Error {
+    WappError::Usage(message.to_string()).into()
+}
+
+/// `error` as a configuration error: as it is if it already is a
+/// [`WappError`] of the configuration, a [`WappError::Config`] otherwise.
+pub fn config_error(error: anyhow::Error) -> anyhow::Error {
+    match error.downcast_ref() {
+        Some(
+            WappError::Config(_)
+            | WappError::ConfigNotFound(_)
+            | WappError::ConfigParse { .. }
+            | WappError::ConfigCorrupted { .. }
+            | WappError::MissingEnvVar(_)
+            | WappError::UnsupportedProvider { .. },
+        ) => error,
+        _ => WappError::Config(error).into(),
+    }
+}
+
+/// The names of the supported providers, built-in and registered.
+fn supported_names() -> Vec<&'static str> {
+    crate::providers::registry::supported_providers()
+        .into_iter()
+        .map(ProviderKind::name)
+        .collect()
+}
+
+/// "City 'Lodnon' not found", with "; did you mean: London?" for
+/// `suggestions`.
+fn not_found_message(location: &Location, suggestions: &[String]) -> String {
+    let kind = location.kind();
+    let mut chars = kind.chars();
+    let kind: String = chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default();
+
+    let mut message = format!("{} '{}' not found", kind, location.label());
+    if !suggestions.is_empty() {
+        message.push_str(&format!("; did you mean: {}?", suggestions.join(", ")));
+    }
+    message
+}
+
+/// ", retry after 30s" for a `Retry-After` wait, rounded up so that waiting
+/// the printed time is enough.
+fn wait_message(retry_after: Option<Duration>) -> String {
+    match retry_after {
+        Some(wait) => {
+            let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            format!(", retry after {}s", secs)
+        }
+        None => String::new(),
+    }
+}
+
+/// "data kind 'hourly' is not supported by provider 'wttr'", or for a date
+/// "history is not supported by provider 'wttr'".
+fn unsupported_message(kind: DataKind, provider: &str) -> String {
+    match kind {
+        DataKind::History(_) => format!("history is not supported by provider '{}'", provider),
+        kind => format!(
+            "data kind '{}' is not supported by provider '{}'",
+            kind, provider
+        ),
+    }
+}
+
+/// "'weatherapi' returned a non-JSON response (status 200, text/html); are
+/// you behind a captive portal?", followed by the start of the body, or
+/// for an empty body "'weatherapi' returned an empty response (status
+/// 200); …".
+fn non_json_message(
+    provider: &str,
+    status: u16,
+    content_type: Option<&str>,
+    excerpt: &str,
+) -> String {
+    const HINT: &str = "are you behind a captive portal?";
+    if excerpt.is_empty() {
+        return format!(
+            "'{}' returned an empty response (status {}); {}",
+            provider, status, HINT
+        );
+    }
+    format!(
+        "'{}' returned a non-JSON response (status {}, {}); {} The response begins: {}",
+        provider,
+        status,
+        content_type.unwrap_or("no content type"),
+        HINT,
+        excerpt
+    )
+}
@@ -0,0 +1,61 @@
+use crate::cache::NoCachedData;
+use crate::error::WappError;
+use crate::providers::{NOT_FOUND_EXIT_CODE, RATE_LIMITED_EXIT_CODE};
+use crate::request::RaceFailed;
+
+/// Exit code of a command used wrongly, e.g. `get` without a location.
+/// Clap exits with it too when it rejects the arguments.
+pub const USAGE_EXIT_CODE: i32 = 2;
+
+/// Exit code of a missing or invalid configuration, e.g. no API key, or a
+/// config file that cannot be read or saved.
+pub const CONFIG_EXIT_CODE: i32 = 3;
+
+/// Exit code of a failed request: no answer, or an error from the provider.
+pub const NETWORK_EXIT_CODE: i32 = 4;
+
+/// The exit code of `error` by its [`WappError`]:
+/// - [`USAGE_EXIT_CODE`] for `Usage` and `UnsupportedDataKind`,
+/// - [`CONFIG_EXIT_CODE`] for `MissingEnvVar`, `UnsupportedProvider`,
+///   `ConfigNotFound`, `ConfigParse`, `ConfigCorrupted` and `Config`,
+/// - [`NETWORK_EXIT_CODE`] for `Http`, `Network`, and `NonJson`, and for a
+///   [`NoCachedData`],
+/// - [`NOT_FOUND_EXIT_CODE`] for `CityNotFound`,
+/// - [`RATE_LIMITED_EXIT_CODE`] for `RateLimited` and `QuotaExhausted`.
+///
+/// A [`RaceFailed`] has the code of its errors when they agree, e.g.
+/// every provider rate limited, else [`NETWORK_EXIT_CODE`].
+///
+/// `None` for any other error.
+pub fn exit_code(error: &anyhow::Error) -> Option<i32> {
+    if error.chain().any(|cause| cause.is::<NoCachedData>()) {
+        return Some(NETWORK_EXIT_CODE);
+    }
+    if let Some(race) = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<RaceFailed>())
+    {
+        let mut codes = race.errors.iter().map(|(_, e)| exit_code(e));
+        let first = codes.next().flatten();
+        return if codes.all(|code| code == first) {
+            first
+        } else {
+            Some(NETWORK_EXIT_CODE)
+        };
+    }
+
+    Some(match WappError::of(error)? {
+        WappError::Usage(_) | WappError::UnsupportedDataKind { .. } => USAGE_EXIT_CODE,
+        WappError::MissingEnvVar(_)
+        | WappError::UnsupportedProvider { .. }
+        | WappError::ConfigNotFound(_)
+        | WappError::ConfigParse { .. }
+        | WappError::ConfigCorrupted { .. }
+        | WappError::Config(_) => CONFIG_EXIT_CODE,
+        WappError::Http { .. } | WappError::Network { .. } | WappError::NonJson { .. } => {
+            NETWORK_EXIT_CODE
+        }
+        WappError::CityNotFound { .. } => NOT_FOUND_EXIT_CODE,
+        WappError::RateLimited { .. } | WappError::QuotaExhausted { .. } => RATE_LIMITED_EXIT_CODE,
+    })
+}
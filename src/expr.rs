@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Result};
+
+/// A parsed boolean expression over numeric fields, e.g.
+/// `temp_c < 0 && wind_kph >= 40`.
+///
+/// # Grammar
+/// ```text
+/// expr    := and (("||" | "or") and)*
+/// and     := unary (("&&" | "and") unary)*
+/// unary   := ("!" | "not") unary | "(" expr ")" | compare
+/// compare := operand ("<" | "<=" | ">" | ">=" | "==" | "!=") operand
+/// operand := number | field
+/// ```
+///
+/// Fields are names or dotted paths (`current.uv`); their values are
+/// supplied by the caller at evaluation time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+}
+
+/// One side of a comparison.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Number(f64),
+    Field(String),
+}
+
+/// Comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Expr {
+    /// Parses an expression.
+    ///
+    /// # Errors
+    /// Returns an error describing the first unexpected token.
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(anyhow!("unexpected '{}' in expression", token)),
+        }
+    }
+
+    /// Evaluates the expression, resolving fields through `lookup`.
+    ///
+    /// # Errors
+    /// Returns an error if a field has no value.
+    pub fn eval(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> Result<bool> {
+        Ok(match self {
+            Expr::Or(a, b) => a.eval(lookup)? || b.eval(lookup)?,
+            Expr::And(a, b) => a.eval(lookup)? && b.eval(lookup)?,
+            Expr::Not(a) => !a.eval(lookup)?,
+            Expr::Compare(left, op, right) => {
+                let (left, right) = (left.value(lookup)?, right.value(lookup)?);
+                match op {
+                    CompareOp::Lt => left < right,
+                    CompareOp::Le => left <= right,
+                    CompareOp::Gt => left > right,
+                    CompareOp::Ge => left >= right,
+                    CompareOp::Eq => left == right,
+                    CompareOp::Ne => left != right,
+                }
+            }
+        })
+    }
+}
+
+impl Operand {
+    fn value(&self, lookup: &dyn Fn(&str) -> Option<f64>) -> Result<f64> {
+        match self {
+            Operand::Number(n) => Ok(*n),
+            Operand::Field(name) => {
+                lookup(name).ok_or_else(|| anyhow!("field '{}' is not available", name))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Op(op) => write!(
+                f,
+                "{}",
+                match op {
+                    CompareOp::Lt => "<",
+                    CompareOp::Le => "<=",
+                    CompareOp::Gt => ">",
+                    CompareOp::Ge => ">=",
+                    CompareOp::Eq => "==",
+                    CompareOp::Ne => "!=",
+                }
+            ),
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('<', Some('=')) => (Token::Op(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Op(CompareOp::Ge), 2),
+            ('=', Some('=')) => (Token::Op(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Op(CompareOp::Ne), 2),
+            ('<', _) => (Token::Op(CompareOp::Lt), 1),
+            ('>', _) => (Token::Op(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            (c, _) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let len = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit() || **c == '.')
+                    .count()
+                    + 1;
+                let text: String = chars[i..i + len].iter().collect();
+                let n = text
+                    .parse()
+                    .map_err(|_| anyhow!("invalid number '{}' in expression", text))?;
+                (Token::Number(n), len)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_' || **c == '.')
+                    .count();
+                let word: String = chars[i..i + len].iter().collect();
+                let token = match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word),
+                };
+                (token, len)
+            }
+            (c, _) => return Err(anyhow!("unexpected '{}' in expression", c)),
+        };
+
+        tokens.push(token);
+        i += len;
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(anyhow!("missing ')' in expression")),
+                }
+            }
+            _ => self.compare(),
+        }
+    }
+
+    fn compare(&mut self) -> Result<Expr> {
+        let left = self.operand()?;
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(token) => return Err(anyhow!("expected a comparison, found '{}'", token)),
+            None => return Err(anyhow!("expected a comparison at end of expression")),
+        };
+        let right = self.operand()?;
+
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Operand::Number(n)),
+            Some(Token::Ident(name)) => Ok(Operand::Field(name)),
+            Some(token) => Err(anyhow!("expected a field or number, found '{}'", token)),
+            None => Err(anyhow!("unexpected end of expression")),
+        }
+    }
+}
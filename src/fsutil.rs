@@ -0,0 +1,95 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Writes `contents` to `path` atomically.
+///
+/// The data is written to a temporary file in the same directory, flushed to
+/// disk, and then renamed over the target. Readers therefore see either the
+/// previous file or the complete new one, never a partial write. If anything
+/// fails, the previous file is left untouched. On Unix the new file is only
+/// readable by its owner (mode `0600`), as the config holds API keys.
+///
+/// # Errors
+/// Returns an error if the temporary file cannot be created, written, synced,
+/// or renamed.
+///
+/// # Example
+/// ```ignore
+/// write_atomic(Path::new("/run/wapp/current.json"), body.as_bytes())?;
+/// ```
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = temp_path(path);
+
+    let result = (|| {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options.open(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// Returns the temporary sibling used by [`write_atomic`],
+/// e.g. `current.json` → `.current.json.tmp.<pid>.<n>`, numbered so that
+/// threads writing the same file do not share one.
+fn temp_path(path: &Path) -> PathBuf {
+    static WRITES: AtomicU64 = AtomicU64::new(0);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(
+        ".{}.tmp.{}.{}",
+        name,
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Takes an advisory lock on the sibling `<file>.lock` of `path`, e.g.
+/// `config.json.lock`, waiting while another process or thread holds it.
+/// The lock is released when the returned file is dropped.
+///
+/// The lock lives on a sibling rather than on `path` itself, as
+/// [`write_atomic`] replaces that file. The directory of `path` is created
+/// if needed.
+///
+/// # Errors
+/// Returns an error if the directory or the lock file cannot be created,
+/// or the file cannot be locked.
+pub fn lock_sibling(path: &Path) -> anyhow::Result<fs::File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        crate::paths::ensure_dir(dir)?;
+    }
+
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    let lock_path = path.with_file_name(name);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| anyhow::anyhow!("cannot create {}: {}", lock_path.display(), e))?;
+    file.lock()
+        .map_err(|e| anyhow::anyhow!("cannot lock {}: {}", lock_path.display(), e))?;
+
+    Ok(file)
+}
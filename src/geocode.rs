@@ -0,0 +1,279 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{Cache, CacheEntry, Namespace};
+use crate::error::WappError;
+use crate::location::{normalize_city, Location, LocationInput, LocationMatch};
+use crate::providers::{openmeteo, openweather};
+
+/// Places kept in memory for the life of the process.
+pub const MEMORY_CAPACITY: usize = 256;
+
+/// How long a looked-up place is kept on disk, in seconds: 90 days, as
+/// cities do not move.
+pub const DISK_TTL_SECS: u64 = 90 * 24 * 60 * 60;
+
+/// Matches asked of the geocoder, so that an exact name can be preferred,
+/// see [`best_match`].
+pub const CANDIDATES: u32 = 5;
+
+/// A place with its coordinates, as resolved by a [`Geocoder`].
+///
+/// # Fields
+/// - `name`: the place's name, as the geocoder spells it,
+/// - `latitude`, `longitude`: its coordinates in decimal degrees.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coords {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Where cities are looked up.
+///
+/// - `OpenMeteo`: Open-Meteo's geocoding API below `base_url`, keyless,
+///   with place names in `lang`; used by Open-Meteo and MET Norway,
+/// - `OpenWeather`: OpenWeatherMap's `/geo/1.0/direct` on `host`, for One
+///   Call and air quality.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    OpenMeteo {
+        base_url: String,
+        lang: Option<String>,
+    },
+    OpenWeather {
+        host: String,
+        api_key: String,
+    },
+}
+
+impl Source {
+    /// The URL looking `city` up, for up to [`CANDIDATES`] matches.
+    pub fn search_url(&self, city: &LocationInput) -> String {
+        match self {
+            Source::OpenMeteo { base_url, lang } => {
+                openmeteo::search_url(base_url, city, lang.as_deref())
+            }
+            Source::OpenWeather { host, api_key } => openweather::direct_url(host, api_key, city),
+        }
+    }
+
+    /// The part of cache keys naming the source; never the API key.
+    fn key(&self) -> String {
+        match self {
+            Source::OpenMeteo { base_url, lang } => {
+                format!("openmeteo:{}:{}", base_url, lang.as_deref().unwrap_or(""))
+            }
+            Source::OpenWeather { host, .. } => format!("openweather:{}", host),
+        }
+    }
+}
+
+/// Resolves locations to coordinates for `provider` through `source`.
+///
+/// Every city is looked up once per process: places are kept in memory,
+/// the [`MEMORY_CAPACITY`] most recently used, and with a cache set by
+/// [`use_disk_cache`], on disk for [`DISK_TTL_SECS`] in the `geocode`
+/// namespace, so `cache clear --geocode` forgets them.
+#[derive(Debug, Clone)]
+pub struct Geocoder {
+    pub provider: String,
+    pub source: Source,
+}
+
+impl Geocoder {
+    /// A geocoder for `provider`, named in its requests and errors.
+    pub fn new(provider: &str, source: Source) -> Self {
+        Self {
+            provider: provider.to_string(),
+            source,
+        }
+    }
+
+    /// The coordinates of `location`: a city's from the memory or disk
+    /// cache, else looked up; coordinates as given, without a request.
+    ///
+    /// # Errors
+    /// Returns an error if the lookup fails, the city is not found, or the
+    /// location is a postal or airport code.
+    pub async fn resolve(&self, location: &Location) -> Result<Coords> {
+        let city = match location {
+            Location::City(city) => city,
+            Location::Coords { lat, lon } => {
+                return Ok(Coords {
+                    name: location.label(),
+                    latitude: *lat,
+                    longitude: *lon,
+                })
+            }
+            Location::Zip { .. } | Location::Iata(_) => {
+                return Err(location.unsupported(&self.provider))
+            }
+        };
+
+        let key = format!("{}:{}", self.source.key(), city.raw.to_lowercase());
+        let remembered = memory().get(&key);
+        if let Some(coords) = remembered {
+            return Ok(coords);
+        }
+        if let Some(coords) = self.stored(&key) {
+            memory().insert(key, coords.clone());
+            return Ok(coords);
+        }
+
+        let coords = self.look_up(location, city).await?;
+        memory().insert(key.clone(), coords.clone());
+        self.store(&key, &coords);
+        Ok(coords)
+    }
+
+    /// Asks the source for `city`.
+    async fn look_up(&self, location: &Location, city: &LocationInput) -> Result<Coords> {
+        let url = self.source.search_url(city);
+        match &self.source {
+            Source::OpenMeteo { .. } => {
+                let body = crate::providers::fetch(&self.provider, &url).await?;
+                openmeteo::OpenMeteoProvider::parse_place(city, &body)
+            }
+            Source::OpenWeather { .. } => {
+                let body = crate::providers::fetch_checked(
+                    &self.provider,
+                    &url,
+                    openweather::error_message,
+                )
+                .await?;
+                openweather::parse_direct(&body)
+                    .and_then(|matches| best_match(&city.city, matches))
+                    .map(|place| Coords {
+                        name: place.name,
+                        latitude: place.lat,
+                        longitude: place.lon,
+                    })
+                    .ok_or_else(|| {
+                        WappError::CityNotFound {
+                            provider: self.provider.clone(),
+                            location: location.clone(),
+                            suggestions: Vec::new(),
+                        }
+                        .into()
+                    })
+            }
+        }
+    }
+
+    /// The place stored on disk for `key`, if unexpired.
+    fn stored(&self, key: &str) -> Option<Coords> {
+        let entry = DISK.get()?.get(Namespace::Geocode, key)?;
+        if entry.is_expired(crate::cache::now_secs()) {
+            return None;
+        }
+        serde_json::from_str(&entry.body).ok()
+    }
+
+    /// Stores `coords` on disk for `key`; a failure is logged.
+    fn store(&self, key: &str, coords: &Coords) {
+        let Some(cache) = DISK.get() else {
+            return;
+        };
+        let now = crate::cache::now_secs();
+        let entry = CacheEntry {
+            namespace: Namespace::Geocode,
+            key: key.to_string(),
+            provider: self.provider.clone(),
+            city: coords.name.clone(),
+            created: now,
+            expires: Some(now + DISK_TTL_SECS),
+            body: serde_json::to_string(coords).unwrap_or_default(),
+            validators: Default::default(),
+        };
+        if let Err(e) = cache.put(&entry) {
+            tracing::info!(error = %e, "place not cached");
+        }
+    }
+}
+
+/// The match named exactly `city`, ignoring case, else the first one;
+/// `None` without matches.
+///
+/// A geocoder ranks big places first, so "Paris" finds the French
+/// capital, but a search for "Mexico" can rank "Mexico City" above the
+/// town named Mexico.
+pub fn best_match(city: &str, matches: Vec<LocationMatch>) -> Option<LocationMatch> {
+    let exact = matches
+        .iter()
+        .position(|place| is_named(&place.name, city))
+        .unwrap_or(0);
+    matches.into_iter().nth(exact)
+}
+
+/// Returns `true` if a place called `name` is the `city` asked for,
+/// ignoring case and spacing, see [`normalize_city`].
+pub fn is_named(name: &str, city: &str) -> bool {
+    normalize_city(name).to_lowercase() == normalize_city(city).to_lowercase()
+}
+
+/// The most recently used places, up to a capacity, each under a key.
+#[derive(Debug)]
+pub struct Lru {
+    capacity: usize,
+    /// Most recently used first.
+    entries: VecDeque<(String, Coords)>,
+}
+
+impl Lru {
+    /// An empty cache of `capacity` places.
+    pub const fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// The place under `key`, which becomes the most recently used.
+    pub fn get(&mut self, key: &str) -> Option<Coords> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        let coords = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(coords)
+    }
+
+    /// Keeps `coords` under `key`, forgetting the least recently used
+    /// place when full.
+    pub fn insert(&mut self, key: String, coords: Coords) {
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push_front((key, coords));
+        self.entries.truncate(self.capacity);
+    }
+
+    /// Number of places kept.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no place is kept.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Places resolved by this process.
+static MEMORY: Mutex<Lru> = Mutex::new(Lru::new(MEMORY_CAPACITY));
+
+/// The memory cache, also after a panic while it was held.
+fn memory() -> std::sync::MutexGuard<'static, Lru> {
+    MEMORY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Disk cache set by [`use_disk_cache`].
+static DISK: OnceLock<Cache> = OnceLock::new();
+
+/// Makes every later [`Geocoder::resolve`] of this process keep places in
+/// `cache` too; the first call wins. Without a call, places are only kept
+/// in memory.
+pub fn use_disk_cache(cache: Cache) {
+    let _ = DISK.set(cache);
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::env;
+
+use crate::cache::{Cache, CacheEntry, Namespace};
+use crate::location::Location;
+
+/// Base URL of the IP geolocation service, overridden by `WAPP_GEOLOCATE_URL`.
+pub const DEFAULT_BASE_URL: &str = "https://ipinfo.io";
+
+/// How long a looked-up position is reused, in seconds.
+pub const CACHE_TTL_SECS: u64 = 3 * 60 * 60;
+
+/// Name used for the lookup in logs, metrics, and cache entries.
+const SERVICE: &str = "geolocate";
+
+/// Finds the approximate position of this machine from its public IP
+/// address, for `get` without a location.
+///
+/// The lookup needs no API key. Its result is kept in the `geocode` cache
+/// namespace for [`CACHE_TTL_SECS`], so `cache clear --geocode` forgets it.
+pub struct Geolocator {
+    pub base_url: String,
+}
+
+impl Geolocator {
+    /// Creates a geolocator for [`DEFAULT_BASE_URL`], or `WAPP_GEOLOCATE_URL`
+    /// when it is set.
+    pub fn from_env() -> Self {
+        Self {
+            base_url: env::var("WAPP_GEOLOCATE_URL").unwrap_or(DEFAULT_BASE_URL.into()),
+        }
+    }
+
+    /// URL of the lookup, e.g. `https://ipinfo.io/json`.
+    pub fn url(&self) -> String {
+        format!("{}/json", self.base_url.trim_end_matches('/'))
+    }
+
+    /// Reads the coordinates of a lookup response, e.g. `{"loc":"50.45,30.52"}`.
+    ///
+    /// # Errors
+    /// Returns an error if the body is not JSON or holds no valid `loc`, as
+    /// for private addresses.
+    pub fn parse(body: &str) -> Result<Location> {
+        let json: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| anyhow::anyhow!("invalid geolocation response: {}", e))?;
+
+        let (lat, lon) = json["loc"]
+            .as_str()
+            .and_then(|loc| loc.split_once(','))
+            .and_then(|(lat, lon)| Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?)))
+            .ok_or_else(|| anyhow::anyhow!("the geolocation response has no position"))?;
+
+        Location::coords(lat, lon)
+    }
+
+    /// Returns the approximate position, from `cache` when a lookup newer
+    /// than [`CACHE_TTL_SECS`] is stored there and from the service otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the response has no position.
+    pub async fn locate(&self, cache: &Cache, now: u64) -> Result<Location> {
+        let key = format!("{}:{}", SERVICE, self.base_url);
+
+        if let Some(entry) = cache.get(Namespace::Geocode, &key) {
+            if !entry.is_expired(now) {
+                if let Ok(location) = Self::parse(&entry.body) {
+                    return Ok(location);
+                }
+            }
+        }
+
+        let body = crate::providers::fetch(SERVICE, &self.url()).await?;
+        let location = Self::parse(&body)?;
+
+        let entry = CacheEntry {
+            namespace: Namespace::Geocode,
+            key,
+            provider: SERVICE.to_string(),
+            city: location.label(),
+            created: now,
+            expires: Some(now + CACHE_TTL_SECS),
+            body,
+            validators: Default::default(),
+        };
+        if let Err(e) = cache.put(&entry) {
+            tracing::info!(error = %e, "geolocation not cached");
+        }
+
+        Ok(location)
+    }
+}
@@ -0,0 +1,410 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::paths::ensure_dir;
+use crate::providers::ApiProvider;
+use crate::series::{header, render_line, ExportFormat, SeriesPoint, EXPORT_COLUMNS};
+use crate::weather::WeatherData;
+
+/// File in the data directory holding the history, one JSON record per line.
+pub const HISTORY_FILE: &str = "history.ndjson";
+
+/// Which records `wapp history export` writes.
+///
+/// # Fields
+/// - `city`: only records for this city (compared case-insensitively),
+/// - `since`, `until`: inclusive range of UTC dates of the record time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryFilter {
+    pub city: Option<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+}
+
+impl HistoryFilter {
+    /// Returns `true` if `point` passes every set condition.
+    pub fn matches(&self, point: &SeriesPoint) -> bool {
+        let date = point.time.date_naive();
+
+        self.city
+            .as_ref()
+            .is_none_or(|city| point.city.eq_ignore_ascii_case(city))
+            && self.since.is_none_or(|since| date >= since)
+            && self.until.is_none_or(|until| date <= until)
+    }
+}
+
+/// One recorded query: the observation, plus the provider that answered and
+/// the data kind asked for.
+///
+/// Observations appended without a query, e.g. by older versions, read with
+/// `provider` and `kind` left empty.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    #[serde(flatten)]
+    pub point: SeriesPoint,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+impl HistoryRecord {
+    /// The record of a query for `kind` at `city`, answered by `provider`
+    /// with `data` at `time`; the current values of `data` are kept.
+    pub fn from_weather(
+        provider: &str,
+        city: &str,
+        kind: &str,
+        data: &WeatherData,
+        time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            point: SeriesPoint {
+                city: city.to_string(),
+                temp_c: data.temp_c,
+                feels_like_c: data.feels_like_c,
+                humidity: data.humidity,
+                pressure_mb: data.pressure_mb,
+                wind_kph: data.wind_kph,
+                condition: data.condition.clone(),
+                ..SeriesPoint::at(time)
+            },
+            provider: Some(provider.to_string()),
+            kind: Some(kind.to_string()),
+        }
+    }
+}
+
+/// Start of the records `wapp history --since` lists.
+///
+/// - `Date`: a UTC date such as `2025-01-01`, from its midnight on,
+/// - `Ago`: an age such as `7d` or `12h`, in seconds before now, see
+///   [`parse_age`](crate::cache::parse_age).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Since {
+    Date(NaiveDate),
+    Ago(u64),
+}
+
+impl Since {
+    /// The earliest record time included when listing at `now`.
+    pub fn cutoff(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Date(date) => date.and_time(chrono::NaiveTime::MIN).and_utc(),
+            Self::Ago(secs) => i64::try_from(secs)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|age| now.checked_sub_signed(age))
+                .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        }
+    }
+}
+
+impl FromStr for Since {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(date) = s.trim().parse() {
+            return Ok(Self::Date(date));
+        }
+        crate::cache::parse_age(s).map(Self::Ago).map_err(|_| {
+            anyhow::anyhow!(
+                "invalid start '{}': expected an age such as 7d or 12h, or a date such as 2025-01-01",
+                s
+            )
+        })
+    }
+}
+
+/// Which records `wapp history` lists.
+///
+/// # Fields
+/// - `city`: only records for this city (compared case-insensitively),
+/// - `since`: only records from this start on,
+/// - `limit`: only the latest this many of the matching records.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistoryQuery {
+    pub city: Option<String>,
+    pub since: Option<Since>,
+    pub limit: Option<usize>,
+}
+
+impl HistoryQuery {
+    /// Returns `true` if `record` passes the `city` and `since` conditions
+    /// at `now`.
+    pub fn matches(&self, record: &HistoryRecord, now: DateTime<Utc>) -> bool {
+        let point = &record.point;
+
+        self.city
+            .as_ref()
+            .is_none_or(|city| point.city.eq_ignore_ascii_case(city))
+            && self
+                .since
+                .is_none_or(|since| point.time >= since.cutoff(now))
+    }
+}
+
+/// Format of `wapp history`.
+///
+/// - `table`: aligned columns, one row per record, see [`render_table`],
+/// - `json`: an array of the records,
+/// - `csv`: the `wapp export` columns followed by `provider` and `kind`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum HistoryFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+/// Renders listed records in `format`.
+pub fn render(records: &[HistoryRecord], format: HistoryFormat) -> String {
+    match format {
+        HistoryFormat::Table => render_table(records),
+        // Serializing structs of strings and numbers cannot fail.
+        HistoryFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(records).unwrap_or_else(|_| "[]".into())
+        ),
+        HistoryFormat::Csv => {
+            let mut out = format!("{},provider,kind\n", EXPORT_COLUMNS.join(","));
+            for record in records {
+                let row = render_line(&record.point, ExportFormat::Csv);
+                out.push_str(&format!(
+                    "{},{},{}\n",
+                    row.trim_end(),
+                    record.provider.as_deref().unwrap_or_default(),
+                    record.kind.as_deref().unwrap_or_default()
+                ));
+            }
+            out
+        }
+    }
+}
+
+/// Renders records as aligned columns with missing values shown as `-`.
+///
+/// ```text
+/// Time                  City  Provider    Kind  Temp °C  Condition
+/// 2025-01-01T12:00:00Z  Kyiv  weatherapi  now       0.5  Clear
+/// ```
+pub fn render_table(records: &[HistoryRecord]) -> String {
+    if records.is_empty() {
+        return "No recorded queries\n".into();
+    }
+
+    let header = ["Time", "City", "Provider", "Kind", "Temp °C", "Condition"];
+    let rows: Vec<[String; 6]> = records
+        .iter()
+        .map(|record| {
+            let point = &record.point;
+            [
+                point.time.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                point.city.clone(),
+                record.provider.clone().unwrap_or("-".into()),
+                record.kind.clone().unwrap_or("-".into()),
+                point
+                    .temp_c
+                    .map(|t| format!("{:.1}", t))
+                    .unwrap_or("-".into()),
+                point.condition.clone().unwrap_or("-".into()),
+            ]
+        })
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| match i {
+                // The temperature is a number, so it is right-aligned.
+                4 => format!("{:>width$}", cell, width = width),
+                _ => format!("{:<width$}", cell, width = width),
+            })
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Appends the query of a fetched `body` to the default store.
+///
+/// Recording never fails the query: a body without weather data is not
+/// recorded, and a store that cannot be written is a warning on stderr.
+pub fn record(
+    provider: &dyn ApiProvider,
+    location: &str,
+    kind: &str,
+    body: &str,
+    now: DateTime<Utc>,
+) {
+    let Some(data) = crate::output::read(provider, kind, body) else {
+        return;
+    };
+
+    let store = HistoryStore::open_default();
+    let record = HistoryRecord::from_weather(provider.name(), location, kind, &data, now);
+    if let Err(e) = store.record(&record) {
+        eprintln!(
+            "Warning: cannot record the query in {}: {}",
+            store.path().display(),
+            e
+        );
+    }
+}
+
+/// Append-only store of weather observations.
+///
+/// Records are [`SeriesPoint`]s, so an export uses the same stable column set
+/// as `wapp export` ([`EXPORT_COLUMNS`](crate::series::EXPORT_COLUMNS)).
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Opens the store kept in `path`; the file is created on first write.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Opens the store in the resolved data directory, see [`Paths`](crate::paths::Paths).
+    pub fn open_default() -> Self {
+        Self::new(crate::paths::Paths::resolve().data.join(HISTORY_FILE))
+    }
+
+    /// File holding the records.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends records to the end of the store.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or file cannot be written.
+    pub fn append(&self, points: &[SeriesPoint]) -> io::Result<()> {
+        self.append_lines(points)
+    }
+
+    /// Appends a recorded query to the end of the store.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or file cannot be written.
+    pub fn record(&self, record: &HistoryRecord) -> io::Result<()> {
+        self.append_lines(std::slice::from_ref(record))
+    }
+
+    fn append_lines<T: Serialize>(&self, records: &[T]) -> io::Result<()> {
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            ensure_dir(dir)?;
+        }
+
+        let mut file = io::BufWriter::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?,
+        );
+        for record in records {
+            serde_json::to_writer(&mut file, record)?;
+            file.write_all(b"\n")?;
+        }
+
+        file.flush()
+    }
+
+    /// Writes the records selected by `filter` to `out`, in the order they
+    /// were recorded, and returns how many were written.
+    ///
+    /// The store is read one line at a time, so memory use does not grow
+    /// with its size. Lines that cannot be parsed are skipped. A store that
+    /// does not exist yet exports as empty (CSV still gets its header).
+    ///
+    /// # Errors
+    /// Returns an error if the store cannot be read or `out` cannot be written.
+    pub fn export(
+        &self,
+        filter: &HistoryFilter,
+        format: ExportFormat,
+        out: &mut dyn Write,
+    ) -> io::Result<usize> {
+        if let Some(header) = header(format) {
+            out.write_all(header.as_bytes())?;
+        }
+
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e),
+        };
+
+        let mut written = 0;
+        for line in BufReader::new(file).lines() {
+            let Ok(point) = serde_json::from_str::<SeriesPoint>(&line?) else {
+                continue;
+            };
+
+            if filter.matches(&point) {
+                out.write_all(render_line(&point, format).as_bytes())?;
+                written += 1;
+            }
+        }
+
+        out.flush()?;
+        Ok(written)
+    }
+
+    /// The records selected by `query` at `now`, in the order they were
+    /// recorded. With a `limit`, only the latest matching records are kept
+    /// while reading, so memory use does not grow with the store.
+    ///
+    /// Lines that cannot be parsed, e.g. one cut short by an interrupted
+    /// write, are skipped. A store that does not exist yet lists as empty.
+    ///
+    /// # Errors
+    /// Returns an error if the store cannot be read.
+    pub fn list(&self, query: &HistoryQuery, now: DateTime<Utc>) -> io::Result<Vec<HistoryRecord>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut records = VecDeque::new();
+        for line in BufReader::new(file).lines() {
+            let Ok(record) = serde_json::from_str::<HistoryRecord>(&line?) else {
+                continue;
+            };
+
+            if query.matches(&record, now) {
+                records.push_back(record);
+                if query.limit.is_some_and(|limit| records.len() > limit) {
+                    records.pop_front();
+                }
+            }
+        }
+
+        Ok(records.into())
+    }
+}
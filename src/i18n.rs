@@ -0,0 +1,263 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone};
+
+/// Locales of the text and table output, the `--locale` option.
+///
+/// A locale sets the decimal separator, the order of day and month in
+/// forecast dates, and the names of weekdays and months:
+/// - `en`: `21.5`, `Mon Jun 10`,
+/// - `uk`: `21,5`, `Пн 10 черв`,
+/// - `de`: `21,5`, `Mo 10. Jun`,
+/// - `fr`: `21,5`, `lun 10 juin`,
+/// - `es`: `21,5`, `lun 10 jun`.
+///
+/// JSON, CSV, porcelain, templates, and the status line do not depend on
+/// it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    Uk,
+    De,
+    Fr,
+    Es,
+}
+
+/// Abbreviated weekday names, Monday first, by locale.
+const WEEKDAYS: [(Locale, [&str; 7]); 5] = [
+    (
+        Locale::En,
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    ),
+    (Locale::Uk, ["Пн", "Вт", "Ср", "Чт", "Пт", "Сб", "Нд"]),
+    (Locale::De, ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"]),
+    (
+        Locale::Fr,
+        ["lun", "mar", "mer", "jeu", "ven", "sam", "dim"],
+    ),
+    (
+        Locale::Es,
+        ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    ),
+];
+
+/// Abbreviated month names, January first, by locale.
+const MONTHS: [(Locale, [&str; 12]); 5] = [
+    (
+        Locale::En,
+        [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+    ),
+    (
+        Locale::Uk,
+        [
+            "січ", "лют", "бер", "квіт", "трав", "черв", "лип", "серп", "вер", "жовт", "лист",
+            "груд",
+        ],
+    ),
+    (
+        Locale::De,
+        [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ],
+    ),
+    (
+        Locale::Fr,
+        [
+            "janv", "févr", "mars", "avr", "mai", "juin", "juil", "août", "sept", "oct", "nov",
+            "déc",
+        ],
+    ),
+    (
+        Locale::Es,
+        [
+            "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+        ],
+    ),
+];
+
+impl Locale {
+    /// The language code of the locale, as parsed.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::Uk => "uk",
+            Self::De => "de",
+            Self::Fr => "fr",
+            Self::Es => "es",
+        }
+    }
+
+    /// The character between the whole and decimal part of numbers.
+    pub fn decimal_separator(self) -> char {
+        match self {
+            Self::En => '.',
+            Self::Uk | Self::De | Self::Fr | Self::Es => ',',
+        }
+    }
+
+    /// `value` with `decimals` places and the decimal separator of the
+    /// locale, e.g. `21,5`.
+    pub fn decimal(self, value: f64, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value);
+        match self.decimal_separator() {
+            '.' => text,
+            separator => text.replace('.', &separator.to_string()),
+        }
+    }
+
+    /// The abbreviated name of a weekday, Monday being 0.
+    pub fn weekday(self, days_from_monday: u32) -> &'static str {
+        let (_, names) = WEEKDAYS
+            .iter()
+            .find(|(l, _)| *l == self)
+            .unwrap_or(&WEEKDAYS[0]);
+        names[days_from_monday as usize % 7]
+    }
+
+    /// The abbreviated name of a month, January being 1.
+    pub fn month(self, month: u32) -> &'static str {
+        let (_, names) = MONTHS
+            .iter()
+            .find(|(l, _)| *l == self)
+            .unwrap_or(&MONTHS[0]);
+        names[(month as usize + 11) % 12]
+    }
+
+    /// A forecast day with its weekday, day and month in the order of the
+    /// locale, e.g. `Mon Jun 10` or `Mo 10. Jun`.
+    pub fn date(self, date: NaiveDate) -> String {
+        let weekday = self.weekday(date.weekday().num_days_from_monday());
+        let month = self.month(date.month());
+        match self {
+            Self::En => format!("{} {} {}", weekday, month, date.day()),
+            Self::De => format!("{} {}. {}", weekday, date.day(), month),
+            Self::Uk | Self::Fr | Self::Es => format!("{} {} {}", weekday, date.day(), month),
+        }
+    }
+
+    /// `time` formatted with the strftime `pattern`, the `%a` weekday and
+    /// `%b` month names in the locale.
+    pub fn format<Tz: TimeZone>(self, time: &DateTime<Tz>, pattern: &str) -> String
+    where
+        Tz::Offset: fmt::Display,
+    {
+        time.format(&self.translate(pattern, time.date_naive()))
+            .to_string()
+    }
+
+    /// `pattern` with `%a` and `%b` replaced by the names of `date`; `%%`
+    /// stays as it is, and a `%` in a name is escaped.
+    fn translate(self, pattern: &str, date: NaiveDate) -> String {
+        let escape = |name: &str| name.replace('%', "%%");
+        let mut out = String::with_capacity(pattern.len());
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('a') => {
+                    out.push_str(&escape(self.weekday(date.weekday().num_days_from_monday())))
+                }
+                Some('b') => out.push_str(&escape(self.month(date.month()))),
+                Some(next) => {
+                    out.push('%');
+                    out.push(next);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    /// Parses a language code, a POSIX locale such as `de_DE.UTF-8`, or a
+    /// BCP-47 tag such as `fr-CA`, ignoring case; the region is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lang = s
+            .trim()
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match lang.as_str() {
+            "en" => Ok(Self::En),
+            "uk" => Ok(Self::Uk),
+            "de" => Ok(Self::De),
+            "fr" => Ok(Self::Fr),
+            "es" => Ok(Self::Es),
+            _ => Err(anyhow::anyhow!(
+                "unknown locale '{}': expected en, uk, de, fr, or es",
+                s.trim()
+            )),
+        }
+    }
+}
+
+/// The locale of a call: `explicit` (`--locale`), else the `detected`
+/// system locale (see [`detect_locale`](crate::locale::detect_locale));
+/// `None` without either, for locale-independent output.
+///
+/// A locale that is not known falls back to English, with a warning when
+/// it was given with `--locale`.
+pub fn resolve(explicit: Option<&str>, detected: Option<String>) -> Option<Locale> {
+    if let Some(tag) = explicit {
+        return Some(tag.parse().unwrap_or_else(|e| {
+            eprintln!("Warning: {}; using English", e);
+            Locale::En
+        }));
+    }
+    Some(detected?.parse().unwrap_or(Locale::En))
+}
+
+/// Locale set by [`use_locale`].
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Makes the text and table output of this process follow `locale`; the
+/// first call wins. Without a call, numbers have a decimal point, forecast
+/// dates are ISO 8601 (`2024-06-10`), and names are English.
+pub fn use_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// The locale set with [`use_locale`], if any.
+pub fn locale() -> Option<Locale> {
+    LOCALE.get().copied()
+}
+
+/// `value` with `decimals` places in the locale set with [`use_locale`],
+/// else with a decimal point.
+pub fn decimal(value: f64, decimals: usize) -> String {
+    locale().unwrap_or(Locale::En).decimal(value, decimals)
+}
+
+/// A forecast day in the locale set with [`use_locale`], else ISO 8601.
+pub fn date(date: NaiveDate) -> String {
+    match locale() {
+        Some(locale) => locale.date(date),
+        None => date.to_string(),
+    }
+}
+
+/// `time` formatted with the strftime `pattern` in the locale set with
+/// [`use_locale`], else with English names.
+pub fn format<Tz: TimeZone>(time: &DateTime<Tz>, pattern: &str) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    locale().unwrap_or(Locale::En).format(time, pattern)
+}
@@ -1,3 +1,52 @@
+pub mod advice;
+pub mod aqi;
+pub mod cache;
+pub mod check;
 pub mod cli;
+pub mod client;
+pub mod comfort;
+pub mod completions;
+pub mod condition;
 pub mod config;
+pub mod daemon;
+pub mod doctor;
+pub mod error;
+pub mod exit;
+pub mod expr;
+pub mod fsutil;
+pub mod geocode;
+pub mod geolocate;
+pub mod history;
+pub mod http;
+pub mod i18n;
+pub mod locale;
+pub mod location;
+pub mod logging;
+pub mod metrics;
+pub mod monitor;
+pub mod notify;
+pub mod output;
+pub mod pager;
+pub mod paths;
+pub mod porcelain;
+pub mod prompt;
 pub mod providers;
+pub mod quota;
+pub mod request;
+pub mod retry;
+pub mod rules;
+pub mod runtime;
+pub mod series;
+pub mod shutdown;
+pub mod timeout;
+pub mod timezone;
+pub mod transport;
+pub mod trend;
+pub mod units;
+pub mod watch;
+pub mod weather;
+pub mod webhook;
+pub mod weekend;
+pub mod wizard;
+
+pub use client::Client;
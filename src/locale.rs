@@ -0,0 +1,238 @@
+/// Locale variables in POSIX precedence order.
+const LOCALE_VARS: &[&str] = &["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// WeatherAPI `lang` codes by locale (`lang` or `lang_REGION`).
+/// English is the API default and has no code.
+const WEATHERAPI_LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "ar"),
+    ("bn", "bn"),
+    ("bg", "bg"),
+    ("zh_TW", "zh_tw"),
+    ("zh_HK", "zh_tw"),
+    ("zh", "zh"),
+    ("cs", "cs"),
+    ("da", "da"),
+    ("nl", "nl"),
+    ("fi", "fi"),
+    ("fr", "fr"),
+    ("de", "de"),
+    ("el", "el"),
+    ("hi", "hi"),
+    ("hu", "hu"),
+    ("it", "it"),
+    ("ja", "ja"),
+    ("jv", "jv"),
+    ("ko", "ko"),
+    ("mr", "mr"),
+    ("pl", "pl"),
+    ("pt", "pt"),
+    ("pa", "pa"),
+    ("ro", "ro"),
+    ("ru", "ru"),
+    ("sr", "sr"),
+    ("si", "si"),
+    ("sk", "sk"),
+    ("es", "es"),
+    ("sv", "sv"),
+    ("ta", "ta"),
+    ("te", "te"),
+    ("tr", "tr"),
+    ("uk", "uk"),
+    ("ur", "ur"),
+    ("vi", "vi"),
+    ("zu", "zu"),
+];
+
+/// OpenWeatherMap `lang` codes by locale (`lang` or `lang_REGION`).
+/// Several codes differ from ISO 639-1 (`cz`, `kr`, `al`, `la`, `ua`).
+const OPENWEATHER_LANGUAGES: &[(&str, &str)] = &[
+    ("af", "af"),
+    ("sq", "al"),
+    ("ar", "ar"),
+    ("az", "az"),
+    ("bg", "bg"),
+    ("ca", "ca"),
+    ("cs", "cz"),
+    ("da", "da"),
+    ("de", "de"),
+    ("el", "el"),
+    ("en", "en"),
+    ("eu", "eu"),
+    ("fa", "fa"),
+    ("fi", "fi"),
+    ("fr", "fr"),
+    ("gl", "gl"),
+    ("he", "he"),
+    ("hi", "hi"),
+    ("hr", "hr"),
+    ("hu", "hu"),
+    ("id", "id"),
+    ("it", "it"),
+    ("ja", "ja"),
+    ("ko", "kr"),
+    ("lv", "la"),
+    ("lt", "lt"),
+    ("mk", "mk"),
+    ("nb", "no"),
+    ("nn", "no"),
+    ("no", "no"),
+    ("nl", "nl"),
+    ("pl", "pl"),
+    ("pt_BR", "pt_br"),
+    ("pt", "pt"),
+    ("ro", "ro"),
+    ("ru", "ru"),
+    ("sv", "sv"),
+    ("sk", "sk"),
+    ("sl", "sl"),
+    ("es", "es"),
+    ("sr", "sr"),
+    ("th", "th"),
+    ("tr", "tr"),
+    ("uk", "ua"),
+    ("vi", "vi"),
+    ("zh_TW", "zh_tw"),
+    ("zh_HK", "zh_tw"),
+    ("zh", "zh_cn"),
+    ("zu", "zu"),
+];
+
+/// Provider codes that are not ISO 639-1, by the language they stand for,
+/// so that e.g. OpenWeatherMap's `ua` also selects Ukrainian on WeatherAPI.
+const CODE_ALIASES: &[(&str, &str)] = &[("al", "sq"), ("cz", "cs"), ("kr", "ko"), ("ua", "uk")];
+
+/// Reads the system locale from `LC_ALL`, `LC_MESSAGES`, or `LANG`.
+pub fn detect_locale() -> Option<String> {
+    detect_locale_with(|name| std::env::var(name).ok())
+}
+
+/// Same as [`detect_locale`], with variables looked up through `lookup`.
+///
+/// Empty values and the `C`/`POSIX` locales are skipped, since they carry no
+/// language preference.
+pub fn detect_locale_with<F>(lookup: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    LOCALE_VARS
+        .iter()
+        .filter_map(|var| lookup(var))
+        .find(|value| {
+            !value.is_empty() && value != "C" && value != "POSIX" && !value.starts_with("C.")
+        })
+}
+
+/// Maps a locale such as `uk_UA.UTF-8` or a BCP-47 tag such as `pt-BR`
+/// onto a provider's `lang` code.
+///
+/// Returns `None` when the provider has no code for the language, which
+/// leaves the provider default (English).
+pub fn provider_language(provider: &str, locale: &str) -> Option<&'static str> {
+    let table = language_table(provider)?;
+    let (lang, region) = split_locale(locale);
+    let base = match &region {
+        Some(region) => format!("{}_{}", lang, region),
+        None => lang.clone(),
+    };
+
+    [base.as_str(), lang.as_str()]
+        .iter()
+        .find_map(|key| table.iter().find(|(id, _)| id == key))
+        .map(|(_, code)| *code)
+}
+
+/// Maps a language given by the user (`--lang`, a `*_LANG` variable or the
+/// saved `lang`) onto a provider's `lang` code.
+///
+/// Accepted are the provider's own codes (`ua`, `zh_tw`), the codes of the
+/// other providers (`ua` is `uk` on WeatherAPI), and BCP-47 tags or locales
+/// (`uk-UA`, `zh-Hant`), ignoring case.
+///
+/// # Errors
+/// Returns an error naming the provider if it has no code for the language.
+/// English is never an error: it is `Ok(None)` where it is the default
+/// without a code.
+pub fn provider_lang(provider: &str, code: &str) -> anyhow::Result<Option<&'static str>> {
+    let table = match language_table(provider) {
+        Some(table) => table,
+        None => return Ok(None),
+    };
+    let native = code.trim().to_lowercase().replace('-', "_");
+    if let Some((_, found)) = table.iter().find(|(_, id)| *id == native) {
+        return Ok(Some(found));
+    }
+
+    let alias = CODE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == native)
+        .map_or(code.trim(), |(_, lang)| lang);
+    match provider_language(provider, alias) {
+        Some(found) => Ok(Some(found)),
+        None if split_locale(alias).0 == "en" => Ok(None),
+        None => Err(anyhow::anyhow!(
+            "language '{}' is not supported by provider '{}'",
+            code.trim(),
+            provider
+        )),
+    }
+}
+
+/// Chooses the response language for a provider.
+///
+/// Precedence: `explicit` (`--lang`), then `env` (the provider's `*_LANG`
+/// variable or the saved `lang`), checked with [`provider_lang`], then the
+/// locale returned by `detect`, mapped through [`provider_language`]. `None`
+/// means the provider default (English); a language the provider does not
+/// support falls back to it with a warning.
+pub fn resolve_lang<F>(
+    provider: &str,
+    explicit: Option<String>,
+    env: Option<String>,
+    detect: F,
+) -> Option<String>
+where
+    F: FnOnce() -> Option<String>,
+{
+    match explicit.or(env) {
+        Some(code) => match provider_lang(provider, &code) {
+            Ok(found) => found.map(String::from),
+            Err(e) => {
+                eprintln!("Warning: {}; using English", e);
+                None
+            }
+        },
+        None => provider_language(provider, &detect()?).map(String::from),
+    }
+}
+
+/// The table of `provider`, if it has one.
+fn language_table(provider: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    match provider {
+        "weatherapi" => Some(WEATHERAPI_LANGUAGES),
+        "openweather" => Some(OPENWEATHER_LANGUAGES),
+        _ => None,
+    }
+}
+
+/// The lowercase language and uppercase region of a locale or BCP-47 tag:
+/// `uk_UA.UTF-8@euro` → (`uk`, `UA`). A script stands for its region where
+/// the tables tell them apart: `zh-Hant` → (`zh`, `TW`).
+fn split_locale(locale: &str) -> (String, Option<String>) {
+    // Strip encoding and modifier: `uk_UA.UTF-8@euro` → `uk_UA`.
+    let base = locale.split(['.', '@']).next().unwrap_or_default().trim();
+    let mut subtags = base.split(['_', '-']);
+    let lang = subtags.next().unwrap_or_default().to_lowercase();
+
+    let (mut region, mut script_region) = (None, None);
+    for subtag in subtags {
+        match subtag.len() {
+            2 | 3 if region.is_none() => region = Some(subtag.to_uppercase()),
+            4 if lang == "zh" && subtag.eq_ignore_ascii_case("hant") => {
+                script_region = Some("TW".to_string());
+            }
+            _ => {}
+        }
+    }
+    let region = region.or(script_region);
+    (lang, region)
+}
@@ -0,0 +1,522 @@
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalizes a user-supplied city name.
+///
+/// City names often arrive with copy-paste artifacts: decomposed accents
+/// (`"Jose\u{301}"` instead of `"José"`), non-breaking spaces, zero-width
+/// characters, or doubled whitespace. Without normalization two visually
+/// identical inputs produce different request URLs.
+///
+/// The following steps are applied in order:
+/// 1. removal of control and zero-width characters,
+/// 2. Unicode NFC normalization,
+/// 3. conversion of any Unicode whitespace to a plain space,
+/// 4. trimming and collapsing of internal whitespace runs.
+///
+/// Letters from non-Latin scripts are left untouched.
+///
+/// # Example
+/// ```ignore
+/// assert_eq!(normalize_city("  San\u{a0}Jose\u{301} "), "San José");
+/// ```
+pub fn normalize_city(input: &str) -> String {
+    let cleaned: String = input
+        .chars()
+        .filter(|c| !is_invisible(*c))
+        .nfc()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns `true` for characters that carry no visible meaning in a city name.
+///
+/// Covers ASCII/Unicode control characters (except whitespace, which is
+/// collapsed separately) and the common zero-width / formatting characters.
+/// The zero-width (non-)joiners are kept because Persian and Indic scripts
+/// use them inside words.
+fn is_invisible(c: char) -> bool {
+    (c.is_control() && !c.is_whitespace())
+        || matches!(
+            c,
+            '\u{200B}' // zero width space
+                | '\u{200E}' // left-to-right mark
+                | '\u{200F}' // right-to-left mark
+                | '\u{2060}' // word joiner
+                | '\u{FEFF}' // byte order mark
+        )
+}
+
+/// Longest accepted city name, in characters after normalization.
+///
+/// Generous enough for `"City, Region, Country"` forms of the longest real
+/// place names.
+pub const MAX_CITY_CHARS: usize = 200;
+
+/// A validated, normalized city name as typed by the user.
+///
+/// Construction goes through [`CityName::new`], so a value of this type is
+/// always non-empty, at most [`MAX_CITY_CHARS`] long, free of control
+/// characters, and contains at least one letter or digit. Invalid input is
+/// rejected locally instead of being sent to the provider.
+///
+/// # Example
+/// ```ignore
+/// assert_eq!(CityName::new("  San Jose\u{301} ")?.as_str(), "San José");
+/// assert!(CityName::new("Kyiv\nLviv").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CityName(String);
+
+impl CityName {
+    /// Validates and normalizes (see [`normalize_city`]) a city name.
+    ///
+    /// Leading and trailing whitespace is ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the name is empty, too long, contains a control
+    /// character (such as an embedded newline), or consists only of
+    /// punctuation and symbols.
+    pub fn new(input: &str) -> anyhow::Result<Self> {
+        if let Some(c) = input.trim().chars().find(|c| c.is_control()) {
+            return Err(anyhow::anyhow!(
+                "city name contains a control character (U+{:04X})",
+                c as u32
+            ));
+        }
+
+        let name = normalize_city(input);
+        let len = name.chars().count();
+
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("city name is empty"));
+        }
+
+        if len > MAX_CITY_CHARS {
+            return Err(anyhow::anyhow!(
+                "city name is too long ({} characters, at most {})",
+                len,
+                MAX_CITY_CHARS
+            ));
+        }
+
+        if !name.chars().any(char::is_alphanumeric) {
+            return Err(anyhow::anyhow!(
+                "city name '{}' has no letters or digits",
+                name
+            ));
+        }
+
+        Ok(Self(name))
+    }
+
+    /// The normalized name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for CityName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for CityName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl AsRef<str> for CityName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Reads a list of cities, one per line, as given to `get --cities-from`.
+///
+/// Blank lines and lines starting with `#` are skipped.
+///
+/// # Errors
+/// Returns an error naming the line number of the first invalid city.
+pub fn parse_city_list(text: &str) -> anyhow::Result<Vec<CityName>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| CityName::new(line).map_err(|e| anyhow::anyhow!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// US state, district and territory postal abbreviations.
+const US_STATES: &[&str] = &[
+    "AL", "AK", "AZ", "AR", "CA", "CO", "CT", "DE", "FL", "GA", "HI", "ID", "IL", "IN", "IA", "KS",
+    "KY", "LA", "ME", "MD", "MA", "MI", "MN", "MS", "MO", "MT", "NE", "NV", "NH", "NJ", "NM", "NY",
+    "NC", "ND", "OH", "OK", "OR", "PA", "RI", "SC", "SD", "TN", "TX", "UT", "VT", "VA", "WA", "WV",
+    "WI", "WY", "DC", "AS", "GU", "MP", "PR", "VI",
+];
+
+/// ISO 3166-1 alpha-2 country codes.
+const COUNTRY_CODES: &[&str] = &[
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// A location typed by the user, split into its components.
+///
+/// Users naturally write `"Portland, OR"` or `"Paris, TX, US"`. Providers want
+/// this information in different shapes, so the input is parsed once into a
+/// structured value and each provider formats it the way its API expects.
+///
+/// # Fields
+/// - `raw`: the normalized input exactly as typed (minus quoting),
+/// - `city`: the city name,
+/// - `region`: a state or region (US state codes are upper-cased),
+/// - `country`: an ISO 3166-1 alpha-2 country code.
+///
+/// When the input cannot be parsed unambiguously, `city` holds the whole
+/// input and `region`/`country` are `None`, so providers fall back to the
+/// raw string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationInput {
+    pub raw: String,
+    pub city: String,
+    pub region: Option<String>,
+    pub country: Option<String>,
+}
+
+impl LocationInput {
+    /// Parses a `"City[, Region][, Country]"` string.
+    ///
+    /// Components are separated by commas; a component wrapped in double
+    /// quotes may itself contain commas (`"\"Washington, D.C.\", US"`).
+    ///
+    /// Recognized shapes:
+    /// - `City`
+    /// - `City, ST` where `ST` is a US state code → region `ST`, country `US`
+    /// - `City, CC` where `CC` is an ISO country code → country `CC`
+    /// - `City, Region, CC`
+    ///
+    /// Two-letter codes that are both a US state and a country (`CA`, `IN`,
+    /// `GA`, …) are ambiguous in the two-component form, so the raw string is
+    /// kept instead.
+    pub fn parse(input: &str) -> Self {
+        let normalized = normalize_city(input);
+        let parts = split_components(&normalized);
+        let raw = parts.join(", ");
+
+        let unstructured = Self {
+            raw: raw.clone(),
+            city: raw.clone(),
+            region: None,
+            country: None,
+        };
+
+        if parts.iter().any(|p| p.is_empty()) {
+            return unstructured;
+        }
+
+        match parts.as_slice() {
+            [city] => Self {
+                city: city.clone(),
+                ..unstructured
+            },
+
+            [city, code] => {
+                let code = code.to_uppercase();
+                let state = US_STATES.contains(&code.as_str());
+                let country = COUNTRY_CODES.contains(&code.as_str());
+
+                match (state, country) {
+                    (true, false) => Self {
+                        raw,
+                        city: city.clone(),
+                        region: Some(code),
+                        country: Some("US".into()),
+                    },
+                    (false, true) => Self {
+                        raw,
+                        city: city.clone(),
+                        region: None,
+                        country: Some(code),
+                    },
+                    // Ambiguous state/country code, or a free-form region name.
+                    _ => unstructured,
+                }
+            }
+
+            [city, region, country] => {
+                let country = country.to_uppercase();
+                if !COUNTRY_CODES.contains(&country.as_str()) {
+                    return unstructured;
+                }
+
+                let region =
+                    if country == "US" && US_STATES.contains(&region.to_uppercase().as_str()) {
+                        region.to_uppercase()
+                    } else {
+                        region.clone()
+                    };
+
+                Self {
+                    raw,
+                    city: city.clone(),
+                    region: Some(region),
+                    country: Some(country),
+                }
+            }
+
+            _ => unstructured,
+        }
+    }
+
+    /// Returns `true` if the input was split into more than a bare city name.
+    pub fn is_structured(&self) -> bool {
+        self.region.is_some() || self.country.is_some()
+    }
+}
+
+impl From<&CityName> for LocationInput {
+    fn from(value: &CityName) -> Self {
+        Self::parse(value.as_str())
+    }
+}
+
+impl From<&str> for LocationInput {
+    fn from(value: &str) -> Self {
+        Self::parse(value)
+    }
+}
+
+impl From<String> for LocationInput {
+    fn from(value: String) -> Self {
+        Self::parse(&value)
+    }
+}
+
+/// One candidate of a location search, see `wapp search`.
+///
+/// # Fields
+/// - `name`: place name as the provider spells it,
+/// - `region`: state, province or other subdivision, if reported,
+/// - `country`: country name or code, if reported,
+/// - `lat`, `lon`: coordinates in decimal degrees.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LocationMatch {
+    pub name: String,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Longest postal code accepted by [`Location::zip`].
+const MAX_ZIP_CHARS: usize = 10;
+
+/// Where a request is for: a named place, a pair of coordinates, a postal
+/// code, or an airport.
+///
+/// Providers receive this enum, so each decides how to query the location;
+/// coordinates skip any city lookup. A provider that cannot query a kind
+/// returns [`Location::unsupported`] before sending anything.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    City(LocationInput),
+    Coords {
+        lat: f64,
+        lon: f64,
+    },
+    /// A postal code, optionally narrowed by an ISO 3166-1 alpha-2 country.
+    Zip {
+        code: String,
+        country: Option<String>,
+    },
+    /// A three-letter IATA airport code, uppercased.
+    Iata(String),
+}
+
+impl Location {
+    /// Validates a pair of coordinates in degrees.
+    ///
+    /// # Errors
+    /// Returns an error if `lat` is outside -90..=90 or `lon` outside
+    /// -180..=180 (or either is not a number).
+    pub fn coords(lat: f64, lon: f64) -> anyhow::Result<Self> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(anyhow::anyhow!(
+                "latitude {} is out of range (-90 to 90)",
+                lat
+            ));
+        }
+
+        if !(-180.0..=180.0).contains(&lon) {
+            return Err(anyhow::anyhow!(
+                "longitude {} is out of range (-180 to 180)",
+                lon
+            ));
+        }
+
+        Ok(Self::Coords { lat, lon })
+    }
+
+    /// Parses a postal code with an optional country, e.g. `10001,us` or
+    /// `SW1A 1AA, GB`. Both parts are uppercased.
+    ///
+    /// # Errors
+    /// Returns an error if the code is empty, longer than 10 characters, or
+    /// holds anything but letters, digits, spaces and dashes, or if the
+    /// country is not an ISO 3166-1 alpha-2 code.
+    pub fn zip(input: &str) -> anyhow::Result<Self> {
+        let (code, country) = match input.rsplit_once(',') {
+            Some((code, country)) => (code, Some(country.trim().to_uppercase())),
+            None => (input, None),
+        };
+        let code = code.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let valid = code.chars().count() <= MAX_ZIP_CHARS
+            && code.chars().any(|c| c.is_ascii_alphanumeric())
+            && code
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-');
+        if !valid {
+            return Err(anyhow::anyhow!(
+                "invalid postal code '{}': expected up to {} letters, digits, spaces or dashes",
+                input.trim(),
+                MAX_ZIP_CHARS
+            ));
+        }
+
+        if let Some(country) = &country {
+            if !COUNTRY_CODES.contains(&country.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "invalid country '{}' in postal code: expected a two-letter ISO code, e.g. US",
+                    country
+                ));
+            }
+        }
+
+        Ok(Self::Zip {
+            code: code.to_uppercase(),
+            country,
+        })
+    }
+
+    /// Parses a three-letter IATA airport code, e.g. `lhr`.
+    ///
+    /// # Errors
+    /// Returns an error unless `input` is exactly three ASCII letters.
+    pub fn iata(input: &str) -> anyhow::Result<Self> {
+        let code = input.trim();
+
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(anyhow::anyhow!(
+                "invalid airport code '{}': expected three letters, e.g. LHR",
+                code
+            ));
+        }
+
+        Ok(Self::Iata(code.to_uppercase()))
+    }
+
+    /// What kind of location this is, as used in error messages.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::City(_) => "city",
+            Self::Coords { .. } => "coordinate",
+            Self::Zip { .. } => "postal code",
+            Self::Iata(_) => "airport code",
+        }
+    }
+
+    /// The error a provider returns for a location kind it cannot query,
+    /// e.g. "airport code locations are not supported by this provider
+    /// (openweather)".
+    pub fn unsupported(&self, provider: &str) -> anyhow::Error {
+        anyhow::anyhow!(
+            "{} locations are not supported by this provider ({})",
+            self.kind(),
+            provider
+        )
+    }
+
+    /// The location as shown to the user: the city as typed, `lat,lon`,
+    /// the postal code with its country, or the airport code.
+    pub fn label(&self) -> String {
+        match self {
+            Self::City(city) => city.raw.clone(),
+            Self::Coords { lat, lon } => format!("{},{}", lat, lon),
+            Self::Zip {
+                code,
+                country: Some(country),
+            } => format!("{},{}", code, country),
+            Self::Zip { code, .. } | Self::Iata(code) => code.clone(),
+        }
+    }
+
+    /// The location as a free-text query value for providers that take one,
+    /// URL-encoded: the city as typed, `lat,lon` with a plain comma, or the
+    /// postal or airport code alone.
+    pub fn query(&self) -> String {
+        match self {
+            Self::City(city) => urlencoding::encode(&city.raw).into_owned(),
+            Self::Coords { lat, lon } => format!("{},{}", lat, lon),
+            Self::Zip { code, .. } | Self::Iata(code) => urlencoding::encode(code).into_owned(),
+        }
+    }
+}
+
+impl From<LocationInput> for Location {
+    fn from(value: LocationInput) -> Self {
+        Self::City(value)
+    }
+}
+
+impl From<&str> for Location {
+    fn from(value: &str) -> Self {
+        Self::City(value.into())
+    }
+}
+
+impl From<&CityName> for Location {
+    fn from(value: &CityName) -> Self {
+        Self::City(value.into())
+    }
+}
+
+/// Splits a location string on commas, honoring double-quoted components.
+///
+/// Quotes are removed and each component is trimmed.
+fn split_components(input: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+
+    parts.into_iter().map(|p| p.trim().to_string()).collect()
+}
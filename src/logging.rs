@@ -0,0 +1,146 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::EnvFilter;
+
+use crate::providers::credentials::REDACTED;
+
+/// Environment variable holding a log filter such as `debug` or `wapp=info`.
+pub const LOG_ENV: &str = "WAPP_LOG";
+
+/// The conventional filter variable, read when [`LOG_ENV`] is not set.
+pub const RUST_LOG_ENV: &str = "RUST_LOG";
+
+/// Query parameters whose values are secrets and never logged.
+const SECRET_PARAMS: &[&str] = &["key", "appid", "apikey"];
+
+/// Format of log events written to stderr.
+///
+/// - `text`: human-readable lines; only warnings and errors by default,
+/// - `json`: one JSON object per event with `timestamp`, `level`, `target`,
+///   `message`, the event's fields, and the fields of its innermost `span`;
+///   request events are included by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    /// Filter used when [`LOG_ENV`] is not set.
+    fn default_filter(self) -> &'static str {
+        match self {
+            LogFormat::Text => "warn",
+            LogFormat::Json => "info",
+        }
+    }
+}
+
+/// Builds a subscriber writing events in `format` to `writer`.
+///
+/// `filter` follows the `EnvFilter` syntax; `None` uses the format's
+/// default level.
+pub fn subscriber<W>(
+    format: LogFormat,
+    filter: Option<&str>,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let filter = EnvFilter::new(filter.unwrap_or(format.default_filter()));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+
+    match format {
+        LogFormat::Text => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(
+            builder
+                .json()
+                .flatten_event(true)
+                .with_current_span(true)
+                .with_span_list(false)
+                .finish(),
+        ),
+    }
+}
+
+/// Filter of `verbosity`, the number of `--verbose` flags: `info` for one,
+/// `debug` for two, `trace` for more, and `None` without any.
+pub fn verbosity_filter(verbosity: u8) -> Option<&'static str> {
+    match verbosity {
+        0 => None,
+        1 => Some("info"),
+        2 => Some("debug"),
+        _ => Some("trace"),
+    }
+}
+
+/// Installs the global subscriber, writing to stderr.
+///
+/// The filter comes from [`LOG_ENV`], else [`RUST_LOG_ENV`], else
+/// [`verbosity_filter`] of `verbosity`, else the format's default. Does
+/// nothing if a subscriber is already installed.
+pub fn init(format: LogFormat, verbosity: u8) {
+    let filter = [LOG_ENV, RUST_LOG_ENV]
+        .into_iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .or_else(|| verbosity_filter(verbosity).map(String::from));
+    let _ = tracing::subscriber::set_global_default(subscriber(
+        format,
+        filter.as_deref(),
+        std::io::stderr,
+    ));
+}
+
+/// Replaces the values of secret query parameters (`key`, `appid`, `apikey`)
+/// in a URL.
+///
+/// Every URL that is logged goes through this function first, so secrets are
+/// removed before any formatter sees them.
+///
+/// # Example
+/// ```ignore
+/// assert_eq!(redact_url("http://x/current.json?key=abc&q=Kyiv"),
+///            "http://x/current.json?key=<redacted>&q=Kyiv");
+/// ```
+pub fn redact_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SECRET_PARAMS.contains(&name.to_ascii_lowercase().as_str()) => {
+                format!("{}={}", name, REDACTED)
+            }
+            _ => pair.to_string(),
+        })
+        .collect();
+
+    format!("{}?{}", base, query.join("&"))
+}
+
+/// Values of the secret query parameters of `url`, decoded, e.g. the API
+/// key of a provider request.
+pub fn url_secrets(url: &str) -> Vec<String> {
+    let Some((_, query)) = url.split_once('?') else {
+        return Vec::new();
+    };
+
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(name, _)| SECRET_PARAMS.contains(&name.to_ascii_lowercase().as_str()))
+        .map(|(_, value)| {
+            urlencoding::decode(value)
+                .map(|value| value.into_owned())
+                .unwrap_or_else(|_| value.to_string())
+        })
+        .collect()
+}
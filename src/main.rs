@@ -7,7 +7,9 @@
 //!
 //! * `cli` - Command-line interface handling and argument parsing
 //! * `config` - Configuration management and settings
+//! * `location` - Normalization of user-supplied locations
 //! * `providers` - Weather API provider implementations
+//! * `runtime` - The Tokio runtime each command runs on
 //!
 //! # Environment Variables
 //!
@@ -21,18 +23,28 @@
 //! cargo run -- --city "London"
 //! ```
 
-mod cli;
-mod config;
-mod providers;
+use std::panic::AssertUnwindSafe;
+use std::sync::OnceLock;
 
-use clap::Parser;
+use anyhow::Context;
+use clap::{CommandFactory, Parser};
+use futures::FutureExt;
+use wapp::check::{ConditionNotMet, CHECK_ERROR_EXIT_CODE, CONDITION_NOT_MET_EXIT_CODE};
+use wapp::cli;
+use wapp::error::{ErrorFormat, ErrorReport};
+use wapp::exit;
+use wapp::output::status::StatusUnavailable;
+use wapp::providers::credentials::redact_secrets;
+use wapp::rules::{RuleExit, RULE_EXIT_CODE};
+use wapp::runtime;
 
 /// Main entry point of the weather application.
 ///
 /// This function performs the following steps:
 /// 1. Loads environment variables from a `.env` file (if present)
 /// 2. Parses command-line arguments
-/// 3. Delegates execution to the CLI handler
+/// 3. Builds the Tokio runtime the command needs, see [`runtime::flavor_for`]
+/// 4. Delegates execution to the CLI handler on that runtime
 ///
 /// # Returns
 ///
@@ -57,14 +69,96 @@ use clap::Parser;
 /// # Get forecast
 /// wapp --city "Paris" --data forecast
 /// ```
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
     // Load environment variables from .env file if it exists
     dotenvy::dotenv().ok();
 
+    // Answer the shell when it asks for completions (`COMPLETE=<shell> wapp`)
+    clap_complete::CompleteEnv::with_factory(cli::Cli::command).complete();
+
     // Parse command-line arguments
     let cli = cli::Cli::parse();
+    let errors = cli.error_format();
+    // `check` keeps exit code 1 for "condition not met"; `--quiet` prints nothing.
+    let check = match &cli.cmd {
+        cli::Commands::Check { quiet, .. } => Some(*quiet),
+        _ => None,
+    };
+    // The panic is reported as JSON below, instead of by the default hook.
+    if errors == ErrorFormat::Json {
+        std::panic::set_hook(Box::new(|info| {
+            let _ = PANIC.set(info.to_string());
+        }));
+    }
+
+    // A single request runs on the current thread; see `runtime`.
+    let threads = std::env::var(runtime::THREADS_ENV).ok();
+    let rt = match runtime::flavor_for(cli.is_concurrent(), threads.as_deref())
+        .and_then(|flavor| runtime::build(flavor).context("cannot start the async runtime"))
+    {
+        Ok(rt) => rt,
+        Err(e) => exit_with(e, errors, check),
+    };
 
     // Handle the CLI command and execute the requested operation
-    cli::handle_cli(cli).await
+    match rt.block_on(AssertUnwindSafe(cli::handle_cli(cli)).catch_unwind()) {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => exit_with(e, errors, check),
+        Err(_) if errors == ErrorFormat::Json => {
+            let message = PANIC.get().cloned().unwrap_or_else(|| "panicked".into());
+            report(&ErrorReport::internal(&message), PANIC_EXIT_CODE)
+        }
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
+
+/// Reports `e` in the `errors` format and exits with its exit code; `check`
+/// is the `--quiet` flag of a `check` command, `None` for other commands.
+fn exit_with(e: anyhow::Error, errors: ErrorFormat, check: Option<bool>) -> ! {
+    // The status line fails quietly; the cause was logged.
+    if e.is::<StatusUnavailable>() {
+        std::process::exit(1);
+    }
+    if e.is::<ConditionNotMet>() {
+        std::process::exit(CONDITION_NOT_MET_EXIT_CODE);
+    }
+    if e.is::<RuleExit>() {
+        match errors {
+            ErrorFormat::Text => fail(&e.to_string(), RULE_EXIT_CODE),
+            ErrorFormat::Json => report(&ErrorReport::of(&e), RULE_EXIT_CODE),
+        }
+    }
+
+    let code = exit::exit_code(&e);
+    if check == Some(true) {
+        std::process::exit(code.unwrap_or(CHECK_ERROR_EXIT_CODE));
+    }
+    match (errors, code) {
+        (ErrorFormat::Json, code) => report(&ErrorReport::of(&e), code.unwrap_or(1)),
+        (ErrorFormat::Text, Some(code)) => fail(&format!("Error: {:#}", e), code),
+        (ErrorFormat::Text, None) if check.is_some() => {
+            fail(&format!("Error: {:#}", e), CHECK_ERROR_EXIT_CODE)
+        }
+        // As the error would be printed when returned from `main`.
+        (ErrorFormat::Text, None) => fail(&format!("Error: {:?}", e), 1),
+    }
+}
+
+/// Exit code of a panic, as Rust exits after one.
+const PANIC_EXIT_CODE: i32 = 101;
+
+/// The message and location of a panic, kept by the hook of `--errors json`.
+static PANIC: OnceLock<String> = OnceLock::new();
+
+/// Prints `message` on stderr without any known API key, see
+/// [`redact_secrets`], and exits with `code`.
+fn fail(message: &str, code: i32) -> ! {
+    eprintln!("{}", redact_secrets(message));
+    std::process::exit(code)
+}
+
+/// Prints `report` on stderr as one line of JSON and exits with `code`.
+fn report(report: &ErrorReport, code: i32) -> ! {
+    eprintln!("{}", report.to_json());
+    std::process::exit(code)
 }
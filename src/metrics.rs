@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Process-wide counters, updated by the request and cache layers.
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics::new());
+
+/// Request latency of one provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Latency {
+    pub requests: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+impl Latency {
+    /// Mean latency, if any request was made.
+    pub fn average_ms(&self) -> Option<u64> {
+        (self.requests > 0).then(|| self.total_ms / self.requests)
+    }
+}
+
+/// What the current process has done so far, reported by `--stats`.
+///
+/// # Fields
+/// - `requests`: HTTP requests sent to providers, including failed ones,
+/// - `failures`: requests that got no response,
+/// - `retries`: requests repeated after a failure,
+/// - `cache_hits`: lookups served from the cache,
+/// - `bytes`: response bytes received,
+/// - `latency`: per-provider latency, keyed by provider name.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Metrics {
+    pub requests: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub cache_hits: u64,
+    pub bytes: u64,
+    pub latency: BTreeMap<String, Latency>,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            requests: 0,
+            failures: 0,
+            retries: 0,
+            cache_hits: 0,
+            bytes: 0,
+            latency: BTreeMap::new(),
+        }
+    }
+}
+
+/// Records one provider request; `bytes` is `None` if it got no response.
+pub fn record_request(provider: &str, elapsed: Duration, bytes: Option<u64>) {
+    update(|m| {
+        m.requests += 1;
+        match bytes {
+            Some(bytes) => m.bytes += bytes,
+            None => m.failures += 1,
+        }
+
+        let ms = elapsed.as_millis() as u64;
+        let latency = m.latency.entry(provider.to_string()).or_default();
+        latency.requests += 1;
+        latency.total_ms += ms;
+        latency.max_ms = latency.max_ms.max(ms);
+    });
+}
+
+/// Records a request repeated after a failure.
+pub fn record_retry() {
+    update(|m| m.retries += 1);
+}
+
+/// Records a lookup served from the cache.
+pub fn record_cache_hit() {
+    update(|m| m.cache_hits += 1);
+}
+
+/// Returns a copy of the counters.
+pub fn snapshot() -> Metrics {
+    METRICS.lock().map(|m| m.clone()).unwrap_or_default()
+}
+
+/// Resets every counter to zero.
+pub fn reset() {
+    update(|m| *m = Metrics::new());
+}
+
+fn update(f: impl FnOnce(&mut Metrics)) {
+    // A poisoned lock only means another thread panicked mid-update;
+    // the counters are still usable.
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut metrics);
+}
+
+/// Renders the `--stats` block for `metrics` over `wall` clock time.
+///
+/// ```text
+/// stats: 3 requests (0 failed, 0 retries), 0 cache hits, 2.4 KiB in 0.41s
+///   weatherapi: 3 requests, avg 131ms, max 162ms
+/// ```
+pub fn render(metrics: &Metrics, wall: Duration) -> String {
+    let mut out = format!(
+        "stats: {} requests ({} failed, {} retries), {} cache hits, {} in {:.2}s\n",
+        metrics.requests,
+        metrics.failures,
+        metrics.retries,
+        metrics.cache_hits,
+        format_bytes(metrics.bytes),
+        wall.as_secs_f64()
+    );
+
+    for (provider, latency) in &metrics.latency {
+        out.push_str(&format!(
+            "  {}: {} requests, avg {}ms, max {}ms\n",
+            provider,
+            latency.requests,
+            latency.average_ms().unwrap_or_default(),
+            latency.max_ms
+        ));
+    }
+
+    out
+}
+
+/// Formats a byte count as e.g. `512 B`, `2.4 KiB`, `1.1 MiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KiB", b as f64 / 1024.0),
+        b => format!("{:.1} MiB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
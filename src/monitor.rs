@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::expr::Expr;
+use crate::fsutil::write_atomic;
+use crate::location::Location;
+use crate::notify::{DesktopNotification, Urgency};
+use crate::providers::credentials::redact_secrets;
+use crate::providers::{ApiProvider, DataKind};
+use crate::weather::{Alert, Severity};
+
+/// Longest wait between polls while the provider keeps failing, unless the
+/// interval itself is longer.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(3600);
+
+/// Settings for a monitor run.
+///
+/// # Fields
+/// - `location`: location whose alerts are polled,
+/// - `interval`: delay between polls,
+/// - `on_alert`: shell command run for every new event, with placeholders
+///   substituted, see [`substitute`]; without one, events are printed,
+/// - `when`: threshold expression over the response, see [`Expr`]; an
+///   event is raised each time it starts to hold,
+/// - `state`: file remembering the alerts already reported,
+/// - `once`: poll a single time, for cron.
+#[derive(Debug, Clone)]
+pub struct MonitorOptions {
+    pub location: Location,
+    pub interval: Duration,
+    pub on_alert: Option<String>,
+    pub when: Option<String>,
+    pub state: PathBuf,
+    pub once: bool,
+}
+
+/// The state file of a monitor: the alerts already reported and whether
+/// the threshold held at the last poll.
+///
+/// ```json
+/// { "seen": { "Heat Advisory|2024-06-10T08:00:00Z|...": 1718060400 }, "threshold": false }
+/// ```
+///
+/// `seen` maps [`alert_id`]s to when the alert ends (seconds since the
+/// epoch), so an alert is forgotten once it has both ended and left the
+/// provider's feed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonitorState {
+    #[serde(default)]
+    pub seen: BTreeMap<String, Option<i64>>,
+    #[serde(default)]
+    pub threshold: bool,
+}
+
+impl MonitorState {
+    /// Reads the state at `path`; a missing file is an empty state.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a state file.
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("invalid monitor state {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("cannot read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Writes the state to `path` atomically, creating its directory.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            crate::paths::ensure_dir(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(path, json.as_bytes())
+            .map_err(|e| anyhow!("cannot write {}: {}", path.display(), e))
+    }
+
+    /// Records the alerts of one poll and returns those not seen before.
+    ///
+    /// Alerts missing from `alerts` are forgotten once they have ended at
+    /// `now`; while they have not, a provider briefly dropping one from its
+    /// feed does not report it again.
+    pub fn observe(&mut self, alerts: &[Alert], now: DateTime<Utc>) -> Vec<Alert> {
+        let current: Vec<String> = alerts.iter().map(alert_id).collect();
+
+        self.seen.retain(|id, ends| {
+            current.contains(id) || ends.is_some_and(|ends| ends > now.timestamp())
+        });
+
+        alerts
+            .iter()
+            .zip(current)
+            .filter(|(alert, id)| {
+                self.seen
+                    .insert(id.clone(), alert.ends.map(|t| t.timestamp()))
+                    .is_none()
+            })
+            .map(|(alert, _)| alert.clone())
+            .collect()
+    }
+}
+
+/// The identity of an alert across polls: its event, start, and headline.
+///
+/// Providers do not give alerts a stable ID, and reissue the same ones on
+/// every request.
+pub fn alert_id(alert: &Alert) -> String {
+    format!(
+        "{}|{}|{}",
+        alert.event,
+        alert
+            .starts
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+            .unwrap_or_default(),
+        alert.headline.as_deref().unwrap_or_default()
+    )
+}
+
+/// The default state file of a location, in the data directory, e.g.
+/// `monitor/new-york.json` for New York.
+pub fn state_path(data_dir: &Path, location: &Location) -> PathBuf {
+    let name: String = location
+        .label()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    data_dir.join("monitor").join(format!("{}.json", name))
+}
+
+/// Something the monitor reports: a new alert, or the threshold starting
+/// to hold.
+///
+/// # Fields
+/// The values of the placeholders of [`substitute`], empty when unknown.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    pub city: String,
+    pub event: String,
+    pub severity: String,
+    pub headline: String,
+    pub starts: String,
+    pub ends: String,
+    pub description: String,
+}
+
+impl Event {
+    /// The event of a new alert.
+    pub fn alert(city: &str, alert: &Alert) -> Self {
+        let time = |t: Option<DateTime<Utc>>| {
+            t.map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+                .unwrap_or_default()
+        };
+
+        Self {
+            city: city.to_string(),
+            event: alert.event.clone(),
+            severity: alert.severity.label().to_string(),
+            headline: alert
+                .headline
+                .clone()
+                .unwrap_or_else(|| alert.event.clone()),
+            starts: time(alert.starts),
+            ends: time(alert.ends),
+            description: alert.description.clone().unwrap_or_default(),
+        }
+    }
+
+    /// The event of the threshold `expr` starting to hold.
+    pub fn threshold(city: &str, expr: &str) -> Self {
+        Self {
+            city: city.to_string(),
+            event: "threshold".into(),
+            headline: format!("{} holds", expr),
+            ..Default::default()
+        }
+    }
+
+    /// The desktop notification of the event, critical for severe and
+    /// extreme alerts.
+    pub fn notification(&self) -> DesktopNotification {
+        DesktopNotification {
+            summary: match self.severity.is_empty() {
+                true => format!("Weather in {}", self.city),
+                false => format!("{} alert in {}", self.severity, self.city),
+            },
+            body: self.headline.clone(),
+            urgency: Urgency::for_severity(Severity::from_text(&self.severity)),
+        }
+    }
+
+    /// The environment variables of an `--on-alert` command, one per
+    /// placeholder of [`substitute`], e.g. `WAPP_HEADLINE`.
+    pub fn vars(&self) -> Vec<(&'static str, &str)> {
+        let values = [
+            &self.city,
+            &self.event,
+            &self.severity,
+            &self.headline,
+            &self.starts,
+            &self.ends,
+            &self.description,
+        ];
+        PLACEHOLDERS
+            .iter()
+            .zip(values)
+            .map(|((_, var), value)| (*var, value.as_str()))
+            .collect()
+    }
+
+    /// The line printed for the event when no command is given.
+    pub fn line(&self) -> String {
+        match self.severity.is_empty() {
+            true => format!("{}: {}", self.city, self.headline),
+            false => format!("{}: [{}] {}", self.city, self.severity, self.headline),
+        }
+    }
+}
+
+/// The placeholders of an `--on-alert` command and the environment
+/// variables their values are passed in, see [`Event::vars`].
+const PLACEHOLDERS: [(&str, &str); 7] = [
+    ("{city}", "WAPP_CITY"),
+    ("{event}", "WAPP_EVENT"),
+    ("{severity}", "WAPP_SEVERITY"),
+    ("{headline}", "WAPP_HEADLINE"),
+    ("{starts}", "WAPP_STARTS"),
+    ("{ends}", "WAPP_ENDS"),
+    ("{description}", "WAPP_DESCRIPTION"),
+];
+
+/// Fills the placeholders of an `--on-alert` command: `{city}`, `{event}`,
+/// `{severity}`, `{headline}`, `{starts}`, `{ends}`, and `{description}`.
+///
+/// A placeholder becomes a reference to the environment variable holding
+/// its value, e.g. `{headline}` → `"$WAPP_HEADLINE"`, or `!WAPP_HEADLINE!`
+/// for `cmd` on Windows. The shell expands it after parsing the command,
+/// so the text of an alert cannot run commands of its own; write
+/// `notify-send {headline}`, not `notify-send '{headline}'`.
+pub fn substitute(command: &str) -> String {
+    let mut out = String::with_capacity(command.len());
+    let mut rest = command;
+    'outer: while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        for (name, var) in PLACEHOLDERS {
+            if let Some(after) = rest.strip_prefix(name) {
+                out.push_str(&var_reference(var));
+                rest = after;
+                continue 'outer;
+            }
+        }
+        out.push('{');
+        rest = &rest[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The shell's reference to the environment variable `var`: quoted for
+/// `sh`, delayed for `cmd`, which [`run_command`] starts with `/V:ON`.
+fn var_reference(var: &str) -> String {
+    match cfg!(windows) {
+        true => format!("!{}!", var),
+        false => format!("\"${}\"", var),
+    }
+}
+
+/// Runs `command`, its placeholders [`substitute`]d, through the shell,
+/// with the [`vars`](Event::vars) of `event` in its environment.
+///
+/// # Errors
+/// Returns an error if the shell cannot be started or the command fails.
+fn run_command(command: &str, event: &Event) -> Result<()> {
+    let mut shell = match cfg!(windows) {
+        true => {
+            let mut shell = std::process::Command::new("cmd");
+            shell.args(["/V:ON", "/C"]);
+            shell
+        }
+        false => {
+            let mut shell = std::process::Command::new("sh");
+            shell.arg("-c");
+            shell
+        }
+    };
+    let status = shell
+        .arg(substitute(command))
+        .envs(event.vars())
+        .status()
+        .map_err(|e| anyhow!("cannot run '{}': {}", command, e))?;
+
+    match status.success() {
+        true => Ok(()),
+        false => Err(anyhow!("'{}' exited with {}", command, status)),
+    }
+}
+
+/// Delay before the next poll after `failures` failed polls in a row:
+/// the interval, doubled for every failure up to [`MAX_BACKOFF`] (or the
+/// interval, if longer).
+pub fn backoff(interval: Duration, failures: u32) -> Duration {
+    let cap = interval.max(MAX_BACKOFF);
+    interval
+        .checked_mul(2u32.saturating_pow(failures.min(16)))
+        .map_or(cap, |delay| delay.min(cap))
+}
+
+/// Polls the provider once and returns the events to report, updating
+/// `state`.
+///
+/// # Errors
+/// Returns an error if the request fails, the provider does not report
+/// alerts, or the threshold expression is invalid.
+pub async fn poll(
+    provider: &dyn ApiProvider,
+    options: &MonitorOptions,
+    state: &mut MonitorState,
+    now: DateTime<Utc>,
+) -> Result<Vec<Event>> {
+    let body = provider
+        .get_data(options.location.clone(), DataKind::Alerts, None)
+        .await?;
+    let alerts = provider
+        .alerts(&body)
+        .ok_or_else(|| anyhow!("{} does not report weather alerts", provider.name()))?;
+
+    let city = options.location.label();
+    let mut events: Vec<Event> = state
+        .observe(&alerts, now)
+        .iter()
+        .map(|alert| Event::alert(&city, alert))
+        .collect();
+
+    if let Some(expr) = &options.when {
+        let json: Option<serde_json::Value> = serde_json::from_str(&body).ok();
+        let lookup = |name: &str| {
+            json.as_ref()
+                .and_then(|j| crate::rules::field_value(j, name))
+        };
+        let holds = Expr::parse(expr)?.eval(&lookup)?;
+        if holds && !state.threshold {
+            events.push(Event::threshold(&city, expr));
+        }
+        state.threshold = holds;
+    }
+
+    Ok(events)
+}
+
+/// Reports an event: runs the `--on-alert` command, or prints its line,
+/// and with `--notify` shows it on the desktop too.
+fn report(options: &MonitorOptions, event: &Event) {
+    if crate::notify::notifications() {
+        crate::notify::notify(&event.notification());
+    }
+
+    match &options.on_alert {
+        Some(command) => {
+            tracing::info!(event = %event.event, "running alert command");
+            if let Err(e) = run_command(command, event) {
+                eprintln!("Warning: {}", e);
+            }
+        }
+        None => println!("{}", event.line()),
+    }
+}
+
+/// Polls the provider's alerts every `interval` until `shutdown` is
+/// cancelled, or once with [`once`](MonitorOptions::once).
+///
+/// Alerts already in the state file are not reported again, so a restart
+/// does not replay them; the state is saved after every successful poll.
+/// A failed poll is reported and retried after a [`backoff`].
+///
+/// # Errors
+/// Returns an error if the state file cannot be read or written, if the
+/// threshold expression is invalid, or, with `once`, if the poll fails.
+pub async fn run(
+    provider: &dyn ApiProvider,
+    options: &MonitorOptions,
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    if let Some(expr) = &options.when {
+        Expr::parse(expr).with_context(|| format!("invalid --when '{}'", expr))?;
+    }
+
+    let mut state = MonitorState::load(&options.state)?;
+    let mut failures = 0;
+
+    loop {
+        let polled = tokio::select! {
+            result = poll(provider, options, &mut state, Utc::now()) => result,
+            _ = shutdown.cancelled() => {
+                tracing::info!("poll cancelled by shutdown");
+                break;
+            }
+        };
+
+        match polled {
+            Ok(events) => {
+                failures = 0;
+                // Saved first: a failing command is not retried on restart
+                state.save(&options.state)?;
+                for event in &events {
+                    report(options, event);
+                }
+                tracing::info!(new = events.len(), "alerts polled");
+            }
+            Err(e) if options.once => return Err(e),
+            Err(e) => {
+                failures += 1;
+                let error = redact_secrets(&format!("{:#}", e));
+                tracing::error!(error = %error, failures, "poll failed");
+                eprintln!("Warning: poll failed: {}", error);
+            }
+        }
+
+        if options.once {
+            break;
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(backoff(options.interval, failures)) => {}
+        }
+    }
+
+    Ok(())
+}
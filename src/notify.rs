@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::units::Units;
+use crate::weather::{Severity, WeatherData};
+
+/// Urgency of a desktop notification: `Critical` for severe and extreme
+/// alerts, which stay on screen until dismissed, else `Normal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    /// The urgency of an alert of `severity`.
+    pub fn for_severity(severity: Severity) -> Self {
+        match severity {
+            Severity::Severe | Severity::Extreme => Self::Critical,
+            Severity::Unknown | Severity::Minor | Severity::Moderate => Self::Normal,
+        }
+    }
+}
+
+/// A desktop notification: a one-line `summary` and a `body` below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopNotification {
+    pub summary: String,
+    pub body: String,
+    pub urgency: Urgency,
+}
+
+impl DesktopNotification {
+    /// The notification of fetched weather: the city, the condition and
+    /// temperature, then one line per alert, most severe first.
+    ///
+    /// Returns `None` when `data` has none of these, e.g. for air quality.
+    pub fn for_weather(city: &str, data: &WeatherData, units: Units) -> Option<Self> {
+        let mut lines = Vec::new();
+
+        let temp = data
+            .temp_c
+            .map(|t| format!("{:.0}{}", units.temp(t), units.temp_symbol()));
+        let current = [data.condition.clone(), temp]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !current.is_empty() {
+            lines.push(current);
+        }
+
+        let alerts = data.alerts.as_deref().unwrap_or_default();
+        for alert in alerts {
+            lines.push(format!("{}: {}", alert.severity.label(), alert.event));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+
+        let severity = alerts.iter().map(|alert| alert.severity).max();
+        Some(Self {
+            summary: match alerts.len() {
+                0 => format!("Weather in {}", city),
+                1 => format!("Weather alert in {}", city),
+                n => format!("{} weather alerts in {}", n, city),
+            },
+            body: lines.join("\n"),
+            urgency: severity.map_or(Urgency::Normal, Urgency::for_severity),
+        })
+    }
+}
+
+/// Whether weather output is also sent as a desktop notification, see
+/// [`use_notifications`].
+static NOTIFY: AtomicBool = AtomicBool::new(false);
+
+/// Whether a failed notification was already reported, so `--watch` does
+/// not repeat the warning on every refresh.
+static WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Sends the output of this process as desktop notifications too
+/// (`--notify`, or `notify` in the config).
+pub fn use_notifications(on: bool) {
+    NOTIFY.store(on, Ordering::Relaxed);
+}
+
+/// Whether [`use_notifications`] turned notifications on.
+pub fn notifications() -> bool {
+    NOTIFY.load(Ordering::Relaxed)
+}
+
+/// Shows `notification` on the desktop.
+///
+/// # Errors
+/// Returns an error if no notification service is running, or if wapp was
+/// built without the `notifications` feature.
+#[cfg(feature = "notifications")]
+pub fn send(notification: &DesktopNotification) -> anyhow::Result<()> {
+    let mut desktop = notify_rust::Notification::new();
+    desktop
+        .appname("wapp")
+        .summary(&notification.summary)
+        .body(&notification.body);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    desktop.urgency(match notification.urgency {
+        Urgency::Normal => notify_rust::Urgency::Normal,
+        Urgency::Critical => notify_rust::Urgency::Critical,
+    });
+
+    desktop
+        .show()
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!("cannot show a desktop notification: {}", e))
+}
+
+/// Shows `notification` on the desktop.
+///
+/// # Errors
+/// Returns an error if no notification service is running, or if wapp was
+/// built without the `notifications` feature.
+#[cfg(not(feature = "notifications"))]
+pub fn send(_notification: &DesktopNotification) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!(
+        "desktop notifications need wapp built with the `notifications` feature"
+    ))
+}
+
+/// Like [`send`], reporting a failure as a warning on stderr, once per
+/// process. Returns whether the notification was shown.
+pub fn notify(notification: &DesktopNotification) -> bool {
+    match send(notification) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(error = %e, "notification not sent");
+            if !WARNED.swap(true, Ordering::Relaxed) {
+                eprintln!("Warning: {}", e);
+            }
+            false
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::io::{self, IsTerminal};
+use std::sync::Mutex;
+
+use crate::condition::Condition;
+
+/// Temperatures below this many °C are shown in blue.
+pub const COLD_BELOW_C: f64 = 5.0;
+
+/// Temperatures from this many °C up are shown in red.
+pub const HOT_FROM_C: f64 = 28.0;
+
+/// The palette chosen by [`init`] for this process.
+static CURRENT: Mutex<Palette> = Mutex::new(Palette::PLAIN);
+
+/// Condition glyphs shown next to the condition text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icons {
+    None,
+    Emoji,
+    Ascii,
+}
+
+/// ANSI styling and condition glyphs of the text and table output.
+///
+/// Renderers format their plain text first and pass each piece through a
+/// palette, so a disabled palette returns its input unchanged and column
+/// widths are never affected by escape codes.
+///
+/// The ramp is defined in °C only: rendered values come from the
+/// normalized [`WeatherData`](crate::weather::WeatherData), which is metric
+/// whatever units the provider was configured with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub enabled: bool,
+    pub icons: Icons,
+}
+
+impl Palette {
+    /// A palette that adds no escape codes and no glyphs.
+    pub const PLAIN: Palette = Palette {
+        enabled: false,
+        icons: Icons::None,
+    };
+
+    /// A palette that adds ANSI escape codes and no glyphs.
+    pub const ANSI: Palette = Palette {
+        enabled: true,
+        icons: Icons::None,
+    };
+
+    /// The palette chosen by [`init`]; plain if it was never called.
+    pub fn current() -> Self {
+        *CURRENT.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The same palette showing `icons`.
+    pub fn with_icons(self, icons: Icons) -> Self {
+        Self { icons, ..self }
+    }
+
+    /// Prefixes a condition text with the glyph of its category, e.g.
+    /// `☀️ Sunny`; unchanged without icons.
+    pub fn condition(self, kind: Condition, text: &str) -> String {
+        match self.icons {
+            Icons::None => text.to_string(),
+            Icons::Emoji => format!("{} {}", kind.emoji(), text),
+            Icons::Ascii => format!("{} {}", kind.ascii(), text),
+        }
+    }
+
+    /// Dims a label or table header.
+    pub fn label(self, text: &str) -> String {
+        self.paint("2", text)
+    }
+
+    /// Colors `text`, showing the temperature `celsius`, by the ramp:
+    /// blue below [`COLD_BELOW_C`], red from [`HOT_FROM_C`], else unchanged.
+    pub fn temp(self, celsius: f64, text: &str) -> String {
+        if celsius < COLD_BELOW_C {
+            self.paint("34", text)
+        } else if celsius >= HOT_FROM_C {
+            self.paint("31", text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Colors a warning yellow.
+    pub fn warning(self, text: &str) -> String {
+        self.paint("33", text)
+    }
+
+    fn paint(self, code: &str, text: &str) -> String {
+        if self.enabled && !text.is_empty() {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Returns `true` if output should be colored: stdout is a terminal,
+/// `NO_COLOR` is unset or empty (see <https://no-color.org>), and
+/// `--no-color` was not passed.
+pub fn should_color(no_color_flag: bool, no_color_env: Option<&str>, is_tty: bool) -> bool {
+    is_tty && !no_color_flag && no_color_env.is_none_or(str::is_empty)
+}
+
+/// Chooses the palette of this process: color if [`should_color`] agrees
+/// for the process's stdout and environment, and emoji glyphs, or ASCII
+/// ones with `ascii`.
+pub fn init(no_color_flag: bool, ascii: bool) {
+    let env = std::env::var("NO_COLOR").ok();
+    let palette = Palette {
+        enabled: should_color(no_color_flag, env.as_deref(), io::stdout().is_terminal()),
+        icons: if ascii { Icons::Ascii } else { Icons::Emoji },
+    };
+
+    *CURRENT.lock().unwrap_or_else(|e| e.into_inner()) = palette;
+}
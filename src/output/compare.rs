@@ -0,0 +1,238 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+use chrono::NaiveDate;
+
+use crate::providers::SkippedProvider;
+use crate::weather::WeatherData;
+
+use super::{number, Palette};
+
+/// Renders the weather of several cities side by side, one column per city
+/// in the given order.
+///
+/// Forecasts are aligned by date: every date in any of the forecasts gets
+/// temperature, condition, and precipitation rows, with `-` for a city whose
+/// forecast does not cover it. When no city has a forecast, one block of
+/// current temperature and condition is rendered instead.
+///
+/// Only the city names and dates are styled by `palette`, so the columns
+/// stay aligned.
+pub fn render_comparison(columns: &[(String, WeatherData)], palette: Palette) -> String {
+    let dates: BTreeSet<NaiveDate> = columns
+        .iter()
+        .flat_map(|(_, data)| data.forecast.iter().map(|day| day.date))
+        .collect();
+
+    // A heading row has no cells.
+    let mut rows: Vec<(String, Vec<String>)> = Vec::new();
+    if dates.is_empty() {
+        rows.push(("Now".into(), Vec::new()));
+        rows.push((
+            "Temp °C".into(),
+            columns
+                .iter()
+                .map(|(_, data)| number(data.temp_c))
+                .collect(),
+        ));
+        rows.push((
+            "Condition".into(),
+            columns
+                .iter()
+                .map(|(_, data)| data.condition.clone().unwrap_or("-".into()))
+                .collect(),
+        ));
+    }
+    for date in dates {
+        let days: Vec<_> = columns
+            .iter()
+            .map(|(_, data)| data.forecast.iter().find(|day| day.date == date))
+            .collect();
+
+        rows.push((crate::i18n::date(date), Vec::new()));
+        rows.push((
+            "Temp °C".into(),
+            days.iter()
+                .map(|day| match day {
+                    Some(day) => format!("{} .. {}", number(day.min_c), number(day.max_c)),
+                    None => "-".into(),
+                })
+                .collect(),
+        ));
+        rows.push((
+            "Condition".into(),
+            days.iter()
+                .map(|day| day.and_then(|d| d.condition.clone()).unwrap_or("-".into()))
+                .collect(),
+        ));
+        rows.push((
+            "Precip mm".into(),
+            days.iter()
+                .map(|day| number(day.and_then(|d| d.precip_mm)))
+                .collect(),
+        ));
+    }
+
+    // Value rows are indented under their heading.
+    let label_width = rows
+        .iter()
+        .map(|(label, cells)| label.chars().count() + if cells.is_empty() { 0 } else { 2 })
+        .max()
+        .unwrap_or(0);
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (city, _))| {
+            rows.iter()
+                .filter_map(|(_, cells)| cells.get(i))
+                .map(|cell| cell.chars().count())
+                .chain([city.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let pad = |text: &str, width: usize| " ".repeat(width - text.chars().count());
+
+    let titles: Vec<String> = columns
+        .iter()
+        .zip(&widths)
+        .map(|((city, _), width)| format!("{}{}", palette.label(city), pad(city, *width)))
+        .collect();
+    let mut out = format!("{}  {}", " ".repeat(label_width), titles.join("  "))
+        .trim_end()
+        .to_string();
+    out.push('\n');
+
+    for (label, cells) in &rows {
+        if cells.is_empty() {
+            out.push_str(&palette.label(label));
+        } else {
+            let label = format!("  {}", label);
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{}{}", cell, pad(cell, *width)))
+                .collect();
+            let line = format!(
+                "{}{}  {}",
+                label,
+                pad(&label, label_width),
+                cells.join("  ")
+            );
+            out.push_str(line.trim_end());
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// What one provider reported in `compare-providers`: the current
+/// temperature and condition, or the first forecast day's when the data
+/// has no current conditions, or why the request failed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderReading {
+    pub provider: String,
+    pub temp_c: Option<f64>,
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ProviderReading {
+    /// Reads the temperature and condition of `data`; a forecast day's
+    /// temperature is the middle of its range.
+    pub fn from_data(provider: &str, data: &WeatherData) -> Self {
+        let day = data.forecast.first();
+        let middle = day.and_then(|d| Some((d.min_c? + d.max_c?) / 2.0));
+
+        Self {
+            provider: provider.to_string(),
+            temp_c: data.temp_c.or(middle),
+            condition: data
+                .condition
+                .clone()
+                .or_else(|| day.and_then(|d| d.condition.clone())),
+            error: None,
+        }
+    }
+
+    /// A provider whose request failed with `error`.
+    pub fn failed(provider: &str, error: &anyhow::Error) -> Self {
+        Self {
+            provider: provider.to_string(),
+            temp_c: None,
+            condition: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Renders the readings of `compare-providers` as a table under a `city`
+/// line, one row per provider, followed by a line per skipped provider.
+pub fn render_provider_table(
+    city: &str,
+    readings: &[ProviderReading],
+    skipped: &[SkippedProvider],
+) -> String {
+    let rows: Vec<[String; 3]> = readings
+        .iter()
+        .map(|reading| match &reading.error {
+            Some(error) => [
+                reading.provider.clone(),
+                "-".into(),
+                format!("error: {}", error),
+            ],
+            None => [
+                reading.provider.clone(),
+                number(reading.temp_c),
+                reading.condition.clone().unwrap_or("-".into()),
+            ],
+        })
+        .collect();
+    let header = ["Provider", "Temp °C", "Condition"];
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = format!("{}\n", city);
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        out.push_str(cells.join("  ").trim_end());
+        out.push('\n');
+    }
+    for skip in skipped {
+        out.push_str(&format!("Skipped {}: {}\n", skip.provider, skip.reason));
+    }
+
+    out
+}
+
+/// Renders the readings of `compare-providers` as a JSON object with the
+/// `city`, the data `kind`, the `providers` readings, and the `skipped`
+/// providers with their reasons.
+pub fn render_provider_json(
+    city: &str,
+    kind: &str,
+    readings: &[ProviderReading],
+    skipped: &[SkippedProvider],
+) -> String {
+    let json = serde_json::json!({
+        "city": city,
+        "kind": kind,
+        "providers": readings,
+        "skipped": skipped,
+    });
+
+    serde_json::to_string_pretty(&json).unwrap_or_default()
+}
@@ -0,0 +1,167 @@
+use crate::series::DailySummary;
+use crate::weather::WeatherData;
+
+/// A value that `get --fields` can select, read from the normalized model.
+pub struct Field<T> {
+    pub name: &'static str,
+    pub value: fn(&T) -> Option<String>,
+}
+
+/// Fields of the current conditions, available for "now".
+pub const CURRENT_FIELDS: &[Field<WeatherData>] = &[
+    Field {
+        name: "temp",
+        value: |d| number(d.temp_c),
+    },
+    Field {
+        name: "feels_like",
+        value: |d| number(d.feels_like_c),
+    },
+    Field {
+        name: "humidity",
+        value: |d| d.humidity.map(|h| format!("{:.0}", h)),
+    },
+    Field {
+        name: "wind_speed",
+        value: |d| number(d.wind_kph),
+    },
+    Field {
+        name: "wind_degree",
+        value: |d| d.wind_degree.map(|w| format!("{:.0}", w)),
+    },
+    Field {
+        name: "condition",
+        value: |d| d.condition.clone(),
+    },
+];
+
+/// Fields of a forecast day, available for every other data kind.
+pub const DAILY_FIELDS: &[Field<DailySummary>] = &[
+    Field {
+        name: "date",
+        value: |d| Some(d.date.to_string()),
+    },
+    Field {
+        name: "min_temp",
+        value: |d| number(d.min_c),
+    },
+    Field {
+        name: "max_temp",
+        value: |d| number(d.max_c),
+    },
+    Field {
+        name: "precipitation",
+        value: |d| number(d.precip_mm),
+    },
+    Field {
+        name: "precip_chance",
+        value: |d| d.precip_chance.map(|p| format!("{:.0}", p)),
+    },
+    Field {
+        name: "wind_speed",
+        value: |d| number(d.max_wind_kph),
+    },
+    Field {
+        name: "condition",
+        value: |d| d.condition.clone(),
+    },
+];
+
+/// Whether a data kind exposes [`CURRENT_FIELDS`] or [`DAILY_FIELDS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldSet {
+    Current,
+    Daily,
+}
+
+impl FieldSet {
+    /// "now" exposes the current conditions, other kinds forecast days.
+    pub fn for_kind(kind: &str) -> Self {
+        if kind == "now" {
+            Self::Current
+        } else {
+            Self::Daily
+        }
+    }
+
+    /// Names of the fields in this set, in registry order.
+    pub fn names(self) -> Vec<&'static str> {
+        match self {
+            Self::Current => CURRENT_FIELDS.iter().map(|f| f.name).collect(),
+            Self::Daily => DAILY_FIELDS.iter().map(|f| f.name).collect(),
+        }
+    }
+}
+
+/// The fields requested with `get --fields`, in the order given.
+///
+/// A field named twice is printed twice, so the n-th value always belongs
+/// to the n-th name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub fields: Vec<String>,
+    pub inline: bool,
+}
+
+impl Selection {
+    /// Checks that every field exists for the data kind.
+    ///
+    /// # Errors
+    /// Returns an error naming the first unknown field and listing the
+    /// fields of each data kind.
+    pub fn check(&self, kind: &str) -> anyhow::Result<()> {
+        let names = FieldSet::for_kind(kind).names();
+
+        match self.fields.iter().find(|f| !names.contains(&f.as_str())) {
+            Some(unknown) => Err(anyhow::anyhow!(
+                "unknown field '{}' for '{}' data; available fields:\n  now: {}\n  forecast and other kinds: {}",
+                unknown,
+                kind,
+                FieldSet::Current.names().join(", "),
+                FieldSet::Daily.names().join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Renders the selected values of `data` for the data kind.
+    ///
+    /// Values are printed one per line, or space-separated on one line with
+    /// [`inline`](Self::inline). Daily fields give one group per forecast
+    /// day. Values the provider does not report are shown as `-`. Fields
+    /// are expected to have passed [`check`](Self::check); unknown ones
+    /// are also shown as `-`.
+    pub fn render(&self, kind: &str, data: &WeatherData) -> String {
+        let groups: Vec<Vec<String>> = match FieldSet::for_kind(kind) {
+            FieldSet::Current => vec![self.values(CURRENT_FIELDS, data)],
+            FieldSet::Daily => data
+                .forecast
+                .iter()
+                .map(|day| self.values(DAILY_FIELDS, day))
+                .collect(),
+        };
+
+        let separator = if self.inline { " " } else { "\n" };
+        groups
+            .iter()
+            .map(|values| format!("{}\n", values.join(separator)))
+            .collect()
+    }
+
+    fn values<T>(&self, registry: &[Field<T>], item: &T) -> Vec<String> {
+        self.fields
+            .iter()
+            .map(|name| {
+                registry
+                    .iter()
+                    .find(|f| f.name == name)
+                    .and_then(|f| (f.value)(item))
+                    .unwrap_or("-".into())
+            })
+            .collect()
+    }
+}
+
+fn number(value: Option<f64>) -> Option<String> {
+    value.map(|v| format!("{:.1}", v))
+}
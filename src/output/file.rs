@@ -0,0 +1,116 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::OutputFormat;
+
+/// Name of the field `--append` adds to every JSON line.
+pub const TIMESTAMP_FIELD: &str = "timestamp";
+
+/// Where `get --out` writes.
+///
+/// - `Stdout`: no `--out`, or `--out -`,
+/// - `File`: the file at `path`, replaced on every run, or added to with
+///   `append`, see [`write`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    Stdout,
+    File { path: PathBuf, append: bool },
+}
+
+impl Destination {
+    /// The destination of `--out` and `--append`.
+    pub fn new(out: Option<PathBuf>, append: bool) -> Self {
+        match out {
+            Some(path) if path != Path::new("-") => Self::File { path, append },
+            _ => Self::Stdout,
+        }
+    }
+
+    /// Whether output goes to a file, and so is written without colors.
+    pub fn is_file(&self) -> bool {
+        matches!(self, Self::File { .. })
+    }
+}
+
+/// Writes `rendered` output of `format` to `destination`.
+///
+/// A file is replaced, or with `append` added to:
+///
+/// - `json` becomes a single compact line with a [`TIMESTAMP_FIELD`] of
+///   `now`, see [`json_line`], so the file holds JSON lines,
+/// - `csv` keeps its header only while the file is empty,
+/// - anything else (text, tables, templates) is added as it is.
+///
+/// `format` is `None` for templates and field selections.
+///
+/// # Errors
+/// Returns an error naming the path if the file cannot be opened or
+/// written, or if `json` output to append is not JSON.
+pub fn write(
+    destination: &Destination,
+    rendered: &[u8],
+    format: Option<OutputFormat>,
+    now: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let (path, append) = match destination {
+        Destination::Stdout => {
+            let mut out = io::stdout();
+            out.write_all(rendered)?;
+            return Ok(out.flush()?);
+        }
+        Destination::File { path, append } => (path, *append),
+    };
+    let io_error = |e: io::Error| anyhow!("cannot write {}: {}", path.display(), e);
+
+    let mut options = OpenOptions::new();
+    match append {
+        true => options.create(true).append(true),
+        false => options.create(true).write(true).truncate(true),
+    };
+    let mut file = options.open(path).map_err(io_error)?;
+
+    let contents = match format {
+        Some(OutputFormat::Json) if append => {
+            let text = String::from_utf8_lossy(rendered);
+            json_line(&text, now)
+                .map_err(|e| anyhow!("cannot append to {}: {}", path.display(), e))?
+        }
+        Some(OutputFormat::Csv) if append && file.metadata().map_err(io_error)?.len() > 0 => {
+            let text = String::from_utf8_lossy(rendered);
+            text.split_once('\n')
+                .map_or(String::new(), |(_header, rows)| rows.to_string())
+        }
+        _ => String::from_utf8_lossy(rendered).into_owned(),
+    };
+
+    file.write_all(contents.as_bytes()).map_err(io_error)?;
+    file.flush().map_err(io_error)
+}
+
+/// `json` as one compact line with a [`TIMESTAMP_FIELD`] of `now`.
+///
+/// The field is added to an object; any other value is wrapped as
+/// `{"timestamp": …, "data": value}`.
+///
+/// # Errors
+/// Returns an error if `json` is not a JSON value.
+pub fn json_line(json: &str, now: DateTime<Utc>) -> anyhow::Result<String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| anyhow!("the output is not JSON: {}", e))?;
+    let timestamp = Value::String(now.format("%Y-%m-%dT%H:%M:%SZ").to_string());
+
+    let line = match value {
+        Value::Object(mut object) => {
+            object.insert(TIMESTAMP_FIELD.into(), timestamp);
+            Value::Object(object)
+        }
+        other => serde_json::json!({ "data": other, TIMESTAMP_FIELD: timestamp }),
+    };
+
+    Ok(format!("{}\n", line))
+}
@@ -0,0 +1,219 @@
+use std::sync::OnceLock;
+
+use crate::units::Units;
+use crate::weather::WeatherData;
+
+use super::color::Icons;
+use super::{day_label, degrees, partial_note, speed, Palette};
+
+/// Width assumed when the output is not a terminal, or its width is unknown.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Width set by [`use_width`].
+static WIDTH: OnceLock<usize> = OnceLock::new();
+
+/// Makes the forecast tables of this process fit `width` columns; the first
+/// call wins.
+pub fn use_width(width: usize) {
+    let _ = WIDTH.set(width);
+}
+
+/// The width set with [`use_width`], else [`DEFAULT_WIDTH`].
+pub fn width() -> usize {
+    WIDTH.get().copied().unwrap_or(DEFAULT_WIDTH)
+}
+
+/// The width of the terminal on stdout, else [`DEFAULT_WIDTH`].
+pub fn terminal_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(width, _)| usize::from(width.0))
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Characters the lines of a grid are drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Borders {
+    /// Unicode box-drawing characters, `┌─┬─┐`.
+    Unicode,
+    /// `+`, `-`, and `|`, for terminals without box drawing, the `--ascii`
+    /// flag.
+    Ascii,
+}
+
+impl Borders {
+    /// The borders going with `palette`: ASCII with its ASCII glyphs.
+    pub fn of(palette: Palette) -> Self {
+        match palette.icons {
+            Icons::Ascii => Self::Ascii,
+            Icons::None | Icons::Emoji => Self::Unicode,
+        }
+    }
+
+    /// The horizontal line, the vertical line, and the joints of the top,
+    /// middle, and bottom rules, each left, inner, and right.
+    fn chars(self) -> (char, char, [char; 3], [char; 3], [char; 3]) {
+        match self {
+            Self::Unicode => ('─', '│', ['┌', '┬', '┐'], ['├', '┼', '┤'], ['└', '┴', '┘']),
+            Self::Ascii => ('-', '|', ['+'; 3], ['+'; 3], ['+'; 3]),
+        }
+    }
+
+    /// What ends a condition text cut to fit its column.
+    fn ellipsis(self) -> &'static str {
+        match self {
+            Self::Unicode => "…",
+            Self::Ascii => "...",
+        }
+    }
+}
+
+/// Renders the forecast days of `data` as a grid under a `city` line, one
+/// column per day and one row per value, fitting `width` columns:
+///
+/// ```text
+/// Kyiv
+/// ┌───────────┬───────────────┬────────────┐
+/// │ Date      │ 2024-06-10    │ 2024-06-11 │
+/// ├───────────┼───────────────┼────────────┤
+/// │ Condition │ Partly cloudy │ Sunny      │
+/// │ High °C   │          18.3 │       16.8 │
+/// ```
+///
+/// Column widths follow their content. Condition texts too long for the
+/// width are cut, ending with an ellipsis; `None` when the days do not fit
+/// even so, for the vertical table instead.
+///
+/// Only the header row and temperatures are styled by `palette`, and conditions
+/// get no glyphs, so the columns stay aligned.
+pub fn render_forecast(
+    city: &str,
+    data: &WeatherData,
+    palette: Palette,
+    units: Units,
+    width: usize,
+    borders: Borders,
+) -> Option<String> {
+    let symbol = units.temp_symbol();
+    let labels = [
+        "Date".to_string(),
+        "Condition".to_string(),
+        format!("High {}", symbol),
+        format!("Low {}", symbol),
+        "Rain %".to_string(),
+        "Wind".to_string(),
+    ];
+    let columns: Vec<[String; 6]> = data
+        .forecast
+        .iter()
+        .map(|day| {
+            [
+                day_label(day),
+                day.condition.clone().unwrap_or("-".into()),
+                degrees(day.max_c, units),
+                degrees(day.min_c, units),
+                day.precip_chance
+                    .map(|p| format!("{:.0}", p))
+                    .unwrap_or("-".into()),
+                day.max_wind_kph
+                    .map(|kph| speed(kph, units))
+                    .unwrap_or("-".into()),
+            ]
+        })
+        .collect();
+
+    // Every column but the conditions keeps its width; the conditions share
+    // what is left, the longest cut first.
+    let len = |text: &String| text.chars().count();
+    let label_width = labels.iter().map(len).max().unwrap_or(0);
+    let fixed: Vec<usize> = columns
+        .iter()
+        .map(|cells| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(row, _)| *row != 1)
+                .map(|(_, cell)| len(cell))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line_width =
+        |widths: &[usize]| 1 + label_width + 3 + widths.iter().map(|w| w + 3).sum::<usize>();
+    let widths_under = |cap: usize| -> Vec<usize> {
+        columns
+            .iter()
+            .zip(&fixed)
+            .map(|(cells, &fixed)| fixed.max(len(&cells[1]).min(cap)))
+            .collect()
+    };
+    let longest = columns
+        .iter()
+        .map(|cells| len(&cells[1]))
+        .max()
+        .unwrap_or(0);
+    let widths = (0..=longest)
+        .rev()
+        .map(widths_under)
+        .find(|widths| line_width(widths) <= width)?;
+
+    let (horizontal, vertical, top, middle, bottom) = borders.chars();
+    let rule = |[left, inner, right]: [char; 3]| {
+        let mut line = String::from(left);
+        for (i, w) in [label_width].iter().chain(&widths).enumerate() {
+            if i > 0 {
+                line.push(inner);
+            }
+            line.extend(std::iter::repeat_n(horizontal, w + 2));
+        }
+        line.push(right);
+        line.push('\n');
+        line
+    };
+
+    let mut out = format!("{}\n", city);
+    out.push_str(&rule(top));
+    for (row, label) in labels.iter().enumerate() {
+        let pad = " ".repeat(label_width - len(label));
+        let mut line = match row {
+            0 => format!("{} {}{} ", vertical, palette.label(label), pad),
+            _ => format!("{} {}{} ", vertical, label, pad),
+        };
+        for (cells, &w) in columns.iter().zip(&widths) {
+            let cell = match row {
+                1 => cut(&cells[1], w, borders.ellipsis()),
+                _ => cells[row].clone(),
+            };
+            let pad = " ".repeat(w - len(&cell));
+            let cell = match row {
+                0 => format!("{}{}", palette.label(&cell), pad),
+                1 => format!("{}{}", cell, pad),
+                2 | 3 => match cell.replace(',', ".").parse::<f64>() {
+                    Ok(t) => format!("{}{}", pad, palette.temp(units.to_celsius(t), &cell)),
+                    Err(_) => format!("{}{}", pad, cell),
+                },
+                _ => format!("{}{}", pad, cell),
+            };
+            line.push_str(&format!("{} {} ", vertical, cell));
+        }
+        line.push(vertical);
+        line.push('\n');
+        out.push_str(&line);
+        if row == 0 {
+            out.push_str(&rule(middle));
+        }
+    }
+    out.push_str(&rule(bottom));
+    out.push_str(&partial_note(data));
+
+    Some(out)
+}
+
+/// `text` cut to `width` characters, ending with `ellipsis` when cut.
+fn cut(text: &str, width: usize, ellipsis: &str) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let keep = width.saturating_sub(ellipsis.chars().count());
+    let cut: String = text.chars().take(keep).collect();
+    format!("{}{}", cut.trim_end(), ellipsis)
+}
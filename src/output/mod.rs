@@ -0,0 +1,1522 @@
+pub mod color;
+pub mod compare;
+pub mod fields;
+pub mod file;
+pub mod grid;
+pub mod prometheus;
+pub mod report;
+pub mod status;
+pub mod template;
+
+use chrono::{DateTime, NaiveDateTime, NaiveTime, Utc};
+use clap::ValueEnum;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::aqi::{AirQuality, ValueSource, ALL_POLLUTANTS};
+use crate::comfort::{comfort, feels_like, FeelsLikeSource};
+use crate::condition::Condition;
+use crate::i18n::{self, decimal};
+use crate::providers::{ApiProvider, DataKind};
+use crate::series::{csv_escape, DailySummary};
+use crate::timezone::{time_display, Zone};
+use crate::trend::{Trend, STEADY_PRESSURE_MB, STEADY_TEMP_C};
+pub use crate::units::compass_point;
+use crate::units::{round, Units};
+use crate::weather::{
+    sort_alerts, Alert, Astronomy, HourlyForecast, MarineDay, Severity, SnowDay, UvCategory,
+    WeatherData,
+};
+pub use color::Palette;
+pub use compare::{
+    render_comparison, render_provider_json, render_provider_table, ProviderReading,
+};
+pub use fields::Selection;
+pub use template::Template;
+
+/// Columns of the `csv` format, in output order.
+///
+/// This header is stable across providers: temperatures are in °C,
+/// precipitation in mm, wind (the day's highest speed) in km/h, and the
+/// chance of precipitation (the day's highest) in %. A value the provider
+/// does not report is left empty.
+pub const CSV_COLUMNS: &[&str] = &[
+    "date",
+    "min_temp",
+    "max_temp",
+    "condition",
+    "precipitation",
+    "wind",
+    "precip_chance",
+];
+
+/// Characters of an alert description shown in text output without
+/// `--full`.
+pub const ALERT_DESCRIPTION_CHARS: usize = 240;
+
+/// Whether text output shows alert descriptions in full, see
+/// [`use_full_alerts`].
+static FULL_ALERTS: AtomicBool = AtomicBool::new(false);
+
+/// Shows alert descriptions in full in the text output of this process,
+/// instead of cutting them to [`ALERT_DESCRIPTION_CHARS`] (`get --full`).
+pub fn use_full_alerts(full: bool) {
+    FULL_ALERTS.store(full, Ordering::Relaxed);
+}
+
+/// Whether text output adds the block of [`render_details`], see
+/// [`use_detailed`].
+static DETAILED: AtomicBool = AtomicBool::new(false);
+
+/// Adds pressure, visibility, dew point, and cloud cover to the current
+/// conditions in the text output of this process (`get --detailed`).
+pub fn use_detailed(detailed: bool) {
+    DETAILED.store(detailed, Ordering::Relaxed);
+}
+
+/// Whether text output is the single line of [`render_minimal`], see
+/// [`use_minimal`].
+static MINIMAL: AtomicBool = AtomicBool::new(false);
+
+/// Shortens the text output of this process to a single line
+/// (`now --minimal`).
+pub fn use_minimal(minimal: bool) {
+    MINIMAL.store(minimal, Ordering::Relaxed);
+}
+
+/// How fetched data is printed.
+///
+/// - `json`: normalized JSON for `get`, the provider response elsewhere,
+///   see [`render_json`],
+/// - `porcelain`: stable `key<TAB>value` lines, see
+///   [`porcelain::render`](crate::porcelain::render),
+/// - `text`: a short summary for terminals, see [`render_text`],
+/// - `table`: aligned columns, one row per forecast day, see [`render_table`],
+/// - `csv`: one row per forecast day under a stable header, see [`render_csv`],
+/// - `prometheus`: gauges of the current conditions in the Prometheus text
+///   format, see [`prometheus::render`],
+/// - `markdown`: a GitHub-flavored Markdown section per location, see
+///   [`report::render_markdown`],
+/// - `html`: a self-contained HTML fragment per location, see
+///   [`report::render_html`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Porcelain,
+    Text,
+    Table,
+    Csv,
+    Prometheus,
+    Markdown,
+    Html,
+}
+
+/// What `get --format` asks for: one of the [`OutputFormat`]s by name, or a
+/// [`Template`] such as `"{city}: {temp}°C {condition}"`; or the values
+/// selected with `get --fields`.
+///
+/// A value is a template if it contains a brace, so `--format json` keeps
+/// selecting the JSON format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rendering {
+    Format(OutputFormat),
+    Template(Template),
+    Fields(Selection),
+}
+
+impl Default for Rendering {
+    fn default() -> Self {
+        Self::Format(OutputFormat::Text)
+    }
+}
+
+impl FromStr for Rendering {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('{') || s.contains('}') {
+            return Template::parse(s).map(Self::Template);
+        }
+
+        OutputFormat::from_str(s, true)
+            .map(Self::Format)
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "unknown format '{}': expected json, porcelain, text, table, csv, prometheus, markdown, html, or a template such as \"{{city}}: {{temp}}°C\"",
+                    s
+                )
+            })
+    }
+}
+
+/// Renders `data` as pretty-printed, provider-independent JSON.
+pub fn render_json(data: &WeatherData) -> String {
+    data.to_json()
+}
+
+/// Renders `data` as a short summary for terminals.
+///
+/// The first line names `city`, followed by one labelled line per current
+/// value the provider reported and one line per forecast day:
+///
+/// ```text
+/// Kyiv
+///   Condition:   Sunny
+///   Temperature: 24.0 °C
+///   Feels like:  24.8 °C
+///   Humidity:    36%
+///   Wind:        11.2 km/h NW
+///   UV index:    7 High
+///   2024-06-11   11.4 .. 16.8 °C  Patchy rain nearby  Rain: 70% (4.2 mm)
+/// ```
+///
+/// Hourly data gets one line per hour, in local time, instead of the days:
+///
+/// ```text
+///   Tue 14:00    21.3 °C   40%  Light rain shower
+/// ```
+///
+/// With [`use_detailed`], the current values are followed by those of
+/// [`render_details`]; with [`use_minimal`], everything is replaced by the
+/// line of [`render_minimal`].
+///
+/// With a [`trend`](WeatherData::trend), the temperature and pressure are
+/// followed by their direction and change since the earlier query, the
+/// pressure getting a line of its own:
+///
+/// ```text
+///   Temperature: 21.0 °C ↓ (3.0 °C colder than this time yesterday)
+///   Pressure:    1008 hPa ↓ (was 1013 2 hours ago)
+/// ```
+///
+/// With [`advice`](WeatherData::advice), each piece of it ends the
+/// output, e.g. `  Advice:      Take an umbrella`.
+///
+/// Alerts replace everything else, see [`render_alerts`], and so do air
+/// quality, see [`render_air`], sun and moon times, see
+/// [`render_astro`], the sea, see [`render_marine`], and snow, see
+/// [`render_snow`].
+pub fn render_text(city: &str, data: &WeatherData) -> String {
+    render_text_with(city, data, Palette::PLAIN)
+}
+
+/// [`render_text`] with labels and temperatures styled by `palette`, in
+/// the units set with [`use_units`](crate::units::use_units).
+pub fn render_text_with(city: &str, data: &WeatherData, palette: Palette) -> String {
+    render_text_in(city, data, palette, crate::units::units())
+}
+
+/// [`render_text_with`] in `units`: temperatures and wind speeds are
+/// converted from the metric `data`.
+pub fn render_text_in(city: &str, data: &WeatherData, palette: Palette, units: Units) -> String {
+    if MINIMAL.load(Ordering::Relaxed) {
+        return render_minimal(city, data, units);
+    }
+    let mut out = format!("{}\n", city);
+    if let Some(alerts) = &data.alerts {
+        let full = FULL_ALERTS.load(Ordering::Relaxed);
+        out.push_str(&render_alerts_in(alerts, full, palette, data.timezone));
+        return out;
+    }
+    if let Some(air) = &data.air {
+        out.push_str(&render_air(air, palette));
+        return out;
+    }
+    if let Some(astro) = &data.astro {
+        out.push_str(&render_astro(astro, palette));
+        return out;
+    }
+    if let Some(marine) = &data.marine {
+        out.push_str(&render_marine_in(marine, palette, units, data.timezone));
+        return out;
+    }
+    if let Some(snow) = &data.snow {
+        out.push_str(&render_snow(snow, palette, units));
+        return out;
+    }
+    if let Some(weekend) = &data.weekend {
+        let label = format!("{:<12}", "Verdict:");
+        out.push_str(&format!(
+            "  {} {}\n",
+            palette.label(&label),
+            weekend.verdict
+        ));
+    }
+    let mut line = |label: &str, value: Option<String>| {
+        if let Some(value) = value {
+            let label = format!("{:<12}", format!("{}:", label));
+            out.push_str(&format!("  {} {}\n", palette.label(&label), value));
+        }
+    };
+    let temp = |t: f64| {
+        palette.temp(
+            t,
+            &format!("{} {}", degrees(Some(t), units), units.temp_symbol()),
+        )
+    };
+
+    line(
+        "Condition",
+        data.condition
+            .as_deref()
+            .map(|c| palette.condition(condition_kind(data), c)),
+    );
+    let trend = data.trend.as_ref();
+    line(
+        "Temperature",
+        data.temp_c
+            .map(|t| temp(t) + &trend.map(|t| temp_trend(t, units)).unwrap_or_default()),
+    );
+    line(
+        "Feels like",
+        feels_like(data).map(|f| temp(f.temp_c) + &source_note(f.source)),
+    );
+    line("Humidity", data.humidity.map(|h| format!("{:.0}%", h)));
+    // The detailed block has the pressure anyway.
+    if !DETAILED.load(Ordering::Relaxed) && trend.is_some_and(|t| t.pressure_mb.is_some()) {
+        line(
+            "Pressure",
+            data.pressure_mb.map(|p| pressure(p, trend, units)),
+        );
+    }
+    line("Wind", wind(data, units));
+    line("UV index", uv(data));
+    line(
+        "Updated",
+        data.updated.map(|t| local_time(t, data.timezone)),
+    );
+    if DETAILED.load(Ordering::Relaxed) {
+        out.push_str(&render_details(data, palette, units));
+    }
+
+    for hour in &data.hourly {
+        let line = format!(
+            "  {} {} {}  {:>4}  {}",
+            palette.label(&format!("{:<12}", local_hour(hour, data.timezone))),
+            match hour.temp_c {
+                Some(t) => palette.temp(t, &degrees(hour.temp_c, units)),
+                None => degrees(hour.temp_c, units),
+            },
+            units.temp_symbol(),
+            chance(hour.precip_chance),
+            hour.condition
+                .as_deref()
+                .map(|c| palette.condition(Condition::from_text(c), c))
+                .unwrap_or_default()
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    // The days of an hourly response only repeat its hours.
+    for day in data.forecast.iter().filter(|_| data.hourly.is_empty()) {
+        let bound = |v: Option<f64>| match v {
+            Some(t) => palette.temp(t, &degrees(v, units)),
+            None => degrees(v, units),
+        };
+        let line = format!(
+            "  {} {} .. {} {}  {}  {}",
+            palette.label(&format!("{:<12}", day_label(day))),
+            bound(day.min_c),
+            bound(day.max_c),
+            units.temp_symbol(),
+            day.condition
+                .as_deref()
+                .map(|c| palette.condition(Condition::from_text(c), c))
+                .unwrap_or_default(),
+            rain(day)
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    if data.hourly.is_empty() {
+        out.push_str(&partial_note(data));
+    }
+    if data.weekend.as_ref().is_some_and(|w| w.under_way) {
+        out.push_str("  The weekend is under way: only the rest of it is shown\n");
+    }
+    for advice in data.advice.iter().flatten() {
+        let label = format!("{:<12}", "Advice:");
+        out.push_str(&format!("  {} {}\n", palette.label(&label), advice.message));
+    }
+
+    out
+}
+
+/// Renders the current conditions of `data` as a single line in `units`,
+/// e.g. `Kyiv: 24.0 °C, Sunny`; the condition is left out when the provider
+/// does not report one.
+pub fn render_minimal(city: &str, data: &WeatherData, units: Units) -> String {
+    let mut line = format!(
+        "{}: {} {}",
+        city,
+        degrees(data.temp_c, units),
+        units.temp_symbol()
+    );
+    if let Some(condition) = &data.condition {
+        line.push_str(&format!(", {}", condition));
+    }
+    line + "\n"
+}
+
+/// The detailed view's lines of the current conditions of `data`, in
+/// `units`: pressure in hPa, or inHg for imperial, visibility in km or
+/// miles, the dew point, the [`comfort`](crate::comfort::comfort), and
+/// cloud cover. Values the provider did not report are left out; with a
+/// [`trend`](WeatherData::trend), the pressure is followed by its change,
+/// see [`render_text`].
+///
+/// ```text
+///   Pressure:    1017 hPa
+///   Visibility:  10.0 km
+///   Dew point:   8.0 °C
+///   Comfort:     pleasant
+///   Cloud cover: 0%
+/// ```
+pub fn render_details(data: &WeatherData, palette: Palette, units: Units) -> String {
+    let distance = |km: f64| {
+        format!(
+            "{} {}",
+            decimal(round(units.distance(km), 1), 1),
+            units.distance_symbol()
+        )
+    };
+    let temp = |t: f64| {
+        palette.temp(
+            t,
+            &format!("{} {}", degrees(Some(t), units), units.temp_symbol()),
+        )
+    };
+
+    [
+        (
+            "Pressure",
+            data.pressure_mb
+                .map(|p| pressure(p, data.trend.as_ref(), units)),
+        ),
+        ("Visibility", data.visibility_km.map(distance)),
+        ("Dew point", data.dew_point_c.map(temp)),
+        ("Comfort", comfort(data).map(|c| c.to_string())),
+        ("Cloud cover", data.cloud.map(|c| format!("{:.0}%", c))),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| {
+        let label = format!("{:<12}", format!("{}:", label));
+        Some(format!("  {} {}\n", palette.label(&label), value?))
+    })
+    .collect()
+}
+
+/// The note after a feels-like temperature computed by wapp, e.g.
+/// ` (heat index)`; empty for the provider's own.
+fn source_note(source: FeelsLikeSource) -> String {
+    source
+        .note()
+        .map(|note| format!(" ({})", note))
+        .unwrap_or_default()
+}
+
+/// `hpa` in `units`, e.g. `1017 hPa` or `30.03 inHg`, followed by its
+/// change since the earlier query of `trend`, if known.
+fn pressure(hpa: f64, trend: Option<&Trend>, units: Units) -> String {
+    let decimals = if units == Units::Imperial { 2 } else { 0 };
+    let value = |hpa: f64| decimal(units.pressure(hpa), decimals);
+    let mut out = format!("{} {}", value(hpa), units.pressure_symbol());
+    if let Some((trend, change)) = trend.and_then(|t| Some((t, t.pressure_mb?))) {
+        out.push_str(&format!(
+            " {} (was {} {})",
+            change.arrow(STEADY_PRESSURE_MB),
+            value(change.was),
+            trend.ago(Utc::now())
+        ));
+    }
+    out
+}
+
+/// The change of the temperature since the earlier query of `trend` in
+/// `units`, e.g. ` ↓ (3.0 °C colder than 2 hours ago)`; empty if unknown.
+fn temp_trend(trend: &Trend, units: Units) -> String {
+    let Some(change) = trend.temp_c else {
+        return String::new();
+    };
+    let ago = trend.ago(Utc::now());
+    let arrow = change.arrow(STEADY_TEMP_C);
+    if change.delta.abs() < STEADY_TEMP_C {
+        return format!(" {} (about the same as {})", arrow, ago);
+    }
+
+    // A difference scales like a temperature, without its offset.
+    let delta = units.temp(change.delta.abs()) - units.temp(0.0);
+    format!(
+        " {} ({} {} {} than {})",
+        arrow,
+        decimal(round(delta, 1), 1),
+        units.temp_symbol(),
+        if change.delta > 0.0 {
+            "warmer"
+        } else {
+            "colder"
+        },
+        ago
+    )
+}
+
+/// The lines of `alerts` in text output, in their order: severity and
+/// headline (the event without one), the time in effect in the machine's
+/// time zone, and the description, cut to [`ALERT_DESCRIPTION_CHARS`] on one
+/// line unless `full`. Severe and extreme alerts are styled as warnings.
+///
+/// ```text
+///   Severe       Flood warning issued May 1 at 10:00AM
+///                Wed 01 May 13:00 – Thu 02 May 13:00
+///                River levels are rising…
+/// ```
+///
+/// Without alerts this is a single `No active alerts` line.
+pub fn render_alerts(alerts: &[Alert], full: bool, palette: Palette) -> String {
+    render_alerts_in(alerts, full, palette, None)
+}
+
+/// [`render_alerts`] with the time in effect in `zone`, the location's,
+/// as the [time display](crate::timezone::time_display) says.
+pub fn render_alerts_in(
+    alerts: &[Alert],
+    full: bool,
+    palette: Palette,
+    zone: Option<Zone>,
+) -> String {
+    if alerts.is_empty() {
+        return "  No active alerts\n".to_string();
+    }
+
+    let indent = " ".repeat(15);
+    let mut out = String::new();
+    for alert in alerts {
+        let severity = format!("{:<12}", alert.severity.label());
+        let title = alert.headline.as_deref().unwrap_or(&alert.event);
+        let line = match alert.severity {
+            Severity::Severe | Severity::Extreme => {
+                format!(
+                    "  {} {}",
+                    palette.warning(&severity),
+                    palette.warning(title)
+                )
+            }
+            _ => format!("  {} {}", palette.label(&severity), title),
+        };
+        out.push_str(line.trim_end());
+        out.push('\n');
+
+        if let Some(period) = alert_period(alert, zone) {
+            out.push_str(&format!("{}{}\n", indent, period));
+        }
+        let Some(description) = &alert.description else {
+            continue;
+        };
+        if full {
+            for line in description.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                out.push_str(&format!("{}{}\n", indent, line));
+            }
+        } else {
+            let text = description.split_whitespace().collect::<Vec<_>>().join(" ");
+            out.push_str(&format!(
+                "{}{}\n",
+                indent,
+                cut(&text, ALERT_DESCRIPTION_CHARS)
+            ));
+        }
+    }
+
+    out
+}
+
+/// The lines of an air-quality report in text output: every index with
+/// its category and the scale it is on, the provider's first, then the
+/// concentration of every pollutant the provider reported.
+///
+/// ```text
+///   Air quality: 2 Fair (OpenWeatherMap index, 1–5)
+///                29 Low (European CAQI, 0–100+, computed)
+///   PM2.5:       12.2 µg/m³
+/// ```
+pub fn render_air(air: &AirQuality, palette: Palette) -> String {
+    let mut out = String::new();
+
+    for (i, index) in air.indices.iter().enumerate() {
+        let label = if i == 0 { "Air quality:" } else { "" };
+        out.push_str(&format!(
+            "  {} {} {} ({}{})\n",
+            palette.label(&format!("{:<12}", label)),
+            index.value,
+            index.category,
+            index.scale.label(),
+            match index.source {
+                ValueSource::Provider => "",
+                ValueSource::Computed => ", computed",
+            }
+        ));
+    }
+    for pollutant in ALL_POLLUTANTS {
+        if let Some(value) = air.pollutants.get(pollutant) {
+            let label = format!("{:<12}", format!("{}:", pollutant.label()));
+            out.push_str(&format!(
+                "  {} {} µg/m³\n",
+                palette.label(&label),
+                decimal(value, 1)
+            ));
+        }
+    }
+
+    out
+}
+
+/// The lines of sun and moon times in text output, in the location's
+/// local time, or as the [time display](crate::timezone::time_display)
+/// says; what the provider did not report is left out.
+///
+/// ```text
+///   Date:        2024-06-10 (Europe/Kyiv)
+///   Sunrise:     04:46
+///   Sunset:      21:11
+///   Day length:  16h 25m
+///   Moon phase:  Waxing Gibbous (64%)
+/// ```
+pub fn render_astro(astro: &Astronomy, palette: Palette) -> String {
+    let mut out = String::new();
+    let mut line = |label: &str, value: Option<String>| {
+        if let Some(value) = value {
+            let label = format!("{:<12}", format!("{}:", label));
+            out.push_str(&format!(
+                "  {} {}
+",
+                palette.label(&label),
+                value
+            ));
+        }
+    };
+    let clock = |time: Option<NaiveTime>| astro_clock(astro, time);
+    let utc = if time_display().utc {
+        ", times in UTC"
+    } else {
+        ""
+    };
+
+    line(
+        "Date",
+        astro.date.map(|date| match &astro.timezone {
+            Some(zone) => format!("{} ({}{})", i18n::date(date), zone, utc),
+            None => i18n::date(date),
+        }),
+    );
+    line("Sunrise", clock(astro.sunrise));
+    line("Sunset", clock(astro.sunset));
+    line("Day length", astro.day_length_min.map(hours_minutes));
+    line("Moonrise", clock(astro.moonrise));
+    line("Moonset", clock(astro.moonset));
+    line("Moon phase", moon(astro));
+
+    out
+}
+
+/// The lines of a marine forecast in text output: per day the highest
+/// waves and swell and the mean water temperature, then the day's tides
+/// in the location's local time. Water temperatures are in `units`.
+///
+/// ```text
+///   2024-06-10   Waves up to 1.2 m, swell up to 0.9 m, water 16.4 °C
+///                High tide  03:12  4.1 m
+///                Low tide   09:25  1.3 m
+/// ```
+pub fn render_marine(days: &[MarineDay], palette: Palette, units: Units) -> String {
+    render_marine_in(days, palette, units, None)
+}
+
+/// [`render_marine`] with the tides of the location's `zone` shown as the
+/// [time display](crate::timezone::time_display) says.
+pub fn render_marine_in(
+    days: &[MarineDay],
+    palette: Palette,
+    units: Units,
+    zone: Option<Zone>,
+) -> String {
+    let indent = " ".repeat(15);
+    let mut out = String::new();
+
+    for day in days {
+        let sea: Vec<String> = [
+            day.max_wave_m
+                .map(|m| format!("waves up to {} m", decimal(m, 1))),
+            day.max_swell_m
+                .map(|m| format!("swell up to {} m", decimal(m, 1))),
+            day.water_temp_c.map(|t| {
+                let water = format!("{} {}", degrees(Some(t), units), units.temp_symbol());
+                format!("water {}", palette.temp(t, &water))
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let mut summary = sea.join(", ");
+        if let Some(first) = summary.get(..1) {
+            summary.replace_range(..1, &first.to_uppercase());
+        }
+        let line = format!(
+            "  {} {}",
+            palette.label(&format!("{:<12}", i18n::date(day.date))),
+            summary
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+
+        for tide in &day.tides {
+            let line = format!(
+                "{}{:<10} {}  {}",
+                indent,
+                tide.kind.label(),
+                wall_clock(tide.time, zone, "%H:%M"),
+                tide.height_m
+                    .map(|m| format!("{} m", decimal(m, 1)))
+                    .unwrap_or_default()
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// The lines of a snow forecast in text output: per day the night low
+/// first, as it decides how the snow holds, then the day's lowest
+/// temperature, snowfall with its chance, the highest wind, and the
+/// condition. Temperatures and wind speeds are in `units`.
+///
+/// ```text
+///   2024-12-10   Night low -8.2 °C, min -9.0 °C, snow 12.5 cm (85%), wind up to 25.2 km/h  Heavy snow
+/// ```
+pub fn render_snow(days: &[SnowDay], palette: Palette, units: Units) -> String {
+    let mut out = String::new();
+    let temp = |t: f64| {
+        let text = format!("{} {}", degrees(Some(t), units), units.temp_symbol());
+        palette.temp(t, &text)
+    };
+
+    for day in days {
+        let snow = match (day.snow_cm, day.snow_chance) {
+            (Some(cm), Some(chance)) => {
+                Some(format!("snow {} cm ({:.0}%)", decimal(cm, 1), chance))
+            }
+            (Some(cm), None) => Some(format!("snow {} cm", decimal(cm, 1))),
+            (None, Some(chance)) => Some(format!("snow {:.0}%", chance)),
+            (None, None) => None,
+        };
+        let parts: Vec<String> = [
+            day.night_low_c.map(|t| format!("night low {}", temp(t))),
+            day.min_c.map(|t| format!("min {}", temp(t))),
+            snow,
+            day.max_wind_kph
+                .map(|kph| format!("wind up to {}", speed(kph, units))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let mut summary = parts.join(", ");
+        if let Some(first) = summary.get(..1) {
+            summary.replace_range(..1, &first.to_uppercase());
+        }
+        let line = format!(
+            "  {} {}  {}",
+            palette.label(&format!("{:<12}", i18n::date(day.date))),
+            summary,
+            day.condition
+                .as_deref()
+                .map(|c| palette.condition(Condition::from_text(c), c))
+                .unwrap_or_default()
+        );
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Renders `data` as a table under a `city` line.
+///
+/// A forecast gets one column per day, see [`grid::render_forecast`], in
+/// the width set with [`grid::use_width`], or one row per day when the days
+/// do not fit. Hourly data gets one row per hour in local time, alerts one
+/// row each, air quality one row per index, sun and moon times a single row
+/// of their day, a marine forecast one row per hour in the location's local
+/// time, a snow forecast one row per day with the night low first; other
+/// data gets a single row of current values. Numeric columns are
+/// right-aligned, text left-aligned, and missing values shown as `-`.
+///
+/// ```text
+/// Kyiv
+/// Date        Min °C  Max °C  Rain %  Precip mm  Condition
+/// 2024-06-10    10.2    18.3      20        0.4  Partly cloudy
+/// ```
+pub fn render_table(city: &str, data: &WeatherData) -> String {
+    render_table_with(city, data, Palette::PLAIN)
+}
+
+/// [`render_table`] with the header and temperatures styled by `palette`,
+/// in the units set with [`use_units`](crate::units::use_units).
+pub fn render_table_with(city: &str, data: &WeatherData, palette: Palette) -> String {
+    render_table_in(city, data, palette, crate::units::units())
+}
+
+/// [`render_table_with`] in `units`: temperatures and wind speeds are
+/// converted from the metric `data`.
+pub fn render_table_in(city: &str, data: &WeatherData, palette: Palette, units: Units) -> String {
+    // The weekend's days, with its verdict below them
+    if let Some(weekend) = &data.weekend {
+        let days = match data.forecast.is_empty() {
+            true => format!("{}\n", city),
+            false => render_table_in(
+                city,
+                &WeatherData {
+                    weekend: None,
+                    ..data.clone()
+                },
+                palette,
+                units,
+            ),
+        };
+        return format!("{}Verdict: {}\n", days, weekend.verdict);
+    }
+
+    // Each layout names its columns and which of them hold numbers; the
+    // temperature columns are the numeric ones labelled in °C, which is
+    // replaced by the symbol of `units` below.
+    let (header, numeric, rows): (Vec<&str>, Vec<bool>, Vec<Vec<String>>) =
+        if !data.hourly.is_empty() {
+            (
+                vec!["Time", "Temp °C", "Precip %", "Precip mm", "Condition"],
+                vec![false, true, true, true, false],
+                data.hourly
+                    .iter()
+                    .map(|hour| {
+                        vec![
+                            local_hour(hour, data.timezone),
+                            degrees(hour.temp_c, units),
+                            hour.precip_chance
+                                .map(|p| format!("{:.0}", p))
+                                .unwrap_or("-".into()),
+                            number(hour.precip_mm),
+                            hour.condition
+                                .as_deref()
+                                .map(|c| palette.condition(Condition::from_text(c), c))
+                                .unwrap_or("-".into()),
+                        ]
+                    })
+                    .collect(),
+            )
+        } else if let Some(alerts) = &data.alerts {
+            if alerts.is_empty() {
+                return format!("{}\nNo active alerts\n", city);
+            }
+            let time = |t: Option<DateTime<Utc>>| {
+                t.map(|t| local_time(t, data.timezone))
+                    .unwrap_or("-".into())
+            };
+            (
+                vec!["Severity", "Starts", "Ends", "Event", "Headline"],
+                vec![false; 5],
+                alerts
+                    .iter()
+                    .map(|alert| {
+                        vec![
+                            alert.severity.label().to_string(),
+                            time(alert.starts),
+                            time(alert.ends),
+                            alert.event.clone(),
+                            alert.headline.clone().unwrap_or("-".into()),
+                        ]
+                    })
+                    .collect(),
+            )
+        } else if let Some(air) = &data.air {
+            (
+                vec!["Index", "Range", "Value", "Category", "Source"],
+                vec![false, false, true, false, false],
+                air.indices
+                    .iter()
+                    .map(|index| {
+                        let label = index.scale.label();
+                        let (name, range) = label.split_once(", ").unwrap_or((label, "-"));
+                        vec![
+                            name.to_string(),
+                            range.to_string(),
+                            index.value.to_string(),
+                            index.category.to_string(),
+                            match index.source {
+                                ValueSource::Provider => "provider".into(),
+                                ValueSource::Computed => "computed".into(),
+                            },
+                        ]
+                    })
+                    .collect(),
+            )
+        } else if let Some(astro) = &data.astro {
+            let clock = |t: Option<NaiveTime>| astro_clock(astro, t);
+            (
+                vec!["Date", "Sunrise", "Sunset", "Day length", "Moon phase"],
+                vec![false; 5],
+                vec![vec![
+                    astro.date.map(|d| d.to_string()).unwrap_or("-".into()),
+                    clock(astro.sunrise).unwrap_or("-".into()),
+                    clock(astro.sunset).unwrap_or("-".into()),
+                    astro
+                        .day_length_min
+                        .map(hours_minutes)
+                        .unwrap_or("-".into()),
+                    moon(astro).unwrap_or("-".into()),
+                ]],
+            )
+        } else if let Some(marine) = &data.marine {
+            (
+                vec![
+                    "Time",
+                    "Wave m",
+                    "Swell m",
+                    "Swell from",
+                    "Period s",
+                    "Water °C",
+                ],
+                vec![false, true, true, false, true, true],
+                marine
+                    .iter()
+                    .flat_map(|day| &day.hours)
+                    .map(|hour| {
+                        vec![
+                            wall_clock(hour.time, data.timezone, "%a %H:%M"),
+                            number(hour.wave_m),
+                            number(hour.swell_m),
+                            hour.swell_degree
+                                .map(|d| compass_point(d).to_string())
+                                .unwrap_or("-".into()),
+                            hour.swell_period_s
+                                .map(|s| format!("{:.0}", s))
+                                .unwrap_or("-".into()),
+                            degrees(hour.water_temp_c, units),
+                        ]
+                    })
+                    .collect(),
+            )
+        } else if let Some(snow) = &data.snow {
+            (
+                vec![
+                    "Date",
+                    "Night °C",
+                    "Min °C",
+                    "Snow cm",
+                    "Snow %",
+                    "Wind",
+                    "Condition",
+                ],
+                vec![false, true, true, true, true, false, false],
+                snow.iter()
+                    .map(|day| {
+                        vec![
+                            i18n::date(day.date),
+                            degrees(day.night_low_c, units),
+                            degrees(day.min_c, units),
+                            number(day.snow_cm),
+                            day.snow_chance
+                                .map(|p| format!("{:.0}", p))
+                                .unwrap_or("-".into()),
+                            day.max_wind_kph
+                                .map(|kph| speed(kph, units))
+                                .unwrap_or("-".into()),
+                            day.condition
+                                .as_deref()
+                                .map(|c| palette.condition(Condition::from_text(c), c))
+                                .unwrap_or("-".into()),
+                        ]
+                    })
+                    .collect(),
+            )
+        } else if data.forecast.is_empty() {
+            let mut header = vec!["Temp °C", "Feels °C", "Humidity %", "Wind", "Condition"];
+            let mut numeric = vec![true, true, true, false, false];
+            let mut row = vec![
+                degrees(data.temp_c, units),
+                degrees(feels_like(data).map(|f| f.temp_c), units),
+                data.humidity
+                    .map(|h| format!("{:.0}", h))
+                    .unwrap_or("-".into()),
+                wind(data, units).unwrap_or("-".into()),
+                data.condition
+                    .as_deref()
+                    .map(|c| palette.condition(condition_kind(data), c))
+                    .unwrap_or("-".into()),
+            ];
+            // Only where the provider has one, like the text line
+            if let Some(uv) = uv(data) {
+                header.push("UV");
+                numeric.push(false);
+                row.push(uv);
+            }
+            (header, numeric, vec![row])
+        } else {
+            let grid = grid::render_forecast(
+                city,
+                data,
+                palette,
+                units,
+                grid::width(),
+                grid::Borders::of(palette),
+            );
+            if let Some(grid) = grid {
+                return grid;
+            }
+            (
+                vec![
+                    "Date",
+                    "Min °C",
+                    "Max °C",
+                    "Rain %",
+                    "Precip mm",
+                    "Condition",
+                ],
+                vec![false, true, true, true, true, false],
+                data.forecast
+                    .iter()
+                    .map(|day| {
+                        vec![
+                            day_label(day),
+                            degrees(day.min_c, units),
+                            degrees(day.max_c, units),
+                            day.precip_chance
+                                .map(|p| format!("{:.0}", p))
+                                .unwrap_or("-".into()),
+                            number(day.precip_mm),
+                            day.condition
+                                .as_deref()
+                                .map(|c| palette.condition(Condition::from_text(c), c))
+                                .unwrap_or("-".into()),
+                        ]
+                    })
+                    .collect(),
+            )
+        };
+
+    let temperature: Vec<bool> = header.iter().map(|h| h.ends_with("°C")).collect();
+    let header: Vec<String> = header
+        .iter()
+        .map(|h| h.replace("°C", units.temp_symbol()))
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header[i].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let cell = |i: usize, value: &str| {
+        let pad = " ".repeat(widths[i] - value.chars().count());
+        let styled = match value.replace(',', ".").parse::<f64>() {
+            Ok(t) if temperature[i] => palette.temp(units.to_celsius(t), value),
+            _ => value.to_string(),
+        };
+        if numeric[i] {
+            format!("{}{}", pad, styled)
+        } else {
+            format!("{}{}", styled, pad)
+        }
+    };
+
+    let mut out = format!("{}\n", city);
+    let titles: Vec<String> = (0..header.len())
+        .map(|i| {
+            let pad = " ".repeat(widths[i] - header[i].chars().count());
+            let title = palette.label(&header[i]);
+            if numeric[i] {
+                format!("{}{}", pad, title)
+            } else {
+                format!("{}{}", title, pad)
+            }
+        })
+        .collect();
+    out.push_str(titles.join("  ").trim_end());
+    out.push('\n');
+    for row in &rows {
+        let line: Vec<String> = row.iter().enumerate().map(|(i, v)| cell(i, v)).collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    }
+    if data.hourly.is_empty() && data.marine.is_none() {
+        out.push_str(&partial_note(data));
+    }
+
+    out
+}
+
+/// Renders the forecast days of `data` as CSV: the [`CSV_COLUMNS`] header,
+/// then one row per day.
+///
+/// Data without a forecast yields only the header.
+pub fn render_csv(data: &WeatherData) -> String {
+    format!("{}{}", csv_header(), csv_rows(data))
+}
+
+/// The [`CSV_COLUMNS`] header line, including the newline.
+pub fn csv_header() -> String {
+    format!("{}\n", CSV_COLUMNS.join(","))
+}
+
+/// The rows of [`render_csv`] without the header.
+pub fn csv_rows(data: &WeatherData) -> String {
+    let num = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+
+    data.forecast
+        .iter()
+        .map(|day| {
+            let fields = [
+                day.date.to_string(),
+                num(day.min_c),
+                num(day.max_c),
+                csv_escape(day.condition.as_deref().unwrap_or_default()),
+                num(day.precip_mm),
+                num(day.max_wind_kph),
+                num(day.precip_chance),
+            ];
+            format!("{}\n", fields.join(","))
+        })
+        .collect()
+}
+
+/// The [`csv_header`] with a leading `city` column, for several cities.
+pub fn city_csv_header() -> String {
+    format!("city,{}", csv_header())
+}
+
+/// The [`csv_rows`] of `data` with `city` in a leading column.
+pub fn city_csv_rows(city: &str, data: &WeatherData) -> String {
+    let city = csv_escape(city);
+
+    csv_rows(data)
+        .lines()
+        .map(|row| format!("{},{}\n", city, row))
+        .collect()
+}
+
+/// Renders the normalized JSON of one data kind of a response.
+///
+/// # Errors
+/// Returns an error naming the kind and provider if the response cannot be
+/// normalized, pointing at `--raw`.
+pub fn normalized_json(
+    provider: &dyn ApiProvider,
+    kind: &str,
+    body: &str,
+) -> anyhow::Result<String> {
+    read(provider, kind, body)
+        .map(|data| render_json(&data))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot read '{}' data from '{}'; use --raw to print the provider response",
+                kind,
+                provider.name()
+            )
+        })
+}
+
+/// [`normalized_json`], or the response as it is when it cannot be read
+/// and extra `params` (`--param`) were sent, which may have changed its
+/// shape; a note on stderr names them.
+///
+/// # Errors
+/// Same as [`normalized_json`] when no `params` were sent.
+pub fn normalized_or_raw(
+    provider: &dyn ApiProvider,
+    kind: &str,
+    body: &str,
+    params: &[(String, String)],
+) -> anyhow::Result<String> {
+    match normalized_json(provider, kind, body) {
+        Err(_) if !params.is_empty() => {
+            let names: Vec<&str> = params.iter().map(|(name, _)| name.as_str()).collect();
+            eprintln!(
+                "Note: cannot read '{}' data from '{}', maybe because --param {} changed the response; printing the raw response",
+                kind,
+                provider.name(),
+                names.join(", ")
+            );
+            Ok(body.trim_end().to_string())
+        }
+        result => result,
+    }
+}
+
+/// Renders a response body as text, a table, CSV, Markdown, or HTML, see
+/// [`render_text`], [`render_table`], [`render_csv`],
+/// [`report::render_markdown`], and [`report::render_html`]; other formats
+/// are rendered as text.
+///
+/// A response that cannot be read, such as an API error, is returned
+/// unchanged after a warning on stderr, so nothing is lost; in Markdown
+/// and HTML it is put in a code block, escaped. For CSV only the header is
+/// returned, so the output stays parseable.
+pub fn summarize(
+    provider: &dyn ApiProvider,
+    city: &str,
+    kind: &str,
+    body: &str,
+    format: OutputFormat,
+) -> String {
+    summarize_with(provider, city, kind, body, format, Palette::PLAIN)
+}
+
+/// [`summarize`] with text, tables, and warnings styled by `palette`; CSV,
+/// Markdown, and HTML are never styled.
+pub fn summarize_with(
+    provider: &dyn ApiProvider,
+    city: &str,
+    kind: &str,
+    body: &str,
+    format: OutputFormat,
+    palette: Palette,
+) -> String {
+    match read(provider, kind, body) {
+        Some(data) => summarize_data(city, kind, &data, format, palette),
+        None if format == OutputFormat::Csv => {
+            eprintln!(
+                "{}",
+                palette.warning(&format!(
+                    "Warning: cannot read '{}' data from '{}'; no rows written",
+                    kind,
+                    provider.name()
+                ))
+            );
+            csv_header()
+        }
+        None => {
+            eprintln!(
+                "{}",
+                palette.warning(&format!(
+                    "Warning: cannot read '{}' data from '{}'; printing the raw response",
+                    kind,
+                    provider.name()
+                ))
+            );
+            match format {
+                OutputFormat::Markdown => report::markdown_code(body),
+                OutputFormat::Html => report::html_pre(body),
+                _ => format!("{}\n", body),
+            }
+        }
+    }
+}
+
+/// Renders `data`, already read from a `kind` response, as
+/// [`summarize_with`] does, e.g. with the [`trend`](WeatherData::trend) of
+/// `get --trend`.
+pub fn summarize_data(
+    city: &str,
+    kind: &str,
+    data: &WeatherData,
+    format: OutputFormat,
+    palette: Palette,
+) -> String {
+    match format {
+        OutputFormat::Table => render_table_with(&title(city, kind), data, palette),
+        OutputFormat::Csv => render_csv(data),
+        OutputFormat::Markdown => report::render_markdown(&title(city, kind), data),
+        OutputFormat::Html => report::render_html(&title(city, kind), data),
+        _ => render_text_with(&title(city, kind), data, palette),
+    }
+}
+
+/// Renders a response body through `template`, followed by a newline.
+///
+/// A response that cannot be read is returned unchanged after a warning on
+/// stderr, as in [`summarize`].
+pub fn render_template(
+    provider: &dyn ApiProvider,
+    city: &str,
+    kind: &str,
+    body: &str,
+    template: &Template,
+) -> String {
+    match read(provider, kind, body) {
+        Some(data) => format!("{}\n", template.render(city, &data)),
+        None => {
+            eprintln!(
+                "Warning: cannot read '{}' data from '{}'; printing the raw response",
+                kind,
+                provider.name()
+            );
+            format!("{}\n", body)
+        }
+    }
+}
+
+/// Renders the values of `selection` from a response body, see
+/// [`Selection::render`].
+///
+/// A response that cannot be read yields nothing after a warning on stderr,
+/// so scripts never mistake the body for a value.
+pub fn render_fields(
+    provider: &dyn ApiProvider,
+    kind: &str,
+    body: &str,
+    selection: &Selection,
+) -> String {
+    match read(provider, kind, body) {
+        Some(data) => selection.render(kind, &data),
+        None => {
+            eprintln!(
+                "Warning: cannot read '{}' data from '{}'; no values printed",
+                kind,
+                provider.name()
+            );
+            String::new()
+        }
+    }
+}
+
+/// Wind speed in the [wind unit](crate::units::wind_unit) of `units`,
+/// with its compass direction and gusts when known, e.g.
+/// `18.0 km/h NNE, gusts 32.4 km/h`.
+fn wind(data: &WeatherData, units: Units) -> Option<String> {
+    let speed = |kph: f64| speed(kph, units);
+
+    data.wind_kph.map(|kph| {
+        let mut wind = speed(kph);
+        if let Some(degree) = data.wind_degree {
+            wind.push(' ');
+            wind.push_str(compass_point(degree));
+        }
+        if let Some(gust) = data.gust_kph {
+            wind.push_str(", gusts ");
+            wind.push_str(&speed(gust));
+        }
+        wind
+    })
+}
+
+/// A wind speed in the [wind unit](crate::units::wind_unit) of `units`,
+/// e.g. `18.0 km/h`.
+fn speed(kph: f64, units: Units) -> String {
+    let unit = crate::units::wind_unit(units);
+    format!(
+        "{} {}",
+        decimal(round(unit.speed(kph), 1), 1),
+        unit.symbol()
+    )
+}
+
+/// Category of the current condition, from the text if the provider gave
+/// no mapped code.
+fn condition_kind(data: &WeatherData) -> Condition {
+    data.condition_kind
+        .unwrap_or_else(|| Condition::from_text(data.condition.as_deref().unwrap_or_default()))
+}
+
+/// A temperature in °C in `units`, rounded to one decimal, or `-` when
+/// the value is missing.
+fn degrees(celsius: Option<f64>, units: Units) -> String {
+    number(celsius.map(|c| round(units.temp(c), 1)))
+}
+
+/// One decimal, or `-` when the value is missing.
+fn number(value: Option<f64>) -> String {
+    value.map(|v| decimal(v, 1)).unwrap_or("-".into())
+}
+
+/// The date of a forecast day, marked `*` when it is
+/// [`partial`](DailySummary::partial).
+fn day_label(day: &DailySummary) -> String {
+    if day.partial {
+        format!("{}*", i18n::date(day.date))
+    } else {
+        i18n::date(day.date)
+    }
+}
+
+/// The chance and amount of precipitation of a forecast day, e.g.
+/// `Rain: 70% (4.2 mm)`; a chance the provider did not report is `—`, not
+/// 0%.
+fn rain(day: &DailySummary) -> String {
+    let chance = day
+        .precip_chance
+        .map(|p| format!("{:.0}%", p))
+        .unwrap_or("—".into());
+    match day.precip_mm {
+        Some(mm) => format!("Rain: {} ({} mm)", chance, decimal(mm, 1)),
+        None => format!("Rain: {}", chance),
+    }
+}
+
+/// The line explaining the `*` of [`day_label`], if any day of `data` is
+/// partial.
+fn partial_note(data: &WeatherData) -> String {
+    if data.forecast.iter().any(|day| day.partial) {
+        "  * partial day: the forecast covers only part of it\n".to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// The title of `city`'s data of `kind`: the observations of a date are
+/// marked "(observed)", so they are not taken for a forecast.
+pub fn title(city: &str, kind: &str) -> String {
+    match kind.parse() {
+        Ok(DataKind::History(_)) => format!("{} (observed)", city),
+        _ => city.to_string(),
+    }
+}
+
+/// Minutes as hours and minutes, e.g. `16h 25m`.
+fn hours_minutes(minutes: i64) -> String {
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// The moon phase with its lit part, e.g. `Waxing Gibbous (64%)`.
+fn moon(astro: &Astronomy) -> Option<String> {
+    let phase = astro.moon_phase.as_deref()?;
+    Some(match astro.moon_illumination {
+        Some(lit) => format!("{} ({:.0}%)", phase, lit),
+        None => phase.to_string(),
+    })
+}
+
+/// The UV index with its category, e.g. `7 High`.
+fn uv(data: &WeatherData) -> Option<String> {
+    let index = data.uv_index?;
+    let category = data.uv_category.unwrap_or(UvCategory::from_index(index));
+    Some(format!("{} {}", index.round(), category.label()))
+}
+
+/// A whole percentage, or `-` when the value is missing.
+fn chance(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.0}%", v)).unwrap_or("-".into())
+}
+
+/// Weekday and time of an hourly entry in the location's time `zone`,
+/// e.g. `Tue 14:00`, see [`TimeDisplay::show`](crate::timezone::TimeDisplay::show).
+fn local_hour(hour: &HourlyForecast, zone: Option<Zone>) -> String {
+    time_display().show(hour.time, zone, "%a %H:%M")
+}
+
+/// When an alert is in effect, in the location's time `zone`, e.g.
+/// `Wed 01 May 13:00 – Thu 02 May 13:00`; `None` without either end.
+fn alert_period(alert: &Alert, zone: Option<Zone>) -> Option<String> {
+    let time = |t| local_time(t, zone);
+    match (alert.starts, alert.ends) {
+        (Some(starts), Some(ends)) => Some(format!("{} – {}", time(starts), time(ends))),
+        (Some(starts), None) => Some(format!("from {}", time(starts))),
+        (None, Some(ends)) => Some(format!("until {}", time(ends))),
+        (None, None) => None,
+    }
+}
+
+/// A time in the location's time `zone` with its weekday and date, e.g.
+/// `Wed 01 May 13:00`, see [`TimeDisplay::show`](crate::timezone::TimeDisplay::show).
+fn local_time(time: DateTime<Utc>, zone: Option<Zone>) -> String {
+    time_display().show(time, zone, "%a %d %b %H:%M")
+}
+
+/// A `local` wall-clock time of the location, as providers give marine
+/// and sun times: shown as the time display says if its `zone` is known,
+/// else as it is, in `default`.
+fn wall_clock(local: NaiveDateTime, zone: Option<Zone>, default: &str) -> String {
+    match zone.and_then(|z| z.to_utc(local)) {
+        Some(time) => time_display().show(time, zone, default),
+        None => i18n::format(&local.and_utc(), default),
+    }
+}
+
+/// A sun or moon `time` of `astro`, on its date and in its zone, see
+/// [`wall_clock`].
+fn astro_clock(astro: &Astronomy, time: Option<NaiveTime>) -> Option<String> {
+    let time = time?;
+    let zone = astro.timezone.as_deref().and_then(|z| z.parse().ok());
+    Some(match astro.date {
+        Some(date) => wall_clock(date.and_time(time), zone, "%H:%M"),
+        None => time.format("%H:%M").to_string(),
+    })
+}
+
+/// `text` cut to `max` characters, ending in `…` if anything was cut.
+fn cut(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// Reads a response body as [`WeatherData`]; `hourly` data also gets its
+/// [`hourly`](WeatherData::hourly) entries, from the provider's series, and
+/// `alerts` data only its [`alerts`](WeatherData::alerts), most severe
+/// first, `air` data only its [`air`](WeatherData::air) report, and
+/// `astro` data only its [`astro`](WeatherData::astro) times, of the day
+/// asked for if any, `marine` data only its
+/// [`marine`](WeatherData::marine) days, `snow` data only its
+/// [`snow`](WeatherData::snow) days, and `weekend` data only the days of
+/// the weekend with its [`weekend`](WeatherData::weekend) verdict, see
+/// [`summarize`](crate::weekend::summarize).
+pub fn read(provider: &dyn ApiProvider, kind: &str, body: &str) -> Option<WeatherData> {
+    if kind == "snow" {
+        return Some(WeatherData {
+            snow: Some(provider.snow(body)?),
+            timezone: provider.timezone(body),
+            ..Default::default()
+        });
+    }
+    if kind == "marine" {
+        return Some(WeatherData {
+            marine: Some(provider.marine(body)?),
+            timezone: provider.timezone(body),
+            ..Default::default()
+        });
+    }
+    if let Ok(DataKind::Astro(date)) = kind.parse() {
+        let mut astro = provider.astronomy(body)?;
+        astro.date = date.or(astro.date);
+        return Some(WeatherData {
+            astro: Some(astro),
+            ..Default::default()
+        });
+    }
+    if kind == "air" {
+        return Some(WeatherData {
+            air: Some(provider.air_quality(body)?),
+            ..Default::default()
+        });
+    }
+    if kind == "alerts" {
+        let mut alerts = provider.alerts(body)?;
+        sort_alerts(&mut alerts);
+        return Some(WeatherData {
+            alerts: Some(alerts),
+            timezone: provider.timezone(body),
+            ..Default::default()
+        });
+    }
+    if kind == "weekend" {
+        let data = provider.weather(body)?;
+        let hours = provider.series(body).unwrap_or_default();
+        return Some(crate::weekend::summarize(
+            data,
+            &hours,
+            Utc::now(),
+            crate::units::units(),
+        ));
+    }
+    if kind != "hourly" {
+        return provider.weather(body);
+    }
+
+    let series = provider.series(body)?;
+    let mut data = provider.weather(body).unwrap_or_default();
+    data.hourly = series.iter().map(HourlyForecast::from).collect();
+    Some(data)
+}
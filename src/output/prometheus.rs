@@ -0,0 +1,134 @@
+use crate::providers::ApiProvider;
+use crate::series::SeriesPoint;
+
+/// One location as the `prometheus` format prints it: its labels, whether
+/// the fetch succeeded, and the current conditions of the response.
+///
+/// # Fields
+/// - `city`, `provider`: values of the `city` and `provider` labels,
+/// - `success`: value of `wapp_fetch_success`,
+/// - `current`: the current conditions, `None` when the fetch failed or
+///   the response holds none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sample {
+    pub city: String,
+    pub provider: String,
+    pub success: bool,
+    pub current: Option<SeriesPoint>,
+}
+
+impl Sample {
+    /// The sample of a response `body` of `provider` for `city`, read with
+    /// [`ApiProvider::current`].
+    pub fn fetched(provider: &dyn ApiProvider, city: &str, body: &str) -> Self {
+        Self {
+            city: city.to_string(),
+            provider: provider.name().to_string(),
+            success: true,
+            current: provider.current(body),
+        }
+    }
+
+    /// The sample of a failed fetch from `provider` for `city`.
+    pub fn failed(provider: &str, city: &str) -> Self {
+        Self {
+            city: city.to_string(),
+            provider: provider.to_string(),
+            success: false,
+            current: None,
+        }
+    }
+
+    fn current(&self, value: fn(&SeriesPoint) -> Option<f64>) -> Option<f64> {
+        self.current.as_ref().and_then(value)
+    }
+}
+
+/// A gauge of the format: its name, help text, and value for a sample.
+struct Metric {
+    name: &'static str,
+    help: &'static str,
+    value: fn(&Sample) -> Option<f64>,
+}
+
+/// Gauges in output order. Names and units are stable: a metric is never
+/// renamed or given another unit within a version; new ones may be added.
+const METRICS: &[Metric] = &[
+    Metric {
+        name: "wapp_fetch_success",
+        help: "Whether the last fetch of the location succeeded (1) or failed (0).",
+        value: |sample| Some(if sample.success { 1.0 } else { 0.0 }),
+    },
+    Metric {
+        name: "wapp_temperature_celsius",
+        help: "Current air temperature in degrees Celsius.",
+        value: |sample| sample.current(|point| point.temp_c),
+    },
+    Metric {
+        name: "wapp_humidity_percent",
+        help: "Current relative humidity in percent.",
+        value: |sample| sample.current(|point| point.humidity),
+    },
+    Metric {
+        name: "wapp_wind_speed_meters_per_second",
+        help: "Current wind speed in meters per second.",
+        value: |sample| sample.current(|point| point.wind_kph.map(|kph| kph / 3.6)),
+    },
+    Metric {
+        name: "wapp_pressure_hectopascals",
+        help: "Current air pressure in hectopascals.",
+        value: |sample| sample.current(|point| point.pressure_mb),
+    },
+];
+
+/// Renders `samples` in the Prometheus text exposition format, e.g. for the
+/// node_exporter textfile collector:
+///
+/// ```text
+/// # HELP wapp_temperature_celsius Current air temperature in degrees Celsius.
+/// # TYPE wapp_temperature_celsius gauge
+/// wapp_temperature_celsius{city="Kyiv",provider="weatherapi"} 21.3
+/// ```
+///
+/// Every metric is a gauge with one line per sample, labelled by `city`
+/// and `provider`. A value the provider does not report is left out rather
+/// than written as `NaN`, and a metric without any value is left out
+/// entirely. Lines carry no timestamp, as the textfile collector rejects
+/// them.
+pub fn render(samples: &[Sample]) -> String {
+    let mut out = String::new();
+
+    for metric in METRICS {
+        let lines: Vec<String> = samples
+            .iter()
+            .filter_map(|sample| {
+                let value = (metric.value)(sample).filter(|v| v.is_finite())?;
+                Some(format!(
+                    "{}{{city=\"{}\",provider=\"{}\"}} {}\n",
+                    metric.name,
+                    escape_label(&sample.city),
+                    escape_label(&sample.provider),
+                    crate::units::round(value, 2)
+                ))
+            })
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("# HELP {} {}\n", metric.name, metric.help));
+        out.push_str(&format!("# TYPE {} gauge\n", metric.name));
+        out.extend(lines);
+    }
+
+    out
+}
+
+/// Escapes a label value: backslashes, double quotes, and line feeds are
+/// written as `\\`, `\"`, and `\n`.
+pub fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
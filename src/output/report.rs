@@ -0,0 +1,338 @@
+use crate::comfort::feels_like;
+use crate::units::Units;
+use crate::weather::WeatherData;
+
+use super::{
+    chance, day_label, degrees, local_hour, local_time, number, source_note, uv, wind, Palette,
+};
+
+/// Inline styles of the HTML fragment, so it needs no stylesheet.
+const SECTION_STYLE: &str = "font-family:sans-serif;margin:0 0 1.5em";
+const HEADING_STYLE: &str = "font-size:1.25em;margin:0 0 0.5em";
+const LIST_STYLE: &str =
+    "display:grid;grid-template-columns:max-content auto;gap:0.25em 1em;margin:0 0 0.75em";
+const TERM_STYLE: &str = "font-weight:bold";
+const DEFINITION_STYLE: &str = "margin:0";
+const TABLE_STYLE: &str = "border-collapse:collapse";
+const CELL_STYLE: &str = "border:1px solid #ccc;padding:0.25em 0.5em";
+const NOTE_STYLE: &str = "font-size:0.875em;margin:0.5em 0 0";
+
+/// What a report shows of a location: a table of its days or hours, or
+/// else its current values as labelled pairs.
+struct Report {
+    current: Vec<(&'static str, String)>,
+    header: Vec<String>,
+    /// Which columns hold numbers, right-aligned.
+    numeric: Vec<bool>,
+    rows: Vec<Vec<String>>,
+    partial: bool,
+}
+
+impl Report {
+    /// The report of `data`, or `None` for data other than current
+    /// conditions, forecast days, and hours.
+    fn of(data: &WeatherData, units: Units) -> Option<Self> {
+        if data.alerts.is_some()
+            || data.air.is_some()
+            || data.astro.is_some()
+            || data.marine.is_some()
+            || data.snow.is_some()
+        {
+            return None;
+        }
+
+        let symbol = units.temp_symbol();
+        let condition = |c: &Option<String>| c.clone().unwrap_or("-".into());
+        let (header, numeric, rows): (Vec<String>, Vec<bool>, Vec<Vec<String>>) =
+            if !data.hourly.is_empty() {
+                (
+                    vec![
+                        "Time".to_string(),
+                        "Condition".to_string(),
+                        format!("Temp {}", symbol),
+                        "Rain %".to_string(),
+                        "Precip mm".to_string(),
+                    ],
+                    vec![false, false, true, true, true],
+                    data.hourly
+                        .iter()
+                        .map(|hour| {
+                            vec![
+                                local_hour(hour, data.timezone),
+                                condition(&hour.condition),
+                                degrees(hour.temp_c, units),
+                                percent(hour.precip_chance),
+                                number(hour.precip_mm),
+                            ]
+                        })
+                        .collect(),
+                )
+            } else {
+                (
+                    vec![
+                        "Date".to_string(),
+                        "Condition".to_string(),
+                        format!("Min {}", symbol),
+                        format!("Max {}", symbol),
+                        "Precip mm".to_string(),
+                        "Rain %".to_string(),
+                    ],
+                    vec![false, false, true, true, true, true],
+                    data.forecast
+                        .iter()
+                        .map(|day| {
+                            vec![
+                                day_label(day),
+                                condition(&day.condition),
+                                degrees(day.min_c, units),
+                                degrees(day.max_c, units),
+                                number(day.precip_mm),
+                                percent(day.precip_chance),
+                            ]
+                        })
+                        .collect(),
+                )
+            };
+
+        // The table of days or hours is the report; the current values
+        // are for data without either. A weekend's verdict comes first.
+        let verdict = data
+            .weekend
+            .as_ref()
+            .map(|weekend| ("Verdict", weekend.verdict.clone()));
+        let current = match rows.is_empty() {
+            false => verdict.into_iter().collect(),
+            true => verdict
+                .into_iter()
+                .chain(
+                    [
+                        ("Condition", data.condition.clone()),
+                        ("Temperature", data.temp_c.map(|t| temperature(t, units))),
+                        (
+                            "Feels like",
+                            feels_like(data)
+                                .map(|f| temperature(f.temp_c, units) + &source_note(f.source)),
+                        ),
+                        ("Humidity", data.humidity.map(|h| format!("{:.0}%", h))),
+                        ("Wind", wind(data, units)),
+                        ("UV index", uv(data)),
+                        (
+                            "Updated",
+                            data.updated.map(|t| local_time(t, data.timezone)),
+                        ),
+                    ]
+                    .into_iter()
+                    .filter_map(|(label, value)| Some((label, value?))),
+                )
+                .collect(),
+        };
+
+        Some(Self {
+            current,
+            header,
+            numeric,
+            rows,
+            partial: data.hourly.is_empty() && data.forecast.iter().any(|day| day.partial),
+        })
+    }
+}
+
+/// Renders `data` as a GitHub-flavored Markdown section under a `city`
+/// heading, in the units set with [`use_units`](crate::units::use_units).
+///
+/// Forecast days are a table with one row per day, hours one row per hour
+/// in local time, and current conditions a list of labelled values:
+///
+/// ```text
+/// ## Kyiv
+///
+/// | Date | Condition | Min °C | Max °C | Precip mm | Rain % |
+/// | --- | --- | ---: | ---: | ---: | ---: |
+/// | 2024-06-10 | Partly cloudy | 10.2 | 18.3 | 0.4 | 20 |
+/// ```
+///
+/// Text from the provider and `city` are escaped, so a `|` or `*` in them
+/// is shown as it is. Alerts, air quality, sun and moon times, marine and
+/// snow data are shown as their text summary in a code block.
+pub fn render_markdown(city: &str, data: &WeatherData) -> String {
+    render_markdown_in(city, data, crate::units::units())
+}
+
+/// [`render_markdown`] in `units`: temperatures and wind speeds are
+/// converted from the metric `data`.
+pub fn render_markdown_in(city: &str, data: &WeatherData, units: Units) -> String {
+    let mut out = format!("## {}\n\n", escape_markdown(city));
+    let Some(report) = Report::of(data, units) else {
+        out.push_str(&markdown_code(&summary(city, data, units)));
+        return out;
+    };
+
+    for (label, value) in &report.current {
+        out.push_str(&format!("- **{}:** {}\n", label, escape_markdown(value)));
+    }
+    if report.rows.is_empty() {
+        return out;
+    }
+
+    let row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    out.push_str(&row(report.header.clone()));
+    out.push_str(&row(report
+        .numeric
+        .iter()
+        .map(|&numeric| if numeric { "---:" } else { "---" }.to_string())
+        .collect()));
+    for cells in &report.rows {
+        out.push_str(&row(cells.iter().map(|c| escape_markdown(c)).collect()));
+    }
+    if report.partial {
+        out.push_str("\n\\* partial day: the forecast covers only part of it\n");
+    }
+
+    out
+}
+
+/// Renders `data` as a self-contained HTML fragment: a `<section>` with
+/// inline styles under a `city` heading, in the units set with
+/// [`use_units`](crate::units::use_units).
+///
+/// Forecast days are a `<table>` with one row per day, hours one row per
+/// hour, and current conditions a `<dl>` of labelled values, as in
+/// [`render_markdown`]. Text from the provider and `city` are escaped.
+/// Alerts, air quality, sun and moon times, marine and snow data are shown
+/// as their text summary in a `<pre>`.
+pub fn render_html(city: &str, data: &WeatherData) -> String {
+    render_html_in(city, data, crate::units::units())
+}
+
+/// [`render_html`] in `units`: temperatures and wind speeds are converted
+/// from the metric `data`.
+pub fn render_html_in(city: &str, data: &WeatherData, units: Units) -> String {
+    let mut out = format!(
+        "<section style=\"{}\">\n<h2 style=\"{}\">{}</h2>\n",
+        SECTION_STYLE,
+        HEADING_STYLE,
+        escape_html(city)
+    );
+    let Some(report) = Report::of(data, units) else {
+        out.push_str(&html_pre(&summary(city, data, units)));
+        out.push_str("</section>\n");
+        return out;
+    };
+
+    if !report.current.is_empty() {
+        out.push_str(&format!("<dl style=\"{}\">\n", LIST_STYLE));
+        for (label, value) in &report.current {
+            out.push_str(&format!(
+                "<dt style=\"{}\">{}</dt><dd style=\"{}\">{}</dd>\n",
+                TERM_STYLE,
+                label,
+                DEFINITION_STYLE,
+                escape_html(value)
+            ));
+        }
+        out.push_str("</dl>\n");
+    }
+
+    if !report.rows.is_empty() {
+        let cell = |tag: &str, i: usize, value: &str| {
+            let align = if report.numeric[i] { "right" } else { "left" };
+            format!(
+                "<{tag} style=\"{};text-align:{}\">{}</{tag}>",
+                CELL_STYLE,
+                align,
+                escape_html(value)
+            )
+        };
+        let row = |tag: &str, cells: &[String]| {
+            let cells: String = cells
+                .iter()
+                .enumerate()
+                .map(|(i, value)| cell(tag, i, value))
+                .collect();
+            format!("<tr>{}</tr>\n", cells)
+        };
+
+        out.push_str(&format!("<table style=\"{}\">\n<thead>\n", TABLE_STYLE));
+        out.push_str(&row("th", &report.header));
+        out.push_str("</thead>\n<tbody>\n");
+        for cells in &report.rows {
+            out.push_str(&row("td", cells));
+        }
+        out.push_str("</tbody>\n</table>\n");
+        if report.partial {
+            out.push_str(&format!(
+                "<p style=\"{}\">* partial day: the forecast covers only part of it</p>\n",
+                NOTE_STYLE
+            ));
+        }
+    }
+
+    out.push_str("</section>\n");
+    out
+}
+
+/// Escapes `text` for HTML content and attribute values: `&`, `<`, `>`,
+/// `"`, and `'` are written as character references.
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes `text` for Markdown inline content, including table cells:
+/// characters that would start emphasis, code, links, HTML, or entities,
+/// or end a cell, get a backslash, and line breaks become spaces.
+pub fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~' | '#' | '&' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' => out.push(' '),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// A temperature in °C in `units` with its symbol, e.g. `24.0 °C`.
+fn temperature(celsius: f64, units: Units) -> String {
+    format!("{} {}", degrees(Some(celsius), units), units.temp_symbol())
+}
+
+/// A whole percentage without its sign, or `-` when the value is missing.
+fn percent(value: Option<f64>) -> String {
+    chance(value).trim_end_matches('%').to_string()
+}
+
+/// The text summary of `data` without its `city` line, which the heading
+/// already shows.
+fn summary(city: &str, data: &WeatherData, units: Units) -> String {
+    let text = super::render_text_in(city, data, Palette::PLAIN, units);
+    text.split_once('\n')
+        .map_or(String::new(), |(_, rest)| rest.to_string())
+}
+
+/// `text` in a Markdown code block, fenced with more backticks than any
+/// run inside it.
+pub fn markdown_code(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}text\n{}\n{}\n", fence, text.trim_end(), fence)
+}
+
+/// `text` in an HTML `<pre>`, escaped.
+pub fn html_pre(text: &str) -> String {
+    format!("<pre>{}</pre>\n", escape_html(text.trim_end()))
+}
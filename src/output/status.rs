@@ -0,0 +1,74 @@
+use std::io;
+
+use crate::cache::{Cache, CacheEntry, Namespace};
+use crate::weather::WeatherData;
+
+use super::template::Template;
+
+/// Template of `get --status-line` when `--status-format` is not given,
+/// e.g. `☀️ 21°C London`.
+pub const DEFAULT_STATUS_FORMAT: &str = "{icon} {temp:.0}°C {city}";
+
+/// Renders the status line of `data` through `template`.
+///
+/// The result is a single line without a trailing newline: line breaks in
+/// the template or the data become spaces and surrounding whitespace is
+/// trimmed. It never holds ANSI escape codes, as templates are not styled
+/// by a [`Palette`](super::Palette).
+pub fn render(template: &Template, city: &str, data: &WeatherData) -> String {
+    template
+        .render(city, data)
+        .replace(['\r', '\n'], " ")
+        .trim()
+        .to_string()
+}
+
+/// Key of the last known good response of a provider, kind, and city in
+/// the `stale` cache namespace, e.g. `weatherapi:now:london`.
+pub fn stale_key(provider: &str, kind: &str, city: &str) -> String {
+    format!("{}:{}:{}", provider, kind, city.to_lowercase())
+}
+
+/// Stores `body` as the last known good response, replacing an older one.
+///
+/// # Errors
+/// Returns an error if the cache entry cannot be written.
+pub fn remember(
+    cache: &Cache,
+    provider: &str,
+    kind: &str,
+    city: &str,
+    body: &str,
+    now: u64,
+) -> io::Result<()> {
+    cache.put(&CacheEntry {
+        namespace: Namespace::Stale,
+        key: stale_key(provider, kind, city),
+        provider: provider.to_string(),
+        city: city.to_string(),
+        created: now,
+        expires: None,
+        body: body.to_string(),
+        validators: Default::default(),
+    })
+}
+
+/// The last known good response stored by [`remember`], if any.
+pub fn last_known(cache: &Cache, provider: &str, kind: &str, city: &str) -> Option<String> {
+    cache
+        .get(Namespace::Stale, &stale_key(provider, kind, city))
+        .map(|entry| entry.body)
+}
+
+/// Error returned when `get --status-line` has nothing to show; `main` exits
+/// with status 1 without printing it, so a status bar shows no error text.
+#[derive(Debug)]
+pub struct StatusUnavailable;
+
+impl std::fmt::Display for StatusUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no weather data for the status line")
+    }
+}
+
+impl std::error::Error for StatusUnavailable {}
@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use crate::condition::Condition;
+use crate::weather::WeatherData;
+
+/// Placeholders a template may use, in documentation order.
+///
+/// - `{city}`: location as given by the user,
+/// - `{temp}`, `{feels_like}`: temperatures in °C,
+/// - `{humidity}`: relative humidity in %,
+/// - `{wind_speed}`: wind speed in km/h,
+/// - `{condition}`: provider's condition text,
+/// - `{icon}`: an emoji for the condition, see [`Condition::emoji`].
+pub const PLACEHOLDERS: &[&str] = &[
+    "city",
+    "temp",
+    "feels_like",
+    "humidity",
+    "wind_speed",
+    "condition",
+    "icon",
+];
+
+/// Placeholders holding numbers, which accept a precision such as `{temp:.0}`.
+pub const NUMERIC_PLACEHOLDERS: &[&str] = &["temp", "feels_like", "humidity", "wind_speed"];
+
+/// A parsed `--format` template such as `"{city}: {temp}°C {condition}"`.
+///
+/// Placeholders are names from [`PLACEHOLDERS`] in braces; numeric ones
+/// may set their number of decimals, e.g. `{temp:.0}`. `{{` and `}}` stand
+/// for literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Placeholder(&'static str, Option<usize>),
+}
+
+impl Template {
+    /// Parses a template.
+    ///
+    /// # Errors
+    /// Returns an error if a placeholder is unknown (listing the valid
+    /// ones), empty, or unclosed, if its precision is malformed or set on
+    /// a text placeholder, or if a `}` is not doubled.
+    pub fn parse(template: &str) -> anyhow::Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|(_, c)| *c) == Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => name.push(c),
+                            None => {
+                                return Err(anyhow::anyhow!(
+                                    "unclosed '{{' at position {} in format template; write '{{{{' for a literal brace",
+                                    start
+                                ))
+                            }
+                        }
+                    }
+
+                    let (name, spec) = match name.split_once(':') {
+                        Some((name, spec)) => (name.to_string(), Some(spec.to_string())),
+                        None => (name, None),
+                    };
+                    let placeholder = PLACEHOLDERS
+                        .iter()
+                        .find(|p| **p == name)
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "unknown placeholder '{{{}}}' in format template; valid placeholders: {}",
+                                name,
+                                valid_list()
+                            )
+                        })?;
+
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    let precision = spec
+                        .map(|spec| parse_precision(placeholder, &spec))
+                        .transpose()?;
+                    parts.push(Part::Placeholder(placeholder, precision));
+                }
+                '}' => {
+                    return Err(anyhow::anyhow!(
+                        "unmatched '}}' at position {} in format template; write '}}}}' for a literal brace",
+                        start
+                    ))
+                }
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Substitutes the placeholders from `data`, with `city` as `{city}`.
+    ///
+    /// Numbers have one decimal (humidity none) unless the placeholder sets
+    /// a precision; values the provider does not report are replaced by `-`.
+    pub fn render(&self, city: &str, data: &WeatherData) -> String {
+        let number = |v: Option<f64>, precision: usize| v.map(|v| format!("{:.*}", precision, v));
+
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => text.clone(),
+                Part::Placeholder(name, precision) => {
+                    let decimals = |default: usize| precision.unwrap_or(default);
+                    let value = match *name {
+                        "city" => Some(city.to_string()),
+                        "temp" => number(data.temp_c, decimals(1)),
+                        "feels_like" => number(data.feels_like_c, decimals(1)),
+                        "humidity" => number(data.humidity, decimals(0)),
+                        "wind_speed" => number(data.wind_kph, decimals(1)),
+                        "condition" => data.condition.clone(),
+                        "icon" => match (data.condition_kind, data.condition.as_deref()) {
+                            (Some(kind), _) => Some(kind.emoji().to_string()),
+                            (None, Some(text)) => Some(icon(text).to_string()),
+                            (None, None) => None,
+                        },
+                        _ => None,
+                    };
+                    value.unwrap_or("-".into())
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Template {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Emoji for a condition text, see [`Condition::from_text`], e.g.
+/// "Patchy rain nearby" → 🌧.
+pub fn icon(condition: &str) -> &'static str {
+    Condition::from_text(condition).emoji()
+}
+
+/// Reads a `.N` precision of a numeric placeholder.
+fn parse_precision(placeholder: &str, spec: &str) -> anyhow::Result<usize> {
+    if !NUMERIC_PLACEHOLDERS.contains(&placeholder) {
+        return Err(anyhow::anyhow!(
+            "placeholder '{{{}}}' takes no precision; only {} do",
+            placeholder,
+            NUMERIC_PLACEHOLDERS
+                .iter()
+                .map(|p| format!("{{{}}}", p))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    spec.strip_prefix('.')
+        .and_then(|digits| digits.parse::<usize>().ok())
+        .filter(|precision| *precision <= 9)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "invalid precision '{}' in '{{{}:{}}}'; expected e.g. '{{{}:.0}}'",
+                spec,
+                placeholder,
+                spec,
+                placeholder
+            )
+        })
+}
+
+fn valid_list() -> String {
+    PLACEHOLDERS
+        .iter()
+        .map(|p| format!("{{{}}}", p))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,112 @@
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+
+/// Pager used when neither the config nor `$PAGER` names one.
+pub const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Terminal rows assumed when the height cannot be determined.
+const DEFAULT_HEIGHT: usize = 24;
+
+/// What is known about stdout when deciding whether to page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Terminal {
+    pub is_tty: bool,
+    pub height: usize,
+}
+
+impl Terminal {
+    /// Inspects the process's stdout; the height is the terminal's, else
+    /// `$LINES`, else [`DEFAULT_HEIGHT`].
+    pub fn detect() -> Self {
+        let height = terminal_size::terminal_size()
+            .map(|(_, height)| usize::from(height.0))
+            .or_else(|| std::env::var("LINES").ok()?.parse().ok())
+            .filter(|&height| height > 0)
+            .unwrap_or(DEFAULT_HEIGHT);
+
+        Self {
+            is_tty: io::stdout().is_terminal(),
+            height,
+        }
+    }
+}
+
+/// Resolves the pager command, like git's `core.pager`.
+///
+/// Precedence: the `pager` config option, then `$PAGER`, then
+/// [`DEFAULT_PAGER`]. An empty value disables paging.
+pub fn resolve_command(config: Option<&str>, env: Option<String>) -> Option<String> {
+    let command = config
+        .map(String::from)
+        .or(env)
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+
+    (!command.trim().is_empty()).then_some(command)
+}
+
+/// Returns `true` if `text` should go through a pager: stdout is a
+/// terminal, the output is for humans, and it does not fit on one screen.
+pub fn should_page(terminal: Terminal, text: &str, machine: bool) -> bool {
+    terminal.is_tty && !machine && text.lines().count() >= terminal.height
+}
+
+/// Writes `text` to `out`, or through `pager` when [`should_page`] agrees.
+///
+/// If the pager cannot be started (e.g. the binary is missing), the text is
+/// written to `out` instead; once it runs, its output is not repeated there.
+/// Returns `true` if a pager was used.
+///
+/// # Errors
+/// Returns an error if writing to `out` or to the pager fails.
+pub fn write_paged(
+    text: &str,
+    machine: bool,
+    pager: Option<&str>,
+    terminal: Terminal,
+    out: &mut dyn Write,
+) -> io::Result<bool> {
+    if let Some(command) = pager.filter(|_| should_page(terminal, text, machine)) {
+        if let Ok(child) = spawn_pager(command) {
+            feed_pager(child, text)?;
+            return Ok(true);
+        }
+    }
+
+    out.write_all(text.as_bytes())?;
+    out.flush()?;
+    Ok(false)
+}
+
+/// Starts the pager with a pipe for its stdin.
+fn spawn_pager(command: &str) -> io::Result<Child> {
+    let mut parts = command.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty pager command"))?;
+
+    let mut cmd = Command::new(program);
+    cmd.args(parts).stdin(Stdio::piped());
+
+    // Same defaults git uses when `less` is invoked without options.
+    if std::env::var_os("LESS").is_none() {
+        cmd.env("LESS", "FRX");
+    }
+
+    cmd.spawn()
+}
+
+/// Feeds `text` to the pager's stdin and waits for it to exit.
+fn feed_pager(mut child: Child, text: &str) -> io::Result<()> {
+    let written = match child.stdin.take() {
+        // The user quitting the pager early closes the pipe; that is not an error.
+        Some(mut stdin) => match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        },
+        None => Ok(()),
+    };
+    // Closed before waiting, so the pager sees the end of its input.
+    child.wait()?;
+
+    written
+}
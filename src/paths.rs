@@ -0,0 +1,84 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+/// Resolved on-disk locations.
+///
+/// Every feature that stores files (response and geocode caches, history,
+/// stores, last results) derives its paths from here instead of hardcoding
+/// them.
+///
+/// # Fields
+/// - `config`: the configuration file; `$WAPP_CONFIG`, else `config.json`
+///   in the platform config directory (`~/.config/wapp` on Linux),
+/// - `cache`: regenerable data; `$WAPP_CACHE_DIR`, else the platform cache
+///   directory (`~/.cache/wapp` on Linux),
+/// - `data`: data worth keeping; `$WAPP_DATA_DIR`, else the platform data
+///   directory (`~/.local/share/wapp` on Linux).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    pub config: PathBuf,
+    pub cache: PathBuf,
+    pub data: PathBuf,
+}
+
+impl Paths {
+    /// Resolves all locations from the environment and platform conventions.
+    pub fn resolve() -> Self {
+        Self::resolve_with(|name| std::env::var_os(name).map(PathBuf::from))
+    }
+
+    /// Same as [`resolve`](Self::resolve), with overrides looked up through `lookup`.
+    ///
+    /// Without a home directory, the platform defaults fall back to
+    /// `.wapp/config.json`, `.wapp/cache` and `.wapp/data` in the working
+    /// directory.
+    pub fn resolve_with<F>(lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<PathBuf>,
+    {
+        let project = ProjectDirs::from("", "", "wapp");
+
+        Self {
+            config: lookup("WAPP_CONFIG").unwrap_or_else(|| match &project {
+                Some(dirs) => dirs.config_dir().join(crate::config::CONFIG_FILE),
+                None => PathBuf::from(".wapp").join(crate::config::CONFIG_FILE),
+            }),
+            cache: lookup("WAPP_CACHE_DIR").unwrap_or_else(|| match &project {
+                Some(dirs) => dirs.cache_dir().to_path_buf(),
+                None => PathBuf::from(".wapp").join("cache"),
+            }),
+            data: lookup("WAPP_DATA_DIR").unwrap_or_else(|| match &project {
+                Some(dirs) => dirs.data_dir().to_path_buf(),
+                None => PathBuf::from(".wapp").join("data"),
+            }),
+        }
+    }
+}
+
+/// Creates `dir` (and its parents) if needed and returns it.
+///
+/// Directories are created only when something is about to be written,
+/// and on Unix they are readable by the owner only (`0700`), since they may
+/// hold API responses with location data.
+///
+/// # Errors
+/// Returns an error if the directory cannot be created.
+pub fn ensure_dir(dir: &Path) -> io::Result<&Path> {
+    if dir.is_dir() {
+        return Ok(dir);
+    }
+
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+
+    builder.create(dir)?;
+    Ok(dir)
+}
@@ -0,0 +1,154 @@
+use serde_json::Value;
+
+use crate::providers::ApiProvider;
+use crate::series::{DailySummary, SeriesPoint};
+
+/// Version of the porcelain key set, printed as the first line.
+///
+/// The keys below are stable: new keys may be added within a version, but
+/// existing keys are never renamed or given a different meaning. Any such
+/// change bumps the version.
+pub const PORCELAIN_VERSION: u32 = 1;
+
+/// Keys of the `now` kind.
+pub const CURRENT_KEYS: &[&str] = &[
+    "time",
+    "temp_c",
+    "feels_like_c",
+    "humidity",
+    "pressure_mb",
+    "wind_kph",
+    "precip_mm",
+    "precip_chance",
+    "cloud",
+    "condition",
+];
+
+/// Keys of each `forecast.<N>.` day of the `forecast` and `tomorrow` kinds.
+pub const DAILY_KEYS: &[&str] = &["date", "min_c", "max_c", "precip_mm", "condition"];
+
+/// Keys of each `hourly.<N>.` record of the `hourly` kind.
+pub const HOURLY_KEYS: &[&str] = CURRENT_KEYS;
+
+/// Keys of each `alerts.<N>.` entry of the `alerts` kind.
+pub const ALERT_KEYS: &[&str] = &["headline", "event", "severity", "effective", "expires"];
+
+/// Header line opening every porcelain output.
+pub fn version_line() -> String {
+    format!("porcelain_version\t{}\n", PORCELAIN_VERSION)
+}
+
+/// Renders the `key<TAB>value` lines of one kind, without the version line.
+///
+/// Keys with no value in the response are omitted. Numbers are printed with
+/// one decimal (percentages included), times as RFC 3339 UTC, and dates as
+/// `YYYY-MM-DD`. Tabs and line breaks in text values become spaces.
+///
+/// # Errors
+/// Returns an error if the kind has no porcelain mapping or the response
+/// cannot be read as that kind.
+pub fn render(provider: &dyn ApiProvider, kind: &str, body: &str) -> anyhow::Result<String> {
+    let unreadable = || {
+        anyhow::anyhow!(
+            "cannot read '{}' data from '{}' for porcelain output",
+            kind,
+            provider.name()
+        )
+    };
+    let mut out = String::new();
+
+    match kind {
+        "now" => {
+            let point = provider.current(body).ok_or_else(unreadable)?;
+            push_point(&mut out, "", &point);
+        }
+        "forecast" | "tomorrow" => {
+            for (i, day) in provider
+                .daily(body)
+                .ok_or_else(unreadable)?
+                .iter()
+                .enumerate()
+            {
+                push_day(&mut out, &format!("forecast.{}.", i), day);
+            }
+        }
+        "hourly" => {
+            for (i, point) in provider
+                .series(body)
+                .ok_or_else(unreadable)?
+                .iter()
+                .enumerate()
+            {
+                push_point(&mut out, &format!("hourly.{}.", i), point);
+            }
+        }
+        "alerts" => {
+            let json: Value = serde_json::from_str(body).map_err(|_| unreadable())?;
+            // WeatherAPI shape: {"alerts": {"alert": [...]}}.
+            let alerts = json["alerts"]["alert"].as_array().ok_or_else(unreadable)?;
+
+            for (i, alert) in alerts.iter().enumerate() {
+                for key in ALERT_KEYS {
+                    push(
+                        &mut out,
+                        &format!("alerts.{}.", i),
+                        key,
+                        alert[key].as_str().map(String::from),
+                    );
+                }
+            }
+        }
+        other => {
+            return Err(anyhow::anyhow!(
+                "no porcelain output is defined for data kind '{}'",
+                other
+            ))
+        }
+    }
+
+    Ok(out)
+}
+
+fn push_point(out: &mut String, prefix: &str, point: &SeriesPoint) {
+    let values = [
+        Some(point.time.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        number(point.temp_c),
+        number(point.feels_like_c),
+        number(point.humidity),
+        number(point.pressure_mb),
+        number(point.wind_kph),
+        number(point.precip_mm),
+        number(point.precip_chance),
+        number(point.cloud),
+        point.condition.clone(),
+    ];
+
+    for (key, value) in CURRENT_KEYS.iter().zip(values) {
+        push(out, prefix, key, value);
+    }
+}
+
+fn push_day(out: &mut String, prefix: &str, day: &DailySummary) {
+    let values = [
+        Some(day.date.to_string()),
+        number(day.min_c),
+        number(day.max_c),
+        number(day.precip_mm),
+        day.condition.clone(),
+    ];
+
+    for (key, value) in DAILY_KEYS.iter().zip(values) {
+        push(out, prefix, key, value);
+    }
+}
+
+fn push(out: &mut String, prefix: &str, key: &str, value: Option<String>) {
+    if let Some(value) = value {
+        let value = value.replace(['\t', '\n', '\r'], " ");
+        out.push_str(&format!("{}{}\t{}\n", prefix, key, value));
+    }
+}
+
+fn number(value: Option<f64>) -> Option<String> {
+    value.map(|v| format!("{:.1}", v))
+}
@@ -0,0 +1,180 @@
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io;
+use std::process::{Command, Stdio};
+
+use clap::ValueEnum;
+
+use crate::cache::{Cache, Namespace};
+use crate::output::color::{Icons, Palette};
+use crate::output::status::stale_key;
+use crate::units::{round, Units};
+use crate::weather::WeatherData;
+
+/// Default age in seconds up to which `wapp prompt` shows the cached
+/// weather: 30 minutes.
+pub const DEFAULT_MAX_AGE_SECS: u64 = 30 * 60;
+
+/// Data kind shown by `wapp prompt`, and kept for it by the refresh.
+pub const KIND: &str = "now";
+
+/// Shell whose prompt shows `wapp prompt`, so escape codes can be marked
+/// as taking no width.
+///
+/// - `zsh`: codes in `%{…%}`, and `%` doubled, for `PROMPT` with
+///   `prompt_subst`,
+/// - `bash`: codes between the `\x01` and `\x02` markers of readline, as
+///   `\[…\]` is not read in command output,
+/// - `fish`: unchanged, as fish measures escape codes itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PromptShell {
+    Zsh,
+    Bash,
+    Fish,
+}
+
+/// How the weather cached for the prompt compares with the maximum age.
+///
+/// - `Fresh`: at most that old, shown as it is,
+/// - `Stale`: older, so not shown and refreshed,
+/// - `Missing`: nothing cached yet, refreshed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Fresh,
+    Stale,
+    Missing,
+}
+
+/// The freshness at `now` of an entry cached at `created`, both in Unix
+/// seconds, for `max_age` seconds. An entry from the future, e.g. after
+/// the clock was set back, is fresh.
+pub fn freshness(created: Option<u64>, now: u64, max_age: u64) -> Freshness {
+    match created {
+        None => Freshness::Missing,
+        Some(created) if now.saturating_sub(created) <= max_age => Freshness::Fresh,
+        Some(_) => Freshness::Stale,
+    }
+}
+
+/// The current weather of `city` kept in `cache` for `provider`, the last
+/// known good response of `get --status-line`, if fresh at `now` for
+/// `max_age`; the cache is only read.
+pub fn cached(
+    cache: &Cache,
+    provider: &str,
+    city: &str,
+    now: u64,
+    max_age: u64,
+) -> (Freshness, Option<String>) {
+    let entry = cache.get(Namespace::Stale, &stale_key(provider, KIND, city));
+    match freshness(entry.as_ref().map(|e| e.created), now, max_age) {
+        Freshness::Fresh => (Freshness::Fresh, entry.map(|e| e.body)),
+        other => (other, None),
+    }
+}
+
+/// The prompt token of `data` in `units`: the condition's glyph and the
+/// temperature without decimals, e.g. `☀️21°`, the temperature colored by
+/// `palette`; `None` without a temperature.
+pub fn token(data: &WeatherData, units: Units, palette: Palette) -> Option<String> {
+    let celsius = data.temp_c?;
+    let kind = data.condition_kind.or_else(|| {
+        data.condition
+            .as_deref()
+            .map(crate::condition::Condition::from_text)
+    });
+    let glyph = match (kind, palette.icons) {
+        (Some(kind), Icons::Ascii) => kind.ascii(),
+        (Some(kind), _) => kind.emoji(),
+        (None, _) => "",
+    };
+    // `-0°` reads oddly in a prompt.
+    let degrees = round(units.temp(celsius), 0) + 0.0;
+
+    Some(format!(
+        "{}{}",
+        glyph,
+        palette.temp(celsius, &format!("{:.0}°", degrees))
+    ))
+}
+
+/// Marks the ANSI escape codes of `text` for the prompt of `shell`, so its
+/// width is counted without them; unchanged for fish.
+pub fn escape(text: &str, shell: PromptShell) -> String {
+    let (open, close) = match shell {
+        PromptShell::Zsh => ("%{", "%}"),
+        PromptShell::Bash => ("\x01", "\x02"),
+        PromptShell::Fish => return text.to_string(),
+    };
+
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // A CSI sequence runs to its final byte, e.g. the `m` of `\x1b[31m`.
+            '\x1b' => {
+                out.push_str(open);
+                out.push(c);
+                if chars.peek() == Some(&'[') {
+                    for c in chars.by_ref() {
+                        out.push(c);
+                        if c.is_ascii_alphabetic() {
+                            break;
+                        }
+                    }
+                }
+                out.push_str(close);
+            }
+            '%' if shell == PromptShell::Zsh => out.push_str("%%"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Lock held by the refresh of one city, see [`lock_refresh`].
+#[derive(Debug)]
+pub struct RefreshLock {
+    _file: File,
+}
+
+/// Takes the lock of refreshing `city` for `provider` in `cache`, so that
+/// prompts drawn while a refresh runs do not start more; `None` if another
+/// process holds it.
+///
+/// # Errors
+/// Returns an error if the lock file cannot be created or locked.
+pub fn lock_refresh(cache: &Cache, provider: &str, city: &str) -> io::Result<Option<RefreshLock>> {
+    let dir = crate::paths::ensure_dir(cache.path())?;
+    let key = stale_key(provider, KIND, city);
+    let path = dir.join(format!("prompt-{:016x}.lock", crate::cache::fnv1a(&key)));
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+
+    match file.try_lock() {
+        Ok(()) => Ok(Some(RefreshLock { _file: file })),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(e),
+    }
+}
+
+/// Starts `wapp` again with `args` in the background, detached from the
+/// terminal and without its output, and returns without waiting for it.
+///
+/// # Errors
+/// Returns an error if the process cannot be started.
+pub fn spawn_refresh(args: &[OsString]) -> io::Result<()> {
+    let mut cmd = Command::new(std::env::current_exe()?);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Its own process group, so the shell's job control leaves it alone.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+
+    cmd.spawn().map(drop)
+}
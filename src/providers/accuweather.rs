@@ -0,0 +1,452 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use urlencoding::encode;
+
+use super::credentials::CredentialStore;
+use super::{
+    append_params, effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest,
+    ProviderOverrides,
+};
+use crate::config::KindOverride;
+use crate::location::{Location, LocationInput, LocationMatch};
+use crate::request::{parse_date, DateWindow};
+use crate::series::{from_epoch, DailySummary, SeriesPoint};
+
+/// Default base URL of the API.
+pub const DEFAULT_BASE_URL: &str = "https://dataservice.accuweather.com";
+
+/// Location keys already resolved, by base URL and location, kept for the
+/// lifetime of the process so batch requests look up each city only once.
+static LOCATION_KEYS: Mutex<BTreeMap<(String, String), String>> = Mutex::new(BTreeMap::new());
+
+/// Provider for working with the AccuWeather API.
+///
+/// AccuWeather serves weather by location key, so every city is first
+/// resolved through the Locations API. Values are requested in metric units.
+///
+/// # Fields
+///
+/// * `api_key` - API key for authentication with AccuWeather
+/// * `base_url` - Base URL of the API
+/// * `lang` - Language of condition descriptions
+/// * `extra_params` - Additional query parameters appended to every weather request
+/// * `kind_overrides` - Per-kind request parameters from the config
+pub struct AccuWeatherProvider {
+    pub api_key: String,
+    pub base_url: String,
+    pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+impl AccuWeatherProvider {
+    /// Creates a new instance of `AccuWeatherProvider` from environment variables.
+    ///
+    /// # Environment Variables
+    ///
+    /// * `ACCUWEATHER_KEY` (required) - AccuWeather API key
+    /// * `ACCUWEATHER_KEY_FILE` (alternative) - File containing the API key
+    /// * `ACCUWEATHER_BASE_URL` (optional) - API base URL
+    ///   (default: "https://dataservice.accuweather.com")
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new provider instance or an error if required variables are missing
+    ///
+    pub fn from_env() -> Result<Self> {
+        Self::from_sources(
+            &ProviderOverrides::default(),
+            &CredentialStore::new(None, None),
+        )
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence and the API key looked up in `credentials`, see
+    /// [`CredentialStore`]; `overrides.api_key` is not read. `overrides.units`
+    /// is ignored: values are always metric.
+    pub fn from_sources(
+        overrides: &ProviderOverrides,
+        credentials: &CredentialStore,
+    ) -> Result<Self> {
+        Ok(Self {
+            api_key: credentials.api_key("ACCUWEATHER_KEY", "accuweather")?,
+            base_url: overrides
+                .env
+                .var("ACCUWEATHER_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            lang: overrides
+                .lang
+                .clone()
+                .or_else(|| overrides.config_lang.clone()),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
+        })
+    }
+
+    /// Builds the city search URL of the Locations API, narrowed to the
+    /// country when one is given, e.g. `/locations/v1/cities/UA/search?q=Kyiv`.
+    pub fn search_url(&self, location: &LocationInput) -> String {
+        let scope = match &location.country {
+            Some(country) => format!("cities/{}", country),
+            None => "cities".to_string(),
+        };
+
+        let mut url = format!(
+            "{}/locations/v1/{}/search?apikey={}&q={}",
+            self.base_url,
+            scope,
+            self.api_key,
+            encode(&location.city)
+        );
+        self.push_lang(&mut url);
+
+        url
+    }
+
+    /// Builds the geoposition search URL of the Locations API, which finds
+    /// the location at coordinates, e.g. `/locations/v1/cities/geoposition/search?q=50.45,30.52`.
+    pub fn geoposition_url(&self, lat: f64, lon: f64) -> String {
+        let mut url = format!(
+            "{}/locations/v1/cities/geoposition/search?apikey={}&q={},{}",
+            self.base_url, self.api_key, lat, lon
+        );
+        self.push_lang(&mut url);
+
+        url
+    }
+
+    /// Builds the postal code search URL of the Locations API, narrowed to
+    /// the country when one is given, e.g. `/locations/v1/postalcodes/US/search?q=10001`.
+    pub fn postal_code_url(&self, code: &str, country: Option<&str>) -> String {
+        let scope = match country {
+            Some(country) => format!("postalcodes/{}", country),
+            None => "postalcodes".to_string(),
+        };
+
+        let mut url = format!(
+            "{}/locations/v1/{}/search?apikey={}&q={}",
+            self.base_url,
+            scope,
+            self.api_key,
+            encode(code)
+        );
+        self.push_lang(&mut url);
+
+        url
+    }
+
+    /// Builds the weather URL for a location key and data kind.
+    ///
+    /// - "now" → `/currentconditions/v1/{key}`,
+    /// - "forecast" → `/forecasts/v1/daily/5day/{key}`,
+    /// - "tomorrow" → `/forecasts/v1/daily/5day/{key}` as well; `1day` is
+    ///   today, so [`get_data`](ApiProvider::get_data) keeps the second day.
+    ///
+    /// [`extra_params`](Self::extra_params) are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind AccuWeather does not serve.
+    pub fn build_url(&self, location_key: &str, kind: DataKind) -> Result<String> {
+        let endpoint = match kind {
+            DataKind::Now => "currentconditions/v1",
+            DataKind::Forecast | DataKind::Tomorrow => "forecasts/v1/daily/5day",
+            _ => return Err(kind.unsupported(self.name())),
+        };
+
+        let mut url = format!(
+            "{}/{}/{}?apikey={}&details=true",
+            self.base_url, endpoint, location_key, self.api_key
+        );
+        if kind != DataKind::Now {
+            url.push_str("&metric=true");
+        }
+        self.push_lang(&mut url);
+
+        append_params(&mut url, &self.extra_params);
+
+        Ok(url)
+    }
+
+    /// Reads the key of the first location in a city search response, or
+    /// of the single location in a geoposition search response.
+    ///
+    /// # Errors
+    /// Returns an error naming the location if the response holds no location.
+    pub fn parse_location_key(location: &Location, body: &str) -> Result<String> {
+        let json: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| anyhow::anyhow!("invalid location response from AccuWeather: {}", e))?;
+
+        let place = match json.as_array() {
+            Some(locations) => locations.first(),
+            None => Some(&json),
+        };
+
+        place
+            .and_then(|place| place["Key"].as_str())
+            .map(String::from)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "location '{}' was not found by AccuWeather",
+                    location.label()
+                )
+            })
+    }
+
+    /// Resolves a location to its location key, asking the Locations API
+    /// only for locations not resolved before in this process.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails, the city is not found, or the
+    /// location is an airport code.
+    pub async fn location_key(&self, location: &Location) -> Result<String> {
+        let cache_key = (self.base_url.clone(), location.label().to_lowercase());
+        let cached = LOCATION_KEYS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&cache_key)
+            .cloned();
+        if let Some(key) = cached {
+            return Ok(key);
+        }
+
+        let url = match location {
+            Location::City(city) => self.search_url(city),
+            Location::Coords { lat, lon } => self.geoposition_url(*lat, *lon),
+            Location::Zip { code, country } => self.postal_code_url(code, country.as_deref()),
+            Location::Iata(_) => return Err(location.unsupported(self.name())),
+        };
+        let body = super::fetch(self.name(), &url).await?;
+        let key = Self::parse_location_key(location, &body)?;
+
+        LOCATION_KEYS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(cache_key, key.clone());
+        Ok(key)
+    }
+
+    fn push_lang(&self, url: &mut String) {
+        if let Some(lang) = &self.lang {
+            url.push_str("&language=");
+            url.push_str(&encode(lang));
+        }
+    }
+}
+
+#[async_trait]
+impl ApiProvider for AccuWeatherProvider {
+    fn name(&self) -> &str {
+        "accuweather"
+    }
+
+    /// The daily forecast endpoint used covers 5 days.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 5,
+            kinds: &[DataKind::Now, DataKind::Forecast, DataKind::Tomorrow],
+            history_since: None,
+        }
+    }
+
+    /// Looks up the location key of [`verify_location`](super::verify_location),
+    /// which costs one call instead of the two of a weather request.
+    fn verify_url(&self) -> Option<String> {
+        match super::verify_location() {
+            Location::Coords { lat, lon } => Some(self.geoposition_url(lat, lon)),
+            _ => None,
+        }
+    }
+
+    /// Returns the dates of the `DailyForecasts` entries.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        Some(
+            json["DailyForecasts"]
+                .as_array()?
+                .iter()
+                .filter_map(|day| parse_date(&day["Date"]))
+                .collect(),
+        )
+    }
+
+    /// Keeps the `DailyForecasts` entries dated inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        json["DailyForecasts"]
+            .as_array_mut()?
+            .retain(|day| parse_date(&day["Date"]).is_some_and(|d| window.contains(d)));
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads the single current conditions entry.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let current = json.get(0)?;
+        let metric = |field: &serde_json::Value| field["Metric"]["Value"].as_f64();
+
+        let mut point = SeriesPoint::at(from_epoch(&current["EpochTime"])?);
+        point.temp_c = metric(&current["Temperature"]);
+        point.feels_like_c = metric(&current["RealFeelTemperature"]);
+        point.humidity = current["RelativeHumidity"].as_f64();
+        point.pressure_mb = metric(&current["Pressure"]);
+        point.wind_kph = metric(&current["Wind"]["Speed"]);
+        point.precip_mm = metric(&current["PrecipitationSummary"]["PastHour"]);
+        point.cloud = current["CloudCover"].as_f64();
+        point.condition = current["WeatherText"].as_str().map(String::from);
+
+        Some(point)
+    }
+
+    /// Reads the `DailyForecasts` entries; the condition is the daytime one.
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["DailyForecasts"]
+            .as_array()?
+            .iter()
+            .filter_map(|day| {
+                let liquid = |half: &str| day[half]["TotalLiquid"]["Value"].as_f64();
+                Some(DailySummary {
+                    date: parse_date(&day["Date"])?,
+                    min_c: day["Temperature"]["Minimum"]["Value"].as_f64(),
+                    max_c: day["Temperature"]["Maximum"]["Value"].as_f64(),
+                    precip_mm: match (liquid("Day"), liquid("Night")) {
+                        (Some(d), Some(n)) => Some(d + n),
+                        (d, n) => d.or(n),
+                    },
+                    precip_chance: ["Day", "Night"]
+                        .iter()
+                        .filter_map(|half| day[*half]["PrecipitationProbability"].as_f64())
+                        .reduce(f64::max),
+                    max_wind_kph: ["Day", "Night"]
+                        .iter()
+                        .filter_map(|half| day[*half]["Wind"]["Speed"]["Value"].as_f64())
+                        .reduce(f64::max),
+                    avg_wind_kph: None,
+                    condition: day["Day"]["IconPhrase"].as_str().map(String::from),
+                    partial: false,
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// Searches the Locations API city search, see [`search_url`](Self::search_url).
+    fn location_search_url(&self, query: &str) -> Option<String> {
+        Some(self.search_url(&LocationInput::parse(query)))
+    }
+
+    /// Reads the `LocalizedName`, `AdministrativeArea`, `Country` and
+    /// `GeoPosition` of every match.
+    fn location_matches(&self, body: &str) -> Option<Vec<LocationMatch>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let matches = json
+            .as_array()?
+            .iter()
+            .filter_map(|place| {
+                Some(LocationMatch {
+                    name: place["LocalizedName"].as_str()?.to_string(),
+                    region: place["AdministrativeArea"]["LocalizedName"]
+                        .as_str()
+                        .map(String::from),
+                    country: place["Country"]["LocalizedName"].as_str().map(String::from),
+                    lat: place["GeoPosition"]["Latitude"].as_f64()?,
+                    lon: place["GeoPosition"]["Longitude"].as_f64()?,
+                })
+            })
+            .collect();
+
+        Some(matches)
+    }
+
+    /// The first request of [`get_data`](Self::get_data): the lookup of
+    /// the AccuWeather location key, which the weather URL is built with,
+    /// unless the key is known from an earlier lookup.
+    ///
+    /// # Errors
+    /// Returns an error for an airport code.
+    fn build_request(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        _days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        let cache_key = (self.base_url.clone(), location.label().to_lowercase());
+        let cached = LOCATION_KEYS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&cache_key)
+            .cloned();
+        if let Some(key) = cached {
+            return Ok(PlannedRequest::get(self.build_url(&key, kind)?));
+        }
+
+        Ok(PlannedRequest::lookup(match location {
+            Location::City(city) => self.search_url(city),
+            Location::Coords { lat, lon } => self.geoposition_url(*lat, *lon),
+            Location::Zip { code, country } => self.postal_code_url(code, country.as_deref()),
+            Location::Iata(_) => return Err(location.unsupported(self.name())),
+        }))
+    }
+
+    /// Retrieves weather data for the specified city.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - Location to fetch data for
+    /// * `kind` - Type of request:
+    ///   - "now" - current conditions
+    ///   - "forecast" - 5-day daily forecast
+    ///   - "tomorrow" - 1-day daily forecast
+    /// * `days` - Forecast length for "forecast"; the endpoint has no length
+    ///   parameter, so the response is trimmed to the first `days` dates
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - JSON response from the API as a string, or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The request type (`kind`) is not supported
+    /// * The city is not found by the Locations API
+    /// * The HTTP request fails
+    ///
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        // Check the kind before spending a location lookup on it
+        if !self.capabilities().kinds.contains(&kind) {
+            return Err(kind.unsupported(self.name()));
+        }
+
+        let key = self.location_key(&location).await?;
+        let body = super::fetch(self.name(), &self.build_url(&key, kind)?).await?;
+
+        // The daily forecasts start with today in the location's time zone
+        let dates = self.forecast_dates(&body).unwrap_or_default();
+        let kept = match (kind, effective_days(&self.kind_overrides, kind, days)) {
+            (DataKind::Tomorrow, _) => dates.get(1..2),
+            (DataKind::Forecast, Some(days)) => dates.get(..dates.len().min(days as usize)),
+            _ => None,
+        };
+
+        match kept.and_then(|kept| kept.first().zip(kept.last())) {
+            Some((&from, &to)) => Ok(self
+                .retain_dates(&body, DateWindow { from, to })
+                .unwrap_or(body)),
+            None => Ok(body),
+        }
+    }
+}
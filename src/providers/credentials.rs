@@ -0,0 +1,240 @@
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+
+/// Resolves a provider API key from the environment.
+///
+/// The key is read from `<VAR>` or, for Docker secrets and systemd
+/// credentials, from the file named by `<VAR>_FILE`. Setting both is
+/// rejected as ambiguous.
+///
+/// # Errors
+/// Returns an error if neither variable is set, both are set, or the key
+/// file cannot be read or is empty.
+pub fn resolve_key(var: &str) -> Result<String> {
+    resolve_key_with(var, |name| std::env::var(name).ok())
+}
+
+/// Same as [`resolve_key`], with variables looked up through `lookup`.
+pub fn resolve_key_with<F>(var: &str, lookup: F) -> Result<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let file_var = format!("{}_FILE", var);
+
+    match (lookup(var), lookup(&file_var)) {
+        (Some(_), Some(_)) => Err(anyhow!(
+            "both {} and {} are set; use only one of them",
+            var,
+            file_var
+        )),
+        (Some(key), None) => Ok(register_secret(key)),
+        (None, Some(path)) => read_key_file(&file_var, &path).map(register_secret),
+        (None, None) => Err(crate::error::WappError::MissingEnvVar(var.to_string()).into()),
+    }
+}
+
+/// Where the API key of one provider is looked up, in order of precedence:
+///
+/// 1. `explicit` — the `--api-key` flag,
+/// 2. `<VAR>` or `<VAR>_FILE`, see [`resolve_key`],
+/// 3. the OS keyring, when built with the `keyring` feature,
+/// 4. `configured` — the key saved in the config file by `configure --key`.
+pub struct CredentialStore {
+    pub explicit: Option<String>,
+    pub configured: Option<String>,
+    env: Lookup,
+    keyring: Lookup,
+}
+
+/// Looks a value up by name, e.g. an environment variable.
+type Lookup = Box<dyn Fn(&str) -> Option<String>>;
+
+impl CredentialStore {
+    /// Looks keys up in the process environment and the OS keyring.
+    pub fn new(explicit: Option<String>, configured: Option<String>) -> Self {
+        Self::with_lookups(
+            explicit,
+            configured,
+            |name| std::env::var(name).ok(),
+            keyring_get,
+        )
+    }
+
+    /// Same as [`new`](Self::new), with variables looked up through `env`
+    /// and keyring entries, by provider name, through `keyring`.
+    pub fn with_lookups<E, K>(
+        explicit: Option<String>,
+        configured: Option<String>,
+        env: E,
+        keyring: K,
+    ) -> Self
+    where
+        E: Fn(&str) -> Option<String> + 'static,
+        K: Fn(&str) -> Option<String> + 'static,
+    {
+        Self {
+            explicit,
+            configured,
+            env: Box::new(env),
+            keyring: Box::new(keyring),
+        }
+    }
+
+    /// The API key of `provider`, whose environment variable is `var`.
+    ///
+    /// # Errors
+    /// Same as [`resolve_key`], except that a missing variable is not an
+    /// error when the keyring or the config holds a key.
+    pub fn api_key(&self, var: &str, provider: &str) -> Result<String> {
+        if let Some(key) = &self.explicit {
+            return Ok(register_secret(key.clone()));
+        }
+
+        let env_set = (self.env)(var).is_some() || (self.env)(&format!("{}_FILE", var)).is_some();
+        if env_set {
+            return resolve_key_with(var, &self.env);
+        }
+
+        match (self.keyring)(provider).or_else(|| self.configured.clone()) {
+            Some(key) => Ok(register_secret(key)),
+            None => resolve_key_with(var, &self.env),
+        }
+    }
+}
+
+/// Service name of wapp's entries in the OS keyring; the entry's user is
+/// the provider name.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "wapp";
+
+/// Runs `f` on a thread of its own. On Linux the keyring talks to the
+/// Secret Service by blocking on a tokio runtime of its own, which panics
+/// on a thread already driving `main`'s.
+#[cfg(feature = "keyring")]
+fn off_runtime<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(f)
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// The key of `provider` in the OS keyring, if there is one.
+#[cfg(feature = "keyring")]
+pub fn keyring_get(provider: &str) -> Option<String> {
+    off_runtime(|| {
+        keyring::Entry::new(KEYRING_SERVICE, provider)
+            .ok()?
+            .get_password()
+            .ok()
+    })
+}
+
+/// Without the `keyring` feature there is no keyring to read.
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_get(_provider: &str) -> Option<String> {
+    None
+}
+
+/// Saves `key` for `provider` in the OS keyring.
+///
+/// # Errors
+/// Returns an error if the keyring cannot be reached or refuses the entry.
+#[cfg(feature = "keyring")]
+pub fn keyring_set(provider: &str, key: &str) -> Result<()> {
+    off_runtime(|| {
+        keyring::Entry::new(KEYRING_SERVICE, provider)?
+            .set_password(key)
+            .map_err(|e| anyhow!("cannot save the key of {} in the keyring: {}", provider, e))
+    })
+}
+
+/// Without the `keyring` feature, saving to the keyring is an error.
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_set(_provider: &str, _key: &str) -> Result<()> {
+    Err(anyhow!(
+        "this wapp was built without the keyring feature; save the key in the config instead"
+    ))
+}
+
+/// Deletes the key of `provider` from the OS keyring, returning whether
+/// there was one.
+///
+/// # Errors
+/// Returns an error if the keyring cannot be reached.
+#[cfg(feature = "keyring")]
+pub fn keyring_delete(provider: &str) -> Result<bool> {
+    off_runtime(
+        || match keyring::Entry::new(KEYRING_SERVICE, provider)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(anyhow!(
+                "cannot remove the key of {} from the keyring: {}",
+                provider,
+                e
+            )),
+        },
+    )
+}
+
+/// Without the `keyring` feature there is no keyring entry to delete.
+#[cfg(not(feature = "keyring"))]
+pub fn keyring_delete(_provider: &str) -> Result<bool> {
+    Ok(false)
+}
+
+/// Placeholder for secrets in debug and diagnostic output.
+pub const REDACTED: &str = "<redacted>";
+
+/// Returns a printable stand-in for an optional secret.
+pub fn redact(secret: &Option<String>) -> Option<&'static str> {
+    secret.as_ref().map(|_| REDACTED)
+}
+
+/// Secrets seen by this process, replaced by [`redact_secrets`].
+static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Shortest secret [`register_secret`] keeps; shorter ones would blank out
+/// common words.
+const MIN_SECRET_LEN: usize = 4;
+
+/// Remembers `secret`, e.g. a resolved API key, so [`redact_secrets`]
+/// hides it from any later message, and returns it.
+pub fn register_secret(secret: String) -> String {
+    let trimmed = secret.trim();
+    if trimmed.len() >= MIN_SECRET_LEN {
+        let mut secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+        if !secrets.iter().any(|known| known == trimmed) {
+            secrets.push(trimmed.to_string());
+        }
+    }
+    secret
+}
+
+/// `text` with every [registered](register_secret) secret replaced by
+/// [`REDACTED`], for messages that may quote a request or an answer, such
+/// as errors printed on stderr.
+pub fn redact_secrets(text: &str) -> String {
+    let secrets = SECRETS.lock().unwrap_or_else(|e| e.into_inner());
+
+    secrets.iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret, REDACTED)
+    })
+}
+
+/// Reads a key file, dropping the trailing newline most editors and
+/// secret stores append.
+fn read_key_file(file_var: &str, path: &str) -> Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("cannot read {} from {}: {}", file_var, path, e))?;
+
+    let key = contents.trim_end_matches(['\r', '\n']);
+
+    if key.trim().is_empty() {
+        return Err(anyhow!("{} points to an empty file: {}", file_var, path));
+    }
+
+    Ok(key.to_string())
+}
@@ -0,0 +1,162 @@
+use chrono::NaiveDate;
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use std::fmt;
+use std::str::FromStr;
+
+/// Type of weather data requested from a provider, the `--data` option.
+///
+/// - `now`: current conditions,
+/// - `forecast`: daily forecast,
+/// - `tomorrow`: the forecast of the next day,
+/// - `weekend`: the forecast of the coming Saturday and Sunday, or of what
+///   is left of the weekend under way, with a verdict, see
+///   [`weekend`](crate::weekend),
+/// - `hourly`: hour-by-hour forecast,
+/// - `alerts`: severe weather warnings in effect,
+/// - `air`: current air quality,
+/// - `astro`: sunrise, sunset and the moon of today, or as `astro:DATE` of
+///   that day (`get --data astro --date DATE`),
+/// - `marine`: waves, swell, water temperature and tides by day,
+/// - `snow`: snowfall, chance of snow, night lows and wind by day,
+/// - a date such as `2024-01-15`: that day's observations, for providers
+///   with [`history_since`](super::Capabilities::history_since).
+///
+/// Parsed from and displayed as the names above; a date is accepted
+/// wherever a name is, but not offered to shell completions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DataKind {
+    Now,
+    Forecast,
+    Tomorrow,
+    Weekend,
+    Hourly,
+    Alerts,
+    Air,
+    Astro(Option<NaiveDate>),
+    Marine,
+    Snow,
+    History(NaiveDate),
+}
+
+impl DataKind {
+    /// Every kind with a name, in the order they are listed to users.
+    pub const NAMED: [DataKind; 10] = [
+        DataKind::Now,
+        DataKind::Forecast,
+        DataKind::Tomorrow,
+        DataKind::Weekend,
+        DataKind::Hourly,
+        DataKind::Alerts,
+        DataKind::Air,
+        DataKind::Astro(None),
+        DataKind::Marine,
+        DataKind::Snow,
+    ];
+
+    /// The [`WappError::UnsupportedDataKind`](crate::error::WappError) of a
+    /// provider asked for a kind it does not serve, e.g. "data kind 'hourly'
+    /// is not supported by provider 'wttr'", or for a date "history is not
+    /// supported by provider 'wttr'".
+    pub fn unsupported(self, provider: &str) -> anyhow::Error {
+        crate::error::WappError::UnsupportedDataKind {
+            kind: self,
+            provider: provider.to_string(),
+        }
+        .into()
+    }
+
+    /// The kind without a day: `astro` for the astronomy of any day, the
+    /// kind itself otherwise. Providers list their kinds undated.
+    pub fn undated(self) -> Self {
+        match self {
+            DataKind::Astro(_) => DataKind::Astro(None),
+            kind => kind,
+        }
+    }
+
+    /// The kind fetched from providers: `weekend` is a forecast, cut to the
+    /// weekend when it is read; any other kind is itself.
+    pub fn fetched(self) -> Self {
+        match self {
+            DataKind::Weekend => DataKind::Forecast,
+            kind => kind,
+        }
+    }
+
+    /// The name of the kind; `None` for a date.
+    fn name(self) -> Option<&'static str> {
+        match self {
+            DataKind::Now => Some("now"),
+            DataKind::Forecast => Some("forecast"),
+            DataKind::Tomorrow => Some("tomorrow"),
+            DataKind::Weekend => Some("weekend"),
+            DataKind::Hourly => Some("hourly"),
+            DataKind::Alerts => Some("alerts"),
+            DataKind::Air => Some("air"),
+            DataKind::Astro(None) => Some("astro"),
+            DataKind::Marine => Some("marine"),
+            DataKind::Snow => Some("snow"),
+            DataKind::Astro(Some(_)) | DataKind::History(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for DataKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataKind::History(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            DataKind::Astro(Some(date)) => write!(f, "astro:{}", date.format("%Y-%m-%d")),
+            kind => f.write_str(kind.name().unwrap_or_default()),
+        }
+    }
+}
+
+impl FromStr for DataKind {
+    type Err = anyhow::Error;
+
+    /// Parses a kind name, ignoring case and surrounding whitespace, a
+    /// `YYYY-MM-DD` date, or `astro:YYYY-MM-DD`.
+    ///
+    /// # Errors
+    /// Returns an error listing the valid kinds.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+
+        if let Some(kind) = Self::NAMED
+            .into_iter()
+            .find(|kind| kind.name() == Some(name.as_str()))
+        {
+            return Ok(kind);
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(&name, "%Y-%m-%d") {
+            return Ok(DataKind::History(date));
+        }
+        if let Some(Ok(date)) = name
+            .strip_prefix("astro:")
+            .map(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        {
+            return Ok(DataKind::Astro(Some(date)));
+        }
+
+        Err(anyhow::anyhow!(
+            "unknown data kind '{}' (valid: {}, or a date as YYYY-MM-DD)",
+            s.trim(),
+            Self::NAMED.map(|kind| kind.to_string()).join(", ")
+        ))
+    }
+}
+
+impl ValueEnum for DataKind {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::NAMED
+    }
+
+    fn from_str(input: &str, _ignore_case: bool) -> Result<Self, String> {
+        input.parse().map_err(|e: anyhow::Error| e.to_string())
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        self.name().map(PossibleValue::new)
+    }
+}
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// One of the supported weather providers: a built-in one, or one
+/// registered by a program embedding wapp, see
+/// [`register_provider`](super::registry::register_provider).
+///
+/// Names are parsed case-insensitively and accept common aliases, e.g.
+/// `OpenWeather`, `owm`, and `openweathermap` for [`OpenWeather`]; they
+/// are written back as the canonical lowercase [`name`], so config files
+/// holding the exact names keep loading.
+///
+/// [`OpenWeather`]: ProviderKind::OpenWeather
+/// [`name`]: ProviderKind::name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum ProviderKind {
+    #[default]
+    WeatherApi,
+    OpenWeather,
+    AccuWeather,
+    OpenMeteo,
+    MetNo,
+    TomorrowIo,
+    VisualCrossing,
+    Wttr,
+    /// A registered provider, by its canonical name.
+    Custom(&'static str),
+}
+
+impl ProviderKind {
+    /// Every built-in provider, in the order they are listed to users; see
+    /// [`supported_providers`](super::registry::supported_providers) for
+    /// registered ones too.
+    pub const ALL: [ProviderKind; 8] = [
+        ProviderKind::WeatherApi,
+        ProviderKind::OpenWeather,
+        ProviderKind::AccuWeather,
+        ProviderKind::OpenMeteo,
+        ProviderKind::MetNo,
+        ProviderKind::TomorrowIo,
+        ProviderKind::VisualCrossing,
+        ProviderKind::Wttr,
+    ];
+
+    /// The canonical names of [`ALL`](Self::ALL), in the same order.
+    pub const NAMES: [&'static str; 8] = [
+        "weatherapi",
+        "openweather",
+        "accuweather",
+        "openmeteo",
+        "metno",
+        "tomorrowio",
+        "visualcrossing",
+        "wttr",
+    ];
+
+    /// Canonical name, as saved in the config, e.g. `openweather`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ProviderKind::WeatherApi => Self::NAMES[0],
+            ProviderKind::OpenWeather => Self::NAMES[1],
+            ProviderKind::AccuWeather => Self::NAMES[2],
+            ProviderKind::OpenMeteo => Self::NAMES[3],
+            ProviderKind::MetNo => Self::NAMES[4],
+            ProviderKind::TomorrowIo => Self::NAMES[5],
+            ProviderKind::VisualCrossing => Self::NAMES[6],
+            ProviderKind::Wttr => Self::NAMES[7],
+            ProviderKind::Custom(name) => name,
+        }
+    }
+
+    /// Other accepted spellings besides the canonical name.
+    pub fn aliases(self) -> &'static [&'static str] {
+        match self {
+            ProviderKind::WeatherApi => &["wapi"],
+            ProviderKind::OpenWeather => &["owm", "openweathermap"],
+            ProviderKind::AccuWeather => &[],
+            ProviderKind::OpenMeteo => &["open-meteo"],
+            ProviderKind::MetNo => &["met.no"],
+            ProviderKind::TomorrowIo => &["tomorrow.io"],
+            ProviderKind::VisualCrossing => &[],
+            ProviderKind::Wttr => &["wttr.in"],
+            ProviderKind::Custom(_) => &[],
+        }
+    }
+
+    /// Description and environment variables of the provider, what
+    /// `wapp providers` lists.
+    pub fn info(self) -> ProviderInfo {
+        let (description, required_env, optional_env): (_, &[&str], &[&str]) = match self {
+            ProviderKind::WeatherApi => (
+                "WeatherAPI.com, current weather and up to 14 forecast days",
+                &["WEATHERAPI_KEY"],
+                &["WEATHERAPI_BASE_URL", "WEATHERAPI_LANG"],
+            ),
+            ProviderKind::OpenWeather => (
+                "OpenWeatherMap, current weather and 5-day forecasts",
+                &["OPENWEATHER_KEY"],
+                &[
+                    "OPENWEATHER_BASE_URL",
+                    "OPENWEATHER_UNITS",
+                    "OPENWEATHER_LANG",
+                ],
+            ),
+            ProviderKind::AccuWeather => (
+                "AccuWeather, current conditions and daily forecasts",
+                &["ACCUWEATHER_KEY"],
+                &["ACCUWEATHER_BASE_URL"],
+            ),
+            ProviderKind::OpenMeteo => (
+                "Open-Meteo, free forecasts without an API key",
+                &[],
+                &[
+                    "OPENMETEO_BASE_URL",
+                    "OPENMETEO_GEOCODING_URL",
+                    "OPENMETEO_LANG",
+                ],
+            ),
+            ProviderKind::MetNo => (
+                "MET Norway, free forecasts without an API key",
+                &[],
+                &["METNO_BASE_URL", "METNO_GEOCODING_URL", "METNO_USER_AGENT"],
+            ),
+            ProviderKind::TomorrowIo => (
+                "Tomorrow.io, current weather and daily forecasts",
+                &["TOMORROWIO_KEY"],
+                &["TOMORROWIO_BASE_URL"],
+            ),
+            ProviderKind::VisualCrossing => (
+                "Visual Crossing, forecasts and historical weather",
+                &["VISUALCROSSING_KEY"],
+                &["VISUALCROSSING_BASE_URL"],
+            ),
+            ProviderKind::Wttr => (
+                "wttr.in, free forecasts without an API key",
+                &[],
+                &["WTTR_BASE_URL"],
+            ),
+            ProviderKind::Custom(_) => ("Registered by the program running wapp", &[], &[]),
+        };
+
+        ProviderInfo {
+            kind: self,
+            description,
+            required_env,
+            optional_env,
+        }
+    }
+}
+
+/// What a provider is and which environment variables it reads.
+///
+/// A required API key variable `<VAR>` may also be given as a file path in
+/// `<VAR>_FILE`, see [`resolve_key`](super::credentials::resolve_key).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderInfo {
+    pub kind: ProviderKind,
+    pub description: &'static str,
+    pub required_env: &'static [&'static str],
+    pub optional_env: &'static [&'static str],
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ProviderKind {
+    type Err = anyhow::Error;
+
+    /// Parses a name or alias of a
+    /// [supported provider](super::registry::supported_providers), ignoring
+    /// case and surrounding whitespace.
+    ///
+    /// # Errors
+    /// Returns a [`WappError::UnsupportedProvider`](crate::error::WappError)
+    /// listing the supported providers for an unknown name, suggesting the
+    /// closest one when it is only a typo away.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        let supported = super::registry::supported_providers();
+
+        if let Some(kind) = supported
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == name || kind.aliases().contains(&name.as_str()))
+        {
+            return Ok(kind);
+        }
+
+        let closest = supported
+            .into_iter()
+            .flat_map(|kind| std::iter::once(kind.name()).chain(kind.aliases().iter().copied()))
+            .map(|candidate| (edit_distance(&name, candidate), candidate))
+            .min()
+            .filter(|(distance, _)| *distance <= 2);
+
+        Err(crate::error::WappError::UnsupportedProvider {
+            name: s.trim().to_string(),
+            suggestion: closest.and_then(|(_, candidate)| candidate.parse().ok()),
+        }
+        .into())
+    }
+}
+
+impl Serialize for ProviderKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProviderKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
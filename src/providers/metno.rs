@@ -0,0 +1,413 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use super::openmeteo::{matches_url, parse_matches, plan_place, Place, DEFAULT_GEOCODING_URL};
+use super::{
+    append_params, effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest,
+    ProviderOverrides,
+};
+use crate::config::KindOverride;
+use crate::geocode::{Geocoder, Source};
+use crate::location::{Location, LocationMatch};
+use crate::request::DateWindow;
+use crate::series::{summarize_day, DailySummary, SeriesPoint};
+use crate::transport::HttpRequest;
+
+/// Default base URL of the Locationforecast API.
+pub const DEFAULT_BASE_URL: &str = "https://api.met.no/weatherapi/locationforecast/2.0";
+
+/// User-Agent sent when `METNO_USER_AGENT` is not set.
+///
+/// met.no blocks requests without a User-Agent that identifies the client.
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "wapp/",
+    env!("CARGO_PKG_VERSION"),
+    " github.com/ATverdyi/wapp"
+);
+
+/// Forecast length when none is requested.
+const DEFAULT_DAYS: u32 = 3;
+
+/// Last response per URL with its `Last-Modified` time, kept for the
+/// lifetime of the process so repeated requests can be conditional.
+static LAST_RESPONSES: Mutex<BTreeMap<String, (String, String)>> = Mutex::new(BTreeMap::new());
+
+/// Provider for working with the MET Norway Locationforecast API.
+///
+/// The API is free and needs no API key, but only accepts coordinates, so
+/// the city is first resolved through the Open-Meteo geocoder. Per the
+/// met.no terms of use, every request carries a descriptive User-Agent and
+/// repeated requests send `If-Modified-Since`.
+///
+/// # Fields
+///
+/// * `base_url` - Base URL of the Locationforecast API
+/// * `geocoding_url` - Base URL of the Open-Meteo geocoding API
+/// * `user_agent` - User-Agent header identifying the client
+/// * `extra_params` - Additional query parameters appended to every forecast request
+/// * `kind_overrides` - Per-kind request parameters from the config
+pub struct MetNoProvider {
+    pub base_url: String,
+    pub geocoding_url: String,
+    pub user_agent: String,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+impl MetNoProvider {
+    /// Creates a new instance of `MetNoProvider` from environment variables.
+    ///
+    /// # Environment Variables
+    ///
+    /// * `METNO_BASE_URL` (optional) - API base URL
+    ///   (default: "https://api.met.no/weatherapi/locationforecast/2.0")
+    /// * `METNO_GEOCODING_URL` (optional) - Geocoding API base URL
+    ///   (default: the Open-Meteo geocoder)
+    /// * `METNO_USER_AGENT` (optional) - User-Agent, ideally with contact details
+    ///   (default: [`DEFAULT_USER_AGENT`])
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new provider instance; no variable is required
+    ///
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with(&ProviderOverrides::default())
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence. `overrides.api_key`, `overrides.units` and
+    /// `overrides.lang` are ignored: no key is needed, values are always
+    /// metric, and conditions are symbol codes.
+    pub fn from_env_with(overrides: &ProviderOverrides) -> Result<Self> {
+        Ok(Self {
+            base_url: overrides
+                .env
+                .var("METNO_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            geocoding_url: overrides
+                .env
+                .var("METNO_GEOCODING_URL")
+                .unwrap_or(DEFAULT_GEOCODING_URL.into()),
+            user_agent: overrides
+                .env
+                .var("METNO_USER_AGENT")
+                .unwrap_or(DEFAULT_USER_AGENT.into()),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
+        })
+    }
+
+    /// The geocoder of the provider: Open-Meteo's geocoding API at
+    /// [`geocoding_url`](Self::geocoding_url).
+    pub fn geocoder(&self) -> Geocoder {
+        Geocoder::new(
+            self.name(),
+            Source::OpenMeteo {
+                base_url: self.geocoding_url.clone(),
+                lang: None,
+            },
+        )
+    }
+
+    /// Builds the `compact` forecast URL for a place.
+    ///
+    /// The URL is the same for every kind; coordinates are rounded to four
+    /// decimals as the terms of use require.
+    /// [`extra_params`](Self::extra_params) are appended last.
+    pub fn build_url(&self, place: &Place) -> String {
+        let mut url = format!(
+            "{}/compact?lat={:.4}&lon={:.4}",
+            self.base_url, place.latitude, place.longitude
+        );
+
+        append_params(&mut url, &self.extra_params);
+
+        url
+    }
+
+    /// Fetches `url`, sending `If-Modified-Since` when it was fetched before.
+    ///
+    /// On `304 Not Modified` the previous body is returned.
+    async fn fetch_forecast(&self, url: &str) -> Result<String> {
+        let previous = LAST_RESPONSES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(url)
+            .cloned();
+
+        let mut request = HttpRequest::get(url).header("User-Agent", &self.user_agent);
+        if let Some((last_modified, _)) = &previous {
+            request = request.header("If-Modified-Since", last_modified);
+        }
+
+        let response = super::send(self.name(), &request).await?;
+
+        match (response.status, previous) {
+            (304, Some((_, body))) => Ok(body),
+            (_, _) => {
+                if let Some(last_modified) = &response.last_modified {
+                    LAST_RESPONSES
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(
+                            url.to_string(),
+                            (last_modified.clone(), response.body.clone()),
+                        );
+                }
+                Ok(response.body)
+            }
+        }
+    }
+
+    /// Converts one `timeseries` entry; units are metric.
+    fn point(entry: &serde_json::Value) -> Option<SeriesPoint> {
+        let time = DateTime::parse_from_rfc3339(entry["time"].as_str()?).ok()?;
+        let data = &entry["data"];
+        let details = &data["instant"]["details"];
+        // The shortest period that is present; later entries only have 6 hours.
+        let next = ["next_1_hours", "next_6_hours"]
+            .iter()
+            .map(|period| &data[*period])
+            .find(|period| period.is_object());
+
+        let mut point = SeriesPoint::at(time.with_timezone(&Utc));
+        point.temp_c = details["air_temperature"].as_f64();
+        point.humidity = details["relative_humidity"].as_f64();
+        point.pressure_mb = details["air_pressure_at_sea_level"].as_f64();
+        point.wind_kph = details["wind_speed"].as_f64().map(|w| w * 3.6);
+        point.cloud = details["cloud_area_fraction"].as_f64();
+        point.precip_mm = next.and_then(|n| n["details"]["precipitation_amount"].as_f64());
+        point.condition = next
+            .and_then(|n| n["summary"]["symbol_code"].as_str())
+            .map(symbol_text);
+
+        Some(point)
+    }
+
+    /// Returns `body` with `timeseries` limited to the entries `keep` accepts.
+    fn retain_entries(
+        body: &str,
+        mut keep: impl FnMut(usize, &serde_json::Value) -> bool,
+    ) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let mut index = 0;
+        json["properties"]["timeseries"]
+            .as_array_mut()?
+            .retain(|entry| {
+                index += 1;
+                keep(index - 1, entry)
+            });
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+}
+
+#[async_trait]
+impl ApiProvider for MetNoProvider {
+    fn name(&self) -> &str {
+        "metno"
+    }
+
+    /// Locationforecast covers about 9 days.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 9,
+            kinds: &[DataKind::Now, DataKind::Forecast, DataKind::Tomorrow],
+            history_since: None,
+        }
+    }
+
+    /// Returns the distinct UTC dates of the `timeseries` entries.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let mut dates: Vec<NaiveDate> = json["properties"]["timeseries"]
+            .as_array()?
+            .iter()
+            .filter_map(entry_date)
+            .collect();
+        dates.dedup();
+
+        Some(dates)
+    }
+
+    /// Keeps the `timeseries` entries whose UTC date is inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        Self::retain_entries(body, |_, entry| {
+            entry_date(entry).is_some_and(|d| window.contains(d))
+        })
+    }
+
+    /// Reads the first `timeseries` entry.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        Self::point(&json["properties"]["timeseries"][0])
+    }
+
+    /// Aggregates the `timeseries` entries per UTC date.
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let points = self.series(body)?;
+
+        let mut days: Vec<(NaiveDate, Vec<SeriesPoint>)> = Vec::new();
+        for point in points {
+            let date = point.time.date_naive();
+            match days.last_mut() {
+                Some((last, points)) if *last == date => points.push(point),
+                _ => days.push((date, vec![point])),
+            }
+        }
+
+        Some(
+            days.iter()
+                .map(|(date, points)| summarize_day(*date, points))
+                .collect(),
+        )
+    }
+
+    /// Flattens the `timeseries` entries.
+    fn series(&self, body: &str) -> Option<Vec<SeriesPoint>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        Some(
+            json["properties"]["timeseries"]
+                .as_array()?
+                .iter()
+                .filter_map(Self::point)
+                .collect(),
+        )
+    }
+
+    /// Searches the Open-Meteo geocoding API, which also resolves cities
+    /// for [`get_data`](Self::get_data).
+    fn location_search_url(&self, query: &str) -> Option<String> {
+        Some(matches_url(&self.geocoding_url, query, None))
+    }
+
+    /// Reads the geocoding `results`, see [`parse_matches`].
+    fn location_matches(&self, body: &str) -> Option<Vec<LocationMatch>> {
+        parse_matches(body)
+    }
+
+    /// The request of [`get_data`](Self::get_data), with its User-Agent,
+    /// or for a city the geocoding lookup that comes first, see
+    /// [`plan_place`].
+    fn build_request(
+        &self,
+        location: &Location,
+        _kind: DataKind,
+        _days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        let plan = plan_place(
+            self.name(),
+            location,
+            |city| self.geocoder().source.search_url(city),
+            |place| Ok(self.build_url(place)),
+        )?;
+
+        Ok(match plan.lookup {
+            true => plan,
+            false => plan.header("User-Agent", &self.user_agent),
+        })
+    }
+
+    /// Retrieves weather data for the specified city.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - Location to fetch data for; resolved through the Open-Meteo geocoder
+    /// * `kind` - Type of request:
+    ///   - "now" - the first `timeseries` entry
+    ///   - "forecast" - the entries of the next `days` (default 3) UTC dates
+    ///   - "tomorrow" - the entries dated the day after the first entry
+    /// * `days` - Forecast length for "forecast"
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - JSON response from the API as a string, or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The city is not found by the geocoder
+    /// * The request type (`kind`) is not supported
+    /// * The HTTP request fails
+    /// * Reading the response text fails
+    ///
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        if !self.capabilities().kinds.contains(&kind) {
+            return Err(kind.unsupported(self.name()));
+        }
+
+        let place = self.geocoder().resolve(&location).await?;
+
+        let body = self.fetch_forecast(&self.build_url(&place)).await?;
+        let Some(first) = self.forecast_dates(&body).and_then(|d| d.first().copied()) else {
+            return Ok(body);
+        };
+
+        let trimmed = match kind {
+            DataKind::Now => Self::retain_entries(&body, |index, _| index == 0),
+            DataKind::Forecast => {
+                let days = effective_days(&self.kind_overrides, kind, days).unwrap_or(DEFAULT_DAYS);
+                self.retain_dates(
+                    &body,
+                    DateWindow {
+                        from: first,
+                        to: first + Duration::days(days as i64 - 1),
+                    },
+                )
+            }
+            _ => {
+                let tomorrow = first + Duration::days(1);
+                self.retain_dates(
+                    &body,
+                    DateWindow {
+                        from: tomorrow,
+                        to: tomorrow,
+                    },
+                )
+            }
+        };
+
+        Ok(trimmed.unwrap_or(body))
+    }
+}
+
+fn entry_date(entry: &serde_json::Value) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(entry["time"].as_str()?)
+        .ok()
+        .map(|t| t.with_timezone(&Utc).date_naive())
+}
+
+/// Turns a met.no symbol code into text, e.g. `lightrainshowers_day` →
+/// `Light rain showers`.
+pub fn symbol_text(code: &str) -> String {
+    let base = code.split('_').next().unwrap_or(code);
+
+    let mut text = base
+        .replace("clearsky", "clear sky")
+        .replace("partlycloudy", "partly cloudy")
+        .replace("showers", " showers")
+        .replace("andthunder", " and thunder");
+    for intensity in ["light", "heavy"] {
+        if let Some(rest) = text.strip_prefix(intensity) {
+            text = format!("{} {}", intensity, rest);
+        }
+    }
+
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => text,
+    }
+}
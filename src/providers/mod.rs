@@ -1,4 +1,16 @@
 use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::aqi::AirQuality;
+use crate::cache::{CacheOutcome, Validators};
+use crate::error::WappError;
+use crate::location::{Location, LocationMatch};
+use crate::request::{DateWindow, HourWindow};
+use crate::series::{DailySummary, SeriesPoint};
+use crate::timezone::Zone;
+use crate::weather::{Alert, Astronomy, MarineDay, SnowDay, WeatherData};
 
 /// A common interface for all weather API providers.
 ///
@@ -10,41 +22,1184 @@ use async_trait::async_trait;
 /// - return weather data as a `String` (raw JSON or formatted text),
 /// - handle provider-specific errors internally and convert them into `anyhow::Error`.
 ///
-/// # Method
+/// # Methods
 ///
 /// ## `get_data`
 /// Fetches weather data from the provider.
 ///
-/// * `city` — city name provided by the user.
-/// * `when` — time/data type such as `"now"`, `"forecast"`, `"tomorrow"`.
+/// * `location` — parsed location provided by the user; each provider formats
+///   its components the way its API expects.
+/// * `when` — type of data, see [`DataKind`].
+/// * `days` — requested forecast length, already validated against
+///   [`Capabilities::max_forecast_days`]; `None` keeps the provider default.
+///
+/// ## `capabilities`
+/// Describes the provider's limits so requests can be validated locally.
+///
+/// ## `forecast_dates` / `forecast_days`
+/// Lists and counts the forecast days contained in a response body, used to
+/// select a single day and to detect when the provider (or the user's plan)
+/// returned fewer days than requested.
+///
+/// ## `retain_dates`
+/// Trims a forecast response to the entries inside a date window.
+///
+/// ## `series`
+/// Flattens a forecast response into timestamped metric records for export.
+///
+/// ## `weather` / `get_weather`
+/// Maps a response onto the provider-independent [`WeatherData`], so
+/// consumers need not know the provider's JSON.
+///
+/// ## `alerts` / `air_quality` / `astronomy`
+/// Reads the warnings of an `alerts` response as [`Alert`]s, the report of
+/// an `air` response as [`AirQuality`], and the sun and moon of an `astro`
+/// response as [`Astronomy`].
+///
+/// ## `timezone`
+/// Reads the time zone of the location of a response, in which its times
+/// are shown, see [`Zone`].
+///
+/// ## `location_search_url` / `location_matches`
+/// Builds a location search request and reads its candidates, for
+/// providers with a search or geocoding endpoint, see
+/// [`search_locations`].
 ///
 /// # Example
 /// ```ignore
 /// let provider = WeatherApiProvider::from_env()?;
-/// let result = provider.get_data("London".into(), "now".into()).await?;
+/// let result = provider.get_data("London".into(), DataKind::Now, None).await?;
 /// println!("{}", result);
 /// ```
 #[async_trait]
 pub trait ApiProvider {
+    /// Short provider name used in messages, e.g. `"weatherapi"`.
+    fn name(&self) -> &str;
+
+    /// Returns the static limits of this provider.
+    fn capabilities(&self) -> Capabilities;
+
+    /// Returns the distinct forecast dates in `body`, in order, if it can be determined.
+    fn forecast_dates(&self, _body: &str) -> Option<Vec<NaiveDate>> {
+        None
+    }
+
+    /// Returns the number of distinct forecast days in `body`, if it can be determined.
+    fn forecast_days(&self, body: &str) -> Option<usize> {
+        self.forecast_dates(body).map(|dates| dates.len())
+    }
+
+    /// Returns `body` with only the forecast entries dated inside `window`,
+    /// or `None` if the response cannot be trimmed.
+    fn retain_dates(&self, _body: &str, _window: DateWindow) -> Option<String> {
+        None
+    }
+
+    /// Returns `body` with only the hourly entries timed inside `window`,
+    /// or `None` if the response cannot be trimmed.
+    fn retain_hours(&self, _body: &str, _window: HourWindow) -> Option<String> {
+        None
+    }
+
+    /// Returns the records of a forecast response in metric units, ordered by
+    /// time, or `None` if the response holds no series.
+    fn series(&self, _body: &str) -> Option<Vec<SeriesPoint>> {
+        None
+    }
+
+    /// Returns the current conditions of a `now` response in metric units,
+    /// or `None` if the response holds none.
+    fn current(&self, _body: &str) -> Option<SeriesPoint> {
+        None
+    }
+
+    /// Returns one summary per forecast day in metric units, or `None` if
+    /// the response holds no forecast.
+    fn daily(&self, _body: &str) -> Option<Vec<DailySummary>> {
+        None
+    }
+
+    /// Returns the warnings of an `alerts` response in the order the
+    /// provider lists them, or `None` if the response cannot be read; a
+    /// response without warnings gives an empty list.
+    fn alerts(&self, _body: &str) -> Option<Vec<Alert>> {
+        None
+    }
+
+    /// Returns the report of an `air` response, or `None` if the response
+    /// holds none.
+    fn air_quality(&self, _body: &str) -> Option<AirQuality> {
+        None
+    }
+
+    /// Returns the sun and moon of an `astro` response in the location's
+    /// local time, or `None` if the response holds neither.
+    fn astronomy(&self, _body: &str) -> Option<Astronomy> {
+        None
+    }
+
+    /// Returns the time zone of the location of `body`, or `None` if the
+    /// response does not tell it.
+    fn timezone(&self, _body: &str) -> Option<Zone> {
+        None
+    }
+
+    /// Returns the days of a `marine` response in the location's local
+    /// time and metric units, or `None` if the response holds none.
+    fn marine(&self, _body: &str) -> Option<Vec<MarineDay>> {
+        None
+    }
+
+    /// Returns the days of a `snow` response, or `None` if the response
+    /// holds none.
+    fn snow(&self, _body: &str) -> Option<Vec<SnowDay>> {
+        None
+    }
+
+    /// Returns `body` as [`WeatherData`], or `None` if it holds neither
+    /// current conditions nor a forecast.
+    ///
+    /// By default built from [`current`](Self::current) and
+    /// [`daily`](Self::daily), see [`from_views`](crate::weather::from_views).
+    fn weather(&self, body: &str) -> Option<WeatherData> {
+        crate::weather::from_views(self, body)
+    }
+
+    /// Returns the URL of the provider's location search for `query`, or
+    /// `None` if the provider has no search endpoint.
+    fn location_search_url(&self, _query: &str) -> Option<String> {
+        None
+    }
+
+    /// Returns the candidates of a location search response, or `None` if
+    /// the response cannot be read.
+    fn location_matches(&self, _body: &str) -> Option<Vec<LocationMatch>> {
+        None
+    }
+
+    /// Returns the URL of a cheap request that only succeeds with a valid
+    /// API key, see [`verify_key`], or `None` if the provider needs no key.
+    fn verify_url(&self) -> Option<String> {
+        None
+    }
+
+    /// Returns the first request [`get_data`](Self::get_data) would send
+    /// for `location`, `kind` and `days`, without sending it, for
+    /// `get --dry-run`.
+    ///
+    /// # Errors
+    /// Returns an error if the request cannot be built, e.g. for an unknown
+    /// kind, or the provider cannot show its requests.
+    fn build_request(
+        &self,
+        _location: &Location,
+        _kind: DataKind,
+        _days: Option<u32>,
+    ) -> anyhow::Result<PlannedRequest> {
+        Err(anyhow::anyhow!(
+            "'{}' cannot show its requests without sending them",
+            self.name()
+        ))
+    }
+
     /// Fetches weather data from the provider asynchronously.
     ///
     /// Returns raw response data as a `String`.
-    async fn get_data(&self, city: String, when: String) -> anyhow::Result<String>;
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind the provider does not
+    /// serve, else the error of the request.
+    async fn get_data(
+        &self,
+        location: Location,
+        when: DataKind,
+        days: Option<u32>,
+    ) -> anyhow::Result<String>;
+
+    /// Like [`get_data`](Self::get_data), with the response mapped onto
+    /// [`WeatherData`] through [`weather`](Self::weather).
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the response holds no
+    /// weather data.
+    async fn get_weather(
+        &self,
+        location: Location,
+        when: DataKind,
+        days: Option<u32>,
+    ) -> anyhow::Result<WeatherData>
+    where
+        Self: Sync,
+    {
+        let body = self.get_data(location, when, days).await?;
+
+        self.weather(&body).ok_or_else(|| {
+            anyhow::anyhow!("'{}' returned no weather data for '{}'", self.name(), when)
+        })
+    }
+}
+
+/// A request a provider would send, see [`ApiProvider::build_request`].
+///
+/// # Fields
+/// - `method`: the HTTP method, e.g. `GET`,
+/// - `url`: the full URL, API key included,
+/// - `headers`: headers besides those of every request, such as the
+///   [User-Agent](crate::http::USER_AGENT),
+/// - `lookup`: whether it looks the location up, e.g. a city's coordinates,
+///   so the weather request can only be built from its answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedRequest {
+    pub method: &'static str,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub lookup: bool,
+}
+
+impl PlannedRequest {
+    /// A `GET` of `url` without extra headers.
+    pub fn get(url: String) -> Self {
+        Self {
+            method: "GET",
+            url,
+            headers: Vec::new(),
+            lookup: false,
+        }
+    }
+
+    /// A `GET` of `url` looking the location up.
+    pub fn lookup(url: String) -> Self {
+        Self {
+            lookup: true,
+            ..Self::get(url)
+        }
+    }
+
+    /// The request with the header `name: value` added.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Static limits declared by a provider.
+///
+/// # Fields
+/// - `max_forecast_days`: the longest forecast horizon the API can serve.
+///   Plans may impose a lower limit, which is only detectable from the response.
+/// - `kinds`: data kinds accepted by `get_data`.
+/// - `history_since`: the first date `get_data` accepts as a
+///   [`DataKind::History`], returning that day's observations; `None`
+///   without history. Plans may reach back less far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub max_forecast_days: u32,
+    pub kinds: &'static [DataKind],
+    pub history_since: Option<NaiveDate>,
+}
+
+/// Data kinds served by most providers: every named kind but `alerts`,
+/// `air` and `astro`.
+pub const STANDARD_KINDS: &[DataKind] = &[
+    DataKind::Now,
+    DataKind::Forecast,
+    DataKind::Tomorrow,
+    DataKind::Hourly,
+];
+
+/// Validates a data kind against the provider's capabilities.
+///
+/// A [`DataKind::History`] date is a valid kind for providers with
+/// [`history_since`](Capabilities::history_since), if it is in their
+/// window, see [`validate_history`].
+///
+/// # Errors
+/// Returns [`DataKind::unsupported`] listing the supported kinds if `kind`
+/// is not one of them, or the error of [`validate_history`].
+pub fn validate_kind(provider: &dyn ApiProvider, kind: DataKind) -> anyhow::Result<()> {
+    let capabilities = provider.capabilities();
+
+    if let (Some(_), DataKind::History(date)) = (capabilities.history_since, kind) {
+        return validate_history(provider, date, chrono::Local::now().date_naive());
+    }
+
+    if !capabilities.kinds.contains(&kind.fetched().undated()) {
+        let supported: Vec<String> = capabilities.kinds.iter().map(|k| k.to_string()).collect();
+        return Err(anyhow::anyhow!(
+            "{} (supported: {}{})",
+            kind.unsupported(provider.name()),
+            supported.join(", "),
+            if capabilities.history_since.is_some() {
+                ", or a date as YYYY-MM-DD"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates the date of a [`DataKind::History`] request against the
+/// provider's history window, which ends `today`.
+///
+/// # Errors
+/// Returns an error if `date` is after `today`, or before the provider's
+/// [`history_since`](Capabilities::history_since) or it has none.
+pub fn validate_history(
+    provider: &dyn ApiProvider,
+    date: NaiveDate,
+    today: NaiveDate,
+) -> anyhow::Result<()> {
+    let Some(since) = provider.capabilities().history_since else {
+        return Err(DataKind::History(date).unsupported(provider.name()));
+    };
+
+    if date > today {
+        return Err(anyhow::anyhow!(
+            "--date {} is in the future; observations end today ({})",
+            date,
+            today
+        ));
+    }
+    if date < since {
+        return Err(anyhow::anyhow!(
+            "--date {} is before the history of provider '{}', which starts on {}",
+            date,
+            provider.name(),
+            since
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates a requested forecast length against the provider's horizon.
+///
+/// # Errors
+/// Returns an error if `days` is zero or exceeds `max_forecast_days`.
+pub fn validate_days(provider: &dyn ApiProvider, days: u32) -> anyhow::Result<()> {
+    let max = provider.capabilities().max_forecast_days;
+
+    if days == 0 || days > max {
+        return Err(anyhow::anyhow!(
+            "--days must be between 1 and {} for provider '{}' (got {})",
+            max,
+            provider.name(),
+            days
+        ));
+    }
+
+    Ok(())
+}
+
+/// Builds a warning when a response holds fewer forecast days than requested.
+///
+/// Returns `None` when the request was satisfied or the day count is unknown.
+pub fn days_shortfall(provider: &dyn ApiProvider, requested: u32, body: &str) -> Option<String> {
+    let returned = provider.forecast_days(body)?;
+
+    if returned >= requested as usize {
+        return None;
+    }
+
+    Some(format!(
+        "requested {} forecast days but '{}' returned only {} (your plan may limit the forecast horizon)",
+        requested,
+        provider.name(),
+        returned
+    ))
+}
+
+/// Query parameters wapp sets itself from the API key and location.
+///
+/// Extra parameters (`--param`, `extra_params` in the config) may not use
+/// these names, so they cannot clobber authentication or the location.
+pub const RESERVED_PARAMS: &[&str] = &[
+    "apikey",
+    "appid",
+    "key",
+    "q",
+    "lat",
+    "lon",
+    "latitude",
+    "longitude",
+    "location",
+];
+
+/// Parses a `--param key=value` argument.
+///
+/// Only the first `=` separates the name from the value, so values may
+/// contain `=`.
+///
+/// # Errors
+/// Returns an error if there is no `=`, the name is empty, or the name is
+/// one of [`RESERVED_PARAMS`].
+pub fn parse_param(arg: &str) -> anyhow::Result<(String, String)> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected KEY=VALUE, got '{}'", arg))?;
+    let key = key.trim();
+
+    if key.is_empty() {
+        return Err(anyhow::anyhow!("parameter name is empty in '{}'", arg));
+    }
+    validate_param(key)?;
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Rejects the names in [`RESERVED_PARAMS`] (compared case-insensitively).
+///
+/// # Errors
+/// Returns an error naming the reserved parameter.
+pub fn validate_param(key: &str) -> anyhow::Result<()> {
+    if RESERVED_PARAMS.contains(&key.to_ascii_lowercase().as_str()) {
+        return Err(anyhow::anyhow!(
+            "query parameter '{}' is reserved: wapp sets it from the API key or location",
+            key
+        ));
+    }
+
+    Ok(())
+}
+
+/// Combines the configured extra parameters with those given on the
+/// command line.
+///
+/// Configured parameters come first, in name order; a flag with the same
+/// name replaces the configured value. Flags keep their command-line order.
+///
+/// # Errors
+/// Returns an error if any name is reserved, see [`validate_param`].
+pub fn merge_params(
+    configured: &BTreeMap<String, String>,
+    flags: &[(String, String)],
+) -> anyhow::Result<Vec<(String, String)>> {
+    let mut params: Vec<(String, String)> = configured
+        .iter()
+        .filter(|(key, _)| !flags.iter().any(|(flag, _)| flag == *key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    params.extend(flags.iter().cloned());
+
+    for (key, _) in &params {
+        validate_param(key)?;
+    }
+
+    Ok(params)
+}
+
+/// Appends `&key=value` for every parameter, URL-encoding names and values.
+///
+/// A parameter replaces those of its name already in `url`, so
+/// `--param days=5` overrides the provider's own `days`.
+pub fn append_params(url: &mut String, params: &[(String, String)]) {
+    for (key, value) in params {
+        *url = url::without_param(url, key);
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&urlencoding::encode(key));
+        url.push('=');
+        url.push_str(&urlencoding::encode(value));
+    }
+}
+
+/// Sends a GET request for `provider` and returns the response body.
+///
+/// Every request is logged with the URL passed through
+/// [`redact_url`](crate::logging::redact_url), the HTTP status, and the
+/// elapsed time, and counted in [`metrics`](crate::metrics). Errors carry
+/// no URL, so the API key cannot leak through them either. The request
+/// gives up after the process [`timeouts`](crate::timeout::timeouts), and
+/// goes through its [`proxy`](crate::http::proxy).
+///
+/// # Errors
+/// Returns an error if the request fails, times out, or the body cannot be
+/// read.
+pub async fn fetch(provider: &str, url: &str) -> anyhow::Result<String> {
+    let response = send(provider, &HttpRequest::get(url)).await?;
+    Ok(response.body)
+}
+
+/// Like [`fetch`], failing with a [`WappError::Http`] unless the status is
+/// `2xx`; `error_message` reads the provider's error body into the message
+/// of the error.
+///
+/// # Errors
+/// Returns an error if the request fails, the body cannot be read, or the
+/// status is unsuccessful.
+pub async fn fetch_checked(
+    provider: &str,
+    url: &str,
+    error_message: fn(&str) -> Option<String>,
+) -> anyhow::Result<String> {
+    let response = send(provider, &HttpRequest::get(url)).await?;
+
+    if !(200..=299).contains(&response.status) {
+        return Err(WappError::Http {
+            provider: provider.to_string(),
+            status: response.status,
+            message: redact_secrets(
+                &error_message(&response.body)
+                    .unwrap_or_else(|| format!("'{}' answered HTTP {}", provider, response.status)),
+            ),
+            body: response.body,
+        }
+        .into());
+    }
+    Ok(response.body)
+}
+
+/// Searches the provider for locations matching `query`, e.g. to pick one
+/// of several cities of the same name.
+///
+/// # Errors
+/// Returns an error if the provider has no location search, the request
+/// fails, or the response cannot be read.
+pub async fn search_locations(
+    provider: &dyn ApiProvider,
+    query: &str,
+) -> anyhow::Result<Vec<LocationMatch>> {
+    let url = provider.location_search_url(query).ok_or_else(|| {
+        anyhow::anyhow!(
+            "location search is not supported by this provider ({})",
+            provider.name()
+        )
+    })?;
+
+    let body = fetch(provider.name(), &url).await?;
+    provider.location_matches(&body).ok_or_else(|| {
+        anyhow::anyhow!(
+            "invalid location search response from '{}'",
+            provider.name()
+        )
+    })
+}
+
+/// Location of the requests of [`ApiProvider::verify_url`]: central London,
+/// which every provider covers.
+pub fn verify_location() -> Location {
+    Location::Coords {
+        lat: 51.5074,
+        lon: -0.1278,
+    }
+}
+
+/// Checks the API key of `provider` with one request to its
+/// [`verify_url`](ApiProvider::verify_url); a provider without a key
+/// passes without a request.
+///
+/// # Errors
+/// Returns a [`KeyRejected`] error if the provider answers `401` or `403`,
+/// a [`WappError::Network`] if it cannot be reached, so the key could
+/// not be checked, and another error for any other unsuccessful status.
+pub async fn verify_key(provider: &dyn ApiProvider) -> anyhow::Result<()> {
+    let Some(url) = provider.verify_url() else {
+        return Ok(());
+    };
+
+    let response = send(provider.name(), &HttpRequest::get(&url)).await?;
+    match response.status {
+        200..=299 => Ok(()),
+        401 | 403 => Err(KeyRejected {
+            provider: provider.name().to_string(),
+            status: response.status,
+        }
+        .into()),
+        status => Err(anyhow::anyhow!(
+            "'{}' answered HTTP {} to the key check",
+            provider.name(),
+            status
+        )),
+    }
+}
+
+/// The provider refused the API key with `401 Unauthorized` or
+/// `403 Forbidden`, see [`verify_key`].
+#[derive(Debug)]
+pub struct KeyRejected {
+    pub provider: String,
+    pub status: u16,
+}
+
+impl std::fmt::Display for KeyRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' rejected the API key (HTTP {})",
+            self.provider, self.status
+        )
+    }
+}
+
+impl std::error::Error for KeyRejected {}
+
+/// Exit code of a request for a location the provider does not know, a
+/// [`WappError::CityNotFound`].
+pub const NOT_FOUND_EXIT_CODE: i32 = 5;
+
+/// Most close matches [`suggest_locations`] adds to a
+/// [`WappError::CityNotFound`].
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Turns a [`WappError::Http`] whose status and body `not_found`
+/// recognizes into a [`WappError::CityNotFound`] for `location`; other
+/// errors are returned as they are.
+pub fn not_found_as(
+    error: anyhow::Error,
+    location: &Location,
+    not_found: fn(u16, &str) -> bool,
+) -> anyhow::Error {
+    match error.downcast_ref() {
+        Some(WappError::Http {
+            provider,
+            status,
+            body,
+            ..
+        }) if not_found(*status, body) => WappError::CityNotFound {
+            provider: provider.clone(),
+            location: location.clone(),
+            suggestions: Vec::new(),
+        }
+        .into(),
+        _ => error,
+    }
+}
+
+/// Adds up to three close matches from the location search of `provider`
+/// to a [`WappError::CityNotFound`] for a city.
+///
+/// The search is best effort: other errors, other location kinds, providers
+/// without a search, and failed or slow searches leave `error` as it is.
+pub async fn suggest_locations(provider: &dyn ApiProvider, error: anyhow::Error) -> anyhow::Error {
+    let mut not_found = match error.downcast::<WappError>() {
+        Ok(not_found @ WappError::CityNotFound { .. }) => not_found,
+        Ok(other) => return other.into(),
+        Err(error) => return error,
+    };
+    let WappError::CityNotFound {
+        location,
+        suggestions,
+        ..
+    } = &mut not_found
+    else {
+        unreachable!()
+    };
+    if !matches!(location, Location::City(_)) || provider.location_search_url("").is_none() {
+        return not_found.into();
+    }
+
+    let query = location.query();
+    let search = search_locations(provider, &query);
+    match tokio::time::timeout(std::time::Duration::from_secs(5), search).await {
+        Ok(Ok(matches)) => {
+            for place in matches {
+                if suggestions.len() < MAX_SUGGESTIONS && !suggestions.contains(&place.name) {
+                    suggestions.push(place.name);
+                }
+            }
+        }
+        Ok(Err(e)) => tracing::info!(error = %e, "no location suggestions"),
+        Err(_) => tracing::info!("location suggestions timed out"),
+    }
+
+    not_found.into()
+}
+
+/// Exit code of a request refused with `429 Too Many Requests`, a
+/// [`WappError::RateLimited`].
+pub const RATE_LIMITED_EXIT_CODE: i32 = 6;
+
+/// Whether `error` is, or was caused by, a [`WappError::Network`] or a
+/// [`WappError::RateLimited`]: unlike a mistake in the request, such as an
+/// unknown city, another provider may still serve it.
+pub fn is_unavailable(error: &anyhow::Error) -> bool {
+    matches!(
+        WappError::of(error),
+        Some(WappError::Network { .. } | WappError::RateLimited { .. })
+    )
+}
+
+/// Whether `error` is, or was caused by, a [`WappError::Http`] `403
+/// Forbidden` telling of a used-up quota, as WeatherAPI's code 2007 "API
+/// key has exceeded calls per month quota" does: unlike a rejected key,
+/// another provider may still serve the request.
+pub fn is_quota_forbidden(error: &anyhow::Error) -> bool {
+    match WappError::of(error) {
+        Some(WappError::Http {
+            status: 403,
+            message,
+            body,
+            ..
+        }) => [message, body].iter().any(|text| {
+            let text = text.to_lowercase();
+            text.contains("quota") || text.contains("exceeded")
+        }),
+        _ => false,
+    }
+}
+
+/// Whether `error` is, or was caused by, a [`WappError::RateLimited`].
+pub fn is_rate_limited(error: &anyhow::Error) -> bool {
+    matches!(WappError::of(error), Some(WappError::RateLimited { .. }))
+}
+
+/// Whether `error` is, or was caused by, a [`WappError::Network`] that got
+/// no answer from the provider.
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    matches!(
+        WappError::of(error),
+        Some(WappError::Network {
+            unreachable: true,
+            ..
+        })
+    )
+}
+
+/// What [`send`] received.
+///
+/// # Fields
+/// - `status`: HTTP status code,
+/// - `last_modified`: the `Last-Modified` header, if any,
+/// - `etag`: the `ETag` header, if any; missing in recordings made before
+///   it was kept,
+/// - `retry_after`: the `Retry-After` header, if any,
+/// - `content_type`: the `Content-Type` header, if any; missing in
+///   recordings made before it was kept,
+/// - `body`: response body.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FetchResponse {
+    pub status: u16,
+    pub last_modified: Option<String>,
+    #[serde(default)]
+    pub etag: Option<String>,
+    pub retry_after: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+/// Characters of a response body quoted by a [`WappError::NonJson`].
+pub const EXCERPT_CHARS: usize = 200;
+
+/// Whether responses that are not JSON pass with a warning, see
+/// [`use_raw_responses`].
+static RAW_RESPONSES: AtomicBool = AtomicBool::new(false);
+
+/// Lets provider responses of this process through even when they are not
+/// JSON, with a warning on stderr instead of a [`WappError::NonJson`], so
+/// `get --raw` prints whatever came back.
+pub fn use_raw_responses(raw: bool) {
+    RAW_RESPONSES.store(raw, Ordering::Relaxed);
+}
+
+/// Checks that the body of a successful `response` from `provider` is
+/// JSON, as every provider answers, so an HTML page of a captive portal or
+/// proxy, or an empty body, is reported as such rather than as a parse
+/// error further on. The `Content-Type` is only reported: some providers
+/// send JSON as `text/plain`.
+///
+/// # Errors
+/// Returns a [`WappError::NonJson`] quoting the first [`EXCERPT_CHARS`]
+/// characters of the body, on one line, without control characters or
+/// secrets.
+pub fn check_json(provider: &str, response: &FetchResponse) -> anyhow::Result<()> {
+    if serde_json::from_str::<serde::de::IgnoredAny>(&response.body).is_ok() {
+        return Ok(());
+    }
+
+    let text = redact_secrets(&response.body);
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let line: String = words
+        .join(" ")
+        .chars()
+        .map(|c| if c.is_control() { '?' } else { c })
+        .collect();
+    let mut excerpt: String = line.chars().take(EXCERPT_CHARS).collect();
+    if excerpt.len() < line.len() {
+        excerpt.push('…');
+    }
+
+    Err(WappError::NonJson {
+        provider: provider.to_string(),
+        status: response.status,
+        content_type: response.content_type.clone(),
+        excerpt,
+    }
+    .into())
+}
+
+/// Like [`fetch`], for a prepared `request`, e.g. one with extra headers;
+/// the status and `Last-Modified` and `ETag` headers are returned with the
+/// body.
+///
+/// The request goes through the [`transport`](crate::transport::transport)
+/// of the process: the network, or a recording with `get --replay`.
+///
+/// While `get` caches responses, see
+/// [`response_cache`](crate::cache::response_cache), a response with
+/// validators is kept, and the same request later asks whether it changed
+/// with `If-None-Match` and `If-Modified-Since`. A `304 Not Modified`
+/// answer is returned as a `200` with the kept body, which is kept for
+/// longer; see [`ResponseCache::validated`](crate::cache::ResponseCache::validated).
+/// Whether the body was revalidated or fetched is logged.
+///
+/// A [`WappError::Network`] failure is repeated after a growing delay as
+/// the [`retry_policy`](crate::retry::retry_policy) allows; every retry is
+/// logged with its reason and delay, and counted in [`metrics`](crate::metrics).
+/// A [`WappError::RateLimited`] answer with a `Retry-After` is repeated once
+/// after that delay, if it is not longer than the policy's `max_retry_after`.
+///
+/// # Errors
+/// Returns a [`WappError::Network`] if the last attempt fails, its body
+/// cannot be read, or its status is a server error, a
+/// [`WappError::RateLimited`] for `429`, a [`WappError::NonJson`] for a
+/// successful status without JSON, see [`check_json`], and a
+/// [`WappError::QuotaExhausted`] without a request once the provider has
+/// used its daily [`quota`](crate::quota::quota).
+pub async fn send(provider: &str, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+    let Some(cache) = crate::cache::response_cache() else {
+        return send_with_retries(provider, request).await;
+    };
+    let cache = cache.at(crate::cache::now_secs());
+    let url_redacted = crate::logging::redact_url(&request.url);
+
+    let stored = cache.validated(request);
+    let mut conditional = request.clone();
+    for (name, value) in stored.iter().flat_map(|entry| entry.validators.headers()) {
+        conditional = conditional.header(name, value);
+    }
+    let response = send_with_retries(provider, &conditional).await?;
+    let stored = match stored {
+        Some(stored) if response.status == 304 => stored,
+        _ => {
+            cache.keep_validated(request, provider, &response);
+            if (200..=299).contains(&response.status) {
+                tracing::info!(
+                    provider,
+                    url_redacted,
+                    outcome = CacheOutcome::Fetched.as_str(),
+                    "response fetched"
+                );
+            }
+            return Ok(response);
+        }
+    };
+
+    cache.revalidated(&stored, Validators::of(&response));
+    tracing::info!(
+        provider,
+        url_redacted,
+        outcome = CacheOutcome::Revalidated.as_str(),
+        age_secs = cache.now.saturating_sub(stored.created),
+        "response not modified"
+    );
+    Ok(FetchResponse {
+        status: 200,
+        last_modified: response.last_modified.or(stored.validators.last_modified),
+        etag: response.etag.or(stored.validators.etag),
+        retry_after: None,
+        content_type: response.content_type,
+        body: stored.body,
+    })
+}
+
+/// Sends `request` as [`send`] does, without revalidating.
+async fn send_with_retries(provider: &str, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+    let policy = crate::retry::retry_policy();
+    let mut attempt = 1;
+    let mut waited = false;
+
+    loop {
+        let e = match send_once(provider, attempt, request).await {
+            Err(e) if attempt < policy.attempts && is_unavailable(&e) => e,
+            result => return result,
+        };
+        let asked = match WappError::of(&e) {
+            Some(WappError::RateLimited { retry_after, .. }) => *retry_after,
+            _ => None,
+        };
+        let delay = match asked {
+            // The provider said how long to wait: waited once, if not too long.
+            Some(wait) if wait <= policy.max_retry_after && !waited => {
+                waited = true;
+                wait
+            }
+            Some(_) => return Err(e),
+            None => policy.delay(attempt, crate::retry::clock_jitter()),
+        };
+
+        tracing::info!(
+            provider,
+            attempt,
+            delay_ms = delay.as_millis() as u64,
+            reason = %e,
+            "retrying provider request"
+        );
+        crate::metrics::record_retry();
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// One attempt of [`send`], in a `request` span naming the provider, the
+/// target host, and the attempt, which records the status and elapsed time
+/// once known.
+async fn send_once(
+    provider: &str,
+    attempt: u32,
+    request: &HttpRequest,
+) -> anyhow::Result<FetchResponse> {
+    let host = reqwest::Url::parse(&request.url)
+        .ok()
+        .and_then(|url| url.host_str().map(String::from))
+        .unwrap_or_default();
+    let span = tracing::info_span!(
+        "request",
+        provider,
+        host,
+        attempt,
+        status = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    );
+    send_attempt(provider, request).instrument(span).await
 }
 
+/// Sends `request` and logs its outcome; see [`send_once`].
+async fn send_attempt(provider: &str, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+    let started = std::time::Instant::now();
+    let url_redacted = crate::logging::redact_url(&request.url);
+    // Messages quoting the request, e.g. from a proxy, must not show the key.
+    for secret in crate::logging::url_secrets(&request.url) {
+        register_secret(secret);
+    }
+    if let Some(quota) = crate::quota::quota() {
+        quota.spend(provider, chrono::Local::now().naive_local())?;
+    }
+
+    let result = crate::transport::transport().send(request).await;
+    let elapsed = started.elapsed();
+    let elapsed_ms = elapsed.as_millis() as u64;
+    let span = tracing::Span::current();
+    span.record("elapsed_ms", elapsed_ms);
+    if let Ok(response) = &result {
+        span.record("status", response.status);
+    }
+
+    crate::metrics::record_request(
+        provider,
+        elapsed,
+        result.as_ref().ok().map(|r| r.body.len() as u64),
+    );
+
+    match result {
+        Ok(response) => {
+            tracing::info!(
+                provider,
+                url_redacted,
+                status = response.status,
+                elapsed_ms,
+                cache_hit = false,
+                "provider request"
+            );
+            if response.status == 429 {
+                let retry_after = response
+                    .retry_after
+                    .as_deref()
+                    .and_then(|value| crate::retry::parse_retry_after(value, chrono::Utc::now()));
+                return Err(WappError::RateLimited {
+                    provider: provider.to_string(),
+                    retry_after,
+                }
+                .into());
+            }
+            if response.status >= 500 {
+                return Err(WappError::Network {
+                    provider: provider.to_string(),
+                    reason: format!("'{}' answered HTTP {}", provider, response.status),
+                    unreachable: false,
+                }
+                .into());
+            }
+            if (200..=299).contains(&response.status) {
+                if let Err(e) = check_json(provider, &response) {
+                    tracing::warn!(provider, url_redacted, error = %e, "response is not JSON");
+                    if !RAW_RESPONSES.load(Ordering::Relaxed) {
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(response)
+        }
+        Err(e) => {
+            let failure = match e.downcast::<TransportError>() {
+                Ok(failure) => failure,
+                // Not a network failure, e.g. a request without a recording.
+                Err(e) => {
+                    tracing::warn!(provider, url_redacted, error = %e, "provider request failed");
+                    return Err(e);
+                }
+            };
+            let timeouts = crate::timeout::timeouts();
+            let reason = match (failure.timeout, failure.connect) {
+                (true, true) => format!(
+                    "could not connect to '{}' within {}",
+                    provider,
+                    crate::timeout::describe_limit(timeouts.connect)
+                ),
+                (true, false) => format!(
+                    "'{}' did not answer within {}",
+                    provider,
+                    crate::timeout::describe_limit(timeouts.request)
+                ),
+                _ => redact_secrets(&failure.reason),
+            };
+            tracing::warn!(
+                provider,
+                url_redacted,
+                elapsed_ms,
+                cache_hit = false,
+                error = %reason,
+                "provider request failed"
+            );
+            Err(WappError::Network {
+                provider: provider.to_string(),
+                reason,
+                unreachable: failure.connect || failure.timeout,
+            }
+            .into())
+        }
+    }
+}
+
+/// Data kinds whose forecast length can be overridden.
+pub const SIZED_KINDS: &[DataKind] = &[DataKind::Forecast, DataKind::Hourly];
+
+/// Validates the `kind_overrides` config of a provider.
+///
+/// # Errors
+/// Returns an error if a kind is not supported by the provider, has no
+/// adjustable length, or its `days` is outside the provider's horizon.
+pub fn validate_kind_overrides(
+    provider: &dyn ApiProvider,
+    overrides: &BTreeMap<String, KindOverride>,
+) -> anyhow::Result<()> {
+    for (kind, kind_override) in overrides {
+        let context = || format!("providers.{}.kind_overrides.{}", provider.name(), kind);
+
+        let kind: DataKind = kind
+            .parse()
+            .map_err(|e| anyhow::anyhow!("{}: {}", context(), e))?;
+        validate_kind(provider, kind).map_err(|e| anyhow::anyhow!("{}: {}", context(), e))?;
+
+        if let Some(days) = kind_override.days {
+            if !SIZED_KINDS.contains(&kind) {
+                return Err(anyhow::anyhow!(
+                    "{}: 'days' only applies to {} and {}",
+                    context(),
+                    DataKind::Forecast,
+                    DataKind::Hourly
+                ));
+            }
+
+            let max = provider.capabilities().max_forecast_days;
+            if days == 0 || days > max {
+                return Err(anyhow::anyhow!(
+                    "{}: days must be between 1 and {} (got {})",
+                    context(),
+                    max,
+                    days
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Forecast length to request for `kind`: the explicit `days`, else the
+/// kind override, else `None` (the provider's built-in default).
+pub fn effective_days(
+    overrides: &BTreeMap<String, KindOverride>,
+    kind: DataKind,
+    days: Option<u32>,
+) -> Option<u32> {
+    days.or_else(|| overrides.get(&kind.to_string()).and_then(|o| o.days))
+}
+
+/// AccuWeather provider implementation.
+/// Located in `providers/accuweather.rs`.
+pub mod accuweather;
+
+/// API key resolution shared by all providers.
+/// Located in `providers/credentials.rs`.
+pub mod credentials;
+
+/// Types of weather data.
+/// Located in `providers/data_kind.rs`.
+pub mod data_kind;
+
+/// Names of the supported providers.
+/// Located in `providers/kind.rs`.
+pub mod kind;
+
+/// MET Norway provider implementation.
+/// Located in `providers/metno.rs`.
+pub mod metno;
+
+/// Open-Meteo provider implementation.
+/// Located in `providers/openmeteo.rs`.
+pub mod openmeteo;
+
 /// OpenWeatherMap provider implementation.
 /// Located in `providers/openweather.rs`.
 pub mod openweather;
 
+/// Providers by name, built-in and registered ones.
+/// Located in `providers/registry.rs`.
+pub mod registry;
+
+/// Test doubles of providers, for programs embedding wapp.
+/// Located in `providers/testing.rs`.
+#[cfg(feature = "test-util")]
+pub mod testing;
+
+/// Tomorrow.io provider implementation.
+/// Located in `providers/tomorrowio.rs`.
+pub mod tomorrowio;
+
+/// Request URLs with encoded query parameters.
+/// Located in `providers/url.rs`.
+pub mod url;
+
+/// Visual Crossing provider implementation.
+/// Located in `providers/visualcrossing.rs`.
+pub mod visualcrossing;
+
 /// WeatherAPI provider implementation.
 /// Located in `providers/weatherapi.rs`.
 pub mod weatherapi;
 
+/// wttr.in provider implementation.
+/// Located in `providers/wttr.rs`.
+pub mod wttr;
+
 /// Re-export for easier access to provider types.
+pub use accuweather::AccuWeatherProvider;
+pub use data_kind::DataKind;
+pub use kind::ProviderKind;
+pub use metno::MetNoProvider;
+pub use openmeteo::OpenMeteoProvider;
 pub use openweather::OpenWeatherProvider;
+pub use tomorrowio::TomorrowIoProvider;
+pub use visualcrossing::VisualCrossingProvider;
 pub use weatherapi::WeatherApiProvider;
+pub use wttr::WttrProvider;
 
-use crate::config::AppConfig;
+use crate::config::{AppConfig, KindOverride};
+use crate::transport::{HttpRequest, TransportError};
+use credentials::{redact_secrets, register_secret};
+use tracing::Instrument;
 
 /// Factory function that constructs the appropriate API provider
 /// based on the application's configuration.
@@ -54,11 +1209,13 @@ use crate::config::AppConfig;
 /// instance of the correct provider implementation.
 ///
 /// Each provider must expose a `from_env()` constructor, which loads
-/// required environment variables (API key, base URL, etc.).
+/// required environment variables (API key, base URL, etc.); providers with
+/// an API key are built through `from_sources()`, which also looks in the OS
+/// keyring and the config. Open-Meteo, MET Norway and wttr.in need no API
+/// key, so no credentials are passed to them.
 ///
 /// # Errors
 /// Returns an error if:
-/// - the provider name is unknown,
 /// - environment variables required by the provider are missing,
 /// - provider initialization fails for any other reason.
 ///
@@ -66,13 +1223,338 @@ use crate::config::AppConfig;
 /// ```ignore
 /// let cfg = load_config()?;
 /// let provider = provider_factory(&cfg)?;
-/// let result = provider.get_data("Tokyo".into(), "forecast".into()).await?;
+/// let result = provider.get_data("Tokyo".into(), "forecast".into(), None).await?;
 /// println!("{}", result);
 /// ```
 pub fn provider_factory(cfg: &AppConfig) -> anyhow::Result<Box<dyn ApiProvider>> {
-    match cfg.provider.as_str() {
-        "weatherapi" => Ok(Box::new(WeatherApiProvider::from_env()?)),
-        "openweather" => Ok(Box::new(OpenWeatherProvider::from_env()?)),
-        other => Err(anyhow::anyhow!("Unsupported provider: {}", other)),
+    provider_factory_with(cfg, &ProviderOverrides::default())
+}
+
+/// Per-invocation settings that take precedence over environment variables
+/// and the config file.
+///
+/// # Fields
+/// - `provider`: provider to use instead of the configured one,
+/// - `api_key`: API key for this call; never shown in debug output,
+/// - `units`: units of measurement (`metric`, `imperial`, `standard`);
+///   only OpenWeatherMap accepts a units parameter, WeatherAPI always
+///   returns both metric and imperial values,
+/// - `lang`: response language code,
+/// - `extra_params`: query parameters appended to every request
+///   (`--param key=value`),
+/// - `kind_overrides`: per-kind request parameters, filled in from the
+///   config by [`provider_factory_with`],
+/// - `config_units`, `config_lang`: units and language saved in the config,
+///   filled in by [`provider_factory_with`]; unlike `units` and `lang`, the
+///   provider's environment variables take precedence over them,
+/// - `env`: where the provider's environment variables are read, the
+///   process environment by default, see [`EnvSource`].
+#[derive(Clone, Default)]
+pub struct ProviderOverrides {
+    pub provider: Option<ProviderKind>,
+    pub api_key: Option<String>,
+    pub units: Option<String>,
+    pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+    pub config_units: Option<String>,
+    pub config_lang: Option<String>,
+    pub env: EnvSource,
+}
+
+/// Debug output never includes the API key.
+impl std::fmt::Debug for ProviderOverrides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderOverrides")
+            .field("provider", &self.provider)
+            .field("api_key", &credentials::redact(&self.api_key))
+            .field("units", &self.units)
+            .field("lang", &self.lang)
+            .field("extra_params", &self.extra_params)
+            .field("kind_overrides", &self.kind_overrides)
+            .field("config_units", &self.config_units)
+            .field("config_lang", &self.config_lang)
+            .field("env", &self.env)
+            .finish()
+    }
+}
+
+/// Where providers read their environment variables (API keys, base URLs,
+/// `*_LANG` and the like): the process environment, or given variables, so
+/// that tests and programs embedding wapp neither depend on nor change the
+/// environment of the process.
+#[derive(Clone)]
+pub struct EnvSource(Option<std::sync::Arc<BTreeMap<String, String>>>);
+
+impl EnvSource {
+    /// The environment of the process.
+    pub fn process() -> Self {
+        Self(None)
+    }
+
+    /// Exactly `vars`; any other variable is unset.
+    pub fn from_vars<I, K, V>(vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let vars = vars
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+        Self(Some(std::sync::Arc::new(vars)))
+    }
+
+    /// The value of the variable `name`, if it is set.
+    pub fn var(&self, name: &str) -> Option<String> {
+        match &self.0 {
+            Some(vars) => vars.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        }
+    }
+}
+
+impl Default for EnvSource {
+    fn default() -> Self {
+        Self::process()
+    }
+}
+
+/// Debug output names the variables, not their values, which may be keys.
+impl std::fmt::Debug for EnvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(vars) => f.debug_set().entries(vars.keys()).finish(),
+            None => f.write_str("process"),
+        }
     }
 }
+
+/// Like [`provider_factory`], but applies `overrides` on top of the
+/// configured provider and the settings loaded from the environment, which
+/// is `overrides.env`: given variables instead of the process environment
+/// make the provider independent of it.
+///
+/// The API key is looked up as described at
+/// [`CredentialStore`](credentials::CredentialStore), with `overrides.api_key`
+/// first and the key saved for the provider in `cfg` last. The saved `units`
+/// and `lang` apply to every provider unless `overrides` or the provider's
+/// environment variables set them. The selected
+/// provider's `extra_params` from `cfg` are merged with
+/// `overrides.extra_params`, see [`merge_params`], and its `kind_overrides`
+/// are checked with [`validate_kind_overrides`].
+///
+/// # Errors
+/// Same as [`provider_factory`], and also if an extra parameter is reserved
+/// or a kind override is invalid for the provider; all of them are
+/// configuration [`WappError`]s.
+pub fn provider_factory_with(
+    cfg: &AppConfig,
+    overrides: &ProviderOverrides,
+) -> anyhow::Result<Box<dyn ApiProvider>> {
+    create_provider(cfg, overrides).map_err(crate::error::config_error)
+}
+
+/// [`provider_factory_with`], with errors as they come: the provider is
+/// looked up in the [`default_registry`](registry::default_registry).
+fn create_provider(
+    cfg: &AppConfig,
+    overrides: &ProviderOverrides,
+) -> anyhow::Result<Box<dyn ApiProvider>> {
+    let kind = overrides.provider.unwrap_or(cfg.provider);
+    let _span = tracing::debug_span!("provider", provider = kind.name()).entered();
+
+    registry::default_registry().create(kind, cfg, overrides)
+}
+
+/// Creates the built-in provider `kind`, see [`provider_factory_with`].
+fn create_builtin(
+    kind: ProviderKind,
+    cfg: &AppConfig,
+    overrides: &ProviderOverrides,
+) -> anyhow::Result<Box<dyn ApiProvider>> {
+    let env = overrides.env.clone();
+    let credentials = credentials::CredentialStore::with_lookups(
+        overrides.api_key.clone(),
+        cfg.api_keys.get(kind.name()).cloned(),
+        move |name| env.var(name),
+        credentials::keyring_get,
+    );
+
+    let settings = cfg.providers.get(kind.name()).cloned().unwrap_or_default();
+    let overrides = &ProviderOverrides {
+        config_units: cfg.units.clone(),
+        config_lang: cfg.lang.clone(),
+        extra_params: merge_params(&settings.extra_params, &overrides.extra_params)?,
+        kind_overrides: settings.kind_overrides,
+        ..overrides.clone()
+    };
+
+    let provider: Box<dyn ApiProvider> = match kind {
+        ProviderKind::WeatherApi => {
+            Box::new(WeatherApiProvider::from_sources(overrides, &credentials)?)
+        }
+        ProviderKind::OpenWeather => {
+            Box::new(OpenWeatherProvider::from_sources(overrides, &credentials)?)
+        }
+        ProviderKind::OpenMeteo => Box::new(OpenMeteoProvider::from_env_with(overrides)?),
+        ProviderKind::VisualCrossing => Box::new(VisualCrossingProvider::from_sources(
+            overrides,
+            &credentials,
+        )?),
+        ProviderKind::AccuWeather => {
+            Box::new(AccuWeatherProvider::from_sources(overrides, &credentials)?)
+        }
+        ProviderKind::MetNo => Box::new(MetNoProvider::from_env_with(overrides)?),
+        ProviderKind::Wttr => Box::new(WttrProvider::from_env_with(overrides)?),
+        ProviderKind::TomorrowIo => {
+            Box::new(TomorrowIoProvider::from_sources(overrides, &credentials)?)
+        }
+        ProviderKind::Custom(name) => {
+            return Err(anyhow::anyhow!("provider '{}' is not built in", name))
+        }
+    };
+
+    validate_kind_overrides(provider.as_ref(), &overrides.kind_overrides)?;
+    Ok(provider)
+}
+
+/// The provider of [`provider_factory_with`], followed by the rest of the
+/// configured [`provider_chain`](AppConfig::provider_chain) unless
+/// `overrides.provider` selects a single provider.
+///
+/// The fallback providers get `overrides` without the API key, which
+/// belongs to the selected provider. A fallback provider that cannot be
+/// created is left out with a warning.
+///
+/// # Errors
+/// Same as [`provider_factory_with`] for the selected provider.
+pub fn provider_chain_with(
+    cfg: &AppConfig,
+    overrides: &ProviderOverrides,
+) -> anyhow::Result<Vec<Box<dyn ApiProvider>>> {
+    let mut chain = vec![provider_factory_with(cfg, overrides)?];
+    if overrides.provider.is_some() {
+        return Ok(chain);
+    }
+
+    for kind in cfg.provider_chain().into_iter().skip(1) {
+        let overrides = ProviderOverrides {
+            provider: Some(kind),
+            api_key: None,
+            ..overrides.clone()
+        };
+        match provider_factory_with(cfg, &overrides) {
+            Ok(provider) => chain.push(provider),
+            Err(e) => {
+                tracing::warn!(provider = kind.name(), error = %e, "fallback provider skipped")
+            }
+        }
+    }
+
+    Ok(chain)
+}
+
+/// A provider left out of a multi-provider run, with the reason, e.g. a
+/// missing API key.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SkippedProvider {
+    pub provider: String,
+    pub reason: String,
+}
+
+/// Creates every provider of `kinds` that can be set up from `cfg` and the
+/// environment, see [`provider_factory_with`], in the order of `kinds`.
+///
+/// A provider that cannot be created, usually because its key is not set,
+/// is returned as a [`SkippedProvider`] instead of failing the others.
+pub fn available_providers(
+    cfg: &AppConfig,
+    kinds: &[ProviderKind],
+) -> (Vec<Box<dyn ApiProvider>>, Vec<SkippedProvider>) {
+    let mut providers = Vec::new();
+    let mut skipped = Vec::new();
+
+    for kind in kinds {
+        let overrides = ProviderOverrides {
+            provider: Some(*kind),
+            ..Default::default()
+        };
+        match provider_factory_with(cfg, &overrides) {
+            Ok(provider) => providers.push(provider),
+            Err(e) => skipped.push(SkippedProvider {
+                provider: kind.to_string(),
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    (providers, skipped)
+}
+
+/// Whether one provider can be used, as listed by `wapp providers`.
+///
+/// # Fields
+/// - `provider`: canonical provider name,
+/// - `description`: see [`ProviderInfo`](kind::ProviderInfo),
+/// - `configured`: whether it is the provider in the config file,
+/// - `available`: whether the provider could be created from the
+///   environment and config, without any network request,
+/// - `missing_env`: required variables set neither directly nor as
+///   `<VAR>_FILE`,
+/// - `error`: why the provider could not be created,
+/// - `quota`: today's calls against the daily limit in the config, see
+///   [`Quota`](crate::quota::Quota).
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ProviderStatus {
+    pub provider: String,
+    pub description: String,
+    pub configured: bool,
+    pub available: bool,
+    pub missing_env: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<crate::quota::QuotaUsage>,
+}
+
+/// The [`ProviderStatus`] of every supported provider, in the order of
+/// [`supported_providers`](registry::supported_providers). Without a
+/// config file (`cfg` is `None`) no provider is marked as configured.
+pub fn provider_statuses(cfg: Option<&AppConfig>) -> Vec<ProviderStatus> {
+    let default = AppConfig::default();
+    let settings = cfg.unwrap_or(&default);
+    let is_set = |var: &str| {
+        std::env::var_os(var).is_some() || std::env::var_os(format!("{}_FILE", var)).is_some()
+    };
+
+    registry::supported_providers()
+        .into_iter()
+        .map(|kind| {
+            let info = kind.info();
+            let overrides = ProviderOverrides {
+                provider: Some(kind),
+                ..Default::default()
+            };
+            let error = provider_factory_with(settings, &overrides)
+                .err()
+                .map(|e| e.to_string());
+
+            ProviderStatus {
+                provider: kind.to_string(),
+                description: info.description.to_string(),
+                configured: cfg.is_some_and(|cfg| cfg.provider == kind),
+                available: error.is_none(),
+                missing_env: info
+                    .required_env
+                    .iter()
+                    .filter(|var| !is_set(var))
+                    .map(|var| var.to_string())
+                    .collect(),
+                error,
+                quota: None,
+            }
+        })
+        .collect()
+}
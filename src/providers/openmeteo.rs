@@ -0,0 +1,602 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use urlencoding::encode;
+
+use super::{
+    append_params, effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest,
+    ProviderOverrides, STANDARD_KINDS,
+};
+use crate::config::KindOverride;
+use crate::geocode::{is_named, Coords, Geocoder, Source, CANDIDATES};
+use crate::location::{Location, LocationInput, LocationMatch};
+use crate::request::{parse_date, DateWindow, HourWindow};
+use crate::series::{from_epoch, DailySummary, SeriesPoint};
+use crate::timezone::Zone;
+
+/// Default base URL of the forecast API.
+pub const DEFAULT_BASE_URL: &str = "https://api.open-meteo.com/v1";
+
+/// Default base URL of the geocoding API.
+pub const DEFAULT_GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1";
+
+/// Variables requested for every `hourly` record.
+const HOURLY_VARS: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,surface_pressure,wind_speed_10m,precipitation,precipitation_probability,cloud_cover,weather_code";
+
+/// Variables requested for every `forecast` and `tomorrow` day.
+const DAILY_VARS: &str = "temperature_2m_max,temperature_2m_min,precipitation_sum,precipitation_probability_max,wind_speed_10m_max,weather_code";
+
+/// Provider for working with the Open-Meteo API.
+///
+/// Open-Meteo is free and needs no API key, but only accepts coordinates,
+/// so every request first resolves the city through the geocoding API.
+///
+/// # Fields
+///
+/// * `base_url` - Base URL of the forecast API
+/// * `geocoding_url` - Base URL of the geocoding API
+/// * `lang` - Language of place names returned by geocoding
+/// * `extra_params` - Additional query parameters appended to every forecast request
+/// * `kind_overrides` - Per-kind request parameters from the config
+pub struct OpenMeteoProvider {
+    pub base_url: String,
+    pub geocoding_url: String,
+    pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+/// A place found by the geocoding API, see [`Coords`].
+pub type Place = Coords;
+
+impl OpenMeteoProvider {
+    /// Creates a new instance of `OpenMeteoProvider` from environment variables.
+    ///
+    /// # Environment Variables
+    ///
+    /// * `OPENMETEO_BASE_URL` (optional) - Forecast API base URL (default: "https://api.open-meteo.com/v1")
+    /// * `OPENMETEO_GEOCODING_URL` (optional) - Geocoding API base URL
+    ///   (default: "https://geocoding-api.open-meteo.com/v1")
+    /// * `OPENMETEO_LANG` (optional) - Language of place names (e.g., "en", "uk")
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new provider instance; no variable is required
+    ///
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with(&ProviderOverrides::default())
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence. `overrides.api_key` and `overrides.units` are
+    /// ignored: no key is needed and responses are always metric.
+    pub fn from_env_with(overrides: &ProviderOverrides) -> Result<Self> {
+        Ok(Self {
+            base_url: overrides
+                .env
+                .var("OPENMETEO_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            geocoding_url: overrides
+                .env
+                .var("OPENMETEO_GEOCODING_URL")
+                .unwrap_or(DEFAULT_GEOCODING_URL.into()),
+            lang: overrides
+                .lang
+                .clone()
+                .or_else(|| overrides.env.var("OPENMETEO_LANG"))
+                .or_else(|| overrides.config_lang.clone()),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
+        })
+    }
+
+    /// Builds the geocoding search URL for a location, see [`search_url`].
+    pub fn geocoding_url(&self, location: &LocationInput) -> String {
+        search_url(&self.geocoding_url, location, self.lang.as_deref())
+    }
+
+    /// Reads the place of a geocoding response: the first one named
+    /// exactly as the city, ignoring case, else the first one, as for
+    /// [`best_match`](crate::geocode::best_match).
+    ///
+    /// # Errors
+    /// Returns an error naming the location if the response holds no place
+    /// or reports an error.
+    pub fn parse_place(location: &LocationInput, body: &str) -> Result<Place> {
+        let json: serde_json::Value = serde_json::from_str(body)
+            .map_err(|e| anyhow::anyhow!("invalid geocoding response from Open-Meteo: {}", e))?;
+
+        if json["error"].as_bool() == Some(true) {
+            return Err(anyhow::anyhow!(
+                "Open-Meteo geocoding failed for '{}': {}",
+                location.raw,
+                json["reason"].as_str().unwrap_or("unknown error")
+            ));
+        }
+
+        // A search without matches has no `results` field at all.
+        let place = json["results"]
+            .as_array()
+            .and_then(|results| {
+                results
+                    .iter()
+                    .find(|place| {
+                        place["name"]
+                            .as_str()
+                            .is_some_and(|name| is_named(name, &location.city))
+                    })
+                    .or(results.first())
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!("city '{}' was not found by Open-Meteo", location.raw)
+            })?;
+
+        match (place["latitude"].as_f64(), place["longitude"].as_f64()) {
+            (Some(latitude), Some(longitude)) => Ok(Place {
+                name: place["name"].as_str().unwrap_or(&location.city).to_string(),
+                latitude,
+                longitude,
+            }),
+            _ => Err(anyhow::anyhow!(
+                "Open-Meteo returned no coordinates for '{}'",
+                location.raw
+            )),
+        }
+    }
+
+    /// The geocoder of the provider: Open-Meteo's geocoding API at
+    /// [`geocoding_url`](Self::geocoding_url), in its language.
+    pub fn geocoder(&self) -> Geocoder {
+        Geocoder::new(
+            self.name(),
+            Source::OpenMeteo {
+                base_url: self.geocoding_url.clone(),
+                lang: self.lang.clone(),
+            },
+        )
+    }
+
+    /// Resolves a location to coordinates through the
+    /// [`geocoder`](Self::geocoder); coordinates are used as given, without
+    /// a request.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails or the city is not found.
+    pub async fn geocode(&self, location: &Location) -> Result<Place> {
+        self.geocoder().resolve(location).await
+    }
+
+    /// Builds the forecast URL for a place and data kind.
+    ///
+    /// "now" requests `current_weather`, "forecast" and "tomorrow" daily
+    /// values in the place's time zone, and "hourly" hourly values. Times
+    /// of current and hourly values are Unix timestamps.
+    /// [`extra_params`](Self::extra_params) are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind Open-Meteo does not serve.
+    pub fn build_url(&self, place: &Place, kind: DataKind, days: Option<u32>) -> Result<String> {
+        // An explicit length wins over the configured kind override
+        let days = effective_days(&self.kind_overrides, kind, days);
+
+        let mut url = format!(
+            "{}/forecast?latitude={}&longitude={}",
+            self.base_url, place.latitude, place.longitude
+        );
+
+        match kind {
+            DataKind::Now => url.push_str("&current_weather=true&timeformat=unixtime"),
+
+            DataKind::Forecast => {
+                url.push_str(&format!("&daily={}&timezone=auto", DAILY_VARS));
+                if let Some(days) = days {
+                    url.push_str(&format!("&forecast_days={}", days));
+                }
+            }
+
+            // Today and tomorrow; the response is trimmed to tomorrow.
+            DataKind::Tomorrow => url.push_str(&format!(
+                "&daily={}&timezone=auto&forecast_days=2",
+                DAILY_VARS
+            )),
+
+            // The next 24 hours unless a length is given
+            DataKind::Hourly => url.push_str(&format!(
+                "&hourly={}&timeformat=unixtime&forecast_days={}",
+                HOURLY_VARS,
+                days.unwrap_or(1)
+            )),
+
+            // Return error for unknown request types
+            _ => return Err(kind.unsupported(self.name())),
+        }
+
+        append_params(&mut url, &self.extra_params);
+
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl ApiProvider for OpenMeteoProvider {
+    fn name(&self) -> &str {
+        "openmeteo"
+    }
+
+    /// The forecast API covers 16 days.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 16,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    /// Returns the dates of the `daily.time` entries, or the distinct UTC
+    /// dates of the `hourly.time` entries.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        if let Some(days) = json["daily"]["time"].as_array() {
+            return Some(days.iter().filter_map(parse_date).collect());
+        }
+
+        let mut dates: Vec<NaiveDate> = json["hourly"]["time"]
+            .as_array()?
+            .iter()
+            .filter_map(|t| from_epoch(t).map(|t| t.date_naive()))
+            .collect();
+        dates.dedup();
+
+        Some(dates)
+    }
+
+    /// Keeps the `daily` or `hourly` entries dated inside `window`.
+    ///
+    /// Both blocks store one array per variable, so every array is filtered
+    /// by the same indexes.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let (block, keep): (&str, Vec<bool>) = if json["daily"]["time"].is_array() {
+            let keep = json["daily"]["time"]
+                .as_array()?
+                .iter()
+                .map(|t| parse_date(t).is_some_and(|d| window.contains(d)))
+                .collect();
+            ("daily", keep)
+        } else {
+            let keep = json["hourly"]["time"]
+                .as_array()?
+                .iter()
+                .map(|t| from_epoch(t).is_some_and(|t| window.contains(t.date_naive())))
+                .collect();
+            ("hourly", keep)
+        };
+
+        retain_entries(&mut json[block], &keep)?;
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Keeps the `hourly` entries timed inside `window`, filtering every
+    /// array of the block by the same indexes.
+    fn retain_hours(&self, body: &str, window: HourWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let keep: Vec<bool> = json["hourly"]["time"]
+            .as_array()?
+            .iter()
+            .map(|t| from_epoch(t).is_some_and(|t| window.contains(t)))
+            .collect();
+
+        retain_entries(&mut json["hourly"], &keep)?;
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads the `current_weather` block.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let current = json.get("current_weather")?;
+
+        let mut point = SeriesPoint::at(from_epoch(&current["time"])?);
+        point.temp_c = current["temperature"].as_f64();
+        point.wind_kph = current["windspeed"].as_f64();
+        point.condition = current["weathercode"]
+            .as_u64()
+            .and_then(weather_code_text)
+            .map(String::from);
+
+        Some(point)
+    }
+
+    /// Reads the `daily` block, one summary per date.
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let daily = &json["daily"];
+
+        let days = daily["time"]
+            .as_array()?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, date)| {
+                Some(DailySummary {
+                    date: parse_date(date)?,
+                    min_c: daily["temperature_2m_min"][i].as_f64(),
+                    max_c: daily["temperature_2m_max"][i].as_f64(),
+                    precip_mm: daily["precipitation_sum"][i].as_f64(),
+                    precip_chance: daily["precipitation_probability_max"][i].as_f64(),
+                    max_wind_kph: daily["wind_speed_10m_max"][i].as_f64(),
+                    avg_wind_kph: None,
+                    condition: daily["weather_code"][i]
+                        .as_u64()
+                        .and_then(weather_code_text)
+                        .map(String::from),
+                    partial: false,
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// Flattens the `hourly` block; values are already metric.
+    fn series(&self, body: &str) -> Option<Vec<SeriesPoint>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let hourly = &json["hourly"];
+
+        let points = hourly["time"]
+            .as_array()?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, time)| {
+                let mut point = SeriesPoint::at(from_epoch(time)?);
+                point.temp_c = hourly["temperature_2m"][i].as_f64();
+                point.feels_like_c = hourly["apparent_temperature"][i].as_f64();
+                point.humidity = hourly["relative_humidity_2m"][i].as_f64();
+                point.pressure_mb = hourly["surface_pressure"][i].as_f64();
+                point.wind_kph = hourly["wind_speed_10m"][i].as_f64();
+                point.precip_mm = hourly["precipitation"][i].as_f64();
+                point.precip_chance = hourly["precipitation_probability"][i].as_f64();
+                point.cloud = hourly["cloud_cover"][i].as_f64();
+                point.condition = hourly["weather_code"][i]
+                    .as_u64()
+                    .and_then(weather_code_text)
+                    .map(String::from);
+                Some(point)
+            })
+            .collect();
+
+        Some(points)
+    }
+
+    /// Reads `timezone`, the location's zone with `timezone=auto`, which
+    /// forecasts ask for; `GMT` otherwise.
+    fn timezone(&self, body: &str) -> Option<Zone> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        json["timezone"].as_str()?.parse().ok()
+    }
+
+    /// Searches the geocoding API for up to 10 matches.
+    fn location_search_url(&self, query: &str) -> Option<String> {
+        Some(matches_url(
+            &self.geocoding_url,
+            query,
+            self.lang.as_deref(),
+        ))
+    }
+
+    /// Reads the geocoding `results`, see [`parse_matches`].
+    fn location_matches(&self, body: &str) -> Option<Vec<LocationMatch>> {
+        parse_matches(body)
+    }
+
+    /// The request of [`get_data`](Self::get_data), or for a city the
+    /// geocoding lookup that comes first, see [`plan_place`].
+    fn build_request(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        plan_place(
+            self.name(),
+            location,
+            |city| self.geocoding_url(city),
+            |place| self.build_url(place, kind, days),
+        )
+    }
+
+    /// Retrieves weather data for the specified city.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - Location to fetch data for; resolved with [`geocode`](OpenMeteoProvider::geocode)
+    /// * `kind` - Type of request:
+    ///   - "now" - current weather
+    ///   - "forecast" - daily forecast
+    ///   - "tomorrow" - tomorrow's daily forecast
+    ///   - "hourly" - hourly values for the next 24 hours (or `days`)
+    /// * `days` - Forecast length for "forecast" and "hourly", sent as `forecast_days`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - JSON response from the API as a string, or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The city is not found by the geocoding API
+    /// * The request type (`kind`) is not supported
+    /// * The HTTP request fails
+    /// * Reading the response text fails
+    ///
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        let place = self.geocode(&location).await?;
+        let url = self.build_url(&place, kind, days)?;
+
+        let body = super::fetch(self.name(), &url).await?;
+
+        if kind != DataKind::Tomorrow {
+            return Ok(body);
+        }
+
+        // Keep only the second day of the two requested.
+        match self
+            .forecast_dates(&body)
+            .and_then(|dates| dates.get(1).copied())
+        {
+            Some(tomorrow) => Ok(self
+                .retain_dates(
+                    &body,
+                    DateWindow {
+                        from: tomorrow,
+                        to: tomorrow,
+                    },
+                )
+                .unwrap_or(body)),
+            None => Ok(body),
+        }
+    }
+}
+
+/// Plans the requests of a [`Geocoder`] and the weather request built
+/// by `build` for the place: coordinates go straight to the weather
+/// request, a city is looked up at the URL built by `search` first.
+///
+/// # Errors
+/// Returns an error for a postal or airport code, or if `build` fails.
+pub fn plan_place(
+    provider: &str,
+    location: &Location,
+    search: impl Fn(&LocationInput) -> String,
+    build: impl FnOnce(&Place) -> Result<String>,
+) -> Result<PlannedRequest> {
+    match location {
+        Location::City(city) => Ok(PlannedRequest::lookup(search(city))),
+        Location::Coords { lat, lon } => Ok(PlannedRequest::get(build(&Place {
+            name: location.label(),
+            latitude: *lat,
+            longitude: *lon,
+        })?)),
+        Location::Zip { .. } | Location::Iata(_) => Err(location.unsupported(provider)),
+    }
+}
+
+/// Builds an Open-Meteo geocoding search URL below `base`.
+///
+/// Only the city is searched for; a country code narrows the search
+/// with `countryCode`. Other providers that take coordinates use this
+/// geocoder too.
+pub fn search_url(base: &str, location: &LocationInput, lang: Option<&str>) -> String {
+    let mut url = format!(
+        "{}/search?name={}&count={}&format=json",
+        base,
+        encode(&location.city),
+        CANDIDATES
+    );
+
+    if let Some(country) = &location.country {
+        url.push_str("&countryCode=");
+        url.push_str(country);
+    }
+
+    if let Some(lang) = lang {
+        url.push_str("&language=");
+        url.push_str(&encode(lang));
+    }
+
+    url
+}
+
+/// Builds an Open-Meteo geocoding URL below `base` listing up to 10
+/// places matching `query`, for location search.
+pub fn matches_url(base: &str, query: &str, lang: Option<&str>) -> String {
+    let mut url = format!(
+        "{}/search?name={}&count=10&format=json",
+        base,
+        encode(query.trim())
+    );
+
+    if let Some(lang) = lang {
+        url.push_str("&language=");
+        url.push_str(&encode(lang));
+    }
+
+    url
+}
+
+/// Reads the `name`, first-level subdivision (`admin1`), `country`,
+/// `latitude` and `longitude` of every place in a geocoding response;
+/// a response without `results` has no matches.
+pub fn parse_matches(body: &str) -> Option<Vec<LocationMatch>> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    if json["error"].as_bool() == Some(true) {
+        return None;
+    }
+
+    let Some(results) = json["results"].as_array() else {
+        return Some(Vec::new());
+    };
+
+    let matches = results
+        .iter()
+        .filter_map(|place| {
+            Some(LocationMatch {
+                name: place["name"].as_str()?.to_string(),
+                region: place["admin1"].as_str().map(String::from),
+                country: place["country"]
+                    .as_str()
+                    .or(place["country_code"].as_str())
+                    .map(String::from),
+                lat: place["latitude"].as_f64()?,
+                lon: place["longitude"].as_f64()?,
+            })
+        })
+        .collect();
+
+    Some(matches)
+}
+
+/// Keeps the entries of every array of a `daily` or `hourly` block whose
+/// index is marked in `keep`.
+fn retain_entries(block: &mut serde_json::Value, keep: &[bool]) -> Option<()> {
+    for values in block.as_object_mut()?.values_mut() {
+        if let Some(values) = values.as_array_mut() {
+            let mut index = 0;
+            values.retain(|_| {
+                index += 1;
+                keep.get(index - 1).copied().unwrap_or(false)
+            });
+        }
+    }
+
+    Some(())
+}
+
+/// Describes a WMO weather interpretation code, as used by Open-Meteo.
+pub fn weather_code_text(code: u64) -> Option<&'static str> {
+    Some(match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 | 63 | 65 => "Rain",
+        66 | 67 => "Freezing rain",
+        71 | 73 | 75 => "Snow",
+        77 => "Snow grains",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => return None,
+    })
+}
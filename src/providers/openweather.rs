@@ -1,9 +1,86 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::env;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::str::FromStr;
 use urlencoding::encode;
 
-use super::ApiProvider;
+use super::credentials::{register_secret, CredentialStore};
+use super::openmeteo::{plan_place, Place};
+use super::url::RequestUrl;
+use super::{
+    effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest, ProviderOverrides,
+};
+use crate::aqi::AirQuality;
+use crate::condition::Condition;
+use crate::config::KindOverride;
+use crate::geocode::{Geocoder, Source, CANDIDATES};
+use crate::locale::{detect_locale_with, resolve_lang};
+use crate::location::{Location, LocationInput, LocationMatch};
+use crate::request::{DateWindow, HourWindow};
+use crate::series::{from_epoch, summarize_day, DailySummary, SeriesPoint};
+use crate::timezone::Zone;
+use crate::units::{f_to_c, k_to_c, mph_to_kph, ms_to_kph};
+use crate::weather::{day_length, from_views, moon_phase, Alert, Astronomy, Severity, WeatherData};
+
+/// Default API host. Endpoints live under versioned paths below it.
+pub const DEFAULT_HOST: &str = "https://api.openweathermap.org";
+
+/// Path of the 2.5 API, which serves `/weather` and `/forecast`.
+const PATH_2_5: &str = "/data/2.5";
+
+/// Path of the 3.0 API, which only serves `/onecall`.
+const PATH_3_0: &str = "/data/3.0";
+
+/// Data kinds of the 2.5 API: the standard ones, air quality, and
+/// today's sun times.
+const CLASSIC_KINDS: &[DataKind] = &[
+    DataKind::Now,
+    DataKind::Forecast,
+    DataKind::Tomorrow,
+    DataKind::Hourly,
+    DataKind::Air,
+    DataKind::Astro(None),
+];
+
+/// Data kinds of One Call: the standard ones, its `alerts` block, air
+/// quality from the 2.5 API, and sun and moon times of its `daily` days.
+const ONECALL_KINDS: &[DataKind] = &[
+    DataKind::Now,
+    DataKind::Forecast,
+    DataKind::Tomorrow,
+    DataKind::Hourly,
+    DataKind::Alerts,
+    DataKind::Air,
+    DataKind::Astro(None),
+];
+
+/// Which OpenWeatherMap API serves the requests, the `OPENWEATHER_API`
+/// setting: `2.5` (the default) or `onecall`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpenWeatherApi {
+    /// `/weather` and `/forecast` of the 2.5 API, which every key can use.
+    #[default]
+    Classic,
+    /// `/onecall` of the 3.0 API, which needs a One Call subscription and
+    /// coordinates, so cities are geocoded with `/geo/1.0/direct` first.
+    OneCall,
+}
+
+impl FromStr for OpenWeatherApi {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "2.5" | "classic" => Ok(OpenWeatherApi::Classic),
+            "3.0" | "onecall" => Ok(OpenWeatherApi::OneCall),
+            other => Err(anyhow::anyhow!(
+                "unknown OPENWEATHER_API '{}' (valid: 2.5, onecall)",
+                other
+            )),
+        }
+    }
+}
 
 /// Provider for working with the OpenWeatherMap API.
 ///
@@ -13,52 +90,804 @@ use super::ApiProvider;
 /// # Fields
 ///
 /// * `api_key` - API key for authentication with OpenWeatherMap
-/// * `base_url` - Base URL of the API
+/// * `base_url` - Base URL of the 2.5 API (`/weather`, `/forecast`)
+/// * `onecall_url` - Base URL of the 3.0 API (`/onecall`)
+/// * `api` - The API requests go to, see [`OpenWeatherApi`]
 /// * `units` - Units of measurement (metric, imperial, standard)
 /// * `lang` - Language of API response
+/// * `extra_params` - Additional query parameters appended to every request
+/// * `kind_overrides` - Per-kind request parameters from the config
 pub struct OpenWeatherProvider {
     pub api_key: String,
     pub base_url: String,
+    pub onecall_url: String,
+    pub api: OpenWeatherApi,
     pub units: Option<String>,
     pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
 }
 
 impl OpenWeatherProvider {
+    /// A provider using `api_key` with the 2.5 API at [`DEFAULT_HOST`], the
+    /// API's default units and language, and no extra parameters. Nothing
+    /// is read from the environment; see [`from_env`](Self::from_env) for
+    /// that.
+    ///
+    /// ```
+    /// use wapp::providers::OpenWeatherProvider;
+    ///
+    /// let provider = OpenWeatherProvider::new("KEY")
+    ///     .with_base_url("http://127.0.0.1:8080")
+    ///     .with_units("metric")
+    ///     .with_lang("uk");
+    /// assert_eq!(provider.base_url, "http://127.0.0.1:8080");
+    /// assert_eq!(provider.units.as_deref(), Some("metric"));
+    /// ```
+    pub fn new(api_key: impl Into<String>) -> Self {
+        let (base_url, onecall_url) = resolve_endpoints(None);
+        Self {
+            api_key: register_secret(api_key.into()),
+            base_url,
+            onecall_url,
+            api: OpenWeatherApi::default(),
+            units: None,
+            lang: None,
+            extra_params: Vec::new(),
+            kind_overrides: BTreeMap::new(),
+        }
+    }
+
+    /// The provider with requests sent below `base_url`, e.g. a mock
+    /// server; both endpoints are derived from it by [`resolve_endpoints`].
+    pub fn with_base_url(mut self, base_url: impl AsRef<str>) -> Self {
+        (self.base_url, self.onecall_url) = resolve_endpoints(Some(base_url.as_ref()));
+        self
+    }
+
+    /// The provider sending its requests to `api`.
+    pub fn with_api(mut self, api: OpenWeatherApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// The provider asking for `units`: `metric`, `imperial` or `standard`.
+    pub fn with_units(mut self, units: impl Into<String>) -> Self {
+        self.units = Some(units.into());
+        self
+    }
+
+    /// The provider asking for responses in `lang`, an OpenWeatherMap
+    /// language code such as `ua`, see
+    /// [`provider_lang`](crate::locale::provider_lang).
+    pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
+    /// The provider appending `extra_params` to every request.
+    pub fn with_extra_params(mut self, extra_params: Vec<(String, String)>) -> Self {
+        self.extra_params = extra_params;
+        self
+    }
+
+    /// The provider applying `kind_overrides`, the per-kind request
+    /// parameters of the config.
+    pub fn with_kind_overrides(mut self, kind_overrides: BTreeMap<String, KindOverride>) -> Self {
+        self.kind_overrides = kind_overrides;
+        self
+    }
+
     /// Creates a new instance of `OpenWeatherProvider` from environment variables.
     ///
     /// # Environment Variables
     ///
     /// * `OPENWEATHER_KEY` (required) - OpenWeatherMap API key
-    /// * `OPENWEATHER_BASE_URL` (optional) - API base URL (default: "https://api.openweathermap.org/data/3.0")
+    /// * `OPENWEATHER_KEY_FILE` (alternative) - File containing the API key
+    /// * `OPENWEATHER_BASE_URL` (optional) - API base URL override, see [`resolve_endpoints`]
+    /// * `OPENWEATHER_API` (optional) - `2.5` (default) or `onecall`, see [`OpenWeatherApi`]
     /// * `OPENWEATHER_UNITS` (optional) - Units of measurement (metric/imperial/standard)
-    /// * `OPENWEATHER_LANG` (optional) - Response language code (e.g., "en", "uk", "es")
+    /// * `OPENWEATHER_LANG` (optional) - Response language code (e.g., "en", "uk", "es");
+    ///   derived from the system locale when unset
     ///
     /// # Returns
     ///
     /// * `Result<Self>` - A new provider instance or an error if required variables are missing
     ///
     pub fn from_env() -> Result<Self> {
+        Self::from_sources(
+            &ProviderOverrides::default(),
+            &CredentialStore::new(None, None),
+        )
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence and the API key looked up in `credentials`, see
+    /// [`CredentialStore`]; `overrides.api_key` is not read. The units and
+    /// language saved in the config come after the environment variables.
+    pub fn from_sources(
+        overrides: &ProviderOverrides,
+        credentials: &CredentialStore,
+    ) -> Result<Self> {
+        let (base_url, onecall_url) =
+            resolve_endpoints(overrides.env.var("OPENWEATHER_BASE_URL").as_deref());
+
         Ok(Self {
-            api_key: env::var("OPENWEATHER_KEY")?,
-            base_url: env::var("OPENWEATHER_BASE_URL")
-                .unwrap_or("https://api.openweathermap.org/data/3.0".into()),
-            units: env::var("OPENWEATHER_UNITS").ok(),
-            lang: env::var("OPENWEATHER_LANG").ok(),
+            api_key: credentials.api_key("OPENWEATHER_KEY", "openweather")?,
+            base_url,
+            onecall_url,
+            api: overrides
+                .env
+                .var("OPENWEATHER_API")
+                .map(|api| api.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            units: overrides
+                .units
+                .clone()
+                .or_else(|| overrides.env.var("OPENWEATHER_UNITS"))
+                .or_else(|| overrides.config_units.clone()),
+            lang: resolve_lang(
+                "openweather",
+                overrides.lang.clone(),
+                overrides
+                    .env
+                    .var("OPENWEATHER_LANG")
+                    .or_else(|| overrides.config_lang.clone()),
+                || detect_locale_with(|name| overrides.env.var(name)),
+            ),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
         })
     }
+
+    /// Formats a location for the `q` parameter.
+    ///
+    /// OpenWeatherMap expects `city,state,country`, where the state code
+    /// is only honored for the US, e.g. `Portland,OR,US` or `Paris,FR`.
+    pub fn query(location: &LocationInput) -> String {
+        let mut parts = vec![location.city.as_str()];
+
+        if location.country.as_deref() == Some("US") {
+            if let Some(region) = &location.region {
+                parts.push(region);
+            }
+        }
+
+        if let Some(country) = &location.country {
+            parts.push(country);
+        }
+
+        parts.join(",")
+    }
+
+    /// Converts a temperature in the configured units to °C.
+    fn to_c(&self, value: Option<f64>) -> Option<f64> {
+        value.map(|t| match self.units.as_deref() {
+            Some("metric") => t,
+            Some("imperial") => f_to_c(t),
+            _ => k_to_c(t),
+        })
+    }
+
+    /// Converts a wind speed in the configured units, m/s except for
+    /// imperial (mph), to km/h.
+    fn to_kph(&self, value: Option<f64>) -> Option<f64> {
+        value.map(|w| match self.units.as_deref() {
+            Some("imperial") => mph_to_kph(w),
+            _ => ms_to_kph(w),
+        })
+    }
+
+    /// Converts one `/weather` response or `/forecast` list entry, or a
+    /// One Call `current` or `hourly` entry, to metric units. `precip` is
+    /// the rain/snow period key (`1h` or `3h`).
+    fn point(&self, entry: &serde_json::Value, precip: &str) -> Option<SeriesPoint> {
+        // One Call has the `main` and `wind` values at the top level.
+        let main = entry.get("main").unwrap_or(entry);
+
+        let mut point = SeriesPoint::at(from_epoch(&entry["dt"])?);
+        point.temp_c = self.to_c(main["temp"].as_f64());
+        point.feels_like_c = self.to_c(main["feels_like"].as_f64());
+        point.humidity = main["humidity"].as_f64();
+        point.pressure_mb = main["pressure"].as_f64();
+        point.wind_kph = self.to_kph(
+            entry["wind"]["speed"]
+                .as_f64()
+                .or(entry["wind_speed"].as_f64()),
+        );
+        // Missing rain/snow blocks mean no precipitation.
+        point.precip_mm = Some(
+            entry["rain"][precip].as_f64().unwrap_or(0.0)
+                + entry["snow"][precip].as_f64().unwrap_or(0.0),
+        );
+        // Forecast entries give the chance as a fraction.
+        point.precip_chance = entry["pop"].as_f64().map(|p| p * 100.0);
+        point.cloud = entry["clouds"]["all"].as_f64().or(entry["clouds"].as_f64());
+        point.condition = entry["weather"][0]["description"]
+            .as_str()
+            .map(String::from);
+
+        Some(point)
+    }
+
+    /// Converts one One Call `daily` entry to metric units.
+    fn day(&self, entry: &serde_json::Value, offset: Duration) -> Option<DailySummary> {
+        Some(DailySummary {
+            date: entry_date(entry, offset)?,
+            min_c: self.to_c(entry["temp"]["min"].as_f64()),
+            max_c: self.to_c(entry["temp"]["max"].as_f64()),
+            // Daily rain and snow are plain amounts in mm.
+            precip_mm: Some(
+                entry["rain"].as_f64().unwrap_or(0.0) + entry["snow"].as_f64().unwrap_or(0.0),
+            ),
+            precip_chance: entry["pop"].as_f64().map(|p| p * 100.0),
+            max_wind_kph: self.to_kph(entry["wind_speed"].as_f64()),
+            avg_wind_kph: None,
+            condition: entry["weather"][0]["description"]
+                .as_str()
+                .map(String::from),
+            partial: false,
+        })
+    }
+
+    /// Trims a `/forecast` or One Call response to the entries of the day
+    /// after `now`, both taken in the city's time zone (see [`utc_offset`]),
+    /// so a query just before midnight or for a city far away still gets
+    /// the city's tomorrow. `None` if the response cannot be read.
+    pub fn keep_tomorrow(&self, body: &str, now: DateTime<Utc>) -> Option<String> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let tomorrow = (now + utc_offset(&json)).date_naive().succ_opt()?;
+
+        self.retain_dates(
+            body,
+            DateWindow {
+                from: tomorrow,
+                to: tomorrow,
+            },
+        )
+    }
+
+    /// Keeps the One Call `daily` entry of `date`, for the sun and moon
+    /// times of that day.
+    ///
+    /// # Errors
+    /// Returns an error if `date` is not one of the 8 days One Call
+    /// forecasts.
+    pub fn keep_day(&self, body: &str, date: NaiveDate) -> Result<String> {
+        let kept = self
+            .retain_dates(
+                body,
+                DateWindow {
+                    from: date,
+                    to: date,
+                },
+            )
+            .unwrap_or_else(|| body.to_string());
+        let json: serde_json::Value = serde_json::from_str(&kept)?;
+
+        if json["daily"].as_array().is_none_or(Vec::is_empty) {
+            return Err(anyhow::anyhow!(
+                "no sun and moon times for {}; provider '{}' has them for the next 8 days",
+                date,
+                self.name()
+            ));
+        }
+
+        Ok(kept)
+    }
+
+    /// Builds the geocoding URL looking `city` up, `/geo/1.0/direct` on the
+    /// host of the 2.5 API, for One Call, see [`direct_url`].
+    pub fn geocoding_url(&self, city: &LocationInput) -> String {
+        direct_url(self.host(), &self.api_key, city)
+    }
+
+    /// The geocoder of the provider: `/geo/1.0/direct` on the host of the
+    /// 2.5 API.
+    pub fn geocoder(&self) -> Geocoder {
+        Geocoder::new(
+            self.name(),
+            Source::OpenWeather {
+                host: self.host().to_string(),
+                api_key: self.api_key.clone(),
+            },
+        )
+    }
+
+    /// Builds the One Call URL for a place and data kind.
+    ///
+    /// `exclude` leaves out every block the kind does not need: "now" keeps
+    /// `current`, "forecast", "tomorrow" and "astro" keep `daily`, "hourly"
+    /// keeps `hourly`, "alerts" keeps `alerts`.
+    /// [`extra_params`](Self::extra_params) are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a date, marine or snow data, or
+    /// for air quality, which One Call does not serve, see
+    /// [`air_pollution_url`](Self::air_pollution_url).
+    pub fn build_onecall_url(&self, place: &Place, kind: DataKind) -> Result<String> {
+        let exclude = match kind {
+            DataKind::Now => "minutely,hourly,daily,alerts",
+            DataKind::Forecast | DataKind::Weekend | DataKind::Tomorrow | DataKind::Astro(_) => {
+                "current,minutely,hourly,alerts"
+            }
+            DataKind::Hourly => "current,minutely,daily,alerts",
+            DataKind::Alerts => "current,minutely,hourly,daily",
+            DataKind::Air | DataKind::Marine | DataKind::Snow | DataKind::History(_) => {
+                return Err(kind.unsupported(self.name()))
+            }
+        };
+
+        let url = RequestUrl::new(&self.onecall_url, "/onecall")
+            .param("lat", place.latitude)
+            .param("lon", place.longitude)
+            .encoded("exclude", exclude)
+            .param("appid", &self.api_key);
+
+        Ok(self.localized(url).params(&self.extra_params).build())
+    }
+
+    /// Builds the Air Pollution URL for a place; the endpoint only takes
+    /// coordinates and belongs to the 2.5 API whichever API is selected.
+    pub fn air_pollution_url(&self, place: &Place) -> String {
+        RequestUrl::new(&self.base_url, "/air_pollution")
+            .param("lat", place.latitude)
+            .param("lon", place.longitude)
+            .param("appid", &self.api_key)
+            .build()
+    }
+
+    /// Resolves `location` to coordinates for One Call and the Air
+    /// Pollution API through the [`geocoder`](Self::geocoder), coordinates
+    /// as given.
+    ///
+    /// # Errors
+    /// Returns a [`WappError::CityNotFound`](crate::error::WappError) if the
+    /// geocoder knows no such city, or an error for a postal or airport
+    /// code or a failed lookup.
+    async fn geocode(&self, location: &Location) -> Result<Place> {
+        self.geocoder().resolve(location).await
+    }
+
+    /// The host the versioned paths live below; an overridden base URL
+    /// without a version is used as it is.
+    fn host(&self) -> &str {
+        self.base_url
+            .strip_suffix(PATH_2_5)
+            .unwrap_or(&self.base_url)
+    }
+
+    /// Builds the request URL for the given location and data kind.
+    ///
+    /// "now" maps to `/weather`, "forecast", "tomorrow" and "hourly" to `/forecast`,
+    /// both under [`base_url`](Self::base_url). [`extra_params`](Self::extra_params)
+    /// are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind OpenWeather does not
+    /// serve, or an error if the location is an airport code.
+    pub fn build_url(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        // Coordinates go in `lat`/`lon`, postal codes in `zip`, names in `q`
+        let target = |url: RequestUrl| match location {
+            Location::City(city) => Ok(url.param("q", Self::query(city))),
+            Location::Coords { lat, lon } => Ok(url.param("lat", lat).param("lon", lon)),
+            Location::Zip {
+                code,
+                country: Some(country),
+            } => Ok(url.encoded("zip", &format!("{},{}", encode(code), encode(country)))),
+            Location::Zip { code, .. } => Ok(url.param("zip", code)),
+            Location::Iata(_) => Err(location.unsupported(self.name())),
+        };
+
+        // An explicit length wins over the configured kind override
+        let days = effective_days(&self.kind_overrides, kind, days);
+
+        // Build URL based on request type
+        let url = match kind {
+            // Today's sunrise and sunset come with the current weather
+            DataKind::Now | DataKind::Astro(None) => {
+                target(RequestUrl::new(&self.base_url, "/weather"))?.param("appid", &self.api_key)
+            }
+
+            DataKind::Forecast | DataKind::Weekend | DataKind::Tomorrow | DataKind::Hourly => {
+                // Limit the number of 3-hour slots: the requested days, or 24 hours for "hourly"
+                let cnt = match (days, kind) {
+                    (Some(days), DataKind::Forecast | DataKind::Weekend | DataKind::Hourly) => {
+                        Some(days * 8)
+                    }
+                    (None, DataKind::Hourly) => Some(8),
+                    _ => None,
+                };
+
+                target(RequestUrl::new(&self.base_url, "/forecast"))?
+                    .param("appid", &self.api_key)
+                    .param_opt("cnt", cnt)
+            }
+
+            DataKind::Astro(Some(_)) => {
+                return Err(anyhow::anyhow!(
+                    "astro of a given day is not supported by provider '{}' with the 2.5 API; \
+                     set OPENWEATHER_API=onecall",
+                    self.name()
+                ))
+            }
+
+            // Return error for unsupported request types; alerts need One
+            // Call, air quality coordinates (see `air_pollution_url`)
+            DataKind::Alerts
+            | DataKind::Air
+            | DataKind::Marine
+            | DataKind::Snow
+            | DataKind::History(_) => return Err(kind.unsupported(self.name())),
+        };
+
+        Ok(self.localized(url).params(&self.extra_params).build())
+    }
+
+    /// Adds the units of measurement and response language, if specified.
+    fn localized(&self, url: RequestUrl) -> RequestUrl {
+        url.param_opt("units", self.units.as_deref())
+            .param_opt("lang", self.lang.as_deref())
+    }
 }
 
 #[async_trait]
 impl ApiProvider for OpenWeatherProvider {
+    fn name(&self) -> &str {
+        "openweather"
+    }
+
+    /// The 3-hourly `/forecast` endpoint covers 5 days, One Call's `daily`
+    /// block 8; only One Call has alerts.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: match self.api {
+                OpenWeatherApi::Classic => 5,
+                OpenWeatherApi::OneCall => 8,
+            },
+            kinds: match self.api {
+                OpenWeatherApi::Classic => CLASSIC_KINDS,
+                OpenWeatherApi::OneCall => ONECALL_KINDS,
+            },
+            history_since: None,
+        }
+    }
+
+    /// Asks `/weather`, or `/onecall`, about
+    /// [`verify_location`](super::verify_location).
+    fn verify_url(&self) -> Option<String> {
+        self.build_request(&super::verify_location(), DataKind::Now, None)
+            .ok()
+            .map(|request| request.url)
+    }
+
+    /// Returns the distinct dates of the `list` entries, or of One Call's
+    /// `daily` or `hourly` entries, in the city's time zone.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let entries = match json["daily"].as_array() {
+            Some(days) => days,
+            None => steps(&json)?.0,
+        };
+
+        let mut dates: Vec<NaiveDate> = entries
+            .iter()
+            .filter_map(|entry| entry_date(entry, utc_offset(&json)))
+            .collect();
+        dates.dedup();
+
+        Some(dates)
+    }
+
+    /// Keeps the `list`, `daily` and `hourly` entries whose local date is
+    /// inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let offset = utc_offset(&json);
+        let mut trimmed = false;
+        for key in ["list", "daily", "hourly"] {
+            if let Some(entries) = json[key].as_array_mut() {
+                entries
+                    .retain(|entry| entry_date(entry, offset).is_some_and(|d| window.contains(d)));
+                trimmed = true;
+            }
+        }
+        if !trimmed {
+            return None;
+        }
+        if let Some(list) = json["list"].as_array() {
+            json["cnt"] = list.len().into();
+        }
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Keeps the `list`, or One Call `hourly`, entries whose `dt` is inside
+    /// `window`.
+    fn retain_hours(&self, body: &str, window: HourWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let key = if json.get("list").is_some() {
+            "list"
+        } else {
+            "hourly"
+        };
+        let entries = json[key].as_array_mut()?;
+        entries.retain(|entry| from_epoch(&entry["dt"]).is_some_and(|t| window.contains(t)));
+        if key == "list" {
+            json["cnt"] = json["list"].as_array()?.len().into();
+        }
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads the `/weather` response, or One Call's `current` block,
+    /// converting from the configured units.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        if let Some(current) = json.get("current") {
+            return self.point(current, "1h");
+        }
+        json.get("main")?;
+
+        self.point(&json, "1h")
+    }
+
+    /// Reads One Call's `daily` entries, or aggregates the 3-hourly `list`
+    /// (One Call: `hourly`) entries per local date, see [`summarize_day`].
+    ///
+    /// Dates are taken in the city's time zone (see [`utc_offset`]), so the
+    /// days line up with WeatherAPI's. A date whose entries cover less than
+    /// 24 hours, as the first and last of `/forecast`, is
+    /// [`partial`](DailySummary::partial).
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let offset = utc_offset(&json);
+        if let Some(entries) = json["daily"].as_array() {
+            return Some(entries.iter().filter_map(|e| self.day(e, offset)).collect());
+        }
+
+        let (entries, precip) = steps(&json)?;
+        let mut days: Vec<(NaiveDate, Vec<SeriesPoint>)> = Vec::new();
+        for entry in entries {
+            let (Some(date), Some(point)) = (entry_date(entry, offset), self.point(entry, precip))
+            else {
+                continue;
+            };
+
+            match days.last_mut() {
+                Some((last, points)) if *last == date => points.push(point),
+                _ => days.push((date, vec![point])),
+            }
+        }
+
+        let hours = if precip == "3h" { 3 } else { 1 };
+        Some(
+            days.iter()
+                .map(|(date, points)| DailySummary {
+                    partial: points.len() * hours < 24,
+                    ..summarize_day(*date, points)
+                })
+                .collect(),
+        )
+    }
+
+    /// Flattens the 3-hourly `list` (One Call: `hourly`) entries,
+    /// converting from the configured units.
+    fn series(&self, body: &str) -> Option<Vec<SeriesPoint>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let (entries, precip) = steps(&json)?;
+
+        let points = entries
+            .iter()
+            .filter_map(|entry| self.point(entry, precip))
+            .collect();
+
+        Some(points)
+    }
+
+    /// Adds the wind direction and gusts, the `visibility` in metres and the
+    /// category of the `weather[0].id` code of a `/weather` response, or One
+    /// Call `current` block, to [`from_views`]; only One Call has a UV index
+    /// (`uvi`) and a dew point (`dew_point`).
+    fn weather(&self, body: &str) -> Option<WeatherData> {
+        let mut data = from_views(self, body)?;
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let current = json.get("current").unwrap_or(&json);
+
+        data.wind_degree = current["wind"]["deg"]
+            .as_f64()
+            .or(current["wind_deg"].as_f64());
+        data.gust_kph = self.to_kph(
+            current["wind"]["gust"]
+                .as_f64()
+                .or(current["wind_gust"].as_f64()),
+        );
+        data.set_uv(current["uvi"].as_f64());
+        data.visibility_km = current["visibility"].as_f64().map(|m| m / 1000.0);
+        if let Some(dew_point) = self.to_c(current["dew_point"].as_f64()) {
+            data.dew_point_c = Some(dew_point);
+        }
+        if let Some(code) = current["weather"][0]["id"].as_i64() {
+            data.condition_kind = Some(Condition::from_openweather(code));
+        }
+        Some(data)
+    }
+
+    /// Reads the One Call `alerts` block, which is left out when no warning
+    /// is in effect. One Call does not grade warnings, so their severity
+    /// is unknown.
+    fn alerts(&self, body: &str) -> Option<Vec<Alert>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        json.get("timezone_offset")?;
+
+        let alerts = json["alerts"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .map(|alert| Alert {
+                event: alert["event"].as_str().unwrap_or_default().to_string(),
+                severity: Severity::Unknown,
+                headline: None,
+                starts: from_epoch(&alert["start"]),
+                ends: from_epoch(&alert["end"]),
+                description: alert["description"]
+                    .as_str()
+                    .filter(|d| !d.is_empty())
+                    .map(String::from),
+            })
+            .collect();
+
+        Some(alerts)
+    }
+
+    /// Reads `sys.sunrise` and `sys.sunset` of a `/weather` response, or the
+    /// first One Call `daily` entry with its moon: `moonrise` and `moonset`
+    /// of 0 do not happen that day, and `moon_phase` is the fraction of the
+    /// lunar month, see [`moon_phase`]. Times are shifted by the response's
+    /// UTC offset; the zone is One Call's `timezone` name, or the offset.
+    fn astronomy(&self, body: &str) -> Option<Astronomy> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        // `/weather` gives the offset as `timezone`, One Call as `timezone_offset`
+        let seconds = json["timezone"]
+            .as_i64()
+            .or(json["timezone_offset"].as_i64())
+            .unwrap_or_default();
+        let offset = Duration::try_seconds(seconds).unwrap_or_default();
+        let local = |value: &serde_json::Value| {
+            from_epoch(value)
+                .filter(|t| t.timestamp() != 0)
+                .map(|t| (t + offset).time())
+        };
+
+        let (entry, moon) = match json["daily"].get(0) {
+            Some(day) => (day, true),
+            None => (json.get("sys")?, false),
+        };
+        let (sunrise, sunset) = (local(&entry["sunrise"]), local(&entry["sunset"]));
+        let phase = entry["moon_phase"]
+            .as_f64()
+            .filter(|_| moon)
+            .map(moon_phase);
+
+        Some(Astronomy {
+            date: entry_date(if moon { entry } else { &json }, offset),
+            timezone: Some(match json["timezone"].as_str() {
+                Some(name) => name.to_string(),
+                None => Zone::from_offset(seconds)?.to_string(),
+            }),
+            sunrise,
+            sunset,
+            day_length_min: day_length(sunrise, sunset),
+            moonrise: local(&entry["moonrise"]),
+            moonset: local(&entry["moonset"]),
+            moon_phase: phase.map(|(name, _)| name.to_string()),
+            moon_illumination: phase.map(|(_, lit)| lit),
+        })
+    }
+
+    /// Reads One Call's `timezone` name, else the UTC offset in seconds of
+    /// `/weather` (`timezone`), `/forecast` (`city.timezone`) or One Call
+    /// (`timezone_offset`), which holds for the whole response.
+    fn timezone(&self, body: &str) -> Option<Zone> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        if let Some(zone) = json["timezone"].as_str().and_then(|name| name.parse().ok()) {
+            return Some(zone);
+        }
+
+        let seconds = json["timezone"]
+            .as_i64()
+            .or(json["city"]["timezone"].as_i64())
+            .or(json["timezone_offset"].as_i64())?;
+        Zone::from_offset(seconds)
+    }
+
+    /// Reads the first `list` entry of an Air Pollution response, see
+    /// [`AirQuality::from_openweather`].
+    fn air_quality(&self, body: &str) -> Option<AirQuality> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        AirQuality::from_openweather(&json["list"][0])
+    }
+
+    /// Searches the Geocoding API (`/geo/1.0/direct`) for up to 5 matches.
+    ///
+    /// The API lives beside `/data/2.5` on the same host; an overridden
+    /// base URL without that suffix is used as the host.
+    fn location_search_url(&self, query: &str) -> Option<String> {
+        Some(
+            RequestUrl::new(self.host(), "/geo/1.0/direct")
+                .param("q", query)
+                .param("limit", 5)
+                .param("appid", &self.api_key)
+                .build(),
+        )
+    }
+
+    /// Reads the `name`, `state`, `country` code, `lat` and `lon` of every match.
+    fn location_matches(&self, body: &str) -> Option<Vec<LocationMatch>> {
+        parse_direct(body)
+    }
+
+    /// The request of [`get_data`](Self::get_data), built by
+    /// [`build_url`](OpenWeatherProvider::build_url); for One Call, by
+    /// [`build_onecall_url`](OpenWeatherProvider::build_onecall_url), or
+    /// for a city the geocoding lookup that comes first.
+    fn build_request(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        if kind == DataKind::Air {
+            return plan_place(
+                self.name(),
+                location,
+                |city| self.geocoding_url(city),
+                |place| Ok(self.air_pollution_url(place)),
+            );
+        }
+
+        match self.api {
+            OpenWeatherApi::Classic => {
+                Ok(PlannedRequest::get(self.build_url(location, kind, days)?))
+            }
+            OpenWeatherApi::OneCall => plan_place(
+                self.name(),
+                location,
+                |city| self.geocoding_url(city),
+                |place| self.build_onecall_url(place, kind),
+            ),
+        }
+    }
+
     /// Retrieves weather data for the specified city.
     ///
     /// # Arguments
     ///
-    /// * `city` - Name of the city to fetch data for
+    /// * `location` - Location to fetch data for
     /// * `kind` - Type of request:
     ///   - "now" - current weather
     ///   - "forecast" - weather forecast
-    ///   - "tomorrow" - tomorrow's forecast (uses the same endpoint as "forecast")
+    ///   - "tomorrow" - the entries of the city's next day, see
+    ///     [`keep_tomorrow`](OpenWeatherProvider::keep_tomorrow)
+    ///   - "hourly" - 3-hour steps for the next 24 hours (or `days`)
+    /// * `days` - Forecast length for "forecast" and "hourly", sent as a 3-hour slot count (`cnt`)
+    ///
+    /// With [`OpenWeatherApi::OneCall`], a city is geocoded first and the
+    /// kind picks the `/onecall` blocks; `days` is left to the caller to
+    /// trim. "air" geocodes a city under either API and asks
+    /// [`air_pollution_url`](OpenWeatherProvider::air_pollution_url).
     ///
     /// # Returns
     ///
@@ -67,64 +896,153 @@ impl ApiProvider for OpenWeatherProvider {
     /// # Errors
     ///
     /// Returns an error if:
-    /// * An unknown request type (`kind`) is specified
+    /// * The request type (`kind`) is not supported
     /// * The HTTP request fails
     /// * Reading the response text fails
+    /// * The location is unknown, a [`WappError::CityNotFound`](crate::error::WappError)
+    /// * OpenWeatherMap answers with an error status, see [`error_message`]
     ///
-    /// # Example
-    async fn get_data(&self, city: String, kind: String) -> Result<String> {
-        // Encode city name for safe use in URL
-        let city = encode(&city);
-
-        // Build URL based on request type
-        let url = match kind.as_str() {
-            "now" => {
-                let mut url = format!(
-                    "{}/weather?q={}&appid={}",
-                    self.base_url, city, self.api_key
-                );
-
-                // Add units of measurement if specified
-                if let Some(units) = &self.units {
-                    url.push_str("&units=");
-                    url.push_str(units);
-                }
-
-                // Add response language if specified
-                if let Some(lang) = &self.lang {
-                    url.push_str("&lang=");
-                    url.push_str(lang);
-                }
-
-                url
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        let url = match self.api {
+            // Air quality comes from the 2.5 API by coordinates under both
+            _ if kind == DataKind::Air => self.air_pollution_url(&self.geocode(&location).await?),
+            OpenWeatherApi::Classic => self.build_url(&location, kind, days)?,
+            OpenWeatherApi::OneCall => {
+                self.build_onecall_url(&self.geocode(&location).await?, kind)?
             }
-
-            "forecast" | "tomorrow" => {
-                let mut url = format!(
-                    "{}/forecast?q={}&appid={}",
-                    self.base_url, city, self.api_key
-                );
-
-                // Add units of measurement if specified
-                if let Some(units) = &self.units {
-                    url.push_str("&units=");
-                    url.push_str(units);
-                }
-
-                // Add response language if specified
-                if let Some(lang) = &self.lang {
-                    url.push_str("&lang=");
-                    url.push_str(lang);
-                }
-
-                url
-            }
-
-            // Return error for unknown request types
-            _ => return Err(anyhow::anyhow!("Unknown data type: {}", kind)),
         };
 
         // Execute HTTP request and return response text
-        Ok(reqwest::get(url).await?.text().await?)
+        let body = super::fetch_checked(self.name(), &url, error_message)
+            .await
+            .map_err(|e| super::not_found_as(e, &location, is_not_found))?;
+
+        // Neither API has a day filter, so tomorrow is cut from the answer,
+        // as is any other day asked for its sun and moon
+        Ok(match kind {
+            DataKind::Tomorrow => self.keep_tomorrow(&body, Utc::now()).unwrap_or(body),
+            DataKind::Astro(Some(date)) => self.keep_day(&body, date)?,
+            _ => body,
+        })
+    }
+}
+
+/// The offset of the city's time zone from UTC, the `city.timezone`
+/// seconds of a `/forecast` response or the `timezone_offset` of a One
+/// Call response; zero without one.
+fn utc_offset(json: &serde_json::Value) -> Duration {
+    json["city"]["timezone"]
+        .as_i64()
+        .or(json["timezone_offset"].as_i64())
+        .and_then(Duration::try_seconds)
+        .unwrap_or_default()
+}
+
+/// The time steps of a response with the key of their rain and snow
+/// amounts: the 3-hourly `/forecast` `list` or One Call's `hourly`.
+fn steps(json: &serde_json::Value) -> Option<(&Vec<serde_json::Value>, &'static str)> {
+    match json["list"].as_array() {
+        Some(list) => Some((list, "3h")),
+        None => Some((json["hourly"].as_array()?, "1h")),
+    }
+}
+
+/// The date of a `/forecast` or One Call entry in the city's time zone.
+fn entry_date(entry: &serde_json::Value, offset: Duration) -> Option<NaiveDate> {
+    Some((from_epoch(&entry["dt"])? + offset).date_naive())
+}
+
+/// Whether an OpenWeatherMap error is `404 city not found`.
+pub fn is_not_found(status: u16, _body: &str) -> bool {
+    status == 404
+}
+
+/// Reads the `cod` and `message` of an OpenWeatherMap error body, e.g.
+/// `OpenWeatherMap error 404: city not found`; `cod` is a number or a
+/// string depending on the endpoint.
+pub fn error_message(body: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let message = json["message"].as_str()?.trim();
+    let code = match &json["cod"] {
+        serde_json::Value::Number(code) => Some(code.to_string()),
+        serde_json::Value::String(code) => Some(code.clone()),
+        _ => None,
+    };
+
+    Some(match code {
+        Some(code) => format!("OpenWeatherMap error {}: {}", code, message),
+        None => format!("OpenWeatherMap error: {}", message),
+    })
+}
+
+/// Builds the `/geo/1.0/direct` URL on `host` looking `city` up, for up
+/// to [`CANDIDATES`] matches.
+pub fn direct_url(host: &str, api_key: &str, city: &LocationInput) -> String {
+    RequestUrl::new(host, "/geo/1.0/direct")
+        .param("q", OpenWeatherProvider::query(city))
+        .param("limit", CANDIDATES)
+        .param("appid", api_key)
+        .build()
+}
+
+/// Reads the `name`, `state`, `country`, `lat` and `lon` of every place in
+/// a `/geo/1.0/direct` response.
+pub fn parse_direct(body: &str) -> Option<Vec<LocationMatch>> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+    let matches = json
+        .as_array()?
+        .iter()
+        .filter_map(|place| {
+            Some(LocationMatch {
+                name: place["name"].as_str()?.to_string(),
+                region: place["state"].as_str().map(String::from),
+                country: place["country"].as_str().map(String::from),
+                lat: place["lat"].as_f64()?,
+                lon: place["lon"].as_f64()?,
+            })
+        })
+        .collect();
+
+    Some(matches)
+}
+
+/// Resolves the 2.5 and 3.0 base URLs from an optional `OPENWEATHER_BASE_URL` override.
+///
+/// OpenWeatherMap serves `/weather` and `/forecast` only under `/data/2.5`, and
+/// One Call only under `/data/3.0`, so a single prefix cannot cover both.
+///
+/// Compatibility rule for existing overrides:
+/// - no override: both URLs are derived from [`DEFAULT_HOST`],
+/// - an override ending in `/data/2.5` or `/data/3.0` is treated as a host
+///   plus version; the version is replaced per endpoint (so the old
+///   `.../data/3.0` default keeps working),
+/// - any other override (proxy, mock server) is used verbatim as the prefix
+///   for every endpoint, exactly as before; a bare host such as
+///   `https://api.openweathermap.org` is no exception and needs `/data/2.5`.
+///
+/// Returns `(base_url, onecall_url)`.
+pub fn resolve_endpoints(base_url: Option<&str>) -> (String, String) {
+    let base = match base_url {
+        Some(base) => base.trim_end_matches('/'),
+        None => {
+            return (
+                format!("{DEFAULT_HOST}{PATH_2_5}"),
+                format!("{DEFAULT_HOST}{PATH_3_0}"),
+            )
+        }
+    };
+
+    match base
+        .strip_suffix(PATH_2_5)
+        .or_else(|| base.strip_suffix(PATH_3_0))
+    {
+        Some(host) => (format!("{host}{PATH_2_5}"), format!("{host}{PATH_3_0}")),
+        None => (base.to_string(), base.to_string()),
     }
 }
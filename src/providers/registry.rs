@@ -0,0 +1,159 @@
+use std::sync::{Arc, OnceLock, RwLock};
+
+use anyhow::Result;
+
+use super::{ApiProvider, ProviderKind, ProviderOverrides};
+use crate::config::AppConfig;
+
+/// How any provider is created: built-in ones also take the per-invocation
+/// overrides, see [`provider_factory_with`](super::provider_factory_with).
+type Constructor =
+    Arc<dyn Fn(&AppConfig, &ProviderOverrides) -> Result<Box<dyn ApiProvider>> + Send + Sync>;
+
+/// The providers wapp can create, by name, in the order they are listed to
+/// users: the built-in ones, then those [registered](Self::register) by a
+/// program embedding wapp.
+///
+/// [`provider_factory`](super::provider_factory) looks providers up in the
+/// process-wide registry, which [`register_provider`] adds to; provider
+/// names of the config, `--provider` and `wapp providers` follow it.
+#[derive(Clone)]
+pub struct ProviderRegistry {
+    entries: Vec<(ProviderKind, Constructor)>,
+}
+
+impl ProviderRegistry {
+    /// A registry of the built-in providers.
+    pub fn new() -> Self {
+        let entries = ProviderKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let ctor: Constructor =
+                    Arc::new(move |cfg, overrides| super::create_builtin(kind, cfg, overrides));
+                (kind, ctor)
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    /// Adds the provider `name`, created by `ctor`, and returns its kind.
+    /// The name is taken in lowercase, as every provider name.
+    ///
+    /// ```
+    /// use wapp::providers::registry::ProviderRegistry;
+    /// use wapp::providers::testing::MockProvider;
+    ///
+    /// let mut registry = ProviderRegistry::new();
+    /// let kind = registry
+    ///     .register("acme", |_cfg| Ok(Box::new(MockProvider::new("{}"))))
+    ///     .unwrap();
+    /// assert_eq!(kind.name(), "acme");
+    ///
+    /// // Names are taken once.
+    /// assert!(registry.register("acme", |_cfg| Ok(Box::new(MockProvider::new("{}")))).is_err());
+    /// assert!(registry.register("owm", |_cfg| Ok(Box::new(MockProvider::new("{}")))).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns an error if the name is empty, holds characters other than
+    /// letters, digits, `-`, `_` and `.`, or is already the name or an
+    /// alias of a provider.
+    pub fn register<F>(&mut self, name: &str, ctor: F) -> Result<ProviderKind>
+    where
+        F: Fn(&AppConfig) -> Result<Box<dyn ApiProvider>> + Send + Sync + 'static,
+    {
+        let name = name.trim().to_lowercase();
+        let valid = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | '.');
+        if name.is_empty() || !name.chars().all(valid) {
+            return Err(anyhow::anyhow!(
+                "invalid provider name '{}'; use letters, digits, '-', '_' and '.'",
+                name
+            ));
+        }
+        if self.get(&name).is_some() {
+            return Err(anyhow::anyhow!("provider '{}' is already registered", name));
+        }
+
+        // Kinds are `Copy`, so the name lives as long as the process; a
+        // program registers a handful of providers at most.
+        let kind = ProviderKind::Custom(Box::leak(name.into_boxed_str()));
+        self.entries
+            .push((kind, Arc::new(move |cfg, _overrides| ctor(cfg))));
+        Ok(kind)
+    }
+
+    /// The provider whose name or alias is `name`, in lowercase.
+    pub fn get(&self, name: &str) -> Option<ProviderKind> {
+        self.entries
+            .iter()
+            .map(|(kind, _)| *kind)
+            .find(|kind| kind.name() == name || kind.aliases().contains(&name))
+    }
+
+    /// Every provider of the registry, built-in ones first.
+    pub fn kinds(&self) -> Vec<ProviderKind> {
+        self.entries.iter().map(|(kind, _)| *kind).collect()
+    }
+
+    /// Creates the provider `kind` from `cfg` and `overrides`; a registered
+    /// provider only gets `cfg`.
+    ///
+    /// # Errors
+    /// Returns an error if `kind` is not in the registry, or the provider's
+    /// constructor fails.
+    pub fn create(
+        &self,
+        kind: ProviderKind,
+        cfg: &AppConfig,
+        overrides: &ProviderOverrides,
+    ) -> Result<Box<dyn ApiProvider>> {
+        let (_, ctor) = self
+            .entries
+            .iter()
+            .find(|(known, _)| *known == kind)
+            .ok_or_else(|| anyhow::anyhow!("provider '{}' is not registered", kind))?;
+
+        ctor(cfg, overrides)
+    }
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The process-wide registry, starting with the built-in providers.
+static REGISTRY: OnceLock<RwLock<ProviderRegistry>> = OnceLock::new();
+
+fn registry() -> &'static RwLock<ProviderRegistry> {
+    REGISTRY.get_or_init(|| RwLock::new(ProviderRegistry::new()))
+}
+
+/// A copy of the process-wide registry, see [`ProviderRegistry`].
+pub fn default_registry() -> ProviderRegistry {
+    registry().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Adds the provider `name`, created by `ctor`, to the process-wide
+/// registry, see [`ProviderRegistry::register`]; call it before the config
+/// is loaded or a command is run, so both know the name.
+///
+/// # Errors
+/// Same as [`ProviderRegistry::register`].
+pub fn register_provider<F>(name: &str, ctor: F) -> Result<ProviderKind>
+where
+    F: Fn(&AppConfig) -> Result<Box<dyn ApiProvider>> + Send + Sync + 'static,
+{
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register(name, ctor)
+}
+
+/// Every provider of the process-wide registry, built-in ones first: the
+/// names `--provider` accepts and `wapp providers` lists.
+pub fn supported_providers() -> Vec<ProviderKind> {
+    registry().read().unwrap_or_else(|e| e.into_inner()).kinds()
+}
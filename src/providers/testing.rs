@@ -0,0 +1,60 @@
+//! Test doubles of [`ApiProvider`], for tests of programs embedding wapp.
+//! Available with the `test-util` feature.
+
+use async_trait::async_trait;
+
+use super::{ApiProvider, Capabilities, DataKind, STANDARD_KINDS};
+use crate::location::Location;
+
+/// A provider answering every request with `response`, without any network
+/// access. It serves the [standard kinds](STANDARD_KINDS), up to three
+/// forecast days.
+///
+/// ```
+/// use wapp::providers::testing::MockProvider;
+/// use wapp::providers::{ApiProvider, DataKind};
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let mock = MockProvider::new("DATA_OK");
+/// let body = mock.get_data("Kyiv".into(), DataKind::Now, None).await?;
+/// assert_eq!(body, "DATA_OK");
+/// # Ok(())
+/// # }
+/// ```
+pub struct MockProvider {
+    pub response: String,
+}
+
+impl MockProvider {
+    /// A provider answering with `response`.
+    pub fn new(response: impl Into<String>) -> Self {
+        Self {
+            response: response.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl ApiProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 3,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    async fn get_data(
+        &self,
+        _location: Location,
+        _when: DataKind,
+        _days: Option<u32>,
+    ) -> anyhow::Result<String> {
+        Ok(self.response.clone())
+    }
+}
@@ -0,0 +1,372 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+use super::credentials::CredentialStore;
+use super::{
+    append_params, effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest,
+    ProviderOverrides, STANDARD_KINDS,
+};
+use crate::config::KindOverride;
+use crate::location::Location;
+use crate::request::{parse_date, DateWindow, HourWindow};
+use crate::series::{DailySummary, SeriesPoint};
+
+/// Default base URL of the API.
+pub const DEFAULT_BASE_URL: &str = "https://api.tomorrow.io/v4";
+
+/// Provider for working with the Tomorrow.io API.
+///
+/// "now" is served by the realtime endpoint, the other kinds by the
+/// forecast timelines endpoint. Values are always requested in metric units.
+///
+/// # Fields
+///
+/// * `api_key` - API key for authentication with Tomorrow.io
+/// * `base_url` - Base URL of the API
+/// * `extra_params` - Additional query parameters appended to every request
+/// * `kind_overrides` - Per-kind request parameters from the config
+pub struct TomorrowIoProvider {
+    pub api_key: String,
+    pub base_url: String,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+impl TomorrowIoProvider {
+    /// Creates a new instance of `TomorrowIoProvider` from environment variables.
+    ///
+    /// # Environment Variables
+    ///
+    /// * `TOMORROWIO_KEY` (required) - Tomorrow.io API key
+    /// * `TOMORROWIO_KEY_FILE` (alternative) - File containing the API key
+    /// * `TOMORROWIO_BASE_URL` (optional) - API base URL (default: "https://api.tomorrow.io/v4")
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new provider instance or an error if required variables are missing
+    ///
+    pub fn from_env() -> Result<Self> {
+        Self::from_sources(
+            &ProviderOverrides::default(),
+            &CredentialStore::new(None, None),
+        )
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence and the API key looked up in `credentials`, see
+    /// [`CredentialStore`]; `overrides.api_key` is not read. `overrides.units`
+    /// and `overrides.lang` are ignored: the API has no response language and
+    /// values are always metric.
+    pub fn from_sources(
+        overrides: &ProviderOverrides,
+        credentials: &CredentialStore,
+    ) -> Result<Self> {
+        Ok(Self {
+            api_key: credentials.api_key("TOMORROWIO_KEY", "tomorrowio")?,
+            base_url: overrides
+                .env
+                .var("TOMORROWIO_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
+        })
+    }
+
+    /// Builds the request URL for the given location and data kind.
+    ///
+    /// "now" maps to `/weather/realtime`; "forecast" and "tomorrow" map to
+    /// `/weather/forecast` in daily steps, "hourly" in hourly steps. The
+    /// location is passed as typed, e.g. `location=Paris, FR`.
+    /// [`extra_params`](Self::extra_params) are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind Tomorrow.io does not
+    /// serve, or an error if the location is an airport code.
+    pub fn build_url(&self, location: &Location, kind: DataKind) -> Result<String> {
+        // Tomorrow.io takes US postal codes as they are, but no airports
+        if let Location::Iata(_) = location {
+            return Err(location.unsupported(self.name()));
+        }
+
+        let endpoint = match kind {
+            DataKind::Now => "realtime?",
+            DataKind::Forecast | DataKind::Weekend | DataKind::Tomorrow => "forecast?timesteps=1d&",
+            DataKind::Hourly => "forecast?timesteps=1h&",
+            DataKind::Alerts
+            | DataKind::Air
+            | DataKind::Astro(_)
+            | DataKind::Marine
+            | DataKind::Snow
+            | DataKind::History(_) => return Err(kind.unsupported(self.name())),
+        };
+
+        let mut url = format!(
+            "{}/weather/{}location={}&units=metric&apikey={}",
+            self.base_url,
+            endpoint,
+            location.query(),
+            self.api_key
+        );
+
+        append_params(&mut url, &self.extra_params);
+
+        Ok(url)
+    }
+
+    /// Converts one realtime or hourly `values` block; units are metric.
+    fn point(time: &serde_json::Value, values: &serde_json::Value) -> Option<SeriesPoint> {
+        let time = DateTime::parse_from_rfc3339(time.as_str()?).ok()?;
+
+        let mut point = SeriesPoint::at(time.with_timezone(&Utc));
+        point.temp_c = values["temperature"].as_f64();
+        point.feels_like_c = values["temperatureApparent"].as_f64();
+        point.humidity = values["humidity"].as_f64();
+        point.pressure_mb = values["pressureSurfaceLevel"].as_f64();
+        point.wind_kph = values["windSpeed"].as_f64().map(|w| w * 3.6);
+        point.precip_mm = values["precipitationIntensity"].as_f64();
+        point.precip_chance = values["precipitationProbability"].as_f64();
+        point.cloud = values["cloudCover"].as_f64();
+        point.condition = values["weatherCode"]
+            .as_u64()
+            .and_then(weather_code_text)
+            .map(String::from);
+
+        Some(point)
+    }
+
+    /// Keeps `take` forecast dates after skipping the first `skip`.
+    fn keep_days(&self, body: String, skip: usize, take: usize) -> String {
+        let Some(dates) = self.forecast_dates(&body) else {
+            return body;
+        };
+        let kept: Vec<NaiveDate> = dates.into_iter().skip(skip).take(take).collect();
+
+        match (kept.first(), kept.last()) {
+            (Some(&from), Some(&to)) => self
+                .retain_dates(&body, DateWindow { from, to })
+                .unwrap_or(body),
+            _ => body,
+        }
+    }
+
+    /// Entries of the `daily` or `hourly` timeline, whichever is present.
+    fn timeline(json: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+        json["timelines"]["daily"]
+            .as_array()
+            .or_else(|| json["timelines"]["hourly"].as_array())
+    }
+}
+
+#[async_trait]
+impl ApiProvider for TomorrowIoProvider {
+    fn name(&self) -> &str {
+        "tomorrowio"
+    }
+
+    /// Forecast timelines cover 5 days.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 5,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    /// Asks `/weather/realtime` about [`verify_location`](super::verify_location).
+    fn verify_url(&self) -> Option<String> {
+        self.build_url(&super::verify_location(), DataKind::Now)
+            .ok()
+    }
+
+    /// Returns the distinct dates of the timeline entries.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let mut dates: Vec<NaiveDate> = Self::timeline(&json)?
+            .iter()
+            .filter_map(|entry| parse_date(&entry["time"]))
+            .collect();
+        dates.dedup();
+
+        Some(dates)
+    }
+
+    /// Keeps the timeline entries whose date is inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        for step in ["daily", "hourly"] {
+            if let Some(entries) = json["timelines"][step].as_array_mut() {
+                entries
+                    .retain(|entry| parse_date(&entry["time"]).is_some_and(|d| window.contains(d)));
+            }
+        }
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Keeps the `hourly` timeline entries timed inside `window`.
+    fn retain_hours(&self, body: &str, window: HourWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        json["timelines"]["hourly"].as_array_mut()?.retain(|entry| {
+            entry["time"]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .is_some_and(|t| window.contains(t.with_timezone(&Utc)))
+        });
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads the realtime `data` block.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        Self::point(&json["data"]["time"], &json["data"]["values"])
+    }
+
+    /// Reads the `daily` timeline, one summary per entry.
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["timelines"]["daily"]
+            .as_array()?
+            .iter()
+            .filter_map(|entry| {
+                let values = &entry["values"];
+                Some(DailySummary {
+                    date: parse_date(&entry["time"])?,
+                    min_c: values["temperatureMin"].as_f64(),
+                    max_c: values["temperatureMax"].as_f64(),
+                    precip_mm: values["rainAccumulationSum"].as_f64(),
+                    precip_chance: values["precipitationProbabilityMax"].as_f64(),
+                    max_wind_kph: values["windSpeedMax"].as_f64().map(|w| w * 3.6),
+                    avg_wind_kph: None,
+                    condition: values["weatherCodeMax"]
+                        .as_u64()
+                        .and_then(weather_code_text)
+                        .map(String::from),
+                    partial: false,
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// Flattens the `hourly` timeline.
+    fn series(&self, body: &str) -> Option<Vec<SeriesPoint>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let points = json["timelines"]["hourly"]
+            .as_array()?
+            .iter()
+            .filter_map(|entry| Self::point(&entry["time"], &entry["values"]))
+            .collect();
+
+        Some(points)
+    }
+
+    /// The request of [`get_data`](Self::get_data), built by
+    /// [`build_url`](TomorrowIoProvider::build_url); shorter forecasts are
+    /// trimmed from the answer.
+    fn build_request(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        _days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        Ok(PlannedRequest::get(self.build_url(location, kind)?))
+    }
+
+    /// Retrieves weather data for the specified city.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - Location to fetch data for
+    /// * `kind` - Type of request:
+    ///   - "now" - realtime weather
+    ///   - "forecast" - daily forecast
+    ///   - "tomorrow" - tomorrow's daily forecast
+    ///   - "hourly" - hourly forecast for the next 24 hours (or `days`)
+    /// * `days` - Forecast length for "forecast" and "hourly"; the API has no
+    ///   length parameter, so the response is trimmed to the first `days`
+    ///   dates (or `days * 24` hours)
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - JSON response from the API as a string, or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The request type (`kind`) is not supported
+    /// * The HTTP request fails
+    /// * Reading the response text fails
+    ///
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        let url = self.build_url(&location, kind)?;
+        let body = super::fetch(self.name(), &url).await?;
+
+        // The API has no length parameter, so the response is trimmed
+        match (kind, effective_days(&self.kind_overrides, kind, days)) {
+            (DataKind::Tomorrow, _) => Ok(self.keep_days(body, 1, 1)),
+            (DataKind::Forecast, Some(days)) => Ok(self.keep_days(body, 0, days as usize)),
+            (DataKind::Hourly, days) => Ok(keep_hours(body, days.unwrap_or(1) as usize * 24)),
+            _ => Ok(body),
+        }
+    }
+}
+
+/// Keeps the first `hours` entries of the `hourly` timeline.
+fn keep_hours(body: String, hours: usize) -> String {
+    let Ok(mut json) = serde_json::from_str::<serde_json::Value>(&body) else {
+        return body;
+    };
+
+    match json["timelines"]["hourly"].as_array_mut() {
+        Some(entries) => {
+            entries.truncate(hours);
+            serde_json::to_string_pretty(&json).unwrap_or(body)
+        }
+        None => body,
+    }
+}
+
+/// Describes a Tomorrow.io weather code.
+pub fn weather_code_text(code: u64) -> Option<&'static str> {
+    Some(match code {
+        1000 => "Clear",
+        1100 => "Mostly clear",
+        1101 => "Partly cloudy",
+        1102 => "Mostly cloudy",
+        1001 => "Cloudy",
+        2000 => "Fog",
+        2100 => "Light fog",
+        4000 => "Drizzle",
+        4001 => "Rain",
+        4200 => "Light rain",
+        4201 => "Heavy rain",
+        5000 => "Snow",
+        5001 => "Flurries",
+        5100 => "Light snow",
+        5101 => "Heavy snow",
+        6000 => "Freezing drizzle",
+        6001 => "Freezing rain",
+        6200 => "Light freezing rain",
+        6201 => "Heavy freezing rain",
+        7000 => "Ice pellets",
+        7101 => "Heavy ice pellets",
+        7102 => "Light ice pellets",
+        8000 => "Thunderstorm",
+        _ => return None,
+    })
+}
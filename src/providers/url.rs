@@ -0,0 +1,117 @@
+use std::fmt::{self, Display};
+
+use urlencoding::encode;
+
+/// A provider request URL: a base URL and a path, then query parameters in
+/// the order they are added.
+///
+/// Names and values are percent-encoded, so a key, language or city
+/// holding `&`, `=`, `+` or spaces cannot break the query. Only
+/// [`encoded`](Self::encoded) takes a value as it is.
+///
+/// ```
+/// use wapp::providers::url::RequestUrl;
+///
+/// let url = RequestUrl::new("https://api.example.com/v1", "/current.json")
+///     .param("key", "k+y")
+///     .param("q", "Trinidad & Tobago")
+///     .param_opt("lang", Some("pt BR"))
+///     .param_opt("units", None::<&str>)
+///     .build();
+///
+/// assert_eq!(
+///     url,
+///     "https://api.example.com/v1/current.json?key=k%2By&q=Trinidad%20%26%20Tobago&lang=pt%20BR"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestUrl {
+    url: String,
+    has_query: bool,
+}
+
+impl RequestUrl {
+    /// A URL of `path` below `base`, without parameters.
+    pub fn new(base: &str, path: &str) -> Self {
+        Self {
+            url: format!("{}{}", base, path),
+            has_query: false,
+        }
+    }
+
+    /// Adds `name=value`, both percent-encoded.
+    pub fn param(self, name: &str, value: impl Display) -> Self {
+        let value = value.to_string();
+        self.encoded(name, &encode(&value))
+    }
+
+    /// Adds `name=value` if `value` is set, see [`param`](Self::param).
+    pub fn param_opt<V: Display>(self, name: &str, value: Option<V>) -> Self {
+        match value {
+            Some(value) => self.param(name, value),
+            None => self,
+        }
+    }
+
+    /// Adds every `(name, value)` pair, e.g. the extra parameters of a
+    /// provider, see [`param`](Self::param). A pair replaces the parameters
+    /// of its name added before, so it overrides them.
+    pub fn params(self, params: &[(String, String)]) -> Self {
+        params.iter().fold(self, |url, (name, value)| {
+            let url = without_param(&url.url, name);
+            Self {
+                has_query: url.contains('?'),
+                url,
+            }
+            .param(name, value)
+        })
+    }
+
+    /// Adds `name=value` with `value` already in the form the API takes,
+    /// e.g. [`Location::query`](crate::location::Location::query), whose
+    /// coordinates keep their comma; the name is still encoded.
+    pub fn encoded(mut self, name: &str, value: &str) -> Self {
+        self.url.push(if self.has_query { '&' } else { '?' });
+        self.url.push_str(&encode(name));
+        self.url.push('=');
+        self.url.push_str(value);
+        self.has_query = true;
+        self
+    }
+
+    /// The URL.
+    pub fn build(self) -> String {
+        self.url
+    }
+}
+
+impl Display for RequestUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+/// `url` without the query parameters named `name`, which is compared
+/// percent-encoded, as the query holds it.
+///
+/// ```
+/// use wapp::providers::url::without_param;
+///
+/// assert_eq!(without_param("http://w/f?key=K&days=3&aqi=no", "days"), "http://w/f?key=K&aqi=no");
+/// assert_eq!(without_param("http://w/f?days=3", "days"), "http://w/f");
+/// ```
+pub fn without_param(url: &str, name: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let name = encode(name);
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| pair.split('=').next() != Some(name.as_ref()))
+        .collect();
+
+    match kept.is_empty() {
+        true => base.to_string(),
+        false => format!("{}?{}", base, kept.join("&")),
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+use super::credentials::CredentialStore;
+use super::{
+    append_params, effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest,
+    ProviderOverrides,
+};
+use crate::config::KindOverride;
+use crate::location::Location;
+use crate::request::{parse_date, DateWindow};
+use crate::series::{from_epoch, DailySummary, SeriesPoint};
+
+/// Default base URL of the Timeline API.
+pub const DEFAULT_BASE_URL: &str =
+    "https://weather.visualcrossing.com/VisualCrossingWebServices/rest/services/timeline";
+
+/// Provider for working with the Visual Crossing Timeline API.
+///
+/// One endpoint serves current conditions, forecasts, and history: the
+/// period is part of the path. Values are requested in metric units.
+///
+/// # Fields
+///
+/// * `api_key` - API key for authentication with Visual Crossing
+/// * `base_url` - Base URL of the Timeline API
+/// * `lang` - Language of condition descriptions
+/// * `extra_params` - Additional query parameters appended to every request
+/// * `kind_overrides` - Per-kind request parameters from the config
+pub struct VisualCrossingProvider {
+    pub api_key: String,
+    pub base_url: String,
+    pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+impl VisualCrossingProvider {
+    /// Creates a new instance of `VisualCrossingProvider` from environment variables.
+    ///
+    /// # Environment Variables
+    ///
+    /// * `VISUALCROSSING_KEY` (required) - Visual Crossing API key
+    /// * `VISUALCROSSING_KEY_FILE` (alternative) - File containing the API key
+    /// * `VISUALCROSSING_BASE_URL` (optional) - Timeline API base URL
+    ///   (default: [`DEFAULT_BASE_URL`])
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new provider instance or an error if required variables are missing
+    ///
+    pub fn from_env() -> Result<Self> {
+        Self::from_sources(
+            &ProviderOverrides::default(),
+            &CredentialStore::new(None, None),
+        )
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence and the API key looked up in `credentials`, see
+    /// [`CredentialStore`]; `overrides.api_key` is not read. `overrides.units`
+    /// is ignored: values are always metric.
+    pub fn from_sources(
+        overrides: &ProviderOverrides,
+        credentials: &CredentialStore,
+    ) -> Result<Self> {
+        Ok(Self {
+            api_key: credentials.api_key("VISUALCROSSING_KEY", "visualcrossing")?,
+            base_url: overrides
+                .env
+                .var("VISUALCROSSING_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            lang: overrides
+                .lang
+                .clone()
+                .or_else(|| overrides.config_lang.clone()),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
+        })
+    }
+
+    /// Builds the request URL for the given location and data kind.
+    ///
+    /// - "now" → `/{location}/today` with current conditions,
+    /// - "forecast" → `/{location}` (15 days), or `/{location}/next{N-1}days`
+    ///   for `N` days,
+    /// - "tomorrow" → `/{location}/tomorrow`,
+    /// - a date such as `2024-01-15` → `/{location}/2024-01-15`, that day's history.
+    ///
+    /// [`extra_params`](Self::extra_params) are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind Visual Crossing does not
+    /// serve, or an error if the location is an airport code.
+    pub fn build_url(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        // Postal codes are addresses to Visual Crossing, airports are not
+        if let Location::Iata(_) = location {
+            return Err(location.unsupported(self.name()));
+        }
+
+        // An explicit length wins over the configured kind override
+        let days = effective_days(&self.kind_overrides, kind, days);
+
+        let (period, include) = match kind {
+            DataKind::Now => ("/today".to_string(), "current"),
+            // `nextNdays` covers today and N more days; no days at all is
+            // just today.
+            DataKind::Forecast | DataKind::Weekend => match days {
+                Some(0 | 1) => ("/today".to_string(), "days"),
+                Some(days) => (format!("/next{}days", days - 1), "days"),
+                None => (String::new(), "days"),
+            },
+            DataKind::Tomorrow => ("/tomorrow".to_string(), "days"),
+            DataKind::History(date) => (format!("/{}", date), "days"),
+            DataKind::Hourly
+            | DataKind::Alerts
+            | DataKind::Air
+            | DataKind::Astro(_)
+            | DataKind::Marine
+            | DataKind::Snow => return Err(kind.unsupported(self.name())),
+        };
+
+        let mut url = format!(
+            "{}/{}{}?unitGroup=metric&include={}&key={}&contentType=json",
+            self.base_url,
+            location.query(),
+            period,
+            include,
+            self.api_key
+        );
+
+        if let Some(lang) = &self.lang {
+            url.push_str("&lang=");
+            url.push_str(&urlencoding::encode(lang));
+        }
+
+        append_params(&mut url, &self.extra_params);
+
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl ApiProvider for VisualCrossingProvider {
+    fn name(&self) -> &str {
+        "visualcrossing"
+    }
+
+    /// The Timeline API forecasts 15 days and serves history by date.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 15,
+            kinds: &[DataKind::Now, DataKind::Forecast, DataKind::Tomorrow],
+            history_since: NaiveDate::from_ymd_opt(1970, 1, 1),
+        }
+    }
+
+    /// Asks for today's conditions at [`verify_location`](super::verify_location).
+    fn verify_url(&self) -> Option<String> {
+        self.build_url(&super::verify_location(), DataKind::Now, None)
+            .ok()
+    }
+
+    /// Returns the dates of the `days[].datetime` entries.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        Some(
+            json["days"]
+                .as_array()?
+                .iter()
+                .filter_map(|day| parse_date(&day["datetime"]))
+                .collect(),
+        )
+    }
+
+    /// Keeps the `days` entries dated inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        json["days"]
+            .as_array_mut()?
+            .retain(|day| parse_date(&day["datetime"]).is_some_and(|d| window.contains(d)));
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads the `currentConditions` block.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let current = json.get("currentConditions")?;
+
+        let mut point = SeriesPoint::at(from_epoch(&current["datetimeEpoch"])?);
+        point.temp_c = current["temp"].as_f64();
+        point.feels_like_c = current["feelslike"].as_f64();
+        point.humidity = current["humidity"].as_f64();
+        point.pressure_mb = current["pressure"].as_f64();
+        point.wind_kph = current["windspeed"].as_f64();
+        point.precip_mm = current["precip"].as_f64();
+        point.cloud = current["cloudcover"].as_f64();
+        point.condition = current["conditions"].as_str().map(String::from);
+
+        Some(point)
+    }
+
+    /// Reads the `days` entries.
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["days"]
+            .as_array()?
+            .iter()
+            .filter_map(|day| {
+                Some(DailySummary {
+                    date: parse_date(&day["datetime"])?,
+                    min_c: day["tempmin"].as_f64(),
+                    max_c: day["tempmax"].as_f64(),
+                    precip_mm: day["precip"].as_f64(),
+                    precip_chance: day["precipprob"].as_f64(),
+                    max_wind_kph: day["windspeed"].as_f64(),
+                    avg_wind_kph: None,
+                    condition: day["conditions"].as_str().map(String::from),
+                    partial: false,
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// The request of [`get_data`](Self::get_data), built by
+    /// [`build_url`](VisualCrossingProvider::build_url).
+    fn build_request(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        Ok(PlannedRequest::get(self.build_url(location, kind, days)?))
+    }
+
+    /// Retrieves weather data for the specified city.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - Location to fetch data for
+    /// * `kind` - Type of request:
+    ///   - "now" - current conditions
+    ///   - "forecast" - daily forecast
+    ///   - "tomorrow" - tomorrow's forecast
+    ///   - a date such as "2024-01-15" - that day's history
+    /// * `days` - Forecast length for "forecast"
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - JSON response from the API as a string, or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The request type (`kind`) is not supported
+    /// * The HTTP request fails
+    /// * Reading the response text fails
+    ///
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        let url = self.build_url(&location, kind, days)?;
+
+        // Execute HTTP request and return response text
+        super::fetch(self.name(), &url).await
+    }
+}
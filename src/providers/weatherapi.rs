@@ -1,9 +1,31 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use std::env;
-use urlencoding::encode;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use std::collections::BTreeMap;
 
-use super::ApiProvider;
+use super::credentials::{register_secret, CredentialStore};
+use super::url::RequestUrl;
+use super::{
+    effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest, ProviderOverrides,
+};
+use crate::aqi::AirQuality;
+use crate::condition::Condition;
+use crate::config::KindOverride;
+use crate::locale::{detect_locale_with, resolve_lang};
+use crate::location::{Location, LocationMatch};
+use crate::request::{parse_date, DateWindow, HourWindow};
+use crate::series::{from_epoch, mean, DailySummary, SeriesPoint};
+use crate::timezone::Zone;
+use crate::weather::{
+    day_length, from_views, Alert, Astronomy, MarineDay, MarineHour, Severity, SnowDay, Tide,
+    TideKind, WeatherData, NIGHT_END_HOUR,
+};
+
+/// Default base URL of the API.
+pub const DEFAULT_BASE_URL: &str = "https://api.weatherapi.com/v1";
+
+/// Data kinds of WeatherAPI: every named kind.
+const KINDS: &[DataKind] = &DataKind::NAMED;
 
 /// Provider for working with the WeatherAPI service.
 ///
@@ -15,46 +37,621 @@ use super::ApiProvider;
 /// * `api_key` - API key for authentication with WeatherAPI
 /// * `base_url` - Base URL of the API
 /// * `lang` - Language of API response
+/// * `extra_params` - Additional query parameters appended to every request
+/// * `kind_overrides` - Per-kind request parameters from the config
 pub struct WeatherApiProvider {
     pub api_key: String,
     pub base_url: String,
     pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
 }
 
 impl WeatherApiProvider {
+    /// A provider using `api_key`, with the default base URL, English
+    /// responses and no extra parameters. Nothing is read from the
+    /// environment; see [`from_env`](Self::from_env) for that.
+    ///
+    /// ```
+    /// use wapp::providers::WeatherApiProvider;
+    ///
+    /// let provider = WeatherApiProvider::new("KEY")
+    ///     .with_base_url("http://127.0.0.1:8080/v1")
+    ///     .with_lang("uk");
+    /// assert_eq!(provider.base_url, "http://127.0.0.1:8080/v1");
+    /// ```
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: register_secret(api_key.into()),
+            base_url: DEFAULT_BASE_URL.into(),
+            lang: None,
+            extra_params: Vec::new(),
+            kind_overrides: BTreeMap::new(),
+        }
+    }
+
+    /// The provider with requests sent to `base_url`, e.g. a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// The provider asking for responses in `lang`, a WeatherAPI language
+    /// code such as `uk`, see [`provider_lang`](crate::locale::provider_lang).
+    pub fn with_lang(mut self, lang: impl Into<String>) -> Self {
+        self.lang = Some(lang.into());
+        self
+    }
+
     /// Creates a new instance of `WeatherApiProvider` from environment variables.
     ///
     /// # Environment Variables
     ///
     /// * `WEATHERAPI_KEY` (required) - WeatherAPI API key
+    /// * `WEATHERAPI_KEY_FILE` (alternative) - File containing the API key
     /// * `WEATHERAPI_BASE_URL` (optional) - API base URL (default: "https://api.weatherapi.com/v1")
-    /// * `WEATHERAPI_LANG` (optional) - Response language code (e.g., "en", "uk", "es")
+    /// * `WEATHERAPI_LANG` (optional) - Response language code (e.g., "en", "uk", "es");
+    ///   derived from the system locale when unset
     ///
     /// # Returns
     ///
     /// * `Result<Self>` - A new provider instance or an error if required variables are missing
     ///
     pub fn from_env() -> Result<Self> {
+        Self::from_sources(
+            &ProviderOverrides::default(),
+            &CredentialStore::new(None, None),
+        )
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence and the API key looked up in `credentials`, see
+    /// [`CredentialStore`]; `overrides.api_key` is not read. `overrides.units`
+    /// is ignored: responses carry both unit systems.
+    pub fn from_sources(
+        overrides: &ProviderOverrides,
+        credentials: &CredentialStore,
+    ) -> Result<Self> {
         Ok(Self {
-            api_key: env::var("WEATHERAPI_KEY")?,
-            base_url: env::var("WEATHERAPI_BASE_URL")
-                .unwrap_or("https://api.weatherapi.com/v1".into()),
-            lang: env::var("WEATHERAPI_LANG").ok(),
+            api_key: credentials.api_key("WEATHERAPI_KEY", "weatherapi")?,
+            base_url: overrides
+                .env
+                .var("WEATHERAPI_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            lang: resolve_lang(
+                "weatherapi",
+                overrides.lang.clone(),
+                overrides
+                    .env
+                    .var("WEATHERAPI_LANG")
+                    .or_else(|| overrides.config_lang.clone()),
+                || detect_locale_with(|name| overrides.env.var(name)),
+            ),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
         })
     }
+
+    /// Trims a `/forecast.json` response to the day after the city's date,
+    /// the date of its `location.localtime`. `None` if the response cannot
+    /// be read.
+    pub fn keep_tomorrow(&self, body: &str) -> Option<String> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let localtime = json["location"]["localtime"].as_str()?;
+        let tomorrow = NaiveDate::parse_from_str(localtime.get(..10)?, "%Y-%m-%d")
+            .ok()?
+            .succ_opt()?;
+
+        self.retain_dates(
+            body,
+            DateWindow {
+                from: tomorrow,
+                to: tomorrow,
+            },
+        )
+    }
+
+    /// Builds the request URL for the given location and data kind.
+    ///
+    /// "now" maps to `/current.json`, "forecast", "tomorrow", "hourly" and
+    /// "snow" to `/forecast.json`, "alerts" to a one-day `/forecast.json` with
+    /// `alerts=yes`, "air" to `/current.json` with `aqi=yes`, "astro" to
+    /// `/astronomy.json` (of a given day with `dt`), "marine" to
+    /// `/marine.json` with `tides=yes`, a date to `/history.json` for that
+    /// day.
+    /// [`extra_params`](Self::extra_params) are appended last.
+    ///
+    /// # Errors
+    /// Returns [`DataKind::unsupported`] for a kind WeatherAPI does not serve.
+    pub fn build_url(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        // WeatherAPI handles free text, `lat,lon` and postal codes as they
+        // are; airports take an `iata:` prefix
+        let city = match location {
+            Location::Iata(_) => format!("iata:{}", location.query()),
+            _ => location.query(),
+        };
+        let lang = self.lang.as_deref();
+
+        // An explicit length wins over the configured kind override
+        let days = effective_days(&self.kind_overrides, kind, days);
+
+        // Build URL based on request type
+        let url = match kind {
+            DataKind::Now => self.request("/current.json", &city).param_opt("lang", lang),
+
+            DataKind::Forecast
+            | DataKind::Weekend
+            | DataKind::Tomorrow
+            | DataKind::Hourly
+            | DataKind::Snow => {
+                // Set forecast days: today and tomorrow for tomorrow, requested or the
+                // kind's default otherwise
+                let days = match kind {
+                    DataKind::Tomorrow => 2,
+                    DataKind::Hourly => days.unwrap_or(1),
+                    _ => days.unwrap_or(3),
+                };
+
+                self.request("/forecast.json", &city)
+                    .param("days", days)
+                    .param_opt("lang", lang)
+            }
+
+            DataKind::Marine => self
+                .request("/marine.json", &city)
+                .param("days", days.unwrap_or(3))
+                .param("tides", "yes")
+                .param_opt("lang", lang),
+
+            DataKind::Air => self
+                .request("/current.json", &city)
+                .param("aqi", "yes")
+                .param_opt("lang", lang),
+
+            // Without a date the API answers for the location's today
+            DataKind::Astro(date) => self.request("/astronomy.json", &city).param_opt("dt", date),
+
+            DataKind::Alerts => self
+                .request("/forecast.json", &city)
+                .param("days", 1)
+                .param("alerts", "yes")
+                .param_opt("lang", lang),
+
+            DataKind::History(date) => self
+                .request("/history.json", &city)
+                .param("dt", date)
+                .param_opt("lang", lang),
+        };
+
+        Ok(url.params(&self.extra_params).build())
+    }
+
+    /// A request of `path` for the location query `city`, already encoded,
+    /// with the API key.
+    fn request(&self, path: &str, city: &str) -> RequestUrl {
+        RequestUrl::new(&self.base_url, path)
+            .param("key", &self.api_key)
+            .encoded("q", city)
+    }
 }
 
 #[async_trait]
 impl ApiProvider for WeatherApiProvider {
+    fn name(&self) -> &str {
+        "weatherapi"
+    }
+
+    /// WeatherAPI serves up to 14 forecast days, the free plan 3, and
+    /// history from 2010 on, the free plan of the last 7 days.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 14,
+            kinds: KINDS,
+            history_since: NaiveDate::from_ymd_opt(2010, 1, 1),
+        }
+    }
+
+    /// Asks `/current.json` about [`verify_location`](super::verify_location).
+    fn verify_url(&self) -> Option<String> {
+        self.build_url(&super::verify_location(), DataKind::Now, None)
+            .ok()
+    }
+
+    /// Returns the `date` of every `forecast.forecastday` entry.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        json["forecast"]["forecastday"]
+            .as_array()?
+            .iter()
+            .map(|day| parse_date(&day["date"]))
+            .collect()
+    }
+
+    /// Keeps the `forecast.forecastday` entries whose `date` is inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        json["forecast"]["forecastday"]
+            .as_array_mut()?
+            .retain(|day| parse_date(&day["date"]).is_some_and(|d| window.contains(d)));
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Keeps the `hour` entries inside `window`, and the days that still
+    /// have some.
+    fn retain_hours(&self, body: &str, window: HourWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["forecast"]["forecastday"].as_array_mut()?;
+        for day in days.iter_mut() {
+            if let Some(hours) = day["hour"].as_array_mut() {
+                hours.retain(|hour| {
+                    from_epoch(&hour["time_epoch"]).is_some_and(|t| window.contains(t))
+                });
+            }
+        }
+        days.retain(|day| {
+            day["hour"]
+                .as_array()
+                .is_some_and(|hours| !hours.is_empty())
+        });
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads the `current` block.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let current = json.get("current")?;
+
+        let mut point = SeriesPoint::at(from_epoch(&current["last_updated_epoch"])?);
+        point.temp_c = current["temp_c"].as_f64();
+        point.feels_like_c = current["feelslike_c"].as_f64();
+        point.humidity = current["humidity"].as_f64();
+        point.pressure_mb = current["pressure_mb"].as_f64();
+        point.wind_kph = current["wind_kph"].as_f64();
+        point.precip_mm = current["precip_mm"].as_f64();
+        point.cloud = current["cloud"].as_f64();
+        point.condition = current["condition"]["text"].as_str().map(String::from);
+        Some(point)
+    }
+
+    /// Reads the `day` block of every `forecast.forecastday`; the average
+    /// wind is the mean of its `hour` entries.
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["forecast"]["forecastday"]
+            .as_array()?
+            .iter()
+            .filter_map(|entry| {
+                let day = &entry["day"];
+                Some(DailySummary {
+                    date: parse_date(&entry["date"])?,
+                    min_c: day["mintemp_c"].as_f64(),
+                    max_c: day["maxtemp_c"].as_f64(),
+                    precip_mm: day["totalprecip_mm"].as_f64(),
+                    // The higher of the chances of rain and of snow.
+                    precip_chance: [&day["daily_chance_of_rain"], &day["daily_chance_of_snow"]]
+                        .into_iter()
+                        .filter_map(serde_json::Value::as_f64)
+                        .reduce(f64::max),
+                    max_wind_kph: day["maxwind_kph"].as_f64(),
+                    avg_wind_kph: mean(
+                        entry["hour"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter_map(|hour| hour["wind_kph"].as_f64()),
+                    ),
+                    condition: day["condition"]["text"].as_str().map(String::from),
+                    partial: false,
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// Flattens the `hour` entries of every `forecast.forecastday`.
+    fn series(&self, body: &str) -> Option<Vec<SeriesPoint>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let points = json["forecast"]["forecastday"]
+            .as_array()?
+            .iter()
+            .filter_map(|day| day["hour"].as_array())
+            .flatten()
+            .filter_map(|hour| {
+                let mut point = SeriesPoint::at(from_epoch(&hour["time_epoch"])?);
+                point.temp_c = hour["temp_c"].as_f64();
+                point.feels_like_c = hour["feelslike_c"].as_f64();
+                point.humidity = hour["humidity"].as_f64();
+                point.pressure_mb = hour["pressure_mb"].as_f64();
+                point.wind_kph = hour["wind_kph"].as_f64();
+                point.precip_mm = hour["precip_mm"].as_f64();
+                // The higher of the chances of rain and of snow.
+                point.precip_chance = [&hour["chance_of_rain"], &hour["chance_of_snow"]]
+                    .into_iter()
+                    .filter_map(serde_json::Value::as_f64)
+                    .reduce(f64::max);
+                point.cloud = hour["cloud"].as_f64();
+                point.condition = hour["condition"]["text"].as_str().map(String::from);
+                Some(point)
+            })
+            .collect();
+
+        Some(points)
+    }
+
+    /// Adds the `current.wind_degree` direction, `current.gust_kph` gusts,
+    /// the `current.uv` index, `current.vis_km` visibility, the
+    /// `current.dewpoint_c` dew point where given and the category of the
+    /// `current.condition.code` to [`from_views`].
+    fn weather(&self, body: &str) -> Option<WeatherData> {
+        let mut data = from_views(self, body)?;
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        data.wind_degree = json["current"]["wind_degree"].as_f64();
+        data.gust_kph = json["current"]["gust_kph"].as_f64();
+        data.set_uv(json["current"]["uv"].as_f64());
+        data.visibility_km = json["current"]["vis_km"].as_f64();
+        if let Some(dew_point) = json["current"]["dewpoint_c"].as_f64() {
+            data.dew_point_c = Some(dew_point);
+        }
+        if let Some(code) = json["current"]["condition"]["code"].as_i64() {
+            data.condition_kind = Some(Condition::from_weatherapi(code));
+        }
+        Some(data)
+    }
+
+    /// Reads `alerts.alert`, which lists CAP fields such as `severity`,
+    /// `effective` and `desc`.
+    fn alerts(&self, body: &str) -> Option<Vec<Alert>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let text = |value: &serde_json::Value| {
+            value
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+        };
+        let time = |value: &serde_json::Value| {
+            DateTime::parse_from_rfc3339(value.as_str()?)
+                .ok()
+                .map(|t| t.with_timezone(&Utc))
+        };
+
+        let alerts = json["alerts"]["alert"]
+            .as_array()?
+            .iter()
+            .map(|alert| Alert {
+                event: text(&alert["event"]).unwrap_or_default(),
+                severity: Severity::from_text(alert["severity"].as_str().unwrap_or_default()),
+                headline: text(&alert["headline"]),
+                starts: time(&alert["effective"]),
+                ends: time(&alert["expires"]),
+                description: text(&alert["desc"]),
+            })
+            .collect();
+
+        Some(alerts)
+    }
+
+    /// Reads `current.air_quality`, which `aqi=yes` adds, see
+    /// [`AirQuality::from_weatherapi`].
+    fn air_quality(&self, body: &str) -> Option<AirQuality> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        AirQuality::from_weatherapi(&json["current"]["air_quality"])
+    }
+
+    /// Reads `astronomy.astro`, whose times are already local to the
+    /// location, e.g. `09:11 PM`, with a text such as `No moonset` for
+    /// those that do not happen; the date and zone are those of `location`.
+    fn astronomy(&self, body: &str) -> Option<Astronomy> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let astro = json["astronomy"].get("astro")?;
+        let time = |key: &str| {
+            astro[key]
+                .as_str()
+                .and_then(|t| NaiveTime::parse_from_str(t, "%I:%M %p").ok())
+        };
+        let (sunrise, sunset) = (time("sunrise"), time("sunset"));
+
+        Some(Astronomy {
+            date: json["location"]["localtime"]
+                .as_str()
+                .and_then(|t| t.get(..10))
+                .and_then(|d| d.parse().ok()),
+            timezone: json["location"]["tz_id"].as_str().map(String::from),
+            sunrise,
+            sunset,
+            day_length_min: day_length(sunrise, sunset),
+            moonrise: time("moonrise"),
+            moonset: time("moonset"),
+            moon_phase: astro["moon_phase"].as_str().map(String::from),
+            // A number in newer responses, a string in older ones
+            moon_illumination: astro["moon_illumination"].as_f64().or_else(|| {
+                astro["moon_illumination"]
+                    .as_str()
+                    .and_then(|v| v.parse().ok())
+            }),
+        })
+    }
+
+    /// Reads `location.tz_id`, e.g. `Pacific/Auckland`, which every
+    /// response has.
+    fn timezone(&self, body: &str) -> Option<Zone> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        json["location"]["tz_id"].as_str()?.parse().ok()
+    }
+
+    /// Reads `forecast.forecastday`: each `hour` with its `sig_ht_mt` waves,
+    /// `swell_*` and `water_temp_c`, and the `day.tides` of the day. Times
+    /// are local to the location, as the response gives them; numbers may
+    /// come as strings.
+    fn marine(&self, body: &str) -> Option<Vec<MarineDay>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let number = |value: &serde_json::Value| {
+            value
+                .as_f64()
+                .or_else(|| value.as_str()?.trim().parse().ok())
+        };
+        let local = |value: &serde_json::Value| {
+            NaiveDateTime::parse_from_str(value.as_str()?, "%Y-%m-%d %H:%M").ok()
+        };
+        let list = |value: &serde_json::Value| value.as_array().cloned().unwrap_or_default();
+
+        let days = json["forecast"]["forecastday"]
+            .as_array()?
+            .iter()
+            .filter_map(|day| {
+                let hours = list(&day["hour"])
+                    .iter()
+                    .filter_map(|hour| {
+                        Some(MarineHour {
+                            time: local(&hour["time"])?,
+                            wave_m: number(&hour["sig_ht_mt"]),
+                            swell_m: number(&hour["swell_ht_mt"]),
+                            swell_degree: number(&hour["swell_dir"]),
+                            swell_period_s: number(&hour["swell_period_secs"]),
+                            water_temp_c: number(&hour["water_temp_c"]),
+                        })
+                    })
+                    .collect();
+                let tides = list(&day["day"]["tides"][0]["tide"])
+                    .iter()
+                    .filter_map(|tide| {
+                        Some(Tide {
+                            time: local(&tide["tide_time"])?,
+                            kind: match tide["tide_type"].as_str()?.to_uppercase().as_str() {
+                                "HIGH" => TideKind::High,
+                                "LOW" => TideKind::Low,
+                                _ => return None,
+                            },
+                            height_m: number(&tide["tide_height_mt"]),
+                        })
+                    })
+                    .collect();
+
+                Some(MarineDay::new(
+                    day["date"].as_str()?.parse().ok()?,
+                    hours,
+                    tides,
+                ))
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// Reads the `day` block of every `forecast.forecastday`: its
+    /// `totalsnow_cm`, `daily_chance_of_snow` and `daily_will_it_snow`,
+    /// lowest temperature and highest wind. The night low is the lowest
+    /// `temp_c` of the `hour` entries before [`NIGHT_END_HOUR`], whose
+    /// `time` is local to the location.
+    fn snow(&self, body: &str) -> Option<Vec<SnowDay>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["forecast"]["forecastday"]
+            .as_array()?
+            .iter()
+            .filter_map(|entry| {
+                let day = &entry["day"];
+                let night_low_c = entry["hour"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|hour| {
+                        hour["time"]
+                            .as_str()
+                            .and_then(|t| NaiveDateTime::parse_from_str(t, "%Y-%m-%d %H:%M").ok())
+                            .is_some_and(|t| t.hour() < NIGHT_END_HOUR)
+                    })
+                    .filter_map(|hour| hour["temp_c"].as_f64())
+                    .reduce(f64::min);
+
+                Some(SnowDay {
+                    date: parse_date(&entry["date"])?,
+                    snow_cm: day["totalsnow_cm"].as_f64(),
+                    snow_chance: day["daily_chance_of_snow"].as_f64(),
+                    will_snow: day["daily_will_it_snow"].as_i64().map(|will| will == 1),
+                    night_low_c,
+                    min_c: day["mintemp_c"].as_f64(),
+                    max_wind_kph: day["maxwind_kph"].as_f64(),
+                    condition: day["condition"]["text"].as_str().map(String::from),
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// Searches `/search.json`.
+    fn location_search_url(&self, query: &str) -> Option<String> {
+        Some(
+            RequestUrl::new(&self.base_url, "/search.json")
+                .param("key", &self.api_key)
+                .param("q", query)
+                .build(),
+        )
+    }
+
+    /// Reads the `name`, `region`, `country`, `lat` and `lon` of every match.
+    fn location_matches(&self, body: &str) -> Option<Vec<LocationMatch>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let text =
+            |value: &serde_json::Value| value.as_str().filter(|s| !s.is_empty()).map(String::from);
+
+        let matches = json
+            .as_array()?
+            .iter()
+            .filter_map(|place| {
+                Some(LocationMatch {
+                    name: text(&place["name"])?,
+                    region: text(&place["region"]),
+                    country: text(&place["country"]),
+                    lat: place["lat"].as_f64()?,
+                    lon: place["lon"].as_f64()?,
+                })
+            })
+            .collect();
+
+        Some(matches)
+    }
+
+    /// The request of [`get_data`](Self::get_data), built by
+    /// [`build_url`](WeatherApiProvider::build_url).
+    fn build_request(
+        &self,
+        location: &Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        Ok(PlannedRequest::get(self.build_url(location, kind, days)?))
+    }
+
     /// Retrieves weather data for the specified city.
     ///
     /// # Arguments
     ///
-    /// * `city` - Name of the city to fetch data for
+    /// * `location` - Location to fetch data for (sent as free text, airports as `iata:CODE`)
     /// * `kind` - Type of request:
     ///   - "now" - current weather
     ///   - "forecast" - weather forecast for 3 days
-    ///   - "tomorrow" - tomorrow's forecast (1 day)
+    ///   - "tomorrow" - tomorrow's forecast (2 days asked, the second one kept)
+    ///   - "hourly" - hour-by-hour forecast (the `hour` arrays of each day, default: 1 day)
+    ///   - "snow" - the forecast of snow (default: 3 days)
+    ///   - a date such as "2024-06-01" - that day's observations
+    /// * `days` - Forecast length for "forecast" and "snow" (default: 3) and "hourly" (default: 1)
     ///
     /// # Returns
     ///
@@ -63,54 +660,51 @@ impl ApiProvider for WeatherApiProvider {
     /// # Errors
     ///
     /// Returns an error if:
-    /// * An unknown request type (`kind`) is specified
+    /// * The request type (`kind`) is not supported
     /// * The HTTP request fails
     /// * Reading the response text fails
+    /// * The location is unknown, a [`WappError::CityNotFound`](crate::error::WappError)
+    /// * WeatherAPI answers with an error status, see [`error_message`]
     ///
-    async fn get_data(&self, city: String, kind: String) -> Result<String> {
-        // Encode city name for safe use in URL
-        let city = encode(&city);
-
-        // Build URL based on request type
-        let url = match kind.as_str() {
-            "now" => {
-                let mut url = format!(
-                    "{}/current.json?key={}&q={}",
-                    self.base_url, self.api_key, city
-                );
-
-                // Add response language if specified
-                if let Some(lang) = &self.lang {
-                    url.push_str("&lang=");
-                    url.push_str(lang);
-                }
-
-                url
-            }
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        let url = self.build_url(&location, kind, days)?;
 
-            "forecast" | "tomorrow" => {
-                // Set forecast days: 1 for tomorrow, 3 for general forecast
-                let days = if kind == "tomorrow" { 1 } else { 3 };
-
-                let mut url = format!(
-                    "{}/forecast.json?key={}&q={}&days={}",
-                    self.base_url, self.api_key, city, days
-                );
+        // Execute HTTP request and return response text
+        let body = super::fetch_checked(self.name(), &url, error_message)
+            .await
+            .map_err(|e| super::not_found_as(e, &location, is_not_found))?;
 
-                // Add response language if specified
-                if let Some(lang) = &self.lang {
-                    url.push_str("&lang=");
-                    url.push_str(lang);
-                }
+        // Days count from the city's today, so tomorrow is cut from the answer
+        Ok(match kind {
+            DataKind::Tomorrow => self.keep_tomorrow(&body).unwrap_or(body),
+            _ => body,
+        })
+    }
+}
 
-                url
-            }
+/// Whether a WeatherAPI error is `1006 No matching location found`.
+pub fn is_not_found(_status: u16, body: &str) -> bool {
+    let json: serde_json::Value = match serde_json::from_str(body) {
+        Ok(json) => json,
+        Err(_) => return false,
+    };
+    json["error"]["code"].as_i64() == Some(1006)
+}
 
-            // Return error for unknown request types
-            _ => return Err(anyhow::anyhow!("Unknown data type: {}", kind)),
-        };
+/// Reads the `error.code` and `error.message` of a WeatherAPI error body,
+/// e.g. `WeatherAPI error 1006: No matching location found`.
+pub fn error_message(body: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = json.get("error")?;
+    let message = error["message"].as_str()?.trim().trim_end_matches('.');
 
-        // Execute HTTP request and return response text
-        Ok(reqwest::get(url).await?.text().await?)
-    }
+    Some(match error["code"].as_i64() {
+        Some(code) => format!("WeatherAPI error {}: {}", code, message),
+        None => format!("WeatherAPI error: {}", message),
+    })
 }
@@ -0,0 +1,328 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::BTreeMap;
+
+use super::{
+    append_params, effective_days, ApiProvider, Capabilities, DataKind, PlannedRequest,
+    ProviderOverrides,
+};
+use crate::config::KindOverride;
+use crate::location::Location;
+use crate::request::{parse_date, DateWindow};
+use crate::series::{summarize_day, DailySummary, SeriesPoint};
+
+/// Default base URL of the service.
+pub const DEFAULT_BASE_URL: &str = "https://wttr.in";
+
+/// Longest excerpt of a non-JSON response quoted in an error.
+const EXCERPT_CHARS: usize = 200;
+
+/// Provider for working with wttr.in.
+///
+/// wttr.in needs no API key or configuration. Every kind uses the same `j1`
+/// JSON payload, which holds the current conditions and a 3-day forecast.
+///
+/// # Fields
+///
+/// * `base_url` - Base URL of the service
+/// * `lang` - Language of condition descriptions
+/// * `extra_params` - Additional query parameters appended to every request
+/// * `kind_overrides` - Per-kind request parameters from the config
+pub struct WttrProvider {
+    pub base_url: String,
+    pub lang: Option<String>,
+    pub extra_params: Vec<(String, String)>,
+    pub kind_overrides: BTreeMap<String, KindOverride>,
+}
+
+impl WttrProvider {
+    /// Creates a new instance of `WttrProvider` from environment variables.
+    ///
+    /// # Environment Variables
+    ///
+    /// * `WTTR_BASE_URL` (optional) - Service base URL (default: "https://wttr.in")
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new provider instance; no variable is required
+    ///
+    pub fn from_env() -> Result<Self> {
+        Self::from_env_with(&ProviderOverrides::default())
+    }
+
+    /// Like [`from_env`](Self::from_env), with per-invocation `overrides`
+    /// taking precedence. `overrides.api_key` and `overrides.units` are
+    /// ignored: no key is needed and the payload carries both unit systems.
+    pub fn from_env_with(overrides: &ProviderOverrides) -> Result<Self> {
+        Ok(Self {
+            base_url: overrides
+                .env
+                .var("WTTR_BASE_URL")
+                .unwrap_or(DEFAULT_BASE_URL.into()),
+            lang: overrides
+                .lang
+                .clone()
+                .or_else(|| overrides.config_lang.clone()),
+            extra_params: overrides.extra_params.clone(),
+            kind_overrides: overrides.kind_overrides.clone(),
+        })
+    }
+
+    /// Builds the request URL for a location, e.g. `https://wttr.in/Paris%2C%20FR?format=j1`.
+    ///
+    /// The URL is the same for every kind. wttr.in reads postal codes as
+    /// they are and a lowercase three-letter path as an airport.
+    /// [`extra_params`](Self::extra_params) are appended last.
+    pub fn build_url(&self, location: &Location) -> String {
+        let path = match location {
+            Location::Iata(code) => code.to_lowercase(),
+            _ => location.query(),
+        };
+        let mut url = format!("{}/{}?format=j1", self.base_url, path);
+
+        if let Some(lang) = &self.lang {
+            url.push_str("&lang=");
+            url.push_str(&urlencoding::encode(lang));
+        }
+
+        append_params(&mut url, &self.extra_params);
+
+        url
+    }
+
+    /// Checks that a response is JSON.
+    ///
+    /// wttr.in answers unknown locations and overload with an HTML or plain
+    /// text page, even with `format=j1`.
+    ///
+    /// # Errors
+    /// Returns an error quoting the start of the page's text.
+    pub fn check_json(location: &Location, body: &str) -> Result<serde_json::Value> {
+        serde_json::from_str(body).map_err(|_| {
+            anyhow::anyhow!(
+                "wttr.in returned no weather data for '{}': {}",
+                location.label(),
+                page_excerpt(body)
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl ApiProvider for WttrProvider {
+    fn name(&self) -> &str {
+        "wttr"
+    }
+
+    /// The `j1` payload holds today and the next two days.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 3,
+            kinds: &[DataKind::Now, DataKind::Forecast, DataKind::Tomorrow],
+            history_since: None,
+        }
+    }
+
+    /// Returns the dates of the `weather` entries.
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        Some(
+            json["weather"]
+                .as_array()?
+                .iter()
+                .filter_map(|day| parse_date(&day["date"]))
+                .collect(),
+        )
+    }
+
+    /// Keeps the `weather` entries dated inside `window`.
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        let mut json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        json["weather"]
+            .as_array_mut()?
+            .retain(|day| parse_date(&day["date"]).is_some_and(|d| window.contains(d)));
+
+        serde_json::to_string_pretty(&json).ok()
+    }
+
+    /// Reads `current_condition`; wttr.in sends every value as a string.
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+        let current = &json["current_condition"][0];
+
+        let mut point = SeriesPoint::at(observation_time(current)?.and_utc());
+        point.temp_c = number(&current["temp_C"]);
+        point.feels_like_c = number(&current["FeelsLikeC"]);
+        point.humidity = number(&current["humidity"]);
+        point.pressure_mb = number(&current["pressure"]);
+        point.wind_kph = number(&current["windspeedKmph"]);
+        point.precip_mm = number(&current["precipMM"]);
+        point.cloud = number(&current["cloudcover"]);
+        point.condition = description(current);
+
+        Some(point)
+    }
+
+    /// Reads the `weather` entries; precipitation and condition are
+    /// aggregated from the day's 3-hourly entries, see [`summarize_day`].
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        let json: serde_json::Value = serde_json::from_str(body).ok()?;
+
+        let days = json["weather"]
+            .as_array()?
+            .iter()
+            .filter_map(|day| {
+                let date = parse_date(&day["date"])?;
+                let midnight = date.and_time(NaiveTime::MIN).and_utc();
+
+                let points: Vec<SeriesPoint> = day["hourly"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|entry| {
+                        let mut point = SeriesPoint::at(midnight);
+                        point.precip_mm = number(&entry["precipMM"]);
+                        point.wind_kph = number(&entry["windspeedKmph"]);
+                        point.condition = description(entry);
+                        point
+                    })
+                    .collect();
+
+                Some(DailySummary {
+                    min_c: number(&day["mintempC"]),
+                    max_c: number(&day["maxtempC"]),
+                    ..summarize_day(date, &points)
+                })
+            })
+            .collect();
+
+        Some(days)
+    }
+
+    /// The request of [`get_data`](Self::get_data); every kind is served
+    /// from the same response.
+    fn build_request(
+        &self,
+        location: &Location,
+        _kind: DataKind,
+        _days: Option<u32>,
+    ) -> Result<PlannedRequest> {
+        Ok(PlannedRequest::get(self.build_url(location)))
+    }
+
+    /// Retrieves weather data for the specified city.
+    ///
+    /// # Arguments
+    ///
+    /// * `location` - Location to fetch data for
+    /// * `kind` - Type of request:
+    ///   - "now" - the full payload
+    ///   - "forecast" - the payload with the first `days` forecast days
+    ///   - "tomorrow" - the payload with only tomorrow's forecast day
+    /// * `days` - Forecast length for "forecast"
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - JSON response from the service as a string, or an error
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The request type (`kind`) is not supported
+    /// * The HTTP request fails
+    /// * wttr.in returns a page instead of JSON, see [`check_json`](WttrProvider::check_json)
+    ///
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        days: Option<u32>,
+    ) -> Result<String> {
+        if !self.capabilities().kinds.contains(&kind) {
+            return Err(kind.unsupported(self.name()));
+        }
+
+        let body = super::fetch(self.name(), &self.build_url(&location)).await?;
+        let json = Self::check_json(&location, &body)?;
+
+        // Slice out the requested forecast days.
+        match (kind, effective_days(&self.kind_overrides, kind, days)) {
+            (DataKind::Forecast, Some(days)) => slice_days(json, 0, days as usize),
+            (DataKind::Tomorrow, _) => slice_days(json, 1, 1),
+            _ => Ok(body),
+        }
+    }
+}
+
+/// Keeps `take` entries of `weather` after skipping the first `skip`.
+fn slice_days(mut json: serde_json::Value, skip: usize, take: usize) -> Result<String> {
+    if let Some(weather) = json["weather"].as_array_mut() {
+        *weather = weather.iter().skip(skip).take(take).cloned().collect();
+    }
+
+    Ok(serde_json::to_string_pretty(&json)?)
+}
+
+/// Parses a numeric value that wttr.in sends as a string, e.g. `"14"`.
+fn number(value: &serde_json::Value) -> Option<f64> {
+    value
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| value.as_f64())
+}
+
+fn description(entry: &serde_json::Value) -> Option<String> {
+    entry["weatherDesc"][0]["value"]
+        .as_str()
+        .map(|s| s.trim().to_string())
+}
+
+/// UTC time of a `current_condition` entry.
+///
+/// `observation_time` is the UTC time of day (`08:00 AM`) without a date,
+/// and `localObsDateTime` the local date and time. The UTC date is the one
+/// that puts the observation closest to the local time, since UTC offsets
+/// never exceed 14 hours.
+fn observation_time(current: &serde_json::Value) -> Option<NaiveDateTime> {
+    let utc_time =
+        NaiveTime::parse_from_str(current["observation_time"].as_str()?, "%I:%M %p").ok()?;
+    let local =
+        NaiveDateTime::parse_from_str(current["localObsDateTime"].as_str()?, "%Y-%m-%d %I:%M %p")
+            .ok()?;
+
+    [-1, 0, 1]
+        .iter()
+        .map(|offset| (local.date() + Duration::days(*offset)).and_time(utc_time))
+        .min_by_key(|candidate| (*candidate - local).num_minutes().abs())
+}
+
+/// Readable start of an HTML or text page: tags removed, whitespace collapsed.
+fn page_excerpt(body: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in body.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return "empty response".to_string();
+    }
+
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
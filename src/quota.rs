@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::error::WappError;
+use crate::fsutil::{lock_sibling, write_atomic};
+
+/// Name of the counter file in the data directory.
+pub const QUOTA_FILE: &str = "quota.json";
+
+/// Calls made to each provider on one local day, as kept in [`QUOTA_FILE`].
+///
+/// ```json
+/// { "date": "2025-01-10", "calls": { "openweather": 412 } }
+/// ```
+///
+/// # Fields
+/// - `date`: the day counted, as `YYYY-MM-DD`; `None` before the first
+///   call,
+/// - `calls`: calls of that day by provider name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaCounter {
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub calls: BTreeMap<String, u32>,
+}
+
+impl QuotaCounter {
+    /// Reads the counter at `path`; a missing file has counted nothing.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a counter.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("invalid quota counter {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!("cannot read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Writes the counter to `path` atomically, creating its directory.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            crate::paths::ensure_dir(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(path, json.as_bytes())
+            .map_err(|e| anyhow!("cannot write {}: {}", path.display(), e))
+    }
+
+    /// The calls to `provider` on `today`; none once the day counted is
+    /// over.
+    pub fn used(&self, provider: &str, today: NaiveDate) -> u32 {
+        if !self.counts(today) {
+            return 0;
+        }
+        self.calls.get(provider).copied().unwrap_or(0)
+    }
+
+    /// Counts one call to `provider` on `today`, starting the count of
+    /// every provider afresh on a new day.
+    pub fn record(&mut self, provider: &str, today: NaiveDate) {
+        if !self.counts(today) {
+            self.date = Some(today.to_string());
+            self.calls.clear();
+        }
+        *self.calls.entry(provider.to_string()).or_default() += 1;
+    }
+
+    /// Whether the counted day is `today`.
+    fn counts(&self, today: NaiveDate) -> bool {
+        self.date.as_deref() == Some(today.to_string().as_str())
+    }
+}
+
+/// Today's calls to a provider against its daily limit, as listed by
+/// `wapp providers`.
+///
+/// # Fields
+/// - `used`: calls made since local midnight,
+/// - `limit`: calls allowed per day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QuotaUsage {
+    pub used: u32,
+    pub limit: u32,
+}
+
+/// Daily call limits of providers, the `quota` section of the config, and
+/// the counter of the calls made against them.
+///
+/// Only network requests are counted, one per attempt, so retries count
+/// too and responses served from the cache do not. Days are local days:
+/// every budget renews at local midnight.
+///
+/// # Fields
+/// - `limits`: calls allowed per day by provider name; other providers are
+///   neither limited nor counted,
+/// - `path`: the counter file, see [`QuotaCounter`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quota {
+    pub limits: BTreeMap<String, u32>,
+    pub path: PathBuf,
+}
+
+impl Quota {
+    /// Limits counted in [`QUOTA_FILE`] of `data_dir`.
+    pub fn new(limits: BTreeMap<String, u32>, data_dir: &Path) -> Self {
+        Self {
+            limits,
+            path: data_dir.join(QUOTA_FILE),
+        }
+    }
+
+    /// The calls to `provider` on the day of `now`, local time, against its
+    /// limit; `None` for a provider without one.
+    pub fn usage(&self, provider: &str, now: NaiveDateTime) -> Option<QuotaUsage> {
+        let limit = *self.limits.get(provider)?;
+        let used = self.counter().used(provider, now.date());
+        Some(QuotaUsage { used, limit })
+    }
+
+    /// Counts a request to `provider` at `now`, local time, if its budget
+    /// for the day allows one more.
+    ///
+    /// The counter is locked while it is updated, so a status bar and a
+    /// `get --watch` running at once count every call. A counter that
+    /// cannot be read is logged and counted afresh; one that cannot be
+    /// written is logged, and the request is made anyway.
+    ///
+    /// # Errors
+    /// Returns a [`WappError::QuotaExhausted`] when `provider` has used its
+    /// limit for the day.
+    pub fn spend(&self, provider: &str, now: NaiveDateTime) -> Result<()> {
+        let Some(&limit) = self.limits.get(provider) else {
+            return Ok(());
+        };
+        let _lock = match self.lock() {
+            Ok(file) => Some(file),
+            Err(e) => {
+                tracing::warn!(error = %e, "quota counter not locked");
+                None
+            }
+        };
+
+        let today = now.date();
+        let mut counter = self.counter();
+        if counter.used(provider, today) >= limit {
+            return Err(WappError::QuotaExhausted {
+                provider: provider.to_string(),
+                limit,
+                renews: renewal(now),
+            }
+            .into());
+        }
+
+        counter.record(provider, today);
+        if let Err(e) = counter.save(&self.path) {
+            tracing::warn!(error = %e, "quota counter not saved");
+        }
+        Ok(())
+    }
+
+    /// The counter of [`path`](Self::path); an unreadable one is logged and
+    /// treated as empty.
+    fn counter(&self) -> QuotaCounter {
+        QuotaCounter::load(&self.path).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "ignoring the quota counter");
+            QuotaCounter::default()
+        })
+    }
+
+    /// Takes the lock of the counter, held on a sibling `quota.json.lock`,
+    /// waiting while another process holds it.
+    fn lock(&self) -> Result<fs::File> {
+        lock_sibling(&self.path)
+    }
+}
+
+/// The local midnight after `now`, when every budget renews.
+pub fn renewal(now: NaiveDateTime) -> NaiveDateTime {
+    (now.date() + chrono::Days::new(1)).and_time(chrono::NaiveTime::MIN)
+}
+
+/// Whether `error` is, or was caused by, a [`WappError::QuotaExhausted`].
+pub fn is_exhausted(error: &anyhow::Error) -> bool {
+    matches!(WappError::of(error), Some(WappError::QuotaExhausted { .. }))
+}
+
+/// Quota set by [`use_quota`].
+static QUOTA: OnceLock<Quota> = OnceLock::new();
+
+/// Makes every later provider request of this process count against
+/// `quota`, see [`Quota::spend`]; the first call wins. Without a call,
+/// requests are neither limited nor counted.
+pub fn use_quota(quota: Quota) {
+    let _ = QUOTA.set(quota);
+}
+
+/// The quota set with [`use_quota`], if any.
+pub fn quota() -> Option<&'static Quota> {
+    QUOTA.get()
+}
@@ -0,0 +1,633 @@
+use chrono::{DateTime, Duration, DurationRound, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::location::{CityName, Location};
+use crate::providers::{days_shortfall, validate_days, validate_kind, ApiProvider, DataKind};
+
+/// A fully resolved weather request.
+///
+/// Every command that fetches weather data (`get`, `forecast`, …) builds one
+/// of these and hands it to [`execute`], so validation and post-processing
+/// live in a single place.
+///
+/// # Fields
+/// - `location`: parsed location,
+/// - `kind`: data kind such as [`DataKind::Now`] or [`DataKind::Hourly`],
+/// - `days`: requested forecast length, validated against the provider,
+/// - `window`: optional date range the forecast is trimmed to,
+/// - `day`: optional index of a single forecast day to keep (0 = today),
+/// - `hours`: optional number of hourly entries to keep, from the hour
+///   under way.
+#[derive(Debug, Clone)]
+pub struct WeatherRequest {
+    pub location: Location,
+    pub kind: DataKind,
+    pub days: Option<u32>,
+    pub window: Option<DateWindow>,
+    pub day: Option<u32>,
+    pub hours: Option<u32>,
+}
+
+/// Hours an hourly request covers unless `--hours` says otherwise.
+pub const DEFAULT_HOURS: u32 = 24;
+
+impl WeatherRequest {
+    /// Creates a request for a city without a date window.
+    pub fn new(city: &CityName, kind: DataKind, days: Option<u32>) -> Self {
+        Self::at(city.into(), kind, days)
+    }
+
+    /// Creates a request for any location without a date window.
+    pub fn at(location: Location, kind: DataKind, days: Option<u32>) -> Self {
+        Self {
+            location,
+            kind,
+            days,
+            window: None,
+            day: None,
+            hours: None,
+        }
+    }
+
+    /// Restricts the request to a single forecast day.
+    ///
+    /// The forecast is fetched up to and including that day.
+    pub fn with_day(mut self, day: u32) -> Self {
+        self.days = Some(day + 1);
+        self.day = Some(day);
+        self
+    }
+
+    /// Restricts the request to the next `hours`, starting with the hour
+    /// under way.
+    ///
+    /// The forecast is fetched far enough to cover them, see [`fetch_days`].
+    pub fn with_hours(mut self, hours: u32) -> Self {
+        self.days = None;
+        self.hours = Some(hours);
+        self
+    }
+}
+
+/// Inclusive range of calendar dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateWindow {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateWindow {
+    /// Validates a forecast window relative to `today`.
+    ///
+    /// A missing `from` defaults to `today` and a missing `to` to `from`.
+    /// Returns the window and the number of forecast days needed to cover it.
+    ///
+    /// # Errors
+    /// Returns an error if the window starts in the past or ends before it starts.
+    pub fn for_forecast(
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        today: NaiveDate,
+    ) -> anyhow::Result<(Self, u32)> {
+        let from = from.unwrap_or(today);
+        let to = to.unwrap_or(from);
+
+        if from < today {
+            return Err(anyhow::anyhow!(
+                "--from {} is in the past; forecasts start today ({})",
+                from,
+                today
+            ));
+        }
+
+        if to < from {
+            return Err(anyhow::anyhow!("--to {} is before --from {}", to, from));
+        }
+
+        let days = (to - today).num_days() as u32 + 1;
+        Ok((Self { from, to }, days))
+    }
+
+    /// Returns `true` if `date` falls inside the window.
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+
+    /// Every date of the window, in order.
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> {
+        let to = self.to;
+        self.from.iter_days().take_while(move |date| *date <= to)
+    }
+}
+
+impl std::str::FromStr for DateWindow {
+    type Err = anyhow::Error;
+
+    /// Parses `FROM..TO`, e.g. `2024-06-01..2024-06-07`.
+    ///
+    /// # Errors
+    /// Returns an error if a date is not `YYYY-MM-DD` or `TO` is before
+    /// `FROM`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once("..")
+            .ok_or_else(|| anyhow::anyhow!("expected FROM..TO, e.g. 2024-06-01..2024-06-07"))?;
+        let date = |value: &str| {
+            NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+                .map_err(|_| anyhow::anyhow!("'{}' is not a date as YYYY-MM-DD", value.trim()))
+        };
+        let (from, to) = (date(from)?, date(to)?);
+
+        if to < from {
+            return Err(anyhow::anyhow!("{} is before {}", to, from));
+        }
+        Ok(Self { from, to })
+    }
+}
+
+/// Range of times from `from` up to, but excluding, `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HourWindow {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl HourWindow {
+    /// The `hours` starting with the hour under way at `now`, so the list
+    /// starts "now" rather than at midnight.
+    pub fn next(now: DateTime<Utc>, hours: u32) -> Self {
+        let from = now.duration_trunc(Duration::hours(1)).unwrap_or(now);
+
+        Self {
+            from,
+            to: from + Duration::hours(hours as i64),
+        }
+    }
+
+    /// Returns `true` if `time` falls inside the window.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.from <= time && time < self.to
+    }
+}
+
+/// Checks the kind and the forecast length of `request` against the
+/// capabilities of `provider`.
+///
+/// # Errors
+/// Returns an error if the provider does not serve the kind, or that many
+/// days or that day, or if `days` are given for current conditions,
+/// alerts, air quality, or the weekend.
+pub fn validate_request(
+    provider: &dyn ApiProvider,
+    request: &WeatherRequest,
+) -> anyhow::Result<()> {
+    validate_kind(provider, request.kind)?;
+
+    let snapshot = matches!(
+        request.kind,
+        DataKind::Now | DataKind::Alerts | DataKind::Air | DataKind::Astro(_) | DataKind::Weekend
+    );
+    if snapshot && request.days.is_some() {
+        return Err(anyhow::anyhow!(
+            "--days does not apply to --data {}; use it with forecast or hourly",
+            request.kind
+        ));
+    }
+
+    // Reject forecast lengths the provider cannot serve.
+    if let Some(hours) = request.hours {
+        validate_hours(provider, hours)?;
+    } else if let Some(day) = request.day {
+        validate_day(provider, day)?;
+    } else if let Some(days) = request.days {
+        validate_days(provider, days)?;
+    }
+    Ok(())
+}
+
+/// Executes a weather request against a provider.
+///
+/// 1. validates the kind and `days` against the provider's capabilities,
+///    see [`validate_request`],
+/// 2. fetches the data, through the [`response_cache`](crate::cache::response_cache)
+///    at the current time if one is set,
+/// 3. logs a warning if fewer days than requested were returned,
+/// 4. trims the response to the date window, the single day, or the next
+///    hours, if any; a forecast of `days` is cut to its first `days` dates,
+///    as a provider counting hours from now also returns part of the next,
+///    and a `weekend` forecast to the weekend in the location's time zone.
+///
+/// # Errors
+/// Returns an error if validation or the provider request fails, or the
+/// selected day is not in the response.
+pub async fn execute(
+    provider: &dyn ApiProvider,
+    request: WeatherRequest,
+) -> anyhow::Result<String> {
+    validate_request(provider, &request)?;
+
+    let days = fetch_days(provider, &request);
+    let response = match crate::cache::response_cache() {
+        Some(cache) => {
+            cache
+                .at(crate::cache::now_secs())
+                .fetch(provider, request.location, request.kind.fetched(), days)
+                .await?
+        }
+        None => {
+            provider
+                .get_data(request.location, request.kind.fetched(), days)
+                .await?
+        }
+    };
+
+    // Warn when the provider returned a shorter forecast than requested.
+    if let Some(days) = request.days {
+        if let Some(warning) = days_shortfall(provider, days, &response) {
+            tracing::warn!(provider = provider.name(), "{}", warning);
+        }
+    }
+
+    if let Some(day) = request.day {
+        return select_day(provider, &response, day);
+    }
+
+    if let Some(hours) = request.hours {
+        let window = HourWindow::next(Utc::now(), hours);
+        return Ok(provider.retain_hours(&response, window).unwrap_or(response));
+    }
+
+    let window = match (request.window, request.kind, request.days) {
+        (Some(window), _, _) => Some(window),
+        (None, DataKind::Forecast, Some(days)) => first_days(provider, &response, days),
+        (None, DataKind::Weekend, _) => {
+            let today = crate::weekend::today(Utc::now(), provider.timezone(&response));
+            Some(crate::weekend::window(today).0)
+        }
+        _ => None,
+    };
+
+    Ok(match window {
+        Some(window) => provider.retain_dates(&response, window).unwrap_or(response),
+        None => response,
+    })
+}
+
+/// The window of the first `days` forecast dates in `body`, or `None` if
+/// there are no more than that.
+fn first_days(provider: &dyn ApiProvider, body: &str, days: u32) -> Option<DateWindow> {
+    let dates = provider.forecast_dates(body)?;
+    let last = *dates.get(days as usize - 1)?;
+
+    (dates.len() > days as usize).then(|| DateWindow {
+        from: dates[0],
+        to: last,
+    })
+}
+
+/// Executes `request` against the first of `providers`, moving on to the
+/// next one only while a provider is unavailable (see
+/// [`is_unavailable`](crate::providers::is_unavailable)) or has used its
+/// daily quota (see [`is_exhausted`](crate::quota::is_exhausted)) or the
+/// provider's own (see
+/// [`is_quota_forbidden`](crate::providers::is_quota_forbidden)), and
+/// returns the provider that served it with the response.
+///
+/// Which provider served the response is logged; a fallback is logged as a
+/// warning.
+///
+/// # Errors
+/// Returns the first error that is not an outage, with close matches for an
+/// unknown city (see [`suggest_locations`](crate::providers::suggest_locations)),
+/// or the last provider's error if every provider is unavailable.
+pub async fn execute_with_fallback(
+    providers: &[Box<dyn ApiProvider>],
+    request: WeatherRequest,
+) -> anyhow::Result<(&dyn ApiProvider, String)> {
+    let mut last_error = anyhow::anyhow!("no provider to query");
+
+    for (i, provider) in providers.iter().enumerate() {
+        match execute(provider.as_ref(), request.clone()).await {
+            Ok(response) => {
+                match i {
+                    0 => tracing::info!(provider = provider.name(), "response served"),
+                    _ => tracing::warn!(
+                        provider = provider.name(),
+                        "response served by fallback provider"
+                    ),
+                }
+                return Ok((provider.as_ref(), response));
+            }
+            Err(e)
+                if crate::providers::is_unavailable(&e)
+                    || crate::quota::is_exhausted(&e)
+                    || crate::providers::is_quota_forbidden(&e) =>
+            {
+                tracing::warn!(provider = provider.name(), error = %e, "provider unavailable");
+                last_error = e;
+            }
+            Err(e) => return Err(crate::providers::suggest_locations(provider.as_ref(), e).await),
+        }
+    }
+
+    Err(last_error)
+}
+
+/// How a request is spread over the provider and its fallbacks, the
+/// `strategy` of the config:
+///
+/// - `single`: the provider alone, its fallbacks left out,
+/// - `fallback`: the provider, then each fallback while the ones before it
+///   are unavailable, see [`execute_with_fallback`]; the default,
+/// - `race`: every provider at once, the first readable response winning,
+///   see [`execute_race`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    Single,
+    #[default]
+    Fallback,
+    Race,
+}
+
+impl Strategy {
+    /// The name of the strategy in the config, e.g. `race`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Single => "single",
+            Strategy::Fallback => "fallback",
+            Strategy::Race => "race",
+        }
+    }
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "single" => Ok(Strategy::Single),
+            "fallback" => Ok(Strategy::Fallback),
+            "race" => Ok(Strategy::Race),
+            _ => Err(anyhow::anyhow!(
+                "unknown strategy '{}': expected single, fallback, or race",
+                value
+            )),
+        }
+    }
+}
+
+/// Error of [`execute_race`] when no provider answered readably, with the
+/// error of each one, by provider name, in provider order.
+#[derive(Debug)]
+pub struct RaceFailed {
+    pub errors: Vec<(String, anyhow::Error)>,
+}
+
+impl std::fmt::Display for RaceFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "every provider failed")?;
+        for (i, (provider, e)) in self.errors.iter().enumerate() {
+            let separator = if i == 0 { ": " } else { "; " };
+            write!(f, "{}{}: {:#}", separator, provider, e)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RaceFailed {}
+
+/// Executes `request` against all `providers` at once and returns the
+/// first response that can be read, see [`read`](crate::output::read),
+/// with the provider that sent it; the requests still under way are then
+/// dropped, which cancels them.
+///
+/// Only the winner's response is kept by the
+/// [`response_cache`](crate::cache::response_cache), so a fresh one wins
+/// the next race at once. The winner is logged, the errors of the others
+/// only at debug level.
+///
+/// # Errors
+/// Returns the error of a lone provider as it is, else a [`RaceFailed`]
+/// with every provider's error once all of them failed.
+pub async fn execute_race(
+    providers: &[Box<dyn ApiProvider>],
+    request: WeatherRequest,
+) -> anyhow::Result<(&dyn ApiProvider, String)> {
+    use futures::stream::{FuturesUnordered, StreamExt};
+
+    let started = std::time::Instant::now();
+    let kind = request.kind.to_string();
+    let mut racing: FuturesUnordered<_> = providers
+        .iter()
+        .enumerate()
+        .map(|(i, provider)| {
+            let (request, kind) = (request.clone(), &kind);
+            async move {
+                let provider = provider.as_ref();
+                let result = execute(provider, request).await.and_then(|body| {
+                    match crate::output::read(provider, kind, &body) {
+                        Some(_) => Ok(body),
+                        None => Err(anyhow::anyhow!(
+                            "cannot read '{}' data from '{}'",
+                            kind,
+                            provider.name()
+                        )),
+                    }
+                });
+                (i, result)
+            }
+        })
+        .collect();
+
+    let mut errors = Vec::new();
+    while let Some((i, result)) = racing.next().await {
+        let provider = providers[i].as_ref();
+        match result {
+            Ok(body) => {
+                tracing::info!(
+                    provider = provider.name(),
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    "race won"
+                );
+                return Ok((provider, body));
+            }
+            Err(e) => {
+                tracing::debug!(provider = provider.name(), error = %e, "race lost");
+                errors.push((i, e));
+            }
+        }
+    }
+
+    errors.sort_by_key(|(i, _)| *i);
+    match errors.len() {
+        0 => Err(anyhow::anyhow!("no provider to query")),
+        1 => {
+            let (i, e) = errors.remove(0);
+            Err(crate::providers::suggest_locations(providers[i].as_ref(), e).await)
+        }
+        _ => Err(RaceFailed {
+            errors: errors
+                .into_iter()
+                .map(|(i, e)| (providers[i].name().to_string(), e))
+                .collect(),
+        }
+        .into()),
+    }
+}
+
+/// Executes several requests concurrently, returning each kind's result in
+/// request order.
+///
+/// Every kind is validated before any request is sent, so a typo in one
+/// kind fails fast. After that, a failing request only affects its own
+/// entry.
+///
+/// # Errors
+/// Returns an error if any kind is not supported by the provider.
+pub async fn execute_many(
+    provider: &dyn ApiProvider,
+    requests: Vec<WeatherRequest>,
+) -> anyhow::Result<Vec<(String, anyhow::Result<String>)>> {
+    for request in &requests {
+        validate_kind(provider, request.kind)?;
+    }
+
+    let kinds: Vec<String> = requests.iter().map(|r| r.kind.to_string()).collect();
+    let results =
+        futures::future::join_all(requests.into_iter().map(|r| execute(provider, r))).await;
+
+    Ok(kinds.into_iter().zip(results).collect())
+}
+
+/// Executes requests for several locations, at most `limit` at a time,
+/// returning each location's label and result in request order.
+///
+/// As with [`execute_many`], kinds are validated up front and a failing
+/// request only affects its own entry.
+///
+/// # Errors
+/// Returns an error if any kind is not supported by the provider.
+pub async fn execute_locations(
+    provider: &dyn ApiProvider,
+    requests: Vec<WeatherRequest>,
+    limit: usize,
+) -> anyhow::Result<Vec<(String, anyhow::Result<String>)>> {
+    use futures::stream::StreamExt;
+
+    for request in &requests {
+        validate_kind(provider, request.kind)?;
+    }
+
+    let labels: Vec<String> = requests.iter().map(|r| r.location.label()).collect();
+    let results: Vec<_> = futures::stream::iter(requests)
+        .map(|r| execute(provider, r))
+        .buffered(limit.max(1))
+        .collect()
+        .await;
+
+    Ok(labels.into_iter().zip(results).collect())
+}
+
+/// Combines the results of several kinds into one JSON object keyed by kind,
+/// in the order they were requested.
+///
+/// Successful bodies are embedded as JSON (or as a string if they are not
+/// JSON); failures become `{"error": "..."}`.
+pub fn render_keyed(results: &[(String, anyhow::Result<String>)]) -> String {
+    let mut object = serde_json::Map::new();
+
+    for (kind, result) in results {
+        let value = match result {
+            Ok(body) => serde_json::from_str(body)
+                .unwrap_or_else(|_| serde_json::Value::String(body.clone())),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        object.insert(kind.clone(), value);
+    }
+
+    serde_json::to_string_pretty(&serde_json::Value::Object(object)).unwrap_or_default()
+}
+
+/// Checks a `--day` index against the provider's horizon.
+fn validate_day(provider: &dyn ApiProvider, day: u32) -> anyhow::Result<()> {
+    let max = provider.capabilities().max_forecast_days;
+
+    if day >= max {
+        return Err(anyhow::anyhow!(
+            "--day {} is beyond the forecast horizon of '{}': days 0 to {} are available",
+            day,
+            provider.name(),
+            max - 1
+        ));
+    }
+
+    Ok(())
+}
+
+/// The forecast length to fetch for `request`: for its `hours`, today and
+/// every day they reach into, up to the provider's horizon; for the
+/// weekend, every day up to its end, see [`weekend::fetch_days`], as far
+/// as the horizon; else the requested `days`.
+///
+/// [`weekend::fetch_days`]: crate::weekend::fetch_days
+pub fn fetch_days(provider: &dyn ApiProvider, request: &WeatherRequest) -> Option<u32> {
+    let max = provider.capabilities().max_forecast_days;
+    match (request.hours, request.kind) {
+        (Some(hours), _) => Some((hours.div_ceil(24) + 1).min(max)),
+        (None, DataKind::Weekend) => Some(crate::weekend::fetch_days(Utc::now()).min(max)),
+        (None, _) => request.days,
+    }
+}
+
+/// Checks `--hours` against the provider's horizon.
+fn validate_hours(provider: &dyn ApiProvider, hours: u32) -> anyhow::Result<()> {
+    let max = provider.capabilities().max_forecast_days * 24;
+
+    if hours == 0 || hours > max {
+        return Err(anyhow::anyhow!(
+            "--hours {} is beyond the forecast horizon of '{}': 1 to {} hours are available",
+            hours,
+            provider.name(),
+            max
+        ));
+    }
+
+    Ok(())
+}
+
+/// Trims a forecast response to its `day`-th forecast date.
+fn select_day(provider: &dyn ApiProvider, body: &str, day: u32) -> anyhow::Result<String> {
+    let dates = provider.forecast_dates(body).ok_or_else(|| {
+        anyhow::anyhow!(
+            "--day needs a forecast; '{}' returned no forecast days",
+            provider.name()
+        )
+    })?;
+
+    let date = *dates.get(day as usize).ok_or_else(|| {
+        anyhow::anyhow!(
+            "--day {} is not available: '{}' returned {} days (0 to {})",
+            day,
+            provider.name(),
+            dates.len(),
+            dates.len().saturating_sub(1)
+        )
+    })?;
+
+    provider
+        .retain_dates(
+            body,
+            DateWindow {
+                from: date,
+                to: date,
+            },
+        )
+        .ok_or_else(|| anyhow::anyhow!("cannot select day {} from the response", day))
+}
+
+/// Parses the leading `YYYY-MM-DD` of a JSON string value, as found in
+/// provider fields like `date` or `dt_txt`.
+pub fn parse_date(value: &serde_json::Value) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.as_str()?.get(..10)?, "%Y-%m-%d").ok()
+}
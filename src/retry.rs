@@ -0,0 +1,103 @@
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+
+/// Attempts per provider request when neither `--retries` nor the config
+/// sets them.
+pub const DEFAULT_ATTEMPTS: u32 = 3;
+
+/// Longest `Retry-After` waited for when the config does not set
+/// `rate_limit_wait`.
+pub const DEFAULT_MAX_RETRY_AFTER_SECS: u64 = 10;
+
+/// How a provider request that failed with a
+/// [`WappError::Network`](crate::error::WappError) is repeated: after
+/// connection errors, timeouts, and `5xx` answers, and after a
+/// [`WappError::RateLimited`](crate::error::WappError) `429` without a
+/// `Retry-After`. Other statuses, such as `400` or `404`, are never repeated.
+///
+/// # Fields
+/// - `attempts`: most requests sent, including the first; `1` never retries,
+/// - `base_delay`: wait before the first retry, doubled for every next one,
+/// - `max_delay`: longest wait between two attempts,
+/// - `max_retry_after`: longest `Retry-After` of a `429` that is waited
+///   for, once, before the request fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_ATTEMPTS,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+            max_retry_after: Duration::from_secs(DEFAULT_MAX_RETRY_AFTER_SECS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy with `attempts` requests at most.
+    pub fn with_attempts(attempts: u32) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// Wait before retry number `retry` (1 for the first), given a `jitter`
+    /// between 0 and 1: half of the exponential delay plus up to the other
+    /// half, so clients that failed together do not retry together.
+    pub fn delay(&self, retry: u32, jitter: f64) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+
+        exponential / 2 + exponential.mul_f64(jitter.clamp(0.0, 1.0) / 2.0)
+    }
+}
+
+/// Reads a `Retry-After` header, either seconds (`120`) or an HTTP date
+/// (`Wed, 21 Oct 2015 07:28:00 GMT`), as the wait from `now`; a date in
+/// the past is no wait.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+/// Policy set by [`use_retry_policy`].
+static POLICY: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Makes every later provider request of this process follow `policy`; the
+/// first call wins. Without a call, the [default](RetryPolicy::default)
+/// policy applies.
+pub fn use_retry_policy(policy: RetryPolicy) {
+    let _ = POLICY.set(policy);
+}
+
+/// The policy set with [`use_retry_policy`], else the default.
+pub fn retry_policy() -> RetryPolicy {
+    POLICY.get().copied().unwrap_or_default()
+}
+
+/// A jitter between 0 and 1 from the clock, for [`RetryPolicy::delay`].
+pub fn clock_jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+
+    nanos as f64 / 1_000_000_000.0
+}
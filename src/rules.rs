@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::Instant;
+
+use crate::expr::Expr;
+use crate::notify::{DesktopNotification, Urgency};
+
+/// Exit code used when an `exit-code` rule fires.
+pub const RULE_EXIT_CODE: i32 = 3;
+
+/// A threshold rule saved in the config file.
+///
+/// ```json
+/// { "name": "frost", "expr": "temp_c < 0", "action": "notify", "cooldown_secs": 3600 }
+/// ```
+///
+/// # Fields
+/// - `name`: rule name shown when it fires,
+/// - `expr`: condition, see [`Expr`] and [`field_value`],
+/// - `action`: what to do when the condition holds,
+/// - `url`: target of the `webhook` action, posted with the headers of
+///   `--webhook-header`, see [`webhook::post`](crate::webhook::post),
+/// - `cooldown_secs`: minimum time between two firings (default: none).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    pub expr: String,
+    pub action: RuleAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+}
+
+/// Action taken when a rule fires.
+///
+/// - `notify`: desktop notification, see [`notify`](crate::notify::notify)
+///   (printed to stderr where unavailable),
+/// - `webhook`: POST a JSON event to the rule's `url`,
+/// - `log`: print a line to stderr,
+/// - `exit-code`: exit with [`RULE_EXIT_CODE`] after the current fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleAction {
+    Notify,
+    Webhook,
+    Log,
+    ExitCode,
+}
+
+/// Result of evaluating one rule against a response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleOutcome {
+    /// The condition holds and the action should run.
+    Fired(Rule),
+    /// The condition holds but the rule is still cooling down.
+    CoolingDown(String),
+    /// The condition does not hold.
+    Clear(String),
+    /// The rule could not be parsed or evaluated.
+    Failed(String, String),
+}
+
+/// Evaluates rules after each fetch and tracks their cooldowns.
+///
+/// Each rule is parsed and evaluated on its own, so a broken rule is
+/// reported without affecting the others.
+pub struct RuleEngine {
+    rules: Vec<(Rule, Result<Expr, String>)>,
+    last_fired: HashMap<String, Instant>,
+}
+
+impl RuleEngine {
+    /// Creates an engine, parsing every rule's expression up front.
+    pub fn new(rules: Vec<Rule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let expr = Expr::parse(&rule.expr).map_err(|e| e.to_string());
+                (rule, expr)
+            })
+            .collect();
+
+        Self {
+            rules,
+            last_fired: HashMap::new(),
+        }
+    }
+
+    /// Returns `true` if there are no rules to evaluate.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Evaluates every rule against a response body at time `now`.
+    ///
+    /// A rule that fires starts its cooldown at `now`.
+    pub fn evaluate(&mut self, body: &str, now: Instant) -> Vec<RuleOutcome> {
+        let json: Option<Value> = serde_json::from_str(body).ok();
+        let lookup = |name: &str| json.as_ref().and_then(|j| field_value(j, name));
+
+        let mut outcomes = Vec::with_capacity(self.rules.len());
+
+        for (rule, expr) in &self.rules {
+            let matched = match expr {
+                Ok(expr) => expr.eval(&lookup).map_err(|e| e.to_string()),
+                Err(e) => Err(e.clone()),
+            };
+
+            let outcome = match matched {
+                Err(e) => RuleOutcome::Failed(rule.name.clone(), e),
+                Ok(false) => RuleOutcome::Clear(rule.name.clone()),
+                Ok(true) => {
+                    let cooldown = Duration::from_secs(rule.cooldown_secs.unwrap_or(0));
+                    let cooling = self
+                        .last_fired
+                        .get(&rule.name)
+                        .is_some_and(|at| now.saturating_duration_since(*at) < cooldown);
+
+                    if cooling {
+                        RuleOutcome::CoolingDown(rule.name.clone())
+                    } else {
+                        self.last_fired.insert(rule.name.clone(), now);
+                        RuleOutcome::Fired(rule.clone())
+                    }
+                }
+            };
+
+            outcomes.push(outcome);
+        }
+
+        outcomes
+    }
+}
+
+/// Friendly field names and the response paths they are read from,
+/// WeatherAPI first, then OpenWeatherMap.
+///
+/// OpenWeatherMap reports temperatures in the requested units, so only
+/// unit-independent fields are aliased for it; use a path such as
+/// `main.temp` to read its raw values.
+const FIELD_ALIASES: &[(&str, &[&str])] = &[
+    ("temp_c", &["current.temp_c"]),
+    ("temp_f", &["current.temp_f"]),
+    ("feels_like_c", &["current.feelslike_c"]),
+    ("humidity", &["current.humidity", "main.humidity"]),
+    ("pressure_mb", &["current.pressure_mb", "main.pressure"]),
+    ("wind_kph", &["current.wind_kph"]),
+    ("gust_kph", &["current.gust_kph"]),
+    ("precip_mm", &["current.precip_mm", "rain.1h"]),
+    ("cloud", &["current.cloud", "clouds.all"]),
+    ("uv", &["current.uv"]),
+    ("visibility_km", &["current.vis_km"]),
+];
+
+/// Reads a numeric field from a provider response.
+///
+/// `name` is either an alias from the table above or a dotted path into
+/// the response, where numeric segments index arrays
+/// (`forecast.forecastday.0.day.maxtemp_c`).
+pub fn field_value(json: &Value, name: &str) -> Option<f64> {
+    if let Some((_, paths)) = FIELD_ALIASES.iter().find(|(alias, _)| *alias == name) {
+        return paths.iter().find_map(|path| path_value(json, path));
+    }
+
+    path_value(json, name)
+}
+
+fn path_value(json: &Value, path: &str) -> Option<f64> {
+    path.split('.')
+        .try_fold(json, |value, segment| match segment.parse::<usize>() {
+            Ok(i) if value.is_array() => value.get(i),
+            _ => value.get(segment),
+        })?
+        .as_f64()
+}
+
+/// Error returned when an `exit-code` rule fires; `main` maps it to
+/// [`RULE_EXIT_CODE`].
+#[derive(Debug)]
+pub struct RuleExit {
+    pub rule: String,
+}
+
+impl std::fmt::Display for RuleExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rule '{}' triggered", self.rule)
+    }
+}
+
+impl std::error::Error for RuleExit {}
+
+/// Evaluates `engine` against `body`, runs the actions of fired rules, and
+/// reports failures on stderr.
+///
+/// # Errors
+/// Returns [`RuleExit`] if an `exit-code` rule fired. Action failures are
+/// reported but never abort the remaining rules.
+pub async fn apply(engine: &mut RuleEngine, body: &str, now: Instant) -> Result<()> {
+    let mut exit = None;
+
+    for outcome in engine.evaluate(body, now) {
+        match outcome {
+            RuleOutcome::Fired(rule) => {
+                if rule.action == RuleAction::ExitCode {
+                    exit.get_or_insert(rule.name.clone());
+                }
+                if let Err(e) = dispatch(&rule, body).await {
+                    eprintln!("Warning: rule '{}' action failed: {}", rule.name, e);
+                }
+            }
+            RuleOutcome::Failed(name, e) => eprintln!("Warning: rule '{}': {}", name, e),
+            RuleOutcome::CoolingDown(_) | RuleOutcome::Clear(_) => {}
+        }
+    }
+
+    match exit {
+        Some(rule) => Err(RuleExit { rule }.into()),
+        None => Ok(()),
+    }
+}
+
+/// Runs the action of a fired rule.
+///
+/// # Errors
+/// Returns an error if a webhook rule has no `url` or the request fails.
+pub async fn dispatch(rule: &Rule, body: &str) -> Result<()> {
+    let message = format!("rule '{}' triggered: {}", rule.name, rule.expr);
+
+    match rule.action {
+        RuleAction::Log | RuleAction::ExitCode => eprintln!("{}", message),
+        RuleAction::Notify => {
+            let notification = DesktopNotification {
+                summary: format!("Rule '{}' triggered", rule.name),
+                body: rule.expr.clone(),
+                urgency: Urgency::Normal,
+            };
+            if !crate::notify::notify(&notification) {
+                eprintln!("{}", message);
+            }
+        }
+        RuleAction::Webhook => {
+            let url = rule
+                .url
+                .as_deref()
+                .ok_or_else(|| anyhow!("webhook action requires a url"))?;
+
+            let event = serde_json::json!({
+                "rule": rule.name,
+                "expr": rule.expr,
+                "data": serde_json::from_str::<Value>(body).unwrap_or(Value::Null),
+            });
+
+            let webhook = crate::webhook::Webhook {
+                url: url.to_string(),
+                headers: crate::webhook::webhook()
+                    .map(|webhook| webhook.headers.clone())
+                    .unwrap_or_default(),
+                strict: false,
+            };
+            crate::webhook::post(&webhook, &event).await?;
+        }
+    }
+
+    Ok(())
+}
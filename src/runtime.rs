@@ -0,0 +1,75 @@
+use std::io;
+
+use crate::error::usage;
+
+/// Environment variable overriding the threads of the runtime, see
+/// [`flavor_for`].
+pub const THREADS_ENV: &str = "WAPP_THREADS";
+
+/// The Tokio runtime a command runs on.
+///
+/// - `CurrentThread`: everything on the main thread, the cheapest to start;
+///   enough for a single request, whose waits are all on the network,
+/// - `MultiThread`: a pool of worker threads for commands that keep many
+///   requests or timers in flight, with `workers` threads or one per CPU
+///   when `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeFlavor {
+    CurrentThread,
+    MultiThread { workers: Option<usize> },
+}
+
+/// The runtime of a command, `concurrent` when it fetches several
+/// locations or providers at once or runs until stopped, see
+/// [`Cli::is_concurrent`](crate::cli::Cli::is_concurrent).
+///
+/// `threads`, the value of [`THREADS_ENV`], overrides the choice: `1` runs
+/// every command on the current thread, and a larger number on that many
+/// worker threads.
+///
+/// # Errors
+/// Returns a [`WappError::Usage`](crate::error::WappError::Usage) unless
+/// `threads` is a positive whole number.
+pub fn flavor_for(concurrent: bool, threads: Option<&str>) -> anyhow::Result<RuntimeFlavor> {
+    let threads = match threads.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => match value.parse::<usize>() {
+            Ok(threads) if threads > 0 => Some(threads),
+            _ => {
+                return Err(usage(format!(
+                    "invalid {} '{}': expected a number of threads, at least 1",
+                    THREADS_ENV, value
+                )))
+            }
+        },
+        None => None,
+    };
+
+    Ok(match threads {
+        Some(1) => RuntimeFlavor::CurrentThread,
+        Some(workers) => RuntimeFlavor::MultiThread {
+            workers: Some(workers),
+        },
+        None if concurrent => RuntimeFlavor::MultiThread { workers: None },
+        None => RuntimeFlavor::CurrentThread,
+    })
+}
+
+/// Builds the runtime of `flavor`, with timers and I/O enabled.
+///
+/// # Errors
+/// Returns an error if the runtime cannot be created, e.g. when no thread
+/// can be started.
+pub fn build(flavor: RuntimeFlavor) -> io::Result<tokio::runtime::Runtime> {
+    let mut builder = match flavor {
+        RuntimeFlavor::CurrentThread => tokio::runtime::Builder::new_current_thread(),
+        RuntimeFlavor::MultiThread { workers } => {
+            let mut builder = tokio::runtime::Builder::new_multi_thread();
+            if let Some(workers) = workers {
+                builder.worker_threads(workers);
+            }
+            builder
+        }
+    };
+
+    builder.enable_all().build()
+}
@@ -0,0 +1,308 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::condition::{dominant, Condition};
+use crate::location::CityName;
+use crate::providers::{ApiProvider, DataKind};
+use crate::request::{execute, WeatherRequest};
+
+/// Columns of an exported series, in output order.
+///
+/// This set is stable across providers: a value the provider does not
+/// report is left empty (CSV) or `null` (JSONL).
+pub const EXPORT_COLUMNS: &[&str] = &[
+    "city",
+    "time",
+    "temp_c",
+    "feels_like_c",
+    "humidity",
+    "pressure_mb",
+    "wind_kph",
+    "precip_mm",
+    "precip_chance",
+    "cloud",
+    "condition",
+];
+
+/// Format of an exported series.
+///
+/// - `csv`: header row plus one row per record,
+/// - `jsonl`: one JSON object per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+}
+
+/// One timestamped record of a weather series, in metric units.
+///
+/// # Fields
+/// - `city`: location the record belongs to, as given by the user,
+/// - `time`: start of the period the record describes,
+/// - `temp_c`, `feels_like_c`: temperatures in °C,
+/// - `humidity`: relative humidity in %,
+/// - `pressure_mb`: pressure in millibars,
+/// - `wind_kph`: wind speed in km/h,
+/// - `precip_mm`: precipitation over the period in mm,
+/// - `precip_chance`: chance of precipitation in %,
+/// - `cloud`: cloud cover in %,
+/// - `condition`: provider's condition text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesPoint {
+    pub city: String,
+    #[serde(
+        serialize_with = "serialize_time",
+        deserialize_with = "deserialize_time"
+    )]
+    pub time: DateTime<Utc>,
+    pub temp_c: Option<f64>,
+    pub feels_like_c: Option<f64>,
+    pub humidity: Option<f64>,
+    pub pressure_mb: Option<f64>,
+    pub wind_kph: Option<f64>,
+    pub precip_mm: Option<f64>,
+    pub precip_chance: Option<f64>,
+    pub cloud: Option<f64>,
+    pub condition: Option<String>,
+}
+
+impl SeriesPoint {
+    /// Creates a record at `time` with every measurement empty.
+    pub fn at(time: DateTime<Utc>) -> Self {
+        Self {
+            city: String::new(),
+            time,
+            temp_c: None,
+            feels_like_c: None,
+            humidity: None,
+            pressure_mb: None,
+            wind_kph: None,
+            precip_mm: None,
+            precip_chance: None,
+            cloud: None,
+            condition: None,
+        }
+    }
+
+    /// Values in [`EXPORT_COLUMNS`] order, formatted for CSV.
+    fn csv_fields(&self) -> Vec<String> {
+        let num = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+
+        vec![
+            csv_escape(&self.city),
+            format_time(&self.time),
+            num(self.temp_c),
+            num(self.feels_like_c),
+            num(self.humidity),
+            num(self.pressure_mb),
+            num(self.wind_kph),
+            num(self.precip_mm),
+            num(self.precip_chance),
+            num(self.cloud),
+            csv_escape(self.condition.as_deref().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Summary of one forecast day, in metric units.
+///
+/// # Fields
+/// - `date`: the day, as dated by the provider,
+/// - `min_c`, `max_c`: lowest and highest temperature in °C,
+/// - `precip_mm`: total precipitation in mm,
+/// - `precip_chance`: the day's highest chance of precipitation in %;
+///   `None` where the provider does not report one, which is not 0%,
+/// - `max_wind_kph`: highest wind speed in km/h,
+/// - `avg_wind_kph`: mean wind speed in km/h, where the provider gives the
+///   day's hours or time steps,
+/// - `condition`: the day's prevailing condition text,
+/// - `partial`: whether the provider's steps cover only part of the day,
+///   as for the first and last day of OpenWeatherMap's `/forecast`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailySummary {
+    #[serde(serialize_with = "serialize_date")]
+    pub date: NaiveDate,
+    pub min_c: Option<f64>,
+    pub max_c: Option<f64>,
+    pub precip_mm: Option<f64>,
+    pub precip_chance: Option<f64>,
+    pub max_wind_kph: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_wind_kph: Option<f64>,
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+}
+
+/// Aggregates the records of one day into a [`DailySummary`].
+///
+/// The condition is the text of the [`dominant`] condition of the day, its
+/// most frequent text (the earliest on ties); texts without a category
+/// only count when nothing else is left. The summary is not
+/// [`partial`](DailySummary::partial); the caller knows the step length.
+pub fn summarize_day(date: NaiveDate, points: &[SeriesPoint]) -> DailySummary {
+    let temps = || points.iter().filter_map(|p| p.temp_c);
+    let precip: Vec<f64> = points.iter().filter_map(|p| p.precip_mm).collect();
+
+    let texts: Vec<&str> = points
+        .iter()
+        .filter_map(|p| p.condition.as_deref())
+        .collect();
+    let kinds: Vec<Condition> = texts.iter().map(|t| Condition::from_text(t)).collect();
+    let kind = dominant(&kinds);
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for (&text, _) in texts.iter().zip(&kinds).filter(|(_, k)| **k == kind) {
+        match counts.iter_mut().find(|(t, _)| *t == text) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((text, 1)),
+        }
+    }
+    // `max_by_key` keeps the last maximum; reverse so the earliest wins ties.
+    let condition = counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, n)| *n)
+        .map(|(text, _)| text.to_string());
+
+    DailySummary {
+        date,
+        min_c: temps().reduce(f64::min),
+        max_c: temps().reduce(f64::max),
+        precip_mm: (!precip.is_empty()).then(|| precip.iter().sum()),
+        precip_chance: points
+            .iter()
+            .filter_map(|p| p.precip_chance)
+            .reduce(f64::max),
+        max_wind_kph: points.iter().filter_map(|p| p.wind_kph).reduce(f64::max),
+        avg_wind_kph: mean(points.iter().filter_map(|p| p.wind_kph)),
+        condition,
+        partial: false,
+    }
+}
+
+/// The mean of `values`; `None` without values.
+pub fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Merges the series of several cities into one, ordered by time.
+///
+/// Records with the same timestamp keep the order in which the cities
+/// were given.
+pub fn merge(series: Vec<Vec<SeriesPoint>>) -> Vec<SeriesPoint> {
+    let mut merged: Vec<SeriesPoint> = series.into_iter().flatten().collect();
+    // Stable sort: ties stay in city order.
+    merged.sort_by_key(|point| point.time);
+    merged
+}
+
+/// Fetches and flattens the series of every city, then [`merge`]s them.
+///
+/// Each fetch goes through [`execute`], so `days` is validated once per city
+/// before any request is made for it.
+///
+/// # Errors
+/// Returns an error if a fetch fails or a response holds no series
+/// (e.g. `now`).
+pub async fn collect(
+    provider: &dyn ApiProvider,
+    cities: &[CityName],
+    kind: DataKind,
+    days: Option<u32>,
+) -> anyhow::Result<Vec<SeriesPoint>> {
+    let mut series = Vec::with_capacity(cities.len());
+
+    for city in cities {
+        let body = execute(provider, WeatherRequest::new(city, kind, days)).await?;
+
+        let mut points = provider.series(&body).ok_or_else(|| {
+            anyhow::anyhow!(
+                "'{}' data from '{}' has no time series to export",
+                kind,
+                provider.name()
+            )
+        })?;
+
+        for point in &mut points {
+            point.city = city.to_string();
+        }
+        series.push(points);
+    }
+
+    Ok(merge(series))
+}
+
+/// Renders records in the given export format.
+pub fn render(points: &[SeriesPoint], format: ExportFormat) -> String {
+    let mut out = header(format).unwrap_or_default();
+
+    for point in points {
+        out.push_str(&render_line(point, format));
+    }
+
+    out
+}
+
+/// Header line of the format, including the newline, if it has one.
+pub fn header(format: ExportFormat) -> Option<String> {
+    match format {
+        ExportFormat::Csv => Some(format!("{}\n", EXPORT_COLUMNS.join(","))),
+        ExportFormat::Jsonl => None,
+    }
+}
+
+/// Renders one record as a line of the format, including the newline.
+pub fn render_line(point: &SeriesPoint, format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => format!("{}\n", point.csv_fields().join(",")),
+        // Serializing a struct of strings and numbers cannot fail.
+        ExportFormat::Jsonl => format!("{}\n", serde_json::to_string(point).unwrap_or_default()),
+    }
+}
+
+/// Converts a Unix timestamp from a provider response.
+pub fn from_epoch(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(value.as_i64()?, 0)
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+fn deserialize_time<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    DateTime::parse_from_rfc3339(&text)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(serde::de::Error::custom)
+}
+
+/// Serializes a time as RFC 3339 UTC, e.g. `2024-06-10T14:00:00Z`.
+pub fn serialize_time<S>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&format_time(time))
+}
+
+/// Serializes a date as `YYYY-MM-DD`.
+pub fn serialize_date<S>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&date.to_string())
+}
+
+/// Quotes a CSV field if it contains a separator, quote, or line break.
+pub fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
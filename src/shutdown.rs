@@ -0,0 +1,56 @@
+use tokio_util::sync::CancellationToken;
+
+/// Exit code when a second Ctrl-C forces the process to stop.
+///
+/// A graceful shutdown after the first SIGINT or SIGTERM exits with 0.
+pub const FORCED_EXIT_CODE: i32 = 130;
+
+/// Returns a token that is cancelled on the first SIGINT or SIGTERM.
+///
+/// Long-running modes pass the token down to their loops, which stop
+/// fetching, finish any write in progress, and return. A second Ctrl-C
+/// exits immediately with [`FORCED_EXIT_CODE`].
+///
+/// Must be called from within a Tokio runtime.
+pub fn install() -> CancellationToken {
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+
+    tokio::spawn(async move {
+        signal().await;
+        tracing::info!("shutdown requested");
+        trigger.cancel();
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Interrupted again, exiting immediately");
+            std::process::exit(FORCED_EXIT_CODE);
+        }
+    });
+
+    token
+}
+
+/// Completes when the process receives SIGINT (Ctrl-C) or, on Unix, SIGTERM.
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Environment variable with the request timeout in seconds, below
+/// `--timeout` and above the `timeout` config option.
+pub const TIMEOUT_ENV: &str = "WAPP_TIMEOUT";
+
+/// Longest wait for a connection to a provider.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest wait for a whole provider request, connecting included.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long a provider request may take before it fails.
+///
+/// # Fields
+/// - `connect`: longest wait for the connection,
+/// - `request`: longest wait for the whole request, until the body is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub connect: Duration,
+    pub request: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect: DEFAULT_CONNECT_TIMEOUT,
+            request: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+impl Timeouts {
+    /// Timeouts of requests allowed `request` in all; connecting may take
+    /// the default time at most.
+    pub fn with_request(request: Duration) -> Self {
+        Self {
+            connect: DEFAULT_CONNECT_TIMEOUT.min(request),
+            request,
+        }
+    }
+}
+
+/// The timeouts of a call whose request timeout comes from, in order:
+/// `flag` (`get --timeout`), `env` (the value of [`TIMEOUT_ENV`]; empty
+/// counts as unset), or the `saved` config option, all in seconds;
+/// without any, the defaults apply.
+///
+/// # Errors
+/// Returns an error if `env` is not a whole number of seconds above `0`.
+pub fn timeouts_for(
+    flag: Option<u64>,
+    env: Option<&str>,
+    saved: Option<u64>,
+) -> anyhow::Result<Timeouts> {
+    let env = match env.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => Some(value.parse().ok().filter(|secs| *secs > 0).ok_or_else(|| {
+            anyhow::anyhow!(
+                "invalid {} '{}': expected a number of seconds, at least 1",
+                TIMEOUT_ENV,
+                value
+            )
+        })?),
+        None => None,
+    };
+
+    Ok(match flag.or(env).or(saved) {
+        Some(secs) => Timeouts::with_request(Duration::from_secs(secs)),
+        None => Timeouts::default(),
+    })
+}
+
+/// Timeouts set by [`use_timeouts`].
+static TIMEOUTS: OnceLock<Timeouts> = OnceLock::new();
+
+/// Makes every later provider request of this process use `timeouts`; the
+/// first call wins. Without a call, the [default](Timeouts::default)
+/// timeouts apply.
+pub fn use_timeouts(timeouts: Timeouts) {
+    let _ = TIMEOUTS.set(timeouts);
+}
+
+/// The timeouts set with [`use_timeouts`], else the default.
+pub fn timeouts() -> Timeouts {
+    TIMEOUTS.get().copied().unwrap_or_default()
+}
+
+/// `limit` for messages, e.g. `15s` or `0.5s`.
+pub fn describe_limit(limit: Duration) -> String {
+    format!("{}s", limit.as_secs_f64())
+}
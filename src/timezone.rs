@@ -0,0 +1,177 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Serialize, Serializer};
+
+use crate::i18n;
+
+/// The time zone of a queried location.
+///
+/// WeatherAPI names the zone (`tz_id`), so its daylight saving time is
+/// followed; OpenWeatherMap gives the offset from UTC in seconds
+/// (`timezone`), fixed for the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    /// An IANA zone, e.g. `Pacific/Auckland`.
+    Named(Tz),
+    /// A fixed offset from UTC, e.g. `UTC+12:00`.
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// The zone `seconds` east of UTC; `None` beyond a day.
+    pub fn from_offset(seconds: i64) -> Option<Self> {
+        FixedOffset::east_opt(i32::try_from(seconds).ok()?).map(Self::Fixed)
+    }
+
+    /// `time` on the wall clock of the zone.
+    pub fn local(self, time: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Self::Named(tz) => time.with_timezone(&tz).naive_local(),
+            Self::Fixed(offset) => time.with_timezone(&offset).naive_local(),
+        }
+    }
+
+    /// The instant the wall clock of the zone shows `local`: the earlier
+    /// one when the clock is turned back, `None` in the gap when it is
+    /// turned forward.
+    pub fn to_utc(self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Named(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|t| t.to_utc()),
+            Self::Fixed(offset) => offset
+                .from_local_datetime(&local)
+                .single()
+                .map(|t| t.to_utc()),
+        }
+    }
+}
+
+impl fmt::Display for Zone {
+    /// The IANA name, or the offset as `UTC+12:00`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Named(tz) => f.write_str(tz.name()),
+            Self::Fixed(offset) => {
+                let seconds = offset.local_minus_utc();
+                write!(
+                    f,
+                    "UTC{}{:02}:{:02}",
+                    if seconds < 0 { '-' } else { '+' },
+                    seconds.abs() / 3600,
+                    seconds.abs() % 3600 / 60
+                )
+            }
+        }
+    }
+}
+
+impl FromStr for Zone {
+    type Err = anyhow::Error;
+
+    /// Parses an IANA name, or an offset as [`Display`](fmt::Display)
+    /// writes it: `UTC+12:00`, `UTC-03:30`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(offset) = s.strip_prefix("UTC").filter(|rest| !rest.is_empty()) {
+            let (sign, rest) = match offset.split_at(1) {
+                ("+", rest) => (1, rest),
+                ("-", rest) => (-1, rest),
+                _ => return Err(anyhow::anyhow!("invalid time zone '{}'", s)),
+            };
+            let (hours, minutes) = rest.split_once(':').unwrap_or((rest, "0"));
+            let (hours, minutes): (i64, i64) = match (hours.parse(), minutes.parse()) {
+                (Ok(hours), Ok(minutes)) if minutes < 60 => (hours, minutes),
+                _ => return Err(anyhow::anyhow!("invalid time zone '{}'", s)),
+            };
+            return Self::from_offset(sign * (hours * 3600 + minutes * 60))
+                .ok_or_else(|| anyhow::anyhow!("invalid time zone '{}'", s));
+        }
+
+        s.parse::<Tz>()
+            .map(Self::Named)
+            .map_err(|_| anyhow::anyhow!("unknown time zone '{}'", s))
+    }
+}
+
+impl Serialize for Zone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// How text and table output show times, the `--utc` and `--time-format`
+/// options.
+///
+/// # Fields
+/// - `utc`: show times in UTC instead of the location's local time,
+/// - `format`: a strftime pattern replacing the default format of every
+///   time, e.g. `%H:%M` or `%Y-%m-%d %H:%M %Z`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TimeDisplay {
+    pub utc: bool,
+    pub format: Option<String>,
+}
+
+impl TimeDisplay {
+    /// `time` on the wall clock of `zone`, or in UTC with
+    /// [`utc`](Self::utc), in [`format`](Self::format) or else `default`.
+    /// Without a zone the machine's local time is shown, as for providers
+    /// that do not report one.
+    ///
+    /// `%Z` names the zone shown: its abbreviation such as `NZST`, else
+    /// the offset. `%a` and `%b` are named in the
+    /// [locale](crate::i18n::use_locale).
+    pub fn show(&self, time: DateTime<Utc>, zone: Option<Zone>, default: &str) -> String {
+        let format = self.format.as_deref().unwrap_or(default);
+
+        if self.utc {
+            return i18n::format(&time, format);
+        }
+        match zone {
+            Some(Zone::Named(tz)) => i18n::format(&time.with_timezone(&tz), format),
+            Some(Zone::Fixed(offset)) => i18n::format(&time.with_timezone(&offset), format),
+            None => i18n::format(&time.with_timezone(&Local), format),
+        }
+    }
+}
+
+/// Checks a `--time-format` strftime pattern.
+///
+/// # Errors
+/// Returns an error if the pattern is empty or holds an unknown `%`
+/// specifier, which chrono could not format.
+pub fn parse_time_format(s: &str) -> Result<String, String> {
+    if s.is_empty() {
+        return Err("the time format is empty".into());
+    }
+    if StrftimeItems::new(s).any(|item| matches!(item, Item::Error)) {
+        return Err(format!(
+            "invalid time format '{}'; use strftime specifiers such as %H:%M",
+            s
+        ));
+    }
+
+    Ok(s.to_string())
+}
+
+/// Time display set by [`use_time_display`].
+static TIME_DISPLAY: OnceLock<TimeDisplay> = OnceLock::new();
+
+/// Makes the text and table output of this process show times as
+/// `display` says; the first call wins. Without a call, times are local
+/// to the location in each output's default format.
+pub fn use_time_display(display: TimeDisplay) {
+    let _ = TIME_DISPLAY.set(display);
+}
+
+/// The display set with [`use_time_display`], else the default.
+pub fn time_display() -> TimeDisplay {
+    TIME_DISPLAY.get().cloned().unwrap_or_default()
+}
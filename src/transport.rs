@@ -0,0 +1,321 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::logging::redact_url;
+use crate::providers::credentials::{redact_secrets, REDACTED};
+use crate::providers::FetchResponse;
+
+/// A provider request, as an [`HttpTransport`] sends it.
+///
+/// # Fields
+/// - `method`: HTTP method, e.g. `GET`,
+/// - `url`: full URL, with the query,
+/// - `headers`: headers besides those of the shared
+///   [`client`](crate::http::client), e.g. `If-Modified-Since`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpRequest {
+    /// A `GET` request for `url`.
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    /// The request with the header `name: value` added.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// `METHOD url` with the API key masked, e.g.
+    /// `GET http://x/current.json?key=<redacted>&q=Kyiv`, naming the request
+    /// in messages and in recordings.
+    pub fn describe(&self) -> String {
+        format!("{} {}", self.method.to_uppercase(), redact_url(&self.url))
+    }
+
+    /// The request as recorded: the URL with the API key masked, and the
+    /// value of every header that may carry a credential, see
+    /// [`is_secret_header`].
+    fn masked(&self) -> Self {
+        Self {
+            method: self.method.to_uppercase(),
+            url: redact_url(&self.url),
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| match is_secret_header(name) {
+                    true => (name.clone(), REDACTED.to_string()),
+                    false => (name.clone(), value.clone()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Returns `true` for a header that may carry a credential, e.g.
+/// `Authorization` or `X-Api-Key`.
+pub fn is_secret_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["auth", "key", "token", "secret"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+/// A request that got no response, e.g. refused or timed out.
+///
+/// # Fields
+/// - `reason`: what went wrong, without the URL,
+/// - `timeout`: the request gave up after a time limit,
+/// - `connect`: no connection to the provider was made.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{reason}")]
+pub struct TransportError {
+    pub reason: String,
+    pub timeout: bool,
+    pub connect: bool,
+}
+
+/// Sends provider requests; every request of
+/// [`send`](crate::providers::send) goes through the [`transport`] of the
+/// process.
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    /// Sends `request` and returns the response, whatever its status.
+    ///
+    /// # Errors
+    /// Returns a [`TransportError`] if no response came, and another error
+    /// if the request cannot be sent at all, e.g. without a recording.
+    async fn send(&self, request: &HttpRequest) -> anyhow::Result<FetchResponse>;
+}
+
+/// Sends requests over the network with the shared
+/// [`client`](crate::http::client).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NetworkTransport;
+
+#[async_trait]
+impl HttpTransport for NetworkTransport {
+    async fn send(&self, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+        let method = reqwest::Method::from_bytes(request.method.as_bytes())
+            .with_context(|| format!("invalid HTTP method '{}'", request.method))?;
+        let mut builder = crate::http::client()?.request(method, &request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let result = async {
+            let response = builder.send().await?;
+            let header = |name| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(String::from)
+            };
+            let last_modified = header(reqwest::header::LAST_MODIFIED);
+            let etag = header(reqwest::header::ETAG);
+            let retry_after = header(reqwest::header::RETRY_AFTER);
+            let content_type = header(reqwest::header::CONTENT_TYPE);
+
+            Ok::<_, reqwest::Error>(FetchResponse {
+                status: response.status().as_u16(),
+                last_modified,
+                etag,
+                retry_after,
+                content_type,
+                body: response.text().await?,
+            })
+        }
+        .await;
+
+        result.map_err(|e| {
+            let e = e.without_url();
+            TransportError {
+                reason: e.to_string(),
+                timeout: e.is_timeout(),
+                connect: e.is_connect(),
+            }
+            .into()
+        })
+    }
+}
+
+/// A request with its response, as stored by a [`Recorder`] and served by
+/// a [`Replay`].
+///
+/// # Fields
+/// - `request`: the request, with its API key masked,
+/// - `response`: status, headers and body of the response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recording {
+    pub request: HttpRequest,
+    pub response: FetchResponse,
+}
+
+/// Sends requests through `inner`, saving each response in `dir`.
+///
+/// Every request is written to its own file, e.g.
+/// `api.weatherapi.com-5c1f…e2.json`, named after the host and a hash of
+/// [`HttpRequest::describe`], so sending it again replaces it. The API
+/// key is masked in the URL and headers, and in the body if quoted.
+pub struct Recorder {
+    dir: PathBuf,
+    inner: Arc<dyn HttpTransport>,
+}
+
+impl Recorder {
+    /// A recorder into `dir`, created on the first response.
+    pub fn new(dir: impl Into<PathBuf>, inner: Arc<dyn HttpTransport>) -> Self {
+        Self {
+            dir: dir.into(),
+            inner,
+        }
+    }
+
+    /// Path of the file recording `request`.
+    fn path(&self, request: &HttpRequest) -> PathBuf {
+        let host = reqwest::Url::parse(&request.url)
+            .ok()
+            .and_then(|url| url.host_str().map(String::from))
+            .unwrap_or_else(|| "request".to_string());
+        self.dir.join(format!(
+            "{}-{:016x}.json",
+            host,
+            crate::cache::fnv1a(&request.describe())
+        ))
+    }
+}
+
+#[async_trait]
+impl HttpTransport for Recorder {
+    async fn send(&self, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+        let response = self.inner.send(request).await?;
+
+        let recording = Recording {
+            request: request.masked(),
+            response: FetchResponse {
+                body: redact_secrets(&response.body),
+                ..response.clone()
+            },
+        };
+        let path = self.path(request);
+        let json = serde_json::to_string_pretty(&recording)?;
+        std::fs::create_dir_all(&self.dir)
+            .and_then(|()| crate::fsutil::write_atomic(&path, json.as_bytes()))
+            .with_context(|| {
+                format!("cannot record {} in {}", request.describe(), path.display())
+            })?;
+        tracing::debug!(path = %path.display(), "response recorded");
+
+        Ok(response)
+    }
+}
+
+/// Serves requests from the recordings of a [`Recorder`], never from the
+/// network.
+///
+/// A request is answered by the recording of the same method and URL, the
+/// API key aside, so recordings work whatever key is configured; headers
+/// are not compared.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    dir: PathBuf,
+    recordings: Vec<Recording>,
+}
+
+impl Replay {
+    /// Reads the recordings of `dir`: every `.json` file in it, whatever
+    /// its name, so recordings may be renamed or written by hand.
+    ///
+    /// # Errors
+    /// Returns an error if `dir` cannot be read, or a `.json` file in it
+    /// is not a recording.
+    pub fn open(dir: &Path) -> anyhow::Result<Self> {
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("cannot read recordings in {}", dir.display()))?;
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let recordings = paths
+            .iter()
+            .map(|path| read_recording(path))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            recordings,
+        })
+    }
+
+    /// Number of recordings read.
+    pub fn len(&self) -> usize {
+        self.recordings.len()
+    }
+
+    /// Returns `true` if the directory held no recording.
+    pub fn is_empty(&self) -> bool {
+        self.recordings.is_empty()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for Replay {
+    async fn send(&self, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+        let wanted = request.describe();
+
+        self.recordings
+            .iter()
+            .find(|recording| recording.request.describe() == wanted)
+            .map(|recording| recording.response.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no recorded response for {} in {}",
+                    wanted,
+                    self.dir.display()
+                )
+            })
+    }
+}
+
+/// Reads the recording at `path`.
+fn read_recording(path: &Path) -> anyhow::Result<Recording> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("cannot read recording {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("{} is not a recording", path.display()))
+}
+
+/// Transport set by [`use_transport`].
+static TRANSPORT: OnceLock<Arc<dyn HttpTransport>> = OnceLock::new();
+
+/// Makes every later provider request of this process go through
+/// `transport`; the first call wins. Without a call, requests go to the
+/// network.
+pub fn use_transport(transport: Arc<dyn HttpTransport>) {
+    let _ = TRANSPORT.set(transport);
+}
+
+/// The transport set with [`use_transport`], else [`NetworkTransport`].
+pub fn transport() -> Arc<dyn HttpTransport> {
+    TRANSPORT
+        .get()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(NetworkTransport))
+}
@@ -0,0 +1,248 @@
+use std::io;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::history::{HistoryQuery, HistoryRecord, HistoryStore};
+use crate::providers::ApiProvider;
+use crate::series::serialize_time;
+use crate::weather::WeatherData;
+
+/// File in the data directory holding the observations of `get --trend`,
+/// one JSON record per line, as in the history.
+pub const OBSERVATIONS_FILE: &str = "observations.ndjson";
+
+/// Observations kept in the store; the oldest are dropped beyond them.
+pub const MAX_OBSERVATIONS: usize = 1000;
+
+/// Default minimum age of the observation compared with, in seconds: one
+/// hour, so queries in quick succession still compare with an older one.
+pub const DEFAULT_GAP_SECS: u64 = 60 * 60;
+
+/// Temperature change in °C below which the temperature is steady.
+pub const STEADY_TEMP_C: f64 = 0.5;
+
+/// Pressure change in hPa below which the pressure is steady.
+pub const STEADY_PRESSURE_MB: f64 = 1.0;
+
+/// How a value changed since an earlier observation.
+///
+/// # Fields
+/// - `was`: the earlier value,
+/// - `delta`: the current value less the earlier one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Change {
+    pub was: f64,
+    pub delta: f64,
+}
+
+impl Change {
+    /// The change from `was` to `now`, `None` unless both are known.
+    pub fn between(was: Option<f64>, now: Option<f64>) -> Option<Self> {
+        let (was, now) = (was?, now?);
+        Some(Self {
+            was,
+            delta: now - was,
+        })
+    }
+
+    /// `↑`, `↓`, or `→` when the change is below `steady`.
+    pub fn arrow(&self, steady: f64) -> &'static str {
+        match self.delta {
+            d if d.abs() < steady => "→",
+            d if d > 0.0 => "↑",
+            _ => "↓",
+        }
+    }
+}
+
+/// What `get --trend` adds to the current conditions: their change since
+/// an earlier observation of the location.
+///
+/// # Fields
+/// - `since`: when the earlier observation was made,
+/// - `temp_c`: the change of the temperature in °C, if both were known,
+/// - `pressure_mb`: the change of the pressure in hPa, if both were known.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trend {
+    #[serde(serialize_with = "serialize_time")]
+    pub since: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_c: Option<Change>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure_mb: Option<Change>,
+}
+
+impl Trend {
+    /// The changes of `data` since `earlier`; `None` if neither the
+    /// temperature nor the pressure can be compared.
+    pub fn between(earlier: &HistoryRecord, data: &WeatherData) -> Option<Self> {
+        let point = &earlier.point;
+        let temp_c = Change::between(point.temp_c, data.temp_c);
+        let pressure_mb = Change::between(point.pressure_mb, data.pressure_mb);
+        if temp_c.is_none() && pressure_mb.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            since: point.time,
+            temp_c,
+            pressure_mb,
+        })
+    }
+
+    /// When the earlier observation was made, as seen at `now`, e.g.
+    /// `2 hours ago`, or `this time yesterday` for one 22 to 26 hours old.
+    pub fn ago(&self, now: DateTime<Utc>) -> String {
+        let secs = u64::try_from((now - self.since).num_seconds()).unwrap_or_default();
+        match secs {
+            79_200..=93_600 => "this time yesterday".into(),
+            secs => crate::cache::describe_age(secs),
+        }
+    }
+}
+
+/// The observation of `city` to compare with at `now`: the latest one at
+/// least `gap` seconds old with a temperature or a pressure. Cities are
+/// compared case-insensitively; `None` without such an observation.
+///
+/// Records are compared by their time, not their order, so a store
+/// appended to by several processes still finds the latest.
+pub fn baseline<'a>(
+    records: &'a [HistoryRecord],
+    city: &str,
+    now: DateTime<Utc>,
+    gap: u64,
+) -> Option<&'a HistoryRecord> {
+    let gap = i64::try_from(gap)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)?;
+    let latest = now.checked_sub_signed(gap)?;
+
+    records
+        .iter()
+        .filter(|record| record.point.city.eq_ignore_ascii_case(city))
+        .filter(|record| record.point.time <= latest)
+        .filter(|record| record.point.temp_c.is_some() || record.point.pressure_mb.is_some())
+        .max_by_key(|record| record.point.time)
+}
+
+/// Store of the observations of `get --trend`, in the data directory.
+///
+/// It is a [`HistoryStore`] of its own, kept whether or not `history` is
+/// on in the config, and cut to the latest [`MAX_OBSERVATIONS`].
+#[derive(Debug, Clone)]
+pub struct Observations {
+    store: HistoryStore,
+}
+
+impl Observations {
+    /// Opens the store kept in `path`; the file is created on first write.
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            store: HistoryStore::new(path),
+        }
+    }
+
+    /// Opens the store in the resolved data directory, see
+    /// [`Paths`](crate::paths::Paths).
+    pub fn open_default() -> Self {
+        Self::new(crate::paths::Paths::resolve().data.join(OBSERVATIONS_FILE))
+    }
+
+    /// File holding the observations.
+    pub fn path(&self) -> &std::path::Path {
+        self.store.path()
+    }
+
+    /// The stored observations, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the store cannot be read.
+    pub fn all(&self) -> io::Result<Vec<HistoryRecord>> {
+        self.store.list(&HistoryQuery::default(), Utc::now())
+    }
+
+    /// Adds `record`, then drops the oldest observations beyond
+    /// [`MAX_OBSERVATIONS`].
+    ///
+    /// # Errors
+    /// Returns an error if the store cannot be read or written.
+    pub fn add(&self, record: &HistoryRecord) -> io::Result<()> {
+        self.store.record(record)?;
+
+        let query = HistoryQuery {
+            limit: Some(MAX_OBSERVATIONS),
+            ..Default::default()
+        };
+        let kept = self.store.list(&query, Utc::now())?;
+        if kept.len() < MAX_OBSERVATIONS {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for record in &kept {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        crate::fsutil::write_atomic(self.path(), lines.as_bytes())
+    }
+}
+
+/// Minimum age set by [`use_trend`].
+static GAP: OnceLock<u64> = OnceLock::new();
+
+/// Makes `get` of this process compare the current conditions with an
+/// observation at least `gap` seconds old (`get --trend`); the first call
+/// wins.
+pub fn use_trend(gap: u64) {
+    let _ = GAP.set(gap);
+}
+
+/// The minimum age set with [`use_trend`]; `None` without `--trend`.
+pub fn gap() -> Option<u64> {
+    GAP.get().copied()
+}
+
+/// Reads a fetched `body` as [`WeatherData`] with its [`Trend`] since the
+/// observation of `city` chosen by [`baseline`], and stores the current
+/// observation in the default store for later queries.
+///
+/// Without an earlier observation the data has no trend. A body without
+/// weather data is `None`; a store that cannot be read or written is a
+/// warning on stderr, never an error.
+pub fn annotate(
+    provider: &dyn ApiProvider,
+    city: &str,
+    kind: &str,
+    body: &str,
+    now: DateTime<Utc>,
+    gap: u64,
+) -> Option<WeatherData> {
+    let mut data = crate::output::read(provider, kind, body)?;
+    if data.temp_c.is_none() && data.pressure_mb.is_none() {
+        return Some(data);
+    }
+
+    let store = Observations::open_default();
+    let warn = |e: io::Error| {
+        eprintln!(
+            "Warning: cannot use the observations in {}: {}",
+            store.path().display(),
+            e
+        )
+    };
+    match store.all() {
+        Ok(records) => {
+            data.trend = baseline(&records, city, now, gap)
+                .and_then(|earlier| Trend::between(earlier, &data))
+        }
+        Err(e) => warn(e),
+    }
+    let record = HistoryRecord::from_weather(provider.name(), city, kind, &data, now);
+    if let Err(e) = store.add(&record) {
+        warn(e);
+    }
+
+    Some(data)
+}
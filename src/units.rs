@@ -0,0 +1,300 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+/// Environment variable with the units of displayed values, below
+/// `--units` and above the `units` config option.
+pub const UNITS_ENV: &str = "WAPP_UNITS";
+
+/// Units of measurement of displayed values, the `--units` option.
+///
+/// Providers normalize to metric; text and table output convert from it.
+/// The names follow OpenWeatherMap's `units` parameter:
+/// - `metric`: °C and km/h,
+/// - `imperial`: °F and mph,
+/// - `standard`: K and m/s.
+///
+/// Pressures are in hPa and distances in km, or inHg and miles for
+/// imperial.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+    Standard,
+}
+
+impl Units {
+    /// The name of the units, as parsed.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Metric => "metric",
+            Self::Imperial => "imperial",
+            Self::Standard => "standard",
+        }
+    }
+
+    /// A temperature in °C in these units.
+    pub fn temp(self, celsius: f64) -> f64 {
+        match self {
+            Self::Metric => celsius,
+            Self::Imperial => c_to_f(celsius),
+            Self::Standard => c_to_k(celsius),
+        }
+    }
+
+    /// A temperature in these units in °C.
+    pub fn to_celsius(self, value: f64) -> f64 {
+        match self {
+            Self::Metric => value,
+            Self::Imperial => f_to_c(value),
+            Self::Standard => k_to_c(value),
+        }
+    }
+
+    /// A speed in km/h in these units.
+    pub fn speed(self, kph: f64) -> f64 {
+        self.wind_unit().speed(kph)
+    }
+
+    /// The unit of wind speeds of these units, unless `--wind-unit` picks
+    /// another.
+    pub fn wind_unit(self) -> WindUnit {
+        match self {
+            Self::Metric => WindUnit::Kph,
+            Self::Imperial => WindUnit::Mph,
+            Self::Standard => WindUnit::Ms,
+        }
+    }
+
+    /// A pressure in hPa in these units: inches of mercury for imperial.
+    pub fn pressure(self, hpa: f64) -> f64 {
+        match self {
+            Self::Imperial => hpa_to_inhg(hpa),
+            Self::Metric | Self::Standard => hpa,
+        }
+    }
+
+    /// A distance in km in these units: miles for imperial.
+    pub fn distance(self, km: f64) -> f64 {
+        match self {
+            Self::Imperial => km_to_miles(km),
+            Self::Metric | Self::Standard => km,
+        }
+    }
+
+    /// The symbol of temperatures, e.g. `°F`.
+    pub fn temp_symbol(self) -> &'static str {
+        match self {
+            Self::Metric => "°C",
+            Self::Imperial => "°F",
+            Self::Standard => "K",
+        }
+    }
+
+    /// The symbol of speeds, e.g. `mph`.
+    pub fn speed_symbol(self) -> &'static str {
+        self.wind_unit().symbol()
+    }
+
+    /// The symbol of pressures, e.g. `inHg`.
+    pub fn pressure_symbol(self) -> &'static str {
+        match self {
+            Self::Imperial => "inHg",
+            Self::Metric | Self::Standard => "hPa",
+        }
+    }
+
+    /// The symbol of distances, e.g. `mi`.
+    pub fn distance_symbol(self) -> &'static str {
+        match self {
+            Self::Imperial => "mi",
+            Self::Metric | Self::Standard => "km",
+        }
+    }
+}
+
+/// Unit of displayed wind speeds, the `--wind-unit` option; without it,
+/// that of the [`Units`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WindUnit {
+    #[value(name = "km/h", alias = "kph")]
+    Kph,
+    #[value(name = "m/s", alias = "ms")]
+    Ms,
+    Mph,
+    #[value(alias = "kn", alias = "kt")]
+    Knots,
+}
+
+impl WindUnit {
+    /// A speed in km/h in this unit.
+    pub fn speed(self, kph: f64) -> f64 {
+        match self {
+            Self::Kph => kph,
+            Self::Ms => kph_to_ms(kph),
+            Self::Mph => kph_to_mph(kph),
+            Self::Knots => kph_to_knots(kph),
+        }
+    }
+
+    /// The symbol of the unit, e.g. `kn`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Kph => "km/h",
+            Self::Ms => "m/s",
+            Self::Mph => "mph",
+            Self::Knots => "kn",
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Units {
+    type Err = anyhow::Error;
+
+    /// Parses units as [`parse_units`](crate::config::parse_units) does.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match crate::config::parse_units(s)?.as_str() {
+            "imperial" => Self::Imperial,
+            "standard" => Self::Standard,
+            _ => Self::Metric,
+        })
+    }
+}
+
+/// Degrees Celsius in Fahrenheit.
+pub fn c_to_f(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+/// Degrees Fahrenheit in Celsius.
+pub fn f_to_c(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// Degrees Celsius in kelvin.
+pub fn c_to_k(celsius: f64) -> f64 {
+    celsius + 273.15
+}
+
+/// Kelvin in degrees Celsius.
+pub fn k_to_c(kelvin: f64) -> f64 {
+    kelvin - 273.15
+}
+
+/// Kilometres per hour in miles per hour.
+pub fn kph_to_mph(kph: f64) -> f64 {
+    kph / 1.609344
+}
+
+/// Miles per hour in kilometres per hour.
+pub fn mph_to_kph(mph: f64) -> f64 {
+    mph * 1.609344
+}
+
+/// Kilometres per hour in metres per second.
+pub fn kph_to_ms(kph: f64) -> f64 {
+    kph / 3.6
+}
+
+/// Metres per second in kilometres per hour.
+pub fn ms_to_kph(ms: f64) -> f64 {
+    ms * 3.6
+}
+
+/// Kilometres per hour in knots.
+pub fn kph_to_knots(kph: f64) -> f64 {
+    kph / 1.852
+}
+
+/// Knots in kilometres per hour.
+pub fn knots_to_kph(knots: f64) -> f64 {
+    knots * 1.852
+}
+
+/// Hectopascals (millibars) in inches of mercury.
+pub fn hpa_to_inhg(hpa: f64) -> f64 {
+    hpa / 33.8639
+}
+
+/// Kilometres in miles.
+pub fn km_to_miles(km: f64) -> f64 {
+    km / 1.609344
+}
+
+/// Sixteen-point compass direction of a bearing in degrees, e.g. `320` →
+/// `NW`. Each point covers 22.5°, from 11.25° before it up to, not
+/// including, 11.25° after it: 348.75° is `N`, 11.25° `NNE`.
+pub fn compass_point(degree: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+
+    let index = (degree.rem_euclid(360.0) / 22.5).round() as usize % POINTS.len();
+    POINTS[index]
+}
+
+/// `value` rounded to `decimals` places, halves away from zero: 70.25
+/// becomes 70.3 and -0.05 becomes -0.1, where formatting alone would round
+/// to even.
+pub fn round(value: f64, decimals: i32) -> f64 {
+    let scale = 10f64.powi(decimals);
+    (value * scale).round() / scale
+}
+
+/// The units of a call, from, in order: `flag` (`get --units`), `env` (the
+/// value of [`UNITS_ENV`]; empty counts as unset), or the `saved` config
+/// option; metric without any.
+///
+/// # Errors
+/// Returns an error if any of them is not `metric`, `imperial` or
+/// `standard`.
+pub fn units_for(
+    flag: Option<&str>,
+    env: Option<&str>,
+    saved: Option<&str>,
+) -> anyhow::Result<Units> {
+    let env = env.map(str::trim).filter(|value| !value.is_empty());
+
+    match flag.or(env).or(saved) {
+        Some(units) => units.parse(),
+        None => Ok(Units::default()),
+    }
+}
+
+/// Units set by [`use_units`].
+static UNITS: OnceLock<Units> = OnceLock::new();
+
+/// Makes the text and table output of this process show `units`; the first
+/// call wins. Without a call, values are metric.
+pub fn use_units(units: Units) {
+    let _ = UNITS.set(units);
+}
+
+/// The units set with [`use_units`], else metric.
+pub fn units() -> Units {
+    UNITS.get().copied().unwrap_or_default()
+}
+
+/// Wind unit set by [`use_wind_unit`].
+static WIND_UNIT: OnceLock<WindUnit> = OnceLock::new();
+
+/// Makes the text and table output of this process show wind speeds in
+/// `unit`, whatever the units; the first call wins.
+pub fn use_wind_unit(unit: WindUnit) {
+    let _ = WIND_UNIT.set(unit);
+}
+
+/// The unit set with [`use_wind_unit`], else the wind unit of `units`.
+pub fn wind_unit(units: Units) -> WindUnit {
+    WIND_UNIT.get().copied().unwrap_or(units.wind_unit())
+}
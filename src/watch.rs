@@ -0,0 +1,140 @@
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use tokio_util::sync::CancellationToken;
+
+use crate::providers::credentials::redact_secrets;
+
+/// Shortest `get --watch` interval accepted without `--force`, so a watch
+/// left running does not use up a provider's request quota.
+pub const MIN_INTERVAL_SECS: u64 = 30;
+
+/// Clears the terminal and moves the cursor to its top-left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Settings for a watch run.
+///
+/// # Fields
+/// - `interval`: delay between refreshes,
+/// - `clear`: clear the screen before every frame; otherwise frames are
+///   appended, separated by a blank line (stdout is not a terminal).
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub interval: Duration,
+    pub clear: bool,
+}
+
+/// The refresh interval of `get --watch <SECS>`.
+///
+/// # Errors
+/// Returns an error for an interval below [`MIN_INTERVAL_SECS`] unless
+/// `force` is set.
+pub fn interval(secs: u64, force: bool) -> anyhow::Result<Duration> {
+    if secs < MIN_INTERVAL_SECS && !force {
+        return Err(anyhow::anyhow!(
+            "--watch {} is below the minimum of {} seconds; pass --force to refresh more often",
+            secs,
+            MIN_INTERVAL_SECS
+        ));
+    }
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Formats one frame: a "Last updated" line above the output of the last
+/// successful refresh. When the latest refresh failed (`failure`), a
+/// warning that the data is stale comes first.
+pub fn render_frame(body: &str, updated: DateTime<Local>, failure: Option<&str>) -> String {
+    let mut frame = String::new();
+    if let Some(error) = failure {
+        frame.push_str(&format!(
+            "Warning: refresh failed: {}; the data below is stale\n",
+            error
+        ));
+    }
+    frame.push_str(&format!(
+        "Last updated: {}\n\n{}",
+        updated.format("%Y-%m-%d %H:%M:%S"),
+        body
+    ));
+    frame
+}
+
+/// Runs `refresh` every `interval` and writes each result to `out` as a
+/// frame, see [`render_frame`], until `shutdown` is cancelled.
+///
+/// A failed refresh keeps the previous output on screen with a stale
+/// warning and is retried on the next tick. Cancelling `shutdown` also
+/// cancels a refresh in progress.
+///
+/// # Errors
+/// Returns the error of the first refresh if it fails for another reason
+/// than an unavailable provider (see
+/// [`is_unavailable`](crate::providers::is_unavailable)), e.g. a missing
+/// API key, [`RuleExit`](crate::rules::RuleExit) when an `exit-code` rule
+/// fires, or an error writing to `out`.
+pub async fn run<F, Fut>(
+    options: &WatchOptions,
+    shutdown: &CancellationToken,
+    out: &mut dyn Write,
+    mut refresh: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<Vec<u8>>>,
+{
+    let mut last: Option<(String, DateTime<Local>)> = None;
+    let mut first = true;
+
+    loop {
+        let result = tokio::select! {
+            result = refresh() => result,
+            _ = shutdown.cancelled() => {
+                tracing::info!("refresh cancelled by shutdown");
+                break;
+            }
+        };
+
+        let frame = match result {
+            Ok(body) => {
+                let body = String::from_utf8_lossy(&body).into_owned();
+                let updated = Local::now();
+                let frame = render_frame(&body, updated, None);
+                last = Some((body, updated));
+                frame
+            }
+            Err(e) if e.is::<crate::rules::RuleExit>() => return Err(e),
+            Err(e) if last.is_none() && !crate::providers::is_unavailable(&e) => return Err(e),
+            Err(e) => {
+                let error = redact_secrets(&format!("{:#}", e));
+                tracing::warn!(error = %error, "refresh failed");
+                match &last {
+                    Some((body, updated)) => render_frame(body, *updated, Some(&error)),
+                    None => format!(
+                        "Warning: refresh failed: {}; retrying in {}s\n",
+                        error,
+                        options.interval.as_secs()
+                    ),
+                }
+            }
+        };
+
+        if options.clear {
+            out.write_all(CLEAR_SCREEN.as_bytes())?;
+        } else if !first {
+            out.write_all(b"\n")?;
+        }
+        out.write_all(frame.as_bytes())?;
+        out.flush()?;
+        first = false;
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(options.interval) => {}
+        }
+    }
+
+    Ok(())
+}
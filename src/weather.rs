@@ -0,0 +1,533 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::Serialize;
+
+use crate::advice::Advice;
+use crate::aqi::AirQuality;
+use crate::condition::Condition;
+use crate::providers::ApiProvider;
+use crate::series::{serialize_date, serialize_time, DailySummary, SeriesPoint};
+use crate::timezone::Zone;
+use crate::trend::Trend;
+use crate::weekend::Weekend;
+
+/// Weather data in a provider-independent shape, in metric units.
+///
+/// Every provider response maps onto this struct, so consumers no longer
+/// need to know each provider's JSON. Values the provider does not report
+/// are `None`; a response without a forecast has an empty `forecast`.
+///
+/// # Fields
+/// - `temp_c`, `feels_like_c`: current temperatures in °C,
+/// - `humidity`: relative humidity in %,
+/// - `wind_kph`: wind speed in km/h,
+/// - `wind_degree`: direction the wind blows from, in degrees (0 = north),
+/// - `gust_kph`: speed of wind gusts in km/h, omitted from JSON where the
+///   provider reports none,
+/// - `uv_index`: the UV index, `None` where the provider or the kind of
+///   data has none, omitted from JSON then,
+/// - `uv_category`: its exposure category, see [`UvCategory`],
+/// - `pressure_mb`: air pressure in hPa (millibars),
+/// - `visibility_km`: visibility in km,
+/// - `dew_point_c`: dew point in °C, the provider's, else computed from
+///   the temperature and humidity, see [`dew_point`],
+/// - `cloud`: cloud cover in %,
+/// - `condition`: provider's condition text,
+/// - `condition_kind`: the condition's category, from the provider's
+///   condition code where known, else from the text,
+/// - `forecast`: one summary per forecast day,
+/// - `hourly`: the entries of an `hourly` response, omitted from JSON for
+///   other kinds,
+/// - `alerts`: the warnings of an `alerts` response, most severe first;
+///   `None`, and omitted from JSON, for other kinds,
+/// - `air`: the report of an `air` response; `None`, and omitted from
+///   JSON, for other kinds,
+/// - `astro`: the sun and moon of an `astro` response; `None`, and omitted
+///   from JSON, for other kinds,
+/// - `marine`: the days of a `marine` response; `None`, and omitted from
+///   JSON, for other kinds,
+/// - `snow`: the days of a `snow` response; `None`, and omitted from JSON,
+///   for other kinds,
+/// - `weekend`: the verdict of a `weekend` response; `None`, and omitted
+///   from JSON, for other kinds,
+/// - `trend`: the change of the temperature and pressure since an earlier
+///   query with `get --trend`; `None`, and omitted from JSON, without an
+///   earlier one, see [`annotate`](crate::trend::annotate),
+/// - `advice`: the advice rules that hold with `get --advice`; `None`,
+///   and omitted from JSON, without it, see
+///   [`AdviceEngine`](crate::advice::AdviceEngine),
+/// - `updated`: when the current conditions were observed,
+/// - `timezone`: the location's time zone, in which text and table output
+///   show times, see [`TimeDisplay`](crate::timezone::TimeDisplay).
+///
+/// `pressure_mb` through `cloud`, `updated`, and `timezone` are omitted
+/// from JSON where unknown.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WeatherData {
+    pub temp_c: Option<f64>,
+    pub feels_like_c: Option<f64>,
+    pub humidity: Option<f64>,
+    pub wind_kph: Option<f64>,
+    pub wind_degree: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gust_kph: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_index: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv_category: Option<UvCategory>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure_mb: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility_km: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dew_point_c: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud: Option<f64>,
+    pub condition: Option<String>,
+    pub condition_kind: Option<Condition>,
+    pub forecast: Vec<DailySummary>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hourly: Vec<HourlyForecast>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<Alert>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub air: Option<AirQuality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub astro: Option<Astronomy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub marine: Option<Vec<MarineDay>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snow: Option<Vec<SnowDay>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekend: Option<Weekend>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend: Option<Trend>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advice: Option<Vec<Advice>>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_opt_time"
+    )]
+    pub updated: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Zone>,
+}
+
+/// One entry of an hourly forecast, in metric units.
+///
+/// # Fields
+/// - `time`: start of the hour, or of the provider's longer step,
+/// - `temp_c`: temperature in °C,
+/// - `precip_chance`: chance of precipitation in %,
+/// - `precip_mm`: precipitation over the step in mm,
+/// - `condition`: provider's condition text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HourlyForecast {
+    #[serde(serialize_with = "serialize_time")]
+    pub time: DateTime<Utc>,
+    pub temp_c: Option<f64>,
+    pub precip_chance: Option<f64>,
+    pub precip_mm: Option<f64>,
+    pub condition: Option<String>,
+}
+
+impl From<&SeriesPoint> for HourlyForecast {
+    fn from(point: &SeriesPoint) -> Self {
+        Self {
+            time: point.time,
+            temp_c: point.temp_c,
+            precip_chance: point.precip_chance,
+            precip_mm: point.precip_mm,
+            condition: point.condition.clone(),
+        }
+    }
+}
+
+/// A severe weather warning issued for the location.
+///
+/// # Fields
+/// - `event`: what is warned of, e.g. `Flood Warning`,
+/// - `severity`: how severe it is, see [`Severity`],
+/// - `headline`: the issuer's one-line summary,
+/// - `starts`, `ends`: when the warning is in effect,
+/// - `description`: the issuer's full text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub event: String,
+    pub severity: Severity,
+    pub headline: Option<String>,
+    #[serde(serialize_with = "serialize_opt_time")]
+    pub starts: Option<DateTime<Utc>>,
+    #[serde(serialize_with = "serialize_opt_time")]
+    pub ends: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+}
+
+/// Severity of an [`Alert`], from least to most severe, as in the Common
+/// Alerting Protocol; `unknown` when the provider does not grade it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Unknown,
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
+}
+
+impl Severity {
+    /// Reads a CAP severity, ignoring case; anything else is `Unknown`.
+    pub fn from_text(text: &str) -> Self {
+        match text.trim().to_lowercase().as_str() {
+            "minor" => Self::Minor,
+            "moderate" => Self::Moderate,
+            "severe" => Self::Severe,
+            "extreme" => Self::Extreme,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The name shown in text output, e.g. `Severe`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Unknown => "Unknown",
+            Self::Minor => "Minor",
+            Self::Moderate => "Moderate",
+            Self::Severe => "Severe",
+            Self::Extreme => "Extreme",
+        }
+    }
+}
+
+/// Orders `alerts` most severe first, then by start time.
+pub fn sort_alerts(alerts: &mut [Alert]) {
+    alerts.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.starts.cmp(&b.starts))
+    });
+}
+
+fn serialize_opt_time<S>(time: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match time {
+        Some(time) => serialize_time(time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Exposure category of a UV index, on the WHO scale: low up to 2,
+/// moderate 3 to 5, high 6 and 7, very high 8 to 10, extreme from 11.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UvCategory {
+    Low,
+    Moderate,
+    High,
+    VeryHigh,
+    Extreme,
+}
+
+impl UvCategory {
+    /// The category of `index`, which is rounded to a whole number first
+    /// as the scale is, so 2.6 is already moderate.
+    pub fn from_index(index: f64) -> Self {
+        match index.round() {
+            i if i < 3.0 => Self::Low,
+            i if i < 6.0 => Self::Moderate,
+            i if i < 8.0 => Self::High,
+            i if i < 11.0 => Self::VeryHigh,
+            _ => Self::Extreme,
+        }
+    }
+
+    /// The name shown in text output, e.g. `Very High`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Low => "Low",
+            Self::Moderate => "Moderate",
+            Self::High => "High",
+            Self::VeryHigh => "Very High",
+            Self::Extreme => "Extreme",
+        }
+    }
+}
+
+/// Sun and moon times of one day, in the location's local time.
+///
+/// Providers report different parts; what one does not report is `None`,
+/// as are rise and set times that do not happen that day.
+///
+/// # Fields
+/// - `date`: the day, in the location's time zone,
+/// - `timezone`: the location's time zone, an IANA name such as
+///   `Europe/Kyiv` or an offset such as `UTC+03:00`,
+/// - `sunrise`, `sunset`, `moonrise`, `moonset`: local times, `HH:MM` in
+///   JSON,
+/// - `day_length_min`: minutes from sunrise to sunset,
+/// - `moon_phase`: the phase name, e.g. `Waxing Gibbous`,
+/// - `moon_illumination`: the lit part of the moon in %.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Astronomy {
+    #[serde(serialize_with = "serialize_opt_date")]
+    pub date: Option<NaiveDate>,
+    pub timezone: Option<String>,
+    #[serde(serialize_with = "serialize_opt_clock")]
+    pub sunrise: Option<NaiveTime>,
+    #[serde(serialize_with = "serialize_opt_clock")]
+    pub sunset: Option<NaiveTime>,
+    pub day_length_min: Option<i64>,
+    #[serde(serialize_with = "serialize_opt_clock")]
+    pub moonrise: Option<NaiveTime>,
+    #[serde(serialize_with = "serialize_opt_clock")]
+    pub moonset: Option<NaiveTime>,
+    pub moon_phase: Option<String>,
+    pub moon_illumination: Option<f64>,
+}
+
+/// Minutes from `sunrise` to `sunset`; `None` without either, or if the
+/// sun sets before it rises, as in a polar day.
+pub fn day_length(sunrise: Option<NaiveTime>, sunset: Option<NaiveTime>) -> Option<i64> {
+    let minutes = (sunset? - sunrise?).num_minutes();
+    (minutes > 0).then_some(minutes)
+}
+
+/// The name and lit % of a moon phase given as the fraction of the lunar
+/// month, as One Call does: 0 and 1 are a new moon, 0.25 the first
+/// quarter, 0.5 a full moon, 0.75 the last quarter.
+pub fn moon_phase(fraction: f64) -> (&'static str, f64) {
+    const NEAR: f64 = 0.02;
+    let name = match fraction.rem_euclid(1.0) {
+        f if !(NEAR..1.0 - NEAR).contains(&f) => "New Moon",
+        f if (f - 0.25).abs() < NEAR => "First Quarter",
+        f if (f - 0.5).abs() < NEAR => "Full Moon",
+        f if (f - 0.75).abs() < NEAR => "Last Quarter",
+        f if f < 0.25 => "Waxing Crescent",
+        f if f < 0.5 => "Waxing Gibbous",
+        f if f < 0.75 => "Waning Gibbous",
+        _ => "Waning Crescent",
+    };
+    let lit = (1.0 - (fraction * std::f64::consts::TAU).cos()) / 2.0 * 100.0;
+
+    (name, lit.round())
+}
+
+/// One day of a marine forecast, in the location's local time and metric
+/// units.
+///
+/// # Fields
+/// - `date`: the day,
+/// - `max_wave_m`: the highest significant wave height of its hours, in m,
+/// - `max_swell_m`: the highest swell of its hours, in m,
+/// - `water_temp_c`: the mean water temperature of its hours, in °C,
+/// - `tides`: high and low tides, in order,
+/// - `hours`: the sea hour by hour, see [`MarineHour`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarineDay {
+    #[serde(serialize_with = "serialize_date")]
+    pub date: NaiveDate,
+    pub max_wave_m: Option<f64>,
+    pub max_swell_m: Option<f64>,
+    pub water_temp_c: Option<f64>,
+    pub tides: Vec<Tide>,
+    pub hours: Vec<MarineHour>,
+}
+
+impl MarineDay {
+    /// The day of `hours` and `tides`, with its highest waves and swell and
+    /// mean water temperature; tides are sorted by time.
+    pub fn new(date: NaiveDate, hours: Vec<MarineHour>, mut tides: Vec<Tide>) -> Self {
+        let max =
+            |value: fn(&MarineHour) -> Option<f64>| hours.iter().filter_map(value).reduce(f64::max);
+        let water: Vec<f64> = hours.iter().filter_map(|h| h.water_temp_c).collect();
+        tides.sort_by_key(|tide| tide.time);
+
+        Self {
+            date,
+            max_wave_m: max(|h| h.wave_m),
+            max_swell_m: max(|h| h.swell_m),
+            water_temp_c: (!water.is_empty())
+                .then(|| water.iter().sum::<f64>() / water.len() as f64),
+            tides,
+            hours,
+        }
+    }
+}
+
+/// One hour of a marine forecast.
+///
+/// # Fields
+/// - `time`: start of the hour in the location's local time,
+///   `YYYY-MM-DD HH:MM` in JSON,
+/// - `wave_m`: significant wave height in m,
+/// - `swell_m`: swell height in m,
+/// - `swell_degree`: direction the swell comes from, in degrees,
+/// - `swell_period_s`: swell period in seconds,
+/// - `water_temp_c`: water temperature in °C.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarineHour {
+    #[serde(serialize_with = "serialize_local")]
+    pub time: NaiveDateTime,
+    pub wave_m: Option<f64>,
+    pub swell_m: Option<f64>,
+    pub swell_degree: Option<f64>,
+    pub swell_period_s: Option<f64>,
+    pub water_temp_c: Option<f64>,
+}
+
+/// A high or low tide, at a local time, with its height in m where known.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tide {
+    #[serde(serialize_with = "serialize_local")]
+    pub time: NaiveDateTime,
+    pub kind: TideKind,
+    pub height_m: Option<f64>,
+}
+
+/// Whether a [`Tide`] is high or low water.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TideKind {
+    High,
+    Low,
+}
+
+impl TideKind {
+    /// The name shown in text output, e.g. `High tide`.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::High => "High tide",
+            Self::Low => "Low tide",
+        }
+    }
+}
+
+/// One day of a snow forecast, in metric units.
+///
+/// # Fields
+/// - `date`: the day, in the location's local time,
+/// - `snow_cm`: the day's total snowfall, in cm,
+/// - `snow_chance`: the day's chance of snow, in %,
+/// - `will_snow`: whether the provider expects snow on the day,
+/// - `night_low_c`: the lowest temperature of the night into the day, from
+///   midnight to 06:00 local time, in °C,
+/// - `min_c`: the day's lowest temperature, in °C,
+/// - `max_wind_kph`: the day's highest wind speed, in km/h,
+/// - `condition`: the day's condition text.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnowDay {
+    #[serde(serialize_with = "serialize_date")]
+    pub date: NaiveDate,
+    pub snow_cm: Option<f64>,
+    pub snow_chance: Option<f64>,
+    pub will_snow: Option<bool>,
+    pub night_low_c: Option<f64>,
+    pub min_c: Option<f64>,
+    pub max_wind_kph: Option<f64>,
+    pub condition: Option<String>,
+}
+
+/// Last hour, exclusive, of the night a [`SnowDay::night_low_c`] is read
+/// from.
+pub const NIGHT_END_HOUR: u32 = 6;
+
+fn serialize_local<S>(time: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&time.format("%Y-%m-%d %H:%M").to_string())
+}
+
+fn serialize_opt_clock<S>(time: &Option<NaiveTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match time {
+        Some(time) => serializer.serialize_str(&time.format("%H:%M").to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_opt_date<S>(date: &Option<NaiveDate>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match date {
+        Some(date) => serializer.serialize_str(&date.format("%Y-%m-%d").to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl WeatherData {
+    /// Sets the UV index and its category; `None` clears both.
+    pub fn set_uv(&mut self, index: Option<f64>) {
+        self.uv_index = index;
+        self.uv_category = index.map(UvCategory::from_index);
+    }
+
+    /// Pretty-printed JSON of the data.
+    pub fn to_json(&self) -> String {
+        // Serializing a struct of strings and numbers cannot fail.
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Builds [`WeatherData`] from a provider's [`current`](ApiProvider::current)
+/// and [`daily`](ApiProvider::daily) views of `body`.
+///
+/// The views carry no wind direction, visibility, or condition code, so
+/// `wind_degree` and `visibility_km` are left empty, `condition_kind` is
+/// read from the condition text, and `dew_point_c` is computed with
+/// [`dew_point`]; `updated` is the time of the current conditions, and
+/// `timezone` the provider's [`timezone`](ApiProvider::timezone) of `body`.
+/// Returns `None` if `body` holds neither current conditions nor a forecast.
+pub fn from_views<P: ApiProvider + ?Sized>(provider: &P, body: &str) -> Option<WeatherData> {
+    let current = provider.current(body);
+    let forecast = provider.daily(body);
+
+    if current.is_none() && forecast.is_none() {
+        return None;
+    }
+
+    let mut data = WeatherData {
+        forecast: forecast.unwrap_or_default(),
+        timezone: provider.timezone(body),
+        ..Default::default()
+    };
+
+    if let Some(point) = current {
+        data.updated = Some(point.time);
+        data.temp_c = point.temp_c;
+        data.feels_like_c = point.feels_like_c;
+        data.humidity = point.humidity;
+        data.wind_kph = point.wind_kph;
+        data.pressure_mb = point.pressure_mb;
+        data.cloud = point.cloud;
+        data.dew_point_c = point
+            .temp_c
+            .zip(point.humidity)
+            .and_then(|(t, h)| dew_point(t, h));
+        data.condition_kind = point.condition.as_deref().map(Condition::from_text);
+        data.condition = point.condition;
+    }
+
+    Some(data)
+}
+
+/// The dew point in °C of air at `temp_c` with `humidity` % relative
+/// humidity, by the Magnus formula with the coefficients of Sonntag (1990),
+/// within 0.4 °C between -45 and 60 °C: 20 °C at 50% is 9.3 °C.
+///
+/// `None` for a humidity of 0% or less, which has no dew point.
+pub fn dew_point(temp_c: f64, humidity: f64) -> Option<f64> {
+    const A: f64 = 17.62;
+    const B: f64 = 243.12;
+
+    if humidity <= 0.0 {
+        return None;
+    }
+    let gamma = (humidity / 100.0).ln() + A * temp_c / (B + temp_c);
+    Some(B * gamma / (A - gamma))
+}
@@ -0,0 +1,142 @@
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::providers::ApiProvider;
+
+/// Where `get --webhook` posts the weather.
+///
+/// # Fields
+/// - `url`: URL the JSON is posted to,
+/// - `headers`: extra request headers, e.g. `Authorization`,
+/// - `strict`: a failed post fails the command, instead of a warning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Webhook {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub strict: bool,
+}
+
+/// Parses a `--webhook-header` such as `Authorization: Bearer TOKEN`.
+///
+/// # Errors
+/// Returns an error without a colon, or for an empty or invalid name.
+pub fn parse_header(s: &str) -> Result<(String, String), String> {
+    let invalid = || {
+        format!(
+            "invalid header '{}'; expected e.g. \"Authorization: Bearer TOKEN\"",
+            s
+        )
+    };
+
+    let (name, value) = s.split_once(':').ok_or_else(invalid)?;
+    let name = name.trim();
+    reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+    reqwest::header::HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+
+    Ok((name.to_string(), value.trim().to_string()))
+}
+
+/// The body posted to a webhook: the normalized weather `json` (see
+/// [`normalized_json`](crate::output::normalized_json)) with the
+/// `provider` that served it, the `location`, the data `kind`, and the
+/// time it was `fetched_at` added.
+///
+/// # Errors
+/// Returns an error if `json` is not a JSON object.
+pub fn payload(
+    json: &str,
+    provider: &str,
+    location: &str,
+    kind: &str,
+    fetched_at: DateTime<Utc>,
+) -> anyhow::Result<Value> {
+    let mut object = match serde_json::from_str(json)? {
+        Value::Object(object) => object,
+        _ => return Err(anyhow!("the weather data is not a JSON object")),
+    };
+
+    object.insert("provider".into(), provider.into());
+    object.insert("location".into(), location.into());
+    object.insert("kind".into(), kind.into());
+    object.insert(
+        "fetched_at".into(),
+        fetched_at.format("%Y-%m-%dT%H:%M:%SZ").to_string().into(),
+    );
+
+    Ok(Value::Object(object))
+}
+
+/// Posts `payload` to the webhook with the shared [`client`](crate::http::client),
+/// so the timeouts, proxy, and certificates of provider requests apply.
+///
+/// # Errors
+/// Returns an error if the request fails or the webhook does not answer
+/// with a 2xx status.
+pub async fn post(webhook: &Webhook, payload: &Value) -> anyhow::Result<()> {
+    let mut request = crate::http::client()?.post(&webhook.url).json(payload);
+    for (name, value) in &webhook.headers {
+        request = request.header(name, value);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| anyhow!("webhook request failed: {}", e.without_url()))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(anyhow!("webhook answered {}", status));
+    }
+
+    tracing::info!(status = status.as_u16(), "webhook posted");
+    Ok(())
+}
+
+/// Webhook set by [`use_webhook`].
+static WEBHOOK: OnceLock<Webhook> = OnceLock::new();
+
+/// Makes every successful `get` fetch of this process post its weather to
+/// `webhook`; the first call wins.
+pub fn use_webhook(webhook: Webhook) {
+    let _ = WEBHOOK.set(webhook);
+}
+
+/// The webhook set with [`use_webhook`], if any.
+pub fn webhook() -> Option<&'static Webhook> {
+    WEBHOOK.get()
+}
+
+/// Posts the weather of a fetched `body` to the webhook set with
+/// [`use_webhook`], if any, see [`payload`].
+///
+/// # Errors
+/// With [`strict`](Webhook::strict), returns an error if the body cannot
+/// be read or the post fails; otherwise a failure is a warning on stderr.
+pub async fn send(
+    provider: &dyn ApiProvider,
+    location: &str,
+    kind: &str,
+    body: &str,
+) -> anyhow::Result<()> {
+    let Some(webhook) = webhook() else {
+        return Ok(());
+    };
+
+    let result = match crate::output::normalized_json(provider, kind, body)
+        .and_then(|json| payload(&json, provider.name(), location, kind, Utc::now()))
+    {
+        Ok(payload) => post(webhook, &payload).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Err(e) if webhook.strict => Err(e),
+        Err(e) => {
+            eprintln!("Warning: {}", e);
+            Ok(())
+        }
+        Ok(()) => Ok(()),
+    }
+}
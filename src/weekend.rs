@@ -0,0 +1,255 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Timelike, Utc, Weekday};
+use serde::Serialize;
+
+use crate::request::DateWindow;
+use crate::series::{DailySummary, SeriesPoint};
+use crate::timezone::Zone;
+use crate::units::Units;
+use crate::weather::WeatherData;
+
+/// Chance of precipitation in %, from which rain is likely.
+pub const RAIN_LIKELY: f64 = 60.0;
+
+/// Chance of precipitation in %, from which showers are possible.
+pub const SHOWERS_POSSIBLE: f64 = 30.0;
+
+/// Precipitation in mm from which showers are possible where the provider
+/// gives no chance of it.
+pub const WET_DAY_MM: f64 = 1.0;
+
+/// Highest temperature in °C from which a dry weekend is hot.
+pub const HOT_C: f64 = 30.0;
+
+/// Highest temperature in °C below which a dry weekend is cold.
+pub const COLD_C: f64 = 10.0;
+
+/// What a `weekend` response adds to its days.
+///
+/// # Fields
+/// - `under_way`: whether the weekend had begun in the location's time
+///   zone, so only what is left of it is covered,
+/// - `verdict`: one line on the weekend, e.g. `Rain likely Sunday
+///   afternoon` or `Good for hiking: dry, 14–22 °C`, see [`verdict`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Weekend {
+    pub under_way: bool,
+    pub verdict: String,
+}
+
+/// A quarter of a day, by the hour of the wall clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DayPart {
+    /// 00:00 to 06:00.
+    Night,
+    /// 06:00 to 12:00.
+    Morning,
+    /// 12:00 to 18:00.
+    Afternoon,
+    /// 18:00 to midnight.
+    Evening,
+}
+
+impl DayPart {
+    /// The part of the day `hour` falls in.
+    pub fn of(hour: u32) -> Self {
+        match hour {
+            0..=5 => Self::Night,
+            6..=11 => Self::Morning,
+            12..=17 => Self::Afternoon,
+            _ => Self::Evening,
+        }
+    }
+
+    /// The name in a verdict, e.g. `afternoon`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Night => "night",
+            Self::Morning => "morning",
+            Self::Afternoon => "afternoon",
+            Self::Evening => "evening",
+        }
+    }
+}
+
+/// The date at `now` in `zone`, the location's, else in the system's time
+/// zone.
+pub fn today(now: DateTime<Utc>, zone: Option<Zone>) -> NaiveDate {
+    match zone {
+        Some(zone) => zone.local(now).date(),
+        None => now.with_timezone(&Local).date_naive(),
+    }
+}
+
+/// The weekend of `today`, and whether it is under way: on Saturday and
+/// Sunday what is left of it, else the coming Saturday and Sunday.
+pub fn window(today: NaiveDate) -> (DateWindow, bool) {
+    let days = |from: i64, to: i64| DateWindow {
+        from: today + Duration::days(from),
+        to: today + Duration::days(to),
+    };
+
+    match today.weekday() {
+        Weekday::Sat => (days(0, 1), true),
+        Weekday::Sun => (days(0, 0), true),
+        weekday => {
+            let saturday = 5 - i64::from(weekday.num_days_from_monday());
+            (days(saturday, saturday + 1), false)
+        }
+    }
+}
+
+/// The forecast length that covers the weekend wherever it is `now`.
+///
+/// The location's time zone is not known before the response, and its
+/// date can be a day behind or ahead of UTC: the length counts from the
+/// earliest date, through the end of the weekend of the latest.
+pub fn fetch_days(now: DateTime<Utc>) -> u32 {
+    let earliest = (now - Duration::hours(12)).date_naive();
+    let latest = (now + Duration::hours(14)).date_naive();
+    let (weekend, _) = window(latest);
+
+    (weekend.to - earliest).num_days() as u32 + 1
+}
+
+/// Cuts the forecast of `data` to the weekend at `now`, in the location's
+/// time zone, and adds its [`Weekend`] with a verdict from the days and
+/// the `hours` still ahead, in `units`.
+///
+/// Current conditions are left out.
+pub fn summarize(
+    data: WeatherData,
+    hours: &[SeriesPoint],
+    now: DateTime<Utc>,
+    units: Units,
+) -> WeatherData {
+    let (window, under_way) = window(today(now, data.timezone));
+    let days: Vec<DailySummary> = data
+        .forecast
+        .into_iter()
+        .filter(|day| window.contains(day.date))
+        .collect();
+    let hours: Vec<SeriesPoint> = hours
+        .iter()
+        .filter(|point| point.time + Duration::hours(1) > now)
+        .cloned()
+        .collect();
+
+    WeatherData {
+        weekend: Some(Weekend {
+            under_way,
+            verdict: verdict(&days, &hours, data.timezone, units),
+        }),
+        forecast: days,
+        timezone: data.timezone,
+        ..Default::default()
+    }
+}
+
+/// One line on the weekend of `days`, temperatures in `units`:
+/// - `Rain likely Saturday and Sunday afternoon` for a chance of
+///   precipitation of [`RAIN_LIKELY`] or more,
+/// - `Showers possible Sunday` for [`SHOWERS_POSSIBLE`] or more,
+/// - `Dry and hot: up to 32 °C` from [`HOT_C`],
+/// - `Dry but cold: 2–8 °C` below [`COLD_C`],
+/// - `Good for hiking: dry, 14–22 °C` otherwise.
+///
+/// The parts of a day are read from the `hours` of the day in `zone`;
+/// without them the verdict names the whole day by its chance, and
+/// [`WET_DAY_MM`] or more makes showers possible on a day without one.
+pub fn verdict(
+    days: &[DailySummary],
+    hours: &[SeriesPoint],
+    zone: Option<Zone>,
+    units: Units,
+) -> String {
+    if days.is_empty() {
+        return "No forecast for the weekend yet".to_string();
+    }
+
+    for (threshold, label) in [
+        (RAIN_LIKELY, "Rain likely"),
+        (SHOWERS_POSSIBLE, "Showers possible"),
+    ] {
+        let wet: Vec<String> = days
+            .iter()
+            .filter_map(|day| wet_parts(day, hours, zone, threshold))
+            .collect();
+        if !wet.is_empty() {
+            return format!("{} {}", label, wet.join(" and "));
+        }
+    }
+
+    let low = days.iter().filter_map(|day| day.min_c).reduce(f64::min);
+    let high = days.iter().filter_map(|day| day.max_c).reduce(f64::max);
+    // Rounded first, so that -0.4 is 0 rather than -0
+    let temp = |c: f64| format!("{:.0}", units.temp(c).round() + 0.0);
+    let symbol = units.temp_symbol();
+    match (low, high) {
+        (_, Some(high)) if high >= HOT_C => format!("Dry and hot: up to {} {}", temp(high), symbol),
+        (Some(low), Some(high)) if high < COLD_C => {
+            format!("Dry but cold: {}–{} {}", temp(low), temp(high), symbol)
+        }
+        (Some(low), Some(high)) => {
+            format!(
+                "Good for hiking: dry, {}–{} {}",
+                temp(low),
+                temp(high),
+                symbol
+            )
+        }
+        _ => "Dry".to_string(),
+    }
+}
+
+/// The weekday of `day` with the parts of it at `threshold` or above, e.g.
+/// `Sunday afternoon`, or only the weekday when it is wet throughout or
+/// has no hours; `None` for a day below `threshold`.
+fn wet_parts(
+    day: &DailySummary,
+    hours: &[SeriesPoint],
+    zone: Option<Zone>,
+    threshold: f64,
+) -> Option<String> {
+    let weekday = day.date.format("%A").to_string();
+
+    let mut parts: Vec<(DayPart, bool)> = Vec::new();
+    for point in hours {
+        let local = match zone {
+            Some(zone) => zone.local(point.time),
+            None => point.time.with_timezone(&Local).naive_local(),
+        };
+        if local.date() != day.date {
+            continue;
+        }
+        let part = DayPart::of(local.hour());
+        let is_wet = point.precip_chance.is_some_and(|c| c >= threshold);
+        match parts.iter_mut().find(|(p, _)| *p == part) {
+            Some((_, wet)) => *wet |= is_wet,
+            None => parts.push((part, is_wet)),
+        }
+    }
+
+    if parts.is_empty() {
+        return wet(day, threshold).then_some(weekday);
+    }
+    parts.sort();
+    let wet: Vec<&str> = parts
+        .iter()
+        .filter(|(_, wet)| *wet)
+        .map(|(part, _)| part.name())
+        .collect();
+    match wet.len() {
+        0 => None,
+        n if n == parts.len() => Some(weekday),
+        _ => Some(format!("{} {}", weekday, wet.join(" and "))),
+    }
+}
+
+/// Whether the day's chance of precipitation is at `threshold` or above;
+/// without a chance, [`WET_DAY_MM`] or more makes showers possible.
+fn wet(day: &DailySummary, threshold: f64) -> bool {
+    match day.precip_chance {
+        Some(chance) => chance >= threshold,
+        None => threshold <= SHOWERS_POSSIBLE && day.precip_mm.is_some_and(|mm| mm >= WET_DAY_MM),
+    }
+}
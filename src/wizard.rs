@@ -0,0 +1,166 @@
+use std::io::{BufRead, Write};
+
+use anyhow::{anyhow, Result};
+
+use crate::config::{parse_lang, parse_units, AppConfig};
+use crate::providers::ProviderKind;
+
+/// Settings collected by the interactive `wapp configure`.
+///
+/// # Fields
+/// - `provider`: the chosen provider,
+/// - `api_key`: key typed in; `None` to use the provider's environment variable,
+/// - `units`: units of measurement, `None` for the provider's default,
+/// - `lang`: response language code, `None` for the system locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub provider: ProviderKind,
+    pub api_key: Option<String>,
+    pub units: Option<String>,
+    pub lang: Option<String>,
+}
+
+impl Answers {
+    /// Stores the answers in `cfg`, replacing its provider, units and
+    /// language, and the provider's saved key when one was typed in;
+    /// fallback providers are dropped, since they were picked for the old
+    /// provider.
+    pub fn apply(self, cfg: &mut AppConfig) {
+        cfg.provider = self.provider;
+        cfg.fallback_providers.clear();
+        if let Some(key) = self.api_key {
+            cfg.api_keys.insert(self.provider.name().to_string(), key);
+        }
+        cfg.units = self.units;
+        cfg.lang = self.lang;
+    }
+}
+
+/// Asks for a provider, its API key, units, and language on `output`,
+/// reading the answers line by line from `input`.
+///
+/// The provider is picked by number or name. An invalid answer is reported
+/// and asked again; an empty answer keeps the default shown in brackets.
+/// With `hide_key` the terminal does not echo the API key while it is typed.
+///
+/// # Errors
+/// Returns an error if `input` ends before every question is answered, or
+/// reading or writing fails.
+pub fn ask<R: BufRead, W: Write>(input: &mut R, output: &mut W, hide_key: bool) -> Result<Answers> {
+    writeln!(output, "Supported providers:")?;
+    for (i, kind) in ProviderKind::ALL.into_iter().enumerate() {
+        writeln!(
+            output,
+            "  {}. {:<14}  {}",
+            i + 1,
+            kind.name(),
+            kind.info().description
+        )?;
+    }
+
+    let provider = loop {
+        let answer = question(
+            input,
+            output,
+            &format!("Provider [1-{}]: ", ProviderKind::ALL.len()),
+        )?;
+        let picked = match answer.parse::<usize>() {
+            Ok(n) => n
+                .checked_sub(1)
+                .and_then(|i| ProviderKind::ALL.get(i).copied())
+                .ok_or_else(|| anyhow!("pick a number from 1 to {}", ProviderKind::ALL.len())),
+            Err(_) => answer.parse(),
+        };
+        match picked {
+            Ok(kind) => break kind,
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    };
+
+    let api_key = match provider.info().required_env.first() {
+        Some(var) => {
+            let prompt = format!("API key (empty to use {}): ", var);
+            if hide_key {
+                set_echo(false);
+                let answer = question(input, output, &prompt);
+                set_echo(true);
+                writeln!(output)?;
+                answer?
+            } else {
+                question(input, output, &prompt)?
+            }
+        }
+        None => String::new(),
+    };
+
+    let units = ask_valid(
+        input,
+        output,
+        "Units (metric, imperial, standard) [default]: ",
+        parse_units,
+    )?;
+    let lang = ask_valid(
+        input,
+        output,
+        "Language code, e.g. en or uk [system]: ",
+        parse_lang,
+    )?;
+
+    Ok(Answers {
+        provider,
+        api_key: Some(api_key).filter(|key| !key.is_empty()),
+        units,
+        lang,
+    })
+}
+
+/// The error for a `configure` without arguments when stdin is not a
+/// terminal, so scripts and CI fail instead of waiting for input.
+pub fn not_a_terminal() -> anyhow::Error {
+    anyhow!(
+        "wapp configure asks its questions on a terminal, but stdin is not one. \
+         In scripts, run: wapp configure <provider> [--api-key KEY]"
+    )
+}
+
+/// Asks until the answer is empty or accepted by `parse`.
+fn ask_valid<R: BufRead, W: Write>(
+    input: &mut R,
+    output: &mut W,
+    prompt: &str,
+    parse: fn(&str) -> Result<String>,
+) -> Result<Option<String>> {
+    loop {
+        let answer = question(input, output, prompt)?;
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        match parse(&answer) {
+            Ok(value) => return Ok(Some(value)),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+/// Writes `prompt` and reads one trimmed line.
+fn question<R: BufRead, W: Write>(input: &mut R, output: &mut W, prompt: &str) -> Result<String> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(anyhow!(
+            "configure cancelled: no answer to '{}'",
+            prompt.trim_end()
+        ));
+    }
+    Ok(line.trim().to_string())
+}
+
+/// Turns terminal echo on or off through `stty`; a no-op where that fails,
+/// e.g. on Windows, where the key stays visible.
+fn set_echo(on: bool) {
+    let _ = std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .status();
+}
@@ -0,0 +1,147 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use mocks::http_server::{HttpServer, Reply};
+use wapp::location::{Location, LocationInput};
+use wapp::providers::{AccuWeatherProvider, ApiProvider, DataKind};
+
+const SEARCH: &str = r#"[{"Key":"324505","LocalizedName":"Kyiv","Country":{"ID":"UA"}}]"#;
+
+const CURRENT: &str = r#"[{"EpochTime":1714557600,"WeatherText":"Partly sunny","Temperature":{"Metric":{"Value":18.3}},"RealFeelTemperature":{"Metric":{"Value":17.1}},"RelativeHumidity":52,"Pressure":{"Metric":{"Value":1016.0}},"Wind":{"Speed":{"Metric":{"Value":11.1}}},"CloudCover":40,"PrecipitationSummary":{"PastHour":{"Metric":{"Value":0.0}}}}]"#;
+
+const FIVE_DAYS: &str = r#"{"DailyForecasts":[
+    {"Date":"2024-05-01T07:00:00+03:00","Temperature":{"Minimum":{"Value":9.0},"Maximum":{"Value":19.5}},"Day":{"IconPhrase":"Sunny","TotalLiquid":{"Value":0.0}},"Night":{"IconPhrase":"Clear","TotalLiquid":{"Value":0.0}}},
+    {"Date":"2024-05-02T07:00:00+03:00","Temperature":{"Minimum":{"Value":10.0},"Maximum":{"Value":17.0}},"Day":{"IconPhrase":"Showers","TotalLiquid":{"Value":2.5}},"Night":{"IconPhrase":"Rain","TotalLiquid":{"Value":1.0}}},
+    {"Date":"2024-05-03T07:00:00+03:00","Temperature":{"Minimum":{"Value":8.0},"Maximum":{"Value":15.0}},"Day":{"IconPhrase":"Cloudy"},"Night":{"IconPhrase":"Cloudy"}},
+    {"Date":"2024-05-04T07:00:00+03:00","Temperature":{"Minimum":{"Value":7.0},"Maximum":{"Value":16.0}},"Day":{"IconPhrase":"Sunny"},"Night":{"IconPhrase":"Clear"}},
+    {"Date":"2024-05-05T07:00:00+03:00","Temperature":{"Minimum":{"Value":9.0},"Maximum":{"Value":20.0}},"Day":{"IconPhrase":"Sunny"},"Night":{"IconPhrase":"Clear"}}
+]}"#;
+
+fn provider(base_url: &str) -> AccuWeatherProvider {
+    AccuWeatherProvider {
+        api_key: "KEY".into(),
+        base_url: base_url.into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+#[test]
+fn test_search_urls() {
+    let p = provider("http://mock");
+
+    assert_eq!(
+        p.search_url(&LocationInput::parse("Kyiv")),
+        "http://mock/locations/v1/cities/search?apikey=KEY&q=Kyiv"
+    );
+    assert_eq!(
+        p.search_url(&LocationInput::parse("Kyiv, UA")),
+        "http://mock/locations/v1/cities/UA/search?apikey=KEY&q=Kyiv"
+    );
+    assert_eq!(
+        p.postal_code_url("10001", Some("US")),
+        "http://mock/locations/v1/postalcodes/US/search?apikey=KEY&q=10001"
+    );
+}
+
+#[test]
+fn test_weather_urls() {
+    let p = AccuWeatherProvider {
+        lang: Some("uk".into()),
+        ..provider("http://mock")
+    };
+
+    assert_eq!(
+        p.build_url("324505", DataKind::Now).unwrap(),
+        "http://mock/currentconditions/v1/324505?apikey=KEY&details=true&language=uk"
+    );
+    assert_eq!(
+        p.build_url("324505", DataKind::Forecast).unwrap(),
+        "http://mock/forecasts/v1/daily/5day/324505?apikey=KEY&details=true&metric=true&language=uk"
+    );
+    assert_eq!(
+        p.build_url("324505", DataKind::Tomorrow).unwrap(),
+        "http://mock/forecasts/v1/daily/5day/324505?apikey=KEY&details=true&metric=true&language=uk"
+    );
+    assert!(p.build_url("324505", DataKind::Hourly).is_err());
+}
+
+#[test]
+fn test_views() {
+    let p = provider("http://mock");
+
+    let now = p.current(CURRENT).unwrap();
+    assert_eq!(now.temp_c, Some(18.3));
+    assert_eq!(now.wind_kph, Some(11.1));
+    assert_eq!(now.condition.as_deref(), Some("Partly sunny"));
+
+    let days = p.daily(FIVE_DAYS).unwrap();
+    assert_eq!(days.len(), 5);
+    assert_eq!(days[1].precip_mm, Some(3.5));
+    assert_eq!(days[2].precip_mm, None);
+    assert_eq!(days[1].condition.as_deref(), Some("Showers"));
+}
+
+#[tokio::test]
+async fn test_location_key_is_looked_up_once() {
+    let locations = HttpServer::start(SEARCH).await;
+    let p = provider(&locations.base_url);
+    let location = Location::from("Lviv");
+
+    assert_eq!(p.location_key(&location).await.unwrap(), "324505");
+    assert_eq!(p.location_key(&location).await.unwrap(), "324505");
+    assert_eq!(locations.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_forecast_is_trimmed_to_days() {
+    let server = HttpServer::start_replies(vec![Reply::ok(SEARCH), Reply::ok(FIVE_DAYS)]).await;
+    let p = provider(&server.base_url);
+
+    let body = p
+        .get_data(Location::from("Odesa"), DataKind::Forecast, Some(2))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        p.forecast_dates(&body).unwrap(),
+        vec![
+            NaiveDate::from_ymd_opt(2024, 5, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 5, 2).unwrap()
+        ]
+    );
+    let received = server.received();
+    assert!(received[0].starts_with("get /locations/v1/cities/search?"));
+    assert!(received[1].starts_with("get /forecasts/v1/daily/5day/324505?"));
+}
+
+#[tokio::test]
+async fn test_tomorrow_keeps_the_second_day() {
+    let server = HttpServer::start_replies(vec![Reply::ok(SEARCH), Reply::ok(FIVE_DAYS)]).await;
+    let p = provider(&server.base_url);
+
+    let body = p
+        .get_data(Location::from("Odesa"), DataKind::Tomorrow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        p.forecast_dates(&body).unwrap(),
+        vec![NaiveDate::from_ymd_opt(2024, 5, 2).unwrap()]
+    );
+    let received = server.received();
+    assert!(received[1].starts_with("get /forecasts/v1/daily/5day/324505?"));
+}
+
+#[tokio::test]
+async fn test_unknown_city_is_an_error() {
+    let locations = HttpServer::start("[]").await;
+
+    let err = provider(&locations.base_url)
+        .get_data(Location::from("Atlantis"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("not found"));
+}
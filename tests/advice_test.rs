@@ -0,0 +1,321 @@
+mod mocks;
+
+use assert_cmd::Command;
+use chrono::NaiveDate;
+use mocks::cli::wapp_get;
+use mocks::http_server::HttpServer;
+use wapp::advice::{field, Advice, AdviceEngine, AdviceRule, AdviceSettings};
+use wapp::config::AppConfig;
+use wapp::series::DailySummary;
+use wapp::weather::{HourlyForecast, WeatherData};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// A mild, calm, dry day without a forecast.
+fn calm() -> WeatherData {
+    WeatherData {
+        temp_c: Some(18.0),
+        wind_kph: Some(10.0),
+        uv_index: Some(3.0),
+        ..Default::default()
+    }
+}
+
+fn today(min_c: f64, precip_mm: f64, precip_chance: f64) -> DailySummary {
+    DailySummary {
+        date: NaiveDate::from_ymd_opt(2025, 1, 10).unwrap(),
+        min_c: Some(min_c),
+        max_c: Some(min_c + 5.0),
+        precip_mm: Some(precip_mm),
+        precip_chance: Some(precip_chance),
+        max_wind_kph: None,
+        avg_wind_kph: None,
+        condition: None,
+        partial: false,
+    }
+}
+
+fn hour(temp_c: f64, precip_chance: f64, precip_mm: f64) -> HourlyForecast {
+    HourlyForecast {
+        time: "2025-01-10T12:00:00Z".parse().unwrap(),
+        temp_c: Some(temp_c),
+        precip_chance: Some(precip_chance),
+        precip_mm: Some(precip_mm),
+        condition: None,
+    }
+}
+
+fn ids(engine: &AdviceEngine, data: &WeatherData) -> Vec<String> {
+    engine.evaluate(data).into_iter().map(|a| a.id).collect()
+}
+
+fn defaults() -> AdviceEngine {
+    AdviceEngine::new(AdviceSettings::default().rules()).unwrap()
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_calm_weather_needs_no_advice() {
+    assert!(defaults().evaluate(&calm()).is_empty());
+    assert!(defaults().evaluate(&WeatherData::default()).is_empty());
+}
+
+#[test]
+fn test_each_builtin_rule_fires_above_its_threshold() {
+    let engine = defaults();
+
+    let rainy = WeatherData {
+        forecast: vec![today(12.0, 3.0, 80.0)],
+        ..calm()
+    };
+    assert_eq!(ids(&engine, &rainy), ["umbrella"]);
+    let borderline = WeatherData {
+        forecast: vec![today(12.0, 1.0, 50.0)],
+        ..calm()
+    };
+    assert!(ids(&engine, &borderline).is_empty());
+
+    let sunny = WeatherData {
+        uv_index: Some(8.0),
+        ..calm()
+    };
+    assert_eq!(
+        engine.evaluate(&sunny),
+        [Advice {
+            id: "sunscreen".into(),
+            message: "High UV — sunscreen recommended".into(),
+        }]
+    );
+
+    let windy = WeatherData {
+        wind_kph: Some(55.0),
+        ..calm()
+    };
+    assert_eq!(ids(&engine, &windy), ["wind"]);
+    // Gusts alone are enough.
+    let gusty = WeatherData {
+        gust_kph: Some(70.0),
+        ..calm()
+    };
+    assert_eq!(ids(&engine, &gusty), ["wind"]);
+
+    let icy = WeatherData {
+        temp_c: Some(1.0),
+        forecast: vec![today(-4.0, 2.0, 40.0)],
+        ..calm()
+    };
+    assert_eq!(ids(&engine, &icy), ["ice"]);
+}
+
+#[test]
+fn test_rules_combine_in_order() {
+    let storm = WeatherData {
+        temp_c: Some(-2.0),
+        wind_kph: Some(60.0),
+        uv_index: Some(7.0),
+        forecast: vec![today(-6.0, 8.0, 90.0)],
+        ..Default::default()
+    };
+    assert_eq!(
+        ids(&defaults(), &storm),
+        ["umbrella", "sunscreen", "wind", "ice"]
+    );
+
+    // Frost without precipitation is no ice risk.
+    let frost = WeatherData {
+        forecast: vec![today(-6.0, 0.0, 10.0)],
+        ..storm
+    };
+    assert_eq!(ids(&defaults(), &frost), ["sunscreen", "wind"]);
+}
+
+#[test]
+fn test_fields_prefer_the_hours_over_the_day() {
+    let data = WeatherData {
+        temp_c: Some(3.0),
+        hourly: vec![hour(2.0, 20.0, 0.5), hour(-1.0, 70.0, 1.0)],
+        forecast: vec![today(5.0, 0.0, 0.0)],
+        ..Default::default()
+    };
+    assert_eq!(field(&data, "precip_chance"), Some(70.0));
+    assert_eq!(field(&data, "precip_mm"), Some(1.5));
+    assert_eq!(field(&data, "min_temp_c"), Some(-1.0));
+    assert_eq!(field(&data, "max_temp_c"), Some(2.0));
+    assert_eq!(ids(&defaults(), &data), ["umbrella", "ice"]);
+
+    // Without a forecast, only the current temperature is known.
+    assert_eq!(field(&calm(), "min_temp_c"), Some(18.0));
+    assert_eq!(field(&calm(), "precip_chance"), None);
+    assert_eq!(field(&calm(), "uv"), Some(3.0));
+    assert_eq!(field(&calm(), "snow"), None);
+}
+
+#[test]
+fn test_thresholds_are_configurable() {
+    let settings: AdviceSettings =
+        serde_json::from_str(r#"{ "uv": 2, "wind_kph": 5, "rain_chance": 95, "ice_below_c": 3 }"#)
+            .unwrap();
+    let engine = AdviceEngine::new(settings.rules()).unwrap();
+
+    let data = WeatherData {
+        forecast: vec![today(2.0, 1.0, 90.0)],
+        ..calm()
+    };
+    assert_eq!(ids(&engine, &data), ["sunscreen", "wind", "ice"]);
+    assert_eq!(ids(&defaults(), &data), ["umbrella"]);
+}
+
+#[test]
+fn test_own_rules_follow_and_replace_builtin_ones() {
+    let settings = AdviceSettings {
+        rules: vec![
+            AdviceRule {
+                id: "umbrella".into(),
+                when: "precip_chance > 10".into(),
+                message: "Bring a raincoat".into(),
+            },
+            AdviceRule {
+                id: "warm".into(),
+                when: "feels_like_c < 0".into(),
+                message: "Wear a hat".into(),
+            },
+        ],
+        ..Default::default()
+    };
+    let rules = settings.rules();
+    assert_eq!(
+        rules.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+        ["sunscreen", "wind", "ice", "umbrella", "warm"]
+    );
+
+    let data = WeatherData {
+        feels_like_c: Some(-3.0),
+        forecast: vec![today(4.0, 0.2, 20.0)],
+        ..calm()
+    };
+    assert_eq!(
+        AdviceEngine::new(rules).unwrap().evaluate(&data),
+        [
+            Advice {
+                id: "umbrella".into(),
+                message: "Bring a raincoat".into(),
+            },
+            Advice {
+                id: "warm".into(),
+                message: "Wear a hat".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_invalid_rules_are_rejected() {
+    let rule = |when: &str| AdviceRule {
+        id: "mine".into(),
+        when: when.into(),
+        message: "?".into(),
+    };
+
+    let err = AdviceEngine::new(vec![rule("uv >")])
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("advice rule 'mine':"), "{}", err);
+
+    // Even where an earlier comparison would decide.
+    let err = AdviceEngine::new(vec![rule("uv > -1 || snow_cm > 5")])
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("unknown field 'snow_cm'"), "{}", err);
+    assert!(err.contains("precip_chance"), "{}", err);
+}
+
+#[test]
+fn test_config_advice_section() {
+    let cfg: AppConfig = serde_json::from_str(
+        r#"{ "provider": "weatherapi", "advice": { "uv": 4, "rules": [
+            { "id": "scarf", "when": "temp_c < -10", "message": "Wear a scarf" }
+        ] } }"#,
+    )
+    .unwrap();
+    let advice = cfg.advice.as_ref().unwrap();
+    assert_eq!(advice.uv, Some(4.0));
+    assert_eq!(advice.rain_chance, None);
+    assert_eq!(advice.rules[0].id, "scarf");
+
+    // Unset thresholds are not written back.
+    let json = serde_json::to_value(&cfg).unwrap();
+    assert_eq!(
+        json["advice"],
+        serde_json::json!({ "uv": 4.0, "rules": advice.rules })
+    );
+    assert!(serde_json::to_value(AppConfig::default())
+        .unwrap()
+        .get("advice")
+        .is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_advice_in_text_and_json() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // The fixture has a UV index of 7.
+    let text =
+        stdout(wapp_get(dir.path(), &server.base_url).args(["--advice", "--output", "text"]));
+    assert!(
+        text.ends_with("  Advice:      High UV — sunscreen recommended\n"),
+        "{}",
+        text
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&stdout(
+        wapp_get(dir.path(), &server.base_url).args(["--advice", "--output", "json"]),
+    ))
+    .unwrap();
+    assert_eq!(json["advice"][0]["id"], "sunscreen");
+    assert_eq!(json["advice"].as_array().unwrap().len(), 1);
+
+    // Without --advice, nothing changes.
+    let text = stdout(wapp_get(dir.path(), &server.base_url).args(["--output", "text"]));
+    assert!(!text.contains("Advice"), "{}", text);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_advice_uses_the_config() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{ "provider": "weatherapi", "advice": { "uv": 8, "wind_kph": 12 } }"#,
+    )
+    .unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&stdout(
+        wapp_get(dir.path(), &server.base_url).args(["--advice", "--output", "json"]),
+    ))
+    .unwrap();
+    assert_eq!(
+        json["advice"],
+        serde_json::json!([{
+            "id": "wind",
+            "message": "Strong wind — secure loose items",
+        }])
+    );
+
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{ "provider": "weatherapi", "advice": { "rules": [{ "id": "x", "when": "uv >", "message": "?" }] } }"#,
+    )
+    .unwrap();
+    let output = wapp_get(dir.path(), &server.base_url)
+        .arg("--advice")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("advice rule 'x'"));
+}
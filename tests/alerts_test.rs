@@ -0,0 +1,214 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::providers::weatherapi;
+use wapp::cli::{Cli, Commands};
+use wapp::location::{CityName, Location};
+use wapp::output::{self, OutputFormat, Palette, ALERT_DESCRIPTION_CHARS};
+use wapp::providers::{ApiProvider, DataKind};
+use wapp::request::{validate_request, WeatherRequest};
+use wapp::weather::{sort_alerts, Alert, Severity};
+
+const WEATHERAPI_ALERTS: &str = include_str!("fixtures/weatherapi_alerts.json");
+
+fn alert(event: &str, severity: Severity, description: &str) -> Alert {
+    Alert {
+        event: event.into(),
+        severity,
+        headline: None,
+        starts: None,
+        ends: None,
+        description: Some(description.into()),
+    }
+}
+
+#[test]
+fn test_weatherapi_alerts_url() {
+    let url = weatherapi("http://w")
+        .build_url(&Location::from("Miami"), DataKind::Alerts, None)
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "http://w/forecast.json?key=KEY&q=Miami&days=1&alerts=yes"
+    );
+}
+
+#[test]
+fn test_weatherapi_alerts_are_normalized() {
+    let alerts = weatherapi("http://w").alerts(WEATHERAPI_ALERTS).unwrap();
+
+    assert_eq!(alerts.len(), 2);
+    assert_eq!(alerts[0].event, "Heat Advisory");
+    assert_eq!(alerts[0].severity, Severity::Moderate);
+    assert!(alerts[0]
+        .headline
+        .as_deref()
+        .unwrap()
+        .starts_with("Heat Advisory issued June 10"));
+    assert_eq!(
+        alerts[0].starts.unwrap().to_rfc3339(),
+        "2024-06-10T08:00:00+00:00"
+    );
+    assert_eq!(
+        alerts[0].ends.unwrap().to_rfc3339(),
+        "2024-06-10T23:00:00+00:00"
+    );
+    assert_eq!(alerts[1].severity, Severity::Severe);
+}
+
+#[test]
+fn test_alerts_sort_most_severe_first() {
+    let mut alerts = vec![
+        alert("Fog", Severity::Minor, ""),
+        alert("Wind", Severity::Unknown, ""),
+        alert("Tornado", Severity::Extreme, ""),
+        alert("Flood", Severity::Severe, ""),
+    ];
+    sort_alerts(&mut alerts);
+
+    let events: Vec<&str> = alerts.iter().map(|a| a.event.as_str()).collect();
+    assert_eq!(events, ["Tornado", "Flood", "Fog", "Wind"]);
+    assert_eq!(Severity::from_text(" EXTREME "), Severity::Extreme);
+    assert_eq!(Severity::from_text(""), Severity::Unknown);
+}
+
+#[test]
+fn test_text_lists_alerts_by_severity() {
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "Miami",
+        "alerts",
+        WEATHERAPI_ALERTS,
+        OutputFormat::Text,
+    );
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines[0], "Miami");
+    assert!(
+        lines[1].starts_with("  Severe       Flash Flood Warning issued"),
+        "{text}"
+    );
+    assert!(lines[2].contains(" – "), "{text}");
+    assert!(lines[3].starts_with("               * WHAT...Flash flooding"));
+    assert!(
+        lines[4].starts_with("  Moderate     Heat Advisory issued"),
+        "{text}"
+    );
+    assert!(!text.contains("Temperature"), "{text}");
+}
+
+#[test]
+fn test_no_alerts() {
+    let body = WEATHERAPI_ALERTS.replace(r#""alert": ["#, r#""alert": [], "old": ["#);
+
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "Miami",
+        "alerts",
+        &body,
+        OutputFormat::Text,
+    );
+    assert_eq!(text, "Miami\n  No active alerts\n");
+
+    let table = output::summarize(
+        &weatherapi("http://w"),
+        "Miami",
+        "alerts",
+        &body,
+        OutputFormat::Table,
+    );
+    assert_eq!(table, "Miami\nNo active alerts\n");
+
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(&weatherapi("http://w"), "alerts", &body).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(json["alerts"], serde_json::json!([]));
+}
+
+#[test]
+fn test_long_descriptions_are_cut_unless_full() {
+    let long = format!("{}\n\nSecond paragraph.", "Heavy rain. ".repeat(40));
+    let alerts = [alert("Flood", Severity::Severe, &long)];
+
+    let cut = output::render_alerts(&alerts, false, Palette::PLAIN);
+    let description = cut.lines().nth(1).unwrap().trim_start();
+    assert_eq!(description.chars().count(), ALERT_DESCRIPTION_CHARS);
+    assert!(description.ends_with('…'));
+    assert!(!cut.contains("Second paragraph."));
+
+    let full = output::render_alerts(&alerts, true, Palette::PLAIN);
+    assert!(full
+        .lines()
+        .any(|l| l == "               Second paragraph."));
+    assert!(!full.contains('…'));
+}
+
+#[test]
+fn test_json_carries_the_normalized_alerts() {
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(&weatherapi("http://w"), "alerts", WEATHERAPI_ALERTS).unwrap(),
+    )
+    .unwrap();
+    let first = &json["alerts"][0];
+
+    assert_eq!(first["event"], "Flash Flood Warning");
+    assert_eq!(first["severity"], "severe");
+    assert_eq!(first["starts"], "2024-06-10T14:00:00Z");
+    assert_eq!(first["ends"], "2024-06-10T20:00:00Z");
+    assert!(first["description"]
+        .as_str()
+        .unwrap()
+        .ends_with("under an evacuation order."));
+    assert_eq!(json["alerts"][1]["severity"], "moderate");
+
+    // Other kinds have no alerts key.
+    let now = output::normalized_json(&weatherapi("http://w"), "now", WEATHERAPI_ALERTS).unwrap();
+    assert!(!now.contains("\"alerts\""));
+}
+
+#[test]
+fn test_table_has_a_row_per_alert() {
+    let table = output::summarize(
+        &weatherapi("http://w"),
+        "Miami",
+        "alerts",
+        WEATHERAPI_ALERTS,
+        OutputFormat::Table,
+    );
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines.len(), 4, "{table}");
+    assert!(lines[1].starts_with("Severity  Starts"), "{table}");
+    assert!(lines[2].starts_with("Severe  "), "{table}");
+    assert!(lines[3].starts_with("Moderate"), "{table}");
+}
+
+#[test]
+fn test_parse_alerts_and_full() {
+    let cli = Cli::parse_from([
+        "wapp", "get", "--city", "Miami", "--data", "alerts", "--full",
+    ]);
+
+    match cli.cmd {
+        Commands::Get { data, full, .. } => {
+            assert_eq!(data, vec![DataKind::Alerts]);
+            assert!(full);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_days_do_not_apply_to_alerts() {
+    let request = WeatherRequest::new(&CityName::new("Miami").unwrap(), DataKind::Alerts, Some(2));
+
+    let err = validate_request(&weatherapi("http://w"), &request)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.starts_with("--days does not apply to --data alerts"),
+        "{err}"
+    );
+}
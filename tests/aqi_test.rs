@@ -0,0 +1,355 @@
+mod mocks;
+
+use mocks::providers::{openweather, weatherapi};
+use serde_json::json;
+use wapp::aqi::{
+    caqi_category, caqi_sub_index, eu_caqi, to_epa_units, us_aqi, us_aqi_category, us_sub_index,
+    AirQuality, AqiScale, Pollutant, Pollutants, ValueSource,
+};
+use wapp::location::Location;
+use wapp::output::{self, OutputFormat};
+use wapp::providers::{ApiProvider, DataKind};
+
+#[test]
+fn test_us_pm25_breakpoint_boundaries() {
+    let cases = [
+        (0.0, 0),
+        (9.0, 50),
+        (9.1, 51),
+        (35.4, 100),
+        (35.5, 101),
+        (55.4, 150),
+        (55.5, 151),
+        (125.4, 200),
+        (125.5, 201),
+        (225.4, 300),
+        (225.5, 301),
+        (325.4, 500),
+    ];
+
+    for (c, expected) in cases {
+        assert_eq!(
+            us_sub_index(Pollutant::Pm2_5, c),
+            Some(expected),
+            "PM2.5 {c}"
+        );
+    }
+}
+
+#[test]
+fn test_us_pm10_breakpoint_boundaries() {
+    let cases = [
+        (54.0, 50),
+        (55.0, 51),
+        (154.0, 100),
+        (155.0, 101),
+        (254.0, 150),
+        (354.0, 200),
+        (424.0, 300),
+        (604.0, 500),
+    ];
+
+    for (c, expected) in cases {
+        assert_eq!(us_sub_index(Pollutant::Pm10, c), Some(expected), "PM10 {c}");
+    }
+}
+
+#[test]
+fn test_us_gas_breakpoint_boundaries() {
+    assert_eq!(us_sub_index(Pollutant::O3, 0.054), Some(50));
+    assert_eq!(us_sub_index(Pollutant::O3, 0.055), Some(51));
+    assert_eq!(us_sub_index(Pollutant::O3, 0.070), Some(100));
+    assert_eq!(us_sub_index(Pollutant::No2, 53.0), Some(50));
+    assert_eq!(us_sub_index(Pollutant::No2, 54.0), Some(51));
+    assert_eq!(us_sub_index(Pollutant::So2, 75.0), Some(100));
+    assert_eq!(us_sub_index(Pollutant::So2, 76.0), Some(101));
+    assert_eq!(us_sub_index(Pollutant::Co, 4.4), Some(50));
+    assert_eq!(us_sub_index(Pollutant::Co, 4.5), Some(51));
+    assert_eq!(us_sub_index(Pollutant::Co, 9.4), Some(100));
+}
+
+#[test]
+fn test_us_o3_top_of_scale_keeps_rising() {
+    let cases = [
+        (0.200, 300),
+        (0.201, 301),
+        (0.404, 367),
+        (0.405, 368),
+        (0.504, 400),
+        (0.505, 401),
+        (0.604, 500),
+        (0.7, 500),
+    ];
+
+    for (c, expected) in cases {
+        assert_eq!(us_sub_index(Pollutant::O3, c), Some(expected), "O3 {c}");
+    }
+}
+
+#[test]
+fn test_us_epa_reference_examples() {
+    // Worked examples from the EPA AQI technical assistance document.
+    assert_eq!(us_sub_index(Pollutant::Pm2_5, 35.9), Some(102));
+    assert_eq!(us_sub_index(Pollutant::O3, 0.078), Some(126));
+}
+
+#[test]
+fn test_us_concentration_is_truncated() {
+    // 9.05 truncates to 9.0, which is still "Good".
+    assert_eq!(us_sub_index(Pollutant::Pm2_5, 9.05), Some(50));
+    assert_eq!(us_sub_index(Pollutant::Pm10, 54.9), Some(50));
+}
+
+#[test]
+fn test_us_above_table_is_capped() {
+    assert_eq!(us_sub_index(Pollutant::Pm2_5, 900.0), Some(500));
+    assert_eq!(us_sub_index(Pollutant::Pm2_5, -1.0), None);
+}
+
+#[test]
+fn test_gas_unit_conversion() {
+    // 1 ppm of CO is about 1145 µg/m³ at 25 °C.
+    assert!((to_epa_units(Pollutant::Co, 1145.6) - 1.0).abs() < 0.01);
+    // 1 ppb of NO2 is about 1.88 µg/m³.
+    assert!((to_epa_units(Pollutant::No2, 1.882) - 1.0).abs() < 0.01);
+    assert_eq!(to_epa_units(Pollutant::Pm10, 42.0), 42.0);
+}
+
+#[test]
+fn test_us_aqi_is_max_of_sub_indices() {
+    let p = Pollutants {
+        pm2_5: Some(40.0), // 112
+        pm10: Some(20.0),  // 19
+        ..Default::default()
+    };
+
+    assert_eq!(us_aqi(&p), Some(112));
+    assert_eq!(us_aqi(&Pollutants::default()), None);
+}
+
+#[test]
+fn test_us_categories() {
+    assert_eq!(us_aqi_category(50), "Good");
+    assert_eq!(us_aqi_category(51), "Moderate");
+    assert_eq!(us_aqi_category(101), "Unhealthy for Sensitive Groups");
+    assert_eq!(us_aqi_category(201), "Very Unhealthy");
+    assert_eq!(us_aqi_category(301), "Hazardous");
+}
+
+#[test]
+fn test_caqi_grid_boundaries() {
+    let cases = [
+        (Pollutant::No2, 0.0, 0),
+        (Pollutant::No2, 50.0, 25),
+        (Pollutant::No2, 100.0, 50),
+        (Pollutant::No2, 200.0, 75),
+        (Pollutant::No2, 400.0, 100),
+        (Pollutant::Pm10, 25.0, 25),
+        (Pollutant::Pm10, 90.0, 75),
+        (Pollutant::Pm2_5, 15.0, 25),
+        (Pollutant::Pm2_5, 110.0, 100),
+        (Pollutant::O3, 120.0, 50),
+        (Pollutant::O3, 240.0, 100),
+    ];
+
+    for (p, c, expected) in cases {
+        assert_eq!(caqi_sub_index(p, c), Some(expected), "{p:?} {c}");
+    }
+}
+
+#[test]
+fn test_caqi_interpolation_and_overflow() {
+    // Halfway through the 50–100 µg/m³ NO2 band.
+    assert_eq!(caqi_sub_index(Pollutant::No2, 75.0), Some(38));
+    // The last band's slope (200 µg/m³ per 25 points) continues above 100.
+    assert_eq!(caqi_sub_index(Pollutant::No2, 600.0), Some(125));
+}
+
+#[test]
+fn test_caqi_categories() {
+    assert_eq!(caqi_category(25), "Very low");
+    assert_eq!(caqi_category(26), "Low");
+    assert_eq!(caqi_category(75), "Medium");
+    assert_eq!(caqi_category(100), "High");
+    assert_eq!(caqi_category(101), "Very high");
+    assert_eq!(eu_caqi(&Pollutants::default()), None);
+}
+
+#[test]
+fn test_weatherapi_mapping_labels_sources() {
+    let air = json!({
+        "co": 230.3, "no2": 13.5, "o3": 58.0, "so2": 2.1,
+        "pm2_5": 12.2, "pm10": 18.7, "us-epa-index": 1, "gb-defra-index": 2
+    });
+
+    let aq = AirQuality::from_weatherapi(&air).unwrap();
+
+    assert_eq!(aq.pollutants.pm2_5, Some(12.2));
+    let epa = aq.index(AqiScale::UsEpaCategory).unwrap();
+    assert_eq!((epa.value, epa.source), (1.0, ValueSource::Provider));
+    assert_eq!(aq.index(AqiScale::UkDefra).unwrap().value, 2.0);
+
+    let us = aq.index(AqiScale::UsAqi).unwrap();
+    assert_eq!(us.source, ValueSource::Computed);
+    assert_eq!(us.value, 57.0);
+    assert_eq!(
+        aq.index(AqiScale::EuCaqi).unwrap().source,
+        ValueSource::Computed
+    );
+}
+
+#[test]
+fn test_openweather_mapping_labels_sources() {
+    let entry = json!({
+        "main": { "aqi": 2 },
+        "components": {
+            "co": 201.94, "no": 0.02, "no2": 0.77, "o3": 68.66,
+            "so2": 0.64, "pm2_5": 0.5, "pm10": 0.54, "nh3": 0.12
+        },
+        "dt": 1718020800
+    });
+
+    let aq = AirQuality::from_openweather(&entry).unwrap();
+
+    let owm = aq.index(AqiScale::OpenWeather).unwrap();
+    assert_eq!((owm.value, owm.source), (2.0, ValueSource::Provider));
+    // O3 at 68.66 µg/m³ sits in the second CAQI band.
+    assert_eq!(aq.index(AqiScale::EuCaqi).unwrap().value, 29.0);
+    assert!(aq.index(AqiScale::UsAqi).is_some());
+}
+
+#[test]
+fn test_missing_components_yield_none() {
+    assert!(AirQuality::from_openweather(&json!({ "main": { "aqi": 1 } })).is_none());
+    assert!(AirQuality::from_weatherapi(&json!("n/a")).is_none());
+}
+
+const WEATHERAPI_AIR: &str = include_str!("fixtures/weatherapi_air.json");
+const OPENWEATHER_AIR: &str = include_str!("fixtures/openweather_air_pollution.json");
+
+#[test]
+fn test_weatherapi_air_url() {
+    let url = weatherapi("http://w")
+        .build_url(&Location::from("London"), DataKind::Air, None)
+        .unwrap();
+
+    assert_eq!(url, "http://w/current.json?key=KEY&q=London&aqi=yes");
+}
+
+#[test]
+fn test_weatherapi_air_response_is_mapped() {
+    let aq = weatherapi("http://w").air_quality(WEATHERAPI_AIR).unwrap();
+
+    assert_eq!(aq.pollutants.pm10, Some(18.7));
+    assert_eq!(aq.pollutants.co, Some(230.3));
+    let primary = aq.primary().unwrap();
+    assert_eq!(primary.scale, AqiScale::UsEpaCategory);
+    assert_eq!((primary.value, primary.category), (1.0, "Good"));
+    assert_eq!(aq.index(AqiScale::UkDefra).unwrap().category, "Low");
+    assert_eq!(aq.index(AqiScale::UsAqi).unwrap().category, "Moderate");
+
+    // A response without `aqi=yes` has no report.
+    let now = include_str!("fixtures/weatherapi_current.json");
+    assert!(weatherapi("http://w").air_quality(now).is_none());
+}
+
+#[test]
+fn test_openweather_air_response_is_mapped() {
+    let aq = openweather("http://o/data/2.5")
+        .air_quality(OPENWEATHER_AIR)
+        .unwrap();
+
+    assert_eq!(aq.pollutants.o3, Some(68.66));
+    let primary = aq.primary().unwrap();
+    assert_eq!(primary.scale, AqiScale::OpenWeather);
+    assert_eq!((primary.value, primary.category), (2.0, "Fair"));
+
+    // A forecast `list` holds no components.
+    let forecast = include_str!("fixtures/openweather_forecast.json");
+    assert!(openweather("http://o/data/2.5")
+        .air_quality(forecast)
+        .is_none());
+}
+
+#[test]
+fn test_scale_categories() {
+    assert_eq!(AqiScale::OpenWeather.category(5.0), "Very poor");
+    assert_eq!(
+        AqiScale::UsEpaCategory.category(3.0),
+        "Unhealthy for Sensitive Groups"
+    );
+    assert_eq!(AqiScale::UkDefra.category(10.0), "Very high");
+    assert_eq!(AqiScale::UsAqi.category(151.0), "Unhealthy");
+    assert_eq!(AqiScale::EuCaqi.category(30.0), "Low");
+}
+
+#[test]
+fn test_text_states_the_scale() {
+    let text = output::summarize(
+        &openweather("http://o/data/2.5"),
+        "Kyiv",
+        "air",
+        OPENWEATHER_AIR,
+        OutputFormat::Text,
+    );
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines[0], "Kyiv");
+    assert_eq!(
+        lines[1],
+        "  Air quality: 2 Fair (OpenWeatherMap index, 1–5)"
+    );
+    assert!(lines[2].ends_with("(US AQI, 0–500, computed)"), "{text}");
+    assert_eq!(
+        lines[3],
+        "               29 Low (European CAQI, 0–100+, computed)"
+    );
+    assert_eq!(lines[4], "  PM2.5:       0.5 µg/m³");
+    assert_eq!(lines.len(), 10, "{text}");
+
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "London",
+        "air",
+        WEATHERAPI_AIR,
+        OutputFormat::Text,
+    );
+    assert!(
+        text.contains("  Air quality: 1 Good (US EPA index, 1–6)\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_json_records_the_scale_of_every_index() {
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(&weatherapi("http://w"), "air", WEATHERAPI_AIR).unwrap(),
+    )
+    .unwrap();
+    let air = &json["air"];
+
+    assert_eq!(air["pollutants"]["pm2_5"], 12.2);
+    assert_eq!(
+        air["indices"][0],
+        json!({"scale": "us_epa_category", "value": 1.0, "category": "Good", "source": "provider"})
+    );
+    assert_eq!(air["indices"][2]["scale"], "us_aqi");
+    assert_eq!(air["indices"][2]["source"], "computed");
+}
+
+#[test]
+fn test_table_has_a_row_per_index() {
+    let table = output::summarize(
+        &openweather("http://o/data/2.5"),
+        "Kyiv",
+        "air",
+        OPENWEATHER_AIR,
+        OutputFormat::Table,
+    );
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines.len(), 5, "{table}");
+    assert!(lines[1].starts_with("Index"), "{table}");
+    assert!(lines[2].starts_with("OpenWeatherMap index  1–5"), "{table}");
+    assert!(lines[2].ends_with("2  Fair      provider"), "{table}");
+}
@@ -0,0 +1,201 @@
+mod mocks;
+
+use chrono::{NaiveDate, NaiveTime};
+use clap::Parser;
+use mocks::providers::weatherapi;
+use wapp::cli::{dated_kinds, Cli, Commands};
+use wapp::location::{CityName, Location};
+use wapp::output::{self, OutputFormat};
+use wapp::providers::{validate_kind, ApiProvider, DataKind};
+use wapp::request::{validate_request, WeatherRequest};
+use wapp::weather::{day_length, moon_phase};
+
+const WEATHERAPI_ASTRONOMY: &str = include_str!("fixtures/weatherapi_astronomy.json");
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+fn clock(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M").ok()
+}
+
+#[test]
+fn test_weatherapi_astronomy_url() {
+    let p = weatherapi("http://w");
+    let url = |kind| p.build_url(&Location::from("Kyiv"), kind, None).unwrap();
+
+    assert_eq!(
+        url(DataKind::Astro(None)),
+        "http://w/astronomy.json?key=KEY&q=Kyiv"
+    );
+    assert_eq!(
+        url(DataKind::Astro(Some(date("2024-06-12")))),
+        "http://w/astronomy.json?key=KEY&q=Kyiv&dt=2024-06-12"
+    );
+}
+
+#[test]
+fn test_weatherapi_astronomy_is_normalized() {
+    let astro = weatherapi("http://w")
+        .astronomy(WEATHERAPI_ASTRONOMY)
+        .unwrap();
+
+    assert_eq!(astro.date, Some(date("2024-06-10")));
+    assert_eq!(astro.timezone.as_deref(), Some("Europe/Kyiv"));
+    assert_eq!(astro.sunrise, clock("04:46"));
+    assert_eq!(astro.sunset, clock("21:11"));
+    assert_eq!(astro.day_length_min, Some(985));
+    assert_eq!(astro.moonrise, clock("13:37"));
+    assert_eq!(astro.moonset, None);
+    assert_eq!(astro.moon_phase.as_deref(), Some("Waxing Crescent"));
+    assert_eq!(astro.moon_illumination, Some(18.0));
+
+    // Older responses give the illumination as a string.
+    let body =
+        WEATHERAPI_ASTRONOMY.replace(r#""moon_illumination": 18"#, r#""moon_illumination": "18""#);
+    let astro = weatherapi("http://w").astronomy(&body).unwrap();
+    assert_eq!(astro.moon_illumination, Some(18.0));
+}
+
+#[test]
+fn test_moon_phase_and_day_length() {
+    assert_eq!(moon_phase(0.0), ("New Moon", 0.0));
+    assert_eq!(moon_phase(0.25), ("First Quarter", 50.0));
+    assert_eq!(moon_phase(0.5), ("Full Moon", 100.0));
+    assert_eq!(moon_phase(0.64).0, "Waning Gibbous");
+    assert_eq!(moon_phase(0.14), ("Waxing Crescent", 18.0));
+    assert_eq!(moon_phase(0.99).0, "New Moon");
+
+    assert_eq!(day_length(clock("04:46"), clock("21:11")), Some(985));
+    assert_eq!(day_length(clock("04:46"), None), None);
+    assert_eq!(day_length(clock("21:11"), clock("04:46")), None);
+}
+
+#[test]
+fn test_text_shows_local_sun_and_moon_times() {
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "Kyiv",
+        "astro",
+        WEATHERAPI_ASTRONOMY,
+        OutputFormat::Text,
+    );
+
+    assert_eq!(
+        text,
+        "Kyiv\n\
+         \x20 Date:        2024-06-10 (Europe/Kyiv)\n\
+         \x20 Sunrise:     04:46\n\
+         \x20 Sunset:      21:11\n\
+         \x20 Day length:  16h 25m\n\
+         \x20 Moonrise:    13:37\n\
+         \x20 Moon phase:  Waxing Crescent (18%)\n"
+    );
+}
+
+#[test]
+fn test_asked_date_replaces_the_location_today() {
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "Kyiv",
+        "astro:2024-06-12",
+        WEATHERAPI_ASTRONOMY,
+        OutputFormat::Text,
+    );
+
+    assert!(
+        text.contains("Date:        2024-06-12 (Europe/Kyiv)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn test_table_and_json() {
+    let table = output::summarize(
+        &weatherapi("http://w"),
+        "Kyiv",
+        "astro",
+        WEATHERAPI_ASTRONOMY,
+        OutputFormat::Table,
+    );
+    assert_eq!(
+        table,
+        "Kyiv\n\
+         Date        Sunrise  Sunset  Day length  Moon phase\n\
+         2024-06-10  04:46    21:11   16h 25m     Waxing Crescent (18%)\n"
+    );
+
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(&weatherapi("http://w"), "astro", WEATHERAPI_ASTRONOMY).unwrap(),
+    )
+    .unwrap();
+    let astro = &json["astro"];
+    assert_eq!(astro["date"], "2024-06-10");
+    assert_eq!(astro["sunrise"], "04:46");
+    assert_eq!(astro["moonset"], serde_json::Value::Null);
+    assert_eq!(astro["day_length_min"], 985);
+}
+
+#[test]
+fn test_date_with_astro() {
+    let cli = Cli::parse_from([
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--data",
+        "astro",
+        "--date",
+        "2024-06-12",
+    ]);
+
+    match cli.cmd {
+        Commands::Get {
+            data,
+            date: Some(day),
+            ..
+        } => {
+            assert_eq!(
+                dated_kinds(&data, day).unwrap(),
+                [DataKind::Astro(Some(date("2024-06-12")))]
+            );
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert_eq!(
+        dated_kinds(&[DataKind::Now], date("2024-06-01")).unwrap(),
+        [DataKind::History(date("2024-06-01"))]
+    );
+    let err = dated_kinds(&[DataKind::Forecast], date("2024-06-01"))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not to --data forecast"), "{err}");
+}
+
+#[test]
+fn test_astro_kind_names() {
+    assert_eq!("astro".parse::<DataKind>().unwrap(), DataKind::Astro(None));
+    let dated = DataKind::Astro(Some(date("2024-06-12")));
+    assert_eq!(dated.to_string(), "astro:2024-06-12");
+    assert_eq!("astro:2024-06-12".parse::<DataKind>().unwrap(), dated);
+    assert!(validate_kind(&weatherapi("http://w"), dated).is_ok());
+}
+
+#[test]
+fn test_days_do_not_apply_to_astro() {
+    let request = WeatherRequest::new(
+        &CityName::new("Kyiv").unwrap(),
+        DataKind::Astro(None),
+        Some(2),
+    );
+
+    let err = validate_request(&weatherapi("http://w"), &request)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("--days does not apply to --data astro"),
+        "{err}"
+    );
+}
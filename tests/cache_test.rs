@@ -0,0 +1,274 @@
+use std::fs;
+
+use clap::Parser;
+use wapp::cache::{format_size, parse_age, Cache, CacheEntry, ClearFilter, Namespace};
+use wapp::cli::{run_cache_command, CacheCommand, Cli, Commands};
+
+const NOW: u64 = 1_700_000_000;
+
+fn entry(namespace: Namespace, key: &str, provider: &str, city: &str) -> CacheEntry {
+    CacheEntry {
+        namespace,
+        key: key.into(),
+        provider: provider.into(),
+        city: city.into(),
+        created: NOW - 120,
+        expires: Some(NOW + 600),
+        body: "{}".into(),
+        validators: Default::default(),
+    }
+}
+
+/// A cache holding four entries plus an unrelated file.
+fn populated() -> (tempfile::TempDir, Cache) {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path());
+
+    cache
+        .put(&entry(
+            Namespace::Response,
+            "weatherapi:now:kyiv",
+            "weatherapi",
+            "Kyiv",
+        ))
+        .unwrap();
+    cache
+        .put(&entry(
+            Namespace::Response,
+            "openweather:now:lviv",
+            "openweather",
+            "Lviv",
+        ))
+        .unwrap();
+    cache
+        .put(&entry(
+            Namespace::Geocode,
+            "geocode:kyiv",
+            "weatherapi",
+            "Kyiv",
+        ))
+        .unwrap();
+
+    let mut expired = entry(
+        Namespace::Stale,
+        "weatherapi:now:rome",
+        "weatherapi",
+        "Rome",
+    );
+    expired.expires = Some(NOW - 1);
+    cache.put(&expired).unwrap();
+
+    fs::write(dir.path().join("notes.txt"), "keep me").unwrap();
+
+    (dir, cache)
+}
+
+fn keys(cache: &Cache) -> Vec<String> {
+    cache.entries().into_iter().map(|(_, e)| e.key).collect()
+}
+
+#[test]
+fn test_put_and_get_roundtrip() {
+    let (_dir, cache) = populated();
+
+    let found = cache
+        .get(Namespace::Response, "weatherapi:now:kyiv")
+        .unwrap();
+    assert_eq!(found.city, "Kyiv");
+    assert!(cache
+        .get(Namespace::Geocode, "weatherapi:now:kyiv")
+        .is_none());
+}
+
+#[test]
+fn test_stats_counts_only_cache_files() {
+    let (_dir, cache) = populated();
+    cache.record_hit();
+    cache.record_hit();
+    cache.record_hit();
+    cache.record_miss();
+
+    let stats = cache.stats();
+
+    assert_eq!(stats.entries, 4);
+    assert!(stats.total_bytes > 0);
+    assert_eq!(stats.hit_rate(), Some(0.75));
+
+    let out = run_cache_command(&cache, CacheCommand::Stats, NOW).unwrap();
+    assert!(out.contains("entries:  4"));
+    assert!(out.contains("75.0% (3 hits, 1 misses)"));
+}
+
+#[test]
+fn test_stats_on_missing_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path().join("missing"));
+
+    assert_eq!(cache.stats().entries, 0);
+    let out = run_cache_command(&cache, CacheCommand::Stats, NOW).unwrap();
+    assert!(out.contains("hit rate: n/a"));
+}
+
+#[test]
+fn test_list_with_provider_filter() {
+    let (_dir, cache) = populated();
+
+    let out = run_cache_command(&cache, CacheCommand::List { provider: None }, NOW).unwrap();
+    assert_eq!(out.lines().count(), 5);
+    assert_eq!(
+        out.lines().next(),
+        Some("CACHE     PROVIDER     LOCATION  KIND     AGE           SIZE")
+    );
+    assert!(out.contains("response  weatherapi   Kyiv      now      2m            1"));
+    assert!(out.contains("geocode   weatherapi   Kyiv      geocode  2m"));
+    assert!(out.contains("stale     weatherapi   Rome      now      2m (expired)"));
+
+    let out = run_cache_command(
+        &cache,
+        CacheCommand::List {
+            provider: Some("openweather".into()),
+        },
+        NOW,
+    )
+    .unwrap();
+    // The header and one entry.
+    assert_eq!(out.trim_end().lines().collect::<Vec<_>>().len(), 2);
+    assert!(out.contains("response  openweather  Lviv      now"));
+}
+
+#[test]
+fn test_clear_expired() {
+    let (_dir, cache) = populated();
+
+    assert_eq!(cache.clear(&ClearFilter::Expired, NOW).unwrap(), 1);
+    assert!(!keys(&cache).contains(&"weatherapi:now:rome".to_string()));
+    assert_eq!(keys(&cache).len(), 3);
+}
+
+#[test]
+fn test_clear_city_is_case_insensitive() {
+    let (_dir, cache) = populated();
+
+    assert_eq!(
+        cache.clear(&ClearFilter::City("kyiv".into()), NOW).unwrap(),
+        2
+    );
+    assert_eq!(
+        keys(&cache),
+        vec!["openweather:now:lviv", "weatherapi:now:rome"]
+    );
+}
+
+#[test]
+fn test_list_skips_unreadable_files() {
+    let (dir, cache) = populated();
+    fs::write(dir.path().join("response-0000000000000000.wcache"), "{old").unwrap();
+
+    let out = run_cache_command(&cache, CacheCommand::List { provider: None }, NOW).unwrap();
+    assert_eq!(out.lines().count(), 5);
+
+    let empty = Cache::new(dir.path().join("missing"));
+    let out = run_cache_command(&empty, CacheCommand::List { provider: None }, NOW).unwrap();
+    assert_eq!(out, "No cached entries.\n");
+}
+
+#[test]
+fn test_clear_older_than() {
+    let (_dir, cache) = populated();
+    let mut old = entry(Namespace::Response, "wttr:now:oslo", "wttr", "Oslo");
+    old.created = NOW - 7200;
+    cache.put(&old).unwrap();
+
+    assert_eq!(cache.clear(&ClearFilter::OlderThan(3600), NOW).unwrap(), 1);
+    assert_eq!(keys(&cache).len(), 4);
+    assert_eq!(cache.clear(&ClearFilter::OlderThan(120), NOW).unwrap(), 4);
+
+    let cli = Cli::try_parse_from(vec!["wapp", "cache", "clear", "--older-than", "1h30m"]).unwrap();
+    match cli.cmd {
+        Commands::Cache {
+            cmd: CacheCommand::Clear { older_than, .. },
+        } => assert_eq!(older_than, Some(5400)),
+        _ => panic!("wrong command parsed"),
+    }
+    assert!(Cli::try_parse_from(vec!["wapp", "cache", "clear", "--older-than", "1w"]).is_err());
+}
+
+#[test]
+fn test_parse_age() {
+    assert_eq!(parse_age("90s").unwrap(), 90);
+    assert_eq!(parse_age("15m").unwrap(), 900);
+    assert_eq!(parse_age(" 2h ").unwrap(), 7200);
+    assert_eq!(parse_age("1d").unwrap(), 86_400);
+    assert_eq!(parse_age("1h30m").unwrap(), 5400);
+
+    for invalid in ["", "h", "15", "2 h", "-1m", "1.5h"] {
+        assert!(parse_age(invalid).is_err(), "{}", invalid);
+    }
+    assert_eq!(
+        parse_age("1w").unwrap_err().to_string(),
+        "invalid duration '1w': expected e.g. 90s, 15m, 2h"
+    );
+}
+
+#[test]
+fn test_format_size() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KB");
+    assert_eq!(format_size(2 * 1024 * 1024), "2.0 MB");
+}
+
+#[test]
+fn test_clear_geocode() {
+    let (_dir, cache) = populated();
+
+    assert_eq!(cache.clear(&ClearFilter::Geocode, NOW).unwrap(), 1);
+    assert!(!keys(&cache).contains(&"geocode:kyiv".to_string()));
+}
+
+#[test]
+fn test_clear_all_keeps_unrelated_files() {
+    let (dir, cache) = populated();
+    cache.record_miss();
+
+    let out = run_cache_command(
+        &cache,
+        CacheCommand::Clear {
+            all: true,
+            expired: false,
+            city: None,
+            older_than: None,
+            geocode: false,
+        },
+        NOW,
+    )
+    .unwrap();
+
+    assert_eq!(out, "Removed 4 cache entries\n");
+    let remaining: Vec<_> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(remaining, vec!["notes.txt"]);
+}
+
+#[test]
+fn test_path_prints_directory() {
+    let (dir, cache) = populated();
+
+    let out = run_cache_command(&cache, CacheCommand::Path, NOW).unwrap();
+    assert_eq!(out, format!("{}\n", dir.path().display()));
+}
+
+#[test]
+fn test_clear_requires_a_selection() {
+    assert!(Cli::try_parse_from(vec!["wapp", "cache", "clear"]).is_err());
+    assert!(Cli::try_parse_from(vec!["wapp", "cache", "clear", "--all", "--expired"]).is_err());
+
+    let cli = Cli::try_parse_from(vec!["wapp", "cache", "clear", "--city", "Kyiv"]).unwrap();
+    match cli.cmd {
+        Commands::Cache {
+            cmd: CacheCommand::Clear { city, .. },
+        } => assert_eq!(city.as_deref(), Some("Kyiv")),
+        _ => panic!("wrong command parsed"),
+    }
+}
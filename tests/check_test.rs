@@ -0,0 +1,300 @@
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+use wapp::check::{days_for, evaluate, parse_within, CheckCondition};
+use wapp::cli::{Cli, Commands};
+use wapp::providers::{ApiProvider, WeatherApiProvider};
+use wapp::series::SeriesPoint;
+
+const FIXTURE: &str = include_str!("fixtures/weatherapi_forecast.json");
+
+/// 2024-06-10 11:00 UTC, the hour of the fixture's third forecast record.
+fn now() -> DateTime<Utc> {
+    DateTime::from_timestamp(1718017200, 0).unwrap()
+}
+
+fn points() -> Vec<SeriesPoint> {
+    let provider = WeatherApiProvider {
+        api_key: "dummy".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    provider.series(FIXTURE).unwrap()
+}
+
+fn check(condition: &str, within: &str) -> (bool, String) {
+    let verdict = evaluate(
+        condition.parse().unwrap(),
+        &points(),
+        "London",
+        now(),
+        parse_within(within).unwrap(),
+    );
+    (verdict.holds, verdict.line)
+}
+
+#[test]
+fn test_parse_conditions() {
+    assert_eq!(
+        "rain".parse::<CheckCondition>().unwrap(),
+        CheckCondition::Rain
+    );
+    assert_eq!(
+        " Snow ".parse::<CheckCondition>().unwrap(),
+        CheckCondition::Snow
+    );
+    assert_eq!(
+        "temp-above:25".parse::<CheckCondition>().unwrap(),
+        CheckCondition::TempAbove(25.0)
+    );
+    assert_eq!(
+        "temp-below:-5.5".parse::<CheckCondition>().unwrap(),
+        CheckCondition::TempBelow(-5.5)
+    );
+    assert_eq!(
+        "wind-above:40".parse::<CheckCondition>().unwrap(),
+        CheckCondition::WindAbove(40.0)
+    );
+    for text in ["rain>50%", "Rain > 50 %", "rain>50"] {
+        assert_eq!(
+            text.parse::<CheckCondition>().unwrap(),
+            CheckCondition::RainChance(50.0),
+            "{}",
+            text
+        );
+    }
+}
+
+#[test]
+fn test_malformed_conditions() {
+    let err = |text: &str| text.parse::<CheckCondition>().unwrap_err().to_string();
+
+    assert_eq!(
+        err("hail"),
+        "unknown condition 'hail'. Supported: rain, rain>N%, snow, temp-above:N, temp-below:N, wind-above:N"
+    );
+    assert_eq!(
+        err("temp-above"),
+        "condition 'temp-above' needs a threshold, e.g. temp-above:25"
+    );
+    assert_eq!(
+        err("temp-above:"),
+        "condition 'temp-above' needs a threshold, e.g. temp-above:25"
+    );
+    assert_eq!(
+        err("wind-above:fast"),
+        "'fast' in condition 'wind-above:fast' is not a number"
+    );
+    assert_eq!(err("rain:5"), "condition 'rain' takes no threshold");
+    assert_eq!(
+        err("rain>150%"),
+        "'150%' in condition 'rain>150%' is not a percentage from 0 to 100, e.g. rain>50%"
+    );
+    assert!(err("rain>likely").contains("'likely'"));
+}
+
+#[test]
+fn test_parse_within() {
+    assert_eq!(parse_within("12h").unwrap(), Duration::hours(12));
+    assert_eq!(parse_within("90m").unwrap(), Duration::minutes(90));
+    assert_eq!(parse_within("2d").unwrap(), Duration::days(2));
+    assert_eq!(parse_within("6").unwrap(), Duration::hours(6));
+
+    for text in ["", "h", "0h", "12w", "-3h"] {
+        assert_eq!(
+            parse_within(text).unwrap_err().to_string(),
+            format!("invalid window '{}'; expected e.g. 90m, 12h or 2d", text)
+        );
+    }
+}
+
+#[test]
+fn test_days_cover_the_window() {
+    assert_eq!(days_for(Duration::minutes(90)), 2);
+    assert_eq!(days_for(Duration::hours(12)), 2);
+    assert_eq!(days_for(Duration::hours(24)), 2);
+    assert_eq!(days_for(Duration::hours(25)), 3);
+}
+
+#[test]
+fn test_rain() {
+    assert_eq!(
+        check("rain", "6h"),
+        (false, "London: no rain expected within 6h".into())
+    );
+    assert_eq!(
+        check("rain", "14h"),
+        (
+            true,
+            "London: rain expected at 2024-06-10 23:00 UTC (0.6 mm)".into()
+        )
+    );
+}
+
+#[test]
+fn test_rain_chance() {
+    assert_eq!(
+        check("rain>50%", "6h"),
+        (
+            false,
+            "London: no chance of rain above 50% expected within 6h".into()
+        )
+    );
+    assert_eq!(
+        check("rain>50%", "14h"),
+        (
+            true,
+            "London: chance of rain above 50% expected at 2024-06-10 23:00 UTC (78% chance)".into()
+        )
+    );
+    assert_eq!(
+        check("rain>10%", "1h"),
+        (
+            true,
+            "London: chance of rain above 10% expected at 2024-06-10 11:00 UTC (12% chance)".into()
+        )
+    );
+
+    // An hour without a chance is unknown, not dry or wet.
+    let mut point = SeriesPoint::at(now());
+    point.precip_mm = Some(3.0);
+    point.condition = Some("Heavy rain".into());
+    let points = [point];
+    assert!(
+        !evaluate(
+            CheckCondition::RainChance(0.0),
+            &points,
+            "Oslo",
+            now(),
+            Duration::hours(1)
+        )
+        .holds
+    );
+}
+
+#[test]
+fn test_snow() {
+    assert_eq!(
+        check("snow", "2d"),
+        (false, "London: no snow expected within 2d".into())
+    );
+
+    let mut point = SeriesPoint::at(now());
+    point.condition = Some("Light snow".into());
+    point.precip_mm = Some(1.2);
+    let points = [point];
+
+    let snow = evaluate(
+        CheckCondition::Snow,
+        &points,
+        "Oslo",
+        now(),
+        Duration::hours(1),
+    );
+    assert!(snow.holds);
+    assert_eq!(
+        snow.line,
+        "Oslo: snow expected at 2024-06-10 11:00 UTC (Light snow)"
+    );
+    // Snowfall is not rain.
+    assert!(
+        !evaluate(
+            CheckCondition::Rain,
+            &points,
+            "Oslo",
+            now(),
+            Duration::hours(1)
+        )
+        .holds
+    );
+}
+
+#[test]
+fn test_temperature() {
+    assert_eq!(
+        check("temp-above:17", "12h"),
+        (
+            true,
+            "London: temperature above 17 °C expected at 2024-06-10 11:00 UTC (17.1 °C)".into()
+        )
+    );
+    assert!(!check("temp-above:18", "24h").0);
+    assert!(check("temp-above:18", "2d").0);
+
+    assert_eq!(
+        check("temp-below:12", "24h"),
+        (
+            false,
+            "London: no temperature below 12 °C expected within 1d".into()
+        )
+    );
+    assert_eq!(
+        check("temp-below:12", "2d"),
+        (
+            true,
+            "London: temperature below 12 °C expected at 2024-06-11 23:00 UTC (11.3 °C)".into()
+        )
+    );
+}
+
+#[test]
+fn test_wind() {
+    assert_eq!(
+        check("wind-above:18", "12h"),
+        (
+            true,
+            "London: wind above 18 km/h expected at 2024-06-10 17:00 UTC (19 km/h)".into()
+        )
+    );
+    assert!(!check("wind-above:20", "2d").0);
+}
+
+#[test]
+fn test_past_hours_are_ignored() {
+    // The 05:00 record is 11.4 °C; only the current hour onwards counts.
+    assert!(!check("temp-below:12", "5h").0);
+}
+
+#[test]
+fn test_parse_check() {
+    let cli = Cli::try_parse_from(vec![
+        "wapp",
+        "check",
+        "--city",
+        "Kyiv",
+        "--condition",
+        "temp-below:0",
+        "--within",
+        "12h",
+        "-q",
+    ])
+    .unwrap();
+
+    match cli.cmd {
+        Commands::Check {
+            city,
+            condition,
+            within,
+            provider,
+            quiet,
+        } => {
+            assert_eq!(city.to_string(), "Kyiv");
+            assert_eq!(condition, CheckCondition::TempBelow(0.0));
+            assert_eq!(within, Duration::hours(12));
+            assert_eq!(provider, None);
+            assert!(quiet);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    let parsed = Cli::try_parse_from(vec![
+        "wapp",
+        "check",
+        "--city",
+        "Kyiv",
+        "--condition",
+        "hail",
+    ]);
+    assert!(parsed.is_err_and(|e| e.to_string().contains("unknown condition 'hail'")));
+}
@@ -1,14 +1,72 @@
+use chrono::NaiveDate;
 use clap::Parser;
-use wapp::cli::{Cli, Commands};
+use wapp::cli::{run_location_command, Cli, Commands, ConfigCommand, ListFormat};
+use wapp::config::AppConfig;
+use wapp::location::CityName;
+use wapp::providers::{DataKind, ProviderKind};
 
 #[test]
 fn test_parse_configure() {
     let cli = Cli::parse_from(vec!["wapp", "configure", "weatherapi"]);
 
     match cli.cmd {
-        Commands::Configure { provider } => assert_eq!(provider, "weatherapi"),
+        Commands::Configure { provider, .. } => {
+            assert_eq!(provider, vec![ProviderKind::WeatherApi])
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_parse_configure_fallback_chain() {
+    let cli = Cli::parse_from(vec!["wapp", "configure", "weatherapi", "openweather"]);
+
+    match cli.cmd {
+        Commands::Configure { provider, .. } => {
+            assert_eq!(
+                provider,
+                vec![ProviderKind::WeatherApi, ProviderKind::OpenWeather]
+            )
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_parse_configure_default_city() {
+    let cli = Cli::parse_from(vec!["wapp", "configure", "--default-city", "Kyiv"]);
+    match cli.cmd {
+        Commands::Configure {
+            provider,
+            default_city,
+            ..
+        } => {
+            assert!(provider.is_empty());
+            assert_eq!(default_city, Some(CityName::new("Kyiv").unwrap()));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    // Without arguments `configure` asks interactively.
+    match Cli::parse_from(vec!["wapp", "configure", "--no-verify"]).cmd {
+        Commands::Configure {
+            provider,
+            default_city,
+            no_verify,
+            ..
+        } => assert!(provider.is_empty() && default_city.is_none() && no_verify),
         _ => panic!("wrong command parsed"),
     }
+    assert!(Cli::try_parse_from(vec!["wapp", "configure", "wttr", "--no-verify"]).is_ok());
+    assert!(Cli::try_parse_from(vec![
+        "wapp",
+        "configure",
+        "--default-city",
+        "Kyiv",
+        "--api-key",
+        "k"
+    ])
+    .is_err());
 }
 
 #[test]
@@ -18,10 +76,665 @@ fn test_parse_get() {
     ]);
 
     match cli.cmd {
-        Commands::Get { city, data } => {
-            assert_eq!(city.unwrap(), "New York");
-            assert_eq!(data, "forecast");
+        Commands::Get { city, data, .. } => {
+            assert_eq!(city[0].as_str(), "New York");
+            assert_eq!(data, vec![DataKind::Forecast]);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_parse_get_days() {
+    let cli = Cli::parse_from(vec![
+        "wapp", "get", "--city", "Rome", "--data", "forecast", "--days", "5",
+    ]);
+
+    match cli.cmd {
+        Commands::Get { days, .. } => assert_eq!(days, Some(5)),
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_parse_forecast() {
+    let cli = Cli::parse_from(vec!["wapp", "forecast", "Kyiv", "--days", "5", "--hourly"]);
+
+    match cli.cmd {
+        Commands::Forecast {
+            city, days, hourly, ..
+        } => {
+            assert_eq!(city.as_str(), "Kyiv");
+            assert_eq!(days, Some(5));
+            assert!(hourly);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_parse_forecast_date_range() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "forecast",
+        "Kyiv",
+        "--from",
+        "2025-06-02",
+        "--to",
+        "2025-06-04",
+    ]);
+
+    match cli.cmd {
+        Commands::Forecast { from, to, .. } => {
+            assert_eq!(from.unwrap().to_string(), "2025-06-02");
+            assert_eq!(to.unwrap().to_string(), "2025-06-04");
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_forecast_days_conflicts_with_range() {
+    let result = Cli::try_parse_from(vec![
+        "wapp",
+        "forecast",
+        "Kyiv",
+        "--days",
+        "3",
+        "--from",
+        "2025-06-02",
+    ]);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_forecast_rejects_invalid_date() {
+    assert!(Cli::try_parse_from(vec!["wapp", "forecast", "Kyiv", "--from", "June 2"]).is_err());
+}
+
+#[test]
+fn test_now_parse_positional_city_and_overrides() {
+    let cli = Cli::try_parse_from(vec![
+        "wapp", "now", "Kyiv", "--units", "imperial", "--lang", "uk",
+    ])
+    .unwrap();
+
+    match cli.cmd {
+        Commands::Now {
+            city, units, lang, ..
+        } => {
+            assert_eq!(city.as_ref().map(CityName::as_str), Some("Kyiv"));
+            assert_eq!(units.as_deref(), Some("imperial"));
+            assert_eq!(lang.as_deref(), Some("uk"));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_now_parse_without_city() {
+    let cli = Cli::try_parse_from(vec!["wapp", "now"]).unwrap();
+
+    match cli.cmd {
+        Commands::Now { city, .. } => assert!(city.is_none()),
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_now_parse_minimal() {
+    let cli = Cli::try_parse_from(vec!["wapp", "now", "Kyiv", "--minimal"]).unwrap();
+
+    match cli.cmd {
+        Commands::Now {
+            format, minimal, ..
+        } => {
+            assert_eq!(format, None);
+            assert!(minimal);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    // A single line has no format to choose.
+    assert!(Cli::try_parse_from(vec!["wapp", "now", "--minimal", "--format", "json"]).is_err());
+}
+
+#[test]
+fn test_get_parse_provider_and_api_key() {
+    let cli = Cli::try_parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--provider",
+        "weatherapi",
+        "--api-key",
+        "k123",
+    ])
+    .unwrap();
+
+    match cli.cmd {
+        Commands::Get {
+            provider, api_key, ..
+        } => {
+            assert_eq!(provider, Some(ProviderKind::WeatherApi));
+            assert_eq!(api_key.as_deref(), Some("k123"));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_get_rejects_unknown_provider() {
+    let result = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--provider", "x"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_get_day_conflicts_with_days() {
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--city", "Rome", "--day", "2"]).unwrap();
+    match cli.cmd {
+        Commands::Get { day, .. } => assert_eq!(day, Some(2)),
+        _ => panic!("wrong command parsed"),
+    }
+
+    let result = Cli::try_parse_from(vec![
+        "wapp", "get", "--city", "Rome", "--day", "2", "--days", "3",
+    ]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_get_parse_coordinates() {
+    let cli =
+        Cli::try_parse_from(vec!["wapp", "get", "--lat", "-33.87", "--lon", "151.21"]).unwrap();
+    match cli.cmd {
+        Commands::Get { city, lat, lon, .. } => {
+            assert!(city.is_empty());
+            assert_eq!((lat, lon), (Some(-33.87), Some(151.21)));
         }
         _ => panic!("wrong command parsed"),
     }
 }
+
+#[test]
+fn test_get_coordinates_need_both_and_exclude_city() {
+    for args in [
+        vec!["wapp", "get", "--lat", "50"],
+        vec!["wapp", "get", "--lon", "30"],
+        vec![
+            "wapp", "get", "--city", "Kyiv", "--lat", "50", "--lon", "30",
+        ],
+    ] {
+        assert!(Cli::try_parse_from(args.clone()).is_err(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_get_parse_zip_and_iata() {
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--zip", "10001,us"]).unwrap();
+    match cli.cmd {
+        Commands::Get { zip, iata, .. } => assert_eq!((zip, iata), (Some("10001,us".into()), None)),
+        _ => panic!("wrong command parsed"),
+    }
+
+    for args in [
+        vec!["wapp", "get", "--zip", "10001", "--city", "Kyiv"],
+        vec![
+            "wapp", "get", "--zip", "10001", "--lat", "50", "--lon", "30",
+        ],
+        vec!["wapp", "get", "--iata", "LHR", "--zip", "10001"],
+        vec!["wapp", "get", "--iata", "LHR", "--city", "London"],
+    ] {
+        assert!(Cli::try_parse_from(args.clone()).is_err(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_get_parse_multiple_kinds() {
+    for args in [
+        vec![
+            "wapp",
+            "get",
+            "--city",
+            "Kyiv",
+            "--data",
+            "now,tomorrow,hourly",
+        ],
+        vec![
+            "wapp", "get", "--city", "Kyiv", "--data", "now", "--data", "tomorrow", "--data",
+            "hourly",
+        ],
+    ] {
+        match Cli::try_parse_from(args).unwrap().cmd {
+            Commands::Get { data, .. } => assert_eq!(
+                data,
+                vec![DataKind::Now, DataKind::Tomorrow, DataKind::Hourly]
+            ),
+            _ => panic!("wrong command parsed"),
+        }
+    }
+}
+
+#[test]
+fn test_get_rejects_unknown_kind_with_the_valid_ones() {
+    let Err(err) = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--data", "forcast"])
+    else {
+        panic!("a typo in --data was accepted");
+    };
+    let err = err.to_string();
+
+    assert!(err.contains("unknown data kind 'forcast'"), "{}", err);
+    assert!(
+        err.contains("now, forecast, tomorrow, weekend, hourly"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_get_parse_kind_ignores_case_and_accepts_dates() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--data",
+        "Hourly,2024-01-15",
+    ]);
+
+    match cli.cmd {
+        Commands::Get { data, .. } => {
+            assert_eq!(data[0], DataKind::Hourly);
+            assert_eq!(data[1].to_string(), "2024-01-15");
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_get_parse_date_and_date_range() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--date",
+        "2024-06-01",
+    ]);
+    match cli.cmd {
+        Commands::Get { date, .. } => {
+            assert_eq!(date, NaiveDate::from_ymd_opt(2024, 6, 1))
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--date-range",
+        "2024-06-01..2024-06-03",
+    ]);
+    match cli.cmd {
+        Commands::Get { date_range, .. } => {
+            let kinds = wapp::cli::history_kinds(date_range.unwrap()).unwrap();
+            assert_eq!(
+                kinds.iter().map(|k| k.to_string()).collect::<Vec<_>>(),
+                ["2024-06-01", "2024-06-02", "2024-06-03"]
+            );
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    for args in [
+        vec!["wapp", "get", "--date", "June 1"],
+        vec!["wapp", "get", "--date", "2024-06-01", "--days", "2"],
+        vec!["wapp", "get", "--date-range", "2024-06-03..2024-06-01"],
+        vec!["wapp", "get", "--date-range", "2024-06-01"],
+        vec![
+            "wapp",
+            "get",
+            "--date-range",
+            "2024-06-01..2024-06-02",
+            "--date",
+            "2024-06-01",
+        ],
+    ] {
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+}
+
+#[test]
+fn test_history_kinds_are_limited_to_a_month() {
+    let range: wapp::request::DateWindow = "2024-06-01..2024-07-01".parse().unwrap();
+    assert_eq!(wapp::cli::history_kinds(range).unwrap().len(), 31);
+
+    let range: wapp::request::DateWindow = "2024-06-01..2024-07-02".parse().unwrap();
+    let err = wapp::cli::history_kinds(range).unwrap_err().to_string();
+    assert!(err.contains("covers 32 days"), "{}", err);
+}
+
+#[test]
+fn test_get_parse_hours() {
+    let cli = Cli::parse_from(vec![
+        "wapp", "get", "--city", "Kyiv", "--data", "hourly", "--hours", "6",
+    ]);
+    match cli.cmd {
+        Commands::Get { hours, .. } => assert_eq!(hours, Some(6)),
+        _ => panic!("wrong command parsed"),
+    }
+
+    for args in [
+        vec!["wapp", "get", "--data", "hourly", "--hours", "0"],
+        vec![
+            "wapp", "get", "--data", "hourly", "--hours", "6", "--days", "2",
+        ],
+        vec![
+            "wapp", "get", "--data", "hourly", "--hours", "6", "--day", "1",
+        ],
+    ] {
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+}
+
+#[test]
+fn test_invalid_city_is_rejected_at_parse_time() {
+    for args in [
+        vec!["wapp", "now", "   "],
+        vec!["wapp", "get", "--city", "Kyiv\nLviv"],
+        vec!["wapp", "forecast", "?!"],
+    ] {
+        match Cli::try_parse_from(args) {
+            Err(e) => assert!(e.to_string().contains("city name"), "{}", e),
+            Ok(_) => panic!("invalid city accepted"),
+        }
+    }
+}
+
+#[test]
+fn test_param_flag_is_repeatable() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--param",
+        "aqi=yes",
+        "--param",
+        "alerts=yes",
+    ]);
+
+    match cli.cmd {
+        Commands::Get { params, .. } => assert_eq!(
+            params,
+            vec![
+                ("aqi".to_string(), "yes".to_string()),
+                ("alerts".to_string(), "yes".to_string())
+            ]
+        ),
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["wapp", "now", "Kyiv", "--param", "appid=x"]).is_err());
+}
+
+#[test]
+fn test_stats_flag_is_global() {
+    let cli = Cli::parse_from(vec!["wapp", "now", "Kyiv", "--stats"]);
+    assert!(cli.stats);
+
+    let cli = Cli::parse_from(vec![
+        "wapp", "--stats", "export", "--city", "Kyiv", "--output", "k.csv",
+    ]);
+    assert!(cli.stats);
+}
+
+#[test]
+fn test_parse_history_export() {
+    use wapp::cli::HistoryCommand;
+
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "history",
+        "export",
+        "--city",
+        "Kyiv",
+        "--since",
+        "2025-01-01",
+        "--until",
+        "2025-06-30",
+        "--output",
+        "-",
+    ]);
+
+    match cli.cmd {
+        Commands::History {
+            cmd:
+                Some(HistoryCommand::Export {
+                    city,
+                    since,
+                    until,
+                    output,
+                    ..
+                }),
+            ..
+        } => {
+            assert_eq!(city.unwrap().as_str(), "Kyiv");
+            assert_eq!(since.unwrap().to_string(), "2025-01-01");
+            assert_eq!(until.unwrap().to_string(), "2025-06-30");
+            assert_eq!(output.to_str(), Some("-"));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_location_commands() {
+    let mut cfg = AppConfig::default();
+    let parse = |args: &[&str]| match Cli::try_parse_from(args).unwrap().cmd {
+        Commands::Location { cmd } => cmd,
+        _ => panic!("wrong command parsed"),
+    };
+
+    let out = run_location_command(
+        &mut cfg,
+        parse(&["wapp", "location", "add", "home", "Kyiv"]),
+    );
+    assert_eq!(out.unwrap(), "Saved @home as Kyiv\n");
+
+    let add_office = [
+        "wapp", "location", "add", "office", "--lat", "50.45", "--lon", "-30.52",
+    ];
+    run_location_command(&mut cfg, parse(&add_office)).unwrap();
+    assert!(run_location_command(&mut cfg, parse(&add_office)).is_err());
+
+    let out = run_location_command(&mut cfg, parse(&["wapp", "location", "list"])).unwrap();
+    assert_eq!(out, "@home    Kyiv\n@office  50.45,-30.52\n");
+
+    let out = run_location_command(&mut cfg, parse(&["wapp", "location", "remove", "home"]));
+    assert_eq!(out.unwrap(), "Removed @home\n");
+    assert_eq!(cfg.locations.len(), 1);
+
+    let bad = parse(&["wapp", "location", "add", "x", "--lat", "91", "--lon", "0"]);
+    assert!(run_location_command(&mut cfg, bad).is_err());
+    assert!(Cli::try_parse_from(["wapp", "location", "add", "x"]).is_err());
+    assert!(Cli::try_parse_from([
+        "wapp", "location", "add", "x", "Kyiv", "--lat", "1", "--lon", "2"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_parse_get_several_cities() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--city",
+        "Lviv",
+        "--concurrency",
+        "2",
+    ]);
+
+    match cli.cmd {
+        Commands::Get {
+            city, concurrency, ..
+        } => {
+            let names: Vec<&str> = city.iter().map(|c| c.as_str()).collect();
+            assert_eq!(names, vec!["Kyiv", "Lviv"]);
+            assert_eq!(concurrency, 2);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(
+        Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--concurrency", "0"]).is_err()
+    );
+}
+
+#[test]
+fn test_parse_get_cities_from() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--cities-from",
+        "-",
+        "--data",
+        "forecast",
+    ]);
+
+    match cli.cmd {
+        Commands::Get { cities_from, .. } => {
+            assert_eq!(cities_from, Some(std::path::PathBuf::from("-")))
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec![
+        "wapp",
+        "get",
+        "--cities-from",
+        "cities.txt",
+        "--lat",
+        "1",
+        "--lon",
+        "2"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_get_provider_flag_ignores_case() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Oslo",
+        "--provider",
+        "OpenWeather",
+    ]);
+
+    match cli.cmd {
+        Commands::Get { provider, .. } => assert_eq!(provider, Some(ProviderKind::OpenWeather)),
+        _ => panic!("wrong command parsed"),
+    }
+
+    let err = Cli::try_parse_from(vec!["wapp", "get", "--provider", "darksky"])
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("weatherapi"));
+    assert!(err.contains("wttr"));
+}
+
+#[test]
+fn test_parse_providers() {
+    let cli = Cli::try_parse_from(vec!["wapp", "providers", "--output", "json"]).unwrap();
+
+    match cli.cmd {
+        Commands::Providers { output } => assert_eq!(output, ListFormat::Json),
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_parse_config_set() {
+    let cli = Cli::try_parse_from(vec!["wapp", "config", "set", "default_city", "Kyiv"]).unwrap();
+
+    match cli.cmd {
+        Commands::Config {
+            cmd: ConfigCommand::Set { key, value },
+        } => assert_eq!((key.as_str(), value.as_str()), ("default_city", "Kyiv")),
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["wapp", "config", "unset"]).is_err());
+}
+
+#[test]
+fn test_parse_verbose() {
+    assert_eq!(Cli::parse_from(vec!["wapp", "-v", "paths"]).verbose, 1);
+    assert_eq!(
+        Cli::parse_from(vec!["wapp", "paths", "--verbose"]).verbose,
+        1
+    );
+    assert_eq!(Cli::parse_from(vec!["wapp", "-vv", "paths"]).verbose, 2);
+    assert_eq!(Cli::parse_from(vec!["wapp", "-vvv", "paths"]).verbose, 3);
+    assert_eq!(Cli::parse_from(vec!["wapp", "paths"]).verbose, 0);
+}
+
+#[test]
+fn test_parse_configure_units_and_lang() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "configure",
+        "openweather",
+        "--units",
+        "Imperial",
+        "--lang",
+        "uk",
+    ]);
+    match cli.cmd {
+        Commands::Configure { units, lang, .. } => {
+            assert_eq!(units.as_deref(), Some("imperial"));
+            assert_eq!(lang.as_deref(), Some("uk"));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["wapp", "configure", "owm", "--units", "kelvin"]).is_err());
+}
+
+#[test]
+fn test_parse_configure_keys() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "configure",
+        "owm",
+        "--key",
+        "k123",
+        "--keyring",
+    ]);
+    match cli.cmd {
+        Commands::Configure {
+            api_key, keyring, ..
+        } => {
+            assert_eq!(api_key.as_deref(), Some("k123"));
+            assert!(keyring);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    let cli = Cli::parse_from(vec!["wapp", "configure", "--remove-key", "OWM"]);
+    match cli.cmd {
+        Commands::Configure { remove_key, .. } => {
+            assert_eq!(remove_key, Some(ProviderKind::OpenWeather))
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["wapp", "configure", "owm", "--keyring"]).is_err());
+    assert!(Cli::try_parse_from(vec!["wapp", "configure", "owm", "--remove-key", "owm"]).is_err());
+}
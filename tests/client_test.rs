@@ -0,0 +1,162 @@
+mod mocks;
+
+use mocks::http_server::fast_retries;
+use wapp::config::{AppConfig, SavedLocation};
+use wapp::location::Location;
+use wapp::providers::testing::MockProvider;
+use wapp::providers::{DataKind, EnvSource, ProviderKind, ProviderOverrides};
+use wapp::request::WeatherRequest;
+use wapp::Client;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+const OPENWEATHER_CURRENT: &str = include_str!("fixtures/openweather_current.json");
+
+/// A server answering `GET route` with `status` and `body`.
+async fn serve(route: &str, status: u16, body: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(status).set_body_string(body))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// A client of `config`'s providers with exactly the environment `vars`.
+fn client(config: AppConfig, vars: &[(&str, String)]) -> Client {
+    let overrides = ProviderOverrides {
+        env: EnvSource::from_vars(vars.iter().cloned()),
+        ..Default::default()
+    };
+    Client::with_overrides(config, &overrides).unwrap()
+}
+
+/// A client of WeatherAPI at `server`.
+fn weatherapi(server: &MockServer, config: AppConfig) -> Client {
+    client(
+        AppConfig {
+            provider: ProviderKind::WeatherApi,
+            ..config
+        },
+        &[
+            ("WEATHERAPI_KEY", "KEY".into()),
+            ("WEATHERAPI_BASE_URL", server.uri()),
+        ],
+    )
+}
+
+#[tokio::test]
+async fn test_current() {
+    let server = serve("/current.json", 200, WEATHERAPI_CURRENT).await;
+
+    let now = weatherapi(&server, AppConfig::default())
+        .current("Kyiv")
+        .await
+        .unwrap();
+
+    assert_eq!(now.temp_c, Some(24.0));
+    assert_eq!(now.condition.as_deref(), Some("Sunny"));
+    assert!(now.forecast.is_empty());
+}
+
+#[tokio::test]
+async fn test_forecast_and_tomorrow() {
+    let server = serve("/forecast.json", 200, WEATHERAPI_FORECAST).await;
+    let client = weatherapi(&server, AppConfig::default());
+
+    let forecast = client.forecast("Kyiv", 2).await.unwrap();
+    let dates: Vec<String> = forecast
+        .forecast
+        .iter()
+        .map(|day| day.date.to_string())
+        .collect();
+    assert_eq!(dates, vec!["2024-06-10", "2024-06-11"]);
+
+    let tomorrow = client.tomorrow("Kyiv").await.unwrap();
+    assert_eq!(tomorrow.forecast.len(), 1);
+    assert_eq!(tomorrow.forecast[0].date.to_string(), "2024-06-11");
+}
+
+#[tokio::test]
+async fn test_saved_locations_are_resolved() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .and(query_param("q", "Lviv"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(WEATHERAPI_CURRENT))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut config = AppConfig::default();
+    config
+        .add_location(
+            "home",
+            SavedLocation::City {
+                city: "Lviv".into(),
+            },
+            false,
+        )
+        .unwrap();
+
+    let client = weatherapi(&server, config);
+    client.current("@home").await.unwrap();
+
+    let err = client.current("@nowhere").await.unwrap_err();
+    assert!(err.to_string().contains("nowhere"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_fallback_provider_serves_an_outage() {
+    fast_retries();
+    let down = serve("/current.json", 503, "busy").await;
+    let up = serve("/data/2.5/weather", 200, OPENWEATHER_CURRENT).await;
+
+    let client = client(
+        AppConfig {
+            provider: ProviderKind::WeatherApi,
+            fallback_providers: vec![ProviderKind::OpenWeather],
+            ..Default::default()
+        },
+        &[
+            ("WEATHERAPI_KEY", "KEY".into()),
+            ("WEATHERAPI_BASE_URL", down.uri()),
+            ("OPENWEATHER_KEY", "KEY".into()),
+            ("OPENWEATHER_BASE_URL", format!("{}/data/2.5", up.uri())),
+        ],
+    );
+    assert_eq!(client.provider().name(), "weatherapi");
+
+    let request = WeatherRequest::at(Location::from("Kyiv"), DataKind::Now, None);
+    let (provider, body) = client.fetch(request).await.unwrap();
+
+    assert_eq!(provider.name(), "openweather");
+    assert_eq!(body, OPENWEATHER_CURRENT);
+}
+
+#[tokio::test]
+async fn test_unreadable_response_is_an_error() {
+    let client =
+        Client::with_provider(AppConfig::default(), Box::new(MockProvider::new("DATA_OK")));
+
+    let err = client.current("Kyiv").await.unwrap_err();
+    assert_eq!(err.to_string(), "cannot read 'now' data from 'mock'");
+}
+
+#[test]
+fn test_missing_key_fails_the_client() {
+    let config = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        ..Default::default()
+    };
+    let overrides = ProviderOverrides {
+        env: EnvSource::from_vars(Vec::<(String, String)>::new()),
+        ..Default::default()
+    };
+
+    let err = Client::with_overrides(config, &overrides).err().unwrap();
+    assert!(err.to_string().contains("WEATHERAPI_KEY"), "{}", err);
+}
@@ -0,0 +1,133 @@
+use chrono::NaiveDate;
+use clap::Parser;
+use wapp::cli::Cli;
+use wapp::output::color::{should_color, Palette};
+use wapp::output::{self, OutputFormat};
+use wapp::providers::WeatherApiProvider;
+use wapp::series::DailySummary;
+use wapp::weather::WeatherData;
+
+fn data(temp: f64, min: f64, max: f64) -> WeatherData {
+    WeatherData {
+        temp_c: Some(temp),
+        feels_like_c: Some(temp),
+        condition: Some("Sunny".into()),
+        forecast: vec![DailySummary {
+            date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            min_c: Some(min),
+            max_c: Some(max),
+            precip_mm: None,
+            precip_chance: None,
+            max_wind_kph: None,
+            avg_wind_kph: None,
+            condition: Some("Sunny".into()),
+            partial: false,
+        }],
+        ..Default::default()
+    }
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| *c == 'm');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[test]
+fn test_should_color() {
+    assert!(should_color(false, None, true));
+    assert!(should_color(false, Some(""), true));
+
+    assert!(!should_color(true, None, true));
+    assert!(!should_color(false, Some("1"), true));
+    assert!(!should_color(false, None, false));
+}
+
+#[test]
+fn test_temperature_ramp() {
+    assert_eq!(Palette::ANSI.temp(-3.0, "-3.0"), "\x1b[34m-3.0\x1b[0m");
+    assert_eq!(Palette::ANSI.temp(4.9, "4.9"), "\x1b[34m4.9\x1b[0m");
+    assert_eq!(Palette::ANSI.temp(5.0, "5.0"), "5.0");
+    assert_eq!(Palette::ANSI.temp(27.9, "27.9"), "27.9");
+    assert_eq!(Palette::ANSI.temp(28.0, "28.0"), "\x1b[31m28.0\x1b[0m");
+    assert_eq!(Palette::ANSI.label("Date"), "\x1b[2mDate\x1b[0m");
+    assert_eq!(Palette::ANSI.warning("Warning"), "\x1b[33mWarning\x1b[0m");
+}
+
+#[test]
+fn test_plain_palette_adds_no_escape_codes() {
+    let data = data(-3.0, -8.0, 31.0);
+
+    for rendered in [
+        output::render_text_with("Kyiv", &data, Palette::PLAIN),
+        output::render_table_with("Kyiv", &data, Palette::PLAIN),
+        output::render_table_with(
+            "Kyiv",
+            &WeatherData {
+                forecast: Vec::new(),
+                ..data.clone()
+            },
+            Palette::PLAIN,
+        ),
+    ] {
+        assert!(!rendered.contains('\x1b'), "{}", rendered);
+    }
+
+    assert_eq!(
+        output::render_text_with("Kyiv", &data, Palette::PLAIN),
+        output::render_text("Kyiv", &data)
+    );
+    assert_eq!(Palette::PLAIN.temp(40.0, "40.0"), "40.0");
+}
+
+#[test]
+fn test_colored_output_keeps_layout() {
+    let data = data(-3.0, -8.0, 31.0);
+
+    let text = output::render_text_with("Kyiv", &data, Palette::ANSI);
+    assert!(text.contains("\x1b[34m-3.0 °C\x1b[0m"), "{}", text);
+    assert!(text.contains("\x1b[31m31.0\x1b[0m"), "{}", text);
+    assert!(text.contains("\x1b[2mCondition:  \x1b[0m"), "{}", text);
+    assert_eq!(strip_ansi(&text), output::render_text("Kyiv", &data));
+
+    let table = output::render_table_with("Kyiv", &data, Palette::ANSI);
+    assert!(table.contains("\x1b[34m-8.0\x1b[0m"), "{}", table);
+    assert!(table.contains("\x1b[2mDate\x1b[0m"), "{}", table);
+    assert_eq!(strip_ansi(&table), output::render_table("Kyiv", &data));
+}
+
+#[test]
+fn test_csv_is_never_colored() {
+    let provider = WeatherApiProvider {
+        api_key: "dummy".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let body = include_str!("fixtures/weatherapi_forecast.json");
+
+    let csv = output::summarize_with(
+        &provider,
+        "Kyiv",
+        "forecast",
+        body,
+        OutputFormat::Csv,
+        Palette::ANSI,
+    );
+    assert!(!csv.contains('\x1b'));
+}
+
+#[test]
+fn test_no_color_flag_is_global() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--no-color"]);
+    assert!(cli.no_color);
+    assert!(!Cli::parse_from(["wapp", "get", "--city", "Kyiv"]).no_color);
+}
@@ -0,0 +1,173 @@
+use wapp::comfort::{
+    classify, comfort, computed, feels_like, heat_index, wind_chill, Comfort, FeelsLike,
+    FeelsLikeSource, HEAT_INDEX_MIN_C, WIND_CHILL_MAX_C, WIND_CHILL_MIN_KPH,
+};
+use wapp::output::color::Palette;
+use wapp::output::{render_details, render_text_in};
+use wapp::units::{c_to_f, f_to_c, mph_to_kph, Units};
+use wapp::weather::WeatherData;
+
+/// Temperature in °F, relative humidity in %, and the heat index in °F,
+/// from the US National Weather Service's heat index chart.
+const HEAT_INDEX_CHART: [(f64, f64, f64); 8] = [
+    (80.0, 40.0, 80.0),
+    (86.0, 90.0, 105.0),
+    (90.0, 70.0, 106.0),
+    (94.0, 50.0, 103.0),
+    (96.0, 50.0, 108.0),
+    (96.0, 55.0, 112.0),
+    (100.0, 40.0, 109.0),
+    (110.0, 40.0, 136.0),
+];
+
+/// Temperature in °F, wind speed in mph, and the wind chill in °F, from the
+/// US National Weather Service's wind chill chart.
+const WIND_CHILL_CHART: [(f64, f64, f64); 8] = [
+    (40.0, 5.0, 36.0),
+    (35.0, 20.0, 24.0),
+    (30.0, 10.0, 21.0),
+    (20.0, 10.0, 9.0),
+    (5.0, 30.0, -19.0),
+    (0.0, 15.0, -19.0),
+    (-10.0, 25.0, -37.0),
+    (-20.0, 60.0, -62.0),
+];
+
+fn current(temp_c: f64, humidity: f64, wind_kph: f64) -> WeatherData {
+    WeatherData {
+        temp_c: Some(temp_c),
+        humidity: Some(humidity),
+        wind_kph: Some(wind_kph),
+        ..Default::default()
+    }
+}
+
+fn source(temp_c: f64, humidity: f64, wind_kph: f64) -> Option<FeelsLikeSource> {
+    computed(temp_c, Some(humidity), Some(wind_kph)).map(|f| f.source)
+}
+
+fn text(data: &WeatherData, units: Units) -> String {
+    render_text_in("Kyiv", data, Palette::PLAIN, units)
+}
+
+#[test]
+fn test_heat_index_matches_the_nws_chart() {
+    for (temp_f, humidity, expected) in HEAT_INDEX_CHART {
+        let index = c_to_f(heat_index(f_to_c(temp_f), humidity));
+        assert_eq!(index.round(), expected, "{} °F at {}%", temp_f, humidity);
+    }
+}
+
+#[test]
+fn test_wind_chill_matches_the_nws_chart() {
+    for (temp_f, wind_mph, expected) in WIND_CHILL_CHART {
+        let chill = c_to_f(wind_chill(f_to_c(temp_f), mph_to_kph(wind_mph)));
+        assert_eq!(chill.round(), expected, "{} °F in {} mph", temp_f, wind_mph);
+    }
+}
+
+#[test]
+fn test_heat_index_threshold() {
+    assert_eq!(
+        source(HEAT_INDEX_MIN_C, 60.0, 20.0),
+        Some(FeelsLikeSource::HeatIndex)
+    );
+    assert_eq!(source(HEAT_INDEX_MIN_C - 0.1, 60.0, 20.0), None);
+    // Without a humidity there is nothing to compute.
+    assert_eq!(computed(35.0, None, Some(20.0)), None);
+}
+
+#[test]
+fn test_wind_chill_thresholds() {
+    assert_eq!(
+        source(WIND_CHILL_MAX_C, 60.0, 4.9),
+        Some(FeelsLikeSource::WindChill)
+    );
+    assert_eq!(source(WIND_CHILL_MAX_C + 0.1, 60.0, 30.0), None);
+    assert_eq!(source(-5.0, 60.0, WIND_CHILL_MIN_KPH), None);
+    assert_eq!(computed(-5.0, Some(60.0), None), None);
+}
+
+#[test]
+fn test_neither_applies_between_the_thresholds() {
+    for temp_c in [WIND_CHILL_MAX_C + 0.1, 18.0, HEAT_INDEX_MIN_C - 0.1] {
+        assert_eq!(source(temp_c, 90.0, 40.0), None, "{}", temp_c);
+    }
+
+    // Nothing is computed, so no feels-like line is shown.
+    let data = current(18.0, 90.0, 40.0);
+    assert_eq!(feels_like(&data), None);
+    assert!(!text(&data, Units::Metric).contains("Feels like"));
+    assert_eq!(comfort(&data), Some(Comfort::Muggy));
+}
+
+#[test]
+fn test_provider_feels_like_comes_first() {
+    let data = WeatherData {
+        feels_like_c: Some(36.0),
+        ..current(32.0, 70.0, 10.0)
+    };
+
+    assert_eq!(
+        feels_like(&data),
+        Some(FeelsLike {
+            temp_c: 36.0,
+            source: FeelsLikeSource::Provider,
+        })
+    );
+    assert!(text(&data, Units::Metric).contains("  Feels like:  36.0 °C\n"));
+}
+
+#[test]
+fn test_computed_feels_like_names_its_source() {
+    // 90 °F at 70% and 0 °F in a 15 mph wind, as in the charts.
+    let hot = current(f_to_c(90.0), 70.0, 5.0);
+    assert!(
+        text(&hot, Units::Imperial).contains("  Feels like:  105.9 °F (heat index)\n"),
+        "{}",
+        text(&hot, Units::Imperial)
+    );
+
+    let cold = current(f_to_c(0.0), 50.0, mph_to_kph(15.0));
+    assert!(
+        text(&cold, Units::Imperial).contains("  Feels like:  -19.4 °F (wind chill)\n"),
+        "{}",
+        text(&cold, Units::Imperial)
+    );
+}
+
+#[test]
+fn test_comfort_boundaries() {
+    assert_eq!(classify(-10.0, None), Comfort::Bitter);
+    assert_eq!(classify(-9.9, None), Comfort::Cold);
+    assert_eq!(classify(9.9, Some(9.0)), Comfort::Cold);
+    assert_eq!(classify(10.0, None), Comfort::Cool);
+    assert_eq!(classify(17.9, Some(15.9)), Comfort::Cool);
+    assert_eq!(classify(18.0, Some(15.9)), Comfort::Pleasant);
+    assert_eq!(classify(18.0, Some(16.0)), Comfort::Muggy);
+    assert_eq!(classify(31.9, Some(22.0)), Comfort::Muggy);
+    assert_eq!(classify(32.0, Some(22.0)), Comfort::Hot);
+    assert_eq!(Comfort::Bitter.to_string(), "bitter");
+}
+
+#[test]
+fn test_comfort_of_weather_data() {
+    // -5 °C feels like -13 °C in a 30 km/h wind.
+    assert_eq!(comfort(&current(-5.0, 70.0, 30.0)), Some(Comfort::Bitter));
+    assert_eq!(comfort(&current(-5.0, 70.0, 0.0)), Some(Comfort::Cold));
+    // 30 °C at 40% has a dew point of 15 °C, at 50% of 18.4 °C.
+    assert_eq!(comfort(&current(30.0, 40.0, 10.0)), Some(Comfort::Pleasant));
+    assert_eq!(comfort(&current(30.0, 50.0, 10.0)), Some(Comfort::Muggy));
+    // The provider's dew point is taken as it is.
+    let data = WeatherData {
+        dew_point_c: Some(10.0),
+        ..current(30.0, 50.0, 10.0)
+    };
+    assert_eq!(comfort(&data), Some(Comfort::Pleasant));
+    assert_eq!(comfort(&WeatherData::default()), None);
+
+    assert_eq!(
+        render_details(&current(22.0, 80.0, 10.0), Palette::PLAIN, Units::Metric),
+        "  Comfort:     muggy\n"
+    );
+}
@@ -0,0 +1,182 @@
+use clap::Parser;
+use wapp::cli::{compare_locations, Cli, Commands};
+use wapp::config::AppConfig;
+use wapp::location::{CityName, Location};
+use wapp::output::{
+    render_comparison, render_provider_json, render_provider_table, Palette, ProviderReading,
+};
+use wapp::providers::{DataKind, SkippedProvider};
+use wapp::series::DailySummary;
+use wapp::weather::WeatherData;
+
+fn day(date: &str, min: f64, max: f64, condition: &str, precip: f64) -> DailySummary {
+    DailySummary {
+        date: date.parse().unwrap(),
+        min_c: Some(min),
+        max_c: Some(max),
+        precip_mm: Some(precip),
+        precip_chance: None,
+        max_wind_kph: None,
+        avg_wind_kph: None,
+        condition: Some(condition.into()),
+        partial: false,
+    }
+}
+
+fn cities(names: &[&str]) -> Vec<CityName> {
+    names.iter().map(|n| CityName::new(n).unwrap()).collect()
+}
+
+#[test]
+fn test_forecasts_are_aligned_by_date() {
+    let lviv = WeatherData {
+        forecast: vec![
+            day("2024-06-10", 12.0, 20.5, "Sunny", 0.0),
+            day("2024-06-11", 11.0, 18.0, "Rain", 4.2),
+        ],
+        ..Default::default()
+    };
+    let odesa = WeatherData {
+        forecast: vec![day("2024-06-10", 17.0, 26.1, "Partly cloudy", 0.0)],
+        ..Default::default()
+    };
+
+    let out = render_comparison(
+        &[("Lviv".into(), lviv), ("Odesa".into(), odesa)],
+        Palette::PLAIN,
+    );
+
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "             Lviv          Odesa",
+            "2024-06-10",
+            "  Temp °C    12.0 .. 20.5  17.0 .. 26.1",
+            "  Condition  Sunny         Partly cloudy",
+            "  Precip mm  0.0           0.0",
+            "2024-06-11",
+            "  Temp °C    11.0 .. 18.0  -",
+            "  Condition  Rain          -",
+            "  Precip mm  4.2           -",
+        ]
+    );
+}
+
+#[test]
+fn test_current_conditions_get_one_column_per_city() {
+    let now = |temp: f64, condition: &str| WeatherData {
+        temp_c: Some(temp),
+        condition: Some(condition.into()),
+        ..Default::default()
+    };
+
+    let out = render_comparison(
+        &[
+            ("Kyiv".into(), now(21.0, "Sunny")),
+            ("Lviv".into(), now(18.4, "Mist")),
+            ("Odesa".into(), WeatherData::default()),
+        ],
+        Palette::PLAIN,
+    );
+
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines[0], "             Kyiv   Lviv  Odesa");
+    assert_eq!(lines[1], "Now");
+    assert_eq!(lines[2], "  Temp °C    21.0   18.4  -");
+    assert_eq!(lines[3], "  Condition  Sunny  Mist  -");
+}
+
+#[test]
+fn test_compare_needs_two_cities() {
+    let cfg = AppConfig::default();
+
+    let err = compare_locations(&cfg, &cities(&["Kyiv"])).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "compare needs at least two --city locations, got 1"
+    );
+
+    let locations = compare_locations(&cfg, &cities(&["Kyiv", "Lviv", "Odesa"])).unwrap();
+    assert_eq!(locations.len(), 3);
+    assert_eq!(locations[1], Location::from("Lviv"));
+    assert!(compare_locations(&cfg, &cities(&["Kyiv", "@cabin"])).is_err());
+}
+
+#[test]
+fn test_parse_compare() {
+    let cli =
+        Cli::try_parse_from(vec!["wapp", "compare", "--city", "Lviv", "--city", "Odesa"]).unwrap();
+
+    match cli.cmd {
+        Commands::Compare { city, data, .. } => {
+            assert_eq!(city.len(), 2);
+            assert_eq!(data, DataKind::Forecast);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["wapp", "compare"]).is_err());
+}
+
+fn readings() -> (Vec<ProviderReading>, Vec<SkippedProvider>) {
+    let now = WeatherData {
+        temp_c: Some(21.0),
+        condition: Some("Sunny".into()),
+        ..Default::default()
+    };
+    let forecast = WeatherData {
+        forecast: vec![day("2024-06-10", 12.0, 20.0, "Rain", 3.0)],
+        ..Default::default()
+    };
+
+    let readings = vec![
+        ProviderReading::from_data("weatherapi", &now),
+        ProviderReading::from_data("openmeteo", &forecast),
+        ProviderReading::failed("wttr", &anyhow::anyhow!("timed out")),
+    ];
+    let skipped = vec![SkippedProvider {
+        provider: "openweather".into(),
+        reason: "OPENWEATHER_KEY (or OPENWEATHER_KEY_FILE) is not set".into(),
+    }];
+
+    (readings, skipped)
+}
+
+#[test]
+fn test_provider_table_lists_failures_and_skips() {
+    let (readings, skipped) = readings();
+
+    let out = render_provider_table("Berlin", &readings, &skipped);
+
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "Berlin",
+            "Provider    Temp °C  Condition",
+            "weatherapi  21.0     Sunny",
+            "openmeteo   16.0     Rain",
+            "wttr        -        error: timed out",
+            "Skipped openweather: OPENWEATHER_KEY (or OPENWEATHER_KEY_FILE) is not set",
+        ]
+    );
+}
+
+#[test]
+fn test_provider_json_names_skipped_providers() {
+    let (readings, skipped) = readings();
+
+    let out = render_provider_json("Berlin", "now", &readings, &skipped);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    assert_eq!(json["city"], "Berlin");
+    assert_eq!(json["providers"][0]["temp_c"], 21.0);
+    assert!(json["providers"][0].get("error").is_none());
+    assert_eq!(json["providers"][2]["error"], "timed out");
+    assert_eq!(json["skipped"][0]["provider"], "openweather");
+    assert!(json["skipped"][0]["reason"]
+        .as_str()
+        .unwrap()
+        .contains("not set"));
+}
@@ -0,0 +1,64 @@
+use clap::Parser;
+use clap_complete::Shell;
+use wapp::cli::{Cli, Commands};
+use wapp::completions::{generate, location_candidates};
+use wapp::config::{AppConfig, SavedLocation};
+
+fn script(shell: Shell) -> String {
+    let mut out = Vec::new();
+    generate(shell, &mut out);
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_bash_script_completes_subcommands_and_providers() {
+    let script = script(Shell::Bash);
+
+    for name in ["configure", "get", "forecast", "completions", "--city"] {
+        assert!(script.contains(name), "{} missing", name);
+    }
+    assert!(script.contains("openweather"));
+}
+
+#[test]
+fn test_zsh_script_completes_subcommands_and_providers() {
+    let script = script(Shell::Zsh);
+
+    assert!(script.starts_with("#compdef wapp"));
+    for name in ["configure", "get", "compare-providers", "completions"] {
+        assert!(script.contains(name), "{} missing", name);
+    }
+    assert!(script.contains("weatherapi"));
+}
+
+#[test]
+fn test_every_shell_generates() {
+    for shell in [Shell::Fish, Shell::PowerShell, Shell::Elvish] {
+        assert!(script(shell).contains("completions"), "{}", shell);
+    }
+}
+
+#[test]
+fn test_saved_locations_complete_after_at() {
+    let mut cfg = AppConfig::default();
+    for (name, city) in [("home", "Kyiv"), ("hotel", "Lviv"), ("office", "Odesa")] {
+        let entry = SavedLocation::City { city: city.into() };
+        cfg.add_location(name, entry, false).unwrap();
+    }
+
+    assert_eq!(location_candidates(&cfg, "@h"), vec!["@home", "@hotel"]);
+    assert_eq!(location_candidates(&cfg, "@").len(), 3);
+    // Plain city names are not completed.
+    assert!(location_candidates(&cfg, "h").is_empty());
+}
+
+#[test]
+fn test_parse_completions() {
+    let cli = Cli::parse_from(["wapp", "completions", "zsh"]);
+    assert!(matches!(
+        cli.cmd,
+        Commands::Completions { shell: Shell::Zsh }
+    ));
+
+    assert!(Cli::try_parse_from(["wapp", "completions", "tcsh"]).is_err());
+}
@@ -0,0 +1,408 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use wapp::condition::Condition::{self, *};
+use wapp::condition::dominant;
+use wapp::output;
+use wapp::output::color::{Icons, Palette};
+use wapp::providers::{ApiProvider, OpenWeatherProvider, WeatherApiProvider};
+use wapp::series::{summarize_day, SeriesPoint};
+
+#[test]
+fn test_openweather_codes() {
+    let cases: &[(i64, Condition)] = &[
+        (200, Thunderstorm),
+        (232, Thunderstorm),
+        (300, Drizzle),
+        (321, Drizzle),
+        (500, Rain),
+        (511, Sleet),
+        (531, Rain),
+        (600, Snow),
+        (611, Sleet),
+        (616, Sleet),
+        (622, Snow),
+        (701, Fog),
+        (741, Fog),
+        (781, Thunderstorm),
+        (800, Clear),
+        (801, PartlyCloudy),
+        (802, PartlyCloudy),
+        (803, Cloudy),
+        (804, Cloudy),
+        (900, Unknown),
+        (-1, Unknown),
+    ];
+
+    for (code, expected) in cases {
+        assert_eq!(
+            Condition::from_openweather(*code),
+            *expected,
+            "code {}",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_weatherapi_codes() {
+    let cases: &[(i64, Condition)] = &[
+        (1000, Clear),
+        (1003, PartlyCloudy),
+        (1006, Cloudy),
+        (1009, Cloudy),
+        (1030, Fog),
+        (1135, Fog),
+        (1147, Fog),
+        (1063, Rain),
+        (1189, Rain),
+        (1246, Rain),
+        (1066, Snow),
+        (1117, Snow),
+        (1225, Snow),
+        (1258, Snow),
+        (1069, Sleet),
+        (1201, Sleet),
+        (1237, Sleet),
+        (1264, Sleet),
+        (1072, Drizzle),
+        (1153, Drizzle),
+        (1171, Drizzle),
+        (1087, Thunderstorm),
+        (1276, Thunderstorm),
+        (1282, Thunderstorm),
+        (1001, Unknown),
+        (800, Unknown),
+    ];
+
+    for (code, expected) in cases {
+        assert_eq!(
+            Condition::from_weatherapi(*code),
+            *expected,
+            "code {}",
+            code
+        );
+    }
+}
+
+/// Every condition, from the best weather to the worst.
+const BY_SEVERITY: [Condition; 9] = [
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Sleet,
+    Thunderstorm,
+];
+
+/// Every documented OpenWeatherMap code, see
+/// <https://openweathermap.org/weather-conditions>.
+const OPENWEATHER_CODES: &[(i64, Condition)] = &[
+    (200, Thunderstorm),
+    (201, Thunderstorm),
+    (202, Thunderstorm),
+    (210, Thunderstorm),
+    (211, Thunderstorm),
+    (212, Thunderstorm),
+    (221, Thunderstorm),
+    (230, Thunderstorm),
+    (231, Thunderstorm),
+    (232, Thunderstorm),
+    (300, Drizzle),
+    (301, Drizzle),
+    (302, Drizzle),
+    (310, Drizzle),
+    (311, Drizzle),
+    (312, Drizzle),
+    (313, Drizzle),
+    (314, Drizzle),
+    (321, Drizzle),
+    (500, Rain),
+    (501, Rain),
+    (502, Rain),
+    (503, Rain),
+    (504, Rain),
+    (511, Sleet),
+    (520, Rain),
+    (521, Rain),
+    (522, Rain),
+    (531, Rain),
+    (600, Snow),
+    (601, Snow),
+    (602, Snow),
+    (611, Sleet),
+    (612, Sleet),
+    (613, Sleet),
+    (615, Sleet),
+    (616, Sleet),
+    (620, Snow),
+    (621, Snow),
+    (622, Snow),
+    (701, Fog),
+    (711, Fog),
+    (721, Fog),
+    (731, Fog),
+    (741, Fog),
+    (751, Fog),
+    (761, Fog),
+    (762, Fog),
+    (771, Thunderstorm),
+    (781, Thunderstorm),
+    (800, Clear),
+    (801, PartlyCloudy),
+    (802, PartlyCloudy),
+    (803, Cloudy),
+    (804, Cloudy),
+];
+
+/// Every documented WeatherAPI code, see
+/// <https://www.weatherapi.com/docs/weather_conditions.json>.
+const WEATHERAPI_CODES: &[(i64, Condition)] = &[
+    (1000, Clear),
+    (1003, PartlyCloudy),
+    (1006, Cloudy),
+    (1009, Cloudy),
+    (1030, Fog),
+    (1063, Rain),
+    (1066, Snow),
+    (1069, Sleet),
+    (1072, Drizzle),
+    (1087, Thunderstorm),
+    (1114, Snow),
+    (1117, Snow),
+    (1135, Fog),
+    (1147, Fog),
+    (1150, Drizzle),
+    (1153, Drizzle),
+    (1168, Drizzle),
+    (1171, Drizzle),
+    (1180, Rain),
+    (1183, Rain),
+    (1186, Rain),
+    (1189, Rain),
+    (1192, Rain),
+    (1195, Rain),
+    (1198, Sleet),
+    (1201, Sleet),
+    (1204, Sleet),
+    (1207, Sleet),
+    (1210, Snow),
+    (1213, Snow),
+    (1216, Snow),
+    (1219, Snow),
+    (1222, Snow),
+    (1225, Snow),
+    (1237, Sleet),
+    (1240, Rain),
+    (1243, Rain),
+    (1246, Rain),
+    (1249, Sleet),
+    (1252, Sleet),
+    (1255, Snow),
+    (1258, Snow),
+    (1261, Sleet),
+    (1264, Sleet),
+    (1273, Thunderstorm),
+    (1276, Thunderstorm),
+    (1279, Thunderstorm),
+    (1282, Thunderstorm),
+];
+
+#[test]
+fn test_every_documented_code_is_mapped() {
+    for (code, expected) in OPENWEATHER_CODES {
+        assert_eq!(
+            Condition::from_openweather(*code),
+            *expected,
+            "code {}",
+            code
+        );
+    }
+    for (code, expected) in WEATHERAPI_CODES {
+        assert_eq!(
+            Condition::from_weatherapi(*code),
+            *expected,
+            "code {}",
+            code
+        );
+    }
+}
+
+#[test]
+fn test_unknown_codes_fall_back() {
+    // Codes outside the documented groups are unknown ...
+    for code in (-10..200).chain(805..1200) {
+        assert_eq!(Condition::from_openweather(code), Unknown, "code {}", code);
+    }
+    // ... and new codes in a group take the group's condition.
+    assert_eq!(Condition::from_openweather(299), Thunderstorm);
+    assert_eq!(Condition::from_openweather(599), Rain);
+    assert_eq!(Condition::from_openweather(799), Fog);
+
+    // Other WeatherAPI codes are unknown, but for the gaps between
+    // precipitation codes, which are mapped by range.
+    for code in 0..1400 {
+        if !WEATHERAPI_CODES.iter().any(|(known, _)| *known == code)
+            && !(1150..=1282).contains(&code)
+        {
+            assert_eq!(Condition::from_weatherapi(code), Unknown, "code {}", code);
+        }
+    }
+    assert_eq!(Condition::from_openweather(1000), Unknown);
+    assert_eq!(Condition::from_weatherapi(800), Unknown);
+}
+
+#[test]
+fn test_severity_order() {
+    for pair in BY_SEVERITY.windows(2) {
+        assert!(
+            pair[0].severity() < pair[1].severity(),
+            "{:?} < {:?}",
+            pair[0],
+            pair[1]
+        );
+    }
+    assert_eq!(Unknown.severity(), Clear.severity());
+}
+
+#[test]
+fn test_dominant() {
+    let cases: &[(&[Condition], Condition)] = &[
+        (&[Clear, Clear, Rain], Clear),
+        (&[Rain, Clear, Rain, Clear, Rain], Rain),
+        // Ties go to the worse weather, in any order.
+        (&[Clear, Rain], Rain),
+        (&[Rain, Clear], Rain),
+        (&[Cloudy, Fog, Cloudy, Fog], Fog),
+        (&[Snow, Thunderstorm], Thunderstorm),
+        (&[Drizzle, Drizzle, Snow, Snow, Clear], Snow),
+        // Unknown conditions are not counted.
+        (&[Unknown, Unknown, Unknown, Rain], Rain),
+        (&[Unknown, Clear], Clear),
+        (&[Unknown], Unknown),
+        (&[], Unknown),
+    ];
+
+    for (conditions, expected) in cases {
+        assert_eq!(dominant(conditions), *expected, "{:?}", conditions);
+    }
+}
+
+#[test]
+fn test_day_summary_takes_the_dominant_condition() {
+    let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+    let day = |conditions: &[&str]| {
+        let points: Vec<SeriesPoint> = conditions
+            .iter()
+            .enumerate()
+            .map(|(i, condition)| {
+                let time = Utc.with_ymd_and_hms(2024, 5, 1, 3 * i as u32, 0, 0);
+                let mut point = SeriesPoint::at(time.unwrap());
+                point.condition = Some(condition.to_string());
+                point
+            })
+            .collect();
+        summarize_day(date, &points).condition
+    };
+
+    // Two texts of rain outnumber the sunny one.
+    let rainy = day(&[
+        "Sunny",
+        "Light rain",
+        "Sunny",
+        "Moderate rain",
+        "Sunny",
+        "Light rain",
+    ]);
+    assert_eq!(rainy.as_deref(), Some("Light rain"));
+    assert_eq!(day(&["Sunny", "Overcast"]).as_deref(), Some("Overcast"));
+    // Texts without a category, e.g. in another language, still count
+    // among themselves.
+    let localized = day(&["Ясно", "Дощ", "Дощ"]);
+    assert_eq!(localized.as_deref(), Some("Дощ"));
+    assert_eq!(day(&["Ясно", "Sunny"]).as_deref(), Some("Sunny"));
+    assert_eq!(day(&[]), None);
+}
+
+#[test]
+fn test_text_keywords() {
+    let cases: &[(&str, Condition)] = &[
+        ("Sunny", Clear),
+        ("Partly cloudy", PartlyCloudy),
+        ("overcast clouds", Cloudy),
+        ("Freezing fog", Fog),
+        ("Light freezing rain", Sleet),
+        ("Patchy light drizzle", Drizzle),
+        ("Moderate rain", Rain),
+        ("Blowing snow", Snow),
+        ("Thundery outbreaks possible", Thunderstorm),
+        ("", Unknown),
+    ];
+
+    for (text, expected) in cases {
+        assert_eq!(Condition::from_text(text), *expected, "{:?}", text);
+    }
+}
+
+#[test]
+fn test_every_condition_has_glyphs() {
+    for condition in [
+        Clear,
+        PartlyCloudy,
+        Cloudy,
+        Fog,
+        Drizzle,
+        Rain,
+        Sleet,
+        Snow,
+        Thunderstorm,
+        Unknown,
+    ] {
+        assert!(!condition.emoji().is_empty());
+        assert!(condition.ascii().is_ascii());
+    }
+}
+
+#[test]
+fn test_providers_map_current_codes() {
+    let weatherapi = WeatherApiProvider::new("dummy").with_base_url("http://localhost");
+    let openweather = OpenWeatherProvider::new("dummy").with_units("metric");
+
+    let data = weatherapi
+        .weather(include_str!("fixtures/weatherapi_current.json"))
+        .unwrap();
+    assert_eq!(data.condition_kind, Some(Clear));
+
+    let data = openweather
+        .weather(include_str!("fixtures/openweather_current.json"))
+        .unwrap();
+    assert_eq!(data.condition_kind, Some(PartlyCloudy));
+    let json: serde_json::Value = serde_json::from_str(&data.to_json()).unwrap();
+    assert_eq!(json["condition_kind"], "partly_cloudy");
+
+    // An unmapped code falls back to the generic glyph.
+    let body = include_str!("fixtures/openweather_current.json").replace("801", "999");
+    let data = openweather.weather(&body).unwrap();
+    assert_eq!(data.condition_kind, Some(Unknown));
+    let text = output::render_text_with("Kyiv", &data, Palette::PLAIN.with_icons(Icons::Emoji));
+    assert!(text.contains("🌡 few clouds"), "{}", text);
+}
+
+#[test]
+fn test_glyphs_in_text_and_table() {
+    let data = wapp::weather::WeatherData {
+        temp_c: Some(24.0),
+        condition: Some("Sunny".into()),
+        condition_kind: Some(Clear),
+        ..Default::default()
+    };
+
+    let emoji = Palette::PLAIN.with_icons(Icons::Emoji);
+    let ascii = Palette::PLAIN.with_icons(Icons::Ascii);
+
+    assert!(output::render_text_with("Kyiv", &data, emoji).contains("Condition:   ☀️ Sunny"));
+    assert!(output::render_text_with("Kyiv", &data, ascii).contains("Condition:   O Sunny"));
+    assert!(output::render_table_with("Kyiv", &data, ascii).contains("O Sunny"));
+    assert!(!output::render_text("Kyiv", &data).contains("☀️"));
+}
@@ -0,0 +1,300 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use wapp::config::{load_config_from, lock_config_at, save_config_to, AppConfig, SavedLocation};
+use wapp::error::WappError;
+use wapp::exit::{exit_code, CONFIG_EXIT_CODE};
+use wapp::providers::ProviderKind;
+
+const SAVED: &str = r#"{"provider":"weatherapi","api_keys":{"weatherapi":"KEY"},"units":"metric"}"#;
+
+/// Files of `dir` but `keep`, by name.
+fn backups(dir: &Path, keep: &str) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name != keep)
+        .collect();
+    names.sort();
+    names
+}
+
+fn wapp(dir: &Path) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin("wapp"));
+    cmd.current_dir(dir)
+        .env("WAPP_CONFIG", dir.join("config.json"))
+        .env("WAPP_CACHE_DIR", dir.join("cache"))
+        .env("WAPP_DATA_DIR", dir.join("data"))
+        .env("WAPP_LOG", "off");
+    cmd
+}
+
+#[test]
+fn test_truncated_config_is_moved_aside() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let truncated = &SAVED[..40];
+    fs::write(&path, truncated).unwrap();
+
+    let err = load_config_from(&path).unwrap_err();
+    let Some(WappError::ConfigCorrupted { backup, .. }) = WappError::of(&err) else {
+        panic!("{:?}", err);
+    };
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "config {} appears corrupted, re-run configure (backup saved to {})",
+            path.display(),
+            backup.display()
+        )
+    );
+    assert_eq!(exit_code(&err), Some(CONFIG_EXIT_CODE));
+
+    assert!(!path.exists());
+    assert_eq!(fs::read_to_string(backup).unwrap(), truncated);
+    let name = backup.file_name().unwrap().to_string_lossy();
+    assert!(name.starts_with("config.json.corrupted-"), "{}", name);
+}
+
+#[test]
+fn test_empty_configs_are_corrupted() {
+    for (name, text) in [
+        ("config.json", ""),
+        ("config.json", " \n"),
+        ("config.json", "\0\0\0\0"),
+        ("config.toml", ""),
+    ] {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(name);
+        fs::write(&path, text).unwrap();
+
+        let err = load_config_from(&path).unwrap_err();
+        assert!(
+            matches!(WappError::of(&err), Some(WappError::ConfigCorrupted { .. })),
+            "{:?}: {}",
+            text,
+            err
+        );
+        assert_eq!(backups(dir.path(), name).len(), 1);
+    }
+}
+
+#[test]
+fn test_invalid_config_stays_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    // Complete JSON, but no config: a mistake to fix, not a crash.
+    fs::write(&path, r#"{"provider": 42}"#).unwrap();
+
+    let err = load_config_from(&path).unwrap_err();
+    assert!(
+        matches!(WappError::of(&err), Some(WappError::ConfigParse { .. })),
+        "{}",
+        err
+    );
+    assert!(path.exists());
+    assert!(backups(dir.path(), "config.json").is_empty());
+}
+
+#[test]
+fn test_lock_waits_for_the_holder() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wapp").join("config.json");
+    let held = lock_config_at(&path).unwrap();
+    assert!(dir.path().join("wapp/config.json.lock").exists());
+
+    let (locked, waiting) = mpsc::channel();
+    let other = path.clone();
+    let thread = std::thread::spawn(move || {
+        let _lock = lock_config_at(&other).unwrap();
+        locked.send(()).unwrap();
+    });
+
+    assert!(waiting.recv_timeout(Duration::from_millis(200)).is_err());
+    drop(held);
+    waiting.recv_timeout(Duration::from_secs(10)).unwrap();
+    thread.join().unwrap();
+}
+
+#[test]
+fn test_concurrent_edits_are_all_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    save_config_to(&path, &AppConfig::default()).unwrap();
+
+    let threads: Vec<_> = (0..8)
+        .map(|i| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let _lock = lock_config_at(&path).unwrap();
+                let mut cfg = load_config_from(&path).unwrap();
+                let city = SavedLocation::City {
+                    city: format!("City {}", i),
+                };
+                cfg.add_location(&format!("place{}", i), city, false)
+                    .unwrap();
+                save_config_to(&path, &cfg).unwrap();
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let cfg = load_config_from(&path).unwrap();
+    assert_eq!(cfg.locations.len(), 8);
+    assert_eq!(
+        backups(dir.path(), "config.json"),
+        vec!["config.json.lock".to_string()]
+    );
+}
+
+#[test]
+fn test_concurrent_saves_never_show_a_partial_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    save_config_to(&path, &AppConfig::default()).unwrap();
+
+    let writers: Vec<_> = [ProviderKind::WeatherApi, ProviderKind::OpenMeteo]
+        .into_iter()
+        .map(|provider| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let cfg = AppConfig {
+                    provider,
+                    ..Default::default()
+                };
+                for _ in 0..50 {
+                    save_config_to(&path, &cfg).unwrap();
+                }
+            })
+        })
+        .collect();
+    for _ in 0..100 {
+        load_config_from(&path).unwrap();
+    }
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    // No temporary file is left behind.
+    assert!(backups(dir.path(), "config.json").is_empty());
+}
+
+#[test]
+fn test_parallel_location_adds_are_all_saved() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let children: Vec<_> = (0..6)
+        .map(|i| {
+            wapp(dir.path())
+                .args(["location", "add", &format!("place{}", i), "Kyiv"])
+                .stdout(Stdio::null())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let cfg = load_config_from(&dir.path().join("config.json")).unwrap();
+    assert_eq!(cfg.locations.len(), 6);
+}
+
+#[test]
+fn test_cli_reports_a_corrupted_config_and_starts_afresh() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("config.json"), &SAVED[..25]).unwrap();
+
+    let output = wapp(dir.path())
+        .args(["get", "--city", "Kyiv", "--dry-run"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(CONFIG_EXIT_CODE));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("appears corrupted, re-run configure (backup saved to "),
+        "{}",
+        stderr
+    );
+
+    let status = wapp(dir.path())
+        .args(["config", "set", "units", "imperial"])
+        .stdout(Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let cfg = load_config_from(&dir.path().join("config.json")).unwrap();
+    assert_eq!(cfg.units.as_deref(), Some("imperial"));
+}
+
+#[test]
+fn test_configure_without_a_key_keeps_the_saved_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    fs::write(&path, SAVED).unwrap();
+
+    for args in [
+        &["configure", "weatherapi", "--no-verify"][..],
+        &["configure", "openweather", "--no-verify"],
+        &["configure", "--units", "imperial"],
+        &[
+            "--profile",
+            "work",
+            "configure",
+            "weatherapi",
+            "--no-verify",
+        ],
+    ] {
+        let status = wapp(dir.path())
+            .args(args)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "{:?}", args);
+
+        let cfg = load_config_from(&path).unwrap();
+        assert_eq!(
+            cfg.api_keys.get("weatherapi").map(String::as_str),
+            Some("KEY"),
+            "{:?}",
+            args
+        );
+    }
+
+    // A key saved with a profile stays with it too.
+    for args in [
+        &[
+            "--profile",
+            "work",
+            "configure",
+            "openweather",
+            "--key",
+            "WORK",
+        ][..],
+        &["--profile", "work", "configure", "--units", "metric"],
+        &["--profile", "work", "configure", "openweather"],
+    ] {
+        let status = wapp(dir.path())
+            .args(args)
+            .arg("--no-verify")
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "{:?}", args);
+    }
+    let cfg = load_config_from(&path).unwrap();
+    let work = cfg.with_profile(Some("work")).unwrap();
+    assert_eq!(
+        work.api_keys.get("openweather").map(String::as_str),
+        Some("WORK")
+    );
+    assert_eq!(
+        work.api_keys.get("weatherapi").map(String::as_str),
+        Some("KEY")
+    );
+}
@@ -1,23 +1,474 @@
 use std::fs;
-use wapp::config::{load_config, save_config, AppConfig};
+use wapp::cli::{render_config, run_config_command, ConfigCommand};
+use wapp::config::{
+    config_for, load_config_from, parse_config, parse_toml_config, save_config_to, AppConfig,
+    ConfigFormat, SavedLocation, CONFIG_KEYS,
+};
+use wapp::location::Location;
+use wapp::providers::ProviderKind;
 
 #[test]
 fn test_save_and_load_config() {
     let cfg = AppConfig {
-        provider: "weatherapi".into(),
+        provider: ProviderKind::WeatherApi,
+        ..Default::default()
     };
 
-    save_config(&cfg).unwrap();
-    let loaded = load_config().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("wapp").join("config.json");
 
-    assert_eq!(loaded.provider, "weatherapi");
+    // The directory is created on the first save.
+    save_config_to(&path, &cfg).unwrap();
+    let loaded = load_config_from(&path).unwrap();
 
-    fs::remove_file("config.json").unwrap();
+    assert_eq!(loaded.provider, ProviderKind::WeatherApi);
+    assert!(fs::read_to_string(&path).unwrap().contains("weatherapi"));
+}
+
+#[test]
+fn test_config_without_default_city_loads() {
+    let cfg: AppConfig = serde_json::from_str(r#"{"provider":"weatherapi"}"#).unwrap();
+    assert_eq!(cfg.default_city, None);
+
+    let cfg = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        ..Default::default()
+    };
+    assert!(!serde_json::to_string(&cfg)
+        .unwrap()
+        .contains("default_city"));
+}
+
+#[test]
+fn test_render_config_redacts_key() {
+    let cfg = AppConfig {
+        provider: ProviderKind::OpenWeather,
+        api_keys: [("openweather".to_string(), "secret".to_string())].into(),
+        default_city: Some("Kyiv".into()),
+        ..Default::default()
+    };
+
+    let out = render_config(&cfg);
+    assert_eq!(
+        out,
+        [
+            "provider:           openweather",
+            "fallback_providers: -",
+            "strategy:           -",
+            "api_key:            <redacted>",
+            "default_city:       Kyiv",
+            "auto_locate:        on",
+            "units:              -",
+            "lang:               -",
+            "pager:              -",
+            "log_format:         -",
+            "cache_ttl:          -",
+            "stale_fallback:     off",
+            "notify:             off",
+            "history:            off",
+            "retries:            -",
+            "rate_limit_wait:    -",
+            "timeout:            -",
+            "proxy:              -",
+            "ca_bundle:          -",
+            "",
+        ]
+        .join("\n")
+    );
+    assert!(!out.contains("secret"));
 }
 
 #[test]
 fn test_missing_config() {
-    let _ = fs::remove_file("config.json");
-    let result = load_config();
-    assert!(result.is_err());
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+
+    let err = load_config_from(&path).unwrap_err();
+    assert!(err.to_string().starts_with("cannot read"));
+    assert!(err.to_string().contains("config.json"));
+}
+
+#[test]
+fn test_saved_locations_roundtrip() {
+    let mut cfg = AppConfig::default();
+    cfg.add_location(
+        "home",
+        SavedLocation::City {
+            city: "Kyiv".into(),
+        },
+        false,
+    )
+    .unwrap();
+    cfg.add_location(
+        "@office",
+        SavedLocation::Coords {
+            lat: 50.45,
+            lon: 30.52,
+        },
+        false,
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&cfg).unwrap();
+    assert!(
+        json.contains(r#""locations":{"home":{"city":"Kyiv"},"office":{"lat":50.45,"lon":30.52}}"#)
+    );
+
+    let loaded: AppConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.locations, cfg.locations);
+
+    let old: AppConfig = serde_json::from_str(r#"{"provider":"weatherapi"}"#).unwrap();
+    assert!(old.locations.is_empty());
+}
+
+#[test]
+fn test_duplicate_location_needs_force() {
+    let mut cfg = AppConfig::default();
+    let kyiv = SavedLocation::City {
+        city: "Kyiv".into(),
+    };
+    let lviv = SavedLocation::City {
+        city: "Lviv".into(),
+    };
+
+    cfg.add_location("home", kyiv, false).unwrap();
+    let err = cfg.add_location("home", lviv.clone(), false).unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+
+    cfg.add_location("home", lviv.clone(), true).unwrap();
+    assert_eq!(cfg.locations["home"], lviv);
+
+    assert!(cfg.add_location("my home", lviv.clone(), false).is_err());
+    assert!(cfg.add_location("@", lviv, false).is_err());
+}
+
+#[test]
+fn test_remove_location() {
+    let mut cfg = AppConfig::default();
+    cfg.add_location(
+        "home",
+        SavedLocation::City {
+            city: "Kyiv".into(),
+        },
+        false,
+    )
+    .unwrap();
+
+    cfg.remove_location("@home").unwrap();
+    assert!(cfg.locations.is_empty());
+    assert!(cfg.remove_location("home").is_err());
+}
+
+#[test]
+fn test_resolve_location_aliases() {
+    let mut cfg = AppConfig::default();
+    cfg.add_location(
+        "home",
+        SavedLocation::City {
+            city: "Kyiv".into(),
+        },
+        false,
+    )
+    .unwrap();
+    cfg.add_location(
+        "office",
+        SavedLocation::Coords {
+            lat: 50.45,
+            lon: 30.52,
+        },
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(
+        cfg.resolve_location(Location::from("@home")).unwrap(),
+        Location::from("Kyiv")
+    );
+    assert_eq!(
+        cfg.resolve_location(Location::from("@office")).unwrap(),
+        Location::Coords {
+            lat: 50.45,
+            lon: 30.52
+        }
+    );
+    assert_eq!(
+        cfg.resolve_location(Location::from("Lviv")).unwrap(),
+        Location::from("Lviv")
+    );
+
+    let err = cfg.resolve_location(Location::from("@cabin")).unwrap_err();
+    assert!(err.to_string().contains("no saved location 'cabin'"));
+}
+
+#[test]
+fn test_config_keys_roundtrip() {
+    let mut cfg = AppConfig::default();
+
+    for (key, value, shown) in [
+        ("provider", "OWM", "openweather"),
+        ("fallback_providers", "wttr, open-meteo", "wttr,openmeteo"),
+        ("api_key", " secret ", "secret"),
+        ("default_city", "  Lviv ", "Lviv"),
+        ("auto_locate", "false", "off"),
+        ("units", "Imperial", "imperial"),
+        ("lang", " uk ", "uk"),
+        ("pager", "less -R", "less -R"),
+        ("log_format", "JSON", "json"),
+        ("cache_ttl", " 300", "300"),
+        ("stale_fallback", "Yes", "on"),
+        ("notify", "on", "on"),
+        ("history", "yes", "on"),
+        ("retries", "5", "5"),
+        ("rate_limit_wait", " 30", "30"),
+        ("timeout", "20", "20"),
+        ("proxy", " http://proxy:8080 ", "http://proxy:8080"),
+        (
+            "ca_bundle",
+            "tests/fixtures/ca.pem",
+            "tests/fixtures/ca.pem",
+        ),
+    ] {
+        cfg.set_key(key, value).unwrap();
+        assert_eq!(cfg.get_key(key).unwrap().as_deref(), Some(shown), "{}", key);
+    }
+    assert_eq!(
+        cfg.fallback_providers,
+        vec![ProviderKind::Wttr, ProviderKind::OpenMeteo]
+    );
+
+    for key in CONFIG_KEYS.into_iter().skip(1) {
+        cfg.unset_key(key).unwrap();
+    }
+    assert_eq!(cfg.get_key("auto_locate").unwrap().as_deref(), Some("on"));
+    assert_eq!(
+        cfg.get_key("stale_fallback").unwrap().as_deref(),
+        Some("off")
+    );
+    let json = serde_json::to_string(&cfg).unwrap();
+    assert_eq!(json, r#"{"provider":"openweather"}"#);
+}
+
+#[test]
+fn test_config_key_validation() {
+    let mut cfg = AppConfig::default();
+
+    let err = cfg.set_key("color", "on").unwrap_err().to_string();
+    assert!(err.starts_with("unknown config key 'color'. Valid keys: provider, fallback_providers"));
+    assert!(cfg.unset_key("rules").is_err());
+    assert!(cfg.get_key("locations").is_err());
+
+    assert!(cfg.set_key("provider", "darksky").is_err());
+    assert!(cfg.set_key("fallback_providers", "wttr,nope").is_err());
+    assert!(cfg.set_key("default_city", "Kyiv\nLviv").is_err());
+    assert!(cfg.set_key("api_key", " ").is_err());
+    assert_eq!(
+        cfg.set_key("auto_locate", "maybe").unwrap_err().to_string(),
+        "invalid value 'maybe' for auto_locate: expected on or off"
+    );
+    assert!(cfg.set_key("log_format", "xml").is_err());
+    assert!(cfg.set_key("units", "kelvin").is_err());
+    assert!(cfg.set_key("lang", "e n").is_err());
+    assert!(cfg.unset_key("provider").is_err());
+
+    // Nothing was changed by the failed calls.
+    assert_eq!(
+        serde_json::to_string(&cfg).unwrap(),
+        r#"{"provider":"weatherapi"}"#
+    );
+}
+
+#[test]
+fn test_run_config_command() {
+    let mut cfg = AppConfig::default();
+
+    let set = ConfigCommand::Set {
+        key: "api_key".into(),
+        value: "secret".into(),
+    };
+    assert_eq!(
+        run_config_command(&mut cfg, set).unwrap(),
+        "Set api_key to <redacted>\n"
+    );
+    assert_eq!(cfg.api_keys["weatherapi"], "secret");
+
+    let set = ConfigCommand::Set {
+        key: "default_city".into(),
+        value: "Kyiv".into(),
+    };
+    assert_eq!(
+        run_config_command(&mut cfg, set).unwrap(),
+        "Set default_city to Kyiv\n"
+    );
+
+    let unset = ConfigCommand::Unset {
+        key: "default_city".into(),
+    };
+    assert_eq!(
+        run_config_command(&mut cfg, unset).unwrap(),
+        "Unset default_city\n"
+    );
+    assert_eq!(cfg.default_city, None);
+
+    let path = run_config_command(&mut cfg, ConfigCommand::Path).unwrap();
+    assert!(path.trim_end().ends_with("config.json"));
+    assert!(std::path::Path::new(path.trim_end()).is_absolute());
+}
+
+#[test]
+fn test_legacy_api_key_belongs_to_the_provider() {
+    let cfg = parse_config(r#"{"provider":"owm","api_key":"old"}"#).unwrap();
+
+    assert_eq!(cfg.api_keys["openweather"], "old");
+    assert_eq!(
+        serde_json::to_string(&cfg).unwrap(),
+        r#"{"provider":"openweather","api_keys":{"openweather":"old"}}"#
+    );
+
+    // A per-provider key wins over the old one.
+    let cfg = parse_config(
+        r#"{"provider":"wttr","api_key":"old","api_keys":{"wttr":"new","metno":"m"}}"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.api_keys["wttr"], "new");
+    assert_eq!(cfg.api_keys.len(), 2);
+}
+
+#[test]
+fn test_api_key_is_the_selected_providers() {
+    let mut cfg = AppConfig {
+        provider: ProviderKind::OpenWeather,
+        api_keys: [("weatherapi".to_string(), "w".to_string())].into(),
+        ..Default::default()
+    };
+    assert_eq!(cfg.get_key("api_key").unwrap(), None);
+
+    cfg.set_key("api_key", "o").unwrap();
+    cfg.set_key("provider", "weatherapi").unwrap();
+    assert_eq!(cfg.get_key("api_key").unwrap().as_deref(), Some("w"));
+
+    cfg.unset_key("api_key").unwrap();
+    assert_eq!(
+        cfg.api_keys,
+        [("openweather".to_string(), "o".to_string())].into()
+    );
+}
+
+/// A config using every section, so both formats are exercised fully.
+fn full_config() -> AppConfig {
+    parse_config(
+        r#"{
+            "provider": "openweather",
+            "fallback_providers": ["wttr"],
+            "api_keys": {"openweather": "secret"},
+            "rules": [{"name": "hot", "expr": "temp_c > 30", "action": "log"}],
+            "providers": {"weatherapi": {"extra_params": {"aqi": "yes"}, "kind_overrides": {"forecast": {"days": 7}}}},
+            "auto_locate": false,
+            "default_city": "Kyiv",
+            "units": "metric",
+            "lang": "uk",
+            "locations": {"home": {"city": "Lviv"}, "office": {"lat": 50.45, "lon": 30.52}}
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_both_formats_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let cfg = full_config();
+    let expected = serde_json::to_value(&cfg).unwrap();
+
+    for name in ["config.json", "config.toml"] {
+        let path = dir.path().join(name);
+        save_config_to(&path, &cfg).unwrap();
+        let loaded = load_config_from(&path).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), expected, "{}", name);
+    }
+
+    // The extension picks the syntax.
+    let toml = fs::read_to_string(dir.path().join("config.toml")).unwrap();
+    assert!(toml.contains("provider = \"openweather\""));
+    assert_eq!(
+        ConfigFormat::of(&dir.path().join("config.toml")),
+        ConfigFormat::Toml
+    );
+    assert_eq!(
+        ConfigFormat::of(&dir.path().join("config.json")),
+        ConfigFormat::Json
+    );
+}
+
+#[test]
+fn test_toml_config_is_read() {
+    let cfg = parse_toml_config(
+        "provider = \"wttr.in\"\ndefault_city = \"Odesa\"\napi_key = \"old\"\n\n[locations.home]\ncity = \"Kyiv\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(cfg.provider, ProviderKind::Wttr);
+    assert_eq!(cfg.default_city.as_deref(), Some("Odesa"));
+    assert_eq!(cfg.api_keys["wttr"], "old");
+    assert_eq!(
+        cfg.locations["home"],
+        SavedLocation::City {
+            city: "Kyiv".into()
+        }
+    );
+}
+
+#[test]
+fn test_malformed_toml_names_the_file_and_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "provider = \"wttr\"\nunits = metric\n").unwrap();
+
+    let err = load_config_from(&path).unwrap_err().to_string();
+    assert!(err.starts_with(&format!("invalid config {}", path.display())));
+    assert!(err.contains("line 2"), "{}", err);
+}
+
+#[test]
+fn test_provider_sources() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    let saved = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        fallback_providers: vec![ProviderKind::Wttr],
+        ..Default::default()
+    };
+    save_config_to(&path, &saved).unwrap();
+    let file = || Some(load_config_from(&path).unwrap());
+
+    // Environment only: no config file is needed; aliases are accepted.
+    let cfg = config_for(None, Some("owm"), None).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::OpenWeather);
+
+    // Config only.
+    let cfg = config_for(None, None, file()).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::WeatherApi);
+    let cfg = config_for(None, Some(" "), file()).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::WeatherApi);
+
+    // Both: the environment wins and the saved fallbacks stay.
+    let cfg = config_for(None, Some("metno"), file()).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::MetNo);
+    assert_eq!(cfg.fallback_providers, vec![ProviderKind::Wttr]);
+
+    // --provider beats the environment.
+    let cfg = config_for(Some(ProviderKind::Wttr), Some("metno"), file()).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::Wttr);
+
+    // Neither.
+    let err = config_for(None, None, None).unwrap_err().to_string();
+    assert!(err.ends_with("Run: wapp configure <provider>, or set WAPP_PROVIDER"));
+}
+
+#[test]
+fn test_invalid_provider_env() {
+    let err = config_for(None, Some("weatherapii"), None)
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.starts_with("WAPP_PROVIDER: provider 'weatherapii' is not supported"));
+    assert!(err.contains("did you mean 'weatherapi'?"));
 }
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use wapp::config::AppConfig;
+use wapp::providers::credentials::{resolve_key_with, CredentialStore};
+use wapp::providers::{
+    provider_factory_with, OpenWeatherProvider, ProviderKind, ProviderOverrides, WeatherApiProvider,
+};
+
+fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let map: HashMap<String, String> = pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    move |name| map.get(name).cloned()
+}
+
+fn key_file(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn test_plain_variable() {
+    let key = resolve_key_with("DEMO_KEY", vars(&[("DEMO_KEY", "abc")])).unwrap();
+    assert_eq!(key, "abc");
+}
+
+#[test]
+fn test_file_variable_trims_trailing_newline() {
+    let file = key_file("secret123\n");
+    let path = file.path().to_str().unwrap();
+
+    let key = resolve_key_with("DEMO_KEY", vars(&[("DEMO_KEY_FILE", path)])).unwrap();
+    assert_eq!(key, "secret123");
+
+    let file = key_file("secret123\r\n");
+    let path = file.path().to_str().unwrap();
+
+    let key = resolve_key_with("DEMO_KEY", vars(&[("DEMO_KEY_FILE", path)])).unwrap();
+    assert_eq!(key, "secret123");
+}
+
+#[test]
+fn test_both_variants_conflict() {
+    let file = key_file("secret123");
+    let path = file.path().to_str().unwrap();
+
+    let err = resolve_key_with(
+        "DEMO_KEY",
+        vars(&[("DEMO_KEY", "abc"), ("DEMO_KEY_FILE", path)]),
+    )
+    .unwrap_err()
+    .to_string();
+
+    assert!(err.contains("both DEMO_KEY and DEMO_KEY_FILE"));
+}
+
+#[test]
+fn test_missing_and_empty_files() {
+    let err = resolve_key_with("DEMO_KEY", vars(&[("DEMO_KEY_FILE", "/nonexistent/key")]))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("cannot read DEMO_KEY_FILE"));
+
+    let file = key_file("\n");
+    let path = file.path().to_str().unwrap();
+    let err = resolve_key_with("DEMO_KEY", vars(&[("DEMO_KEY_FILE", path)]))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("empty file"));
+}
+
+#[test]
+fn test_neither_variant_set() {
+    let err = resolve_key_with("DEMO_KEY", vars(&[]))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("DEMO_KEY (or DEMO_KEY_FILE) is not set"));
+}
+
+fn store(
+    explicit: Option<&str>,
+    configured: Option<&str>,
+    env: &[(&str, &str)],
+    keyring: &[(&str, &str)],
+) -> CredentialStore {
+    CredentialStore::with_lookups(
+        explicit.map(String::from),
+        configured.map(String::from),
+        vars(env),
+        vars(keyring),
+    )
+}
+
+#[test]
+fn test_explicit_key_takes_precedence() {
+    let credentials = store(
+        Some("flag"),
+        Some("config"),
+        &[("DEMO_KEY", "env"), ("DEMO_KEY_FILE", "/also/set")],
+        &[("demo", "keyring")],
+    );
+
+    assert_eq!(credentials.api_key("DEMO_KEY", "demo").unwrap(), "flag");
+}
+
+#[test]
+fn test_key_precedence_after_the_flag() {
+    let env = [("DEMO_KEY", "env")];
+    let keyring = [("demo", "keyring")];
+
+    let credentials = store(None, Some("config"), &env, &keyring);
+    assert_eq!(credentials.api_key("DEMO_KEY", "demo").unwrap(), "env");
+
+    let credentials = store(None, Some("config"), &[], &keyring);
+    assert_eq!(credentials.api_key("DEMO_KEY", "demo").unwrap(), "keyring");
+
+    // Keyring entries are per provider.
+    let credentials = store(None, Some("config"), &[], &keyring);
+    assert_eq!(credentials.api_key("OTHER_KEY", "other").unwrap(), "config");
+
+    let credentials = store(None, None, &[], &[]);
+    assert_eq!(
+        credentials
+            .api_key("DEMO_KEY", "demo")
+            .unwrap_err()
+            .to_string(),
+        "DEMO_KEY (or DEMO_KEY_FILE) is not set"
+    );
+}
+
+#[test]
+fn test_env_error_is_not_hidden_by_stored_keys() {
+    let credentials = store(
+        None,
+        Some("config"),
+        &[("DEMO_KEY", "env"), ("DEMO_KEY_FILE", "/also/set")],
+        &[("demo", "keyring")],
+    );
+
+    assert!(credentials
+        .api_key("DEMO_KEY", "demo")
+        .unwrap_err()
+        .to_string()
+        .starts_with("both DEMO_KEY and DEMO_KEY_FILE are set"));
+}
+
+#[test]
+fn test_provider_uses_explicit_key() {
+    let credentials = store(Some("flag-key"), Some("config-key"), &[], &[]);
+    let overrides = ProviderOverrides::default();
+
+    let provider = WeatherApiProvider::from_sources(&overrides, &credentials).unwrap();
+    assert_eq!(provider.api_key, "flag-key");
+
+    let provider = OpenWeatherProvider::from_sources(&overrides, &credentials).unwrap();
+    assert_eq!(provider.api_key, "flag-key");
+}
+
+#[test]
+fn test_factory_uses_the_providers_saved_key() {
+    std::env::remove_var("TOMORROWIO_KEY");
+    std::env::remove_var("TOMORROWIO_KEY_FILE");
+
+    let cfg = AppConfig {
+        provider: ProviderKind::Wttr,
+        api_keys: [("tomorrowio".to_string(), "saved".to_string())].into(),
+        ..Default::default()
+    };
+    let overrides = ProviderOverrides {
+        provider: Some(ProviderKind::TomorrowIo),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        provider_factory_with(&cfg, &overrides).unwrap().name(),
+        "tomorrowio"
+    );
+}
+
+#[test]
+fn test_keys_redacted_in_debug_output() {
+    let overrides = ProviderOverrides {
+        api_key: Some("flag-key".into()),
+        ..Default::default()
+    };
+    let cfg = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        api_keys: [("weatherapi".to_string(), "config-key".to_string())].into(),
+        ..Default::default()
+    };
+
+    let overrides = format!("{:?}", overrides);
+    let cfg = format!("{:?}", cfg);
+
+    assert!(!overrides.contains("flag-key"));
+    assert!(!cfg.contains("config-key"));
+    assert!(overrides.contains("<redacted>"));
+    assert!(cfg.contains("<redacted>"));
+}
@@ -0,0 +1,291 @@
+mod mocks;
+
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use wapp::location::Location;
+use wapp::providers::{ApiProvider, Capabilities, DataKind, STANDARD_KINDS};
+
+use mocks::scripted_provider::ScriptedProvider;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use wapp::daemon::{render_snapshot, run, stale_marker_path, DaemonOptions, SnapshotFormat};
+use wapp::fsutil::write_atomic;
+
+fn options(dir: &Path, format: SnapshotFormat) -> DaemonOptions {
+    DaemonOptions {
+        location: "Kyiv".into(),
+        kind: DataKind::Now,
+        interval: Duration::from_secs(10),
+        output: dir.join("current.json"),
+        format,
+        stale_after: Duration::from_secs(15),
+        rules: Vec::new(),
+    }
+}
+
+/// Returns a token cancelled after `secs` of (paused) time.
+fn cancel_after(secs: u64) -> CancellationToken {
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        trigger.cancel();
+    });
+    token
+}
+
+/// Runs the daemon loop with time paused, shutting down after `secs`.
+async fn run_for(provider: &ScriptedProvider, options: &DaemonOptions, secs: u64) {
+    run(provider, options, &cancel_after(secs)).await.unwrap();
+}
+
+fn entries(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_each_success_replaces_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Json);
+    let provider = ScriptedProvider::new(vec![Ok("{\"n\":1}"), Ok("{\"n\":2}")]);
+
+    // Ticks at t=0 and t=10; shut down before t=20.
+    run_for(&provider, &opts, 15).await;
+
+    assert_eq!(fs::read_to_string(&opts.output).unwrap(), "{\"n\":2}");
+    // No temporary files are left behind.
+    assert_eq!(entries(dir.path()), vec!["current.json"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failure_keeps_previous_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Json);
+    let provider = ScriptedProvider::new(vec![Ok("{\"n\":1}"), Err("network down")]);
+
+    run_for(&provider, &opts, 15).await;
+
+    assert_eq!(fs::read_to_string(&opts.output).unwrap(), "{\"n\":1}");
+    // Data is 10s old, below the 15s threshold.
+    assert!(!stale_marker_path(&opts.output).exists());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_stale_marker_written_when_data_gets_old() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Json);
+    let provider = ScriptedProvider::new(vec![Ok("{\"n\":1}"), Err("down"), Err("down")]);
+
+    // Ticks at t=0 (ok), t=10 (fail), t=20 (fail, data 20s old).
+    run_for(&provider, &opts, 25).await;
+
+    let marker = stale_marker_path(&opts.output);
+    assert!(marker.exists());
+    assert!(fs::read_to_string(marker)
+        .unwrap()
+        .contains("age_seconds=20"));
+    assert_eq!(fs::read_to_string(&opts.output).unwrap(), "{\"n\":1}");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_stale_marker_removed_after_recovery() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Json);
+    let provider = ScriptedProvider::new(vec![
+        Ok("{\"n\":1}"),
+        Err("down"),
+        Err("down"),
+        Ok("{\"n\":2}"),
+    ]);
+
+    run_for(&provider, &opts, 35).await;
+
+    assert!(!stale_marker_path(&opts.output).exists());
+    assert_eq!(fs::read_to_string(&opts.output).unwrap(), "{\"n\":2}");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_never_successful_becomes_stale() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Json);
+    let provider = ScriptedProvider::new(vec![Err("down")]);
+
+    // Every tick fails; by t=20 the daemon has gone 20s without data.
+    run_for(&provider, &opts, 25).await;
+
+    assert!(!opts.output.exists());
+    assert!(stale_marker_path(&opts.output).exists());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_unwritable_stale_marker_keeps_daemon_running() {
+    let dir = tempfile::tempdir().unwrap();
+    // Neither the snapshot nor its marker can be written here.
+    let opts = options(&dir.path().join("missing"), SnapshotFormat::Json);
+    let provider = ScriptedProvider::new(vec![Err("down")]);
+
+    // Stale from t=20 on; the ticks at t=30 and t=40 still fetch.
+    run_for(&provider, &opts, 45).await;
+
+    assert_eq!(provider.calls().len(), 5);
+    assert!(!stale_marker_path(&opts.output).exists());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_oneline_snapshot_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Oneline);
+    let provider = ScriptedProvider::new(vec![Ok("{\n  \"temp\": 21.5,\n  \"city\": \"Kyiv\"\n}")]);
+
+    run_for(&provider, &opts, 5).await;
+
+    let written = fs::read_to_string(&opts.output).unwrap();
+    assert_eq!(written.lines().count(), 1);
+    assert!(written.contains("\"temp\":21.5"));
+}
+
+#[test]
+fn test_render_oneline_for_non_json() {
+    assert_eq!(
+        render_snapshot("not\n  json", SnapshotFormat::Oneline),
+        "not json\n"
+    );
+}
+
+#[test]
+fn test_write_atomic_replaces_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.json");
+
+    write_atomic(&path, b"first").unwrap();
+    write_atomic(&path, b"second").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+    assert_eq!(entries(dir.path()), vec!["snapshot.json"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_write_atomic_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+
+    write_atomic(&path, b"api_key = \"secret\"").unwrap();
+
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn test_write_atomic_failure_keeps_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("missing-dir").join("snapshot.json");
+
+    assert!(write_atomic(&path, b"data").is_err());
+    assert!(entries(dir.path()).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_exit_code_rule_stops_daemon_after_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut opts = options(dir.path(), SnapshotFormat::Json);
+    opts.rules = vec![wapp::rules::Rule {
+        name: "frost".into(),
+        expr: "temp_c < 0".into(),
+        action: wapp::rules::RuleAction::ExitCode,
+        url: None,
+        cooldown_secs: None,
+    }];
+    let provider = ScriptedProvider::new(vec![
+        Ok(r#"{"current":{"temp_c":2}}"#),
+        Ok(r#"{"current":{"temp_c":-1}}"#),
+        Ok(r#"{"current":{"temp_c":-5}}"#),
+    ]);
+
+    let result = run(&provider, &opts, &cancel_after(100)).await;
+
+    assert!(result.unwrap_err().is::<wapp::rules::RuleExit>());
+    assert_eq!(provider.calls().len(), 2);
+    assert_eq!(
+        fs::read_to_string(&opts.output).unwrap(),
+        r#"{"current":{"temp_c":-1}}"#
+    );
+}
+
+/// Answers the first request, then hangs until the request is dropped.
+struct HangingProvider {
+    calls: AtomicUsize,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Sets the flag when the in-flight request future is dropped.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl ApiProvider for HangingProvider {
+    fn name(&self) -> &str {
+        "hanging"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 3,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    async fn get_data(
+        &self,
+        _location: Location,
+        _when: DataKind,
+        _days: Option<u32>,
+    ) -> anyhow::Result<String> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Ok(r#"{"current":{"temp_c":1}}"#.into());
+        }
+
+        let _flag = DropFlag(self.cancelled.clone());
+        std::future::pending().await
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_cancels_request_in_flight() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path(), SnapshotFormat::Json);
+    let provider = HangingProvider {
+        calls: AtomicUsize::new(0),
+        cancelled: Arc::new(AtomicBool::new(false)),
+    };
+
+    // The second fetch starts at t=10s and never answers; shut down at t=12s.
+    let started = tokio::time::Instant::now();
+    run(&provider, &opts, &cancel_after(12)).await.unwrap();
+
+    assert_eq!(started.elapsed(), Duration::from_secs(12));
+    assert_eq!(provider.calls.load(Ordering::SeqCst), 2);
+    assert!(provider.cancelled.load(Ordering::SeqCst));
+    // The last complete snapshot stays, with no temporary files left behind.
+    assert_eq!(
+        fs::read_to_string(&opts.output).unwrap(),
+        r#"{"current":{"temp_c":1}}"#
+    );
+    assert_eq!(entries(dir.path()), vec!["current.json"]);
+}
@@ -0,0 +1,168 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::providers::{openweather, weatherapi};
+use wapp::cli::{Cli, Commands};
+use wapp::output::color::Palette;
+use wapp::output::render_details;
+use wapp::providers::ApiProvider;
+use wapp::units::{f_to_c, Units};
+use wapp::weather::{dew_point, WeatherData};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const OPENWEATHER_CURRENT: &str = include_str!("fixtures/openweather_current.json");
+const OPENWEATHER_ONECALL: &str = include_str!("fixtures/openweather_onecall.json");
+
+fn assert_near(actual: Option<f64>, expected: f64) {
+    let actual = actual.unwrap();
+    assert!(
+        (actual - expected).abs() < 0.05,
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_dew_point_matches_reference_values() {
+    assert_near(dew_point(20.0, 50.0), 9.3);
+    assert_near(dew_point(25.0, 60.0), 16.7);
+    assert_near(dew_point(30.0, 80.0), 26.2);
+    assert_near(dew_point(-10.0, 70.0), -14.4);
+
+    // Saturated air is at its dew point.
+    assert_near(dew_point(0.0, 100.0), 0.0);
+    assert_near(dew_point(10.0, 100.0), 10.0);
+
+    assert_eq!(dew_point(20.0, 0.0), None);
+}
+
+#[test]
+fn test_weatherapi_fields_are_mapped() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_CURRENT)
+        .unwrap();
+
+    assert_eq!(data.pressure_mb, Some(1017.0));
+    assert_eq!(data.visibility_km, Some(10.0));
+    assert_eq!(data.cloud, Some(0.0));
+    // No `dewpoint_c` in the fixture: computed from 24 °C at 36%.
+    assert_near(data.dew_point_c, 8.0);
+
+    let mut json: serde_json::Value = serde_json::from_str(WEATHERAPI_CURRENT).unwrap();
+    json["current"]["dewpoint_c"] = 7.2.into();
+    let data = weatherapi("http://localhost")
+        .weather(&json.to_string())
+        .unwrap();
+    assert_eq!(data.dew_point_c, Some(7.2));
+}
+
+#[test]
+fn test_openweather_fields_are_mapped() {
+    let data = openweather("http://localhost")
+        .with_units("metric")
+        .weather(OPENWEATHER_CURRENT)
+        .unwrap();
+
+    assert_eq!(data.pressure_mb, Some(1017.0));
+    assert_eq!(data.visibility_km, Some(10.0));
+    assert_eq!(data.cloud, Some(20.0));
+    // `/weather` has no dew point: computed from 23.46 °C at 46%.
+    assert_near(data.dew_point_c, 11.2);
+}
+
+#[test]
+fn test_openweather_onecall_fields_are_mapped() {
+    let data = openweather("http://localhost")
+        .with_units("metric")
+        .weather(OPENWEATHER_ONECALL)
+        .unwrap();
+
+    assert_eq!(data.pressure_mb, Some(1014.0));
+    assert_eq!(data.visibility_km, Some(10.0));
+    assert_eq!(data.cloud, Some(40.0));
+    // One Call's own, not the 12.06 computed.
+    assert_eq!(data.dew_point_c, Some(12.1));
+
+    // In the configured units, as the temperatures.
+    let data = openweather("http://localhost")
+        .with_units("imperial")
+        .weather(OPENWEATHER_ONECALL)
+        .unwrap();
+    assert_near(data.dew_point_c, f_to_c(12.1));
+}
+
+#[test]
+fn test_details_in_metric_and_imperial() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_CURRENT)
+        .unwrap();
+
+    assert_eq!(
+        render_details(&data, Palette::PLAIN, Units::Metric),
+        "  Pressure:    1017 hPa\n\
+         \x20 Visibility:  10.0 km\n\
+         \x20 Dew point:   8.0 °C\n\
+         \x20 Comfort:     pleasant\n\
+         \x20 Cloud cover: 0%\n"
+    );
+    assert_eq!(
+        render_details(&data, Palette::PLAIN, Units::Imperial),
+        "  Pressure:    30.03 inHg\n\
+         \x20 Visibility:  6.2 mi\n\
+         \x20 Dew point:   46.4 °F\n\
+         \x20 Comfort:     pleasant\n\
+         \x20 Cloud cover: 0%\n"
+    );
+}
+
+#[test]
+fn test_unavailable_details_are_left_out() {
+    let data = WeatherData {
+        pressure_mb: Some(1009.4),
+        cloud: Some(75.0),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        render_details(&data, Palette::PLAIN, Units::Metric),
+        "  Pressure:    1009 hPa\n\
+         \x20 Cloud cover: 75%\n"
+    );
+    assert_eq!(
+        render_details(&WeatherData::default(), Palette::PLAIN, Units::Metric),
+        ""
+    );
+}
+
+#[test]
+fn test_json_carries_the_known_details() {
+    let data = openweather("http://localhost")
+        .with_units("metric")
+        .weather(OPENWEATHER_ONECALL)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&data.to_json()).unwrap();
+
+    assert_eq!(json["pressure_mb"], 1014.0);
+    assert_eq!(json["visibility_km"], 10.0);
+    assert_eq!(json["dew_point_c"], 12.1);
+    assert_eq!(json["cloud"], 40.0);
+
+    let json: serde_json::Value = serde_json::from_str(&WeatherData::default().to_json()).unwrap();
+    assert!(json.get("visibility_km").is_none());
+}
+
+#[test]
+fn test_parse_detailed() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--detailed"]);
+    match cli.cmd {
+        Commands::Get { detailed, .. } => assert!(detailed),
+        _ => panic!("wrong command parsed"),
+    }
+
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv"]);
+    match cli.cmd {
+        Commands::Get { detailed, .. } => assert!(!detailed),
+        _ => panic!("wrong command parsed"),
+    }
+}
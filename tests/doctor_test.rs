@@ -0,0 +1,264 @@
+mod mocks;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use assert_cmd::Command;
+use serde_json::Value;
+use wapp::doctor::{failed, render_json, render_text, Check, Status};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// `wapp doctor --output json` with its config, cache and data below `dir`,
+/// and WeatherAPI at `base_url`. The config names WeatherAPI.
+fn doctor(dir: &Path, base_url: &str) -> Command {
+    std::fs::write(dir.join("config.json"), r#"{"provider":"weatherapi"}"#).unwrap();
+
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.env_remove("WAPP_PROVIDER")
+        .args(["doctor", "--output", "json"]);
+    cmd
+}
+
+/// A server answering the key check with `status`.
+async fn serve(status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .respond_with(ResponseTemplate::new(status).set_body_string(WEATHERAPI_CURRENT))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Runs `cmd` and returns whether it succeeded and the status of each check.
+fn run(cmd: &mut Command) -> (bool, Vec<(String, String)>, Value) {
+    let output = cmd.output().unwrap();
+    let json: Value =
+        serde_json::from_slice(&output.stdout).unwrap_or_else(|e| panic!("{}: {:?}", e, output));
+    let statuses = json["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|check| {
+            (
+                check["name"].as_str().unwrap().to_string(),
+                check["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    (output.status.success(), statuses, json)
+}
+
+fn status_of<'a>(statuses: &'a [(String, String)], name: &str) -> &'a str {
+    &statuses.iter().find(|(n, _)| n == name).unwrap().1
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_healthy_setup_passes_every_check() {
+    let server = serve(200).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let (ok, statuses, json) = run(&mut doctor(dir.path(), &server.uri()));
+    assert!(ok, "{}", json);
+    assert_eq!(json["ok"], true);
+    assert_eq!(
+        statuses.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(),
+        [
+            "config",
+            "provider",
+            "credentials",
+            "dns",
+            "connect",
+            "api_key",
+            "config_dir",
+            "cache_dir",
+            "data_dir"
+        ]
+    );
+    assert!(statuses.iter().all(|(_, s)| s == "pass"), "{}", json);
+    assert!(!json.to_string().contains("\"KEY\""), "{}", json);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rejected_key_fails_with_a_hint() {
+    let server = serve(401).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut cmd = doctor(dir.path(), &server.uri());
+    let (ok, statuses, json) = run(&mut cmd);
+    assert!(!ok);
+    assert_eq!(json["ok"], false);
+    assert_eq!(status_of(&statuses, "connect"), "pass");
+    assert_eq!(status_of(&statuses, "api_key"), "fail");
+    let check = &json["checks"][5];
+    assert!(
+        check["message"]
+            .as_str()
+            .unwrap()
+            .contains("rejected the API key"),
+        "{}",
+        check
+    );
+    assert!(
+        check["hint"]
+            .as_str()
+            .unwrap()
+            .contains("wapp configure weatherapi --key KEY"),
+        "{}",
+        check
+    );
+
+    let output = cmd.output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 critical check failed"), "{}", stderr);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_missing_key_skips_the_network_checks() {
+    let server = serve(200).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let (ok, statuses, json) = run(doctor(dir.path(), &server.uri()).env_remove("WEATHERAPI_KEY"));
+    assert!(!ok);
+    assert_eq!(status_of(&statuses, "credentials"), "fail");
+    assert!(json["checks"][2]["hint"]
+        .as_str()
+        .unwrap()
+        .starts_with("set WEATHERAPI_KEY"));
+    for name in ["dns", "connect", "api_key"] {
+        assert_eq!(status_of(&statuses, name), "skip", "{}", name);
+    }
+    assert_eq!(status_of(&statuses, "cache_dir"), "pass");
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_missing_and_broken_configs() {
+    let server = serve(200).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut cmd = doctor(dir.path(), &server.uri());
+    std::fs::remove_file(dir.path().join("config.json")).unwrap();
+    let (ok, statuses, _) = run(&mut cmd);
+    assert!(!ok);
+    assert_eq!(status_of(&statuses, "config"), "fail");
+    assert_eq!(status_of(&statuses, "provider"), "skip");
+
+    // A provider from the environment needs no config file.
+    let (ok, statuses, json) = run(cmd.env("WAPP_PROVIDER", "weatherapi"));
+    assert!(ok, "{}", json);
+    assert_eq!(status_of(&statuses, "config"), "warn");
+    assert_eq!(status_of(&statuses, "api_key"), "pass");
+
+    std::fs::write(dir.path().join("config.json"), "{ not json").unwrap();
+    let (ok, statuses, _) = run(&mut cmd);
+    assert!(!ok);
+    assert_eq!(status_of(&statuses, "config"), "fail");
+    assert_eq!(status_of(&statuses, "credentials"), "skip");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unreachable_provider_fails_to_connect() {
+    let dir = tempfile::tempdir().unwrap();
+    // Nothing listens on a port of a listener that was just closed.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let (ok, statuses, json) = run(&mut doctor(
+        dir.path(),
+        &format!("http://127.0.0.1:{}", port),
+    ));
+    assert!(!ok);
+    assert_eq!(status_of(&statuses, "dns"), "pass");
+    assert_eq!(status_of(&statuses, "connect"), "fail", "{}", json);
+    assert_eq!(status_of(&statuses, "api_key"), "skip");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_slow_provider_times_out() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(30)))
+        .mount(&server)
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let started = Instant::now();
+    let (ok, statuses, json) = run(doctor(dir.path(), &server.uri()).args(["--timeout", "1"]));
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(!ok);
+    assert_eq!(status_of(&statuses, "api_key"), "fail", "{}", json);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unwritable_cache_is_a_warning() {
+    let server = serve(200).await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("cache"), "").unwrap();
+
+    let (ok, statuses, json) = run(&mut doctor(dir.path(), &server.uri()));
+    assert!(ok, "{}", json);
+    assert_eq!(status_of(&statuses, "cache_dir"), "warn");
+    assert!(json["checks"][7]["hint"]
+        .as_str()
+        .unwrap()
+        .contains("WAPP_CACHE_DIR"));
+}
+
+#[test]
+fn test_text_report() {
+    let checks = [
+        Check::pass("config", "/etc/wapp/config.json"),
+        Check::fail(
+            "credentials",
+            "WEATHERAPI_KEY is not set",
+            "set WEATHERAPI_KEY",
+        ),
+        Check::skip("dns", "needs the provider's credentials"),
+        Check::warn("cache_dir", "cannot write /cache", "make it writable"),
+    ];
+
+    assert_eq!(
+        render_text(&checks),
+        [
+            "PASS  config       /etc/wapp/config.json",
+            "FAIL  credentials  WEATHERAPI_KEY is not set",
+            "                   hint: set WEATHERAPI_KEY",
+            "SKIP  dns          needs the provider's credentials",
+            "WARN  cache_dir    cannot write /cache",
+            "                   hint: make it writable",
+            "1 check failed",
+            "",
+        ]
+        .join("\n")
+    );
+    assert_eq!(failed(&checks), 1);
+
+    assert!(render_text(&checks[..1]).ends_with("\nAll checks passed\n"));
+    assert!(render_text(&[checks[0].clone(), checks[3].clone()])
+        .ends_with("\nAll critical checks passed, 1 warning\n"));
+}
+
+#[test]
+fn test_json_report() {
+    let checks = [
+        Check::pass("dns", "api.weatherapi.com resolves to 192.0.2.1"),
+        Check::warn("data_dir", "cannot write /data", "make it writable"),
+    ];
+
+    let json: Value = serde_json::from_str(&render_json(&checks)).unwrap();
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["checks"][0]["status"], "pass");
+    assert!(json["checks"][0].get("hint").is_none());
+    assert_eq!(json["checks"][1]["status"], "warn");
+    assert_eq!(json["checks"][1]["hint"], "make it writable");
+    assert_eq!(Check::skip("dns", "x\ny").message, "x y");
+    assert_eq!(Check::skip("dns", "x").status, Status::Skip);
+}
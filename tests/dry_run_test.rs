@@ -0,0 +1,180 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::providers::weatherapi;
+use wapp::cli::{render_dry_run, Cli, Commands};
+use wapp::location::Location;
+use wapp::providers::metno::DEFAULT_USER_AGENT;
+use wapp::providers::{
+    AccuWeatherProvider, ApiProvider, DataKind, MetNoProvider, OpenMeteoProvider, PlannedRequest,
+    WeatherApiProvider, WttrProvider,
+};
+
+fn kyiv_coords() -> Location {
+    Location::Coords {
+        lat: 50.45,
+        lon: 30.52,
+    }
+}
+
+#[test]
+fn test_build_request_uses_the_request_url() {
+    let provider = weatherapi("http://w");
+
+    let plan = provider
+        .build_request(&Location::from("Kyiv"), DataKind::Forecast, Some(3))
+        .unwrap();
+    assert_eq!(
+        plan,
+        PlannedRequest::get(
+            provider
+                .build_url(&Location::from("Kyiv"), DataKind::Forecast, Some(3))
+                .unwrap()
+        )
+    );
+    assert_eq!(plan.method, "GET");
+    assert!(!plan.lookup);
+
+    let plan = provider
+        .build_request(&Location::from("Kyiv"), "2024-01-15".parse().unwrap(), None)
+        .unwrap();
+    assert!(plan.url.contains("/history.json?"));
+    assert!(plan.url.contains("&dt=2024-01-15"));
+}
+
+#[test]
+fn test_geocoding_providers_plan_the_lookup_of_a_city() {
+    let openmeteo = OpenMeteoProvider {
+        base_url: "http://f".into(),
+        geocoding_url: "http://g".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let plan = openmeteo
+        .build_request(&Location::from("Kyiv"), DataKind::Now, None)
+        .unwrap();
+    assert!(plan.lookup);
+    assert!(plan.url.starts_with("http://g/search?name=Kyiv"));
+
+    let plan = openmeteo
+        .build_request(&kyiv_coords(), DataKind::Now, None)
+        .unwrap();
+    assert!(!plan.lookup);
+    assert!(plan
+        .url
+        .starts_with("http://f/forecast?latitude=50.45&longitude=30.52&current_weather=true"));
+
+    let zip = Location::Zip {
+        code: "10001".into(),
+        country: None,
+    };
+    assert!(openmeteo.build_request(&zip, DataKind::Now, None).is_err());
+}
+
+#[test]
+fn test_metno_plans_its_user_agent() {
+    let metno = MetNoProvider {
+        base_url: "http://m".into(),
+        geocoding_url: "http://g".into(),
+        user_agent: DEFAULT_USER_AGENT.into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let plan = metno
+        .build_request(&kyiv_coords(), DataKind::Forecast, None)
+        .unwrap();
+    assert_eq!(plan.url, "http://m/compact?lat=50.4500&lon=30.5200");
+    assert_eq!(
+        plan.headers,
+        vec![("User-Agent".to_string(), DEFAULT_USER_AGENT.to_string())]
+    );
+
+    let plan = metno
+        .build_request(&Location::from("Kyiv"), DataKind::Forecast, None)
+        .unwrap();
+    assert!(plan.lookup);
+    assert!(plan.headers.is_empty());
+}
+
+#[test]
+fn test_accuweather_plans_the_location_key_lookup() {
+    let accuweather = AccuWeatherProvider {
+        api_key: "SECRET".into(),
+        base_url: "http://a".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let plan = accuweather
+        .build_request(&kyiv_coords(), DataKind::Now, None)
+        .unwrap();
+    assert!(plan.lookup);
+    assert_eq!(plan.url, accuweather.geoposition_url(50.45, 30.52));
+    assert!(accuweather
+        .build_request(&Location::Iata("KBP".into()), DataKind::Now, None)
+        .is_err());
+}
+
+#[test]
+fn test_wttr_serves_every_kind_from_one_request() {
+    let wttr = WttrProvider {
+        base_url: "http://t".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let now = wttr
+        .build_request(&Location::from("Kyiv"), DataKind::Now, None)
+        .unwrap();
+    let tomorrow = wttr
+        .build_request(&Location::from("Kyiv"), DataKind::Tomorrow, None)
+        .unwrap();
+    assert_eq!(now, tomorrow);
+    assert_eq!(now.url, wttr.build_url(&Location::from("Kyiv")));
+}
+
+#[test]
+fn test_render_dry_run_hides_the_key() {
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-123".into(),
+        ..weatherapi("http://w")
+    };
+    let plans = vec![
+        provider
+            .build_request(&Location::from("Kyiv"), DataKind::Now, None)
+            .unwrap(),
+        PlannedRequest::lookup("http://g/search?name=Oslo".into()).header("User-Agent", "test/1"),
+    ];
+
+    assert_eq!(
+        render_dry_run(&plans, &[]),
+        "GET http://w/current.json?key=<redacted>&q=Kyiv\n\
+         \n\
+         GET http://g/search?name=Oslo\n\
+         User-Agent: test/1\n\
+         # looks the location up; the weather request is built from the answer\n"
+    );
+}
+
+#[test]
+fn test_parse_dry_run() {
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--dry-run"]).unwrap();
+    assert!(matches!(cli.cmd, Commands::Get { dry_run: true, .. }));
+
+    for conflicting in ["--offline", "--status-line"] {
+        assert!(Cli::try_parse_from(vec![
+            "wapp",
+            "get",
+            "--city",
+            "Kyiv",
+            "--dry-run",
+            conflicting
+        ])
+        .is_err());
+    }
+}
@@ -0,0 +1,241 @@
+mod mocks;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use clap::Parser;
+use mocks::http_server::{HttpServer, Reply};
+use wapp::cache::NoCachedData;
+use wapp::cli::Cli;
+use wapp::error::{ErrorFormat, ErrorReport, WappError};
+use wapp::exit::{NETWORK_EXIT_CODE, USAGE_EXIT_CODE};
+use wapp::providers::{NOT_FOUND_EXIT_CODE, RATE_LIMITED_EXIT_CODE};
+use wapp::request::RaceFailed;
+
+const NO_MATCH: &str = r#"{"error":{"code":1006,"message":"No matching location found."}}"#;
+
+fn wapp(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.env("WEATHERAPI_KEY", "SECRETKEY").args([
+        "--retries",
+        "1",
+        "get",
+        "--provider",
+        "weatherapi",
+    ]);
+    cmd
+}
+
+/// The exit code of `cmd`, and its stderr read as one JSON object.
+fn failure(cmd: &mut Command) -> (Option<i32>, serde_json::Value) {
+    let output = cmd.output().unwrap();
+    assert!(!output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 1, "{}", stderr);
+    let json = serde_json::from_str(&stderr).unwrap_or_else(|e| panic!("{}: {}", e, stderr));
+    (output.status.code(), json)
+}
+
+fn error_format(args: &[&str]) -> ErrorFormat {
+    Cli::parse_from([&["wapp"], args].concat()).error_format()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_city_not_found() {
+    let server = HttpServer::start_replies(vec![Reply {
+        status: 400,
+        headers: Vec::new(),
+        body: NO_MATCH,
+    }])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let (code, json) =
+        failure(wapp(dir.path(), &server.base_url).args(["--city", "Lodnon", "--errors", "json"]));
+    assert_eq!(code, Some(NOT_FOUND_EXIT_CODE));
+    assert_eq!(
+        json,
+        serde_json::json!({ "error": {
+            "kind": "city_not_found",
+            "provider": "weatherapi",
+            "message": "City 'Lodnon' not found",
+            "retryable": false,
+        } })
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rate_limited_is_retryable() {
+    let server = HttpServer::start_replies(vec![Reply {
+        status: 429,
+        headers: Vec::new(),
+        body: "{}",
+    }])
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let (code, json) =
+        failure(wapp(dir.path(), &server.base_url).args(["--city", "Kyiv", "--output", "json"]));
+    assert_eq!(code, Some(RATE_LIMITED_EXIT_CODE));
+    assert_eq!(json["error"]["kind"], "rate_limited");
+    assert_eq!(json["error"]["provider"], "weatherapi");
+    assert_eq!(json["error"]["retryable"], true);
+}
+
+#[test]
+fn test_usage_and_network_errors() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let (code, json) = failure(wapp(dir.path(), "http://127.0.0.1:9").args([
+        "--no-auto-locate",
+        "--errors",
+        "json",
+    ]));
+    assert_eq!(code, Some(USAGE_EXIT_CODE));
+    assert_eq!(json["error"]["kind"], "usage");
+    assert_eq!(json["error"]["provider"], serde_json::Value::Null);
+    assert!(json["error"]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("a location is required"));
+
+    // Nothing listens on port 9; the key is not in the message.
+    let (code, json) = failure(
+        wapp(dir.path(), "http://127.0.0.1:9").args(["--city", "Kyiv", "--format", "json"]),
+    );
+    assert_eq!(code, Some(NETWORK_EXIT_CODE));
+    assert_eq!(json["error"]["kind"], "network");
+    assert_eq!(json["error"]["retryable"], true);
+    assert!(!json.to_string().contains("SECRETKEY"), "{}", json);
+}
+
+#[test]
+fn test_offline_without_cached_data() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let (code, json) = failure(wapp(dir.path(), "http://127.0.0.1:9").args([
+        "--city",
+        "Kyiv",
+        "--offline",
+        "--errors",
+        "json",
+    ]));
+    assert_eq!(code, Some(NETWORK_EXIT_CODE));
+    assert_eq!(json["error"]["kind"], "no_cached_data");
+    assert_eq!(json["error"]["message"], "no cached data for Kyiv");
+}
+
+#[test]
+fn test_text_errors_are_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp(dir.path(), "http://127.0.0.1:9")
+        .args(["--no-auto-locate", "--output", "json", "--errors", "text"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(USAGE_EXIT_CODE));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("Error: a location is required"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_json_output_implies_json_errors() {
+    assert_eq!(
+        error_format(&["get", "--output", "json"]),
+        ErrorFormat::Json
+    );
+    assert_eq!(
+        error_format(&["get", "--format", "json"]),
+        ErrorFormat::Json
+    );
+    assert_eq!(error_format(&["get"]), ErrorFormat::Text);
+    assert_eq!(error_format(&["get", "--output", "csv"]), ErrorFormat::Text);
+    assert_eq!(
+        error_format(&["get", "--output", "json", "--errors", "text"]),
+        ErrorFormat::Text
+    );
+    assert_eq!(
+        error_format(&["--errors", "json", "providers"]),
+        ErrorFormat::Json
+    );
+}
+
+#[test]
+fn test_every_kind_is_snake_case() {
+    let errors = [
+        WappError::Usage("?".into()),
+        WappError::MissingEnvVar("WEATHERAPI_KEY".into()),
+        WappError::RateLimited {
+            provider: "wttr".into(),
+            retry_after: None,
+        },
+        WappError::UnsupportedDataKind {
+            kind: "hourly".parse().unwrap(),
+            provider: "wttr".into(),
+        },
+    ];
+    let kinds: Vec<&str> = errors.iter().map(WappError::kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            "usage",
+            "missing_env_var",
+            "rate_limited",
+            "unsupported_data_kind"
+        ]
+    );
+    assert_eq!(errors[2].provider(), Some("wttr"));
+    assert_eq!(errors[1].provider(), None);
+}
+
+#[test]
+fn test_reports_of_other_errors() {
+    let other = ErrorReport::of(&anyhow::anyhow!("disk full").context("cannot save"));
+    assert_eq!(other.kind, "other");
+    assert_eq!(other.message, "cannot save: disk full");
+
+    let offline = ErrorReport::of(
+        &NoCachedData {
+            location: "Kyiv".into(),
+        }
+        .into(),
+    );
+    assert_eq!(offline.kind, "no_cached_data");
+
+    // A race may be won later if any provider may answer later.
+    let race: anyhow::Error = RaceFailed {
+        errors: vec![
+            ("weatherapi".into(), WappError::Usage("?".into()).into()),
+            (
+                "wttr".into(),
+                WappError::RateLimited {
+                    provider: "wttr".into(),
+                    retry_after: None,
+                }
+                .into(),
+            ),
+        ],
+    }
+    .into();
+    let report = ErrorReport::of(&race);
+    assert_eq!(report.kind, "all_providers_failed");
+    assert_eq!(report.provider, None);
+    assert!(report.retryable);
+}
+
+#[tokio::test]
+async fn test_panics_are_internal_errors() {
+    let joined = tokio::spawn(async { panic!("boom") }).await.unwrap_err();
+    let report = ErrorReport::of(&anyhow::Error::new(joined).context("fetching failed"));
+    assert_eq!(report.kind, "internal");
+    assert!(!report.retryable);
+
+    assert_eq!(
+        ErrorReport::internal("panicked at src/main.rs:1:1:\nboom").to_json(),
+        r#"{"error":{"kind":"internal","provider":null,"message":"panicked at src/main.rs:1:1:\nboom","retryable":false}}"#
+    );
+}
@@ -0,0 +1,210 @@
+mod mocks;
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::weatherapi;
+use wapp::cli::dated_kinds;
+use wapp::config::{load_config_file, load_config_from};
+use wapp::error::WappError;
+use wapp::exit::{exit_code, CONFIG_EXIT_CODE, NETWORK_EXIT_CODE, USAGE_EXIT_CODE};
+use wapp::location::Location;
+use wapp::providers::credentials::resolve_key_with;
+use wapp::providers::{
+    is_unavailable, is_unreachable, ApiProvider, DataKind, ProviderKind, NOT_FOUND_EXIT_CODE,
+    RATE_LIMITED_EXIT_CODE,
+};
+
+/// The error of a `now` request for Atlantis answered with `reply`.
+async fn error_of(reply: Reply) -> anyhow::Error {
+    let server = HttpServer::start_replies(vec![reply]).await;
+
+    weatherapi(&server.base_url)
+        .get_data(Location::from("Atlantis"), DataKind::Now, None)
+        .await
+        .unwrap_err()
+}
+
+#[test]
+fn test_missing_key_variable() {
+    let err = resolve_key_with("WEATHERAPI_KEY", |_| None).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(WappError::MissingEnvVar(name)) if name == "WEATHERAPI_KEY"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "WEATHERAPI_KEY (or WEATHERAPI_KEY_FILE) is not set"
+    );
+    assert_eq!(exit_code(&err), Some(CONFIG_EXIT_CODE));
+}
+
+#[test]
+fn test_unsupported_provider() {
+    let err = "weatherapii".parse::<ProviderKind>().unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(WappError::UnsupportedProvider {
+            suggestion: Some(ProviderKind::WeatherApi),
+            ..
+        })
+    ));
+    assert!(err
+        .to_string()
+        .starts_with("provider 'weatherapii' is not supported; did you mean 'weatherapi'?"));
+
+    let err = "acme".parse::<ProviderKind>().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("provider 'acme' is not supported. Supported providers: "));
+}
+
+#[test]
+fn test_config_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::env::set_var("WAPP_CONFIG", &path);
+
+    let err = load_config_file().unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(WappError::ConfigNotFound(missing)) if *missing == path
+    ));
+    assert_eq!(exit_code(&err), Some(CONFIG_EXIT_CODE));
+}
+
+#[test]
+fn test_config_parse() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, "{ not json").unwrap();
+
+    let err = load_config_from(&path).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(WappError::ConfigParse { path: invalid, .. }) if *invalid == path
+    ));
+    assert!(err.to_string().starts_with("invalid config "));
+}
+
+#[tokio::test]
+async fn test_http_error() {
+    let err = error_of(Reply {
+        status: 401,
+        headers: Vec::new(),
+        body: r#"{"error":{"code":2006,"message":"API key is invalid."}}"#,
+    })
+    .await;
+
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::Http { status: 401, provider, .. }) if provider == "weatherapi"
+    ));
+    assert!(!is_unavailable(&err));
+    assert_eq!(exit_code(&err), Some(NETWORK_EXIT_CODE));
+}
+
+#[tokio::test]
+async fn test_city_not_found() {
+    let err = error_of(Reply {
+        status: 400,
+        headers: Vec::new(),
+        body: r#"{"error":{"code":1006,"message":"No matching location found."}}"#,
+    })
+    .await;
+
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::CityNotFound { location, .. }) if *location == Location::from("Atlantis")
+    ));
+    assert_eq!(exit_code(&err), Some(NOT_FOUND_EXIT_CODE));
+}
+
+#[tokio::test]
+async fn test_rate_limited() {
+    let err = error_of(Reply {
+        status: 429,
+        headers: vec![("Retry-After", "3600")],
+        body: "{}",
+    })
+    .await;
+
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::RateLimited {
+            retry_after: Some(wait),
+            ..
+        }) if *wait == Duration::from_secs(3600)
+    ));
+    assert!(is_unavailable(&err));
+    assert_eq!(exit_code(&err), Some(RATE_LIMITED_EXIT_CODE));
+}
+
+#[tokio::test]
+async fn test_network_errors() {
+    let err = error_of(Reply {
+        status: 503,
+        headers: Vec::new(),
+        body: "busy",
+    })
+    .await;
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::Network {
+            unreachable: false,
+            ..
+        })
+    ));
+    assert!(is_unavailable(&err));
+
+    // Nothing listens on port 1.
+    let err = weatherapi("http://127.0.0.1:1")
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert!(is_unreachable(&err));
+    assert_eq!(exit_code(&err), Some(NETWORK_EXIT_CODE));
+}
+
+#[test]
+fn test_unsupported_data_kind() {
+    let err = DataKind::Hourly.unsupported("wttr");
+
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(WappError::UnsupportedDataKind {
+            kind: DataKind::Hourly,
+            ..
+        })
+    ));
+    assert_eq!(
+        err.to_string(),
+        "data kind 'hourly' is not supported by provider 'wttr'"
+    );
+
+    let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let err = DataKind::History(day).unsupported("wttr");
+    assert_eq!(
+        err.to_string(),
+        "history is not supported by provider 'wttr'"
+    );
+    assert_eq!(exit_code(&err), Some(USAGE_EXIT_CODE));
+}
+
+#[test]
+fn test_usage_and_context() {
+    let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let err = dated_kinds(&[DataKind::Hourly], day).unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(WappError::Usage(_))));
+
+    // Context added on the way up keeps the variant.
+    let err = err.context("cannot fetch Kyiv");
+    assert!(matches!(WappError::of(&err), Some(WappError::Usage(_))));
+    assert_eq!(exit_code(&err), Some(USAGE_EXIT_CODE));
+    assert_eq!(exit_code(&anyhow::anyhow!("other")), None);
+}
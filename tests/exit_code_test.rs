@@ -0,0 +1,122 @@
+use assert_cmd::Command;
+use std::path::Path;
+use wapp::exit::{CONFIG_EXIT_CODE, NETWORK_EXIT_CODE, USAGE_EXIT_CODE};
+
+/// `wapp` with its config, cache and data below `dir`, and without any
+/// provider variable of the environment running the tests; logs are off,
+/// so stderr has the error alone.
+fn wapp(dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("wapp").unwrap();
+    for (name, _) in std::env::vars() {
+        let provider_var = ["WAPP_", "WEATHERAPI_", "OPENWEATHER_", "ACCUWEATHER_"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix));
+        if provider_var {
+            cmd.env_remove(name);
+        }
+    }
+    cmd.current_dir(dir)
+        .env("WAPP_CONFIG", dir.join("config.json"))
+        .env("WAPP_CACHE_DIR", dir.join("cache"))
+        .env("WAPP_DATA_DIR", dir.join("data"))
+        .env("WAPP_LOG", "off")
+        .env("NO_COLOR", "1");
+    cmd
+}
+
+fn stderr(output: &std::process::Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_missing_location_is_a_usage_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = wapp(dir.path())
+        .args(["get", "--no-auto-locate"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(USAGE_EXIT_CODE));
+    assert!(
+        stderr(&output).starts_with("Error: a location is required."),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_invalid_arguments_are_usage_errors() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp(dir.path())
+        .args(["get", "--city", "Kyiv", "--units", "kelvin"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(USAGE_EXIT_CODE));
+
+    let output = wapp(dir.path())
+        .args(["get", "--city", "Kyiv", "--hours", "3"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(USAGE_EXIT_CODE));
+    assert_eq!(stderr(&output), "Error: --hours needs --data hourly\n");
+}
+
+#[test]
+fn test_missing_key_is_a_config_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = wapp(dir.path())
+        .args(["get", "--city", "Kyiv", "--provider", "weatherapi"])
+        .args(["--no-cache", "--raw"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(CONFIG_EXIT_CODE));
+    assert!(
+        stderr(&output).contains("WEATHERAPI_KEY (or WEATHERAPI_KEY_FILE) is not set"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_unwritable_config_fails_configure() {
+    let dir = tempfile::tempdir().unwrap();
+    // A file where the config directory should be.
+    let blocker = dir.path().join("blocker");
+    std::fs::write(&blocker, "").unwrap();
+
+    let output = wapp(dir.path())
+        .env("WAPP_CONFIG", blocker.join("config.json"))
+        .args(["configure", "--default-city", "Kyiv"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(CONFIG_EXIT_CODE));
+    assert!(output.stdout.is_empty(), "nothing was saved");
+    assert!(
+        stderr(&output).starts_with("Error: "),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn test_unreachable_provider_is_a_network_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = wapp(dir.path())
+        .env("WEATHERAPI_KEY", "KEY")
+        // Nothing listens on port 1.
+        .env("WEATHERAPI_BASE_URL", "http://127.0.0.1:1")
+        .args(["--retries", "1", "get", "--city", "Kyiv"])
+        .args(["--provider", "weatherapi", "--no-cache"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(NETWORK_EXIT_CODE));
+    assert!(
+        stderr(&output).starts_with("Error: "),
+        "{}",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,155 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::providers::{openweather, weatherapi};
+use mocks::scripted_provider::ScriptedProvider;
+use wapp::cli::{Cli, Commands};
+use wapp::providers::{ApiProvider, DataKind};
+use wapp::series::{collect, render, ExportFormat, EXPORT_COLUMNS};
+
+const WEATHERAPI_FIXTURE: &str = include_str!("fixtures/weatherapi_forecast.json");
+const OPENWEATHER_FIXTURE: &str = include_str!("fixtures/openweather_forecast.json");
+
+#[test]
+fn test_export_parse_repeated_cities() {
+    let cli = Cli::try_parse_from(vec![
+        "wapp",
+        "export",
+        "--city",
+        "Kyiv",
+        "--city",
+        "Lviv",
+        "--output",
+        "out.jsonl",
+        "--format",
+        "jsonl",
+    ])
+    .unwrap();
+
+    match cli.cmd {
+        Commands::Export {
+            cities,
+            data,
+            format,
+            ..
+        } => {
+            assert_eq!(
+                cities.iter().map(|c| c.as_str()).collect::<Vec<_>>(),
+                vec!["Kyiv", "Lviv"]
+            );
+            assert_eq!(data, DataKind::Hourly);
+            assert_eq!(format, ExportFormat::Jsonl);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
+
+#[test]
+fn test_weatherapi_series() {
+    let points = weatherapi("http://localhost")
+        .series(WEATHERAPI_FIXTURE)
+        .unwrap();
+
+    // 3 days with 4 hours each.
+    assert_eq!(points.len(), 12);
+    assert_eq!(points[0].time.to_rfc3339(), "2024-06-09T23:00:00+00:00");
+    assert_eq!(points[0].temp_c, Some(11.4));
+    assert_eq!(points[0].condition.as_deref(), Some("Partly cloudy"));
+}
+
+#[test]
+fn test_openweather_series_converts_units() {
+    let metric = openweather("http://localhost")
+        .with_units("metric")
+        .series(OPENWEATHER_FIXTURE)
+        .unwrap();
+    assert_eq!(metric.len(), 40);
+    assert_eq!(metric[0].temp_c, Some(18.54));
+    assert!((metric[0].wind_kph.unwrap() - 10.8).abs() < 1e-9);
+    assert_eq!(metric[0].precip_mm, Some(0.0));
+
+    // Without units the API reports Kelvin.
+    let standard = openweather("http://localhost")
+        .series(OPENWEATHER_FIXTURE)
+        .unwrap();
+    assert!((standard[0].temp_c.unwrap() - (18.54 - 273.15)).abs() < 1e-9);
+}
+
+#[test]
+fn test_columns_are_stable_across_providers() {
+    let wa = render(
+        &weatherapi("http://localhost")
+            .series(WEATHERAPI_FIXTURE)
+            .unwrap(),
+        ExportFormat::Csv,
+    );
+    let owm = render(
+        &openweather("http://localhost")
+            .with_units("metric")
+            .series(OPENWEATHER_FIXTURE)
+            .unwrap(),
+        ExportFormat::Csv,
+    );
+
+    let header = EXPORT_COLUMNS.join(",");
+    for csv in [&wa, &owm] {
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(header.as_str()));
+        assert!(lines.all(|line| line.split(',').count() == EXPORT_COLUMNS.len()));
+    }
+}
+
+#[test]
+fn test_jsonl_lines_are_valid_objects_with_all_columns() {
+    let points = openweather("http://localhost")
+        .with_units("metric")
+        .series(OPENWEATHER_FIXTURE)
+        .unwrap();
+    let jsonl = render(&points, ExportFormat::Jsonl);
+
+    assert_eq!(jsonl.lines().count(), points.len());
+    for line in jsonl.lines() {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        let keys: Vec<&String> = value.as_object().unwrap().keys().collect();
+        assert_eq!(keys.len(), EXPORT_COLUMNS.len());
+        assert!(EXPORT_COLUMNS.iter().all(|c| value.get(c).is_some()));
+    }
+}
+
+#[tokio::test]
+async fn test_multi_city_merge_orders_by_time_then_city() {
+    let provider = ScriptedProvider::new(vec![Ok(WEATHERAPI_FIXTURE), Ok(WEATHERAPI_FIXTURE)])
+        .parsing_as(weatherapi("http://localhost"));
+    let cities = vec!["Lviv".parse().unwrap(), "Kyiv".parse().unwrap()];
+
+    let points = collect(&provider, &cities, DataKind::Hourly, Some(3))
+        .await
+        .unwrap();
+
+    assert_eq!(points.len(), 24);
+    assert!(points.windows(2).all(|w| w[0].time <= w[1].time));
+    // Same timestamps keep the order the cities were given in.
+    assert_eq!(points[0].city, "Lviv");
+    assert_eq!(points[1].city, "Kyiv");
+    assert_eq!(points[0].time, points[1].time);
+
+    let csv = render(&points, ExportFormat::Csv);
+    assert!(csv
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("Lviv,2024-06-09T23:00:00Z,"));
+}
+
+#[tokio::test]
+async fn test_kinds_without_series_are_rejected() {
+    let provider = ScriptedProvider::new(vec![Ok(r#"{"current":{"temp_c":1}}"#)])
+        .parsing_as(weatherapi("http://localhost"));
+
+    let err = collect(&provider, &["Kyiv".parse().unwrap()], DataKind::Now, None)
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("no time series"));
+}
@@ -0,0 +1,61 @@
+use wapp::expr::{CompareOp, Expr, Operand};
+
+fn eval(input: &str, fields: &[(&str, f64)]) -> anyhow::Result<bool> {
+    let lookup = |name: &str| fields.iter().find(|(n, _)| *n == name).map(|(_, v)| *v);
+    Expr::parse(input)?.eval(&lookup)
+}
+
+#[test]
+fn test_parse_comparison() {
+    assert_eq!(
+        Expr::parse("temp_c < -2.5").unwrap(),
+        Expr::Compare(
+            Operand::Field("temp_c".into()),
+            CompareOp::Lt,
+            Operand::Number(-2.5)
+        )
+    );
+}
+
+#[test]
+fn test_operators() {
+    let fields = [("t", 0.0)];
+
+    assert!(eval("t <= 0", &fields).unwrap());
+    assert!(eval("t >= 0", &fields).unwrap());
+    assert!(eval("t == 0", &fields).unwrap());
+    assert!(!eval("t != 0", &fields).unwrap());
+    assert!(!eval("t > 0", &fields).unwrap());
+    assert!(eval("-1 < t", &fields).unwrap());
+}
+
+#[test]
+fn test_boolean_logic_and_precedence() {
+    let fields = [("a", 1.0), ("b", 2.0)];
+
+    // `and` binds tighter than `or`.
+    assert!(eval("a > 5 || a == 1 && b == 2", &fields).unwrap());
+    assert!(!eval("(a > 5 or a == 1) and b == 3", &fields).unwrap());
+    assert!(eval("not a > 5", &fields).unwrap());
+    assert!(eval("!(a > 5)", &fields).unwrap());
+}
+
+#[test]
+fn test_dotted_fields() {
+    assert!(eval("current.uv >= 6", &[("current.uv", 7.0)]).unwrap());
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(Expr::parse("temp_c <").is_err());
+    assert!(Expr::parse("temp_c 0").is_err());
+    assert!(Expr::parse("(temp_c < 0").is_err());
+    assert!(Expr::parse("temp_c < 0 )").is_err());
+    assert!(Expr::parse("temp_c ~ 0").is_err());
+}
+
+#[test]
+fn test_missing_field_is_an_error() {
+    let err = eval("wind_kph > 10", &[]).unwrap_err().to_string();
+    assert!(err.contains("wind_kph"));
+}
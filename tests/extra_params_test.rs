@@ -0,0 +1,226 @@
+mod mocks;
+
+use std::collections::BTreeMap;
+
+use mocks::cli::wapp_get;
+use mocks::http_server::HttpServer;
+use mocks::providers::openweather;
+use wapp::cli::render_dry_run;
+use wapp::config::{AppConfig, ProviderSettings};
+use wapp::location::Location;
+use wapp::providers::url::without_param;
+use wapp::providers::{
+    merge_params, parse_param, provider_factory_with, DataKind, PlannedRequest, ProviderKind,
+    ProviderOverrides, WeatherApiProvider,
+};
+
+fn params(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_parse_param_splits_on_first_equals() {
+    assert_eq!(
+        parse_param("aqi=yes").unwrap(),
+        ("aqi".into(), "yes".into())
+    );
+    assert_eq!(
+        parse_param("filter=a=b").unwrap(),
+        ("filter".into(), "a=b".into())
+    );
+    assert_eq!(parse_param("empty=").unwrap(), ("empty".into(), "".into()));
+
+    assert!(parse_param("novalue").is_err());
+    assert!(parse_param("=yes").is_err());
+}
+
+#[test]
+fn test_reserved_params_are_rejected() {
+    for arg in ["appid=x", "key=x", "q=Paris", "lat=1", "lon=2", "KEY=x"] {
+        let err = parse_param(arg).unwrap_err().to_string();
+        assert!(err.contains("reserved"), "{}: {}", arg, err);
+    }
+
+    let configured = BTreeMap::from([("q".to_string(), "Paris".to_string())]);
+    assert!(merge_params(&configured, &[]).is_err());
+}
+
+#[test]
+fn test_flags_override_configured_params() {
+    let configured = BTreeMap::from([
+        ("aqi".to_string(), "no".to_string()),
+        ("alerts".to_string(), "yes".to_string()),
+    ]);
+
+    let merged = merge_params(&configured, &params(&[("aqi", "yes"), ("tp", "15")])).unwrap();
+
+    assert_eq!(
+        merged,
+        params(&[("alerts", "yes"), ("aqi", "yes"), ("tp", "15")])
+    );
+}
+
+#[test]
+fn test_weatherapi_url_encodes_extra_params() {
+    let provider = WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: params(&[("aqi", "yes"), ("note", "a b&c")]),
+        kind_overrides: Default::default(),
+    };
+
+    let url = provider
+        .build_url(&Location::from("Kyiv"), DataKind::Now, None)
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "http://localhost/current.json?key=KEY&q=Kyiv&aqi=yes&note=a%20b%26c"
+    );
+}
+
+#[test]
+fn test_openweather_url_appends_extra_params() {
+    let provider = openweather("http://localhost").with_extra_params(params(&[("mode", "xml")]));
+
+    let url = provider
+        .build_url(&Location::from("Oslo"), DataKind::Forecast, Some(1))
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "http://localhost/forecast?q=Oslo&appid=KEY&cnt=8&mode=xml"
+    );
+}
+
+#[test]
+fn test_factory_rejects_reserved_configured_params() {
+    std::env::set_var("WEATHERAPI_KEY", "dummy");
+
+    let cfg = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        providers: BTreeMap::from([(
+            "weatherapi".to_string(),
+            ProviderSettings {
+                extra_params: BTreeMap::from([("key".to_string(), "other".to_string())]),
+                ..Default::default()
+            },
+        )]),
+        ..Default::default()
+    };
+
+    let err = provider_factory_with(&cfg, &ProviderOverrides::default())
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("reserved"), "{}", err);
+}
+
+#[test]
+fn test_extra_params_config_round_trips() {
+    let json =
+        r#"{"provider":"weatherapi","providers":{"weatherapi":{"extra_params":{"aqi":"yes"}}}}"#;
+    let cfg: AppConfig = serde_json::from_str(json).unwrap();
+
+    assert_eq!(cfg.providers["weatherapi"].extra_params["aqi"], "yes");
+    assert_eq!(serde_json::to_string(&cfg).unwrap(), json);
+}
+
+#[test]
+fn test_extra_params_override_the_providers_own() {
+    let weatherapi = WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: params(&[("days", "10"), ("aqi", "yes")]),
+        kind_overrides: Default::default(),
+    };
+    assert_eq!(
+        weatherapi
+            .build_url(&Location::from("Kyiv"), DataKind::Forecast, Some(3))
+            .unwrap(),
+        "http://localhost/forecast.json?key=KEY&q=Kyiv&days=10&aqi=yes"
+    );
+
+    let openweather = openweather("http://localhost").with_extra_params(params(&[("cnt", "16")]));
+    assert_eq!(
+        openweather
+            .build_url(&Location::from("Oslo"), DataKind::Forecast, Some(1))
+            .unwrap(),
+        "http://localhost/forecast?q=Oslo&appid=KEY&cnt=16"
+    );
+
+    assert_eq!(
+        without_param("http://w/f?a%20b=1&ab=2&a=3", "a b"),
+        "http://w/f?ab=2&a=3"
+    );
+    assert_eq!(without_param("http://w/f", "a"), "http://w/f");
+}
+
+#[test]
+fn test_dry_run_lists_the_merged_params() {
+    let plans = vec![PlannedRequest::lookup("http://g/search?name=Oslo".into())];
+
+    assert_eq!(
+        render_dry_run(&plans, &params(&[("alerts", "yes"), ("days", "10")])),
+        "GET http://g/search?name=Oslo\n\
+         # looks the location up; the weather request is built from the answer\n\
+         \n\
+         # extra parameters, after the provider's own: alerts=yes days=10\n"
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{"provider":"weatherapi","providers":{"weatherapi":{"extra_params":{"aqi":"no","alerts":"yes"}}}}"#,
+    )
+    .unwrap();
+    let output = wapp_get(dir.path(), "http://w")
+        .args([
+            "--data", "forecast", "--param", "aqi=yes", "--param", "days=10",
+        ])
+        .arg("--dry-run")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "GET http://w/forecast.json?key=<redacted>&q=Kyiv&alerts=yes&aqi=yes&days=10\n\
+         \n\
+         # extra parameters, after the provider's own: alerts=yes aqi=yes days=10\n"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unreadable_response_falls_back_to_raw_with_params() {
+    let server = HttpServer::start(r#"{"forecast":"in another shape"}"#).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.base_url)
+        .args(["--output", "json", "--param", "mode=compact"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"forecast\":\"in another shape\"}\n"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("maybe because --param mode changed the response"),
+        "{}",
+        stderr
+    );
+
+    // Without parameters, an unreadable response is still an error.
+    let output = wapp_get(dir.path(), &server.base_url)
+        .args(["--output", "json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use --raw"));
+}
@@ -0,0 +1,185 @@
+mod mocks;
+
+use mocks::http_server::{fast_retries, HttpServer, Reply};
+use mocks::providers::weatherapi;
+use mocks::scripted_provider::ScriptedProvider;
+use wapp::config::AppConfig;
+use wapp::location::Location;
+use wapp::providers::{
+    is_quota_forbidden, is_unavailable, ApiProvider, ProviderKind, WttrProvider,
+};
+use wapp::request::{execute_with_fallback, WeatherRequest};
+
+const WTTR: &str = r#"{"current_condition":[{"temp_C":"21"}],"weather":[]}"#;
+
+fn wttr(base_url: &str) -> Box<dyn ApiProvider> {
+    fast_retries();
+    Box::new(WttrProvider {
+        base_url: base_url.into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    })
+}
+
+fn request(kind: &str) -> WeatherRequest {
+    WeatherRequest::at(Location::from("Oslo"), kind.parse().unwrap(), None)
+}
+
+fn status(status: u16, body: &'static str) -> Reply {
+    Reply {
+        status,
+        headers: Vec::new(),
+        body,
+    }
+}
+
+#[tokio::test]
+async fn test_unreachable_provider_falls_back_in_order() {
+    let outage = HttpServer::start_replies(vec![status(503, "busy")]).await;
+    let backup = HttpServer::start(WTTR).await;
+    let unused = HttpServer::start(WTTR).await;
+    let providers = vec![
+        wttr("http://127.0.0.1:9"),
+        wttr(&outage.base_url),
+        wttr(&backup.base_url),
+        wttr(&unused.base_url),
+    ];
+
+    let (provider, body) = execute_with_fallback(&providers, request("now"))
+        .await
+        .unwrap();
+
+    assert!(std::ptr::addr_eq(provider, providers[2].as_ref()));
+    assert_eq!(body, WTTR);
+    // Every provider is retried before the next one is tried.
+    assert_eq!(outage.requests(), 3);
+    assert_eq!(backup.requests(), 1);
+    assert_eq!(unused.requests(), 0);
+}
+
+#[tokio::test]
+async fn test_every_provider_unavailable_returns_last_error() {
+    let quota = HttpServer::start_replies(vec![status(429, "slow down")]).await;
+    let providers = vec![wttr("http://127.0.0.1:9"), wttr(&quota.base_url)];
+
+    let err = execute_with_fallback(&providers, request("now"))
+        .await
+        .map(|(_, body)| body)
+        .unwrap_err();
+
+    assert!(is_unavailable(&err));
+    assert_eq!(err.to_string(), "rate limited by wttr");
+}
+
+#[tokio::test]
+async fn test_forbidden_for_quota_falls_back() {
+    let primary = HttpServer::start_replies(vec![status(
+        403,
+        r#"{"error":{"code":2007,"message":"API key has exceeded calls per month quota."}}"#,
+    )])
+    .await;
+    let backup = HttpServer::start(WTTR).await;
+    let providers: Vec<Box<dyn ApiProvider>> = vec![
+        Box::new(weatherapi(&primary.base_url)),
+        wttr(&backup.base_url),
+    ];
+
+    let (provider, body) = execute_with_fallback(&providers, request("now"))
+        .await
+        .unwrap();
+
+    assert_eq!(provider.name(), "wttr");
+    assert_eq!(body, WTTR);
+    assert_eq!(primary.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_forbidden_key_does_not_fall_back() {
+    let primary = HttpServer::start_replies(vec![status(
+        403,
+        r#"{"error":{"code":2008,"message":"API key has been disabled."}}"#,
+    )])
+    .await;
+    let backup = HttpServer::start(WTTR).await;
+    let providers: Vec<Box<dyn ApiProvider>> = vec![
+        Box::new(weatherapi(&primary.base_url)),
+        wttr(&backup.base_url),
+    ];
+
+    let err = execute_with_fallback(&providers, request("now"))
+        .await
+        .map(|(_, body)| body)
+        .unwrap_err();
+
+    assert!(!is_quota_forbidden(&err));
+    assert_eq!(
+        err.to_string(),
+        "WeatherAPI error 2008: API key has been disabled"
+    );
+    assert_eq!(backup.requests(), 0);
+}
+
+#[tokio::test]
+async fn test_unknown_city_does_not_fall_back() {
+    let primary = HttpServer::start_replies(vec![status(404, "Unknown location")]).await;
+    let backup = HttpServer::start(WTTR).await;
+    let providers = vec![wttr(&primary.base_url), wttr(&backup.base_url)];
+
+    let err = execute_with_fallback(&providers, request("now"))
+        .await
+        .map(|(_, body)| body)
+        .unwrap_err();
+
+    assert!(!is_unavailable(&err));
+    assert!(err.to_string().contains("no weather data for 'Oslo'"));
+    assert_eq!(backup.requests(), 0);
+}
+
+#[tokio::test]
+async fn test_unknown_kind_does_not_fall_back() {
+    let backup = ScriptedProvider::new(vec![Ok("{}")]);
+    let providers: Vec<Box<dyn ApiProvider>> = vec![wttr("http://127.0.0.1:9"), Box::new(backup)];
+
+    let err = execute_with_fallback(&providers, request("hourly"))
+        .await
+        .map(|(_, body)| body)
+        .unwrap_err();
+
+    assert!(err.to_string().contains("'hourly' is not supported"));
+}
+
+#[tokio::test]
+async fn test_provider_errors_do_not_fall_back() {
+    let primary = ScriptedProvider::new(vec![Err("city not found")]);
+    let backup = HttpServer::start(WTTR).await;
+    let providers: Vec<Box<dyn ApiProvider>> = vec![Box::new(primary), wttr(&backup.base_url)];
+
+    let err = execute_with_fallback(&providers, request("now"))
+        .await
+        .map(|(_, body)| body)
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "city not found");
+    assert_eq!(backup.requests(), 0);
+}
+
+#[test]
+fn test_provider_chain_starts_with_selected_provider() {
+    let cfg: AppConfig = serde_json::from_str(
+        r#"{"provider":"weatherapi","fallback_providers":["openweather","weatherapi","wttr"]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        cfg.provider_chain(),
+        vec![
+            ProviderKind::WeatherApi,
+            ProviderKind::OpenWeather,
+            ProviderKind::Wttr
+        ]
+    );
+
+    let cfg: AppConfig = serde_json::from_str(r#"{"provider":"wttr"}"#).unwrap();
+    assert_eq!(cfg.provider_chain(), vec![ProviderKind::Wttr]);
+    assert!(!serde_json::to_string(&cfg).unwrap().contains("fallback"));
+}
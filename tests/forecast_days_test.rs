@@ -0,0 +1,82 @@
+mod mocks;
+
+use mocks::providers::{openweather, weatherapi};
+use wapp::location::Location;
+use wapp::providers::{days_shortfall, validate_days, ApiProvider, DataKind};
+
+#[test]
+fn test_days_within_horizon_are_accepted() {
+    assert!(validate_days(&weatherapi("http://localhost"), 1).is_ok());
+    assert!(validate_days(&weatherapi("http://localhost"), 14).is_ok());
+    assert!(validate_days(&openweather("http://localhost"), 5).is_ok());
+}
+
+#[test]
+fn test_days_beyond_horizon_are_rejected() {
+    let err = validate_days(&openweather("http://localhost"), 6)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("between 1 and 5"), "{err}");
+    assert!(err.contains("openweather"), "{err}");
+
+    assert!(validate_days(&weatherapi("http://localhost"), 15).is_err());
+}
+
+#[test]
+fn test_zero_days_is_rejected() {
+    assert!(validate_days(&weatherapi("http://localhost"), 0).is_err());
+}
+
+#[test]
+fn test_weatherapi_shortfall_is_reported() {
+    let body = include_str!("fixtures/weatherapi_forecast.json");
+    let provider = weatherapi("http://localhost");
+
+    assert_eq!(provider.forecast_days(body), Some(3));
+
+    let warning = days_shortfall(&provider, 7, body).unwrap();
+    assert!(warning.contains("requested 7"), "{warning}");
+    assert!(warning.contains("only 3"), "{warning}");
+
+    assert_eq!(days_shortfall(&provider, 3, body), None);
+}
+
+#[test]
+fn test_weatherapi_sends_the_requested_days() {
+    for days in [1, 5, 14] {
+        let url = weatherapi("http://localhost")
+            .build_url(&Location::from("Rome"), DataKind::Forecast, Some(days))
+            .unwrap();
+
+        assert!(url.ends_with(&format!("&days={}", days)), "{url}");
+    }
+}
+
+#[test]
+fn test_openweather_sends_eight_slots_a_day() {
+    for (days, cnt) in [(1, 8), (3, 24), (5, 40)] {
+        let url = openweather("http://localhost")
+            .build_url(&Location::from("Rome"), DataKind::Forecast, Some(days))
+            .unwrap();
+
+        assert!(url.ends_with(&format!("&cnt={}", cnt)), "{url}");
+    }
+}
+
+#[test]
+fn test_openweather_counts_distinct_dates() {
+    let body = include_str!("fixtures/openweather_forecast.json");
+    let provider = openweather("http://localhost");
+
+    // 40 three-hour slots starting at midday span six calendar dates.
+    assert_eq!(provider.forecast_days(body), Some(6));
+    assert_eq!(days_shortfall(&provider, 5, body), None);
+}
+
+#[test]
+fn test_unparseable_body_gives_no_warning() {
+    assert_eq!(
+        days_shortfall(&weatherapi("http://localhost"), 7, "<html>oops</html>"),
+        None
+    );
+}
@@ -0,0 +1,236 @@
+mod mocks;
+
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::openweather;
+use wapp::cache::{Cache, Namespace};
+use wapp::geocode::{best_match, use_disk_cache, Coords, Geocoder, Lru, Source, DISK_TTL_SECS};
+use wapp::location::{Location, LocationInput, LocationMatch};
+use wapp::providers::metno::{MetNoProvider, DEFAULT_USER_AGENT};
+use wapp::providers::OpenMeteoProvider;
+
+const GEOCODING: &str = r#"{"results":[{"id":703448,"name":"Kyiv","latitude":50.45466,"longitude":30.5238,"country_code":"UA"}],"generationtime_ms":0.5}"#;
+
+/// "Mexico City" ranks above the town in Maine named Mexico.
+const AMBIGUOUS: &str = r#"{"results":[
+    {"name":"Mexico City","latitude":19.42847,"longitude":-99.12766,"country_code":"MX"},
+    {"name":"Mexico","latitude":44.56089,"longitude":-70.54563,"country_code":"US"}
+]}"#;
+
+const DIRECT: &str = r#"[
+    {"name":"Paris","lat":48.8589,"lon":2.32,"country":"FR"},
+    {"name":"Paris","lat":33.6609,"lon":-95.5555,"country":"US","state":"Texas"}
+]"#;
+
+fn open_meteo(base_url: &str) -> Geocoder {
+    Geocoder::new(
+        "openmeteo",
+        Source::OpenMeteo {
+            base_url: base_url.into(),
+            lang: None,
+        },
+    )
+}
+
+fn place(name: &str, lat: f64) -> LocationMatch {
+    LocationMatch {
+        name: name.into(),
+        region: None,
+        country: None,
+        lat,
+        lon: 0.0,
+    }
+}
+
+fn coords(name: &str, latitude: f64) -> Coords {
+    Coords {
+        name: name.into(),
+        latitude,
+        longitude: 0.0,
+    }
+}
+
+#[tokio::test]
+async fn test_repeated_resolutions_hit_the_network_once() {
+    let server = HttpServer::start(GEOCODING).await;
+    let geocoder = open_meteo(&server.base_url);
+
+    for city in ["Kyiv", "kyiv", "Kyiv"] {
+        let place = geocoder.resolve(&Location::from(city)).await.unwrap();
+        assert_eq!(place.name, "Kyiv");
+        assert_eq!(place.latitude, 50.45466);
+    }
+    assert_eq!(server.requests(), 1);
+
+    // A new geocoder for the same source shares the places.
+    open_meteo(&server.base_url)
+        .resolve(&Location::from("Kyiv"))
+        .await
+        .unwrap();
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_providers_on_the_same_geocoder_share_places() {
+    let server = HttpServer::start(GEOCODING).await;
+    let open_meteo = OpenMeteoProvider {
+        base_url: "http://f".into(),
+        geocoding_url: server.base_url.clone(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let metno = MetNoProvider {
+        base_url: "http://f".into(),
+        geocoding_url: server.base_url.clone(),
+        user_agent: DEFAULT_USER_AGENT.into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let first = open_meteo.geocode(&Location::from("Kyiv")).await.unwrap();
+    let second = metno
+        .geocoder()
+        .resolve(&Location::from("Kyiv"))
+        .await
+        .unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_openweather_lookups_are_cached_without_the_key() {
+    let server = HttpServer::start(DIRECT).await;
+    let provider = openweather(&format!("{}/data/2.5", server.base_url));
+
+    for _ in 0..2 {
+        let place = provider
+            .geocoder()
+            .resolve(&Location::from("Paris"))
+            .await
+            .unwrap();
+        assert_eq!(place.latitude, 48.8589);
+    }
+    assert_eq!(server.requests(), 1);
+    assert!(
+        server.received()[0].starts_with("get /geo/1.0/direct?q=paris&limit=5&appid=key "),
+        "{}",
+        server.received()[0]
+    );
+}
+
+#[tokio::test]
+async fn test_coordinates_and_failures_are_not_looked_up_again() {
+    let server = HttpServer::start_replies(vec![
+        Reply::ok(r#"{"generationtime_ms":0.3}"#),
+        Reply::ok(GEOCODING),
+    ])
+    .await;
+    let geocoder = open_meteo(&server.base_url);
+
+    let place = geocoder
+        .resolve(&Location::coords(50.45, 30.52).unwrap())
+        .await
+        .unwrap();
+    assert_eq!((place.latitude, place.longitude), (50.45, 30.52));
+    assert_eq!(server.requests(), 0);
+
+    // A city that was not found is asked for again.
+    let err = geocoder.resolve(&Location::from("Kyiv")).await.unwrap_err();
+    assert_eq!(err.to_string(), "city 'Kyiv' was not found by Open-Meteo");
+    assert!(geocoder.resolve(&Location::from("Kyiv")).await.is_ok());
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_ambiguous_results_prefer_exact_names() {
+    let server = HttpServer::start(AMBIGUOUS).await;
+    let geocoder = open_meteo(&server.base_url);
+
+    let place = geocoder.resolve(&Location::from("mexico")).await.unwrap();
+    assert_eq!(place.name, "Mexico");
+    assert_eq!(place.latitude, 44.56089);
+
+    let place = geocoder
+        .resolve(&Location::from("Mexico City"))
+        .await
+        .unwrap();
+    assert_eq!(place.latitude, 19.42847);
+
+    // Without an exact name, the geocoder's first place.
+    let place = geocoder.resolve(&Location::from("Mexic")).await.unwrap();
+    assert_eq!(place.name, "Mexico City");
+}
+
+#[test]
+fn test_best_match() {
+    let matches = || {
+        vec![
+            place("Saint Petersburg", 59.9),
+            place("St. Petersburg", 27.8),
+            place("São Paulo", -23.5),
+        ]
+    };
+
+    assert_eq!(best_match("st. petersburg", matches()).unwrap().lat, 27.8);
+    assert_eq!(best_match("São  Paulo", matches()).unwrap().lat, -23.5);
+    assert_eq!(best_match("Petersburg", matches()).unwrap().lat, 59.9);
+    assert_eq!(best_match("Kyiv", Vec::new()), None);
+}
+
+#[test]
+fn test_lru_forgets_the_least_recently_used() {
+    let mut lru = Lru::new(2);
+    lru.insert("kyiv".into(), coords("Kyiv", 50.45));
+    lru.insert("lviv".into(), coords("Lviv", 49.84));
+
+    // Kyiv was used last, so Lviv goes.
+    assert!(lru.get("kyiv").is_some());
+    lru.insert("odesa".into(), coords("Odesa", 46.48));
+
+    assert_eq!(lru.len(), 2);
+    assert_eq!(lru.get("lviv"), None);
+    assert_eq!(lru.get("kyiv"), Some(coords("Kyiv", 50.45)));
+    assert_eq!(lru.get("odesa"), Some(coords("Odesa", 46.48)));
+
+    // A place stored again replaces the old one.
+    lru.insert("kyiv".into(), coords("Kyiv", 50.0));
+    assert_eq!(lru.len(), 2);
+    assert_eq!(lru.get("kyiv"), Some(coords("Kyiv", 50.0)));
+}
+
+#[tokio::test]
+async fn test_places_are_kept_on_disk() {
+    let dir = tempfile::tempdir().unwrap().keep();
+    use_disk_cache(Cache::new(&dir));
+    let cache = Cache::new(&dir);
+
+    let server = HttpServer::start(GEOCODING).await;
+    open_meteo(&server.base_url)
+        .resolve(&Location::City(LocationInput::parse("Kyiv")))
+        .await
+        .unwrap();
+
+    let (_, stored) = cache
+        .entries()
+        .into_iter()
+        .find(|(_, entry)| entry.key.contains(&server.base_url))
+        .unwrap();
+    assert_eq!(stored.namespace, Namespace::Geocode);
+    assert_eq!(stored.provider, "openmeteo");
+    assert_eq!(stored.expires, Some(stored.created + DISK_TTL_SECS));
+
+    // A place on disk is not looked up, as in a later process.
+    let other = HttpServer::start(GEOCODING).await;
+    let mut entry = stored.clone();
+    entry.key = stored.key.replace(&server.base_url, &other.base_url);
+    entry.body = r#"{"name":"Kyiv","latitude":50.0,"longitude":30.0}"#.into();
+    cache.put(&entry).unwrap();
+
+    let place = open_meteo(&other.base_url)
+        .resolve(&Location::from("Kyiv"))
+        .await
+        .unwrap();
+    assert_eq!(place.latitude, 50.0);
+    assert_eq!(other.requests(), 0);
+}
@@ -0,0 +1,93 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::http_server::HttpServer;
+use wapp::cache::{Cache, Namespace};
+use wapp::cli::{Cli, Commands};
+use wapp::geolocate::{Geolocator, CACHE_TTL_SECS};
+use wapp::location::Location;
+
+const IPINFO: &str = r#"{"ip":"203.0.113.7","city":"Kyiv","country":"UA","loc":"50.4501,30.5234","timezone":"Europe/Kyiv"}"#;
+
+const BOGON: &str = r#"{"ip":"10.0.0.1","bogon":true}"#;
+
+const NOW: u64 = 1_700_000_000;
+
+fn kyiv() -> Location {
+    Location::Coords {
+        lat: 50.4501,
+        lon: 30.5234,
+    }
+}
+
+#[test]
+fn test_url_under_base() {
+    let geolocator = Geolocator {
+        base_url: "http://geo/".into(),
+    };
+
+    assert_eq!(geolocator.url(), "http://geo/json");
+}
+
+#[test]
+fn test_parse_position() {
+    assert_eq!(Geolocator::parse(IPINFO).unwrap(), kyiv());
+
+    let err = Geolocator::parse(BOGON).unwrap_err();
+    assert!(err.to_string().contains("no position"));
+    assert!(Geolocator::parse(r#"{"loc":"95,0"}"#).is_err());
+    assert!(Geolocator::parse("<html>").is_err());
+}
+
+#[tokio::test]
+async fn test_locate_is_cached() {
+    let server = HttpServer::start(IPINFO).await;
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path());
+    let geolocator = Geolocator {
+        base_url: server.base_url.clone(),
+    };
+
+    assert_eq!(geolocator.locate(&cache, NOW).await.unwrap(), kyiv());
+    assert_eq!(geolocator.locate(&cache, NOW + 60).await.unwrap(), kyiv());
+    assert_eq!(server.requests(), 1);
+
+    let key = format!("geolocate:{}", server.base_url);
+    let entry = cache.get(Namespace::Geocode, &key).unwrap();
+    assert_eq!(entry.expires, Some(NOW + CACHE_TTL_SECS));
+
+    geolocator
+        .locate(&cache, NOW + CACHE_TTL_SECS)
+        .await
+        .unwrap();
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_locate_fails_without_position() {
+    let server = HttpServer::start(BOGON).await;
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path());
+    let geolocator = Geolocator {
+        base_url: server.base_url.clone(),
+    };
+
+    assert!(geolocator.locate(&cache, NOW).await.is_err());
+    assert!(cache.entries().is_empty());
+}
+
+#[test]
+fn test_no_auto_locate_flag() {
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--no-auto-locate"]).unwrap();
+    match cli.cmd {
+        Commands::Get {
+            city,
+            no_auto_locate,
+            ..
+        } => {
+            assert!(city.is_empty());
+            assert!(no_auto_locate);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
@@ -1,14 +1,14 @@
-mod mocks;
-use mocks::mock_provider::MockProvider;
-use wapp::providers::ApiProvider;
+use wapp::providers::testing::MockProvider;
+use wapp::providers::{ApiProvider, DataKind};
 
 #[tokio::test]
 async fn test_get_data_with_mock() {
-    let mock = MockProvider {
-        response: "DATA_OK".into(),
-    };
+    let mock = MockProvider::new("DATA_OK");
 
-    let out = mock.get_data("Kyiv".into(), "now".into()).await.unwrap();
+    let out = mock
+        .get_data("Kyiv".into(), DataKind::Now, None)
+        .await
+        .unwrap();
 
     assert_eq!(out, "DATA_OK");
 }
@@ -0,0 +1,188 @@
+use chrono::NaiveDate;
+use wapp::output;
+use wapp::output::color::{Icons, Palette};
+use wapp::output::grid::{render_forecast, Borders};
+use wapp::series::DailySummary;
+use wapp::units::Units;
+use wapp::weather::WeatherData;
+
+const CONDITIONS: [&str; 4] = [
+    "Sunny",
+    "Patchy rain nearby",
+    "Moderate or heavy rain with thunder",
+    "Partly cloudy",
+];
+
+/// `n` forecast days from 2024-06-10, cycling through [`CONDITIONS`].
+fn days(n: u32) -> WeatherData {
+    WeatherData {
+        forecast: (0..n)
+            .map(|i| DailySummary {
+                date: NaiveDate::from_ymd_opt(2024, 6, 10 + i).unwrap(),
+                min_c: Some(10.0 + f64::from(i) / 2.0),
+                max_c: Some(18.5 + f64::from(i)),
+                precip_mm: Some(0.4),
+                precip_chance: Some(f64::from(i * 10)),
+                max_wind_kph: Some(12.0 + f64::from(i)),
+                avg_wind_kph: None,
+                condition: Some(CONDITIONS[i as usize % CONDITIONS.len()].into()),
+                partial: false,
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn grid(data: &WeatherData, width: usize, borders: Borders) -> Option<String> {
+    render_forecast("Kyiv", data, Palette::PLAIN, Units::Metric, width, borders)
+}
+
+/// Asserts that every line of the grid below the city is `width` characters
+/// at most and that its column lines are in the same places on each line.
+fn assert_aligned(grid: &str, width: usize) {
+    let lines: Vec<&str> = grid.lines().skip(1).collect();
+    let joints = |line: &str| -> Vec<usize> {
+        line.chars()
+            .enumerate()
+            .filter(|(_, c)| "│┌┬┐├┼┤└┴┘|+".contains(*c))
+            .map(|(i, _)| i)
+            .collect()
+    };
+
+    let first = joints(lines[0]);
+    for line in &lines {
+        assert!(line.chars().count() <= width, "{}\n{}", line, grid);
+        assert_eq!(line.chars().count(), lines[0].chars().count(), "{}", grid);
+        assert_eq!(joints(line), first, "{}\n{}", line, grid);
+    }
+}
+
+#[test]
+fn test_three_days_fit_in_full() {
+    let grid = grid(&days(3), 86, Borders::Unicode).unwrap();
+
+    assert_eq!(
+        grid,
+        "Kyiv\n\
+         ┌───────────┬────────────┬────────────────────┬─────────────────────────────────────┐\n\
+         │ Date      │ 2024-06-10 │ 2024-06-11         │ 2024-06-12                          │\n\
+         ├───────────┼────────────┼────────────────────┼─────────────────────────────────────┤\n\
+         │ Condition │ Sunny      │ Patchy rain nearby │ Moderate or heavy rain with thunder │\n\
+         │ High °C   │       18.5 │               19.5 │                                20.5 │\n\
+         │ Low °C    │       10.0 │               10.5 │                                11.0 │\n\
+         │ Rain %    │          0 │                 10 │                                  20 │\n\
+         │ Wind      │  12.0 km/h │          13.0 km/h │                           14.0 km/h │\n\
+         └───────────┴────────────┴────────────────────┴─────────────────────────────────────┘\n"
+    );
+    assert_aligned(&grid, 86);
+}
+
+#[test]
+fn test_three_days_cut_the_longest_condition_first() {
+    let grid = grid(&days(3), 70, Borders::Unicode).unwrap();
+
+    assert!(
+        grid.contains("│ Condition │ Sunny      │ Patchy rain nearby │ Moderate or heavy r… │\n"),
+        "{}",
+        grid
+    );
+    assert_aligned(&grid, 70);
+}
+
+#[test]
+fn test_seven_days_are_aligned() {
+    let grid = grid(&days(7), 120, Borders::Unicode).unwrap();
+
+    assert_eq!(grid.lines().count(), 10, "{}", grid);
+    assert!(
+        grid.contains("│ Date      │ 2024-06-10 │ 2024-06-11    │"),
+        "{}",
+        grid
+    );
+    assert!(
+        grid.contains("│ Patchy rain…  │ Moderate or…  │"),
+        "{}",
+        grid
+    );
+    assert!(grid.contains("│          24.5 │\n"), "{}", grid);
+    assert_aligned(&grid, 120);
+}
+
+#[test]
+fn test_ten_days_are_aligned_when_wide_enough() {
+    let data = days(10);
+    let grid = grid(&data, 160, Borders::Ascii).unwrap();
+
+    assert!(grid
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("+-----------+------------+"));
+    assert!(grid.contains("| 2024-06-19   |\n"), "{}", grid);
+    assert!(grid.contains("| Patchy ra... | Moderate...  |"), "{}", grid);
+    assert!(!grid.contains('│') && !grid.contains('…'), "{}", grid);
+    assert_aligned(&grid, 160);
+
+    // Ten columns of at least a date do not fit in 80.
+    assert_eq!(self::grid(&data, 80, Borders::Ascii), None);
+}
+
+#[test]
+fn test_narrow_terminals_get_the_vertical_table() {
+    let data = days(7);
+
+    // The grid needs the dates at least: 13 characters a day and the labels.
+    assert!(grid(&data, 13 + 7 * 13, Borders::Unicode).is_some());
+    assert_eq!(grid(&data, 12 + 7 * 13, Borders::Unicode), None);
+}
+
+#[test]
+fn test_table_output_falls_back_to_rows() {
+    // Without a width set, the table is fitted to 80 columns.
+    assert!(output::render_table("Kyiv", &days(3)).contains("│ Date      │ 2024-06-10 │"));
+
+    let table = output::render_table("Kyiv", &days(10));
+    assert_eq!(table.lines().count(), 12, "{}", table);
+    assert_eq!(
+        table.lines().nth(1),
+        Some("Date        Min °C  Max °C  Rain %  Precip mm  Condition")
+    );
+}
+
+#[test]
+fn test_ascii_borders_follow_the_ascii_glyphs() {
+    assert_eq!(
+        Borders::of(Palette::PLAIN.with_icons(Icons::Ascii)),
+        Borders::Ascii
+    );
+    assert_eq!(
+        Borders::of(Palette::PLAIN.with_icons(Icons::Emoji)),
+        Borders::Unicode
+    );
+    assert_eq!(Borders::of(Palette::PLAIN), Borders::Unicode);
+}
+
+#[test]
+fn test_temperatures_and_partial_days() {
+    let mut data = days(2);
+    data.forecast[0].partial = true;
+    data.forecast[1].max_c = Some(31.0);
+
+    let grid = render_forecast(
+        "Kyiv",
+        &data,
+        Palette::ANSI,
+        Units::Imperial,
+        80,
+        Borders::Unicode,
+    )
+    .unwrap();
+    assert!(grid.contains("│ High °F   │"), "{}", grid);
+    assert!(grid.contains("\x1b[31m87.8\x1b[0m │"), "{}", grid);
+    assert!(grid.contains("\x1b[2m2024-06-10*\x1b[0m │"), "{}", grid);
+    assert!(
+        grid.ends_with("┘\n  * partial day: the forecast covers only part of it\n"),
+        "{}",
+        grid
+    );
+}
@@ -0,0 +1,429 @@
+mod mocks;
+
+use std::io::Write;
+
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+use mocks::cli::wapp;
+use mocks::http_server::HttpServer;
+use wapp::cli::{Cli, Commands};
+use wapp::history::{
+    render, HistoryFilter, HistoryFormat, HistoryQuery, HistoryRecord, HistoryStore, Since,
+};
+use wapp::series::{ExportFormat, SeriesPoint, EXPORT_COLUMNS};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+fn at(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+fn point(city: &str, time: &str, temp_c: f64) -> SeriesPoint {
+    SeriesPoint {
+        city: city.into(),
+        temp_c: Some(temp_c),
+        condition: Some("Clear, calm".into()),
+        ..SeriesPoint::at(at(time))
+    }
+}
+
+fn export(store: &HistoryStore, filter: &HistoryFilter, format: ExportFormat) -> String {
+    let mut out = Vec::new();
+    store.export(filter, format, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn sample_store(dir: &tempfile::TempDir) -> HistoryStore {
+    let store = HistoryStore::new(dir.path().join("data/history.ndjson"));
+    store
+        .append(&[
+            point("Kyiv", "2024-12-31T23:00:00Z", -3.0),
+            point("Lviv", "2025-01-01T09:00:00Z", -1.5),
+            point("Kyiv", "2025-01-01T12:00:00Z", 0.5),
+        ])
+        .unwrap();
+    store
+        .append(&[point("kyiv", "2025-07-01T00:00:00Z", 24.0)])
+        .unwrap();
+    store
+}
+
+#[test]
+fn test_csv_export_has_stable_header_and_recording_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = sample_store(&dir);
+
+    let csv = export(&store, &HistoryFilter::default(), ExportFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines[0], EXPORT_COLUMNS.join(","));
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].starts_with("Kyiv,2024-12-31T23:00:00Z,-3,"));
+    assert!(lines[2].starts_with("Lviv,2025-01-01T09:00:00Z,-1.5,"));
+    assert!(lines[4].ends_with(",\"Clear, calm\""));
+}
+
+#[test]
+fn test_export_filters_by_city_and_date_range() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = sample_store(&dir);
+
+    let filter = HistoryFilter {
+        city: Some("KYIV".into()),
+        since: Some("2025-01-01".parse().unwrap()),
+        until: Some("2025-06-30".parse().unwrap()),
+    };
+    let jsonl = export(&store, &filter, ExportFormat::Jsonl);
+    let records: Vec<serde_json::Value> = jsonl
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["time"], "2025-01-01T12:00:00Z");
+    assert_eq!(records[0]["temp_c"], 0.5);
+}
+
+#[test]
+fn test_missing_store_exports_header_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = HistoryStore::new(dir.path().join("none.ndjson"));
+
+    assert_eq!(
+        export(&store, &HistoryFilter::default(), ExportFormat::Csv),
+        format!("{}\n", EXPORT_COLUMNS.join(","))
+    );
+    assert_eq!(
+        export(&store, &HistoryFilter::default(), ExportFormat::Jsonl),
+        ""
+    );
+}
+
+#[test]
+fn test_unreadable_lines_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = sample_store(&dir);
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(store.path())
+        .unwrap()
+        .write_all(b"not json\n")
+        .unwrap();
+
+    let mut out = Vec::new();
+    let count = store
+        .export(&HistoryFilter::default(), ExportFormat::Jsonl, &mut out)
+        .unwrap();
+
+    assert_eq!(count, 4);
+}
+
+/// Counts lines as they are written, without keeping them.
+#[derive(Default)]
+struct LineCounter {
+    lines: usize,
+    writes: usize,
+}
+
+impl Write for LineCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.lines += buf.iter().filter(|b| **b == b'\n').count();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_large_store_is_streamed() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = HistoryStore::new(dir.path().join("history.ndjson"));
+    let start = at("2025-01-01T00:00:00Z");
+
+    let points: Vec<SeriesPoint> = (0..5000)
+        .map(|i| SeriesPoint {
+            city: if i % 2 == 0 { "Kyiv" } else { "Lviv" }.into(),
+            temp_c: Some(i as f64),
+            ..SeriesPoint::at(start + Duration::hours(i))
+        })
+        .collect();
+    store.append(&points).unwrap();
+
+    let filter = HistoryFilter {
+        city: Some("Kyiv".into()),
+        ..Default::default()
+    };
+    let mut out = LineCounter::default();
+    let count = store.export(&filter, ExportFormat::Csv, &mut out).unwrap();
+
+    assert_eq!(count, 2500);
+    assert_eq!(out.lines, 2501);
+    // One write per record plus the header: rows are emitted as they are read.
+    assert_eq!(out.writes, 2501);
+}
+
+fn query(provider: &str, city: &str, time: &str, temp_c: f64) -> HistoryRecord {
+    HistoryRecord {
+        point: point(city, time, temp_c),
+        provider: Some(provider.into()),
+        kind: Some("now".into()),
+    }
+}
+
+#[test]
+fn test_since_takes_an_age_or_a_date() {
+    let now = at("2025-01-08T12:00:00Z");
+
+    assert_eq!("7d".parse::<Since>().unwrap(), Since::Ago(7 * 86_400));
+    assert_eq!("1h30m".parse::<Since>().unwrap(), Since::Ago(5400));
+    assert_eq!(
+        " 2025-01-01".parse::<Since>().unwrap(),
+        Since::Date("2025-01-01".parse().unwrap())
+    );
+    assert_eq!(
+        Since::Ago(7 * 86_400).cutoff(now),
+        at("2025-01-01T12:00:00Z")
+    );
+    assert_eq!(
+        Since::Date("2025-01-01".parse().unwrap()).cutoff(now),
+        at("2025-01-01T00:00:00Z")
+    );
+    assert_eq!(Since::Ago(u64::MAX).cutoff(now), DateTime::<Utc>::MIN_UTC);
+
+    for invalid in ["", "7", "yesterday", "2025-13-01", "-7d"] {
+        let err = invalid.parse::<Since>().unwrap_err();
+        assert!(err.to_string().contains("invalid start"), "{}", err);
+    }
+}
+
+#[test]
+fn test_history_flags_are_parsed() {
+    let cli = Cli::try_parse_from([
+        "wapp", "history", "--city", "Kyiv", "--since", "7d", "--limit", "5", "--output", "csv",
+    ])
+    .unwrap();
+    match cli.cmd {
+        Commands::History {
+            cmd: None,
+            city,
+            since,
+            limit,
+            output,
+        } => {
+            assert_eq!(city.unwrap().to_string(), "Kyiv");
+            assert_eq!(since, Some(Since::Ago(7 * 86_400)));
+            assert_eq!(limit, Some(5));
+            assert_eq!(output, HistoryFormat::Csv);
+        }
+        _ => panic!("expected the history command"),
+    }
+
+    let cli = Cli::try_parse_from(["wapp", "history"]).unwrap();
+    assert!(matches!(
+        cli.cmd,
+        Commands::History {
+            cmd: None,
+            output: HistoryFormat::Table,
+            ..
+        }
+    ));
+    assert!(Cli::try_parse_from(["wapp", "history", "export", "--output", "-"]).is_ok());
+    assert!(Cli::try_parse_from(["wapp", "history", "--since", "soon"]).is_err());
+    assert!(Cli::try_parse_from(["wapp", "history", "--limit", "-1"]).is_err());
+}
+
+#[test]
+fn test_list_skips_a_corrupt_line_in_the_middle() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = HistoryStore::new(dir.path().join("history.ndjson"));
+    store
+        .record(&query("weatherapi", "Kyiv", "2025-01-01T09:00:00Z", -2.0))
+        .unwrap();
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(store.path())
+        .unwrap()
+        .write_all(b"{\"city\":\"Kyiv\",\"time\":\"2025-01-01T1\n")
+        .unwrap();
+    store
+        .record(&query("openmeteo", "Kyiv", "2025-01-01T12:00:00Z", 0.5))
+        .unwrap();
+
+    let now = at("2025-01-02T00:00:00Z");
+    let records = store.list(&HistoryQuery::default(), now).unwrap();
+
+    assert_eq!(
+        records,
+        [
+            query("weatherapi", "Kyiv", "2025-01-01T09:00:00Z", -2.0),
+            query("openmeteo", "Kyiv", "2025-01-01T12:00:00Z", 0.5),
+        ]
+    );
+}
+
+#[test]
+fn test_list_keeps_the_latest_matching_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = sample_store(&dir);
+    let now = at("2025-07-01T06:00:00Z");
+
+    let list = |query: HistoryQuery| -> Vec<(String, f64)> {
+        store
+            .list(&query, now)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.point.city, r.point.temp_c.unwrap()))
+            .collect()
+    };
+
+    assert_eq!(list(HistoryQuery::default()).len(), 4);
+    assert_eq!(
+        list(HistoryQuery {
+            city: Some("KYIV".into()),
+            limit: Some(2),
+            ..Default::default()
+        }),
+        [("Kyiv".into(), 0.5), ("kyiv".into(), 24.0)]
+    );
+    assert_eq!(
+        list(HistoryQuery {
+            since: Some(Since::Ago(86_400)),
+            ..Default::default()
+        }),
+        [("kyiv".into(), 24.0)]
+    );
+    assert_eq!(
+        list(HistoryQuery {
+            since: Some(Since::Date("2025-01-01".parse().unwrap())),
+            limit: Some(0),
+            ..Default::default()
+        }),
+        []
+    );
+
+    let missing = HistoryStore::new(dir.path().join("none.ndjson"));
+    assert!(missing
+        .list(&HistoryQuery::default(), now)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_records_render_as_table_json_and_csv() {
+    let records = [
+        query("weatherapi", "Kyiv", "2025-01-01T09:00:00Z", -2.0),
+        HistoryRecord {
+            point: SeriesPoint {
+                city: "Lviv".into(),
+                ..SeriesPoint::at(at("2025-01-01T12:00:00Z"))
+            },
+            provider: None,
+            kind: None,
+        },
+    ];
+
+    assert_eq!(
+        render(&records, HistoryFormat::Table),
+        [
+            "Time                  City  Provider    Kind  Temp °C  Condition",
+            "2025-01-01T09:00:00Z  Kyiv  weatherapi  now      -2.0  Clear, calm",
+            "2025-01-01T12:00:00Z  Lviv  -           -           -  -",
+            "",
+        ]
+        .join("\n")
+    );
+    assert_eq!(render(&[], HistoryFormat::Table), "No recorded queries\n");
+
+    let csv = render(&records, HistoryFormat::Csv);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        format!("{},provider,kind", EXPORT_COLUMNS.join(","))
+    );
+    assert!(lines[1].starts_with("Kyiv,2025-01-01T09:00:00Z,-2,"));
+    assert!(lines[1].ends_with(",\"Clear, calm\",weatherapi,now"));
+    assert!(lines[2].ends_with(",,"));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render(&records, HistoryFormat::Json)).unwrap();
+    assert_eq!(json[0]["provider"], "weatherapi");
+    assert_eq!(json[0]["temp_c"], -2.0);
+    assert!(json[1].get("provider").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_queries_are_recorded_when_enabled() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let get = || {
+        let output = wapp(dir.path(), &server.base_url)
+            .args(["get", "--city", "Kyiv", "--provider", "weatherapi"])
+            .args(["--no-cache", "--output", "json"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+    };
+    let history = |args: &[&str]| {
+        let output = wapp(dir.path(), &server.base_url)
+            .arg("history")
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    get();
+    assert_eq!(history(&[]), "No recorded queries\n");
+
+    wapp(dir.path(), &server.base_url)
+        .args(["config", "set", "history", "on"])
+        .assert()
+        .success();
+    get();
+    get();
+
+    let records: Vec<serde_json::Value> = serde_json::from_str(&history(&[
+        "--city", "kyiv", "--since", "1h", "--output", "json",
+    ]))
+    .unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["city"], "Kyiv");
+    assert_eq!(records[0]["provider"], "weatherapi");
+    assert_eq!(records[0]["kind"], "now");
+    assert!(records[0]["temp_c"].is_number());
+
+    let table = history(&["--limit", "1"]);
+    assert_eq!(table.lines().count(), 2, "{}", table);
+    assert!(table.contains("weatherapi"), "{}", table);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unwritable_history_warns_and_continues() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    // The data directory is a file, so the history cannot be created.
+    std::fs::write(dir.path().join("data"), "").unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{"provider":"weatherapi","history":true}"#,
+    )
+    .unwrap();
+
+    let output = wapp(dir.path(), &server.base_url)
+        .args(["get", "--city", "Kyiv", "--no-cache", "--output", "json"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"temp_c\""));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.starts_with("Warning: cannot record the query in "),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,202 @@
+mod mocks;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use chrono::{NaiveDate, TimeZone, Utc};
+use mocks::http_server::HttpServer;
+use wapp::i18n::{resolve, Locale};
+
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+
+/// `wapp get --data forecast` for Kyiv against WeatherAPI at `base_url`,
+/// with its config, cache and data below `dir`.
+fn wapp_forecast(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.args([
+        "get",
+        "--provider",
+        "weatherapi",
+        "--no-cache",
+        "--ascii",
+        "--city",
+        "Kyiv",
+        "--data",
+        "forecast",
+    ]);
+    cmd
+}
+
+#[test]
+fn test_decimal_separators() {
+    assert_eq!(Locale::En.decimal(21.5, 1), "21.5");
+    assert_eq!(Locale::Uk.decimal(21.5, 1), "21,5");
+    assert_eq!(Locale::De.decimal(-0.25, 2), "-0,25");
+    assert_eq!(Locale::Fr.decimal(1017.0, 0), "1017");
+    assert_eq!(Locale::Es.decimal(30.03, 2), "30,03");
+}
+
+#[test]
+fn test_dates_follow_the_order_of_the_locale() {
+    // A Monday.
+    let date = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
+
+    assert_eq!(Locale::En.date(date), "Mon Jun 10");
+    assert_eq!(Locale::Uk.date(date), "Пн 10 черв");
+    assert_eq!(Locale::De.date(date), "Mo 10. Jun");
+    assert_eq!(Locale::Fr.date(date), "lun 10 juin");
+    assert_eq!(Locale::Es.date(date), "lun 10 jun");
+
+    let sunday = NaiveDate::from_ymd_opt(2024, 12, 1).unwrap();
+    assert_eq!(Locale::Uk.date(sunday), "Нд 1 груд");
+    assert_eq!(Locale::Es.date(sunday), "dom 1 dic");
+}
+
+#[test]
+fn test_time_patterns_get_translated_names() {
+    let time = Utc.with_ymd_and_hms(2024, 5, 1, 13, 5, 0).unwrap();
+
+    assert_eq!(
+        Locale::En.format(&time, "%a %d %b %H:%M"),
+        "Wed 01 May 13:05"
+    );
+    assert_eq!(
+        Locale::De.format(&time, "%a %d %b %H:%M"),
+        "Mi 01 Mai 13:05"
+    );
+    assert_eq!(Locale::Fr.format(&time, "%a %H:%M"), "mer 13:05");
+    // `%%a` is a literal `%a`, not a weekday.
+    assert_eq!(Locale::Uk.format(&time, "%%a %a"), "%a Ср");
+}
+
+#[test]
+fn test_locales_parse_from_tags() {
+    assert_eq!("de".parse::<Locale>().unwrap(), Locale::De);
+    assert_eq!("de_DE.UTF-8".parse::<Locale>().unwrap(), Locale::De);
+    assert_eq!("fr-CA".parse::<Locale>().unwrap(), Locale::Fr);
+    assert_eq!("ES".parse::<Locale>().unwrap(), Locale::Es);
+    assert_eq!("uk_UA@euro".parse::<Locale>().unwrap(), Locale::Uk);
+
+    let err = "ja_JP".parse::<Locale>().unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "unknown locale 'ja_JP': expected en, uk, de, fr, or es"
+    );
+}
+
+#[test]
+fn test_flag_wins_over_the_system_locale() {
+    assert_eq!(
+        resolve(Some("uk"), Some("de_DE.UTF-8".into())),
+        Some(Locale::Uk)
+    );
+    assert_eq!(resolve(None, Some("fr_FR.UTF-8".into())), Some(Locale::Fr));
+    assert_eq!(resolve(None, None), None);
+
+    // Unknown locales are English.
+    assert_eq!(resolve(Some("ja"), None), Some(Locale::En));
+    assert_eq!(resolve(None, Some("ja_JP.UTF-8".into())), Some(Locale::En));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_german_text_output() {
+    let server = HttpServer::start(WEATHERAPI_FORECAST).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_forecast(dir.path(), &server.base_url)
+        .args(["--locale", "de_DE.UTF-8"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let text = String::from_utf8(output.stdout).unwrap();
+
+    assert!(text.contains("  Temperature: 17,0 °C\n"), "{}", text);
+    assert!(
+        text.contains("  Wind:        15,1 km/h WSW, gusts 19,4 km/h\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("  Updated:     Mo 10 Jun 12:55\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains(
+            "  Di 11. Jun   11,4 .. 16,8 °C  // Patchy rain nearby  Rain: 78% (2,4 mm)\n"
+        ),
+        "{}",
+        text
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_ukrainian_forecast_table() {
+    let server = HttpServer::start(WEATHERAPI_FORECAST).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_forecast(dir.path(), &server.base_url)
+        .args(["--locale", "uk", "--output", "table"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Kyiv\n\
+         +-----------+---------------+--------------------+------------+\n\
+         | Date      | Пн 10 черв    | Вт 11 черв         | Ср 12 черв |\n\
+         +-----------+---------------+--------------------+------------+\n\
+         | Condition | Partly cloudy | Patchy rain nearby | Sunny      |\n\
+         | High °C   |          18,3 |               16,8 |       20,1 |\n\
+         | Low °C    |          10,2 |               11,4 |        9,8 |\n\
+         | Rain %    |            12 |                 78 |          4 |\n\
+         | Wind      |     19,1 km/h |          19,1 km/h |  19,1 km/h |\n\
+         +-----------+---------------+--------------------+------------+\n"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_json_and_csv_do_not_follow_the_locale() {
+    let server = HttpServer::start(WEATHERAPI_FORECAST).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_forecast(dir.path(), &server.base_url)
+        .args(["--locale", "fr", "--output", "csv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        csv.contains("\n2024-06-11,11.4,16.8,Patchy rain nearby,2.4,19.1,78\n"),
+        "{}",
+        csv
+    );
+
+    let output = wapp_forecast(dir.path(), &server.base_url)
+        .args(["--locale", "fr", "--output", "json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["forecast"][1]["date"], "2024-06-11");
+    assert_eq!(json["forecast"][1]["min_c"], 11.4);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unknown_locale_falls_back_to_english() {
+    let server = HttpServer::start(WEATHERAPI_FORECAST).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_forecast(dir.path(), &server.base_url)
+        .args(["--locale", "ja"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("  Tue Jun 11   11.4 .. 16.8 °C"), "{}", text);
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "Warning: unknown locale 'ja': expected en, uk, de, fr, or es; using English\n"
+    );
+}
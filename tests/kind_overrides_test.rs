@@ -0,0 +1,114 @@
+mod mocks;
+
+use std::collections::BTreeMap;
+
+use mocks::providers::{openweather, weatherapi};
+use wapp::config::{AppConfig, KindOverride, ProviderSettings};
+use wapp::location::Location;
+use wapp::providers::{provider_factory, validate_kind_overrides, DataKind, WeatherApiProvider};
+
+fn overrides(pairs: &[(&str, u32)]) -> BTreeMap<String, KindOverride> {
+    pairs
+        .iter()
+        .map(|(kind, days)| (kind.to_string(), KindOverride { days: Some(*days) }))
+        .collect()
+}
+
+fn weatherapi_with(kind_overrides: BTreeMap<String, KindOverride>) -> WeatherApiProvider {
+    WeatherApiProvider {
+        kind_overrides,
+        ..weatherapi("http://localhost")
+    }
+}
+
+fn url(provider: &WeatherApiProvider, kind: &str, days: Option<u32>) -> String {
+    provider
+        .build_url(&Location::from("Kyiv"), kind.parse().unwrap(), days)
+        .unwrap()
+}
+
+#[test]
+fn test_defaults_without_overrides() {
+    let p = weatherapi("http://localhost");
+
+    assert!(url(&p, "forecast", None).ends_with("&days=3"));
+    assert!(url(&p, "hourly", None).ends_with("&days=1"));
+}
+
+#[test]
+fn test_overrides_replace_kind_defaults() {
+    let p = weatherapi_with(overrides(&[("forecast", 7), ("hourly", 2)]));
+
+    assert!(url(&p, "forecast", None).ends_with("&days=7"));
+    assert!(url(&p, "hourly", None).ends_with("&days=2"));
+    // Kinds without an override keep their defaults.
+    assert!(url(&p, "tomorrow", None).ends_with("&days=2"));
+
+    let ow = openweather("http://localhost")
+        .with_kind_overrides(overrides(&[("hourly", 2)]))
+        .build_url(&Location::from("Oslo"), DataKind::Hourly, None)
+        .unwrap();
+    assert!(ow.ends_with("&cnt=16"), "{}", ow);
+}
+
+#[test]
+fn test_explicit_days_win_over_overrides() {
+    let p = weatherapi_with(overrides(&[("forecast", 7)]));
+
+    assert!(url(&p, "forecast", Some(2)).ends_with("&days=2"));
+}
+
+#[test]
+fn test_out_of_range_overrides_are_rejected() {
+    let err = validate_kind_overrides(
+        &openweather("http://localhost"),
+        &overrides(&[("forecast", 7)]),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("providers.openweather.kind_overrides.forecast: days must be between 1 and 5"),
+        "{}",
+        err
+    );
+
+    assert!(validate_kind_overrides(
+        &weatherapi("http://localhost"),
+        &overrides(&[("hourly", 0)])
+    )
+    .is_err());
+    assert!(
+        validate_kind_overrides(&weatherapi("http://localhost"), &overrides(&[("now", 2)]))
+            .is_err()
+    );
+    assert!(validate_kind_overrides(
+        &weatherapi("http://localhost"),
+        &overrides(&[("pollen", 2)])
+    )
+    .is_err());
+    assert!(validate_kind_overrides(
+        &weatherapi("http://localhost"),
+        &overrides(&[("forecast", 14)])
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_factory_validates_configured_overrides() {
+    std::env::set_var("OPENWEATHER_KEY", "dummy");
+
+    let cfg: AppConfig = serde_json::from_str(
+        r#"{"provider":"openweather","providers":{"openweather":{"kind_overrides":{"forecast":{"days":9}}}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        cfg.providers["openweather"],
+        ProviderSettings {
+            kind_overrides: overrides(&[("forecast", 9)]),
+            ..Default::default()
+        }
+    );
+
+    let err = provider_factory(&cfg).err().unwrap().to_string();
+    assert!(err.contains("between 1 and 5 (got 9)"), "{}", err);
+}
@@ -0,0 +1,193 @@
+use clap::Parser;
+use wapp::cli::{Cli, Commands};
+use wapp::locale::{detect_locale_with, provider_lang, provider_language, resolve_lang};
+use wapp::location::Location;
+use wapp::providers::{DataKind, WeatherApiProvider};
+
+fn env(pairs: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+    move |name| {
+        pairs
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.to_string())
+    }
+}
+
+#[test]
+fn test_detect_locale_precedence() {
+    assert_eq!(
+        detect_locale_with(env(&[
+            ("LANG", "en_US.UTF-8"),
+            ("LC_MESSAGES", "uk_UA.UTF-8")
+        ])),
+        Some("uk_UA.UTF-8".into())
+    );
+    assert_eq!(
+        detect_locale_with(env(&[("LANG", "en_US.UTF-8"), ("LC_ALL", "de_DE")])),
+        Some("de_DE".into())
+    );
+}
+
+#[test]
+fn test_detect_locale_skips_c_locale() {
+    assert_eq!(
+        detect_locale_with(env(&[("LC_ALL", "C"), ("LANG", "C.UTF-8")])),
+        None
+    );
+    assert_eq!(
+        detect_locale_with(env(&[("LC_ALL", ""), ("LANG", "fr_FR.UTF-8")])),
+        Some("fr_FR.UTF-8".into())
+    );
+}
+
+#[test]
+fn test_ukrainian_locale() {
+    assert_eq!(provider_language("weatherapi", "uk_UA.UTF-8"), Some("uk"));
+    assert_eq!(provider_language("openweather", "uk_UA.UTF-8"), Some("ua"));
+}
+
+#[test]
+fn test_english_locale() {
+    // WeatherAPI has no code for its default language.
+    assert_eq!(provider_language("weatherapi", "en_US"), None);
+    assert_eq!(provider_language("openweather", "en_US"), Some("en"));
+}
+
+#[test]
+fn test_provider_specific_codes() {
+    assert_eq!(provider_language("openweather", "cs_CZ.UTF-8"), Some("cz"));
+    assert_eq!(
+        provider_language("openweather", "pt_BR.UTF-8"),
+        Some("pt_br")
+    );
+    assert_eq!(provider_language("openweather", "pt_PT.UTF-8"), Some("pt"));
+    assert_eq!(
+        provider_language("weatherapi", "zh_TW.UTF-8"),
+        Some("zh_tw")
+    );
+    assert_eq!(provider_language("weatherapi", "zh_CN.UTF-8"), Some("zh"));
+}
+
+#[test]
+fn test_unsupported_locale_falls_back_to_english() {
+    assert_eq!(provider_language("weatherapi", "eo.UTF-8"), None);
+    assert_eq!(
+        resolve_lang("openweather", None, None, || Some("eo".into())),
+        None
+    );
+}
+
+#[test]
+fn test_explicit_and_env_override_locale() {
+    let detect = || Some("uk_UA.UTF-8".to_string());
+
+    assert_eq!(
+        resolve_lang("weatherapi", Some("fr".into()), Some("de".into()), detect),
+        Some("fr".into())
+    );
+    assert_eq!(
+        resolve_lang("weatherapi", None, Some("de".into()), detect),
+        Some("de".into())
+    );
+    assert_eq!(
+        resolve_lang("weatherapi", None, None, detect),
+        Some("uk".into())
+    );
+}
+
+#[test]
+fn test_provider_lang_maps_bcp47_tags() {
+    assert_eq!(provider_lang("weatherapi", "uk-UA").unwrap(), Some("uk"));
+    assert_eq!(provider_lang("openweather", "uk-UA").unwrap(), Some("ua"));
+    assert_eq!(
+        provider_lang("openweather", "pt-BR").unwrap(),
+        Some("pt_br")
+    );
+    assert_eq!(
+        provider_lang("openweather", "ZH-tw").unwrap(),
+        Some("zh_tw")
+    );
+    assert_eq!(
+        provider_lang("weatherapi", "zh-Hant").unwrap(),
+        Some("zh_tw")
+    );
+    assert_eq!(
+        provider_lang("weatherapi", "zh-Hans-CN").unwrap(),
+        Some("zh")
+    );
+    assert_eq!(provider_lang("openweather", "nb-NO").unwrap(), Some("no"));
+}
+
+#[test]
+fn test_provider_lang_accepts_provider_codes() {
+    assert_eq!(provider_lang("openweather", "ua").unwrap(), Some("ua"));
+    assert_eq!(provider_lang("openweather", "cz").unwrap(), Some("cz"));
+    assert_eq!(
+        provider_lang("openweather", "zh_cn").unwrap(),
+        Some("zh_cn")
+    );
+    // Another provider's code selects the same language.
+    assert_eq!(provider_lang("weatherapi", "ua").unwrap(), Some("uk"));
+    assert_eq!(provider_lang("weatherapi", "kr").unwrap(), Some("ko"));
+}
+
+#[test]
+fn test_provider_lang_english() {
+    assert_eq!(provider_lang("weatherapi", "en").unwrap(), None);
+    assert_eq!(provider_lang("weatherapi", "en-GB").unwrap(), None);
+    assert_eq!(provider_lang("openweather", "en-US").unwrap(), Some("en"));
+}
+
+#[test]
+fn test_provider_lang_rejects_unsupported() {
+    let e = provider_lang("weatherapi", "eo").unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "language 'eo' is not supported by provider 'weatherapi'"
+    );
+    // WeatherAPI has no Afrikaans, OpenWeatherMap does.
+    assert!(provider_lang("weatherapi", "af").is_err());
+    assert_eq!(provider_lang("openweather", "af").unwrap(), Some("af"));
+}
+
+#[test]
+fn test_unsupported_lang_falls_back_to_english() {
+    let detect = || Some("uk_UA.UTF-8".to_string());
+
+    assert_eq!(
+        resolve_lang("weatherapi", Some("eo".into()), Some("de".into()), detect),
+        None
+    );
+    assert_eq!(
+        resolve_lang("openweather", None, Some("uk".into()), detect),
+        Some("ua".into())
+    );
+}
+
+#[test]
+fn test_lang_is_percent_encoded() {
+    let p = WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: "http://w".into(),
+        lang: Some("zh tw&x".into()),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    assert_eq!(
+        p.build_url(&Location::from("Kyiv"), DataKind::Now, None)
+            .unwrap(),
+        "http://w/current.json?key=KEY&q=Kyiv&lang=zh%20tw%26x"
+    );
+}
+
+#[test]
+fn test_get_parses_lang() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--lang", " pt-BR "]);
+    match cli.cmd {
+        Commands::Get { lang, .. } => assert_eq!(lang.as_deref(), Some("pt-BR")),
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(["wapp", "get", "--lang", "e n"]).is_err());
+}
@@ -0,0 +1,387 @@
+use proptest::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+use urlencoding::encode;
+use wapp::location::{
+    normalize_city, parse_city_list, CityName, Location, LocationInput, MAX_CITY_CHARS,
+};
+use wapp::providers::{DataKind, OpenWeatherProvider, WeatherApiProvider};
+
+#[test]
+fn test_composed_and_decomposed_match() {
+    let composed = "San Jos\u{e9}";
+    let decomposed = "San Jose\u{301}";
+
+    assert_ne!(composed, decomposed);
+    assert_eq!(normalize_city(composed), normalize_city(decomposed));
+}
+
+#[test]
+fn test_whitespace_is_trimmed_and_collapsed() {
+    assert_eq!(normalize_city("  New \t  York \n"), "New York");
+    assert_eq!(normalize_city("San\u{a0}Francisco"), "San Francisco");
+}
+
+#[test]
+fn test_zero_width_and_control_chars_are_stripped() {
+    assert_eq!(normalize_city("\u{feff}Ky\u{200b}iv\u{7}"), "Kyiv");
+    assert_eq!(normalize_city("\u{200e}Lviv\u{200f}"), "Lviv");
+}
+
+#[test]
+fn test_non_latin_scripts_pass_through() {
+    for city in ["Київ", "東京", "القاهرة", "नई दिल्ली", "Αθήνα", "서울"] {
+        assert_eq!(normalize_city(city), city);
+    }
+}
+
+#[test]
+fn test_zero_width_non_joiner_is_kept() {
+    // Used inside Persian words, must not be stripped.
+    let city = "\u{645}\u{6cc}\u{200c}\u{631}\u{648}\u{62f}";
+    assert_eq!(normalize_city(city), city);
+}
+
+#[test]
+fn test_parse_plain_city() {
+    let loc = LocationInput::parse("London");
+
+    assert_eq!(loc.city, "London");
+    assert!(!loc.is_structured());
+}
+
+#[test]
+fn test_parse_us_state() {
+    let loc = LocationInput::parse("Portland, OR");
+
+    assert_eq!(loc.city, "Portland");
+    assert_eq!(loc.region.as_deref(), Some("OR"));
+    assert_eq!(loc.country.as_deref(), Some("US"));
+    assert_eq!(OpenWeatherProvider::query(&loc), "Portland,OR,US");
+}
+
+#[test]
+fn test_parse_country_code() {
+    let loc = LocationInput::parse("Paris, fr");
+
+    assert_eq!(loc.city, "Paris");
+    assert_eq!(loc.region, None);
+    assert_eq!(loc.country.as_deref(), Some("FR"));
+    assert_eq!(OpenWeatherProvider::query(&loc), "Paris,FR");
+}
+
+#[test]
+fn test_parse_city_region_country() {
+    let loc = LocationInput::parse("Paris, tx, US");
+
+    assert_eq!(loc.city, "Paris");
+    assert_eq!(loc.region.as_deref(), Some("TX"));
+    assert_eq!(loc.country.as_deref(), Some("US"));
+    assert_eq!(OpenWeatherProvider::query(&loc), "Paris,TX,US");
+}
+
+#[test]
+fn test_non_us_region_is_dropped_for_openweather() {
+    let loc = LocationInput::parse("Cambridge, England, GB");
+
+    assert_eq!(loc.region.as_deref(), Some("England"));
+    assert_eq!(OpenWeatherProvider::query(&loc), "Cambridge,GB");
+}
+
+#[test]
+fn test_state_country_ambiguity_falls_back_to_raw() {
+    // "CA" is both California and Canada.
+    let loc = LocationInput::parse("Toronto, CA");
+
+    assert!(!loc.is_structured());
+    assert_eq!(loc.city, "Toronto, CA");
+    assert_eq!(loc.raw, "Toronto, CA");
+}
+
+#[test]
+fn test_ambiguity_resolved_by_explicit_country() {
+    let loc = LocationInput::parse("Los Angeles, CA, US");
+
+    assert_eq!(loc.region.as_deref(), Some("CA"));
+    assert_eq!(loc.country.as_deref(), Some("US"));
+}
+
+#[test]
+fn test_parse_extra_whitespace() {
+    let loc = LocationInput::parse("  Portland ,   OR  ");
+
+    assert_eq!(loc.city, "Portland");
+    assert_eq!(loc.region.as_deref(), Some("OR"));
+    assert_eq!(loc.raw, "Portland, OR");
+}
+
+#[test]
+fn test_parse_quoted_city_with_comma() {
+    let loc = LocationInput::parse("\"Washington, D.C.\", US");
+
+    assert_eq!(loc.city, "Washington, D.C.");
+    assert_eq!(loc.country.as_deref(), Some("US"));
+}
+
+#[test]
+fn test_unrecognized_components_fall_back_to_raw() {
+    for input in [
+        "Springfield, Nowhere",
+        "A, B, C, D",
+        "Kyiv, , UA",
+        "Lviv, Ukraine, XX",
+    ] {
+        let loc = LocationInput::parse(input);
+        assert!(!loc.is_structured(), "{input} should not be structured");
+        assert_eq!(loc.city, loc.raw);
+    }
+}
+
+#[test]
+fn test_city_name_rejects_empty_input() {
+    for input in ["", "   ", "\u{200b}\u{feff}"] {
+        let err = CityName::new(input).unwrap_err().to_string();
+        assert!(err.contains("empty"), "{:?}: {}", input, err);
+    }
+}
+
+#[test]
+fn test_city_name_rejects_overlong_input() {
+    let err = CityName::new(&"a".repeat(5000)).unwrap_err().to_string();
+    assert!(err.contains("too long (5000 characters"), "{}", err);
+
+    assert!(CityName::new(&"a".repeat(MAX_CITY_CHARS)).is_ok());
+}
+
+#[test]
+fn test_city_name_rejects_control_characters() {
+    for input in ["Kyiv\nLviv", "Kyiv\r\nLviv", "Ky\u{7}iv", "New\tYork"] {
+        let err = CityName::new(input).unwrap_err().to_string();
+        assert!(err.contains("control character"), "{:?}: {}", input, err);
+    }
+}
+
+#[test]
+fn test_city_name_rejects_pure_punctuation() {
+    for input in ["...", "?!", "-- --", "\"\""] {
+        let err = CityName::new(input).unwrap_err().to_string();
+        assert!(err.contains("no letters or digits"), "{:?}: {}", input, err);
+    }
+}
+
+#[test]
+fn test_city_name_accepts_edge_cases() {
+    // "Y" is a commune in the Somme department of France.
+    for input in [
+        "Y",
+        "Å",
+        "東京",
+        "Saint-Louis-du-Ha! Ha!",
+        "10115, DE",
+        "Portland, OR",
+    ] {
+        assert_eq!(CityName::new(input).unwrap().as_str(), input);
+    }
+}
+
+#[test]
+fn test_city_name_is_normalized() {
+    let name = CityName::new("  San\u{a0}Jose\u{301} \n").unwrap();
+
+    assert_eq!(name.as_str(), "San Jos\u{e9}");
+    assert_eq!(LocationInput::from(&name), LocationInput::parse("San José"));
+}
+
+#[test]
+fn test_coordinates_are_range_checked() {
+    for (lat, lon) in [(90.0, 180.0), (-90.0, -180.0), (0.0, 0.0), (50.45, 30.52)] {
+        assert!(Location::coords(lat, lon).is_ok(), "{} {}", lat, lon);
+    }
+
+    let err = Location::coords(90.5, 0.0).unwrap_err();
+    assert!(err.to_string().contains("latitude 90.5 is out of range"));
+    let err = Location::coords(0.0, -181.0).unwrap_err();
+    assert!(err.to_string().contains("longitude -181 is out of range"));
+    assert!(Location::coords(f64::NAN, 0.0).is_err());
+}
+
+#[test]
+fn test_coordinates_label_and_query() {
+    let location = Location::coords(50.45, -30.52).unwrap();
+
+    assert_eq!(location.label(), "50.45,-30.52");
+    assert_eq!(location.query(), "50.45,-30.52");
+    assert_eq!(Location::from("São Paulo").query(), encode("São Paulo"));
+}
+
+#[test]
+fn test_openweather_sends_coordinates_as_lat_lon() {
+    let provider = OpenWeatherProvider::new("KEY");
+
+    let url = provider
+        .build_url(
+            &Location::coords(50.45, 30.52).unwrap(),
+            DataKind::Now,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "https://api.openweathermap.org/data/2.5/weather?lat=50.45&lon=30.52&appid=KEY"
+    );
+}
+
+#[test]
+fn test_weatherapi_sends_coordinates_as_query() {
+    let provider = WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let url = provider
+        .build_url(
+            &Location::coords(50.45, 30.52).unwrap(),
+            DataKind::Now,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(url, "http://localhost/current.json?key=KEY&q=50.45,30.52");
+}
+
+#[test]
+fn test_parse_zip() {
+    assert_eq!(
+        Location::zip("10001,us").unwrap(),
+        Location::Zip {
+            code: "10001".into(),
+            country: Some("US".into())
+        }
+    );
+    assert_eq!(
+        Location::zip(" sw1a  1aa , gb").unwrap(),
+        Location::Zip {
+            code: "SW1A 1AA".into(),
+            country: Some("GB".into())
+        }
+    );
+    assert_eq!(Location::zip("10001").unwrap().label(), "10001");
+    assert_eq!(Location::zip("SW1A 1AA").unwrap().query(), "SW1A%201AA");
+}
+
+#[test]
+fn test_invalid_zip_is_rejected() {
+    for input in ["", " , us", "1000/1", "12345678901"] {
+        let err = Location::zip(input).unwrap_err();
+        assert!(err.to_string().contains("invalid postal code"), "{}", input);
+    }
+
+    let err = Location::zip("10001,usa").unwrap_err();
+    assert!(err.to_string().contains("invalid country 'USA'"));
+}
+
+#[test]
+fn test_parse_iata() {
+    assert_eq!(
+        Location::iata(" lhr ").unwrap(),
+        Location::Iata("LHR".into())
+    );
+
+    for input in ["LH", "LHRX", "L1R", ""] {
+        let err = Location::iata(input).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid airport code"),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_openweather_sends_zip_and_rejects_airports() {
+    let provider = OpenWeatherProvider::new("KEY");
+
+    let url = provider
+        .build_url(&Location::zip("10001,us").unwrap(), DataKind::Now, None)
+        .unwrap();
+    assert_eq!(
+        url,
+        "https://api.openweathermap.org/data/2.5/weather?zip=10001,US&appid=KEY"
+    );
+
+    let err = provider
+        .build_url(&Location::iata("LHR").unwrap(), DataKind::Now, None)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "airport code locations are not supported by this provider (openweather)"
+    );
+}
+
+#[test]
+fn test_weatherapi_sends_zip_and_iata_as_query() {
+    let provider = WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let url = provider
+        .build_url(&Location::zip("10001,us").unwrap(), DataKind::Now, None)
+        .unwrap();
+    assert_eq!(url, "http://localhost/current.json?key=KEY&q=10001");
+
+    let url = provider
+        .build_url(&Location::iata("lhr").unwrap(), DataKind::Now, None)
+        .unwrap();
+    assert_eq!(url, "http://localhost/current.json?key=KEY&q=iata:LHR");
+}
+
+proptest! {
+    #[test]
+    fn prop_nfc_and_nfd_produce_identical_requests(s in "\\PC{0,40}") {
+        let composed: String = s.nfc().collect();
+        let decomposed: String = s.nfd().collect();
+
+        let a = normalize_city(&composed);
+        let b = normalize_city(&decomposed);
+
+        prop_assert_eq!(&a, &b);
+        prop_assert_eq!(encode(&a), encode(&b));
+    }
+
+    #[test]
+    fn prop_normalization_is_idempotent(s in "\\PC{0,40}") {
+        let once = normalize_city(&s);
+        prop_assert_eq!(normalize_city(&once), once.clone());
+    }
+
+    #[test]
+    fn prop_output_has_no_outer_or_double_spaces(s in "[ \\t\\u{a0}a-zé]{0,40}") {
+        let out = normalize_city(&s);
+        prop_assert_eq!(out.trim(), out.as_str());
+        prop_assert!(!out.contains("  "));
+    }
+}
+
+#[test]
+fn test_city_list_skips_blanks_and_comments() {
+    let cities =
+        parse_city_list("# nightly report\nKyiv\n\n  Lviv  \n   # Odesa\n@home\n").unwrap();
+
+    let names: Vec<&str> = cities.iter().map(|c| c.as_str()).collect();
+    assert_eq!(names, vec!["Kyiv", "Lviv", "@home"]);
+    assert!(parse_city_list("\n# none\n").unwrap().is_empty());
+}
+
+#[test]
+fn test_city_list_names_invalid_line() {
+    let err = parse_city_list("Kyiv\n???\n").unwrap_err();
+
+    assert!(err.to_string().starts_with("line 2: "));
+}
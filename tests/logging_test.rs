@@ -0,0 +1,193 @@
+mod mocks;
+
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use mocks::http_server::HttpServer;
+use tracing_subscriber::fmt::MakeWriter;
+use wapp::location::Location;
+use wapp::logging::{redact_url, subscriber, verbosity_filter, LogFormat};
+use wapp::providers::{ApiProvider, DataKind, WeatherApiProvider};
+
+/// Collects everything the subscriber writes.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Capture {
+    type Writer = Capture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[test]
+fn test_redact_url_hides_secret_params() {
+    assert_eq!(
+        redact_url("http://x/current.json?key=SECRET&q=Kyiv&lang=uk"),
+        "http://x/current.json?key=<redacted>&q=Kyiv&lang=uk"
+    );
+    assert_eq!(
+        redact_url("http://x/weather?q=Oslo&APPID=SECRET"),
+        "http://x/weather?q=Oslo&APPID=<redacted>"
+    );
+    assert_eq!(
+        redact_url("http://x/weather/realtime?location=Oslo&apikey=SECRET"),
+        "http://x/weather/realtime?location=Oslo&apikey=<redacted>"
+    );
+    assert_eq!(redact_url("http://x/weather"), "http://x/weather");
+}
+
+#[tokio::test]
+async fn test_json_log_of_mocked_request() {
+    let server = HttpServer::start(r#"{"current":{"temp_c":21}}"#).await;
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-123".into(),
+        base_url: server.base_url.clone(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(subscriber(LogFormat::Json, None, capture.clone()));
+
+    let body = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert!(body.contains("temp_c"));
+
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(!output.contains("SECRET-KEY-123"), "{}", output);
+
+    let events: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let event = events
+        .iter()
+        .find(|e| e["message"] == "provider request")
+        .expect("request event logged");
+
+    for field in ["timestamp", "level", "target", "elapsed_ms"] {
+        assert!(event.get(field).is_some(), "missing {}: {}", field, event);
+    }
+    assert_eq!(event["level"], "INFO");
+    assert_eq!(event["provider"], "weatherapi");
+    assert_eq!(event["status"], 200);
+    assert_eq!(event["cache_hit"], false);
+    assert!(event["url_redacted"]
+        .as_str()
+        .unwrap()
+        .ends_with("/current.json?key=<redacted>&q=Kyiv"));
+}
+
+#[tokio::test]
+async fn test_request_span_fields() {
+    let server = HttpServer::start(r#"{"current":{"temp_c":21}}"#).await;
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-456".into(),
+        base_url: server.base_url.clone(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(subscriber(
+        LogFormat::Json,
+        Some("debug"),
+        capture.clone(),
+    ));
+
+    provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(!output.contains("SECRET-KEY-456"), "{}", output);
+    let event: serde_json::Value = output
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|e| e["message"] == "provider request")
+        .expect("request event logged");
+
+    let span = &event["span"];
+    assert_eq!(span["name"], "request", "{}", event);
+    assert_eq!(span["provider"], "weatherapi");
+    assert_eq!(span["host"], "127.0.0.1");
+    assert_eq!(span["attempt"], 1);
+    assert_eq!(span["status"], 200);
+    assert!(span["elapsed_ms"].is_u64(), "{}", event);
+}
+
+#[test]
+fn test_verbosity_filter() {
+    assert_eq!(verbosity_filter(0), None);
+    assert_eq!(verbosity_filter(1), Some("info"));
+    assert_eq!(verbosity_filter(2), Some("debug"));
+    assert_eq!(verbosity_filter(5), Some("trace"));
+}
+
+#[tokio::test]
+async fn test_text_format_hides_request_events_by_default() {
+    let server = HttpServer::start("{}").await;
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-123".into(),
+        base_url: server.base_url.clone(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(subscriber(LogFormat::Text, None, capture.clone()));
+
+    provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    assert!(capture.0.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_request_logs_hide_the_key() {
+    mocks::http_server::fast_retries();
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-789".into(),
+        base_url: "http://127.0.0.1:9".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let capture = Capture::default();
+    let _guard =
+        tracing::subscriber::set_default(subscriber(LogFormat::Json, None, capture.clone()));
+
+    let err = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("provider request failed"), "{}", output);
+    assert!(!output.contains("SECRET-KEY-789"), "{}", output);
+    assert!(!format!("{:?}", err).contains("SECRET-KEY-789"));
+}
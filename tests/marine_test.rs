@@ -0,0 +1,165 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use mocks::providers::weatherapi;
+use wapp::cli::{Cli, Commands};
+use wapp::location::{CityName, Location};
+use wapp::output::{self, OutputFormat};
+use wapp::providers::{validate_kind, ApiProvider, DataKind, TomorrowIoProvider};
+use wapp::request::{validate_request, WeatherRequest};
+use wapp::weather::TideKind;
+
+const WEATHERAPI_MARINE: &str = include_str!("fixtures/weatherapi_marine.json");
+
+#[test]
+fn test_weatherapi_marine_url_takes_coordinates_and_days() {
+    let p = weatherapi("http://w");
+    let open_water = Location::coords(50.7, -0.5).unwrap();
+
+    assert_eq!(
+        p.build_url(&open_water, DataKind::Marine, Some(2)).unwrap(),
+        "http://w/marine.json?key=KEY&q=50.7,-0.5&days=2&tides=yes"
+    );
+    assert_eq!(
+        p.build_url(&Location::from("Brighton"), DataKind::Marine, None)
+            .unwrap(),
+        "http://w/marine.json?key=KEY&q=Brighton&days=3&tides=yes"
+    );
+}
+
+#[test]
+fn test_weatherapi_marine_is_normalized() {
+    let days = weatherapi("http://w").marine(WEATHERAPI_MARINE).unwrap();
+
+    assert_eq!(days.len(), 2);
+    let day = &days[0];
+    assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 6, 10).unwrap());
+    assert_eq!(day.max_wave_m, Some(1.2));
+    assert_eq!(day.max_swell_m, Some(0.9));
+    assert!((day.water_temp_c.unwrap() - 16.4).abs() < 1e-9);
+
+    let hour = &day.hours[1];
+    assert_eq!(hour.time.to_string(), "2024-06-10 06:00:00");
+    assert_eq!(hour.wave_m, Some(1.2));
+    assert_eq!(hour.swell_degree, Some(230.0));
+    assert_eq!(hour.swell_period_s, Some(8.1));
+
+    // Tides are sorted, their heights read from strings.
+    let tides: Vec<(String, TideKind, Option<f64>)> = day
+        .tides
+        .iter()
+        .map(|t| (t.time.format("%H:%M").to_string(), t.kind, t.height_m))
+        .collect();
+    assert_eq!(
+        tides,
+        [
+            ("03:12".into(), TideKind::High, Some(4.1)),
+            ("09:25".into(), TideKind::Low, Some(1.3)),
+            ("15:40".into(), TideKind::High, Some(4.3)),
+            ("21:58".into(), TideKind::Low, Some(1.1)),
+        ]
+    );
+}
+
+#[test]
+fn test_text_groups_tides_by_day() {
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "Brighton",
+        "marine",
+        WEATHERAPI_MARINE,
+        OutputFormat::Text,
+    );
+
+    assert_eq!(
+        text,
+        "Brighton\n\
+         \x20 2024-06-10   Waves up to 1.2 m, swell up to 0.9 m, water 16.4 °C\n\
+         \x20              High tide  03:12  4.1 m\n\
+         \x20              Low tide   09:25  1.3 m\n\
+         \x20              High tide  15:40  4.3 m\n\
+         \x20              Low tide   21:58  1.1 m\n\
+         \x20 2024-06-11   Waves up to 0.5 m, swell up to 0.4 m, water 16.0 °C\n\
+         \x20              High tide  04:01  4.0 m\n"
+    );
+}
+
+#[test]
+fn test_table_has_a_row_per_hour() {
+    let table = output::summarize(
+        &weatherapi("http://w"),
+        "Brighton",
+        "marine",
+        WEATHERAPI_MARINE,
+        OutputFormat::Table,
+    );
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines.len(), 6, "{table}");
+    assert_eq!(
+        lines[1],
+        "Time       Wave m  Swell m  Swell from  Period s  Water °C"
+    );
+    assert_eq!(
+        lines[3],
+        "Mon 06:00     1.2      0.9  SW                 8      16.4"
+    );
+}
+
+#[test]
+fn test_json_carries_days_hours_and_tides() {
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(&weatherapi("http://w"), "marine", WEATHERAPI_MARINE).unwrap(),
+    )
+    .unwrap();
+    let day = &json["marine"][0];
+
+    assert_eq!(day["date"], "2024-06-10");
+    assert_eq!(day["max_wave_m"], 1.2);
+    assert_eq!(day["hours"][0]["time"], "2024-06-10 00:00");
+    assert_eq!(day["hours"][0]["swell_period_s"], 7.5);
+    assert_eq!(day["tides"][0]["time"], "2024-06-10 03:12");
+    assert_eq!(day["tides"][0]["kind"], "high");
+    assert_eq!(day["tides"][1]["height_m"], 1.3);
+}
+
+#[test]
+fn test_marine_only_from_weatherapi() {
+    assert!(validate_kind(&weatherapi("http://w"), DataKind::Marine).is_ok());
+
+    let tomorrow = TomorrowIoProvider {
+        api_key: "KEY".into(),
+        base_url: "http://t".into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let err = validate_kind(&tomorrow, DataKind::Marine)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.starts_with("data kind 'marine' is not supported by provider 'tomorrowio'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_days_set_the_marine_range() {
+    let request = WeatherRequest::new(
+        &CityName::new("Brighton").unwrap(),
+        DataKind::Marine,
+        Some(2),
+    );
+    assert!(validate_request(&weatherapi("http://w"), &request).is_ok());
+
+    let cli = Cli::parse_from([
+        "wapp", "get", "--lat", "50.7", "--lon", "-0.5", "--data", "marine", "--days", "2",
+    ]);
+    match cli.cmd {
+        Commands::Get { data, days, .. } => {
+            assert_eq!(data, vec![DataKind::Marine]);
+            assert_eq!(days, Some(2));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
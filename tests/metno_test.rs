@@ -0,0 +1,162 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use mocks::http_server::{HttpServer, Reply};
+use wapp::location::Location;
+use wapp::providers::metno::{symbol_text, DEFAULT_USER_AGENT};
+use wapp::providers::openmeteo::Place;
+use wapp::providers::{ApiProvider, DataKind, MetNoProvider};
+
+const GEOCODING: &str = r#"{"results":[{"name":"Oslo","latitude":59.91273,"longitude":10.74609}]}"#;
+
+const COMPACT: &str = r#"{"type":"Feature","properties":{"meta":{"updated_at":"2024-05-01T09:00:00Z"},"timeseries":[
+    {"time":"2024-05-01T22:00:00Z","data":{"instant":{"details":{"air_temperature":8.1,"air_pressure_at_sea_level":1012.3,"cloud_area_fraction":75.0,"relative_humidity":80.0,"wind_speed":2.5}},"next_1_hours":{"summary":{"symbol_code":"partlycloudy_night"},"details":{"precipitation_amount":0.0}}}},
+    {"time":"2024-05-01T23:00:00Z","data":{"instant":{"details":{"air_temperature":7.4}},"next_1_hours":{"summary":{"symbol_code":"lightrain"},"details":{"precipitation_amount":0.3}}}},
+    {"time":"2024-05-02T00:00:00Z","data":{"instant":{"details":{"air_temperature":6.9}},"next_6_hours":{"summary":{"symbol_code":"lightrain"},"details":{"precipitation_amount":1.2}}}},
+    {"time":"2024-05-03T00:00:00Z","data":{"instant":{"details":{"air_temperature":5.0}},"next_6_hours":{"summary":{"symbol_code":"cloudy"},"details":{"precipitation_amount":0.0}}}},
+    {"time":"2024-05-04T00:00:00Z","data":{"instant":{"details":{"air_temperature":4.0}},"next_6_hours":{"summary":{"symbol_code":"clearsky_day"},"details":{"precipitation_amount":0.0}}}}
+]}}"#;
+
+fn provider(base_url: &str, geocoding_url: &str) -> MetNoProvider {
+    MetNoProvider {
+        base_url: base_url.into(),
+        geocoding_url: geocoding_url.into(),
+        user_agent: DEFAULT_USER_AGENT.into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+async fn get(kind: &str, days: Option<u32>) -> (MetNoProvider, String) {
+    let geocoding = HttpServer::start(GEOCODING).await;
+    let forecast = HttpServer::start(COMPACT).await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    let body = p
+        .get_data(Location::from("Oslo"), kind.parse().unwrap(), days)
+        .await
+        .unwrap();
+    (p, body)
+}
+
+#[test]
+fn test_url_rounds_coordinates() {
+    let place = Place {
+        name: "Oslo".into(),
+        latitude: 59.91273,
+        longitude: 10.74609,
+    };
+
+    assert_eq!(
+        provider(
+            "https://api.met.no/weatherapi/locationforecast/2.0",
+            "http://g"
+        )
+        .build_url(&place),
+        "https://api.met.no/weatherapi/locationforecast/2.0/compact?lat=59.9127&lon=10.7461"
+    );
+}
+
+#[test]
+fn test_symbol_text() {
+    assert_eq!(symbol_text("clearsky_day"), "Clear sky");
+    assert_eq!(symbol_text("partlycloudy_night"), "Partly cloudy");
+    assert_eq!(
+        symbol_text("heavyrainshowersandthunder_polartwilight"),
+        "Heavy rain showers and thunder"
+    );
+}
+
+#[test]
+fn test_views() {
+    let p = provider("http://f", "http://g");
+
+    let now = p.current(COMPACT).unwrap();
+    assert_eq!(now.temp_c, Some(8.1));
+    assert_eq!(now.wind_kph, Some(9.0));
+    assert_eq!(now.condition.as_deref(), Some("Partly cloudy"));
+
+    let days = p.daily(COMPACT).unwrap();
+    assert_eq!(days.len(), 4);
+    assert_eq!(days[0].precip_mm, Some(0.3));
+    assert_eq!(days[1].condition.as_deref(), Some("Light rain"));
+}
+
+#[tokio::test]
+async fn test_now_keeps_first_entry() {
+    let (p, body) = get("now", None).await;
+
+    assert_eq!(p.series(&body).unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_forecast_defaults_to_three_days() {
+    let (p, body) = get("forecast", None).await;
+
+    assert_eq!(
+        p.forecast_dates(&body).unwrap(),
+        vec![date(1), date(2), date(3)]
+    );
+}
+
+#[tokio::test]
+async fn test_tomorrow_filters_to_next_date() {
+    let (p, body) = get("tomorrow", None).await;
+
+    assert_eq!(p.forecast_dates(&body).unwrap(), vec![date(2)]);
+}
+
+#[tokio::test]
+async fn test_sends_user_agent_and_if_modified_since() {
+    let geocoding = HttpServer::start(GEOCODING).await;
+    let forecast = HttpServer::start_replies(vec![
+        Reply {
+            status: 200,
+            headers: vec![("last-modified", "Wed, 01 May 2024 09:00:00 GMT")],
+            body: COMPACT,
+        },
+        Reply {
+            status: 304,
+            headers: Vec::new(),
+            body: "",
+        },
+    ])
+    .await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    let first = p
+        .get_data(Location::from("Oslo"), DataKind::Forecast, Some(9))
+        .await
+        .unwrap();
+    let second = p
+        .get_data(Location::from("Oslo"), DataKind::Forecast, Some(9))
+        .await
+        .unwrap();
+
+    assert_eq!(first, second);
+    let received = forecast.received();
+    assert!(received[0].contains(&format!(
+        "user-agent: {}",
+        DEFAULT_USER_AGENT.to_ascii_lowercase()
+    )));
+    assert!(!received[0].contains("if-modified-since"));
+    assert!(received[1].contains("if-modified-since: wed, 01 may 2024 09:00:00 gmt"));
+}
+
+#[tokio::test]
+async fn test_unknown_city_is_an_error() {
+    let geocoding = HttpServer::start(r#"{"generationtime_ms":0.3}"#).await;
+    let forecast = HttpServer::start(COMPACT).await;
+
+    let err = provider(&forecast.base_url, &geocoding.base_url)
+        .get_data(Location::from("Atlantis"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("not found"));
+    assert_eq!(forecast.requests(), 0);
+}
@@ -0,0 +1,90 @@
+mod mocks;
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use mocks::http_server::HttpServer;
+use wapp::metrics::{self, format_bytes, Latency, Metrics};
+use wapp::providers::{DataKind, WeatherApiProvider};
+use wapp::series::collect;
+
+const FIXTURE: &str = include_str!("fixtures/weatherapi_forecast.json");
+
+// The only test in this file touching the process-wide counters.
+#[tokio::test]
+async fn test_stats_match_server_request_log() {
+    metrics::reset();
+
+    let server = HttpServer::start(FIXTURE).await;
+    let provider = WeatherApiProvider {
+        api_key: "dummy".into(),
+        base_url: server.base_url.clone(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let cities = vec![
+        "Kyiv".parse().unwrap(),
+        "Lviv".parse().unwrap(),
+        "Odesa".parse().unwrap(),
+    ];
+
+    collect(&provider, &cities, DataKind::Hourly, Some(3))
+        .await
+        .unwrap();
+
+    let stats = metrics::snapshot();
+    assert_eq!(stats.requests, server.requests() as u64);
+    assert_eq!(stats.requests, 3);
+    assert_eq!(stats.failures, 0);
+    assert_eq!(stats.bytes, 3 * FIXTURE.len() as u64);
+    assert_eq!(stats.latency["weatherapi"].requests, 3);
+
+    let block = metrics::render(&stats, Duration::from_millis(1500));
+    assert!(block.starts_with("stats: 3 requests (0 failed, 0 retries), 0 cache hits"));
+    assert!(block.contains("in 1.50s"));
+    assert!(block.contains("  weatherapi: 3 requests, avg "));
+}
+
+#[test]
+fn test_render_lists_every_provider() {
+    let stats = Metrics {
+        requests: 3,
+        failures: 1,
+        cache_hits: 2,
+        bytes: 2048,
+        latency: BTreeMap::from([
+            (
+                "openweather".to_string(),
+                Latency {
+                    requests: 1,
+                    total_ms: 90,
+                    max_ms: 90,
+                },
+            ),
+            (
+                "weatherapi".to_string(),
+                Latency {
+                    requests: 2,
+                    total_ms: 300,
+                    max_ms: 200,
+                },
+            ),
+        ]),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        metrics::render(&stats, Duration::from_millis(420)),
+        "stats: 3 requests (1 failed, 0 retries), 2 cache hits, 2.0 KiB in 0.42s\n\
+         \x20 openweather: 1 requests, avg 90ms, max 90ms\n\
+         \x20 weatherapi: 2 requests, avg 150ms, max 200ms\n"
+    );
+}
+
+#[test]
+fn test_format_bytes() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(2560), "2.5 KiB");
+    assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+}
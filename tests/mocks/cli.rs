@@ -0,0 +1,27 @@
+use std::path::Path;
+
+use assert_cmd::Command;
+
+/// The `wapp` binary with its config, cache and data below `dir` and
+/// logging off, asking WeatherAPI at `base_url` with the key `KEY`, e.g.
+/// an [`HttpServer`](super::http_server::HttpServer).
+pub fn wapp(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = Command::cargo_bin("wapp").unwrap();
+    cmd.current_dir(dir)
+        .env("WAPP_CONFIG", dir.join("config.json"))
+        .env("WAPP_CACHE_DIR", dir.join("cache"))
+        .env("WAPP_DATA_DIR", dir.join("data"))
+        .env("WAPP_LOG", "off")
+        .env("WEATHERAPI_KEY", "KEY")
+        .env("WEATHERAPI_BASE_URL", base_url);
+    cmd
+}
+
+/// [`wapp`] getting the weather of Kyiv from WeatherAPI, bypassing the
+/// response cache.
+pub fn wapp_get(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = wapp(dir, base_url);
+    cmd.args(["get", "--city", "Kyiv", "--provider", "weatherapi"])
+        .arg("--no-cache");
+    cmd
+}
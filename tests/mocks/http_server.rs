@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// One scripted HTTP response.
+#[derive(Clone)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(&'static str, &'static str)>,
+    pub body: &'static str,
+}
+
+impl Reply {
+    /// `200 OK` with a JSON body.
+    pub fn ok(body: &'static str) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body,
+        }
+    }
+}
+
+/// Local HTTP server answering requests with scripted responses.
+///
+/// Each request is counted and its head (request line and headers) kept,
+/// so tests can compare what a client reports with what the server
+/// actually saw.
+pub struct HttpServer {
+    pub base_url: String,
+    requests: Arc<AtomicUsize>,
+    received: Arc<Mutex<Vec<String>>>,
+}
+
+impl HttpServer {
+    /// Starts a server answering every request with the same JSON body.
+    pub async fn start(body: &'static str) -> Self {
+        Self::start_replies(vec![Reply::ok(body)]).await
+    }
+
+    /// Starts a server answering with `replies` in order; the last reply is
+    /// repeated once the others are used up.
+    pub async fn start_replies(replies: Vec<Reply>) -> Self {
+        Self::start_delayed(replies, std::time::Duration::ZERO).await
+    }
+
+    /// Like [`start_replies`](Self::start_replies), waiting `delay` before
+    /// each reply, e.g. to let a client time out.
+    pub async fn start_delayed(replies: Vec<Reply>, delay: std::time::Duration) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let (counter, log) = (requests.clone(), received.clone());
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let reply = replies[n.min(replies.len() - 1)].clone();
+
+                let mut request = [0u8; 4096];
+                let len = socket.read(&mut request).await.unwrap_or(0);
+                log.lock()
+                    .unwrap()
+                    .push(String::from_utf8_lossy(&request[..len]).into_owned());
+                tokio::time::sleep(delay).await;
+
+                let headers: String = reply
+                    .headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                    .collect();
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+                    reply.status,
+                    reply.body.len(),
+                    headers,
+                    reply.body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        Self {
+            base_url,
+            requests,
+            received,
+        }
+    }
+
+    /// Number of requests served so far.
+    pub fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    /// Heads of the requests served so far, lowercased for header lookups.
+    pub fn received(&self) -> Vec<String> {
+        self.received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|head| head.to_ascii_lowercase())
+            .collect()
+    }
+}
+
+/// Makes retries of failed requests wait a millisecond instead of seconds
+/// for the rest of the test binary.
+pub fn fast_retries() {
+    wapp::retry::use_retry_policy(wapp::retry::RetryPolicy {
+        base_delay: std::time::Duration::from_millis(1),
+        max_delay: std::time::Duration::from_millis(1),
+        ..Default::default()
+    });
+}
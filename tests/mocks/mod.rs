@@ -1 +1,8 @@
-pub mod mock_provider;
+#[allow(dead_code)]
+pub mod cli;
+#[allow(dead_code)]
+pub mod http_server;
+#[allow(dead_code)]
+pub mod providers;
+#[allow(dead_code)]
+pub mod scripted_provider;
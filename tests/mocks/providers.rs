@@ -0,0 +1,20 @@
+use wapp::providers::{OpenWeatherProvider, WeatherApiProvider};
+
+use super::http_server::fast_retries;
+
+/// WeatherAPI with the key `KEY`, sending its requests to `base_url`, e.g.
+/// that of an [`HttpServer`](super::http_server::HttpServer); failed
+/// requests are retried without waiting, see [`fast_retries`].
+pub fn weatherapi(base_url: &str) -> WeatherApiProvider {
+    fast_retries();
+    WeatherApiProvider::new("KEY").with_base_url(base_url)
+}
+
+/// OpenWeatherMap with the key `KEY`, sending its requests to `base_url`
+/// as [`weatherapi`] does; a `base_url` ending in `/data/2.5` sends One
+/// Call requests to `/data/3.0`, see
+/// [`resolve_endpoints`](wapp::providers::openweather::resolve_endpoints).
+pub fn openweather(base_url: &str) -> OpenWeatherProvider {
+    fast_retries();
+    OpenWeatherProvider::new("KEY").with_base_url(base_url)
+}
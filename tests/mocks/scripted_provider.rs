@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use wapp::location::Location;
+use wapp::providers::{ApiProvider, Capabilities, DataKind, STANDARD_KINDS};
+use wapp::request::DateWindow;
+use wapp::series::{DailySummary, SeriesPoint};
+use wapp::weather::Alert;
+
+/// Provider that replays a fixed sequence of responses, one per call.
+///
+/// `Err` entries are returned as errors; once the script is exhausted every
+/// further call fails. Every call is recorded as `(location, when, days)`.
+/// Response parsing (`forecast_dates`, `retain_dates`, `series`, `current`,
+/// `daily`, `alerts`) is delegated
+/// to the provider given to [`ScriptedProvider::parsing_as`], if any.
+pub struct ScriptedProvider {
+    script: Mutex<VecDeque<Result<String, String>>>,
+    calls: Mutex<Vec<(String, DataKind, Option<u32>)>>,
+    parser: Option<Box<dyn ApiProvider + Send + Sync>>,
+}
+
+impl ScriptedProvider {
+    pub fn new(script: Vec<Result<&str, &str>>) -> Self {
+        Self {
+            script: Mutex::new(
+                script
+                    .into_iter()
+                    .map(|r| r.map(String::from).map_err(String::from))
+                    .collect(),
+            ),
+            calls: Mutex::new(Vec::new()),
+            parser: None,
+        }
+    }
+
+    /// Parses responses the way `provider` does.
+    pub fn parsing_as(mut self, provider: impl ApiProvider + Send + Sync + 'static) -> Self {
+        self.parser = Some(Box::new(provider));
+        self
+    }
+
+    /// Returns the recorded calls in order.
+    pub fn calls(&self) -> Vec<(String, DataKind, Option<u32>)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ApiProvider for ScriptedProvider {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 3,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    fn forecast_dates(&self, body: &str) -> Option<Vec<NaiveDate>> {
+        self.parser.as_ref()?.forecast_dates(body)
+    }
+
+    fn retain_dates(&self, body: &str, window: DateWindow) -> Option<String> {
+        self.parser.as_ref()?.retain_dates(body, window)
+    }
+
+    fn series(&self, body: &str) -> Option<Vec<SeriesPoint>> {
+        self.parser.as_ref()?.series(body)
+    }
+
+    fn current(&self, body: &str) -> Option<SeriesPoint> {
+        self.parser.as_ref()?.current(body)
+    }
+
+    fn daily(&self, body: &str) -> Option<Vec<DailySummary>> {
+        self.parser.as_ref()?.daily(body)
+    }
+
+    fn alerts(&self, body: &str) -> Option<Vec<Alert>> {
+        self.parser.as_ref()?.alerts(body)
+    }
+
+    async fn get_data(
+        &self,
+        location: Location,
+        when: DataKind,
+        days: Option<u32>,
+    ) -> anyhow::Result<String> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((location.label(), when, days));
+
+        match self.script.lock().unwrap().pop_front() {
+            Some(Ok(body)) => Ok(body),
+            Some(Err(e)) => Err(anyhow::anyhow!(e)),
+            None => Err(anyhow::anyhow!("script exhausted")),
+        }
+    }
+}
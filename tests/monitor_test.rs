@@ -0,0 +1,363 @@
+mod mocks;
+
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use mocks::scripted_provider::ScriptedProvider;
+use tokio_util::sync::CancellationToken;
+use wapp::cli::{Cli, Commands};
+use wapp::monitor::{
+    backoff, poll, run, state_path, substitute, Event, MonitorOptions, MonitorState,
+};
+use wapp::providers::{ApiProvider, DataKind, WeatherApiProvider};
+use wapp::weather::Alert;
+
+/// Miami on 10 June 2024: a heat advisory until 23:00 UTC and a flash
+/// flood warning until 20:00 UTC.
+const WEATHERAPI_ALERTS: &str = include_str!("fixtures/weatherapi_alerts.json");
+
+fn utc(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+fn alerts() -> Vec<Alert> {
+    WeatherApiProvider::new("KEY")
+        .alerts(WEATHERAPI_ALERTS)
+        .unwrap()
+}
+
+fn events(alerts: &[Alert]) -> Vec<&str> {
+    alerts.iter().map(|a| a.event.as_str()).collect()
+}
+
+fn options(dir: &Path) -> MonitorOptions {
+    MonitorOptions {
+        location: "Miami".into(),
+        interval: Duration::from_secs(10),
+        on_alert: None,
+        when: None,
+        state: dir.join("state.json"),
+        once: false,
+    }
+}
+
+fn scripted(script: Vec<Result<&str, &str>>) -> ScriptedProvider {
+    ScriptedProvider::new(script).parsing_as(WeatherApiProvider::new("KEY"))
+}
+
+#[test]
+fn test_alerts_are_reported_once() {
+    let mut state = MonitorState::default();
+    let now = utc("2024-06-10T15:00:00Z");
+
+    let new = state.observe(&alerts(), now);
+    assert_eq!(events(&new), ["Heat Advisory", "Flash Flood Warning"]);
+    assert!(state.observe(&alerts(), now).is_empty());
+
+    // Only the warning is left in the feed, then a new one is issued
+    let warning = &alerts()[1..];
+    assert!(state.observe(warning, now).is_empty());
+    let mut reissued = alerts();
+    reissued[1].starts = Some(utc("2024-06-10T16:00:00Z"));
+    assert_eq!(
+        events(&state.observe(&reissued, now)),
+        ["Flash Flood Warning"]
+    );
+}
+
+#[test]
+fn test_alerts_are_forgotten_once_ended_and_gone() {
+    let mut state = MonitorState::default();
+    state.observe(&alerts(), utc("2024-06-10T15:00:00Z"));
+
+    // Dropped from the feed before its end: still remembered
+    state.observe(&alerts()[..1], utc("2024-06-10T16:00:00Z"));
+    assert_eq!(state.seen.len(), 2);
+    assert!(state
+        .observe(&alerts(), utc("2024-06-10T16:30:00Z"))
+        .is_empty());
+
+    // Ended and gone: forgotten; still in the feed: kept
+    state.observe(&alerts()[..1], utc("2024-06-10T21:00:00Z"));
+    assert_eq!(state.seen.len(), 1);
+    state.observe(&[], utc("2024-06-11T00:00:00Z"));
+    assert!(state.seen.is_empty());
+}
+
+#[test]
+fn test_state_survives_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("monitor").join("miami.json");
+
+    assert_eq!(MonitorState::load(&path).unwrap(), MonitorState::default());
+
+    let mut state = MonitorState::default();
+    state.observe(&alerts(), utc("2024-06-10T15:00:00Z"));
+    state.threshold = true;
+    state.save(&path).unwrap();
+
+    let mut restarted = MonitorState::load(&path).unwrap();
+    assert_eq!(restarted, state);
+    assert!(restarted
+        .observe(&alerts(), utc("2024-06-10T15:15:00Z"))
+        .is_empty());
+
+    fs::write(&path, "not json").unwrap();
+    let err = MonitorState::load(&path).unwrap_err();
+    assert!(err.to_string().contains("invalid monitor state"), "{}", err);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_placeholders_refer_to_environment_variables() {
+    assert_eq!(
+        substitute("notify-send {severity} {event}"),
+        r#"notify-send "$WAPP_SEVERITY" "$WAPP_EVENT""#
+    );
+    assert_eq!(
+        substitute("echo {starts}-{ends} {unknown} {"),
+        r#"echo "$WAPP_STARTS"-"$WAPP_ENDS" {unknown} {"#
+    );
+
+    let event = Event::alert("Miami", &alerts()[1]);
+    let vars = event.vars();
+    assert!(vars.contains(&("WAPP_CITY", "Miami")), "{:?}", vars);
+    assert!(vars.contains(&("WAPP_SEVERITY", "Severe")), "{:?}", vars);
+    assert!(
+        vars.contains(&("WAPP_STARTS", "2024-06-10T14:00:00Z")),
+        "{:?}",
+        vars
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_alert_text_cannot_run_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let hostile = Event {
+        headline: "it's $(touch pwned); touch pwned".into(),
+        ..Default::default()
+    };
+
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(substitute("printf %s {headline}"))
+        .envs(hostile.vars())
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.stdout, b"it's $(touch pwned); touch pwned");
+    assert!(!dir.path().join("pwned").exists());
+}
+
+#[test]
+fn test_backoff_doubles_up_to_an_hour() {
+    let interval = Duration::from_secs(300);
+    let delays: Vec<u64> = (0..6)
+        .map(|failures| backoff(interval, failures).as_secs())
+        .collect();
+    assert_eq!(delays, [300, 600, 1200, 2400, 3600, 3600]);
+
+    // An interval above the cap is kept
+    let daily = Duration::from_secs(86_400);
+    assert_eq!(backoff(daily, 3), daily);
+    assert_eq!(
+        backoff(Duration::from_secs(60), u32::MAX),
+        Duration::from_secs(3600)
+    );
+}
+
+#[tokio::test]
+async fn test_poll_reports_new_alerts_and_threshold_crossings() {
+    let dir = tempfile::tempdir().unwrap();
+    let provider = scripted(vec![
+        Ok(WEATHERAPI_ALERTS),
+        Ok(WEATHERAPI_ALERTS),
+        Ok("{\"current\":{\"temp_c\":20.0},\"alerts\":{\"alert\":[]}}"),
+        Ok(WEATHERAPI_ALERTS),
+    ]);
+    let opts = MonitorOptions {
+        when: Some("temp_c > 29".into()),
+        ..options(dir.path())
+    };
+    let mut state = MonitorState::default();
+    let now = utc("2024-06-10T15:00:00Z");
+
+    let first = poll(&provider, &opts, &mut state, now).await.unwrap();
+    let lines: Vec<String> = first.iter().map(Event::line).collect();
+    assert_eq!(
+        lines,
+        [
+            "Miami: [Moderate] Heat Advisory issued June 10 at 4:00AM EDT until June 10 \
+             at 7:00PM EDT by NWS Miami FL",
+            "Miami: [Severe] Flash Flood Warning issued June 10 at 10:00AM EDT until \
+             June 10 at 4:00PM EDT by NWS Miami FL",
+            "Miami: temp_c > 29 holds",
+        ]
+    );
+
+    // Still holding, then clear, then holding again
+    assert!(poll(&provider, &opts, &mut state, now)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(poll(&provider, &opts, &mut state, now)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(!state.threshold);
+    let again = poll(&provider, &opts, &mut state, now).await.unwrap();
+    assert_eq!(again, [Event::threshold("Miami", "temp_c > 29")]);
+
+    let calls = provider.calls();
+    assert_eq!(calls.len(), 4);
+    assert!(calls.iter().all(|(_, kind, _)| *kind == DataKind::Alerts));
+}
+
+#[tokio::test]
+async fn test_poll_needs_a_provider_with_alerts() {
+    let dir = tempfile::tempdir().unwrap();
+    let provider = ScriptedProvider::new(vec![Ok("{}")]);
+
+    let err = poll(
+        &provider,
+        &options(dir.path()),
+        &mut MonitorState::default(),
+        Utc::now(),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("does not report weather alerts"),
+        "{}",
+        err
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_once_runs_the_command_for_new_alerts_only() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("alerts.log");
+    let opts = MonitorOptions {
+        on_alert: Some(format!("echo {{event}} >> '{}'", log.display())),
+        once: true,
+        ..options(dir.path())
+    };
+    let provider = scripted(vec![Ok(WEATHERAPI_ALERTS), Ok(WEATHERAPI_ALERTS)]);
+    let shutdown = CancellationToken::new();
+
+    run(&provider, &opts, &shutdown).await.unwrap();
+    // A restart reads the state file and finds nothing new
+    run(&provider, &opts, &shutdown).await.unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        "Heat Advisory\nFlash Flood Warning\n"
+    );
+    assert_eq!(MonitorState::load(&opts.state).unwrap().seen.len(), 2);
+}
+
+#[tokio::test]
+async fn test_once_fails_when_the_poll_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = MonitorOptions {
+        once: true,
+        ..options(dir.path())
+    };
+    let provider = scripted(vec![Err("network down")]);
+
+    let err = run(&provider, &opts, &CancellationToken::new())
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "network down");
+    assert!(!opts.state.exists());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failing_polls_back_off() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = options(dir.path());
+    let provider = scripted(vec![Err("down"), Err("down"), Ok(WEATHERAPI_ALERTS)]);
+
+    // Polls at t=0 and t=20, the next one due at t=60
+    let shutdown = CancellationToken::new();
+    let trigger = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(50)).await;
+        trigger.cancel();
+    });
+    run(&provider, &opts, &shutdown).await.unwrap();
+
+    assert_eq!(provider.calls().len(), 2);
+    assert!(!opts.state.exists());
+}
+
+#[tokio::test]
+async fn test_invalid_threshold_is_rejected_up_front() {
+    let dir = tempfile::tempdir().unwrap();
+    let opts = MonitorOptions {
+        when: Some("temp_c >".into()),
+        ..options(dir.path())
+    };
+    let provider = scripted(vec![]);
+
+    let err = run(&provider, &opts, &CancellationToken::new())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("invalid --when"), "{}", err);
+    assert!(provider.calls().is_empty());
+}
+
+#[test]
+fn test_state_file_is_named_after_the_city() {
+    let data = Path::new("/data");
+    assert_eq!(
+        state_path(data, &"New York".into()),
+        Path::new("/data/monitor/new-york.json")
+    );
+    assert_eq!(
+        state_path(data, &"São Paulo, BR".into()),
+        Path::new("/data/monitor/são-paulo-br.json")
+    );
+}
+
+#[test]
+fn test_monitor_options_are_parsed() {
+    let cli = Cli::try_parse_from([
+        "wapp",
+        "monitor",
+        "--city",
+        "Miami",
+        "--interval",
+        "15m",
+        "--on-alert",
+        "notify-send {headline}",
+        "--once",
+    ])
+    .unwrap();
+    let Commands::Monitor {
+        interval,
+        on_alert,
+        once,
+        when,
+        state,
+        ..
+    } = cli.cmd
+    else {
+        panic!("not a monitor command");
+    };
+    assert_eq!(interval, 900);
+    assert_eq!(on_alert.as_deref(), Some("notify-send {headline}"));
+    assert!(once);
+    assert_eq!((when, state), (None, None));
+
+    for invalid in ["0", "15x", ""] {
+        let args = ["wapp", "monitor", "--city", "Miami", "--interval", invalid];
+        assert!(Cli::try_parse_from(args).is_err(), "{}", invalid);
+    }
+}
@@ -0,0 +1,191 @@
+mod mocks;
+
+use assert_cmd::Command;
+use mocks::http_server::fast_retries;
+use wapp::config::AppConfig;
+use wapp::error::WappError;
+use wapp::exit::{exit_code, NETWORK_EXIT_CODE};
+use wapp::providers::{
+    check_json, is_unavailable, EnvSource, FetchResponse, ProviderKind, ProviderOverrides,
+    EXCERPT_CHARS,
+};
+use wapp::Client;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const PORTAL: &str = "<!DOCTYPE html>\n<html>\n  <head><title>Hotel Wi-Fi</title></head>\n  <body>Accept the terms to continue</body>\n</html>\n";
+
+/// A server answering `/current.json` with `200`, `body`, and
+/// `content_type`.
+async fn serve(body: &'static str, content_type: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, content_type))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// The error of the current weather of Kyiv from WeatherAPI at `server`.
+async fn error_of(server: &MockServer) -> anyhow::Error {
+    fast_retries();
+    let overrides = ProviderOverrides {
+        env: EnvSource::from_vars([
+            ("WEATHERAPI_KEY", "KEY".to_string()),
+            ("WEATHERAPI_BASE_URL", server.uri()),
+        ]),
+        ..Default::default()
+    };
+    let config = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        ..Default::default()
+    };
+    Client::with_overrides(config, &overrides)
+        .unwrap()
+        .current("Kyiv")
+        .await
+        .unwrap_err()
+}
+
+fn response(status: u16, content_type: Option<&str>, body: &str) -> FetchResponse {
+    FetchResponse {
+        status,
+        last_modified: None,
+        etag: None,
+        retry_after: None,
+        content_type: content_type.map(String::from),
+        body: body.into(),
+    }
+}
+
+fn wapp_get(server: &MockServer, dir: &std::path::Path) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, &server.uri());
+    cmd.args(["get", "--city", "Kyiv", "--provider", "weatherapi"]);
+    cmd
+}
+
+#[tokio::test]
+async fn test_html_page_names_the_status_and_content_type() {
+    let server = serve(PORTAL, "text/html; charset=utf-8").await;
+
+    let err = error_of(&server).await;
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::NonJson { status: 200, provider, .. }) if provider == "weatherapi"
+    ));
+    assert_eq!(
+        err.to_string(),
+        "'weatherapi' returned a non-JSON response (status 200, text/html; charset=utf-8); \
+         are you behind a captive portal? The response begins: <!DOCTYPE html> <html> <head>\
+         <title>Hotel Wi-Fi</title></head> <body>Accept the terms to continue</body> </html>"
+    );
+    assert_eq!(exit_code(&err), Some(NETWORK_EXIT_CODE));
+    // Another provider would be answered by the same portal.
+    assert!(!is_unavailable(&err));
+}
+
+#[tokio::test]
+async fn test_empty_body() {
+    let server = serve("", "application/json").await;
+
+    let err = error_of(&server).await;
+    assert_eq!(
+        err.to_string(),
+        "'weatherapi' returned an empty response (status 200); are you behind a captive portal?"
+    );
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::NonJson { excerpt, .. }) if excerpt.is_empty()
+    ));
+
+    // Whitespace is as empty.
+    let err = check_json("wttr", &response(200, None, " \n\t")).unwrap_err();
+    assert!(err.to_string().contains("an empty response"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_json_of_an_unexpected_shape() {
+    let server = serve(r#"{"status":"ok","items":[]}"#, "application/json").await;
+
+    let err = error_of(&server).await;
+    assert!(WappError::of(&err).is_none(), "{:?}", err);
+    assert_eq!(err.to_string(), "cannot read 'now' data from 'weatherapi'");
+}
+
+#[test]
+fn test_json_passes_whatever_the_content_type() {
+    assert!(check_json("wttr", &response(200, Some("text/plain"), "{\"a\": 1}")).is_ok());
+    assert!(check_json("wttr", &response(200, None, "[1, 2]")).is_ok());
+
+    let err = check_json("wttr", &response(200, None, "Unknown location")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'wttr' returned a non-JSON response (status 200, no content type); are you behind a \
+         captive portal? The response begins: Unknown location"
+    );
+}
+
+#[test]
+fn test_excerpt_is_short_and_on_one_line() {
+    let body = format!("<html>\r\n\x07{}</html>", "x".repeat(500));
+    let err = check_json("weatherapi", &response(200, Some("text/html"), &body)).unwrap_err();
+
+    let Some(WappError::NonJson { excerpt, .. }) = WappError::of(&err) else {
+        panic!("expected NonJson, got {:?}", err);
+    };
+    assert_eq!(excerpt.chars().count(), EXCERPT_CHARS + 1);
+    assert!(excerpt.starts_with("<html> ?xxx"), "{}", excerpt);
+    assert!(excerpt.ends_with("x…"), "{}", excerpt);
+    assert!(!err.to_string().contains('\n'));
+}
+
+#[test]
+fn test_recordings_without_a_content_type_still_load() {
+    let recorded = r#"{"status":200,"last_modified":null,"retry_after":null,"body":"{}"}"#;
+    let response: FetchResponse = serde_json::from_str(recorded).unwrap();
+    assert_eq!(response.content_type, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_fails_but_raw_prints_the_page() {
+    let server = serve(PORTAL, "text/html").await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(&server, dir.path()).output().unwrap();
+    assert_eq!(output.status.code(), Some(NETWORK_EXIT_CODE));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("non-JSON response (status 200, text/html)"),
+        "{}",
+        stderr
+    );
+
+    let output = wapp_get(&server, dir.path())
+        .env_remove("WAPP_LOG")
+        .env_remove("RUST_LOG")
+        .arg("--raw")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim_end(),
+        PORTAL.trim_end()
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr
+            .matches("'weatherapi' returned a non-JSON response")
+            .count(),
+        1,
+        "{}",
+        stderr
+    );
+
+    // The page is not cached as the weather.
+    let output = wapp_get(&server, dir.path())
+        .arg("--offline")
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{:?}", output);
+}
@@ -0,0 +1,133 @@
+use clap::Parser;
+use wapp::cli::Cli;
+use wapp::monitor::Event;
+use wapp::notify::{DesktopNotification, Urgency};
+use wapp::output;
+use wapp::providers::{ApiProvider, WeatherApiProvider};
+use wapp::units::Units;
+use wapp::weather::{Severity, WeatherData};
+
+/// Miami on 10 June 2024: heavy rain, a moderate heat advisory and a
+/// severe flash flood warning.
+const WEATHERAPI_ALERTS: &str = include_str!("fixtures/weatherapi_alerts.json");
+
+fn read(kind: &str) -> WeatherData {
+    output::read(&WeatherApiProvider::new("KEY"), kind, WEATHERAPI_ALERTS).unwrap()
+}
+
+#[test]
+fn test_current_weather_is_a_normal_notification() {
+    let data = read("now");
+
+    let notification = DesktopNotification::for_weather("Miami", &data, Units::Metric).unwrap();
+    assert_eq!(
+        notification,
+        DesktopNotification {
+            summary: "Weather in Miami".into(),
+            body: "Heavy rain, 29°C".into(),
+            urgency: Urgency::Normal,
+        }
+    );
+
+    let imperial = DesktopNotification::for_weather("Miami", &data, Units::Imperial).unwrap();
+    assert_eq!(imperial.body, "Heavy rain, 85°F");
+}
+
+#[test]
+fn test_severe_alerts_are_critical() {
+    let data = read("alerts");
+
+    let notification = DesktopNotification::for_weather("Miami", &data, Units::Metric).unwrap();
+    assert_eq!(notification.summary, "2 weather alerts in Miami");
+    assert_eq!(
+        notification.body,
+        "Severe: Flash Flood Warning\nModerate: Heat Advisory"
+    );
+    assert_eq!(notification.urgency, Urgency::Critical);
+
+    let mut moderate = data.clone();
+    moderate.alerts.as_mut().unwrap().remove(0);
+    let notification = DesktopNotification::for_weather("Miami", &moderate, Units::Metric).unwrap();
+    assert_eq!(notification.summary, "Weather alert in Miami");
+    assert_eq!(notification.urgency, Urgency::Normal);
+}
+
+#[test]
+fn test_nothing_to_notify() {
+    assert_eq!(
+        DesktopNotification::for_weather("Miami", &WeatherData::default(), Units::Metric),
+        None
+    );
+}
+
+#[test]
+fn test_urgency_follows_severity() {
+    let urgencies: Vec<Urgency> = [
+        Severity::Unknown,
+        Severity::Minor,
+        Severity::Moderate,
+        Severity::Severe,
+        Severity::Extreme,
+    ]
+    .into_iter()
+    .map(Urgency::for_severity)
+    .collect();
+    assert_eq!(
+        urgencies,
+        [
+            Urgency::Normal,
+            Urgency::Normal,
+            Urgency::Normal,
+            Urgency::Critical,
+            Urgency::Critical
+        ]
+    );
+}
+
+#[test]
+fn test_monitor_events_are_notified() {
+    let alerts = WeatherApiProvider::new("KEY")
+        .alerts(WEATHERAPI_ALERTS)
+        .unwrap();
+
+    let warning = Event::alert("Miami", &alerts[1]).notification();
+    assert_eq!(warning.summary, "Severe alert in Miami");
+    assert!(warning.body.starts_with("Flash Flood Warning issued"));
+    assert_eq!(warning.urgency, Urgency::Critical);
+
+    let threshold = Event::threshold("Miami", "temp_c > 29").notification();
+    assert_eq!(
+        threshold,
+        DesktopNotification {
+            summary: "Weather in Miami".into(),
+            body: "temp_c > 29 holds".into(),
+            urgency: Urgency::Normal,
+        }
+    );
+}
+
+#[cfg(not(feature = "notifications"))]
+#[test]
+fn test_builds_without_the_feature_explain_why() {
+    let notification =
+        DesktopNotification::for_weather("Miami", &read("now"), Units::Metric).unwrap();
+
+    let err = wapp::notify::send(&notification).unwrap_err();
+    assert!(
+        err.to_string().contains("`notifications` feature"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_notify_is_a_global_flag() {
+    let cli = Cli::try_parse_from(["wapp", "get", "--city", "Miami", "--notify"]).unwrap();
+    assert!(cli.notify);
+
+    let cli = Cli::try_parse_from(["wapp", "--notify", "monitor", "--city", "Miami"]).unwrap();
+    assert!(cli.notify);
+
+    let cli = Cli::try_parse_from(["wapp", "now", "Miami"]).unwrap();
+    assert!(!cli.notify);
+}
@@ -0,0 +1,225 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use mocks::http_server::HttpServer;
+use wapp::location::{Location, LocationInput};
+use wapp::providers::openmeteo::Place;
+use wapp::providers::{ApiProvider, DataKind, OpenMeteoProvider};
+
+const GEOCODING: &str = r#"{"results":[{"id":703448,"name":"Kyiv","latitude":50.45466,"longitude":30.5238,"country_code":"UA"}],"generationtime_ms":0.5}"#;
+
+const NOT_FOUND: &str = r#"{"generationtime_ms":0.3}"#;
+
+const CURRENT: &str = r#"{"latitude":50.45,"longitude":30.52,"current_weather":{"time":1714557600,"temperature":14.2,"windspeed":11.5,"winddirection":200,"weathercode":2}}"#;
+
+const DAILY: &str = r#"{"latitude":50.45,"longitude":30.52,"daily":{"time":["2024-05-01","2024-05-02"],"temperature_2m_max":[18.1,20.4],"temperature_2m_min":[7.3,9.0],"precipitation_sum":[0.0,2.6],"weather_code":[3,61]}}"#;
+
+const HOURLY: &str = r#"{"hourly":{"time":[1714600800,1714604400,1714608000],"temperature_2m":[9.0,8.5,8.1],"apparent_temperature":[7.0,6.4,6.0],"relative_humidity_2m":[80,82,85],"surface_pressure":[1001.2,1001.0,1000.8],"wind_speed_10m":[10.1,9.7,9.0],"precipitation":[0.0,0.1,0.0],"cloud_cover":[90,95,100],"weather_code":[3,51,3]}}"#;
+
+fn provider(base_url: &str, geocoding_url: &str) -> OpenMeteoProvider {
+    OpenMeteoProvider {
+        base_url: base_url.into(),
+        geocoding_url: geocoding_url.into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+fn kyiv() -> Place {
+    Place {
+        name: "Kyiv".into(),
+        latitude: 50.45466,
+        longitude: 30.5238,
+    }
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+#[test]
+fn test_geocoding_url_narrows_by_country() {
+    let p = provider("http://f", "http://g");
+
+    assert_eq!(
+        p.geocoding_url(&LocationInput::parse("São Paulo, BR")),
+        "http://g/search?name=S%C3%A3o%20Paulo&count=5&format=json&countryCode=BR"
+    );
+}
+
+#[test]
+fn test_forecast_urls() {
+    let p = provider("http://f", "http://g");
+
+    assert_eq!(
+        p.build_url(&kyiv(), DataKind::Now, None).unwrap(),
+        "http://f/forecast?latitude=50.45466&longitude=30.5238&current_weather=true&timeformat=unixtime"
+    );
+    assert!(p
+        .build_url(&kyiv(), DataKind::Forecast, Some(7))
+        .unwrap()
+        .ends_with("&timezone=auto&forecast_days=7"));
+    assert!(p
+        .build_url(&kyiv(), DataKind::Tomorrow, None)
+        .unwrap()
+        .ends_with("&forecast_days=2"));
+    assert!(p
+        .build_url(&kyiv(), DataKind::Hourly, None)
+        .unwrap()
+        .ends_with("&timeformat=unixtime&forecast_days=1"));
+    assert!(p
+        .build_url(&kyiv(), "2024-01-15".parse().unwrap(), None)
+        .is_err());
+}
+
+#[test]
+fn test_unknown_city_is_a_clear_error() {
+    let err =
+        OpenMeteoProvider::parse_place(&LocationInput::parse("Atlantis"), NOT_FOUND).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "city 'Atlantis' was not found by Open-Meteo"
+    );
+}
+
+#[test]
+fn test_geocoding_error_reason_is_reported() {
+    let body = r#"{"error":true,"reason":"Parameter count must be between 1 and 100."}"#;
+    let err = OpenMeteoProvider::parse_place(&LocationInput::parse("Kyiv"), body).unwrap_err();
+
+    assert!(err.to_string().contains("Parameter count"));
+}
+
+#[test]
+fn test_parse_place() {
+    let place = OpenMeteoProvider::parse_place(&LocationInput::parse("kyiv"), GEOCODING).unwrap();
+
+    assert_eq!(place, kyiv());
+}
+
+#[test]
+fn test_current_and_daily_views() {
+    let p = provider("http://f", "http://g");
+
+    let now = p.current(CURRENT).unwrap();
+    assert_eq!(now.temp_c, Some(14.2));
+    assert_eq!(now.wind_kph, Some(11.5));
+    assert_eq!(now.condition.as_deref(), Some("Partly cloudy"));
+
+    let days = p.daily(DAILY).unwrap();
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[1].date, date(2));
+    assert_eq!(days[1].min_c, Some(9.0));
+    assert_eq!(days[1].precip_mm, Some(2.6));
+    assert_eq!(days[1].condition.as_deref(), Some("Rain"));
+}
+
+#[test]
+fn test_hourly_series_and_dates() {
+    let p = provider("http://f", "http://g");
+
+    let points = p.series(HOURLY).unwrap();
+    assert_eq!(points.len(), 3);
+    assert_eq!(points[1].feels_like_c, Some(6.4));
+    assert_eq!(points[1].condition.as_deref(), Some("Drizzle"));
+
+    // 2024-05-01T22:00Z, 23:00Z, 2024-05-02T00:00Z.
+    assert_eq!(p.forecast_dates(HOURLY).unwrap(), vec![date(1), date(2)]);
+}
+
+#[test]
+fn test_retain_dates_filters_every_daily_array() {
+    let p = provider("http://f", "http://g");
+    let window = wapp::request::DateWindow {
+        from: date(2),
+        to: date(2),
+    };
+
+    let trimmed = p.retain_dates(DAILY, window).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&trimmed).unwrap();
+
+    assert_eq!(json["daily"]["time"], serde_json::json!(["2024-05-02"]));
+    assert_eq!(
+        json["daily"]["temperature_2m_max"],
+        serde_json::json!([20.4])
+    );
+    assert_eq!(json["daily"]["weather_code"], serde_json::json!([61]));
+}
+
+#[tokio::test]
+async fn test_get_data_geocodes_then_fetches() {
+    let geocoding = HttpServer::start(GEOCODING).await;
+    let forecast = HttpServer::start(CURRENT).await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    let body = p
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    assert_eq!(body, CURRENT);
+    assert_eq!((geocoding.requests(), forecast.requests()), (1, 1));
+}
+
+#[tokio::test]
+async fn test_get_data_stops_when_city_is_not_found() {
+    let geocoding = HttpServer::start(NOT_FOUND).await;
+    let forecast = HttpServer::start(CURRENT).await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    let err = p
+        .get_data(Location::from("Atlantis"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("not found"));
+    assert_eq!(forecast.requests(), 0);
+}
+
+#[tokio::test]
+async fn test_get_data_with_coordinates_skips_geocoding() {
+    let geocoding = HttpServer::start(GEOCODING).await;
+    let forecast = HttpServer::start(CURRENT).await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    let body = p
+        .get_data(Location::coords(50.45, 30.52).unwrap(), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    assert_eq!(body, CURRENT);
+    assert_eq!((geocoding.requests(), forecast.requests()), (0, 1));
+    assert!(forecast.received()[0].contains("latitude=50.45&longitude=30.52"));
+}
+
+#[tokio::test]
+async fn test_get_data_rejects_postal_and_airport_codes() {
+    let geocoding = HttpServer::start(GEOCODING).await;
+    let forecast = HttpServer::start(CURRENT).await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    for location in [
+        Location::zip("10001").unwrap(),
+        Location::iata("LHR").unwrap(),
+    ] {
+        let err = p.get_data(location, DataKind::Now, None).await.unwrap_err();
+        assert!(err.to_string().contains("not supported by this provider"));
+    }
+    assert_eq!((geocoding.requests(), forecast.requests()), (0, 0));
+}
+
+#[tokio::test]
+async fn test_tomorrow_keeps_second_day() {
+    let geocoding = HttpServer::start(GEOCODING).await;
+    let forecast = HttpServer::start(DAILY).await;
+    let p = provider(&forecast.base_url, &geocoding.base_url);
+
+    let body = p
+        .get_data(Location::from("Kyiv"), DataKind::Tomorrow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(p.forecast_dates(&body).unwrap(), vec![date(2)]);
+}
@@ -0,0 +1,577 @@
+mod mocks;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use mocks::http_server::{HttpServer, Reply};
+use wapp::error::WappError;
+use wapp::location::Location;
+use wapp::providers::openweather::OpenWeatherApi;
+use wapp::providers::{validate_kind, ApiProvider, DataKind, OpenWeatherProvider};
+use wapp::weather::Severity;
+
+fn provider(base_url: Option<&str>) -> OpenWeatherProvider {
+    match base_url {
+        Some(base_url) => mocks::providers::openweather(base_url),
+        None => OpenWeatherProvider::new("KEY"),
+    }
+}
+
+#[test]
+fn test_default_endpoints_use_versioned_paths() {
+    let p = provider(None);
+
+    assert_eq!(p.base_url, "https://api.openweathermap.org/data/2.5");
+    assert_eq!(p.onecall_url, "https://api.openweathermap.org/data/3.0");
+}
+
+#[test]
+fn test_default_now_url() {
+    let url = provider(None)
+        .build_url(&Location::from("London"), DataKind::Now, None)
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "https://api.openweathermap.org/data/2.5/weather?q=London&appid=KEY"
+    );
+}
+
+#[test]
+fn test_default_forecast_url() {
+    let url = provider(None)
+        .build_url(&Location::from("London"), DataKind::Forecast, None)
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "https://api.openweathermap.org/data/2.5/forecast?q=London&appid=KEY"
+    );
+}
+
+#[test]
+fn test_default_tomorrow_url() {
+    let url = provider(None)
+        .build_url(&Location::from("London"), DataKind::Tomorrow, None)
+        .unwrap();
+
+    assert_eq!(
+        url,
+        "https://api.openweathermap.org/data/2.5/forecast?q=London&appid=KEY"
+    );
+}
+
+#[test]
+fn test_forecast_days_become_slot_count() {
+    let url = provider(None)
+        .build_url(&Location::from("London"), DataKind::Forecast, Some(2))
+        .unwrap();
+
+    assert!(url.ends_with("&cnt=16"), "{url}");
+}
+
+#[test]
+fn test_old_3_0_override_is_rewritten() {
+    let p = provider(Some("https://api.openweathermap.org/data/3.0"));
+
+    assert_eq!(p.base_url, "https://api.openweathermap.org/data/2.5");
+    assert_eq!(p.onecall_url, "https://api.openweathermap.org/data/3.0");
+}
+
+#[test]
+fn test_readme_override_reaches_versioned_endpoints() {
+    let readme = include_str!("../README.md");
+    let base_url = readme
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("export OPENWEATHER_BASE_URL="))
+        .unwrap()
+        .trim_matches('"');
+    let p = provider(Some(base_url));
+
+    assert_eq!(p.base_url, "https://api.openweathermap.org/data/2.5");
+    assert_eq!(p.onecall_url, "https://api.openweathermap.org/data/3.0");
+}
+
+#[test]
+fn test_2_5_override_with_trailing_slash() {
+    let p = provider(Some("https://proxy.example.com/data/2.5/"));
+
+    assert_eq!(p.base_url, "https://proxy.example.com/data/2.5");
+    assert_eq!(p.onecall_url, "https://proxy.example.com/data/3.0");
+}
+
+#[test]
+fn test_custom_override_is_used_verbatim() {
+    let p = provider(Some("http://127.0.0.1:8080/owm"));
+    let url = p
+        .build_url(&Location::from("Oslo"), DataKind::Now, None)
+        .unwrap();
+
+    assert_eq!(url, "http://127.0.0.1:8080/owm/weather?q=Oslo&appid=KEY");
+}
+
+#[test]
+fn test_unsupported_kind_is_rejected() {
+    assert!(provider(None)
+        .build_url(&Location::from("Oslo"), "2024-01-15".parse().unwrap(), None)
+        .is_err());
+}
+
+const FORECAST: &str = include_str!("fixtures/openweather_forecast.json");
+
+/// The fixture's forecast for London (UTC+1), moved to `offset` seconds.
+fn forecast_at(offset: i64) -> String {
+    let mut json: serde_json::Value = serde_json::from_str(FORECAST).unwrap();
+    json["city"]["timezone"] = offset.into();
+    json.to_string()
+}
+
+/// The `dt_txt` (UTC) of every entry in `body`.
+fn entry_times(body: &str) -> Vec<String> {
+    let json: serde_json::Value = serde_json::from_str(body).unwrap();
+    json["list"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["dt_txt"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn utc(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_tomorrow_keeps_only_the_next_day() {
+    let p = provider(None);
+    let body = p
+        .keep_tomorrow(FORECAST, utc("2024-06-10T12:00:00Z"))
+        .unwrap();
+
+    let times = entry_times(&body);
+    assert_eq!(times.len(), 8);
+    assert_eq!(times[0], "2024-06-11 00:00:00");
+    assert_eq!(times[7], "2024-06-11 21:00:00");
+
+    let days = p.daily(&body).unwrap();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2024, 6, 11).unwrap());
+}
+
+#[test]
+fn test_tomorrow_follows_the_city_clock_around_midnight() {
+    let p = provider(None);
+
+    // 23:30 in London is still the 10th...
+    let body = p
+        .keep_tomorrow(FORECAST, utc("2024-06-10T22:30:00Z"))
+        .unwrap();
+    assert_eq!(entry_times(&body)[0], "2024-06-11 00:00:00");
+
+    // ...and 00:30 is already the 11th, though UTC is not.
+    let body = p
+        .keep_tomorrow(FORECAST, utc("2024-06-10T23:30:00Z"))
+        .unwrap();
+    assert_eq!(entry_times(&body)[0], "2024-06-12 00:00:00");
+}
+
+#[test]
+fn test_tomorrow_of_a_far_away_city() {
+    let p = provider(None);
+    // Tokyo (UTC+9) is on the 11th at 16:00 UTC, so tomorrow is the 12th,
+    // which starts at 15:00 UTC on the 11th.
+    let body = p
+        .keep_tomorrow(&forecast_at(9 * 3600), utc("2024-06-10T16:00:00Z"))
+        .unwrap();
+
+    let times = entry_times(&body);
+    assert_eq!(times.first().unwrap(), "2024-06-11 15:00:00");
+    assert_eq!(times.last().unwrap(), "2024-06-12 12:00:00");
+
+    let dates = p.forecast_dates(&body).unwrap();
+    assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2024, 6, 12).unwrap()]);
+}
+
+/// Rounds to two decimals, for sums and means of converted values.
+fn round2(value: Option<f64>) -> Option<f64> {
+    value.map(|v| (v * 100.0).round() / 100.0)
+}
+
+#[test]
+fn test_forecast_slots_become_local_days() {
+    let p = provider(None).with_units("metric");
+    let days = p.daily(FORECAST).unwrap();
+
+    let dates: Vec<String> = days.iter().map(|day| day.date.to_string()).collect();
+    assert_eq!(
+        dates,
+        [
+            "2024-06-10",
+            "2024-06-11",
+            "2024-06-12",
+            "2024-06-13",
+            "2024-06-14",
+            "2024-06-15"
+        ]
+    );
+    // The list starts at 13:00 and ends at 10:00 London time.
+    let partial: Vec<bool> = days.iter().map(|day| day.partial).collect();
+    assert_eq!(partial, [true, false, false, false, false, true]);
+
+    let first = &days[0];
+    assert_eq!((first.min_c, first.max_c), (Some(15.0), Some(20.0)));
+    assert_eq!(first.precip_mm, Some(0.0));
+    assert_eq!(round2(first.avg_wind_kph), Some(15.12));
+    assert_eq!(round2(first.max_wind_kph), Some(19.44));
+    // Few and scattered clouds are both partly cloudy, which ties with the
+    // two clear steps and is the worse weather.
+    assert_eq!(first.condition.as_deref(), Some("few clouds"));
+
+    // Broken and overcast clouds, twice each, are four cloudy steps against
+    // two of light rain; the broken clouds come first.
+    let second = &days[1];
+    assert_eq!((second.min_c, second.max_c), (Some(10.0), Some(20.7)));
+    assert_eq!(round2(second.precip_mm), Some(0.84));
+    assert_eq!(round2(second.avg_wind_kph), Some(16.2));
+    assert_eq!(second.condition.as_deref(), Some("broken clouds"));
+
+    // Light and moderate rain tie as rain; the earlier one wins.
+    assert_eq!(days[2].condition.as_deref(), Some("light rain"));
+    assert_eq!(round2(days[2].precip_mm), Some(3.54));
+}
+
+#[test]
+fn test_forecast_days_follow_the_city_clock() {
+    let p = provider(None).with_units("metric");
+
+    // In Tokyo (UTC+9) the 12:00 UTC slot is already 21:00, so the first
+    // day has a single entry and the last one seven.
+    let days = p.daily(&forecast_at(9 * 3600)).unwrap();
+    assert_eq!(days.first().unwrap().date.to_string(), "2024-06-10");
+    assert_eq!(days.first().unwrap().max_c, Some(18.54));
+    assert_eq!(days.last().unwrap().date.to_string(), "2024-06-15");
+    assert!(days.first().unwrap().partial && days.last().unwrap().partial);
+    assert!(days[1..days.len() - 1].iter().all(|day| !day.partial));
+
+    // Tomorrow, asked at noon UTC, is a whole day.
+    let body = p
+        .keep_tomorrow(FORECAST, utc("2024-06-10T12:00:00Z"))
+        .unwrap();
+    let days = p.daily(&body).unwrap();
+    assert_eq!(days.len(), 1);
+    assert!(!days[0].partial);
+}
+
+const ONECALL: &str = include_str!("fixtures/openweather_onecall.json");
+
+const KYIV: &str = r#"[{"name":"Kyiv","lat":50.4501,"lon":30.5234,"country":"UA"}]"#;
+
+/// A One Call provider in metric units below `base_url`.
+fn onecall(base_url: Option<&str>) -> OpenWeatherProvider {
+    provider(base_url)
+        .with_api(OpenWeatherApi::OneCall)
+        .with_units("metric")
+}
+
+fn kyiv() -> Location {
+    Location::coords(50.4501, 30.5234).unwrap()
+}
+
+#[test]
+fn test_api_setting_parses() {
+    assert_eq!(
+        "onecall".parse::<OpenWeatherApi>().unwrap(),
+        OpenWeatherApi::OneCall
+    );
+    assert_eq!(
+        "2.5".parse::<OpenWeatherApi>().unwrap(),
+        OpenWeatherApi::Classic
+    );
+    assert_eq!(OpenWeatherApi::default(), OpenWeatherApi::Classic);
+
+    let err = "4.0".parse::<OpenWeatherApi>().unwrap_err().to_string();
+    assert!(err.contains("valid: 2.5, onecall"), "{err}");
+}
+
+#[test]
+fn test_onecall_excludes_what_the_kind_does_not_need() {
+    let p = onecall(None);
+    let url = |kind| p.build_request(&kyiv(), kind, None).unwrap().url;
+
+    assert_eq!(
+        url(DataKind::Now),
+        "https://api.openweathermap.org/data/3.0/onecall?lat=50.4501&lon=30.5234\
+         &exclude=minutely,hourly,daily,alerts&appid=KEY&units=metric"
+    );
+    assert!(url(DataKind::Forecast).contains("&exclude=current,minutely,hourly,alerts&"));
+    assert!(url(DataKind::Tomorrow).contains("&exclude=current,minutely,hourly,alerts&"));
+    assert!(url(DataKind::Hourly).contains("&exclude=current,minutely,daily,alerts&"));
+    assert!(url(DataKind::Alerts).contains("&exclude=current,minutely,hourly,daily&"));
+}
+
+#[test]
+fn test_alerts_need_onecall() {
+    assert!(validate_kind(&onecall(None), DataKind::Alerts).is_ok());
+
+    let err = validate_kind(&provider(None), DataKind::Alerts)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.starts_with("data kind 'alerts' is not supported by provider 'openweather'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_onecall_alerts_are_normalized() {
+    let body = r#"{
+        "lat": 50.4501, "lon": 30.5234, "timezone": "Europe/Kyiv", "timezone_offset": 10800,
+        "alerts": [{
+            "sender_name": "Ukrainian Hydrometeorological Center",
+            "event": "Strong wind",
+            "start": 1718002800,
+            "end": 1718049600,
+            "description": "Gusts of 15-20 m/s.",
+            "tags": ["Wind"]
+        }]
+    }"#;
+
+    let alerts = onecall(None).alerts(body).unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].event, "Strong wind");
+    assert_eq!(alerts[0].severity, Severity::Unknown);
+    assert_eq!(alerts[0].headline, None);
+    assert_eq!(
+        alerts[0].starts,
+        Some(DateTime::from_timestamp(1718002800, 0).unwrap())
+    );
+    assert_eq!(
+        alerts[0].description.as_deref(),
+        Some("Gusts of 15-20 m/s.")
+    );
+
+    // One Call leaves the block out when nothing is in effect.
+    let quiet = r#"{"lat": 50.45, "lon": 30.52, "timezone_offset": 10800}"#;
+    assert_eq!(onecall(None).alerts(quiet), Some(Vec::new()));
+    assert_eq!(onecall(None).alerts(r#"{"cod": 401}"#), None);
+}
+
+#[test]
+fn test_onecall_plans_the_city_lookup_first() {
+    let planned = onecall(None)
+        .build_request(&Location::from("Kyiv"), DataKind::Now, None)
+        .unwrap();
+
+    assert!(planned.lookup);
+    assert_eq!(
+        planned.url,
+        "https://api.openweathermap.org/geo/1.0/direct?q=Kyiv&limit=5&appid=KEY"
+    );
+}
+
+#[test]
+fn test_onecall_response_is_read() {
+    let p = onecall(None);
+
+    let data = p.weather(ONECALL).unwrap();
+    assert_eq!(data.temp_c, Some(22.4));
+    assert_eq!(data.wind_degree, Some(320.0));
+    assert_eq!(data.condition.as_deref(), Some("scattered clouds"));
+    assert_eq!(data.forecast.len(), 3);
+    assert_eq!(data.forecast[1].max_c, Some(27.3));
+    assert_eq!(data.forecast[2].precip_mm, Some(6.2));
+
+    let series = p.series(ONECALL).unwrap();
+    assert_eq!(series.len(), 4);
+    assert_eq!(series[1].precip_mm, Some(0.4));
+    assert_eq!(series[1].precip_chance, Some(35.0));
+}
+
+#[test]
+fn test_onecall_dates_are_local() {
+    let p = onecall(None);
+    let body = p
+        .keep_tomorrow(ONECALL, utc("2024-06-10T12:00:00Z"))
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+
+    // 21:00 UTC on the 10th is already the 11th in Kyiv (UTC+3).
+    assert_eq!(json["daily"].as_array().unwrap().len(), 1);
+    assert_eq!(json["hourly"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        p.forecast_dates(&body).unwrap(),
+        vec![NaiveDate::from_ymd_opt(2024, 6, 11).unwrap()]
+    );
+}
+
+#[tokio::test]
+async fn test_onecall_geocodes_the_city_first() {
+    let server = HttpServer::start_replies(vec![Reply::ok(KYIV), Reply::ok(ONECALL)]).await;
+
+    let body = onecall(Some(&server.base_url))
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    assert_eq!(body, ONECALL);
+    let received = server.received();
+    assert!(
+        received[0].starts_with("get /geo/1.0/direct?q=kyiv&limit=5&"),
+        "{}",
+        received[0]
+    );
+    assert!(
+        received[1].starts_with("get /onecall?lat=50.4501&lon=30.5234&exclude="),
+        "{}",
+        received[1]
+    );
+}
+
+#[tokio::test]
+async fn test_air_quality_geocodes_the_city_under_either_api() {
+    let air = include_str!("fixtures/openweather_air_pollution.json");
+
+    for p in [provider, onecall] {
+        let server = HttpServer::start_replies(vec![Reply::ok(KYIV), Reply::ok(air)]).await;
+
+        let body = p(Some(&server.base_url))
+            .get_data(Location::from("Kyiv"), DataKind::Air, None)
+            .await
+            .unwrap();
+
+        assert_eq!(body, air);
+        let received = server.received();
+        assert!(received[0].starts_with("get /geo/1.0/direct?q=kyiv&limit=5&"));
+        assert!(
+            received[1].starts_with("get /air_pollution?lat=50.4501&lon=30.5234&appid=key "),
+            "{}",
+            received[1]
+        );
+    }
+}
+
+#[test]
+fn test_air_quality_plans_the_city_lookup_first() {
+    let planned = provider(None)
+        .build_request(&Location::from("Kyiv"), DataKind::Air, None)
+        .unwrap();
+    assert!(planned.lookup);
+
+    let planned = provider(None)
+        .build_request(&kyiv(), DataKind::Air, None)
+        .unwrap();
+    assert_eq!(
+        planned.url,
+        "https://api.openweathermap.org/data/2.5/air_pollution?lat=50.4501&lon=30.5234&appid=KEY"
+    );
+}
+
+#[tokio::test]
+async fn test_onecall_unknown_city_is_not_found() {
+    let server = HttpServer::start("[]").await;
+
+    let err = onecall(Some(&server.base_url))
+        .get_data(Location::from("Atlantis"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err.downcast_ref(), Some(WappError::CityNotFound { .. })),
+        "{err:#}"
+    );
+    assert_eq!(err.to_string(), "City 'Atlantis' not found");
+    assert_eq!(server.requests(), 1);
+}
+
+/// One Call `daily` of two days in Kyiv, with the moon of the first.
+const ONECALL_ASTRO: &str = r#"{
+    "lat": 50.4501, "lon": 30.5234, "timezone": "Europe/Kyiv", "timezone_offset": 10800,
+    "daily": [
+        {"dt": 1718010000, "sunrise": 1717983960, "sunset": 1718043060,
+         "moonrise": 1718015820, "moonset": 0, "moon_phase": 0.14},
+        {"dt": 1718096400, "sunrise": 1718070360, "sunset": 1718129520,
+         "moonrise": 1718106000, "moonset": 1718060000, "moon_phase": 0.17}
+    ]
+}"#;
+
+fn clock(s: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(s, "%H:%M").ok()
+}
+
+#[test]
+fn test_onecall_astro_is_read_in_local_time() {
+    let astro = onecall(None).astronomy(ONECALL_ASTRO).unwrap();
+
+    assert_eq!(astro.date, NaiveDate::from_ymd_opt(2024, 6, 10));
+    assert_eq!(astro.timezone.as_deref(), Some("Europe/Kyiv"));
+    assert_eq!(astro.sunrise, clock("04:46"));
+    assert_eq!(astro.sunset, clock("21:11"));
+    assert_eq!(astro.day_length_min, Some(985));
+    assert_eq!(astro.moonrise, clock("13:37"));
+    assert_eq!(astro.moonset, None);
+    assert_eq!(astro.moon_phase.as_deref(), Some("Waxing Crescent"));
+    assert_eq!(astro.moon_illumination, Some(18.0));
+}
+
+#[test]
+fn test_classic_astro_has_the_sun_of_today() {
+    let body = r#"{"dt": 1718010000, "timezone": 10800, "name": "Kyiv",
+        "sys": {"country": "UA", "sunrise": 1717983960, "sunset": 1718043060}}"#;
+
+    let astro = provider(None).astronomy(body).unwrap();
+    assert_eq!(astro.date, NaiveDate::from_ymd_opt(2024, 6, 10));
+    assert_eq!(astro.timezone.as_deref(), Some("UTC+03:00"));
+    assert_eq!(astro.sunrise, clock("04:46"));
+    assert_eq!(astro.sunset, clock("21:11"));
+    assert_eq!(astro.moonrise, None);
+    assert_eq!(astro.moon_phase, None);
+
+    let url = provider(None)
+        .build_url(&Location::from("Kyiv"), DataKind::Astro(None), None)
+        .unwrap();
+    assert!(url.contains("/data/2.5/weather?q=Kyiv&"), "{url}");
+}
+
+#[test]
+fn test_astro_of_a_day_needs_onecall() {
+    let day = DataKind::Astro(NaiveDate::from_ymd_opt(2024, 6, 11));
+
+    let err = provider(None)
+        .build_request(&kyiv(), day, None)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("OPENWEATHER_API=onecall"), "{err}");
+
+    let url = onecall(None).build_request(&kyiv(), day, None).unwrap().url;
+    assert!(
+        url.contains("&exclude=current,minutely,hourly,alerts&"),
+        "{url}"
+    );
+}
+
+#[tokio::test]
+async fn test_onecall_astro_keeps_the_asked_day() {
+    let server = HttpServer::start(ONECALL_ASTRO).await;
+    let p = onecall(Some(&server.base_url));
+
+    let body = p
+        .get_data(
+            kyiv(),
+            DataKind::Astro(NaiveDate::from_ymd_opt(2024, 6, 11)),
+            None,
+        )
+        .await
+        .unwrap();
+    let astro = p.astronomy(&body).unwrap();
+    assert_eq!(astro.date, NaiveDate::from_ymd_opt(2024, 6, 11));
+    assert_eq!(astro.sunset, clock("21:12"));
+
+    let err = p
+        .get_data(
+            kyiv(),
+            DataKind::Astro(NaiveDate::from_ymd_opt(2024, 7, 1)),
+            None,
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("next 8 days"), "{err}");
+}
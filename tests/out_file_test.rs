@@ -0,0 +1,164 @@
+mod mocks;
+
+use std::fs;
+
+use chrono::{DateTime, Utc};
+use mocks::cli::wapp_get;
+use mocks::http_server::HttpServer;
+use wapp::output::file::{json_line, write, Destination};
+use wapp::output::OutputFormat;
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+
+fn utc(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_json_runs_accumulate_as_lines() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("weather.jsonl");
+
+    for _ in 0..2 {
+        let output = wapp_get(dir.path(), &server.base_url)
+            .args(["--output", "json", "--out", "weather.jsonl", "--append"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        assert!(output.stdout.is_empty());
+    }
+
+    let contents = fs::read_to_string(&log).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", contents);
+    for line in lines {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(record["temp_c"].is_number(), "{}", line);
+        let timestamp = record["timestamp"].as_str().unwrap();
+        assert!(timestamp.parse::<DateTime<Utc>>().is_ok(), "{}", timestamp);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_csv_runs_share_one_header() {
+    let server = HttpServer::start(WEATHERAPI_FORECAST).await;
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("forecast.csv");
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        let output = wapp_get(dir.path(), &server.base_url)
+            .args(["--data", "forecast", "--output", "csv"])
+            .args(["--out", "forecast.csv", "--append"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        runs.push(fs::read_to_string(&log).unwrap());
+    }
+
+    let first: Vec<&str> = runs[0].lines().collect();
+    let second: Vec<&str> = runs[1].lines().collect();
+    assert!(first[0].starts_with("date,"), "{}", runs[0]);
+    assert_eq!(second.len(), 2 * first.len() - 1);
+    assert_eq!(second[first.len()..], first[1..]);
+    assert_eq!(second.iter().filter(|l| l.starts_with("date,")).count(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_out_replaces_the_file_without_colors() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("now.txt");
+    fs::write(&path, "old contents\n").unwrap();
+
+    for _ in 0..2 {
+        let output = wapp_get(dir.path(), &server.base_url)
+            .args(["--output", "text", "--out", "now.txt"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+    }
+
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("old contents"), "{}", text);
+    assert_eq!(text.matches("Temperature").count(), 1, "{}", text);
+    assert!(!text.contains('\x1b'), "{}", text);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dash_is_stdout() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.base_url)
+        .args(["--output", "json", "--out", "-"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"temp_c\""), "{}", stdout);
+    assert!(!dir.path().join("-").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unwritable_file_is_named() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.base_url)
+        .args(["--out", "missing/weather.json"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("cannot write missing/weather.json:"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_json_lines_carry_a_timestamp() {
+    let now = utc("2024-06-10T09:30:00Z");
+
+    assert_eq!(
+        json_line("{\n  \"temp_c\": 21.5\n}", now).unwrap(),
+        "{\"temp_c\":21.5,\"timestamp\":\"2024-06-10T09:30:00Z\"}\n"
+    );
+    assert_eq!(
+        json_line("[1, 2]", now).unwrap(),
+        "{\"data\":[1,2],\"timestamp\":\"2024-06-10T09:30:00Z\"}\n"
+    );
+    assert!(json_line("Kyiv: 21°C", now).is_err());
+}
+
+#[test]
+fn test_appending_to_an_existing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log.csv");
+    fs::write(&path, "date,temp\n2024-06-09,20\n").unwrap();
+    let destination = Destination::new(Some(path.clone()), true);
+    let now = utc("2024-06-10T09:30:00Z");
+
+    write(
+        &destination,
+        b"date,temp\n2024-06-10,21\n",
+        Some(OutputFormat::Csv),
+        now,
+    )
+    .unwrap();
+    write(&destination, b"Kyiv: 21\n", None, now).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "date,temp\n2024-06-09,20\n2024-06-10,21\nKyiv: 21\n"
+    );
+    assert_eq!(
+        Destination::new(Some("-".into()), true),
+        Destination::Stdout
+    );
+    assert_eq!(Destination::new(None, false), Destination::Stdout);
+}
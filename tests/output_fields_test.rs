@@ -0,0 +1,178 @@
+use chrono::NaiveDate;
+use clap::Parser;
+use wapp::cli::{Cli, Commands};
+use wapp::output::fields::{FieldSet, Selection};
+use wapp::series::DailySummary;
+use wapp::weather::WeatherData;
+
+fn data() -> WeatherData {
+    WeatherData {
+        temp_c: Some(24.0),
+        feels_like_c: Some(24.8),
+        humidity: Some(36.0),
+        wind_kph: Some(11.2),
+        wind_degree: None,
+        gust_kph: None,
+        uv_index: None,
+        uv_category: None,
+        pressure_mb: None,
+        visibility_km: None,
+        dew_point_c: None,
+        cloud: None,
+        condition: Some("Sunny".into()),
+        condition_kind: None,
+        forecast: vec![
+            DailySummary {
+                date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+                min_c: Some(10.2),
+                max_c: Some(18.3),
+                precip_mm: Some(0.4),
+                precip_chance: None,
+                max_wind_kph: Some(19.1),
+                avg_wind_kph: None,
+                condition: Some("Partly cloudy".into()),
+                partial: false,
+            },
+            DailySummary {
+                date: NaiveDate::from_ymd_opt(2024, 6, 11).unwrap(),
+                min_c: Some(11.4),
+                max_c: None,
+                precip_mm: None,
+                precip_chance: None,
+                max_wind_kph: None,
+                avg_wind_kph: None,
+                condition: None,
+                partial: false,
+            },
+        ],
+        hourly: Vec::new(),
+        alerts: None,
+        air: None,
+        astro: None,
+        marine: None,
+        snow: None,
+        weekend: None,
+        trend: None,
+        advice: None,
+        updated: None,
+        timezone: None,
+    }
+}
+
+fn select(fields: &[&str], inline: bool) -> Selection {
+    Selection {
+        fields: fields.iter().map(|f| f.to_string()).collect(),
+        inline,
+    }
+}
+
+#[test]
+fn test_current_fields_in_requested_order() {
+    let selection = select(&["humidity", "temp", "wind_speed"], false);
+    assert_eq!(selection.render("now", &data()), "36\n24.0\n11.2\n");
+
+    let selection = select(&["condition", "temp"], true);
+    assert_eq!(selection.render("now", &data()), "Sunny 24.0\n");
+}
+
+#[test]
+fn test_duplicates_are_kept() {
+    let selection = select(&["temp", "humidity", "temp"], true);
+    assert_eq!(selection.render("now", &data()), "24.0 36 24.0\n");
+}
+
+#[test]
+fn test_missing_values_render_as_dash() {
+    let selection = select(&["wind_degree", "temp"], true);
+    assert_eq!(selection.render("now", &data()), "- 24.0\n");
+}
+
+#[test]
+fn test_daily_fields_one_group_per_day() {
+    let selection = select(&["date", "max_temp", "condition"], true);
+    assert_eq!(
+        selection.render("forecast", &data()),
+        "2024-06-10 18.3 Partly cloudy\n2024-06-11 - -\n"
+    );
+
+    let selection = select(&["date", "min_temp"], false);
+    assert_eq!(
+        selection.render("forecast", &data()),
+        "2024-06-10\n10.2\n2024-06-11\n11.4\n"
+    );
+}
+
+#[test]
+fn test_field_sets_per_kind() {
+    assert_eq!(FieldSet::for_kind("now"), FieldSet::Current);
+    assert_eq!(FieldSet::for_kind("forecast"), FieldSet::Daily);
+    assert_eq!(FieldSet::for_kind("tomorrow"), FieldSet::Daily);
+
+    assert!(select(&["temp", "condition"], false).check("now").is_ok());
+    assert!(select(&["date", "condition"], false)
+        .check("forecast")
+        .is_ok());
+}
+
+#[test]
+fn test_unknown_field_lists_fields_per_kind() {
+    let err = select(&["temp", "pressure"], false)
+        .check("now")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("unknown field 'pressure' for 'now'"),
+        "{}",
+        err
+    );
+    assert!(err.contains("now: temp, feels_like, humidity"), "{}", err);
+    assert!(err.contains("date, min_temp, max_temp"), "{}", err);
+
+    // Fields exist only for their own kind.
+    let err = select(&["temp"], false)
+        .check("forecast")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("unknown field 'temp' for 'forecast'"),
+        "{}",
+        err
+    );
+    assert!(select(&["date"], false).check("now").is_err());
+}
+
+#[test]
+fn test_fields_flags() {
+    let cli = Cli::parse_from([
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--fields",
+        "temp,humidity",
+        "--fields",
+        "wind_speed",
+        "--fields-inline",
+    ]);
+    match cli.cmd {
+        Commands::Get {
+            fields,
+            fields_inline,
+            ..
+        } => {
+            assert_eq!(fields, vec!["temp", "humidity", "wind_speed"]);
+            assert!(fields_inline);
+        }
+        _ => panic!("expected get"),
+    }
+
+    let conflicting = [
+        vec!["--fields", "temp", "--output", "json"],
+        vec!["--fields", "temp", "--raw"],
+        vec!["--fields-inline"],
+    ];
+    for args in conflicting {
+        let argv = ["wapp", "get", "--city", "Kyiv"].into_iter().chain(args);
+        assert!(Cli::try_parse_from(argv).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+use clap::Parser;
+use wapp::cli::{get_rendering, Cli, Commands};
+use wapp::output::template::{icon, Template, PLACEHOLDERS};
+use wapp::output::{self, OutputFormat, Rendering};
+use wapp::providers::WeatherApiProvider;
+use wapp::weather::WeatherData;
+
+fn data() -> WeatherData {
+    WeatherData {
+        temp_c: Some(24.0),
+        feels_like_c: Some(24.8),
+        humidity: Some(36.0),
+        wind_kph: Some(11.2),
+        condition: Some("Sunny".into()),
+        ..Default::default()
+    }
+}
+
+fn render(template: &str) -> String {
+    Template::parse(template).unwrap().render("Kyiv", &data())
+}
+
+#[test]
+fn test_renders_every_placeholder() {
+    assert_eq!(render("{city}: {temp}°C {condition}"), "Kyiv: 24.0°C Sunny");
+    assert_eq!(
+        render("{feels_like} {humidity}% {wind_speed} km/h {icon}"),
+        "24.8 36% 11.2 km/h ☀️"
+    );
+    assert_eq!(render("{temp}{temp}"), "24.024.0");
+}
+
+#[test]
+fn test_literal_text_and_escapes() {
+    assert_eq!(render(""), "");
+    assert_eq!(render("no placeholders"), "no placeholders");
+    assert_eq!(render("{{city}}"), "{city}");
+    assert_eq!(render("{{{city}}}"), "{Kyiv}");
+    assert_eq!(render("}} {{"), "} {");
+    assert_eq!(render("Ünïcode {city} ✓"), "Ünïcode Kyiv ✓");
+}
+
+#[test]
+fn test_missing_values_render_as_dash() {
+    let template = Template::parse("{temp} {condition} {icon}").unwrap();
+    assert_eq!(template.render("Kyiv", &WeatherData::default()), "- - -");
+}
+
+#[test]
+fn test_unknown_placeholder_lists_valid_ones() {
+    let err = Template::parse("{city} {temperature}")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("'{temperature}'"), "{}", err);
+    for placeholder in PLACEHOLDERS {
+        assert!(err.contains(&format!("{{{}}}", placeholder)), "{}", err);
+    }
+
+    let err = Template::parse("{}").unwrap_err().to_string();
+    assert!(err.contains("unknown placeholder '{}'"), "{}", err);
+
+    // Placeholders are case-sensitive and not trimmed.
+    assert!(Template::parse("{City}").is_err());
+    assert!(Template::parse("{ city }").is_err());
+}
+
+#[test]
+fn test_precision() {
+    assert_eq!(render("{temp:.0}°C"), "24°C");
+    assert_eq!(render("{wind_speed:.2} {humidity:.1}"), "11.20 36.0");
+    assert_eq!(render("{{temp:.0}}"), "{temp:.0}");
+
+    let err = Template::parse("{city:.0}").unwrap_err().to_string();
+    assert!(err.contains("'{city}' takes no precision"), "{}", err);
+
+    for spec in ["{temp:}", "{temp:0}", "{temp:.x}", "{temp:.10}"] {
+        let err = Template::parse(spec).unwrap_err().to_string();
+        assert!(err.contains("invalid precision"), "{}: {}", spec, err);
+    }
+}
+
+#[test]
+fn test_unbalanced_braces_are_errors() {
+    let err = Template::parse("{city").unwrap_err().to_string();
+    assert!(err.contains("unclosed '{' at position 0"), "{}", err);
+
+    let err = Template::parse("temp} ").unwrap_err().to_string();
+    assert!(err.contains("unmatched '}' at position 4"), "{}", err);
+    assert!(err.contains("'}}'"), "{}", err);
+}
+
+#[test]
+fn test_icon_matches_condition_keywords() {
+    assert_eq!(icon("Sunny"), "☀️");
+    assert_eq!(icon("clear sky"), "☀️");
+    assert_eq!(icon("Partly cloudy"), "⛅");
+    assert_eq!(icon("Overcast"), "☁️");
+    assert_eq!(icon("Patchy rain nearby"), "🌧");
+    assert_eq!(icon("Light snow showers"), "❄️");
+    assert_eq!(icon("Thundery outbreaks possible"), "⛈");
+    assert_eq!(icon("Freezing fog"), "🌫");
+    assert_eq!(icon("Unknown"), "🌡");
+}
+
+#[test]
+fn test_rendering_parses_formats_and_templates() {
+    assert_eq!(
+        "json".parse::<Rendering>().unwrap(),
+        Rendering::Format(OutputFormat::Json)
+    );
+    assert_eq!(
+        "Porcelain".parse::<Rendering>().unwrap(),
+        Rendering::Format(OutputFormat::Porcelain)
+    );
+    assert_eq!(
+        "{city}".parse::<Rendering>().unwrap(),
+        Rendering::Template(Template::parse("{city}").unwrap())
+    );
+
+    let err = "yaml".parse::<Rendering>().unwrap_err().to_string();
+    assert!(err.contains("unknown format 'yaml'"), "{}", err);
+    assert!("{nope}".parse::<Rendering>().is_err());
+}
+
+#[test]
+fn test_format_flag_takes_template() {
+    let cli = Cli::try_parse_from([
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--format",
+        "{city}: {temp}",
+    ])
+    .unwrap();
+    match cli.cmd {
+        Commands::Get { output, format, .. } => assert_eq!(
+            get_rendering(output, format),
+            Rendering::Template(Template::parse("{city}: {temp}").unwrap())
+        ),
+        _ => panic!("expected get"),
+    }
+
+    let err = Cli::try_parse_from(["wapp", "get", "--city", "Kyiv", "--format", "{temperature}"])
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("valid placeholders"), "{}", err);
+}
+
+#[test]
+fn test_format_conflicts_with_output() {
+    let result = Cli::try_parse_from([
+        "wapp", "get", "--city", "Kyiv", "--output", "json", "--format", "{city}",
+    ]);
+    assert_eq!(
+        result.err().unwrap().kind(),
+        clap::error::ErrorKind::ArgumentConflict
+    );
+}
+
+#[test]
+fn test_render_template_falls_back_to_raw_body() {
+    let provider = WeatherApiProvider {
+        api_key: "k".into(),
+        base_url: "http://localhost".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let template = Template::parse("{city}").unwrap();
+
+    assert_eq!(
+        output::render_template(&provider, "Kyiv", "now", "not json", &template),
+        "not json\n"
+    );
+}
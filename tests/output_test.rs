@@ -0,0 +1,549 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use mocks::providers::{openweather, weatherapi};
+use wapp::cli::{get_rendering, Cli, Commands};
+use wapp::output::{self, OutputFormat, Rendering};
+use wapp::providers::ApiProvider;
+use wapp::series::DailySummary;
+use wapp::units::Units;
+use wapp::weather::WeatherData;
+
+fn day(day: u32, min: f64, max: f64, precip: Option<f64>, condition: &str) -> DailySummary {
+    DailySummary {
+        date: NaiveDate::from_ymd_opt(2024, 6, day).unwrap(),
+        min_c: Some(min),
+        max_c: Some(max),
+        precip_mm: precip,
+        precip_chance: None,
+        max_wind_kph: Some(20.0),
+        avg_wind_kph: None,
+        condition: Some(condition.into()),
+        partial: false,
+    }
+}
+
+fn current() -> WeatherData {
+    WeatherData {
+        temp_c: Some(24.0),
+        feels_like_c: Some(24.8),
+        humidity: Some(36.0),
+        wind_kph: Some(11.2),
+        wind_degree: Some(320.0),
+        gust_kph: None,
+        uv_index: None,
+        uv_category: None,
+        pressure_mb: None,
+        visibility_km: None,
+        dew_point_c: None,
+        cloud: None,
+        condition: Some("Sunny".into()),
+        condition_kind: None,
+        forecast: Vec::new(),
+        hourly: Vec::new(),
+        alerts: None,
+        air: None,
+        astro: None,
+        marine: None,
+        snow: None,
+        weekend: None,
+        trend: None,
+        advice: None,
+        updated: None,
+        timezone: None,
+    }
+}
+
+fn forecast() -> WeatherData {
+    WeatherData {
+        forecast: vec![
+            DailySummary {
+                precip_chance: Some(20.0),
+                ..day(10, 10.2, 18.3, Some(0.4), "Partly cloudy")
+            },
+            DailySummary {
+                precip_chance: Some(70.0),
+                ..day(11, 11.4, 16.8, Some(12.5), "Patchy rain nearby")
+            },
+            day(12, -9.8, 20.1, None, "Sunny"),
+        ],
+        ..current()
+    }
+}
+
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+const OPENWEATHER_FORECAST: &str = include_str!("fixtures/openweather_forecast.json");
+
+/// Splits CSV into records of fields, undoing the quoting.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let (mut record, mut field) = (Vec::new(), String::new());
+    let (mut quoted, mut chars) = (false, text.chars().peekable());
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+
+    records
+}
+
+#[test]
+fn test_json() {
+    let json: serde_json::Value = serde_json::from_str(&output::render_json(&forecast())).unwrap();
+
+    assert_eq!(json["temp_c"], 24.0);
+    assert_eq!(json["wind_degree"], 320.0);
+    assert_eq!(json["forecast"][0]["date"], "2024-06-10");
+    assert_eq!(json["forecast"][1]["condition"], "Patchy rain nearby");
+    assert!(json["forecast"][2]["precip_mm"].is_null());
+}
+
+#[test]
+fn test_text() {
+    assert_eq!(
+        output::render_text("Kyiv", &forecast()),
+        "Kyiv\n\
+         \x20 Condition:   Sunny\n\
+         \x20 Temperature: 24.0 °C\n\
+         \x20 Feels like:  24.8 °C\n\
+         \x20 Humidity:    36%\n\
+         \x20 Wind:        11.2 km/h NW\n\
+         \x20 2024-06-10   10.2 .. 18.3 °C  Partly cloudy  Rain: 20% (0.4 mm)\n\
+         \x20 2024-06-11   11.4 .. 16.8 °C  Patchy rain nearby  Rain: 70% (12.5 mm)\n\
+         \x20 2024-06-12   -9.8 .. 20.1 °C  Sunny  Rain: —\n"
+    );
+}
+
+#[test]
+fn test_unknown_rain_chance_is_not_zero() {
+    let mut data = forecast();
+    data.forecast[0].precip_chance = Some(0.0);
+    data.forecast[1].precip_chance = None;
+
+    let text = output::render_text("Kyiv", &data);
+    assert!(
+        text.contains("Partly cloudy  Rain: 0% (0.4 mm)\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("Patchy rain nearby  Rain: — (12.5 mm)\n"),
+        "{}",
+        text
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&output::render_json(&data)).unwrap();
+    assert_eq!(json["forecast"][0]["precip_chance"], 0.0);
+    assert!(json["forecast"][1]["precip_chance"].is_null());
+}
+
+#[test]
+fn test_text_skips_missing_values() {
+    let data = WeatherData {
+        temp_c: Some(3.0),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        output::render_text("Oslo", &data),
+        "Oslo\n  Temperature: 3.0 °C\n"
+    );
+}
+
+#[test]
+fn test_minimal() {
+    assert_eq!(
+        output::render_minimal("Kyiv", &current(), Units::Metric),
+        "Kyiv: 24.0 °C, Sunny\n"
+    );
+
+    let data = WeatherData {
+        temp_c: Some(3.0),
+        ..Default::default()
+    };
+    assert_eq!(
+        output::render_minimal("Oslo", &data, Units::Imperial),
+        "Oslo: 37.4 °F\n"
+    );
+}
+
+#[test]
+fn test_table_forecast() {
+    assert_eq!(
+        output::render_table("Kyiv", &forecast()),
+        "Kyiv\n\
+         ┌───────────┬───────────────┬────────────────────┬────────────┐\n\
+         │ Date      │ 2024-06-10    │ 2024-06-11         │ 2024-06-12 │\n\
+         ├───────────┼───────────────┼────────────────────┼────────────┤\n\
+         │ Condition │ Partly cloudy │ Patchy rain nearby │ Sunny      │\n\
+         │ High °C   │          18.3 │               16.8 │       20.1 │\n\
+         │ Low °C    │          10.2 │               11.4 │       -9.8 │\n\
+         │ Rain %    │            20 │                 70 │          - │\n\
+         │ Wind      │     20.0 km/h │          20.0 km/h │  20.0 km/h │\n\
+         └───────────┴───────────────┴────────────────────┴────────────┘\n"
+    );
+}
+
+#[test]
+fn test_table_current() {
+    assert_eq!(
+        output::render_table("Kyiv", &current()),
+        "Kyiv\n\
+         Temp °C  Feels °C  Humidity %  Wind          Condition\n\
+         \x20  24.0      24.8          36  11.2 km/h NW  Sunny\n"
+    );
+}
+
+#[test]
+fn test_uv_index_is_shown_only_when_known() {
+    let mut data = current();
+    data.set_uv(Some(7.2));
+
+    let text = output::render_text("Kyiv", &data);
+    assert!(
+        text.ends_with("  Wind:        11.2 km/h NW\n  UV index:    7 High\n"),
+        "{text}"
+    );
+    assert_eq!(
+        output::render_table("Kyiv", &data),
+        "Kyiv\n\
+         Temp °C  Feels °C  Humidity %  Wind          Condition  UV\n\
+         \x20  24.0      24.8          36  11.2 km/h NW  Sunny      7 High\n"
+    );
+    let json: serde_json::Value = serde_json::from_str(&output::render_json(&data)).unwrap();
+    assert_eq!(json["uv_index"], 7.2);
+    assert_eq!(json["uv_category"], "high");
+
+    // Without a UV index there is no line, column, or key.
+    let data = current();
+    assert!(!output::render_text("Kyiv", &data).contains("UV"));
+    assert!(!output::render_table("Kyiv", &data).contains("UV"));
+    assert!(!output::render_json(&data).contains("uv_"));
+}
+
+#[test]
+fn test_wind_gusts_are_read() {
+    let mut json: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/weatherapi_current.json")).unwrap();
+    json["current"]["gust_kph"] = 25.2.into();
+    let data = weatherapi("http://localhost")
+        .weather(&json.to_string())
+        .unwrap();
+    assert_eq!(data.gust_kph, Some(25.2));
+
+    let openweather = openweather("http://localhost").with_units("metric");
+    let mut json: serde_json::Value =
+        serde_json::from_str(include_str!("fixtures/openweather_current.json")).unwrap();
+    assert_eq!(
+        openweather.weather(&json.to_string()).unwrap().gust_kph,
+        None
+    );
+    json["wind"]["gust"] = 7.5.into();
+    assert_eq!(
+        openweather.weather(&json.to_string()).unwrap().gust_kph,
+        Some(27.0)
+    );
+}
+
+#[test]
+fn test_compass_point() {
+    assert_eq!(output::compass_point(0.0), "N");
+    assert_eq!(output::compass_point(350.0), "N");
+    assert_eq!(output::compass_point(250.0), "WSW");
+    assert_eq!(output::compass_point(320.0), "NW");
+    assert_eq!(output::compass_point(135.0), "SE");
+}
+
+#[test]
+fn test_unreadable_response() {
+    let body = r#"{"error":{"code":1006,"message":"No matching location found."}}"#;
+
+    assert_eq!(
+        output::summarize(
+            &weatherapi("http://localhost"),
+            "Atlantis",
+            "now",
+            body,
+            OutputFormat::Table
+        ),
+        format!("{}\n", body)
+    );
+    assert_eq!(
+        output::summarize(
+            &weatherapi("http://localhost"),
+            "Kyiv",
+            "now",
+            "not json",
+            OutputFormat::Text
+        ),
+        "not json\n"
+    );
+
+    let err = output::normalized_json(&weatherapi("http://localhost"), "now", "{}").unwrap_err();
+    assert!(err.to_string().contains("--raw"));
+}
+
+#[test]
+fn test_output_flag() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--output", "table"]);
+    match cli.cmd {
+        Commands::Get { output, format, .. } => assert_eq!(
+            get_rendering(output, format),
+            Rendering::Format(OutputFormat::Table)
+        ),
+        _ => panic!("expected get"),
+    }
+
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv"]);
+    match cli.cmd {
+        Commands::Get { output, format, .. } => assert_eq!(
+            get_rendering(output, format),
+            Rendering::Format(OutputFormat::Text)
+        ),
+        _ => panic!("expected get"),
+    }
+}
+
+#[test]
+fn test_csv_escapes_fields() {
+    let mut data = forecast();
+    data.forecast[0].condition = Some("Rain, \"heavy\" at times".into());
+
+    let csv = output::render_csv(&data);
+    assert!(csv.contains("\"Rain, \"\"heavy\"\" at times\""));
+
+    let records = parse_csv(&csv);
+    assert_eq!(
+        records[0],
+        [
+            "date",
+            "min_temp",
+            "max_temp",
+            "condition",
+            "precipitation",
+            "wind",
+            "precip_chance"
+        ]
+    );
+    assert_eq!(records.len(), 4);
+    assert!(records.iter().all(|r| r.len() == output::CSV_COLUMNS.len()));
+    assert_eq!(records[1][3], "Rain, \"heavy\" at times");
+    assert_eq!(records[1][5], "20");
+    assert_eq!(records[3][4], "");
+    assert_eq!(records[1][6], "20");
+    assert_eq!(records[3][6], "");
+}
+
+#[test]
+fn test_csv_without_forecast_is_header_only() {
+    assert_eq!(
+        output::render_csv(&current()),
+        "date,min_temp,max_temp,condition,precipitation,wind,precip_chance\n"
+    );
+}
+
+#[test]
+fn test_city_csv_leads_with_city_column() {
+    let data = forecast();
+    let csv = format!(
+        "{}{}",
+        output::city_csv_header(),
+        output::city_csv_rows("Portland, OR", &data)
+    );
+
+    let records = parse_csv(&csv);
+    assert_eq!(records[0][0], "city");
+    assert_eq!(records.len(), 4);
+    assert!(records
+        .iter()
+        .all(|r| r.len() == output::CSV_COLUMNS.len() + 1));
+    assert_eq!(records[1][0], "Portland, OR");
+}
+
+#[test]
+fn test_csv_from_weatherapi_forecast() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_FORECAST)
+        .unwrap();
+    let records = parse_csv(&output::render_csv(&data));
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[2][0], "2024-06-11");
+    assert_eq!(records[2][5], "19.1");
+}
+
+#[test]
+fn test_csv_aggregates_openweather_to_days() {
+    let provider = openweather("http://localhost").with_units("metric");
+    let data = provider.weather(OPENWEATHER_FORECAST).unwrap();
+    let records = parse_csv(&output::render_csv(&data));
+
+    let dates: Vec<&str> = records[1..].iter().map(|r| r[0].as_str()).collect();
+    assert_eq!(
+        dates,
+        [
+            "2024-06-10",
+            "2024-06-11",
+            "2024-06-12",
+            "2024-06-13",
+            "2024-06-14",
+            "2024-06-15"
+        ]
+    );
+    assert!(records[1..]
+        .iter()
+        .all(|r| !r[1].is_empty() && !r[5].is_empty()));
+}
+
+#[test]
+fn test_table_marks_partial_days() {
+    let mut data = forecast();
+    data.forecast[0].partial = true;
+
+    assert_eq!(
+        output::render_table("Kyiv", &data),
+        "Kyiv\n\
+         ┌───────────┬───────────────┬────────────────────┬────────────┐\n\
+         │ Date      │ 2024-06-10*   │ 2024-06-11         │ 2024-06-12 │\n\
+         ├───────────┼───────────────┼────────────────────┼────────────┤\n\
+         │ Condition │ Partly cloudy │ Patchy rain nearby │ Sunny      │\n\
+         │ High °C   │          18.3 │               16.8 │       20.1 │\n\
+         │ Low °C    │          10.2 │               11.4 │       -9.8 │\n\
+         │ Rain %    │            20 │                 70 │          - │\n\
+         │ Wind      │     20.0 km/h │          20.0 km/h │  20.0 km/h │\n\
+         └───────────┴───────────────┴────────────────────┴────────────┘\n\
+         \x20 * partial day: the forecast covers only part of it\n"
+    );
+    assert!(output::render_text("Kyiv", &data).ends_with(
+        "  2024-06-10*  10.2 .. 18.3 °C  Partly cloudy  Rain: 20% (0.4 mm)\n\
+                    \x20 2024-06-11   11.4 .. 16.8 °C  Patchy rain nearby  Rain: 70% (12.5 mm)\n\
+                    \x20 2024-06-12   -9.8 .. 20.1 °C  Sunny  Rain: —\n\
+                    \x20 * partial day: the forecast covers only part of it\n"
+    ));
+}
+
+#[test]
+fn test_csv_of_unreadable_response_is_header_only() {
+    assert_eq!(
+        output::summarize(
+            &weatherapi("http://localhost"),
+            "Kyiv",
+            "forecast",
+            "oops",
+            OutputFormat::Csv
+        ),
+        output::csv_header()
+    );
+}
+
+#[test]
+fn test_observed_days_are_titled_as_such() {
+    let mut history: serde_json::Value = serde_json::from_str(WEATHERAPI_FORECAST).unwrap();
+    history.as_object_mut().unwrap().remove("current");
+    let body = history.to_string();
+
+    let text = output::summarize(
+        &weatherapi("http://localhost"),
+        "Kyiv",
+        "2024-06-10",
+        &body,
+        OutputFormat::Text,
+    );
+    assert!(text.contains("Kyiv (observed)"), "{}", text);
+    assert!(text.contains("2024-06-10"), "{}", text);
+
+    let table = output::summarize(
+        &weatherapi("http://localhost"),
+        "Kyiv",
+        "forecast",
+        &body,
+        OutputFormat::Table,
+    );
+    assert!(!table.contains("(observed)"), "{}", table);
+
+    assert_eq!(output::title("Kyiv", "2024-06-10"), "Kyiv (observed)");
+    assert_eq!(output::title("Kyiv", "now"), "Kyiv");
+}
+
+#[test]
+fn test_table_hourly_from_weatherapi() {
+    let table = output::summarize(
+        &weatherapi("http://localhost"),
+        "Kyiv",
+        "hourly",
+        WEATHERAPI_FORECAST,
+        OutputFormat::Table,
+    );
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(
+        lines[1],
+        "Time       Temp °C  Precip %  Precip mm  Condition"
+    );
+    assert_eq!(lines.len(), 2 + 12);
+    assert!(
+        lines[2].ends_with("   11.4        12        0.0  Partly cloudy"),
+        "{}",
+        table
+    );
+}
+
+#[test]
+fn test_text_hourly_lists_hours_instead_of_days() {
+    let text = output::summarize(
+        &weatherapi("http://localhost"),
+        "Kyiv",
+        "hourly",
+        WEATHERAPI_FORECAST,
+        OutputFormat::Text,
+    );
+
+    assert!(!text.contains("2024-06-10"), "{}", text);
+    let hours: Vec<&str> = text.lines().filter(|l| l.contains(" °C  ")).collect();
+    assert_eq!(hours.len(), 12, "{}", text);
+    assert!(
+        hours[0].ends_with("11.4 °C   12%  Partly cloudy"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn test_json_hourly_entries() {
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(
+            &weatherapi("http://localhost"),
+            "hourly",
+            WEATHERAPI_FORECAST,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(json["hourly"][0]["time"], "2024-06-09T23:00:00Z");
+    assert_eq!(json["hourly"][0]["precip_chance"], 12.0);
+
+    let daily: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(
+            &weatherapi("http://localhost"),
+            "forecast",
+            WEATHERAPI_FORECAST,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(daily.get("hourly").is_none());
+}
@@ -0,0 +1,104 @@
+use clap::Parser;
+use wapp::cli::Cli;
+use wapp::pager::{resolve_command, should_page, write_paged, Terminal, DEFAULT_PAGER};
+
+const TTY: Terminal = Terminal {
+    is_tty: true,
+    height: 5,
+};
+
+fn long_text() -> String {
+    (1..=20).map(|i| format!("line {}\n", i)).collect()
+}
+
+#[test]
+fn test_resolve_command_precedence() {
+    assert_eq!(
+        resolve_command(Some("most"), Some("more".into())).as_deref(),
+        Some("most")
+    );
+    assert_eq!(
+        resolve_command(None, Some("more".into())).as_deref(),
+        Some("more")
+    );
+    assert_eq!(resolve_command(None, None).as_deref(), Some(DEFAULT_PAGER));
+    // An empty config value disables paging, like `core.pager = ""`.
+    assert_eq!(resolve_command(Some(""), Some("more".into())), None);
+}
+
+#[test]
+fn test_should_page() {
+    let text = long_text();
+
+    assert!(should_page(TTY, &text, false));
+    assert!(!should_page(TTY, "short\n", false));
+    assert!(!should_page(TTY, &text, true));
+
+    let redirected = Terminal {
+        is_tty: false,
+        ..TTY
+    };
+    assert!(!should_page(redirected, &text, false));
+}
+
+#[test]
+fn test_no_tty_writes_directly() {
+    let mut out = Vec::new();
+    let redirected = Terminal {
+        is_tty: false,
+        height: 5,
+    };
+
+    let paged = write_paged(&long_text(), false, Some("less"), redirected, &mut out).unwrap();
+
+    assert!(!paged);
+    assert_eq!(String::from_utf8(out).unwrap(), long_text());
+}
+
+#[test]
+fn test_missing_pager_falls_back_to_stdout() {
+    let mut out = Vec::new();
+
+    let paged = write_paged(
+        &long_text(),
+        false,
+        Some("wapp-no-such-pager-binary -R"),
+        TTY,
+        &mut out,
+    )
+    .unwrap();
+
+    assert!(!paged);
+    assert_eq!(String::from_utf8(out).unwrap(), long_text());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pager_receives_output() {
+    let mut out = Vec::new();
+
+    // `true` reads nothing and exits; the closed pipe must not be an error.
+    let paged = write_paged(&long_text(), false, Some("true"), TTY, &mut out).unwrap();
+
+    assert!(paged);
+    assert!(out.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_started_pager_is_not_replaced_by_stdout() {
+    let mut out = Vec::new();
+
+    // `false` exits with an error before reading; the text was handed to
+    // the pager all the same and must not be printed a second time.
+    let paged = write_paged(&long_text(), false, Some("false"), TTY, &mut out).unwrap();
+
+    assert!(paged);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_no_pager_flag_is_global() {
+    let cli = Cli::try_parse_from(vec!["wapp", "cache", "list", "--no-pager"]).unwrap();
+    assert!(cli.no_pager);
+}
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use wapp::cache::{Cache, CacheEntry, Namespace};
+use wapp::cli::render_paths;
+use wapp::paths::{ensure_dir, Paths};
+
+#[test]
+fn test_env_overrides() {
+    let paths = Paths::resolve_with(|name| match name {
+        "WAPP_CONFIG" => Some(PathBuf::from("/tmp/wapp.json")),
+        "WAPP_CACHE_DIR" => Some(PathBuf::from("/tmp/wapp-cache")),
+        "WAPP_DATA_DIR" => Some(PathBuf::from("/tmp/wapp-data")),
+        _ => None,
+    });
+
+    assert_eq!(paths.config, PathBuf::from("/tmp/wapp.json"));
+    assert_eq!(paths.cache, PathBuf::from("/tmp/wapp-cache"));
+    assert_eq!(paths.data, PathBuf::from("/tmp/wapp-data"));
+
+    let out = render_paths(&paths);
+    assert!(out.contains("config: /tmp/wapp.json"));
+    assert!(out.contains("cache:  /tmp/wapp-cache"));
+    assert!(out.contains("data:   /tmp/wapp-data"));
+}
+
+#[test]
+fn test_platform_defaults_are_distinct() {
+    let paths = Paths::resolve_with(|_| None);
+
+    assert_ne!(paths.cache, paths.data);
+    assert!(paths.config.ends_with("wapp/config.json"));
+    assert!(paths.cache.to_string_lossy().contains("wapp"));
+    assert!(paths.data.to_string_lossy().contains("wapp"));
+}
+
+#[test]
+fn test_directories_are_created_lazily() {
+    let root = tempfile::tempdir().unwrap();
+    let dir = root.path().join("nested").join("cache");
+    let cache = Cache::new(&dir);
+
+    // Reading never creates the directory.
+    assert_eq!(cache.stats().entries, 0);
+    assert!(cache.entries().is_empty());
+    assert!(!dir.exists());
+
+    cache
+        .put(&CacheEntry {
+            namespace: Namespace::Response,
+            key: "k".into(),
+            provider: "weatherapi".into(),
+            city: "Kyiv".into(),
+            created: 0,
+            expires: None,
+            body: "{}".into(),
+            validators: Default::default(),
+        })
+        .unwrap();
+    assert!(dir.is_dir());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_created_directories_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let root = tempfile::tempdir().unwrap();
+    let dir = root.path().join("data");
+
+    ensure_dir(&dir).unwrap();
+
+    let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+    // Calling it again on an existing directory is fine.
+    assert!(ensure_dir(&dir).is_ok());
+}
@@ -0,0 +1,189 @@
+mod mocks;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use clap::Parser;
+use mocks::providers::{openweather, weatherapi};
+use wapp::cli::{get_rendering, Cli, Commands};
+use wapp::output::{OutputFormat, Rendering};
+use wapp::porcelain::{self, ALERT_KEYS, CURRENT_KEYS, DAILY_KEYS};
+use wapp::series::{summarize_day, SeriesPoint};
+
+const WEATHERAPI_FIXTURE: &str = include_str!("fixtures/weatherapi_forecast.json");
+const OPENWEATHER_FIXTURE: &str = include_str!("fixtures/openweather_forecast.json");
+
+const WEATHERAPI_ALERTS: &str = r#"{
+    "alerts": {"alert": [{
+        "headline": "Flood\twarning",
+        "event": "Flood",
+        "severity": "Moderate",
+        "effective": "2024-05-01T10:00:00+00:00",
+        "expires": "2024-05-02T10:00:00+00:00",
+        "desc": "River levels\nrising"
+    }]}
+}"#;
+
+const OPENWEATHER_CURRENT: &str = r#"{
+    "dt": 1714557600,
+    "main": {"temp": 12.5, "feels_like": 11.0, "humidity": 80, "pressure": 1012},
+    "wind": {"speed": 5.0},
+    "clouds": {"all": 40},
+    "weather": [{"description": "scattered clouds"}]
+}"#;
+
+/// Keys of the rendered lines, with any `<section>.<N>.` prefix removed.
+fn bare_keys(out: &str) -> Vec<String> {
+    out.lines()
+        .map(|line| line.split('\t').next().unwrap())
+        .map(|key| key.rsplit('.').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_version_line() {
+    assert_eq!(porcelain::version_line(), "porcelain_version\t1\n");
+}
+
+#[test]
+fn test_format_flag_defaults() {
+    let cli = Cli::try_parse_from(["wapp", "get", "--city", "Kyiv"]).unwrap();
+    match cli.cmd {
+        Commands::Get { output, format, .. } => assert_eq!(
+            get_rendering(output, format),
+            Rendering::Format(OutputFormat::Text)
+        ),
+        _ => panic!("expected get"),
+    }
+
+    let cli = Cli::try_parse_from(["wapp", "now", "--format", "porcelain"]).unwrap();
+    match cli.cmd {
+        Commands::Now { format, .. } => assert_eq!(format, Some(OutputFormat::Porcelain)),
+        _ => panic!("expected now"),
+    }
+}
+
+#[test]
+fn test_weatherapi_now() {
+    let out =
+        porcelain::render(&weatherapi("http://localhost"), "now", WEATHERAPI_FIXTURE).unwrap();
+
+    assert!(out.starts_with("time\t"));
+    assert!(out.lines().all(|line| line.split('\t').count() == 2));
+    for key in bare_keys(&out) {
+        assert!(CURRENT_KEYS.contains(&key.as_str()), "unknown key {}", key);
+    }
+}
+
+#[test]
+fn test_weatherapi_forecast_days() {
+    let out = porcelain::render(
+        &weatherapi("http://localhost"),
+        "forecast",
+        WEATHERAPI_FIXTURE,
+    )
+    .unwrap();
+
+    assert_eq!(out.lines().filter(|l| l.contains(".date\t")).count(), 3);
+    assert!(out.contains("forecast.0.date\t"));
+    assert!(out.contains("forecast.2.date\t"));
+    assert!(!out.contains("forecast.3."));
+    for key in bare_keys(&out) {
+        assert!(DAILY_KEYS.contains(&key.as_str()), "unknown key {}", key);
+    }
+}
+
+#[test]
+fn test_weatherapi_hourly() {
+    let out = porcelain::render(
+        &weatherapi("http://localhost"),
+        "hourly",
+        WEATHERAPI_FIXTURE,
+    )
+    .unwrap();
+
+    assert!(out.starts_with("hourly.0.time\t"));
+    assert!(out.lines().all(|line| line.starts_with("hourly.")));
+}
+
+#[test]
+fn test_alerts_strip_line_breaks() {
+    let out =
+        porcelain::render(&weatherapi("http://localhost"), "alerts", WEATHERAPI_ALERTS).unwrap();
+
+    assert_eq!(
+        out,
+        "alerts.0.headline\tFlood warning\n\
+         alerts.0.event\tFlood\n\
+         alerts.0.severity\tModerate\n\
+         alerts.0.effective\t2024-05-01T10:00:00+00:00\n\
+         alerts.0.expires\t2024-05-02T10:00:00+00:00\n"
+    );
+    for key in bare_keys(&out) {
+        assert!(ALERT_KEYS.contains(&key.as_str()));
+    }
+}
+
+#[test]
+fn test_openweather_now_snapshot() {
+    let out = porcelain::render(
+        &openweather("http://localhost").with_units("metric"),
+        "now",
+        OPENWEATHER_CURRENT,
+    )
+    .unwrap();
+
+    assert_eq!(
+        out,
+        "time\t2024-05-01T10:00:00Z\n\
+         temp_c\t12.5\n\
+         feels_like_c\t11.0\n\
+         humidity\t80.0\n\
+         pressure_mb\t1012.0\n\
+         wind_kph\t18.0\n\
+         precip_mm\t0.0\n\
+         cloud\t40.0\n\
+         condition\tscattered clouds\n"
+    );
+}
+
+#[test]
+fn test_openweather_forecast_groups_by_day() {
+    let out = porcelain::render(
+        &openweather("http://localhost").with_units("metric"),
+        "forecast",
+        OPENWEATHER_FIXTURE,
+    )
+    .unwrap();
+
+    assert!(out.starts_with("forecast.0.date\t"));
+    for key in bare_keys(&out) {
+        assert!(DAILY_KEYS.contains(&key.as_str()), "unknown key {}", key);
+    }
+}
+
+#[test]
+fn test_unknown_kind_and_bad_body_fail() {
+    let err = porcelain::render(&weatherapi("http://localhost"), "astronomy", "{}").unwrap_err();
+    assert!(err.to_string().contains("astronomy"));
+
+    let err = porcelain::render(&weatherapi("http://localhost"), "now", "not json").unwrap_err();
+    assert!(err.to_string().contains("weatherapi"));
+}
+
+#[test]
+fn test_summarize_day_prefers_earliest_condition_on_tie() {
+    let point = |hour: u32, temp: f64, condition: &str| {
+        let mut point = SeriesPoint::at(Utc.with_ymd_and_hms(2024, 5, 1, hour, 0, 0).unwrap());
+        point.temp_c = Some(temp);
+        point.precip_mm = Some(0.5);
+        point.condition = Some(condition.to_string());
+        point
+    };
+    let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+
+    let day = summarize_day(date, &[point(0, 8.0, "Rain"), point(3, 14.0, "Sun")]);
+
+    assert_eq!(day.min_c, Some(8.0));
+    assert_eq!(day.max_c, Some(14.0));
+    assert_eq!(day.precip_mm, Some(1.0));
+    assert_eq!(day.condition.as_deref(), Some("Rain"));
+}
@@ -0,0 +1,166 @@
+use clap::Parser;
+use wapp::cli::{run_profile_command, Cli, Commands, ProfileCommand};
+use wapp::config::{parse_config, AppConfig, Profile};
+use wapp::providers::{provider_factory, ProviderKind};
+
+/// A personal default and a `work` profile with its own provider.
+fn config() -> AppConfig {
+    parse_config(
+        r#"{
+            "provider": "wttr",
+            "fallback_providers": ["openmeteo"],
+            "units": "metric",
+            "lang": "uk",
+            "default_city": "Kyiv",
+            "profiles": {"work": {"provider": "openmeteo", "units": "imperial", "lang": "en"}}
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_single_provider_config_is_the_default_profile() {
+    let cfg = parse_config(r#"{"provider":"owm","units":"metric"}"#).unwrap();
+
+    assert_eq!(cfg.profile_names(), vec!["default"]);
+    assert_eq!(cfg.profile_name(None), "default");
+
+    let resolved = cfg.with_profile(None).unwrap();
+    assert_eq!(resolved.provider, ProviderKind::OpenWeather);
+    assert_eq!(resolved.units.as_deref(), Some("metric"));
+}
+
+#[test]
+fn test_profile_settings_replace_the_top_level_ones() {
+    let cfg = config();
+    let work = cfg.with_profile(Some("work")).unwrap();
+
+    assert_eq!(work.provider, ProviderKind::OpenMeteo);
+    assert_eq!(work.units.as_deref(), Some("imperial"));
+    assert_eq!(work.lang.as_deref(), Some("en"));
+    // Not set in the profile, so the top-level one applies.
+    assert_eq!(work.default_city.as_deref(), Some("Kyiv"));
+    assert_eq!(work.fallback_providers, vec![ProviderKind::OpenMeteo]);
+
+    assert_eq!(provider_factory(&work).unwrap().name(), "openmeteo");
+    assert_eq!(provider_factory(&config()).unwrap().name(), "wttr");
+}
+
+#[test]
+fn test_active_profile_is_used_without_a_name() {
+    let mut cfg = config();
+    cfg.set_active_profile("work").unwrap();
+
+    assert_eq!(cfg.profile_name(None), "work");
+    assert_eq!(cfg.profile_name(Some("default")), "default");
+    assert_eq!(
+        cfg.with_profile(None).unwrap().provider,
+        ProviderKind::OpenMeteo
+    );
+    assert_eq!(
+        cfg.with_profile(Some("default")).unwrap().provider,
+        ProviderKind::Wttr
+    );
+
+    cfg.set_active_profile("default").unwrap();
+    assert_eq!(cfg.active_profile, None);
+}
+
+#[test]
+fn test_unknown_profile() {
+    let mut cfg = config();
+
+    let err = cfg.with_profile(Some("home")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "no profile 'home'. Profiles: default, work"
+    );
+    assert!(cfg.set_active_profile("home").is_err());
+    assert!(cfg.delete_profile("home").is_err());
+}
+
+#[test]
+fn test_store_profile_keeps_the_top_level_settings() {
+    let mut cfg = config();
+    let mut work = cfg.with_profile(Some("work")).unwrap();
+    work.provider = ProviderKind::MetNo;
+    work.default_city = Some("Lviv".into());
+    work.api_keys.insert("metno".into(), "k".into());
+
+    cfg.store_profile("work", work);
+
+    assert_eq!(cfg.provider, ProviderKind::Wttr);
+    assert_eq!(cfg.default_city.as_deref(), Some("Kyiv"));
+    assert_eq!(cfg.api_keys["metno"], "k");
+    assert_eq!(
+        cfg.profiles["work"],
+        Profile {
+            provider: Some(ProviderKind::MetNo),
+            units: Some("imperial".into()),
+            lang: Some("en".into()),
+            default_city: Some("Lviv".into()),
+        }
+    );
+
+    // A setting equal to the top-level one keeps following it.
+    let mut empty = cfg.clone();
+    empty.profiles.insert("same".into(), Profile::default());
+    let same = empty.with_profile(Some("same")).unwrap();
+    empty.store_profile("same", same);
+    assert_eq!(empty.profiles["same"], Profile::default());
+}
+
+#[test]
+fn test_profile_commands() {
+    let mut cfg = config();
+
+    assert_eq!(
+        run_profile_command(&mut cfg, ProfileCommand::List).unwrap(),
+        "* default  wttr\n  work     openmeteo\n"
+    );
+
+    let used = ProfileCommand::Use {
+        name: "work".into(),
+    };
+    assert_eq!(
+        run_profile_command(&mut cfg, used).unwrap(),
+        "Using profile work\n"
+    );
+    assert!(run_profile_command(&mut cfg, ProfileCommand::List)
+        .unwrap()
+        .contains("* work"));
+
+    let deleted = ProfileCommand::Delete {
+        name: "work".into(),
+    };
+    assert_eq!(
+        run_profile_command(&mut cfg, deleted).unwrap(),
+        "Deleted profile work\n"
+    );
+    assert_eq!(cfg.active_profile, None);
+    assert!(cfg.profiles.is_empty());
+
+    let err = cfg.delete_profile("default").unwrap_err();
+    assert_eq!(err.to_string(), "the default profile cannot be deleted");
+}
+
+#[test]
+fn test_parse_profile_flag() {
+    let cli =
+        Cli::try_parse_from(vec!["wapp", "get", "--profile", "work", "--city", "Kyiv"]).unwrap();
+    assert_eq!(cli.profile.as_deref(), Some("work"));
+
+    let cli = Cli::try_parse_from(vec!["wapp", "configure", "--profile", "work", "owm"]).unwrap();
+    assert_eq!(cli.profile.as_deref(), Some("work"));
+    assert!(matches!(cli.cmd, Commands::Configure { .. }));
+
+    let cli = Cli::try_parse_from(vec!["wapp", "profile", "use", "work"]).unwrap();
+    assert!(matches!(
+        cli.cmd,
+        Commands::Profile {
+            cmd: ProfileCommand::Use { .. }
+        }
+    ));
+
+    assert!(Cli::try_parse_from(vec!["wapp", "get", "--profile", "my work"]).is_err());
+}
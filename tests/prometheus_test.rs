@@ -0,0 +1,273 @@
+mod mocks;
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use assert_cmd::Command;
+use chrono::Utc;
+use mocks::http_server::HttpServer;
+use wapp::output::prometheus::{escape_label, render, Sample};
+use wapp::providers::WeatherApiProvider;
+use wapp::series::SeriesPoint;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// Kyiv: 24 °C, 36 % humidity, 11.2 km/h wind, 1017 mb.
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// `wapp get --output prometheus` against WeatherAPI at `base_url`, with
+/// its config, cache and data below `dir`.
+fn wapp_get(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.args(["get", "--provider", "weatherapi", "--no-cache"])
+        .args(["--output", "prometheus"]);
+    cmd
+}
+
+/// A parsed sample: metric name, labels in order, and value.
+type ParsedSample = (String, Vec<(String, String)>, f64);
+
+fn is_name(name: &str, colons: bool) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || (colons && c == ':'))
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || (colons && c == ':'))
+}
+
+/// Reads the labels of a sample up to the closing brace, checking the
+/// escapes of their values; returns the labels and the rest of the line.
+fn labels(mut rest: &str) -> (Vec<(String, String)>, &str) {
+    let mut labels = Vec::new();
+    loop {
+        if let Some(after) = rest.strip_prefix('}') {
+            return (labels, after);
+        }
+        let (name, after) = rest.split_once("=\"").expect("label without a value");
+        assert!(is_name(name, false), "invalid label name '{}'", name);
+
+        let mut value = String::new();
+        let mut chars = after.char_indices();
+        let end = loop {
+            match chars.next().expect("unterminated label value") {
+                (_, '\\') => match chars.next().expect("escape at the end") {
+                    (_, '\\') => value.push('\\'),
+                    (_, '"') => value.push('"'),
+                    (_, 'n') => value.push('\n'),
+                    (_, other) => panic!("invalid escape '\\{}'", other),
+                },
+                (i, '"') => break i,
+                (_, c) => value.push(c),
+            }
+        };
+        labels.push((name.to_string(), value));
+
+        rest = &after[end + 1..];
+        rest = rest.strip_prefix(',').unwrap_or(rest);
+    }
+}
+
+/// Checks `text` against the grammar of the Prometheus text exposition
+/// format, and that each metric family is typed before its samples and not
+/// split; returns the samples as (name, labels, value).
+fn parse_exposition(text: &str) -> Vec<ParsedSample> {
+    assert!(text.is_empty() || text.ends_with('\n'), "{:?}", text);
+
+    let mut typed = HashSet::new();
+    let mut finished = HashSet::new();
+    let mut current: Option<String> = None;
+    let mut samples = Vec::new();
+    for line in text.lines() {
+        if let Some(comment) = line.strip_prefix("# ") {
+            let mut words = comment.splitn(3, ' ');
+            let (keyword, name) = (words.next().unwrap(), words.next().unwrap());
+            assert!(is_name(name, true), "invalid metric name '{}'", name);
+            match keyword {
+                "HELP" => assert!(!words.next().unwrap_or_default().contains('\n')),
+                "TYPE" => {
+                    assert_eq!(words.next(), Some("gauge"), "{}", line);
+                    assert!(typed.insert(name.to_string()), "typed twice: {}", name);
+                }
+                _ => panic!("unexpected comment '{}'", line),
+            }
+            continue;
+        }
+
+        let end = line.find(['{', ' ']).expect("sample without a value");
+        let name = &line[..end];
+        assert!(is_name(name, true), "invalid metric name '{}'", name);
+        assert!(
+            typed.contains(name),
+            "{} is not typed before its samples",
+            name
+        );
+        if current.as_deref() != Some(name) {
+            if let Some(previous) = current.replace(name.to_string()) {
+                finished.insert(previous);
+            }
+            assert!(!finished.contains(name), "{} is split", name);
+        }
+
+        let (labels, rest) = match line[end..].strip_prefix('{') {
+            Some(rest) => labels(rest),
+            None => (Vec::new(), &line[end..]),
+        };
+        let value = rest.strip_prefix(' ').expect("no space before the value");
+        assert!(!value.contains(' '), "a timestamp: {}", line);
+        let value: f64 = value.parse().unwrap_or_else(|_| panic!("{}", line));
+        assert!(value.is_finite(), "{}", line);
+
+        samples.push((name.to_string(), labels, value));
+    }
+
+    samples
+}
+
+fn value(samples: &[ParsedSample], name: &str, city: &str) -> f64 {
+    samples
+        .iter()
+        .find(|(n, labels, _)| n == name && labels[0] == ("city".into(), city.into()))
+        .unwrap_or_else(|| panic!("no {} for {}", name, city))
+        .2
+}
+
+#[test]
+fn test_fixture_renders_valid_exposition() {
+    let provider = WeatherApiProvider::new("KEY");
+    let text = render(&[Sample::fetched(&provider, "Kyiv", WEATHERAPI_CURRENT)]);
+    let samples = parse_exposition(&text);
+
+    assert_eq!(
+        samples.iter().map(|s| s.0.as_str()).collect::<Vec<_>>(),
+        [
+            "wapp_fetch_success",
+            "wapp_temperature_celsius",
+            "wapp_humidity_percent",
+            "wapp_wind_speed_meters_per_second",
+            "wapp_pressure_hectopascals",
+        ]
+    );
+    for (_, labels, _) in &samples {
+        assert_eq!(
+            labels,
+            &[
+                ("city".into(), "Kyiv".into()),
+                ("provider".into(), "weatherapi".into())
+            ]
+        );
+    }
+    assert!(text.contains("\nwapp_temperature_celsius{city=\"Kyiv\",provider=\"weatherapi\"} 24\n"));
+    assert_eq!(value(&samples, "wapp_fetch_success", "Kyiv"), 1.0);
+    assert_eq!(value(&samples, "wapp_humidity_percent", "Kyiv"), 36.0);
+    assert_eq!(
+        value(&samples, "wapp_wind_speed_meters_per_second", "Kyiv"),
+        3.11
+    );
+    assert_eq!(
+        value(&samples, "wapp_pressure_hectopascals", "Kyiv"),
+        1017.0
+    );
+}
+
+#[test]
+fn test_labels_are_escaped() {
+    assert_eq!(
+        escape_label("C:\\Temp \"Kyiv\"\nLviv"),
+        "C:\\\\Temp \\\"Kyiv\\\"\\nLviv"
+    );
+
+    let city = "Kyiv \"Center\"\\\nOld Town";
+    let text = render(&[Sample::failed("weatherapi", city)]);
+    let samples = parse_exposition(&text);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].1[0], ("city".into(), city.into()));
+}
+
+#[test]
+fn test_missing_values_are_left_out() {
+    let reading = Sample {
+        city: "Kyiv".into(),
+        provider: "wttr".into(),
+        success: true,
+        current: Some(SeriesPoint {
+            temp_c: Some(-3.5),
+            humidity: Some(f64::NAN),
+            ..SeriesPoint::at(Utc::now())
+        }),
+    };
+
+    let text = render(&[reading]);
+    let samples = parse_exposition(&text);
+    assert_eq!(samples.len(), 2, "{}", text);
+    assert_eq!(value(&samples, "wapp_temperature_celsius", "Kyiv"), -3.5);
+    assert!(!text.contains("humidity"), "{}", text);
+    assert!(!text.contains("NaN"), "{}", text);
+
+    let failed = render(&[Sample::failed("wttr", "Kyiv")]);
+    assert_eq!(
+        failed,
+        "# HELP wapp_fetch_success Whether the last fetch of the location succeeded (1) or failed (0).\n\
+         # TYPE wapp_fetch_success gauge\n\
+         wapp_fetch_success{city=\"Kyiv\",provider=\"wttr\"} 0\n"
+    );
+    assert_eq!(render(&[]), "");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cities_share_the_metric_names() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.base_url)
+        .args(["--city", "Kyiv", "--city", "Lviv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let text = String::from_utf8(output.stdout).unwrap();
+    let samples = parse_exposition(&text);
+    assert_eq!(samples.len(), 10, "{}", text);
+    for city in ["Kyiv", "Lviv"] {
+        assert_eq!(value(&samples, "wapp_temperature_celsius", city), 24.0);
+    }
+    assert_eq!(text.matches("# TYPE wapp_temperature_celsius").count(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_failed_fetch_is_a_sample() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.uri())
+        .args(["--city", "Kyiv", "--retries", "1"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let text = String::from_utf8(output.stdout).unwrap();
+    let samples = parse_exposition(&text);
+    assert_eq!(samples.len(), 1, "{}", text);
+    assert_eq!(value(&samples, "wapp_fetch_success", "Kyiv"), 0.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_several_kinds_are_refused() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.base_url)
+        .args(["--city", "Kyiv", "--data", "now", "--data", "forecast"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--output prometheus takes a single data kind"),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,242 @@
+mod mocks;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use assert_cmd::Command;
+use clap::Parser;
+use mocks::http_server::HttpServer;
+use wapp::cache::{Cache, CacheEntry, Namespace};
+use wapp::cli::{Cli, Commands};
+use wapp::condition::Condition;
+use wapp::output::color::{Icons, Palette};
+use wapp::output::status::stale_key;
+use wapp::prompt::{
+    cached, escape, freshness, lock_refresh, token, Freshness, PromptShell, DEFAULT_MAX_AGE_SECS,
+};
+use wapp::units::Units;
+use wapp::weather::WeatherData;
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+fn wapp(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.env_remove("NO_COLOR")
+        .args(["prompt", "--city", "Kyiv", "--provider", "weatherapi"]);
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn weather(temp_c: f64, condition: Condition) -> WeatherData {
+    WeatherData {
+        temp_c: Some(temp_c),
+        condition_kind: Some(condition),
+        ..Default::default()
+    }
+}
+
+fn emoji(enabled: bool) -> Palette {
+    Palette {
+        enabled,
+        icons: Icons::Emoji,
+    }
+}
+
+/// Keeps `body` as the weather of Kyiv cached at `created`.
+fn cache_kyiv(cache: &Cache, body: &str, created: u64) {
+    cache
+        .put(&CacheEntry {
+            namespace: Namespace::Stale,
+            key: stale_key("weatherapi", "now", "Kyiv"),
+            provider: "weatherapi".into(),
+            city: "Kyiv".into(),
+            created,
+            expires: None,
+            body: body.into(),
+            validators: Default::default(),
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_freshness_against_the_maximum_age() {
+    let now = 1_700_000_000;
+    let max_age = 30 * 60;
+
+    assert_eq!(freshness(None, now, max_age), Freshness::Missing);
+    assert_eq!(freshness(Some(now), now, max_age), Freshness::Fresh);
+    assert_eq!(
+        freshness(Some(now - max_age), now, max_age),
+        Freshness::Fresh
+    );
+    assert_eq!(
+        freshness(Some(now - max_age - 1), now, max_age),
+        Freshness::Stale
+    );
+    // Cached "later", after the clock was set back.
+    assert_eq!(freshness(Some(now + 60), now, max_age), Freshness::Fresh);
+    assert_eq!(freshness(Some(now - 1), now, 0), Freshness::Stale);
+}
+
+#[test]
+fn test_cached_reads_only_fresh_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path());
+    let now = 1_700_000_000;
+
+    assert_eq!(
+        cached(&cache, "weatherapi", "kyiv", now, 60),
+        (Freshness::Missing, None)
+    );
+
+    cache_kyiv(&cache, "{}", now - 120);
+    assert_eq!(
+        cached(&cache, "weatherapi", "kyiv", now, 60),
+        (Freshness::Stale, None)
+    );
+    assert_eq!(
+        cached(&cache, "weatherapi", "KYIV", now, 300),
+        (Freshness::Fresh, Some("{}".to_string()))
+    );
+    assert_eq!(
+        cached(&cache, "openmeteo", "Kyiv", now, 300).0,
+        Freshness::Missing
+    );
+}
+
+#[test]
+fn test_token_is_a_glyph_and_whole_degrees() {
+    let plain = Palette::PLAIN.with_icons(Icons::Emoji);
+    assert_eq!(
+        token(&weather(21.4, Condition::Clear), Units::Metric, plain).unwrap(),
+        "☀️21°"
+    );
+    assert_eq!(
+        token(&weather(21.4, Condition::Clear), Units::Imperial, plain).unwrap(),
+        "☀️71°"
+    );
+    assert_eq!(
+        token(&weather(-0.3, Condition::Snow), Units::Metric, plain).unwrap(),
+        format!("{}0°", Condition::Snow.emoji())
+    );
+    let ascii = Palette::PLAIN.with_icons(Icons::Ascii);
+    assert_eq!(
+        token(&weather(21.4, Condition::Clear), Units::Metric, ascii).unwrap(),
+        format!("{}21°", Condition::Clear.ascii())
+    );
+    assert_eq!(token(&WeatherData::default(), Units::Metric, plain), None);
+
+    // Cold and hot temperatures are colored.
+    assert_eq!(
+        token(&weather(-7.0, Condition::Snow), Units::Metric, emoji(true)).unwrap(),
+        format!("{}\x1b[34m-7°\x1b[0m", Condition::Snow.emoji())
+    );
+}
+
+#[test]
+fn test_escape_marks_codes_for_each_shell() {
+    let colored = "☀️\x1b[31m31°\x1b[0m";
+
+    assert_eq!(
+        escape(colored, PromptShell::Zsh),
+        "☀️%{\x1b[31m%}31°%{\x1b[0m%}"
+    );
+    assert_eq!(
+        escape(colored, PromptShell::Bash),
+        "☀️\x01\x1b[31m\x0231°\x01\x1b[0m\x02"
+    );
+    assert_eq!(escape(colored, PromptShell::Fish), colored);
+
+    // Without codes, only zsh's `%` needs escaping.
+    assert_eq!(escape("☀️21°", PromptShell::Bash), "☀️21°");
+    assert_eq!(escape("50%", PromptShell::Zsh), "50%%");
+    assert_eq!(escape("50%", PromptShell::Bash), "50%");
+}
+
+#[test]
+fn test_one_refresh_at_a_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path().join("cache"));
+
+    let held = lock_refresh(&cache, "weatherapi", "Kyiv").unwrap();
+    assert!(held.is_some());
+    assert!(lock_refresh(&cache, "weatherapi", "kyiv")
+        .unwrap()
+        .is_none());
+    // Other cities are refreshed meanwhile.
+    assert!(lock_refresh(&cache, "weatherapi", "Lviv")
+        .unwrap()
+        .is_some());
+
+    drop(held);
+    assert!(lock_refresh(&cache, "weatherapi", "Kyiv")
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fresh_cache_is_printed_without_a_request() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path().join("cache"));
+    cache_kyiv(&cache, WEATHERAPI_CURRENT, wapp::cache::now_secs() - 60);
+
+    assert_eq!(stdout(&mut wapp(dir.path(), &server.base_url)), "☀️24°");
+    assert_eq!(
+        stdout(wapp(dir.path(), &server.base_url).args(["--shell", "zsh"])),
+        "☀️24°"
+    );
+    assert_eq!(server.requests(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_missing_cache_prints_nothing_and_refreshes_in_the_background() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    assert_eq!(stdout(&mut wapp(dir.path(), &server.base_url)), "");
+
+    // The next prompt has the weather once the refresh is done.
+    let started = Instant::now();
+    let mut shown = String::new();
+    while shown.is_empty() && started.elapsed() < Duration::from_secs(20) {
+        std::thread::sleep(Duration::from_millis(100));
+        shown = stdout(&mut wapp(dir.path(), &server.base_url));
+    }
+    assert_eq!(shown, "☀️24°");
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_stale_cache_prints_nothing() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path().join("cache"));
+    cache_kyiv(&cache, WEATHERAPI_CURRENT, wapp::cache::now_secs() - 3600);
+
+    assert_eq!(stdout(&mut wapp(dir.path(), &server.base_url)), "");
+    assert_eq!(
+        stdout(wapp(dir.path(), &server.base_url).args(["--max-age", "2h"])),
+        "☀️24°"
+    );
+}
+
+#[test]
+fn test_prompt_flags() {
+    let cli = Cli::parse_from(["wapp", "prompt", "--city", "Kyiv", "--shell", "bash"]);
+    match cli.cmd {
+        Commands::Prompt { max_age, shell, .. } => {
+            assert_eq!(max_age, DEFAULT_MAX_AGE_SECS);
+            assert_eq!(shell, Some(PromptShell::Bash));
+        }
+        _ => panic!("expected prompt"),
+    }
+
+    assert!(Cli::try_parse_from(["wapp", "prompt", "--shell", "tcsh"]).is_err());
+    assert!(Cli::try_parse_from(["wapp", "prompt", "--max-age", "soon"]).is_err());
+}
@@ -0,0 +1,213 @@
+mod mocks;
+
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::{openweather, weatherapi};
+use wapp::error::WappError;
+use wapp::location::Location;
+use wapp::providers::{is_unavailable, openweather, weatherapi, ApiProvider, DataKind};
+use wapp::request::{execute_with_fallback, WeatherRequest};
+
+fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref(), Some(WappError::CityNotFound { .. }))
+}
+
+fn reply(status: u16, body: &'static str) -> Vec<Reply> {
+    vec![Reply {
+        status,
+        headers: Vec::new(),
+        body,
+    }]
+}
+
+/// The error of a `now` request for Atlantis answered with `status` and `body`.
+async fn error_of(
+    make: fn(&str) -> Box<dyn ApiProvider>,
+    status: u16,
+    body: &'static str,
+) -> anyhow::Error {
+    let server = HttpServer::start_replies(reply(status, body)).await;
+
+    make(&server.base_url)
+        .get_data(Location::from("Atlantis"), DataKind::Now, None)
+        .await
+        .unwrap_err()
+}
+
+fn boxed_weatherapi(base_url: &str) -> Box<dyn ApiProvider> {
+    Box::new(weatherapi(base_url))
+}
+
+fn boxed_openweather(base_url: &str) -> Box<dyn ApiProvider> {
+    Box::new(openweather(base_url))
+}
+
+#[tokio::test]
+async fn test_weatherapi_error_bodies() {
+    let err = error_of(
+        boxed_weatherapi,
+        400,
+        r#"{"error":{"code":1007,"message":"Parameter q is missing."}}"#,
+    )
+    .await;
+    let error = err.downcast_ref::<WappError>().unwrap();
+    assert!(matches!(error, WappError::Http { status: 400, .. }));
+    assert_eq!(
+        error.to_string(),
+        "WeatherAPI error 1007: Parameter q is missing"
+    );
+
+    let err = error_of(
+        boxed_weatherapi,
+        401,
+        r#"{"error":{"code":2006,"message":"API key is invalid."}}"#,
+    )
+    .await;
+    assert_eq!(err.to_string(), "WeatherAPI error 2006: API key is invalid");
+
+    let err = error_of(boxed_weatherapi, 404, "<html>Not Found</html>").await;
+    assert_eq!(err.to_string(), "'weatherapi' answered HTTP 404");
+    assert!(!is_unavailable(&err));
+}
+
+#[tokio::test]
+async fn test_openweather_error_bodies() {
+    let err = error_of(
+        boxed_openweather,
+        401,
+        r#"{"cod":401, "message":"Invalid API key. Please see https://openweathermap.org/faq#error401 for more info."}"#,
+    )
+    .await;
+    let error = err.downcast_ref::<WappError>().unwrap();
+    assert!(matches!(error, WappError::Http { status: 401, .. }));
+    assert!(error
+        .to_string()
+        .starts_with("OpenWeatherMap error 401: Invalid API key."));
+
+    let err = error_of(
+        boxed_openweather,
+        400,
+        r#"{"cod":"400","message":"Nothing to geocode"}"#,
+    )
+    .await;
+    assert_eq!(
+        err.to_string(),
+        "OpenWeatherMap error 400: Nothing to geocode"
+    );
+    assert!(!is_unavailable(&err));
+}
+
+#[tokio::test]
+async fn test_unknown_cities_are_not_found() {
+    let err = error_of(boxed_weatherapi, 400, NO_MATCH).await;
+    let Some(WappError::CityNotFound { provider, .. }) = err.downcast_ref() else {
+        panic!("not a CityNotFound: {err:#}");
+    };
+    assert_eq!(provider, "weatherapi");
+    assert_eq!(err.to_string(), "City 'Atlantis' not found");
+
+    let err = error_of(
+        boxed_openweather,
+        404,
+        r#"{"cod":"404","message":"city not found"}"#,
+    )
+    .await;
+    assert!(is_not_found(&err));
+    assert!(!is_unavailable(&err));
+
+    let err = error_of(boxed_weatherapi, 404, "<html>Not Found</html>").await;
+    assert!(!is_not_found(&err));
+}
+
+const NO_MATCH: &str = r#"{"error":{"code":1006,"message":"No matching location found."}}"#;
+
+#[tokio::test]
+async fn test_not_found_suggests_close_matches() {
+    let search = r#"[
+        {"name":"London","region":"City of London, Greater London","country":"United Kingdom","lat":51.52,"lon":-0.11},
+        {"name":"London","region":"Ontario","country":"Canada","lat":42.98,"lon":-81.25},
+        {"name":"Loudon","region":"Tennessee","country":"USA","lat":35.73,"lon":-84.33},
+        {"name":"Londonderry","region":"Londonderry","country":"United Kingdom","lat":55.0,"lon":-7.32},
+        {"name":"Londrina","region":"Parana","country":"Brazil","lat":-23.3,"lon":-51.15}
+    ]"#;
+    let server = HttpServer::start_replies(vec![
+        Reply {
+            status: 400,
+            headers: Vec::new(),
+            body: NO_MATCH,
+        },
+        Reply::ok(search),
+    ])
+    .await;
+    let providers: Vec<Box<dyn ApiProvider>> = vec![Box::new(weatherapi(&server.base_url))];
+
+    let request = WeatherRequest::at(Location::from("Lodnon"), DataKind::Now, None);
+    let err = execute_with_fallback(&providers, request)
+        .await
+        .err()
+        .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "City 'Lodnon' not found; did you mean: London, Loudon, Londonderry?"
+    );
+    assert!(server.received()[1].starts_with("get /search.json?key=key&q=lodnon"));
+}
+
+#[tokio::test]
+async fn test_failed_suggestions_keep_the_not_found_error() {
+    let server = HttpServer::start_replies(reply(500, "busy")).await;
+    let providers: Vec<Box<dyn ApiProvider>> = vec![Box::new(weatherapi(&server.base_url))];
+    let request = WeatherRequest::at(Location::from("Lodnon"), DataKind::Now, None);
+
+    // The search is the only request a server error can fail here.
+    let err = wapp::providers::suggest_locations(
+        providers[0].as_ref(),
+        WappError::CityNotFound {
+            provider: "weatherapi".into(),
+            location: request.location,
+            suggestions: Vec::new(),
+        }
+        .into(),
+    )
+    .await;
+
+    assert!(is_not_found(&err));
+    assert_eq!(err.to_string(), "City 'Lodnon' not found");
+    // The search was retried as any request, then given up.
+    assert_eq!(server.requests(), 3);
+}
+
+#[tokio::test]
+async fn test_server_errors_leave_the_provider_unavailable() {
+    for make in [boxed_weatherapi, boxed_openweather] {
+        let err = error_of(make, 500, r#"{"cod":500,"message":"Internal error"}"#).await;
+
+        assert!(is_unavailable(&err));
+        assert!(err.to_string().ends_with("answered HTTP 500"));
+    }
+}
+
+#[tokio::test]
+async fn test_successful_responses_are_returned() {
+    let server = HttpServer::start(r#"{"current":{"temp_c":1.0}}"#).await;
+
+    let body = weatherapi(&server.base_url)
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, r#"{"current":{"temp_c":1.0}}"#);
+}
+
+#[test]
+fn test_error_messages() {
+    assert_eq!(
+        weatherapi::error_message(r#"{"error":{"message":"Parameter q is missing."}}"#),
+        Some("WeatherAPI error: Parameter q is missing".into())
+    );
+    assert_eq!(weatherapi::error_message(r#"{"current":{}}"#), None);
+    assert_eq!(
+        openweather::error_message(r#"{"message":"Nothing to geocode"}"#),
+        Some("OpenWeatherMap error: Nothing to geocode".into())
+    );
+    assert_eq!(openweather::error_message("not json"), None);
+}
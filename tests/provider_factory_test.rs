@@ -1,33 +1,311 @@
-use wapp::config::AppConfig;
-use wapp::providers::provider_factory;
+use wapp::cli::{render_providers, ListFormat};
+use wapp::config::{load_config_for, AppConfig};
+use wapp::providers::{
+    available_providers, provider_factory, provider_factory_with, provider_statuses, EnvSource,
+    ProviderKind, ProviderOverrides, ProviderStatus,
+};
+
+/// Overrides with exactly the environment variables `vars`.
+fn with_env(vars: &[(&str, &str)]) -> ProviderOverrides {
+    ProviderOverrides {
+        env: EnvSource::from_vars(vars.iter().copied()),
+        ..Default::default()
+    }
+}
 
 #[test]
 fn test_weatherapi_provider_exists() {
-    std::env::set_var("WEATHERAPI_KEY", "dummy");
-
     let cfg = AppConfig {
-        provider: "weatherapi".into(),
+        provider: ProviderKind::WeatherApi,
+        ..Default::default()
     };
+    let overrides = with_env(&[("WEATHERAPI_KEY", "dummy")]);
 
-    assert!(provider_factory(&cfg).is_ok());
+    assert!(provider_factory_with(&cfg, &overrides).is_ok());
 }
 
 #[test]
 fn test_openweather_provider_exists() {
-    std::env::set_var("OPENWEATHER_KEY", "dummy");
+    let cfg = AppConfig {
+        provider: ProviderKind::OpenWeather,
+        ..Default::default()
+    };
+    let overrides = with_env(&[("OPENWEATHER_KEY", "dummy")]);
 
+    assert!(provider_factory_with(&cfg, &overrides).is_ok());
+}
+
+#[test]
+fn test_accuweather_provider_exists() {
     let cfg = AppConfig {
-        provider: "openweather".into(),
+        provider: ProviderKind::AccuWeather,
+        ..Default::default()
     };
+    let overrides = with_env(&[("ACCUWEATHER_KEY", "dummy")]);
 
-    assert!(provider_factory(&cfg).is_ok());
+    assert_eq!(
+        provider_factory_with(&cfg, &overrides).unwrap().name(),
+        "accuweather"
+    );
+}
+
+#[test]
+fn test_openmeteo_provider_needs_no_key() {
+    let cfg = AppConfig {
+        provider: ProviderKind::OpenMeteo,
+        ..Default::default()
+    };
+
+    assert_eq!(provider_factory(&cfg).unwrap().name(), "openmeteo");
+}
+
+#[test]
+fn test_wttr_provider_needs_no_key() {
+    let cfg = AppConfig {
+        provider: ProviderKind::Wttr,
+        ..Default::default()
+    };
+
+    assert_eq!(provider_factory(&cfg).unwrap().name(), "wttr");
+}
+
+#[test]
+fn test_metno_provider_needs_no_key() {
+    let cfg = AppConfig {
+        provider: ProviderKind::MetNo,
+        ..Default::default()
+    };
+
+    assert_eq!(provider_factory(&cfg).unwrap().name(), "metno");
 }
 
 #[test]
 fn test_invalid_provider() {
+    let err = serde_json::from_str::<AppConfig>(r#"{"provider":"unknown"}"#)
+        .err()
+        .unwrap();
+
+    assert!(err
+        .to_string()
+        .starts_with("provider 'unknown' is not supported. Supported providers: weatherapi,"));
+}
+
+#[test]
+fn test_provider_names_ignore_case_and_accept_aliases() {
+    for (name, kind) in [
+        ("OpenWeather", ProviderKind::OpenWeather),
+        ("owm", ProviderKind::OpenWeather),
+        ("OpenWeatherMap", ProviderKind::OpenWeather),
+        (" wapi ", ProviderKind::WeatherApi),
+        ("wttr.in", ProviderKind::Wttr),
+    ] {
+        assert_eq!(name.parse::<ProviderKind>().unwrap(), kind, "{}", name);
+    }
+
+    for kind in ProviderKind::ALL {
+        assert_eq!(kind.to_string().parse::<ProviderKind>().unwrap(), kind);
+    }
+}
+
+#[test]
+fn test_unknown_provider_suggests_closest() {
+    let err = "openwether".parse::<ProviderKind>().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("provider 'openwether' is not supported; did you mean 'openweather'?"));
+
+    let err = "wtr.in".parse::<ProviderKind>().unwrap_err();
+    assert!(err.to_string().contains("did you mean 'wttr'?"));
+
+    let err = "darksky".parse::<ProviderKind>().unwrap_err();
+    assert!(!err.to_string().contains("did you mean"));
+}
+
+#[test]
+fn test_config_keeps_canonical_provider_names() {
+    let cfg: AppConfig =
+        serde_json::from_str(r#"{"provider":"OWM","fallback_providers":["wttr"]}"#).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::OpenWeather);
+
+    let json = serde_json::to_string(&cfg).unwrap();
+    assert!(json.contains(r#""provider":"openweather""#));
+    assert!(json.contains(r#""fallback_providers":["wttr"]"#));
+}
+
+#[test]
+fn test_available_providers_skip_missing_keys() {
+    std::env::remove_var("VISUALCROSSING_KEY");
+    std::env::remove_var("VISUALCROSSING_KEY_FILE");
+
+    let (providers, skipped) = available_providers(
+        &AppConfig::default(),
+        &[
+            ProviderKind::OpenMeteo,
+            ProviderKind::VisualCrossing,
+            ProviderKind::Wttr,
+        ],
+    );
+
+    let names: Vec<&str> = providers.iter().map(|p| p.name()).collect();
+    assert_eq!(names, vec!["openmeteo", "wttr"]);
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].provider, "visualcrossing");
+    assert!(skipped[0].reason.contains("VISUALCROSSING_KEY"));
+}
+
+#[test]
+fn test_provider_flag_works_without_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("config.json");
+    std::env::set_var("WAPP_CONFIG", &missing);
+
+    let cfg = load_config_for(Some(ProviderKind::Wttr)).unwrap();
+    assert_eq!(cfg.provider, ProviderKind::Wttr);
+    assert_eq!(provider_factory(&cfg).unwrap().name(), "wttr");
+
+    let err = load_config_for(None).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{} not found. Run: wapp configure <provider>, or set WAPP_PROVIDER",
+            missing.display()
+        )
+    );
+}
+
+#[test]
+fn test_provider_statuses() {
+    std::env::remove_var("TOMORROWIO_KEY");
+    std::env::remove_var("TOMORROWIO_KEY_FILE");
+
+    let cfg = AppConfig {
+        provider: ProviderKind::Wttr,
+        ..Default::default()
+    };
+    let statuses = provider_statuses(Some(&cfg));
+
+    assert_eq!(
+        statuses
+            .iter()
+            .map(|s| s.provider.as_str())
+            .collect::<Vec<_>>(),
+        ProviderKind::NAMES
+    );
+
+    let tomorrowio = &statuses[5];
+    assert!(!tomorrowio.available && !tomorrowio.configured);
+    assert_eq!(tomorrowio.missing_env, vec!["TOMORROWIO_KEY"]);
+
+    let wttr = &statuses[7];
+    assert!(wttr.available && wttr.configured && wttr.missing_env.is_empty());
+    assert_eq!(wttr.error, None);
+
+    assert!(provider_statuses(None).iter().all(|s| !s.configured));
+}
+
+#[test]
+fn test_provider_info_lists_key_variables() {
+    for kind in ProviderKind::ALL {
+        let info = kind.info();
+        assert_eq!(info.kind, kind);
+        assert!(!info.description.is_empty());
+        assert!(info.required_env.iter().all(|var| var.ends_with("_KEY")));
+    }
+    assert!(ProviderKind::OpenMeteo.info().required_env.is_empty());
+}
+
+fn status(provider: &str, configured: bool, missing: &[&str]) -> ProviderStatus {
+    ProviderStatus {
+        provider: provider.into(),
+        description: format!("{} forecasts", provider),
+        configured,
+        available: missing.is_empty(),
+        missing_env: missing.iter().map(|var| var.to_string()).collect(),
+        error: (!missing.is_empty()).then(|| "not set".into()),
+        quota: None,
+    }
+}
+
+#[test]
+fn test_render_providers() {
+    let statuses = vec![
+        status("weatherapi", true, &[]),
+        status("tomorrowio", false, &["TOMORROWIO_KEY"]),
+    ];
+
+    assert_eq!(
+        render_providers(&statuses, ListFormat::Text),
+        "* weatherapi  ready                   weatherapi forecasts\n  \
+         tomorrowio  missing TOMORROWIO_KEY  tomorrowio forecasts\n"
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_str(&render_providers(&statuses, ListFormat::Json)).unwrap();
+    assert_eq!(json[0]["configured"], true);
+    assert_eq!(json[0].get("error"), None);
+    assert_eq!(json[1]["missing_env"][0], "TOMORROWIO_KEY");
+    assert_eq!(json[1]["error"], "not set");
+}
+
+#[test]
+fn test_saved_units_and_lang_come_after_flags_and_env() {
     let cfg = AppConfig {
-        provider: "unknown".into(),
+        provider: ProviderKind::OpenWeather,
+        units: Some("imperial".into()),
+        lang: Some("uk".into()),
+        ..Default::default()
+    };
+    let url = |overrides: &ProviderOverrides| {
+        provider_factory_with(&cfg, overrides)
+            .unwrap()
+            .verify_url()
+            .unwrap()
     };
 
-    assert!(provider_factory(&cfg).is_err());
+    // The config applies when nothing else is set, as OpenWeatherMap's code.
+    let saved = url(&with_env(&[("OPENWEATHER_KEY", "dummy")]));
+    assert!(saved.contains("&units=imperial&lang=ua"), "{}", saved);
+
+    // The environment beats the config...
+    let env = with_env(&[
+        ("OPENWEATHER_KEY", "dummy"),
+        ("OPENWEATHER_UNITS", "standard"),
+        ("OPENWEATHER_LANG", "de"),
+    ]);
+    let from_env = url(&env);
+    assert!(from_env.contains("&units=standard&lang=de"), "{}", from_env);
+
+    // ...and flags beat both.
+    let flags = url(&ProviderOverrides {
+        units: Some("metric".into()),
+        lang: Some("es".into()),
+        ..env
+    });
+    assert!(flags.contains("&units=metric&lang=es"), "{}", flags);
+}
+
+#[test]
+fn test_given_environment_replaces_the_process_one() {
+    let cfg = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        ..Default::default()
+    };
+    let url = provider_factory_with(
+        &cfg,
+        &with_env(&[
+            ("WEATHERAPI_KEY", "dummy"),
+            ("WEATHERAPI_BASE_URL", "http://given.invalid/v1"),
+            ("LANG", "de_DE.UTF-8"),
+        ]),
+    )
+    .unwrap()
+    .verify_url()
+    .unwrap();
+    assert!(
+        url.starts_with("http://given.invalid/v1/current.json?"),
+        "{}",
+        url
+    );
+    // The language is detected from the given locale too.
+    assert!(url.contains("&lang=de"), "{}", url);
 }
@@ -0,0 +1,438 @@
+mod mocks;
+
+use mocks::http_server::fast_retries;
+use wapp::config::AppConfig;
+use wapp::error::WappError;
+use wapp::location::Location;
+use wapp::providers::{
+    is_unavailable, provider_factory_with, ApiProvider, DataKind, EnvSource, OpenWeatherProvider,
+    ProviderKind, ProviderOverrides, WeatherApiProvider,
+};
+use wapp::retry::retry_policy;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+const OPENWEATHER_CURRENT: &str = include_str!("fixtures/openweather_current.json");
+const OPENWEATHER_FORECAST: &str = include_str!("fixtures/openweather_forecast.json");
+
+/// A server answering `GET route` with `body`, expected to be asked once.
+async fn serve(route: &str, body: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .respond_with(ResponseTemplate::new(200).set_body_string(body))
+        .expect(1)
+        .mount(&server)
+        .await;
+    server
+}
+
+/// The path and query of the only request `server` received.
+async fn only_request(server: &MockServer) -> (String, String) {
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1, "{:?}", requests);
+
+    let url = &requests[0].url;
+    (
+        url.path().to_string(),
+        url.query().unwrap_or("").to_string(),
+    )
+}
+
+/// The path and query `provider` sends for `kind` about Kyiv, answered
+/// with `body` at `route`.
+async fn request_of(
+    provider: impl Fn(&str) -> Box<dyn ApiProvider>,
+    route: &str,
+    body: &'static str,
+    kind: DataKind,
+    days: Option<u32>,
+) -> (String, String) {
+    query_of(provider, route, body, "Kyiv", kind, days).await
+}
+
+/// Like [`request_of`], about `city`.
+async fn query_of(
+    provider: impl Fn(&str) -> Box<dyn ApiProvider>,
+    route: &str,
+    body: &'static str,
+    city: &str,
+    kind: DataKind,
+    days: Option<u32>,
+) -> (String, String) {
+    let server = serve(route, body).await;
+
+    provider(&server.uri())
+        .get_data(Location::from(city), kind, days)
+        .await
+        .unwrap();
+    only_request(&server).await
+}
+
+/// The error of a `now` request of `provider` answered with `status` and
+/// `body`, and how many requests the server received.
+async fn error_of(
+    provider: impl Fn(&str) -> Box<dyn ApiProvider>,
+    status: u16,
+    body: &'static str,
+) -> (anyhow::Error, usize) {
+    fast_retries();
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(status).set_body_string(body))
+        .mount(&server)
+        .await;
+
+    let err = provider(&server.uri())
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    (err, server.received_requests().await.unwrap().len())
+}
+
+/// The configured `kind` provider with `overrides`, sending its requests
+/// to `base_url` through `var` and authenticating with `key_var`.
+fn configured(
+    kind: ProviderKind,
+    base_url: &str,
+    var: &str,
+    key_var: &str,
+    overrides: ProviderOverrides,
+) -> Box<dyn ApiProvider> {
+    let cfg = AppConfig {
+        provider: kind,
+        ..Default::default()
+    };
+    let env = EnvSource::from_vars([(var, base_url), (key_var, "KEY")]);
+    provider_factory_with(&cfg, &ProviderOverrides { env, ..overrides }).unwrap()
+}
+
+fn weatherapi(base_url: &str) -> Box<dyn ApiProvider> {
+    Box::new(
+        WeatherApiProvider::new("KEY")
+            .with_base_url(format!("{}/v1", base_url))
+            .with_lang("uk"),
+    )
+}
+
+fn openweather(base_url: &str) -> Box<dyn ApiProvider> {
+    Box::new(
+        OpenWeatherProvider::new("KEY")
+            .with_base_url(format!("{}/data/2.5", base_url))
+            .with_units("metric")
+            .with_lang("ua"),
+    )
+}
+
+#[tokio::test]
+async fn test_weatherapi_now_request() {
+    let request = request_of(
+        weatherapi,
+        "/v1/current.json",
+        WEATHERAPI_CURRENT,
+        DataKind::Now,
+        None,
+    )
+    .await;
+
+    assert_eq!(
+        request,
+        ("/v1/current.json".into(), "key=KEY&q=Kyiv&lang=uk".into())
+    );
+}
+
+#[tokio::test]
+async fn test_weatherapi_forecast_request() {
+    let request = request_of(
+        weatherapi,
+        "/v1/forecast.json",
+        WEATHERAPI_FORECAST,
+        DataKind::Forecast,
+        Some(5),
+    )
+    .await;
+
+    assert_eq!(
+        request,
+        (
+            "/v1/forecast.json".into(),
+            "key=KEY&q=Kyiv&days=5&lang=uk".into()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_weatherapi_tomorrow_request() {
+    let request = request_of(
+        weatherapi,
+        "/v1/forecast.json",
+        WEATHERAPI_FORECAST,
+        DataKind::Tomorrow,
+        None,
+    )
+    .await;
+
+    assert_eq!(
+        request,
+        (
+            "/v1/forecast.json".into(),
+            "key=KEY&q=Kyiv&days=2&lang=uk".into()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_weatherapi_tomorrow_keeps_the_day_after_the_city_date() {
+    let server = serve("/v1/forecast.json", WEATHERAPI_FORECAST).await;
+
+    // The explicit length of a forecast does not change tomorrow.
+    let body = weatherapi(&server.uri())
+        .get_data(Location::from("Kyiv"), DataKind::Tomorrow, Some(5))
+        .await
+        .unwrap();
+
+    let (_, query) = only_request(&server).await;
+    assert!(query.contains("&days=2&"), "{}", query);
+
+    // The fixture's `location.localtime` is 2024-06-10.
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let dates: Vec<&str> = json["forecast"]["forecastday"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|day| day["date"].as_str().unwrap())
+        .collect();
+    assert_eq!(dates, vec!["2024-06-11"]);
+}
+
+#[tokio::test]
+async fn test_weatherapi_encodes_cities() {
+    let (_, query) = query_of(
+        weatherapi,
+        "/v1/current.json",
+        WEATHERAPI_CURRENT,
+        "New York",
+        DataKind::Now,
+        None,
+    )
+    .await;
+    assert_eq!(query, "key=KEY&q=New%20York&lang=uk");
+
+    let (_, query) = query_of(
+        weatherapi,
+        "/v1/current.json",
+        WEATHERAPI_CURRENT,
+        "Київ",
+        DataKind::Now,
+        None,
+    )
+    .await;
+    assert_eq!(query, "key=KEY&q=%D0%9A%D0%B8%D1%97%D0%B2&lang=uk");
+}
+
+#[tokio::test]
+async fn test_openweather_now_request() {
+    let request = request_of(
+        openweather,
+        "/data/2.5/weather",
+        OPENWEATHER_CURRENT,
+        DataKind::Now,
+        None,
+    )
+    .await;
+
+    assert_eq!(
+        request,
+        (
+            "/data/2.5/weather".into(),
+            "q=Kyiv&appid=KEY&units=metric&lang=ua".into()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_openweather_forecast_request() {
+    let request = request_of(
+        openweather,
+        "/data/2.5/forecast",
+        OPENWEATHER_FORECAST,
+        DataKind::Forecast,
+        Some(2),
+    )
+    .await;
+
+    assert_eq!(
+        request,
+        (
+            "/data/2.5/forecast".into(),
+            "q=Kyiv&appid=KEY&cnt=16&units=metric&lang=ua".into()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_openweather_tomorrow_request() {
+    let request = request_of(
+        openweather,
+        "/data/2.5/forecast",
+        OPENWEATHER_FORECAST,
+        DataKind::Tomorrow,
+        None,
+    )
+    .await;
+
+    assert_eq!(
+        request,
+        (
+            "/data/2.5/forecast".into(),
+            "q=Kyiv&appid=KEY&units=metric&lang=ua".into()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_openweather_encodes_cities() {
+    let (_, query) = query_of(
+        openweather,
+        "/data/2.5/weather",
+        OPENWEATHER_CURRENT,
+        "São Paulo",
+        DataKind::Now,
+        None,
+    )
+    .await;
+    assert_eq!(query, "q=S%C3%A3o%20Paulo&appid=KEY&units=metric&lang=ua");
+
+    let (_, query) = query_of(
+        openweather,
+        "/data/2.5/weather",
+        OPENWEATHER_CURRENT,
+        "Rio de Janeiro,BR",
+        DataKind::Now,
+        None,
+    )
+    .await;
+    assert!(query.starts_with("q=Rio%20de%20Janeiro%2CBR&"), "{}", query);
+}
+
+#[tokio::test]
+async fn test_lang_and_units_reach_the_request() {
+    let overrides = || ProviderOverrides {
+        units: Some("imperial".into()),
+        lang: Some("uk".into()),
+        ..Default::default()
+    };
+
+    // WeatherAPI answers in both unit systems, so only the language is sent.
+    let (_, query) = request_of(
+        |url| {
+            configured(
+                ProviderKind::WeatherApi,
+                url,
+                "WEATHERAPI_BASE_URL",
+                "WEATHERAPI_KEY",
+                overrides(),
+            )
+        },
+        "/current.json",
+        WEATHERAPI_CURRENT,
+        DataKind::Now,
+        None,
+    )
+    .await;
+    assert_eq!(query, "key=KEY&q=Kyiv&lang=uk");
+
+    // OpenWeatherMap knows Ukrainian as `ua`.
+    let (_, query) = request_of(
+        |url| {
+            configured(
+                ProviderKind::OpenWeather,
+                &format!("{}/data/2.5", url),
+                "OPENWEATHER_BASE_URL",
+                "OPENWEATHER_KEY",
+                overrides(),
+            )
+        },
+        "/data/2.5/forecast",
+        OPENWEATHER_FORECAST,
+        DataKind::Forecast,
+        Some(1),
+    )
+    .await;
+    assert_eq!(query, "q=Kyiv&appid=KEY&cnt=8&units=imperial&lang=ua");
+}
+
+#[tokio::test]
+async fn test_client_error_bodies() {
+    let (err, requests) = error_of(
+        weatherapi,
+        403,
+        r#"{"error":{"code":2008,"message":"API key has been disabled."}}"#,
+    )
+    .await;
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::Http { status: 403, body, .. }) if body.contains("2008")
+    ));
+    assert_eq!(
+        err.to_string(),
+        "WeatherAPI error 2008: API key has been disabled"
+    );
+    // A mistake in the request is not repeated.
+    assert_eq!(requests, 1);
+
+    let (err, _) = error_of(
+        openweather,
+        401,
+        r#"{"cod":401,"message":"Invalid API key."}"#,
+    )
+    .await;
+    assert_eq!(
+        err.to_string(),
+        "OpenWeatherMap error 401: Invalid API key."
+    );
+
+    // A body without a message names the status.
+    let (err, _) = error_of(openweather, 400, "<html>Bad Request</html>").await;
+    assert_eq!(err.to_string(), "'openweather' answered HTTP 400");
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried() {
+    let (err, requests) = error_of(weatherapi, 502, "<html>Bad Gateway</html>").await;
+
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::Network {
+            unreachable: false,
+            ..
+        })
+    ));
+    assert!(is_unavailable(&err));
+    assert_eq!(err.to_string(), "'weatherapi' answered HTTP 502");
+    assert_eq!(requests, retry_policy().attempts as usize);
+}
+
+#[test]
+fn test_constructors_read_no_environment() {
+    std::env::set_var("OPENWEATHER_UNITS", "imperial");
+    std::env::set_var("OPENWEATHER_BASE_URL", "http://env.invalid");
+
+    let provider = OpenWeatherProvider::new("KEY");
+    assert_eq!(provider.base_url, "https://api.openweathermap.org/data/2.5");
+    assert_eq!(
+        provider.onecall_url,
+        "https://api.openweathermap.org/data/3.0"
+    );
+    assert_eq!(provider.units, None);
+    assert_eq!(provider.lang, None);
+
+    let provider = provider.with_base_url("http://127.0.0.1:8080/data/3.0/");
+    assert_eq!(provider.base_url, "http://127.0.0.1:8080/data/2.5");
+    assert_eq!(provider.onecall_url, "http://127.0.0.1:8080/data/3.0");
+
+    std::env::remove_var("OPENWEATHER_UNITS");
+    std::env::remove_var("OPENWEATHER_BASE_URL");
+}
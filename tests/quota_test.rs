@@ -0,0 +1,303 @@
+mod mocks;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use assert_cmd::Command;
+use chrono::{NaiveDate, NaiveDateTime};
+use mocks::http_server::HttpServer;
+use wapp::cli::{render_providers, ListFormat};
+use wapp::config::AppConfig;
+use wapp::error::WappError;
+use wapp::exit::exit_code;
+use wapp::providers::{ProviderStatus, RATE_LIMITED_EXIT_CODE};
+use wapp::quota::{is_exhausted, renewal, Quota, QuotaCounter, QuotaUsage, QUOTA_FILE};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// 10 January 2025 at `hour:minute`, local time.
+fn at(hour: u32, minute: u32) -> NaiveDateTime {
+    day(10).and_hms_opt(hour, minute, 0).unwrap()
+}
+
+fn day(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, day).unwrap()
+}
+
+fn quota(dir: &Path, limits: &[(&str, u32)]) -> Quota {
+    let limits: BTreeMap<String, u32> = limits
+        .iter()
+        .map(|(provider, limit)| (provider.to_string(), *limit))
+        .collect();
+    Quota::new(limits, dir)
+}
+
+fn usage(quota: &Quota, provider: &str, now: NaiveDateTime) -> u32 {
+    quota.usage(provider, now).unwrap().used
+}
+
+fn wapp(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.env("WTTR_BASE_URL", base_url);
+    cmd
+}
+
+fn get(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = wapp(dir, base_url);
+    cmd.args(["get", "--city", "Kyiv", "--output", "json"]);
+    cmd
+}
+
+fn write_config(dir: &Path, config: &str) {
+    std::fs::write(dir.join("config.json"), config).unwrap();
+}
+
+#[test]
+fn test_calls_are_counted_per_provider_and_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = [("openweather", 1000), ("weatherapi", 50)];
+    let counting = quota(dir.path(), &limits);
+
+    for _ in 0..3 {
+        counting.spend("openweather", at(9, 0)).unwrap();
+    }
+    counting.spend("weatherapi", at(9, 5)).unwrap();
+
+    // Another process reads the same counter.
+    let later = quota(dir.path(), &limits);
+    assert_eq!(
+        later.usage("openweather", at(18, 0)),
+        Some(QuotaUsage {
+            used: 3,
+            limit: 1000
+        })
+    );
+    assert_eq!(usage(&later, "weatherapi", at(18, 0)), 1);
+
+    let counter = QuotaCounter::load(&dir.path().join(QUOTA_FILE)).unwrap();
+    assert_eq!(counter.date.as_deref(), Some("2025-01-10"));
+    assert_eq!(counter.calls["openweather"], 3);
+}
+
+#[test]
+fn test_count_rolls_over_at_local_midnight() {
+    let dir = tempfile::tempdir().unwrap();
+    let counting = quota(dir.path(), &[("openweather", 2)]);
+
+    counting.spend("openweather", at(23, 58)).unwrap();
+    counting.spend("openweather", at(23, 59)).unwrap();
+    assert!(counting.spend("openweather", at(23, 59)).is_err());
+
+    let midnight = day(11).and_hms_opt(0, 0, 0).unwrap();
+    assert_eq!(usage(&counting, "openweather", midnight), 0);
+    counting.spend("openweather", midnight).unwrap();
+    assert_eq!(usage(&counting, "openweather", midnight), 1);
+    // The day before is not counted any more.
+    assert_eq!(usage(&counting, "openweather", at(23, 59)), 0);
+}
+
+#[test]
+fn test_exhausted_budget_is_refused_until_midnight() {
+    let dir = tempfile::tempdir().unwrap();
+    let counting = quota(dir.path(), &[("openweather", 1)]);
+    counting.spend("openweather", at(8, 0)).unwrap();
+
+    let err = counting.spend("openweather", at(21, 30)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "daily quota for openweather exhausted (renews at 2025-01-11 00:00)"
+    );
+    assert!(matches!(
+        WappError::of(&err),
+        Some(WappError::QuotaExhausted { limit: 1, renews, .. })
+            if *renews == day(11).and_hms_opt(0, 0, 0).unwrap()
+    ));
+    assert!(is_exhausted(&err));
+    assert_eq!(exit_code(&err), Some(RATE_LIMITED_EXIT_CODE));
+
+    // Refused calls are not counted.
+    assert_eq!(usage(&counting, "openweather", at(21, 31)), 1);
+    let end_of_month = day(31).and_hms_opt(12, 0, 0).unwrap();
+    assert_eq!(
+        renewal(end_of_month),
+        NaiveDate::from_ymd_opt(2025, 2, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_providers_without_a_limit_are_not_counted() {
+    let dir = tempfile::tempdir().unwrap();
+    let counting = quota(dir.path(), &[("openweather", 1)]);
+
+    for _ in 0..5 {
+        counting.spend("wttr", at(12, 0)).unwrap();
+    }
+    assert_eq!(counting.usage("wttr", at(12, 0)), None);
+    assert!(!dir.path().join(QUOTA_FILE).exists());
+
+    // A limit of zero refuses every call.
+    let closed = quota(dir.path(), &[("openweather", 0)]);
+    assert!(closed.spend("openweather", at(12, 0)).is_err());
+}
+
+#[test]
+fn test_unreadable_counter_starts_afresh() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join(QUOTA_FILE), "{ not json").unwrap();
+    assert!(QuotaCounter::load(&dir.path().join(QUOTA_FILE)).is_err());
+
+    let counting = quota(dir.path(), &[("openweather", 10)]);
+    assert_eq!(usage(&counting, "openweather", at(7, 0)), 0);
+    counting.spend("openweather", at(7, 0)).unwrap();
+    assert_eq!(usage(&counting, "openweather", at(7, 0)), 1);
+}
+
+#[test]
+fn test_config_quota_section() {
+    let cfg: AppConfig = serde_json::from_str(
+        r#"{ "provider": "openweather", "quota": { "openweather": 1000, "weatherapi": 50 } }"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.quota["openweather"], 1000);
+    assert_eq!(cfg.quota.len(), 2);
+
+    // Unset, it is not written back.
+    assert!(serde_json::to_value(AppConfig::default())
+        .unwrap()
+        .get("quota")
+        .is_none());
+}
+
+#[test]
+fn test_render_providers_with_usage() {
+    let status = |provider: &str, quota| ProviderStatus {
+        provider: provider.into(),
+        description: format!("{} forecasts", provider),
+        configured: false,
+        available: true,
+        missing_env: Vec::new(),
+        error: None,
+        quota,
+    };
+    let statuses = vec![
+        status(
+            "openweather",
+            Some(QuotaUsage {
+                used: 412,
+                limit: 1000,
+            }),
+        ),
+        status("wttr", None),
+    ];
+
+    assert_eq!(
+        render_providers(&statuses, ListFormat::Text),
+        "  openweather  ready, 412/1000 calls today  openweather forecasts\n  \
+         wttr         ready                        wttr forecasts\n"
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&render_providers(&statuses, ListFormat::Json)).unwrap();
+    assert_eq!(
+        json[0]["quota"],
+        serde_json::json!({ "used": 412, "limit": 1000 })
+    );
+    assert_eq!(json[1].get("quota"), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_counts_requests_but_not_cache_hits() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    write_config(
+        dir.path(),
+        r#"{ "provider": "weatherapi", "quota": { "weatherapi": 5 } }"#,
+    );
+
+    for _ in 0..2 {
+        let output = get(dir.path(), &server.base_url).output().unwrap();
+        assert!(output.status.success(), "{:?}", output);
+    }
+    assert_eq!(server.requests(), 1);
+
+    let output = wapp(dir.path(), &server.base_url)
+        .arg("providers")
+        .output()
+        .unwrap();
+    let listed = String::from_utf8(output.stdout).unwrap();
+    let line = listed
+        .lines()
+        .find(|line| line.contains("weatherapi"))
+        .unwrap();
+    assert!(line.contains("ready, 1/5 calls today"), "{}", listed);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_exhausted_quota_fails_or_serves_the_cache() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    write_config(
+        dir.path(),
+        r#"{ "provider": "weatherapi", "quota": { "weatherapi": 1 } }"#,
+    );
+
+    let output = get(dir.path(), &server.base_url).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let output = get(dir.path(), &server.base_url)
+        .arg("--refresh")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(RATE_LIMITED_EXIT_CODE));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("daily quota for weatherapi exhausted (renews at "),
+        "{}",
+        stderr
+    );
+
+    let output = get(dir.path(), &server.base_url)
+        .env_remove("WAPP_LOG")
+        .env_remove("RUST_LOG")
+        .args(["--refresh", "--allow-stale"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["temp_c"], 24.0);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("WARN"), "{}", stderr);
+    assert!(
+        stderr.contains("daily quota for weatherapi exhausted"),
+        "{}",
+        stderr
+    );
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_exhausted_provider_falls_back() {
+    let wttr = HttpServer::start(
+        r#"{
+            "current_condition": [{
+                "localObsDateTime": "2024-05-02 12:30 PM", "observation_time": "10:30 AM",
+                "temp_C": "21"
+            }],
+            "weather": []
+        }"#,
+    )
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+    write_config(
+        dir.path(),
+        r#"{ "provider": "weatherapi", "fallback_providers": ["wttr"], "quota": { "weatherapi": 0 } }"#,
+    );
+
+    let output = get(dir.path(), &wttr.base_url).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["temp_c"], 21.0);
+    assert_eq!(wttr.requests(), 1);
+}
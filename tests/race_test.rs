@@ -0,0 +1,290 @@
+mod mocks;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use mocks::http_server::{fast_retries, HttpServer, Reply};
+use wapp::config::AppConfig;
+use wapp::error::WappError;
+use wapp::exit::{exit_code, NETWORK_EXIT_CODE};
+use wapp::location::Location;
+use wapp::providers::{
+    ApiProvider, Capabilities, DataKind, EnvSource, ProviderKind, ProviderOverrides, WttrProvider,
+    RATE_LIMITED_EXIT_CODE, STANDARD_KINDS,
+};
+use wapp::request::{execute_race, RaceFailed, Strategy, WeatherRequest};
+use wapp::weather::WeatherData;
+use wapp::Client;
+
+const WTTR: &str = r#"{
+    "current_condition": [{
+        "localObsDateTime": "2024-05-02 12:30 PM", "observation_time": "10:30 AM",
+        "temp_C": "21"
+    }],
+    "weather": []
+}"#;
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// A provider answering after `delay` with `answer`, an error message for
+/// `Err`; responses are read as wttr.in's. Finished requests are counted.
+struct Delayed {
+    name: &'static str,
+    delay: Duration,
+    answer: Result<&'static str, &'static str>,
+    finished: Arc<AtomicUsize>,
+}
+
+fn delayed(name: &'static str, millis: u64, answer: Result<&'static str, &'static str>) -> Delayed {
+    Delayed {
+        name,
+        delay: Duration::from_millis(millis),
+        answer,
+        finished: Arc::new(AtomicUsize::new(0)),
+    }
+}
+
+fn wttr() -> WttrProvider {
+    WttrProvider {
+        base_url: "http://127.0.0.1:9".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+#[async_trait]
+impl ApiProvider for Delayed {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 3,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    fn weather(&self, body: &str) -> Option<WeatherData> {
+        wttr().weather(body)
+    }
+
+    async fn get_data(
+        &self,
+        _location: Location,
+        _when: DataKind,
+        _days: Option<u32>,
+    ) -> anyhow::Result<String> {
+        tokio::time::sleep(self.delay).await;
+        self.finished.fetch_add(1, Ordering::SeqCst);
+        self.answer
+            .map(String::from)
+            .map_err(|message| anyhow::anyhow!("{}", message))
+    }
+}
+
+fn now() -> WeatherRequest {
+    WeatherRequest::at(Location::from("Oslo"), DataKind::Now, None)
+}
+
+async fn winner(providers: &[Box<dyn ApiProvider>]) -> String {
+    let (provider, _) = execute_race(providers, now()).await.unwrap();
+    provider.name().to_string()
+}
+
+async fn race_error(providers: &[Box<dyn ApiProvider>]) -> anyhow::Error {
+    execute_race(providers, now())
+        .await
+        .map(|(_, body)| body)
+        .unwrap_err()
+}
+
+#[tokio::test]
+async fn test_fastest_provider_wins() {
+    let slow = delayed("slow", 400, Ok(WTTR));
+    let slow_finished = slow.finished.clone();
+    let providers: Vec<Box<dyn ApiProvider>> = vec![
+        Box::new(slow),
+        Box::new(delayed("fast", 10, Ok(WTTR))),
+        Box::new(delayed("medium", 100, Ok(WTTR))),
+    ];
+
+    let started = Instant::now();
+    assert_eq!(winner(&providers).await, "fast");
+    assert!(started.elapsed() < Duration::from_millis(300));
+
+    // The others are cancelled, not waited for.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(slow_finished.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_failures_of_faster_providers_are_ignored() {
+    let providers: Vec<Box<dyn ApiProvider>> = vec![
+        Box::new(delayed("broken", 5, Err("boom"))),
+        Box::new(delayed("garbled", 10, Ok(r#"{"unexpected":true}"#))),
+        Box::new(delayed("steady", 80, Ok(WTTR))),
+    ];
+
+    let (provider, body) = execute_race(&providers, now()).await.unwrap();
+    assert_eq!(provider.name(), "steady");
+    assert_eq!(body, WTTR);
+}
+
+#[tokio::test]
+async fn test_all_failures_are_reported_together_in_provider_order() {
+    let providers: Vec<Box<dyn ApiProvider>> = vec![
+        Box::new(delayed("first", 50, Err("key rejected"))),
+        Box::new(delayed("second", 5, Err("boom"))),
+        Box::new(delayed("third", 20, Ok("{}"))),
+    ];
+
+    let err = race_error(&providers).await;
+    let race = err.downcast_ref::<RaceFailed>().unwrap();
+    let names: Vec<&str> = race.errors.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["first", "second", "third"]);
+    assert_eq!(
+        err.to_string(),
+        "every provider failed: first: key rejected; second: boom; \
+         third: cannot read 'now' data from 'third'"
+    );
+}
+
+#[tokio::test]
+async fn test_a_lone_provider_keeps_its_error() {
+    let providers: Vec<Box<dyn ApiProvider>> = vec![Box::new(delayed("only", 5, Err("boom")))];
+
+    let err = race_error(&providers).await;
+    assert!(err.downcast_ref::<RaceFailed>().is_none());
+    assert_eq!(err.to_string(), "boom");
+}
+
+#[tokio::test]
+async fn test_exit_code_of_a_failed_race() {
+    fast_retries();
+    let quota = HttpServer::start_replies(vec![Reply {
+        status: 429,
+        headers: Vec::new(),
+        body: "{}",
+    }])
+    .await;
+    let limited = || -> Box<dyn ApiProvider> {
+        Box::new(WttrProvider {
+            base_url: quota.base_url.clone(),
+            ..wttr()
+        })
+    };
+
+    let err = race_error(&[limited(), limited()]).await;
+    assert_eq!(exit_code(&err), Some(RATE_LIMITED_EXIT_CODE));
+
+    let mixed: Vec<Box<dyn ApiProvider>> = vec![limited(), Box::new(wttr())];
+    let err = race_error(&mixed).await;
+    assert!(WappError::of(&err).is_none());
+    assert_eq!(exit_code(&err), Some(NETWORK_EXIT_CODE));
+}
+
+#[tokio::test]
+async fn test_client_races_with_the_race_strategy() {
+    fast_retries();
+    let weatherapi = HttpServer::start_delayed(
+        vec![Reply::ok(WEATHERAPI_CURRENT)],
+        Duration::from_millis(300),
+    )
+    .await;
+    let wttr = HttpServer::start(WTTR).await;
+    let client = |strategy| {
+        let overrides = ProviderOverrides {
+            env: EnvSource::from_vars([
+                ("WEATHERAPI_KEY", "KEY".to_string()),
+                ("WEATHERAPI_BASE_URL", weatherapi.base_url.clone()),
+                ("WTTR_BASE_URL", wttr.base_url.clone()),
+            ]),
+            ..Default::default()
+        };
+        let config = AppConfig {
+            provider: ProviderKind::WeatherApi,
+            fallback_providers: vec![ProviderKind::Wttr],
+            strategy,
+            ..Default::default()
+        };
+        Client::with_overrides(config, &overrides).unwrap()
+    };
+
+    let raced = client(Some(Strategy::Race)).current("Oslo").await.unwrap();
+    assert_eq!(raced.temp_c, Some(21.0));
+
+    // Otherwise the first provider answers, however slowly.
+    let first = client(None).current("Oslo").await.unwrap();
+    assert_eq!(first.temp_c, Some(24.0));
+    let single = client(Some(Strategy::Single))
+        .current("Oslo")
+        .await
+        .unwrap();
+    assert_eq!(single.temp_c, Some(24.0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_caches_and_names_the_winner() {
+    let weatherapi = HttpServer::start_delayed(
+        vec![Reply::ok(WEATHERAPI_CURRENT)],
+        Duration::from_millis(500),
+    )
+    .await;
+    let wttr = HttpServer::start(WTTR).await;
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{ "provider": "weatherapi", "fallback_providers": ["wttr"], "strategy": "race" }"#,
+    )
+    .unwrap();
+    let get = || {
+        let output = mocks::cli::wapp(dir.path(), &weatherapi.base_url)
+            .env_remove("WAPP_LOG")
+            .env_remove("RUST_LOG")
+            .env("WTTR_BASE_URL", &wttr.base_url)
+            .args(["get", "--city", "Oslo", "--output", "json", "-v"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (json, String::from_utf8(output.stderr).unwrap())
+    };
+
+    let (json, log) = get();
+    assert_eq!(json["temp_c"], 21.0);
+    assert!(log.contains("race won"), "{}", log);
+    assert!(log.contains("wttr"), "{}", log);
+
+    // The winner's response is cached, and wins the next race at once.
+    let (json, _) = get();
+    assert_eq!(json["temp_c"], 21.0);
+    assert_eq!(wttr.requests(), 1);
+}
+
+#[test]
+fn test_strategy_in_the_config() {
+    let cfg: AppConfig =
+        serde_json::from_str(r#"{ "provider": "weatherapi", "strategy": "race" }"#).unwrap();
+    assert_eq!(cfg.strategy, Some(Strategy::Race));
+    assert_eq!(cfg.get_key("strategy").unwrap().as_deref(), Some("race"));
+
+    let mut cfg = AppConfig::default();
+    assert_eq!(cfg.get_key("strategy").unwrap(), None);
+    cfg.set_key("strategy", "Single").unwrap();
+    assert_eq!(cfg.strategy, Some(Strategy::Single));
+    assert!(serde_json::to_string(&cfg)
+        .unwrap()
+        .contains(r#""strategy":"single""#));
+    let err = cfg.set_key("strategy", "fastest").unwrap_err().to_string();
+    assert!(
+        err.contains("expected single, fallback, or race"),
+        "{}",
+        err
+    );
+    cfg.unset_key("strategy").unwrap();
+    assert_eq!(cfg.strategy, None);
+}
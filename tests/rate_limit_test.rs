@@ -0,0 +1,162 @@
+mod mocks;
+
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::weatherapi;
+use wapp::cache::{Cache, CacheMode, ResponseCache};
+use wapp::error::WappError;
+use wapp::location::Location;
+use wapp::providers::{is_rate_limited, is_unavailable, ApiProvider, DataKind};
+use wapp::retry::{parse_retry_after, DEFAULT_ATTEMPTS};
+
+const NOW: u64 = 1_700_000_000;
+const BODY: &str = r#"{"current":{"temp_c":1.0}}"#;
+
+fn limited(retry_after: Option<&'static str>) -> Reply {
+    Reply {
+        status: 429,
+        headers: retry_after
+            .map(|value| vec![("Retry-After", value)])
+            .unwrap_or_default(),
+        body: r#"{"error":{"code":2007,"message":"API key has exceeded calls per month quota."}}"#,
+    }
+}
+
+async fn get(server: &HttpServer) -> anyhow::Result<String> {
+    weatherapi(&server.base_url)
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+}
+
+#[test]
+fn test_parse_retry_after() {
+    let now = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+
+    assert_eq!(
+        parse_retry_after("120", now),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+        Some(Duration::from_secs(30))
+    );
+    // A date already passed asks for no wait.
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon", now), None);
+    assert_eq!(parse_retry_after("-5", now), None);
+}
+
+#[test]
+fn test_rate_limited_message() {
+    let err = WappError::RateLimited {
+        provider: "weatherapi".into(),
+        retry_after: Some(Duration::from_millis(29_200)),
+    };
+    assert_eq!(
+        err.to_string(),
+        "rate limited by weatherapi, retry after 30s"
+    );
+
+    let err = WappError::RateLimited {
+        provider: "weatherapi".into(),
+        retry_after: None,
+    };
+    assert_eq!(err.to_string(), "rate limited by weatherapi");
+}
+
+#[tokio::test]
+async fn test_short_retry_after_seconds_is_waited_once() {
+    let server = HttpServer::start_replies(vec![limited(Some("1")), Reply::ok(BODY)]).await;
+
+    assert_eq!(get(&server).await.unwrap(), BODY);
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_retry_after_is_not_waited_twice() {
+    let server = HttpServer::start_replies(vec![limited(Some("0"))]).await;
+
+    let err = get(&server).await.unwrap_err();
+    assert!(is_rate_limited(&err));
+    assert_eq!(
+        err.to_string(),
+        "rate limited by weatherapi, retry after 0s"
+    );
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_passed_retry_after_date_is_retried() {
+    let server = HttpServer::start_replies(vec![
+        limited(Some("Wed, 21 Oct 2015 07:28:00 GMT")),
+        Reply::ok(BODY),
+    ])
+    .await;
+
+    assert_eq!(get(&server).await.unwrap(), BODY);
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_long_retry_after_fails_at_once() {
+    let server = HttpServer::start_replies(vec![
+        limited(Some("Fri, 01 Jan 2100 00:00:00 GMT")),
+        Reply::ok(BODY),
+    ])
+    .await;
+
+    let err = get(&server).await.unwrap_err();
+    assert!(is_rate_limited(&err));
+    // Other providers may still serve the request.
+    assert!(is_unavailable(&err));
+    assert!(err
+        .to_string()
+        .starts_with("rate limited by weatherapi, retry after "));
+    assert_eq!(server.requests(), 1);
+
+    let server = HttpServer::start_replies(vec![limited(Some("3600")), Reply::ok(BODY)]).await;
+    let err = get(&server).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "rate limited by weatherapi, retry after 3600s"
+    );
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_missing_retry_after_backs_off() {
+    let server = HttpServer::start_replies(vec![limited(None)]).await;
+
+    let err = get(&server).await.unwrap_err();
+    assert!(is_rate_limited(&err));
+    assert_eq!(err.to_string(), "rate limited by weatherapi");
+    assert_eq!(server.requests(), DEFAULT_ATTEMPTS as usize);
+}
+
+#[tokio::test]
+async fn test_rate_limited_requests_show_cached_data() {
+    let server = HttpServer::start_replies(vec![Reply::ok(BODY), limited(Some("3600"))]).await;
+    let provider = weatherapi(&server.base_url);
+    let dir = tempfile::tempdir().unwrap();
+    let cache =
+        |now| ResponseCache::new(Cache::new(dir.path()), 600, CacheMode::Use, "metric", now);
+
+    cache(NOW)
+        .fetch(&provider, Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    // Without `stale_fallback`, too.
+    let body = cache(NOW + 3600)
+        .fetch(&provider, Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, BODY);
+    assert_eq!(server.requests(), 2);
+}
@@ -0,0 +1,82 @@
+mod mocks;
+
+use mocks::http_server::{fast_retries, HttpServer, Reply};
+use mocks::providers::weatherapi;
+use wapp::location::Location;
+use wapp::providers::credentials::{redact_secrets, register_secret, CredentialStore};
+use wapp::providers::{ApiProvider, DataKind, OpenWeatherProvider, WeatherApiProvider};
+
+#[tokio::test]
+async fn test_unreachable_provider_errors_hide_the_key() {
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-123".into(),
+        ..weatherapi("http://127.0.0.1:9")
+    };
+
+    let err = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    for rendered in [err.to_string(), format!("{:#}", err), format!("{:?}", err)] {
+        assert!(!rendered.contains("SECRET-KEY-123"), "{}", rendered);
+    }
+}
+
+#[tokio::test]
+async fn test_unroutable_openweather_errors_hide_the_key() {
+    fast_retries();
+    let provider =
+        OpenWeatherProvider::new("SECRET-APPID-456").with_base_url("http://127.0.0.1:9/data/2.5");
+
+    let err = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert!(!format!("{:?}", err).contains("SECRET-APPID-456"));
+}
+
+#[tokio::test]
+async fn test_provider_messages_quoting_the_key_are_redacted() {
+    let server = HttpServer::start_replies(vec![Reply {
+        status: 401,
+        headers: Vec::new(),
+        body: r#"{"error":{"code":2006,"message":"API key SECRET-KEY-321 is invalid."}}"#,
+    }])
+    .await;
+
+    let provider = WeatherApiProvider {
+        api_key: "SECRET-KEY-321".into(),
+        ..weatherapi(&server.base_url)
+    };
+
+    let err = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "WeatherAPI error 2006: API key <redacted> is invalid"
+    );
+}
+
+#[test]
+fn test_resolved_keys_are_redacted() {
+    let store =
+        CredentialStore::with_lookups(None, Some("SAVED-KEY-654".into()), |_| None, |_| None);
+    assert_eq!(
+        store.api_key("WEATHERAPI_KEY", "weatherapi").unwrap(),
+        "SAVED-KEY-654"
+    );
+
+    assert_eq!(
+        redact_secrets("rejected SAVED-KEY-654 twice: SAVED-KEY-654"),
+        "rejected <redacted> twice: <redacted>"
+    );
+}
+
+#[test]
+fn test_short_secrets_are_not_registered() {
+    assert_eq!(register_secret("abc".into()), "abc");
+    assert_eq!(redact_secrets("abc is not a key"), "abc is not a key");
+}
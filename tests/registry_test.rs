@@ -0,0 +1,142 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use async_trait::async_trait;
+use clap::Parser;
+use wapp::cli::{handle_cli, Cli};
+use wapp::config::AppConfig;
+use wapp::location::Location;
+use wapp::providers::registry::{register_provider, supported_providers, ProviderRegistry};
+use wapp::providers::testing::MockProvider;
+use wapp::providers::{
+    provider_factory, provider_statuses, ApiProvider, Capabilities, DataKind, ProviderKind,
+    STANDARD_KINDS,
+};
+
+/// Requests `acme` received: the location label and kind of each.
+type Requests = Arc<Mutex<Vec<(String, DataKind)>>>;
+
+/// A company-internal provider, recording its requests.
+struct AcmeProvider {
+    requests: Requests,
+}
+
+#[async_trait]
+impl ApiProvider for AcmeProvider {
+    fn name(&self) -> &str {
+        "acme"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            max_forecast_days: 3,
+            kinds: STANDARD_KINDS,
+            history_since: None,
+        }
+    }
+
+    async fn get_data(
+        &self,
+        location: Location,
+        kind: DataKind,
+        _days: Option<u32>,
+    ) -> anyhow::Result<String> {
+        self.requests.lock().unwrap().push((location.label(), kind));
+        Ok(r#"{"temp":21}"#.into())
+    }
+}
+
+/// Registers `acme` once for the whole test binary, and returns its kind
+/// and the requests it received.
+fn acme() -> &'static (ProviderKind, Requests) {
+    static ACME: OnceLock<(ProviderKind, Requests)> = OnceLock::new();
+
+    ACME.get_or_init(|| {
+        let requests = Requests::default();
+        let received = requests.clone();
+        let kind = register_provider("Acme", move |_cfg: &AppConfig| {
+            Ok(Box::new(AcmeProvider {
+                requests: received.clone(),
+            }) as Box<dyn ApiProvider>)
+        })
+        .unwrap();
+        (kind, requests)
+    })
+}
+
+#[test]
+fn test_registered_provider_is_supported() {
+    let (kind, _) = *acme();
+
+    assert_eq!(kind.name(), "acme");
+    assert_eq!(supported_providers().last(), Some(&kind));
+    assert_eq!("ACME".parse::<ProviderKind>().unwrap(), kind);
+
+    let err = "acne".parse::<ProviderKind>().unwrap_err();
+    assert!(err.to_string().contains("did you mean 'acme'?"), "{}", err);
+    assert!(err.to_string().ends_with(", wttr, acme"), "{}", err);
+
+    let statuses = provider_statuses(None);
+    let status = statuses.last().unwrap();
+    assert_eq!(status.provider, "acme");
+    assert!(status.available);
+
+    let cfg = AppConfig {
+        provider: kind,
+        ..Default::default()
+    };
+    assert_eq!(provider_factory(&cfg).unwrap().name(), "acme");
+}
+
+#[test]
+fn test_duplicate_names_are_rejected() {
+    acme();
+    let mock = |_cfg: &AppConfig| Ok(Box::new(MockProvider::new("{}")) as Box<dyn ApiProvider>);
+
+    for name in ["acme", " ACME ", "weatherapi", "owm"] {
+        let err = register_provider(name, mock).unwrap_err();
+        assert!(err.to_string().contains("already registered"), "{}", err);
+    }
+    for name in ["", "acme corp", "a,b"] {
+        assert!(register_provider(name, mock).is_err(), "{}", name);
+    }
+}
+
+#[test]
+fn test_registries_are_independent() {
+    let mut registry = ProviderRegistry::new();
+    let kind = registry
+        .register("local", |_cfg| Ok(Box::new(MockProvider::new("LOCAL"))))
+        .unwrap();
+
+    assert_eq!(registry.kinds().len(), ProviderKind::ALL.len() + 1);
+    assert_eq!(registry.get("local"), Some(kind));
+    assert!(!supported_providers().contains(&kind));
+    assert!("local".parse::<ProviderKind>().is_err());
+}
+
+#[tokio::test]
+async fn test_get_through_a_registered_provider() {
+    let (_, requests) = acme();
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("WAPP_CONFIG", dir.path().join("config.json"));
+    std::env::set_var("WAPP_DATA_DIR", dir.path().join("data"));
+    std::env::set_var("WAPP_LOG", "off");
+
+    let cli = Cli::try_parse_from([
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--provider",
+        "acme",
+        "--raw",
+        "--no-cache",
+    ])
+    .unwrap();
+    handle_cli(cli).await.unwrap();
+
+    assert_eq!(
+        *requests.lock().unwrap(),
+        vec![("Kyiv".to_string(), DataKind::Now)]
+    );
+}
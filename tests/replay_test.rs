@@ -0,0 +1,266 @@
+mod mocks;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use assert_cmd::Command;
+use async_trait::async_trait;
+use clap::Parser;
+use serde_json::Value;
+use wapp::cli::Cli;
+use wapp::providers::FetchResponse;
+use wapp::transport::{is_secret_header, HttpRequest, HttpTransport, Recorder, Recording, Replay};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// Answers every request with `200` and the URL as body.
+struct Echo;
+
+#[async_trait]
+impl HttpTransport for Echo {
+    async fn send(&self, request: &HttpRequest) -> anyhow::Result<FetchResponse> {
+        Ok(FetchResponse {
+            status: 200,
+            last_modified: Some("Mon, 10 Jun 2024 09:00:00 GMT".into()),
+            etag: None,
+            retry_after: None,
+            content_type: Some("text/plain".into()),
+            body: format!("answer to {}", request.url),
+        })
+    }
+}
+
+async fn serve() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(WEATHERAPI_CURRENT))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// `wapp get --city Kyiv --provider weatherapi` with `key`, its base URL
+/// set to `base_url` if any, and config, cache and data below `dir`.
+fn wapp_get(dir: &Path, key: &str, base_url: Option<&str>) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url.unwrap_or_default());
+    cmd.env("WEATHERAPI_KEY", key)
+        .args(["get", "--city", "Kyiv", "--provider", "weatherapi"])
+        .args(["--output", "json"]);
+    if base_url.is_none() {
+        cmd.env_remove("WEATHERAPI_BASE_URL");
+    }
+    cmd
+}
+
+fn recordings(dir: &Path) -> Vec<Recording> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_record_saves_requests_with_the_key_masked() {
+    let server = serve().await;
+    let dir = tempfile::tempdir().unwrap();
+    let recorded = dir.path().join("recorded");
+
+    let output = wapp_get(dir.path(), "SECRET-KEY-1234", Some(&server.uri()))
+        .arg("--record")
+        .arg(&recorded)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let saved = recordings(&recorded);
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].request.method, "GET");
+    assert_eq!(
+        saved[0].request.url,
+        format!("{}/current.json?key=<redacted>&q=Kyiv", server.uri())
+    );
+    assert_eq!(saved[0].response.status, 200);
+    assert_eq!(saved[0].response.body, WEATHERAPI_CURRENT);
+
+    let file = std::fs::read_dir(&recorded).unwrap().next().unwrap();
+    let text = std::fs::read_to_string(file.unwrap().path()).unwrap();
+    assert!(!text.contains("SECRET-KEY-1234"), "{}", text);
+    // Recordings bypass the response cache.
+    assert!(!dir.path().join("cache").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_replay_shows_the_recorded_response_without_the_network() {
+    let server = serve().await;
+    let uri = server.uri();
+    let dir = tempfile::tempdir().unwrap();
+    let recorded = dir.path().join("recorded");
+
+    let live = wapp_get(dir.path(), "SECRET-KEY-1234", Some(&uri))
+        .arg("--record")
+        .arg(&recorded)
+        .output()
+        .unwrap();
+    assert!(live.status.success(), "{:?}", live);
+    drop(server);
+
+    // Another key: recordings do not depend on it.
+    let replayed = wapp_get(dir.path(), "OTHER-KEY-5678", Some(&uri))
+        .arg("--replay")
+        .arg(&recorded)
+        .output()
+        .unwrap();
+    assert!(replayed.status.success(), "{:?}", replayed);
+    assert_eq!(
+        serde_json::from_slice::<Value>(&replayed.stdout).unwrap(),
+        serde_json::from_slice::<Value>(&live.stdout).unwrap()
+    );
+}
+
+#[test]
+fn test_replay_without_a_recording_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let empty = dir.path().join("empty");
+    std::fs::create_dir(&empty).unwrap();
+
+    let output = wapp_get(dir.path(), "SECRET-KEY-1234", Some("http://127.0.0.1:9"))
+        .arg("--replay")
+        .arg(&empty)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "no recorded response for GET http://127.0.0.1:9/current.json?key=<redacted>&q=Kyiv"
+        ),
+        "{}",
+        stderr
+    );
+    assert!(!stderr.contains("SECRET-KEY-1234"), "{}", stderr);
+}
+
+#[test]
+fn test_shipped_recordings_run_the_whole_pipeline() {
+    let dir = tempfile::tempdir().unwrap();
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay");
+
+    let output = wapp_get(dir.path(), "ANY-KEY-0000", None)
+        .arg("--replay")
+        .arg(&fixtures)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let json: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["temp_c"], 24.0);
+    assert_eq!(json["timezone"], "Europe/Kiev");
+    assert_eq!(json["condition"], "Sunny");
+}
+
+#[tokio::test]
+async fn test_recorder_masks_credentials_and_replay_serves_them() {
+    let dir = tempfile::tempdir().unwrap();
+    let recorder = Recorder::new(dir.path(), Arc::new(Echo));
+    let request = HttpRequest::get("http://api/forecast?appid=SECRET&q=Kyiv")
+        .header("Authorization", "Bearer SECRET")
+        .header("User-Agent", "wapp-test");
+
+    let response = recorder.send(&request).await.unwrap();
+    assert_eq!(
+        response.body,
+        "answer to http://api/forecast?appid=SECRET&q=Kyiv"
+    );
+
+    let saved = recordings(dir.path());
+    assert_eq!(saved.len(), 1);
+    assert_eq!(
+        saved[0].request.headers,
+        vec![
+            ("Authorization".to_string(), "<redacted>".to_string()),
+            ("User-Agent".to_string(), "wapp-test".to_string()),
+        ]
+    );
+    assert_eq!(
+        saved[0].response.last_modified.as_deref(),
+        Some("Mon, 10 Jun 2024 09:00:00 GMT")
+    );
+
+    // Sent again, the request replaces its recording.
+    recorder.send(&request).await.unwrap();
+    assert_eq!(recordings(dir.path()).len(), 1);
+
+    let replay = Replay::open(dir.path()).unwrap();
+    assert_eq!(replay.len(), 1);
+    let other_key = HttpRequest::get("http://api/forecast?appid=OTHER&q=Kyiv");
+    assert_eq!(
+        replay.send(&other_key).await.unwrap().last_modified,
+        response.last_modified
+    );
+    let err = replay
+        .send(&HttpRequest::get("http://api/forecast?appid=OTHER&q=Lviv"))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "no recorded response for GET http://api/forecast?appid=<redacted>&q=Lviv in {}",
+            dir.path().display()
+        )
+    );
+}
+
+#[test]
+fn test_replay_rejects_unreadable_recordings() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = Replay::open(&dir.path().join("missing")).unwrap_err();
+    assert!(
+        err.to_string().starts_with("cannot read recordings in "),
+        "{}",
+        err
+    );
+
+    // Other files are ignored, JSON files must be recordings.
+    std::fs::write(dir.path().join("notes.txt"), "not JSON").unwrap();
+    assert!(Replay::open(dir.path()).unwrap().is_empty());
+    std::fs::write(dir.path().join("bad.json"), "{}").unwrap();
+    let err = Replay::open(dir.path()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "{} is not a recording",
+            dir.path().join("bad.json").display()
+        )
+    );
+}
+
+#[test]
+fn test_secret_headers() {
+    for name in ["Authorization", "X-Api-Key", "apikey", "X-Auth-Token"] {
+        assert!(is_secret_header(name), "{}", name);
+    }
+    for name in ["User-Agent", "If-Modified-Since", "Accept"] {
+        assert!(!is_secret_header(name), "{}", name);
+    }
+}
+
+#[test]
+fn test_record_and_replay_exclude_each_other() {
+    for args in [
+        &["wapp", "get", "--record", "a", "--replay", "b"][..],
+        &["wapp", "get", "--replay", "b", "--offline"][..],
+        &["wapp", "get", "--record", "a", "--dry-run"][..],
+    ] {
+        assert!(Cli::try_parse_from(args).is_err(), "{:?}", args);
+    }
+    assert!(Cli::try_parse_from(["wapp", "get", "--replay", "b", "--city", "Kyiv"]).is_ok());
+}
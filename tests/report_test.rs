@@ -0,0 +1,246 @@
+mod mocks;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use mocks::http_server::HttpServer;
+use wapp::output::report::{escape_html, escape_markdown, render_html_in, render_markdown_in};
+use wapp::providers::{ApiProvider, WeatherApiProvider};
+use wapp::units::Units;
+use wapp::weather::WeatherData;
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+
+fn weatherapi(body: &str) -> WeatherData {
+    WeatherApiProvider::new("KEY").weather(body).unwrap()
+}
+
+/// `wapp get --output <format>` against WeatherAPI at `base_url`, with its
+/// config, cache and data below `dir`.
+fn wapp_get(dir: &Path, base_url: &str, format: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.args(["get", "--provider", "weatherapi", "--no-cache"])
+        .args(["--output", format]);
+    cmd
+}
+
+#[test]
+fn test_markdown_forecast() {
+    assert_eq!(
+        render_markdown_in("Kyiv", &weatherapi(WEATHERAPI_FORECAST), Units::Metric),
+        "## Kyiv\n\
+         \n\
+         | Date | Condition | Min °C | Max °C | Precip mm | Rain % |\n\
+         | --- | --- | ---: | ---: | ---: | ---: |\n\
+         | 2024-06-10 | Partly cloudy | 10.2 | 18.3 | 0.0 | 12 |\n\
+         | 2024-06-11 | Patchy rain nearby | 11.4 | 16.8 | 2.4 | 78 |\n\
+         | 2024-06-12 | Sunny | 9.8 | 20.1 | 0.0 | 4 |\n"
+    );
+}
+
+#[test]
+fn test_markdown_now() {
+    assert_eq!(
+        render_markdown_in("Kyiv", &weatherapi(WEATHERAPI_CURRENT), Units::Metric),
+        "## Kyiv\n\
+         \n\
+         - **Condition:** Sunny\n\
+         - **Temperature:** 24.0 °C\n\
+         - **Feels like:** 24.8 °C\n\
+         - **Humidity:** 36%\n\
+         - **Wind:** 11.2 km/h NW, gusts 13.0 km/h\n\
+         - **UV index:** 7 High\n\
+         - **Updated:** Mon 10 Jun 15:55\n"
+    );
+}
+
+#[test]
+fn test_html_forecast() {
+    let cell = |tag: &str, align: &str, value: &str| {
+        format!(
+            "<{tag} style=\"border:1px solid #ccc;padding:0.25em 0.5em;text-align:{align}\">{value}</{tag}>"
+        )
+    };
+    let row = |tag: &str, values: [&str; 6]| {
+        let cells: String = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| cell(tag, if i < 2 { "left" } else { "right" }, v))
+            .collect();
+        format!("<tr>{}</tr>\n", cells)
+    };
+
+    let expected = [
+        "<section style=\"font-family:sans-serif;margin:0 0 1.5em\">\n".to_string(),
+        "<h2 style=\"font-size:1.25em;margin:0 0 0.5em\">Kyiv</h2>\n".to_string(),
+        "<table style=\"border-collapse:collapse\">\n<thead>\n".to_string(),
+        row(
+            "th",
+            [
+                "Date",
+                "Condition",
+                "Min °C",
+                "Max °C",
+                "Precip mm",
+                "Rain %",
+            ],
+        ),
+        "</thead>\n<tbody>\n".to_string(),
+        row(
+            "td",
+            ["2024-06-10", "Partly cloudy", "10.2", "18.3", "0.0", "12"],
+        ),
+        row(
+            "td",
+            [
+                "2024-06-11",
+                "Patchy rain nearby",
+                "11.4",
+                "16.8",
+                "2.4",
+                "78",
+            ],
+        ),
+        row("td", ["2024-06-12", "Sunny", "9.8", "20.1", "0.0", "4"]),
+        "</tbody>\n</table>\n</section>\n".to_string(),
+    ]
+    .concat();
+
+    assert_eq!(
+        render_html_in("Kyiv", &weatherapi(WEATHERAPI_FORECAST), Units::Metric),
+        expected
+    );
+}
+
+#[test]
+fn test_html_now() {
+    let term = "<dt style=\"font-weight:bold\">";
+    let definition = "<dd style=\"margin:0\">";
+    let html = render_html_in("Kyiv", &weatherapi(WEATHERAPI_CURRENT), Units::Imperial);
+
+    assert_eq!(
+        html,
+        format!(
+            "<section style=\"font-family:sans-serif;margin:0 0 1.5em\">\n\
+             <h2 style=\"font-size:1.25em;margin:0 0 0.5em\">Kyiv</h2>\n\
+             <dl style=\"display:grid;grid-template-columns:max-content auto;gap:0.25em 1em;margin:0 0 0.75em\">\n\
+             {term}Condition</dt>{definition}Sunny</dd>\n\
+             {term}Temperature</dt>{definition}75.2 °F</dd>\n\
+             {term}Feels like</dt>{definition}76.6 °F</dd>\n\
+             {term}Humidity</dt>{definition}36%</dd>\n\
+             {term}Wind</dt>{definition}7.0 mph NW, gusts 8.1 mph</dd>\n\
+             {term}UV index</dt>{definition}7 High</dd>\n\
+             {term}Updated</dt>{definition}Mon 10 Jun 15:55</dd>\n\
+             </dl>\n\
+             </section>\n"
+        )
+    );
+}
+
+#[test]
+fn test_text_of_the_provider_is_escaped() {
+    assert_eq!(
+        escape_html("<b>Tom & \"Jerry's\"</b>"),
+        "&lt;b&gt;Tom &amp; &quot;Jerry&#39;s&quot;&lt;/b&gt;"
+    );
+    assert_eq!(
+        escape_markdown("a|b *c* _d_ [e](f) <g> `h` \\ #i &amp;\nj"),
+        "a\\|b \\*c\\* \\_d\\_ \\[e\\](f) \\<g\\> \\`h\\` \\\\ \\#i \\&amp; j"
+    );
+
+    let mut data = weatherapi(WEATHERAPI_FORECAST);
+    data.forecast.truncate(1);
+    data.forecast[0].condition = Some("Rain | <b>heavy</b> & *wind*".into());
+    data.forecast[0].partial = true;
+    let city = "St. John's <Old> & \"New\" | Town";
+
+    let markdown = render_markdown_in(city, &data, Units::Metric);
+    assert_eq!(
+        markdown,
+        "## St. John's \\<Old\\> \\& \"New\" \\| Town\n\
+         \n\
+         | Date | Condition | Min °C | Max °C | Precip mm | Rain % |\n\
+         | --- | --- | ---: | ---: | ---: | ---: |\n\
+         | 2024-06-10\\* | Rain \\| \\<b\\>heavy\\</b\\> \\& \\*wind\\* | 10.2 | 18.3 | 0.0 | 12 |\n\
+         \n\
+         \\* partial day: the forecast covers only part of it\n"
+    );
+
+    let html = render_html_in(city, &data, Units::Metric);
+    assert!(html.contains(">St. John&#39;s &lt;Old&gt; &amp; &quot;New&quot; | Town</h2>"));
+    assert!(html.contains(">Rain | &lt;b&gt;heavy&lt;/b&gt; &amp; *wind*</td>"));
+    assert!(!html.contains("<b>") && !html.contains("<Old>"), "{}", html);
+    assert!(html.contains("* partial day: the forecast covers only part of it</p>"));
+}
+
+#[test]
+fn test_other_kinds_are_a_code_block() {
+    let alerts = WeatherData {
+        alerts: Some(Vec::new()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        render_markdown_in("Kyiv", &alerts, Units::Metric),
+        "## Kyiv\n\n```text\n  No active alerts\n```\n"
+    );
+    assert!(render_html_in("Kyiv", &alerts, Units::Metric)
+        .ends_with("</h2>\n<pre>  No active alerts</pre>\n</section>\n"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cities_get_a_section_each() {
+    let server = HttpServer::start(WEATHERAPI_FORECAST).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    for (format, heading, end) in [
+        (
+            "markdown",
+            "## ",
+            "| 2024-06-12 | Sunny | 9.8 | 20.1 | 0.0 | 4 |\n",
+        ),
+        ("html", "<h2 ", "</section>\n"),
+    ] {
+        let output = wapp_get(dir.path(), &server.base_url, format)
+            .args(["--city", "Kyiv", "--city", "Lviv", "--data", "forecast"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+
+        let text = String::from_utf8(output.stdout).unwrap();
+        assert_eq!(text.matches(heading).count(), 2, "{}", text);
+        let kyiv = text.find("Kyiv").unwrap();
+        let lviv = text.find("Lviv").unwrap();
+        assert!(kyiv < lviv, "{}", text);
+        assert_eq!(text.matches("2024-06-11").count(), 2, "{}", text);
+        assert!(text.ends_with(end), "{}", text);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_prints_a_report_section() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server.base_url, "markdown")
+        .args(["--city", "Kyiv"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        text.starts_with("## Kyiv\n\n- **Condition:** Sunny\n"),
+        "{}",
+        text
+    );
+
+    // The raw response is JSON, so --raw prints it instead.
+    let output = wapp_get(dir.path(), &server.base_url, "html")
+        .args(["--city", "Kyiv", "--raw"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["location"]["name"], "Kyiv");
+}
@@ -0,0 +1,693 @@
+mod mocks;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser;
+use mocks::providers::{openweather, weatherapi};
+use mocks::scripted_provider::ScriptedProvider;
+use wapp::cli::{
+    default_location, forecast_request, get_location, get_requests, now_request, Cli, Commands,
+};
+use wapp::config::AppConfig;
+use wapp::location::{CityName, Location};
+use wapp::providers::{validate_history, ApiProvider, DataKind};
+use wapp::request::{
+    execute, execute_locations, execute_many, fetch_days, render_keyed, DateWindow, HourWindow,
+    WeatherRequest, DEFAULT_HOURS,
+};
+
+fn city(name: &str) -> CityName {
+    CityName::new(name).unwrap()
+}
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_window_counts_days_from_today() {
+    let today = date("2024-06-10");
+    let (window, days) =
+        DateWindow::for_forecast(Some(date("2024-06-11")), Some(date("2024-06-12")), today)
+            .unwrap();
+
+    assert_eq!(window.from, date("2024-06-11"));
+    assert_eq!(window.to, date("2024-06-12"));
+    assert_eq!(days, 3);
+}
+
+#[test]
+fn test_window_defaults() {
+    let today = date("2024-06-10");
+
+    let (window, days) = DateWindow::for_forecast(None, Some(date("2024-06-11")), today).unwrap();
+    assert_eq!((window.from, days), (today, 2));
+
+    let (window, days) = DateWindow::for_forecast(Some(date("2024-06-12")), None, today).unwrap();
+    assert_eq!((window.to, days), (date("2024-06-12"), 3));
+}
+
+#[test]
+fn test_window_rejects_past_and_reversed_ranges() {
+    let today = date("2024-06-10");
+
+    assert!(DateWindow::for_forecast(Some(date("2024-06-09")), None, today).is_err());
+    assert!(
+        DateWindow::for_forecast(Some(date("2024-06-12")), Some(date("2024-06-11")), today)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_date_window_parses_a_range_of_days() {
+    let window: DateWindow = "2024-06-01..2024-06-03".parse().unwrap();
+    assert_eq!(
+        window.dates().collect::<Vec<_>>(),
+        [date("2024-06-01"), date("2024-06-02"), date("2024-06-03")]
+    );
+
+    let one: DateWindow = "2024-06-01..2024-06-01".parse().unwrap();
+    assert_eq!(one.dates().count(), 1);
+
+    for (range, error) in [
+        ("2024-06-01", "expected FROM..TO"),
+        ("2024-06-01..June 3", "'June 3' is not a date"),
+        ("2024-06-03..2024-06-01", "2024-06-01 is before 2024-06-03"),
+    ] {
+        let err = range.parse::<DateWindow>().unwrap_err().to_string();
+        assert!(err.contains(error), "{}: {}", range, err);
+    }
+}
+
+#[test]
+fn test_weatherapi_history_url() {
+    let url = weatherapi("http://localhost")
+        .build_url(
+            &Location::from("Kyiv"),
+            DataKind::History(date("2024-06-01")),
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        url,
+        "http://localhost/history.json?key=KEY&q=Kyiv&dt=2024-06-01"
+    );
+}
+
+#[test]
+fn test_history_dates_are_checked_against_the_provider() {
+    let today = date("2024-06-10");
+
+    assert!(validate_history(&weatherapi("http://localhost"), date("2024-06-10"), today).is_ok());
+    assert!(validate_history(&weatherapi("http://localhost"), date("2010-01-01"), today).is_ok());
+
+    let err = validate_history(&weatherapi("http://localhost"), date("2024-06-11"), today)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "--date 2024-06-11 is in the future; observations end today (2024-06-10)"
+    );
+
+    let err = validate_history(&weatherapi("http://localhost"), date("2009-12-31"), today)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "--date 2009-12-31 is before the history of provider 'weatherapi', which starts on 2010-01-01"
+    );
+
+    let err = validate_history(&openweather("http://localhost"), date("2024-06-01"), today)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(err, "history is not supported by provider 'openweather'");
+}
+
+#[test]
+fn test_weatherapi_retain_dates() {
+    let body = include_str!("fixtures/weatherapi_forecast.json");
+    let window = DateWindow {
+        from: date("2024-06-11"),
+        to: date("2024-06-12"),
+    };
+
+    let trimmed = weatherapi("http://localhost")
+        .retain_dates(body, window)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&trimmed).unwrap();
+    let days = json["forecast"]["forecastday"].as_array().unwrap();
+
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[0]["date"], "2024-06-11");
+}
+
+#[test]
+fn test_openweather_retain_dates() {
+    let body = include_str!("fixtures/openweather_forecast.json");
+    let window = DateWindow {
+        from: date("2024-06-11"),
+        to: date("2024-06-11"),
+    };
+
+    let trimmed = openweather("http://localhost")
+        .retain_dates(body, window)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&trimmed).unwrap();
+
+    assert_eq!(json["cnt"], 8);
+    assert!(json["list"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|e| e["dt_txt"].as_str().unwrap().starts_with("2024-06-11")));
+}
+
+#[tokio::test]
+async fn test_forecast_subcommand_end_to_end() {
+    let cli = Cli::parse_from(vec!["wapp", "forecast", "Kyiv", "--days", "2"]);
+    let request = match cli.cmd {
+        Commands::Forecast {
+            city,
+            days,
+            hourly,
+            from,
+            to,
+            ..
+        } => forecast_request(&city, days, hourly, from, to, date("2024-06-10")).unwrap(),
+        _ => panic!("wrong command parsed"),
+    };
+
+    let provider = ScriptedProvider::new(vec![Ok("FORECAST")]);
+    let out = execute(&provider, request).await.unwrap();
+
+    assert_eq!(out, "FORECAST");
+    assert_eq!(
+        provider.calls(),
+        vec![("Kyiv".to_string(), DataKind::Forecast, Some(2))]
+    );
+}
+
+#[tokio::test]
+async fn test_forecast_hourly_and_range_map_onto_request() {
+    let request = forecast_request(
+        &city("Kyiv"),
+        None,
+        true,
+        Some(date("2024-06-11")),
+        None,
+        date("2024-06-10"),
+    )
+    .unwrap();
+
+    let provider = ScriptedProvider::new(vec![Ok("HOURLY")]);
+    execute(&provider, request).await.unwrap();
+
+    assert_eq!(
+        provider.calls(),
+        vec![("Kyiv".to_string(), DataKind::Hourly, Some(2))]
+    );
+}
+
+#[tokio::test]
+async fn test_get_and_forecast_share_request_path() {
+    let provider = ScriptedProvider::new(vec![Ok("A"), Ok("B")]);
+
+    execute(
+        &provider,
+        WeatherRequest::new(&city("Kyiv"), DataKind::Forecast, Some(3)),
+    )
+    .await
+    .unwrap();
+    execute(
+        &provider,
+        forecast_request(
+            &city("Kyiv"),
+            Some(3),
+            false,
+            None,
+            None,
+            date("2024-06-10"),
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let calls = provider.calls();
+    assert_eq!(calls[0], calls[1]);
+}
+
+#[tokio::test]
+async fn test_days_beyond_horizon_rejected_before_request() {
+    let provider = ScriptedProvider::new(vec![Ok("never")]);
+
+    let result = execute(
+        &provider,
+        WeatherRequest::new(&city("Kyiv"), DataKind::Forecast, Some(10)),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(provider.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_days_with_current_conditions_rejected_before_request() {
+    let provider = ScriptedProvider::new(vec![Ok("never")]);
+
+    let err = execute(
+        &provider,
+        WeatherRequest::new(&city("Kyiv"), DataKind::Now, Some(2)),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+
+    assert!(
+        err.contains("--days does not apply to --data now"),
+        "{}",
+        err
+    );
+    assert!(provider.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_forecast_cut_to_the_requested_days() {
+    // 3-hour slots counted from midday run into a third date.
+    let body = include_str!("fixtures/openweather_forecast.json");
+    let provider =
+        ScriptedProvider::new(vec![Ok(body)]).parsing_as(openweather("http://localhost"));
+
+    let out = execute(
+        &provider,
+        WeatherRequest::new(&city("Kyiv"), DataKind::Forecast, Some(2)),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        openweather("http://localhost")
+            .forecast_dates(&out)
+            .unwrap(),
+        vec![date("2024-06-10"), date("2024-06-11")]
+    );
+}
+
+#[tokio::test]
+async fn test_now_subcommand_end_to_end() {
+    let cli = Cli::parse_from(vec!["wapp", "now", "Portland, OR"]);
+    let request = match cli.cmd {
+        Commands::Now { city, .. } => now_request(city).unwrap(),
+        _ => panic!("wrong command parsed"),
+    };
+
+    let provider = ScriptedProvider::new(vec![Ok("NOW")]);
+    let out = execute(&provider, request).await.unwrap();
+
+    assert_eq!(out, "NOW");
+    assert_eq!(
+        provider.calls(),
+        vec![("Portland, OR".to_string(), DataKind::Now, None)]
+    );
+}
+
+#[test]
+fn test_now_without_city_explains_usage() {
+    let err = now_request(None).unwrap_err().to_string();
+
+    assert!(err.contains("no city given"));
+    assert!(err.contains("wapp now <CITY>"));
+}
+
+const WEATHERAPI_FIXTURE: &str = include_str!("fixtures/weatherapi_forecast.json");
+
+fn forecast_dates(body: &str) -> Vec<String> {
+    let json: serde_json::Value = serde_json::from_str(body).unwrap();
+    json["forecast"]["forecastday"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["date"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_day_selects_single_forecast_day() {
+    for (day, expected) in [(0, "2024-06-10"), (2, "2024-06-12")] {
+        let provider = ScriptedProvider::new(vec![Ok(WEATHERAPI_FIXTURE)])
+            .parsing_as(weatherapi("http://localhost"));
+        let request = WeatherRequest::new(&city("London"), DataKind::Forecast, None).with_day(day);
+
+        let out = execute(&provider, request).await.unwrap();
+
+        assert_eq!(forecast_dates(&out), vec![expected]);
+        // The hourly breakdown of that day is kept.
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(
+            json["forecast"]["forecastday"][0]["hour"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+        // Enough days are requested to cover the index.
+        assert_eq!(provider.calls()[0].2, Some(day + 1));
+    }
+}
+
+#[tokio::test]
+async fn test_day_beyond_horizon_rejected_before_request() {
+    let provider = ScriptedProvider::new(vec![Ok(WEATHERAPI_FIXTURE)])
+        .parsing_as(weatherapi("http://localhost"));
+
+    let err = execute(
+        &provider,
+        WeatherRequest::new(&city("London"), DataKind::Forecast, None).with_day(3),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+
+    assert!(err.contains("days 0 to 2 are available"));
+    assert!(provider.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_day_missing_from_response_reports_available_days() {
+    let one_day = weatherapi("http://localhost")
+        .retain_dates(
+            WEATHERAPI_FIXTURE,
+            DateWindow {
+                from: date("2024-06-10"),
+                to: date("2024-06-10"),
+            },
+        )
+        .unwrap();
+    let provider =
+        ScriptedProvider::new(vec![Ok(&one_day)]).parsing_as(weatherapi("http://localhost"));
+
+    let err = execute(
+        &provider,
+        WeatherRequest::new(&city("London"), DataKind::Forecast, None).with_day(2),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+
+    assert!(err.contains("returned 1 days (0 to 0)"));
+}
+
+fn kinds(list: &[&str]) -> Vec<DataKind> {
+    list.iter().map(|k| k.parse().unwrap()).collect()
+}
+
+#[test]
+fn test_get_location_from_each_option() {
+    assert_eq!(
+        get_location(Some(city("Kyiv")), None, None, None, None)
+            .unwrap()
+            .unwrap(),
+        Location::from("Kyiv")
+    );
+    assert_eq!(
+        get_location(None, Some(50.45), Some(30.52), None, None)
+            .unwrap()
+            .unwrap(),
+        Location::Coords {
+            lat: 50.45,
+            lon: 30.52
+        }
+    );
+    assert_eq!(
+        get_location(None, None, None, Some("10001,us".into()), None)
+            .unwrap()
+            .unwrap(),
+        Location::Zip {
+            code: "10001".into(),
+            country: Some("US".into())
+        }
+    );
+    assert_eq!(
+        get_location(None, None, None, None, Some("lhr".into()))
+            .unwrap()
+            .unwrap(),
+        Location::Iata("LHR".into())
+    );
+    assert!(get_location(None, Some(91.0), Some(0.0), None, None)
+        .unwrap()
+        .is_err());
+    assert!(get_location(None, None, None, None, Some("LHRX".into()))
+        .unwrap()
+        .is_err());
+    assert!(get_location(None, None, None, None, None).is_none());
+}
+
+#[test]
+fn test_explicit_location_beats_default_city() {
+    let cfg = AppConfig {
+        default_city: Some("Lviv".into()),
+        ..Default::default()
+    };
+    let resolve = |city: Option<CityName>, cfg: &AppConfig| {
+        get_location(city, None, None, None, None).or_else(|| default_location(cfg))
+    };
+
+    assert_eq!(
+        resolve(Some(city("Kyiv")), &cfg).unwrap().unwrap(),
+        Location::from("Kyiv")
+    );
+    assert_eq!(
+        resolve(None, &cfg).unwrap().unwrap(),
+        Location::from("Lviv")
+    );
+    assert!(resolve(None, &AppConfig::default()).is_none());
+
+    let broken = AppConfig {
+        default_city: Some("   ".into()),
+        ..Default::default()
+    };
+    assert!(default_location(&broken).unwrap().is_err());
+}
+
+#[test]
+fn test_get_requests_share_coordinates() {
+    let location = Location::coords(-33.87, 151.21).unwrap();
+    let requests = get_requests(
+        location.clone(),
+        kinds(&["now", "hourly"]),
+        None,
+        None,
+        None,
+    );
+
+    assert!(requests.iter().all(|r| r.location == location));
+}
+
+#[tokio::test]
+async fn test_multiple_kinds_keep_request_order() {
+    let provider = ScriptedProvider::new(vec![
+        Ok(r#"{"current":{}}"#),
+        Ok(r#"{"tomorrow":[]}"#),
+        Ok(r#"{"forecast":{}}"#),
+    ]);
+    let requests = get_requests(
+        &city("Kyiv"),
+        kinds(&["now", "tomorrow", "hourly"]),
+        None,
+        None,
+        None,
+    );
+
+    let results = execute_many(&provider, requests).await.unwrap();
+
+    let order: Vec<&str> = results.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(order, vec!["now", "tomorrow", "hourly"]);
+
+    let out: serde_json::Value = serde_json::from_str(&render_keyed(&results)).unwrap();
+    let keys: Vec<&str> = out
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(keys, vec!["now", "tomorrow", "hourly"]);
+    assert!(out["tomorrow"]["tomorrow"].is_array());
+}
+
+fn utc(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_hourly_request_defaults_to_a_day_of_hours() {
+    let requests = get_requests(&city("Kyiv"), kinds(&["now", "hourly"]), None, None, None);
+
+    assert_eq!(requests[0].hours, None);
+    assert_eq!(requests[1].hours, Some(DEFAULT_HOURS));
+
+    let requests = get_requests(&city("Kyiv"), kinds(&["hourly"]), None, None, Some(6));
+    assert_eq!(requests[0].hours, Some(6));
+}
+
+#[test]
+fn test_hour_window_starts_with_the_hour_under_way() {
+    let window = HourWindow::next(utc("2024-06-10T14:35:10Z"), 3);
+
+    assert_eq!(window.from, utc("2024-06-10T14:00:00Z"));
+    assert_eq!(window.to, utc("2024-06-10T17:00:00Z"));
+    assert!(window.contains(utc("2024-06-10T14:00:00Z")));
+    assert!(window.contains(utc("2024-06-10T16:59:59Z")));
+    assert!(!window.contains(utc("2024-06-10T17:00:00Z")));
+    assert!(!window.contains(utc("2024-06-10T13:00:00Z")));
+}
+
+#[tokio::test]
+async fn test_hours_fetch_every_day_they_reach() {
+    let provider = ScriptedProvider::new(vec![Ok("{}")]);
+    let request =
+        WeatherRequest::new(&city("Kyiv"), DataKind::Hourly, Some(3)).with_hours(DEFAULT_HOURS);
+
+    // Today and tomorrow, as 24 hours from now run past midnight.
+    assert_eq!(fetch_days(&provider, &request), Some(2));
+    assert_eq!(
+        fetch_days(&provider, &request.clone().with_hours(72)),
+        Some(3)
+    );
+
+    execute(&provider, request).await.unwrap();
+    assert_eq!(
+        provider.calls(),
+        vec![("Kyiv".to_string(), DataKind::Hourly, Some(2))]
+    );
+}
+
+#[tokio::test]
+async fn test_hours_beyond_horizon_rejected_before_request() {
+    let provider = ScriptedProvider::new(vec![Ok("never")]);
+
+    let err = execute(
+        &provider,
+        WeatherRequest::new(&city("Kyiv"), DataKind::Hourly, None).with_hours(73),
+    )
+    .await
+    .unwrap_err()
+    .to_string();
+
+    assert!(err.contains("1 to 72 hours are available"), "{}", err);
+    assert!(provider.calls().is_empty());
+}
+
+#[test]
+fn test_weatherapi_retain_hours() {
+    let body = include_str!("fixtures/weatherapi_forecast.json");
+    let window = HourWindow::next(utc("2024-06-10T05:20:00Z"), 12);
+
+    let trimmed = weatherapi("http://localhost")
+        .retain_hours(body, window)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&trimmed).unwrap();
+
+    let hours: Vec<&str> = json["forecast"]["forecastday"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|day| day["hour"].as_array().unwrap())
+        .map(|hour| hour["time"].as_str().unwrap())
+        .collect();
+    assert_eq!(hours, vec!["2024-06-10 06:00", "2024-06-10 12:00"]);
+    assert_eq!(json["forecast"]["forecastday"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_openweather_retain_hours() {
+    let body = include_str!("fixtures/openweather_forecast.json");
+    let window = HourWindow::next(utc("2024-06-11T00:00:00Z"), 6);
+
+    let trimmed = openweather("http://localhost")
+        .retain_hours(body, window)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&trimmed).unwrap();
+
+    assert_eq!(json["cnt"], 2);
+    let times: Vec<&str> = json["list"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["dt_txt"].as_str().unwrap())
+        .collect();
+    assert_eq!(times, vec!["2024-06-11 00:00:00", "2024-06-11 03:00:00"]);
+}
+
+#[tokio::test]
+async fn test_multiple_kinds_partial_failure_reported_inline() {
+    let provider = ScriptedProvider::new(vec![Ok(r#"{"current":{}}"#), Err("hourly unavailable")]);
+    let requests = get_requests(&city("Kyiv"), kinds(&["now", "hourly"]), None, None, None);
+
+    let results = execute_many(&provider, requests).await.unwrap();
+
+    assert!(results[0].1.is_ok());
+    assert!(results[1].1.is_err());
+
+    let out: serde_json::Value = serde_json::from_str(&render_keyed(&results)).unwrap();
+    assert!(out["now"]["current"].is_object());
+    assert_eq!(out["hourly"]["error"], "hourly unavailable");
+}
+
+#[tokio::test]
+async fn test_multiple_kinds_share_one_parsed_location() {
+    let provider = ScriptedProvider::new(vec![Ok("{}"), Ok("{}")]);
+    let requests = get_requests(
+        &city("  Portland,   OR "),
+        kinds(&["now", "hourly"]),
+        None,
+        None,
+        None,
+    );
+
+    assert_eq!(requests[0].location, requests[1].location);
+
+    execute_many(&provider, requests).await.unwrap();
+
+    let locations: Vec<String> = provider.calls().into_iter().map(|c| c.0).collect();
+    assert_eq!(locations, vec!["Portland, OR", "Portland, OR"]);
+}
+
+#[tokio::test]
+async fn test_unsupported_kind_rejected_before_any_request() {
+    let provider = ScriptedProvider::new(vec![Ok("{}"), Ok("{}")]);
+    let requests = get_requests(
+        &city("Kyiv"),
+        kinds(&["now", "2024-01-15"]),
+        None,
+        None,
+        None,
+    );
+
+    let err = execute_many(&provider, requests)
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("history is not supported by provider"));
+    assert!(provider.calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_several_cities_keep_request_order() {
+    let provider = ScriptedProvider::new(vec![Ok("{}"), Err("city not found"), Ok("{}")]);
+    let requests = ["Kyiv", "Atlantis", "Lviv"]
+        .iter()
+        .flat_map(|name| get_requests(&city(name), kinds(&["now"]), None, None, None))
+        .collect();
+
+    let results = execute_locations(&provider, requests, 2).await.unwrap();
+
+    let labels: Vec<&str> = results.iter().map(|(c, _)| c.as_str()).collect();
+    assert_eq!(labels, vec!["Kyiv", "Atlantis", "Lviv"]);
+    assert!(results[0].1.is_ok());
+    assert_eq!(
+        results[1].1.as_ref().unwrap_err().to_string(),
+        "city not found"
+    );
+    assert!(results[2].1.is_ok());
+    assert_eq!(provider.calls().len(), 3);
+}
@@ -0,0 +1,132 @@
+use wapp::location::Location;
+use wapp::providers::openmeteo::Place;
+use wapp::providers::url::RequestUrl;
+use wapp::providers::{ApiProvider, DataKind, OpenWeatherProvider, WeatherApiProvider};
+
+/// A key holding every character with a meaning in a query.
+const KEY: &str = "k&y=a+b/c?d#e f";
+const ENCODED_KEY: &str = "k%26y%3Da%2Bb%2Fc%3Fd%23e%20f";
+
+fn weatherapi() -> WeatherApiProvider {
+    WeatherApiProvider::new(KEY)
+        .with_base_url("http://w")
+        .with_lang("pt BR")
+}
+
+fn openweather() -> OpenWeatherProvider {
+    OpenWeatherProvider::new(KEY)
+        .with_base_url("http://o/data/2.5")
+        .with_units("metric")
+        .with_lang("pt BR")
+}
+
+#[test]
+fn test_parameters_keep_their_order() {
+    let url = RequestUrl::new("http://w", "/forecast.json")
+        .param("key", "KEY")
+        .param("days", 3)
+        .param_opt("dt", None::<&str>)
+        .param_opt("lang", Some("uk"))
+        .params(&[("aqi".into(), "yes".into())])
+        .build();
+
+    assert_eq!(url, "http://w/forecast.json?key=KEY&days=3&lang=uk&aqi=yes");
+    assert_eq!(RequestUrl::new("http://w", "/a").build(), "http://w/a");
+}
+
+#[test]
+fn test_names_and_values_are_encoded() {
+    let url = RequestUrl::new("http://w", "/current.json")
+        .param("key", KEY)
+        .param("q", "Trinidad & Tobago")
+        .param("lang", "pt BR")
+        .params(&[("x y".into(), "1&2".into())])
+        .encoded("coords", "50.45,30.52")
+        .build();
+
+    assert_eq!(
+        url,
+        format!(
+            "http://w/current.json?key={ENCODED_KEY}&q=Trinidad%20%26%20Tobago\
+             &lang=pt%20BR&x%20y=1%262&coords=50.45,30.52"
+        )
+    );
+}
+
+#[test]
+fn test_weatherapi_encodes_every_parameter() {
+    let p = weatherapi();
+
+    let url = p
+        .build_url(&Location::from("Trinidad & Tobago"), DataKind::Now, None)
+        .unwrap();
+    assert_eq!(
+        url,
+        format!("http://w/current.json?key={ENCODED_KEY}&q=Trinidad%20%26%20Tobago&lang=pt%20BR")
+    );
+
+    let url = p
+        .build_url(
+            &Location::from("Bosnia & Herzegovina"),
+            DataKind::Forecast,
+            Some(2),
+        )
+        .unwrap();
+    assert!(
+        url.ends_with("&q=Bosnia%20%26%20Herzegovina&days=2&lang=pt%20BR"),
+        "{}",
+        url
+    );
+
+    let url = p.location_search_url("Saint Kitts & Nevis").unwrap();
+    assert_eq!(
+        url,
+        format!("http://w/search.json?key={ENCODED_KEY}&q=Saint%20Kitts%20%26%20Nevis")
+    );
+}
+
+#[test]
+fn test_openweather_encodes_every_parameter() {
+    let p = openweather();
+
+    let url = p
+        .build_url(&Location::from("Trinidad & Tobago"), DataKind::Now, None)
+        .unwrap();
+    assert_eq!(
+        url,
+        format!(
+            "http://o/data/2.5/weather?q=Trinidad%20%26%20Tobago&appid={ENCODED_KEY}\
+             &units=metric&lang=pt%20BR"
+        )
+    );
+
+    let url = p
+        .build_url(&Location::from("Kyiv"), DataKind::Hourly, None)
+        .unwrap();
+    assert!(url.contains("&cnt=8&units=metric&lang=pt%20BR"), "{}", url);
+
+    let place = Place {
+        name: "Kyiv".into(),
+        latitude: 50.45,
+        longitude: 30.52,
+    };
+    assert_eq!(
+        p.air_pollution_url(&place),
+        format!("http://o/data/2.5/air_pollution?lat=50.45&lon=30.52&appid={ENCODED_KEY}")
+    );
+    assert_eq!(
+        p.build_onecall_url(&place, DataKind::Now).unwrap(),
+        format!(
+            "http://o/data/3.0/onecall?lat=50.45&lon=30.52&exclude=minutely,hourly,daily,alerts\
+             &appid={ENCODED_KEY}&units=metric&lang=pt%20BR"
+        )
+    );
+
+    let url = p.location_search_url("Saint Kitts & Nevis").unwrap();
+    assert_eq!(
+        url,
+        format!(
+            "http://o/geo/1.0/direct?q=Saint%20Kitts%20%26%20Nevis&limit=5&appid={ENCODED_KEY}"
+        )
+    );
+}
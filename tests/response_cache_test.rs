@@ -0,0 +1,310 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::http_server::{fast_retries, HttpServer, Reply};
+use wapp::cache::{describe_age, Cache, CacheMode, Namespace, NoCachedData, ResponseCache};
+use wapp::cli::{Cli, Commands};
+use wapp::location::Location;
+use wapp::providers::{is_unavailable, is_unreachable, ApiProvider, DataKind, WeatherApiProvider};
+
+const NOW: u64 = 1_700_000_000;
+const FIRST: &str = r#"{"current":{"temp_c":1.0}}"#;
+const SECOND: &str = r#"{"current":{"temp_c":2.0}}"#;
+
+fn provider(base_url: &str) -> WeatherApiProvider {
+    fast_retries();
+    WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: base_url.into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+fn kyiv() -> Location {
+    Location::from("Kyiv")
+}
+
+/// A response cache in `dir` as seen at `now`.
+fn at(dir: &tempfile::TempDir, mode: CacheMode, now: u64) -> ResponseCache {
+    ResponseCache::new(Cache::new(dir.path()), 600, mode, "metric", now)
+}
+
+#[tokio::test]
+async fn test_fresh_responses_are_reused_until_the_ttl() {
+    let server = HttpServer::start_replies(vec![Reply::ok(FIRST), Reply::ok(SECOND)]).await;
+    let provider = provider(&server.base_url);
+    let dir = tempfile::tempdir().unwrap();
+
+    let body = at(&dir, CacheMode::Use, NOW)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, FIRST);
+
+    // Younger than the TTL: served without a request.
+    let body = at(&dir, CacheMode::Use, NOW + 599)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, FIRST);
+    assert_eq!(server.requests(), 1);
+
+    // As old as the TTL: fetched again and stored.
+    let body = at(&dir, CacheMode::Use, NOW + 600)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, SECOND);
+    assert_eq!(server.requests(), 2);
+
+    let stats = Cache::new(dir.path()).stats();
+    assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 2));
+}
+
+#[tokio::test]
+async fn test_refresh_fetches_and_updates_the_cache() {
+    let server = HttpServer::start_replies(vec![Reply::ok(FIRST), Reply::ok(SECOND)]).await;
+    let provider = provider(&server.base_url);
+    let dir = tempfile::tempdir().unwrap();
+
+    at(&dir, CacheMode::Use, NOW)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    let body = at(&dir, CacheMode::Refresh, NOW + 1)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, SECOND);
+
+    // The refreshed response is what later calls get.
+    let body = at(&dir, CacheMode::Use, NOW + 2)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, SECOND);
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_corrupt_entries_are_fetched_again() {
+    let server = HttpServer::start(FIRST).await;
+    let provider = provider(&server.base_url);
+    let dir = tempfile::tempdir().unwrap();
+    let cache = at(&dir, CacheMode::Use, NOW);
+
+    cache
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    for (path, _) in Cache::new(dir.path()).entries() {
+        std::fs::write(path, "{not json").unwrap();
+    }
+
+    let key = cache.key("weatherapi", &kyiv(), "now", None);
+    assert!(cache.cache.try_get(Namespace::Response, &key).is_err());
+    assert_eq!(cache.lookup(&key), None);
+
+    let body = cache
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, FIRST);
+    assert_eq!(server.requests(), 2);
+}
+
+#[tokio::test]
+async fn test_unwritable_cache_is_ignored() {
+    let server = HttpServer::start(FIRST).await;
+    let provider = provider(&server.base_url);
+    // A file where the cache directory should be.
+    let blocker = tempfile::NamedTempFile::new().unwrap();
+    let cache = ResponseCache::new(Cache::new(blocker.path()), 600, CacheMode::Use, "", NOW);
+
+    let body = cache
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, FIRST);
+}
+
+/// Nothing listens on port 9 of the loopback interface.
+const UNREACHABLE: &str = "http://127.0.0.1:9";
+
+#[tokio::test]
+async fn test_offline_serves_responses_of_any_age() {
+    let server = HttpServer::start(FIRST).await;
+    let dir = tempfile::tempdir().unwrap();
+    at(&dir, CacheMode::Use, NOW)
+        .fetch(&provider(&server.base_url), kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    let body = at(&dir, CacheMode::Offline, NOW + 86_400)
+        .fetch(&provider(UNREACHABLE), kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, FIRST);
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_offline_without_a_cached_response() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let err = at(&dir, CacheMode::Offline, NOW)
+        .fetch(&provider(UNREACHABLE), kyiv(), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert!(err.is::<NoCachedData>());
+    assert_eq!(err.to_string(), "no cached data for Kyiv");
+}
+
+#[tokio::test]
+async fn test_stale_fallback_for_unreachable_providers() {
+    let server = HttpServer::start(FIRST).await;
+    let dir = tempfile::tempdir().unwrap();
+    at(&dir, CacheMode::Use, NOW)
+        .fetch(&provider(&server.base_url), kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    let mut cache = at(&dir, CacheMode::Use, NOW + 3600);
+    let err = cache
+        .fetch(&provider(UNREACHABLE), kyiv(), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert!(is_unreachable(&err));
+
+    cache.stale_fallback = true;
+    let body = cache
+        .fetch(&provider(UNREACHABLE), kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, FIRST);
+}
+
+#[tokio::test]
+async fn test_stale_fallback_ignores_server_errors() {
+    let reply = Reply {
+        status: 503,
+        headers: Vec::new(),
+        body: "busy",
+    };
+    let server = HttpServer::start_replies(vec![Reply::ok(FIRST), reply]).await;
+    let provider = provider(&server.base_url);
+    let dir = tempfile::tempdir().unwrap();
+    at(&dir, CacheMode::Use, NOW)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    let mut cache = at(&dir, CacheMode::Use, NOW + 3600);
+    cache.stale_fallback = true;
+    let err = cache
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert!(is_unavailable(&err));
+    assert!(!is_unreachable(&err));
+}
+
+#[tokio::test]
+async fn test_connection_errors_are_unreachable() {
+    let err = provider(UNREACHABLE)
+        .get_data(kyiv(), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(is_unavailable(&err));
+    assert!(is_unreachable(&err));
+}
+
+#[test]
+fn test_describe_age() {
+    assert_eq!(describe_age(59), "just now");
+    assert_eq!(describe_age(60), "1 minute ago");
+    assert_eq!(describe_age(42 * 60 + 30), "42 minutes ago");
+    assert_eq!(describe_age(3 * 3600), "3 hours ago");
+    assert_eq!(describe_age(86_400), "1 day ago");
+}
+
+#[test]
+fn test_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let metric = at(&dir, CacheMode::Use, NOW);
+    let imperial = ResponseCache::new(Cache::new(dir.path()), 600, CacheMode::Use, "imperial", NOW);
+
+    let key = metric.key("weatherapi", &kyiv(), "forecast", Some(3));
+    assert!(key.starts_with("weatherapi:forecast:city:kyiv:3d#"));
+    assert_ne!(key, metric.key("weatherapi", &kyiv(), "forecast", Some(5)));
+    assert_ne!(
+        key,
+        imperial.key("weatherapi", &kyiv(), "forecast", Some(3))
+    );
+    assert_ne!(
+        metric.key("weatherapi", &kyiv(), "now", None),
+        metric.key("openweather", &kyiv(), "now", None)
+    );
+    assert!(metric
+        .key(
+            "wttr",
+            &Location::coords(50.45, 30.52).unwrap(),
+            "now",
+            None
+        )
+        .starts_with("wttr:now:coords:50.45,30.52#"));
+}
+
+#[test]
+fn test_parse_cache_flags() {
+    let cli = Cli::parse_from(vec![
+        "wapp",
+        "get",
+        "--city",
+        "Kyiv",
+        "--cache-ttl",
+        "60",
+        "--refresh",
+    ]);
+    match cli.cmd {
+        Commands::Get {
+            cache_ttl,
+            refresh,
+            no_cache,
+            ..
+        } => assert_eq!((cache_ttl, refresh, no_cache), (Some(60), true, false)),
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(vec!["wapp", "get", "--no-cache", "--refresh"]).is_err());
+
+    let cli = Cli::parse_from(vec!["wapp", "get", "--city", "Kyiv", "--offline"]);
+    assert!(matches!(cli.cmd, Commands::Get { offline: true, .. }));
+    for conflict in ["--no-cache", "--refresh"] {
+        assert!(Cli::try_parse_from(vec!["wapp", "get", "--offline", conflict]).is_err());
+    }
+}
+
+#[tokio::test]
+async fn test_cache_at_a_later_time_sees_the_response_expire() {
+    let server = HttpServer::start_replies(vec![Reply::ok(FIRST), Reply::ok(SECOND)]).await;
+    let provider = provider(&server.base_url);
+    let dir = tempfile::tempdir().unwrap();
+    let cache = at(&dir, CacheMode::Use, NOW);
+
+    cache
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+    let body = cache
+        .at(NOW + 600)
+        .fetch(&provider, kyiv(), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    assert_eq!(body, SECOND);
+    assert_eq!(server.requests(), 2);
+}
@@ -0,0 +1,104 @@
+mod mocks;
+
+use std::time::Duration;
+
+use clap::Parser;
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::weatherapi;
+use wapp::cli::Cli;
+use wapp::config::AppConfig;
+use wapp::error::WappError;
+use wapp::location::Location;
+use wapp::providers::{is_unavailable, ApiProvider, DataKind};
+use wapp::retry::{retry_policy, RetryPolicy, DEFAULT_ATTEMPTS};
+
+const BODY: &str = r#"{"current":{"temp_c":1.0}}"#;
+
+fn status(status: u16) -> Reply {
+    Reply {
+        status,
+        headers: Vec::new(),
+        body: r#"{"error":{"code":9999,"message":"Internal application error."}}"#,
+    }
+}
+
+async fn get(server: &HttpServer) -> anyhow::Result<String> {
+    weatherapi(&server.base_url)
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+}
+
+#[tokio::test]
+async fn test_transient_failures_are_retried() {
+    let server = HttpServer::start_replies(vec![status(503), status(429), Reply::ok(BODY)]).await;
+
+    assert_eq!(get(&server).await.unwrap(), BODY);
+    assert_eq!(server.requests(), 3);
+}
+
+#[tokio::test]
+async fn test_retries_give_up_after_the_last_attempt() {
+    let server = HttpServer::start_replies(vec![status(500)]).await;
+
+    let err = get(&server).await.unwrap_err();
+    assert!(is_unavailable(&err));
+    assert_eq!(server.requests(), DEFAULT_ATTEMPTS as usize);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let server = HttpServer::start_replies(vec![status(400), Reply::ok(BODY)]).await;
+
+    let err = get(&server).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref(),
+        Some(WappError::Http { status: 400, .. })
+    ));
+    assert_eq!(server.requests(), 1);
+}
+
+#[tokio::test]
+async fn test_connection_errors_are_retried() {
+    assert_eq!(retry_policy().attempts, DEFAULT_ATTEMPTS);
+    let before = wapp::metrics::snapshot().retries;
+
+    let err = weatherapi("http://127.0.0.1:9")
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+    assert!(is_unavailable(&err));
+    // Other tests may retry at the same time.
+    assert!(wapp::metrics::snapshot().retries >= before + 2);
+}
+
+#[test]
+fn test_backoff_doubles_with_jitter() {
+    let policy = RetryPolicy::default();
+
+    assert_eq!(policy.delay(1, 0.0), Duration::from_millis(250));
+    assert_eq!(policy.delay(1, 1.0), Duration::from_millis(500));
+    assert_eq!(policy.delay(2, 0.5), Duration::from_millis(750));
+    assert_eq!(policy.delay(3, 1.0), Duration::from_secs(2));
+    // Capped at `max_delay`.
+    assert_eq!(policy.delay(10, 1.0), Duration::from_secs(8));
+    assert_eq!(policy.delay(40, 2.0), Duration::from_secs(8));
+
+    assert_eq!(RetryPolicy::with_attempts(0).attempts, 1);
+}
+
+#[test]
+fn test_retries_option() {
+    let cli = Cli::try_parse_from(vec!["wapp", "--retries", "5", "get", "--city", "Kyiv"]).unwrap();
+    assert_eq!(cli.retries, Some(5));
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--retries", "1"]).unwrap();
+    assert_eq!(cli.retries, Some(1));
+    assert!(Cli::try_parse_from(vec!["wapp", "--retries", "0", "paths"]).is_err());
+
+    let mut cfg = AppConfig::default();
+    cfg.set_key("retries", "2").unwrap();
+    assert_eq!(cfg.retries, Some(2));
+    assert_eq!(
+        cfg.set_key("retries", "0").unwrap_err().to_string(),
+        "invalid value '0' for retries: expected a number of attempts, at least 1"
+    );
+}
@@ -0,0 +1,302 @@
+mod mocks;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use wapp::cache::{
+    is_conditional, Cache, CacheEntry, CacheMode, CacheOutcome, Namespace, ResponseCache,
+    Validators, VALIDATED_TTL_SECS,
+};
+use wapp::providers::FetchResponse;
+use wapp::transport::HttpRequest;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const ETAG: &str = "\"v1\"";
+const LAST_MODIFIED: &str = "Wed, 01 May 2024 09:00:00 GMT";
+
+/// A WeatherAPI answering `/current.json` with `body` and both validators,
+/// and `304 Not Modified` to a request asking with the same `ETag`.
+async fn serve(body: &'static str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .and(header("if-none-match", ETAG))
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("etag", ETAG)
+                .insert_header("last-modified", LAST_MODIFIED)
+                .set_body_raw(body, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn get(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, base_url);
+    cmd.env_remove("WAPP_LOG").env_remove("RUST_LOG").args([
+        "get",
+        "--city",
+        "Kyiv",
+        "--provider",
+        "weatherapi",
+        "--output",
+        "json",
+        "-v",
+    ]);
+    cmd
+}
+
+/// The temperature printed by `cmd` and what it logged.
+fn run(cmd: &mut Command) -> (serde_json::Value, String) {
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    (
+        json["temp_c"].clone(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+fn header_of<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request.headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn response_cache(dir: &Path, mode: CacheMode, now: u64) -> ResponseCache {
+    ResponseCache::new(Cache::new(dir), 600, mode, "", now)
+}
+
+fn ok(body: &str, etag: Option<&str>) -> FetchResponse {
+    FetchResponse {
+        status: 200,
+        last_modified: None,
+        etag: etag.map(String::from),
+        retry_after: None,
+        content_type: Some("application/json".into()),
+        body: body.into(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_unchanged_response_is_revalidated() {
+    let server = serve(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let (temp, log) = run(&mut get(dir.path(), &server.uri()));
+    assert_eq!(temp, 24.0);
+    assert!(log.contains("fetched"), "{}", log);
+
+    let (temp, log) = run(get(dir.path(), &server.uri()).arg("--refresh"));
+    assert_eq!(temp, 24.0);
+    assert!(log.contains("response not modified"), "{}", log);
+    assert!(log.contains("revalidated"), "{}", log);
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(header_of(&requests[0], "if-none-match"), None);
+    assert_eq!(header_of(&requests[0], "if-modified-since"), None);
+    assert_eq!(header_of(&requests[1], "if-none-match"), Some(ETAG));
+    assert_eq!(
+        header_of(&requests[1], "if-modified-since"),
+        Some(LAST_MODIFIED)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fresh_responses_are_not_revalidated() {
+    let server = serve(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&mut get(dir.path(), &server.uri()));
+    let (temp, log) = run(&mut get(dir.path(), &server.uri()));
+    assert_eq!(temp, 24.0);
+    assert!(log.contains("fresh hit"), "{}", log);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_changed_response_is_fetched_and_kept() {
+    let server = serve(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    run(&mut get(dir.path(), &server.uri()));
+
+    // A new version: the old ETag no longer matches.
+    server.reset().await;
+    let changed = WEATHERAPI_CURRENT.replace("\"temp_c\": 24.0", "\"temp_c\": 19.5");
+    assert_ne!(changed, WEATHERAPI_CURRENT);
+    Mock::given(path("/current.json"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("etag", "\"v2\"")
+                .set_body_raw(changed, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let (temp, log) = run(get(dir.path(), &server.uri()).arg("--refresh"));
+    assert_eq!(temp, 19.5);
+    assert!(!log.contains("revalidated"), "{}", log);
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(header_of(&requests[0], "if-none-match"), Some(ETAG));
+
+    // The next revalidation asks for the new version.
+    run(get(dir.path(), &server.uri()).arg("--refresh"));
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(header_of(&requests[1], "if-none-match"), Some("\"v2\""));
+    assert_eq!(header_of(&requests[1], "if-modified-since"), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_responses_without_validators_are_fetched_again() {
+    let server = MockServer::start().await;
+    Mock::given(path("/current.json"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(WEATHERAPI_CURRENT, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&mut get(dir.path(), &server.uri()));
+    run(get(dir.path(), &server.uri()).arg("--refresh"));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(header_of(&requests[1], "if-none-match"), None);
+    assert_eq!(header_of(&requests[1], "if-modified-since"), None);
+    let cache = Cache::new(dir.path().join("cache"));
+    assert!(cache
+        .entries()
+        .iter()
+        .all(|(_, entry)| entry.namespace != Namespace::Http));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_no_cache_sends_no_conditional_request() {
+    let server = serve(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    run(&mut get(dir.path(), &server.uri()));
+    run(get(dir.path(), &server.uri()).arg("--no-cache"));
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(header_of(&requests[1], "if-none-match"), None);
+}
+
+#[test]
+fn test_validated_responses_are_kept_with_their_headers() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = response_cache(dir.path(), CacheMode::Use, 1_000);
+    let request = HttpRequest::get("http://api.example/current.json?key=SECRET&q=Kyiv");
+
+    cache.keep_validated(&request, "weatherapi", &ok("{}", Some(ETAG)));
+    let entry = cache.validated(&request).unwrap();
+    assert_eq!(entry.namespace, Namespace::Http);
+    assert_eq!(
+        entry.key,
+        "GET http://api.example/current.json?key=<redacted>&q=Kyiv"
+    );
+    assert_eq!(entry.body, "{}");
+    assert_eq!(entry.validators.etag.as_deref(), Some(ETAG));
+    assert_eq!(entry.expires, Some(1_000 + VALIDATED_TTL_SECS));
+    assert_eq!(entry.kind(), "http");
+
+    // Kept a week, then forgotten.
+    let later = response_cache(dir.path(), CacheMode::Refresh, 1_000 + VALIDATED_TTL_SECS);
+    assert!(later.validated(&request).is_none());
+    // Offline, nothing is asked.
+    let offline = response_cache(dir.path(), CacheMode::Offline, 1_000);
+    assert!(offline.validated(&request).is_none());
+}
+
+#[test]
+fn test_only_successful_json_with_validators_is_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = response_cache(dir.path(), CacheMode::Use, 1_000);
+    let request = HttpRequest::get("http://api.example/current.json");
+
+    cache.keep_validated(&request, "weatherapi", &ok("{}", None));
+    cache.keep_validated(&request, "weatherapi", &ok("<html>", Some(ETAG)));
+    let failed = FetchResponse {
+        status: 404,
+        ..ok("{}", Some(ETAG))
+    };
+    cache.keep_validated(&request, "weatherapi", &failed);
+    assert!(cache.validated(&request).is_none());
+    assert!(Cache::new(dir.path()).entries().is_empty());
+}
+
+#[test]
+fn test_revalidation_renews_the_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let request = HttpRequest::get("http://api.example/current.json");
+    let first = response_cache(dir.path(), CacheMode::Use, 1_000);
+    first.keep_validated(
+        &request,
+        "weatherapi",
+        &FetchResponse {
+            last_modified: Some(LAST_MODIFIED.into()),
+            ..ok("{\"v\":1}", Some(ETAG))
+        },
+    );
+    let entry = first.validated(&request).unwrap();
+
+    // A 304 may carry a new ETag; the other validator is kept.
+    let later = response_cache(dir.path(), CacheMode::Use, 5_000);
+    later.revalidated(
+        &entry,
+        Validators {
+            etag: Some("\"v1b\"".into()),
+            last_modified: None,
+        },
+    );
+    let renewed = later.validated(&request).unwrap();
+    assert_eq!(renewed.created, 5_000);
+    assert_eq!(renewed.expires, Some(5_000 + VALIDATED_TTL_SECS));
+    assert_eq!(renewed.body, "{\"v\":1}");
+    assert_eq!(
+        renewed.validators.headers(),
+        [
+            ("If-None-Match", "\"v1b\""),
+            ("If-Modified-Since", LAST_MODIFIED)
+        ]
+    );
+}
+
+#[test]
+fn test_entry_format_and_conditional_requests() {
+    // Entries written before validators were kept still read, and entries
+    // without them are written as before.
+    let old = r#"{"namespace":"response","key":"k","provider":"wttr","city":"Kyiv",
+        "created":1,"expires":null,"body":"{}"}"#;
+    let entry: CacheEntry = serde_json::from_str(old).unwrap();
+    assert!(entry.validators.is_empty());
+    assert!(!serde_json::to_string(&entry)
+        .unwrap()
+        .contains("validators"));
+
+    // met.no asks on its own; such a request is left as it is.
+    let request = HttpRequest::get("http://api.met.no/compact");
+    assert!(!is_conditional(&request));
+    assert!(is_conditional(
+        &request.header("if-modified-since", LAST_MODIFIED)
+    ));
+
+    let outcomes = [
+        CacheOutcome::Fresh,
+        CacheOutcome::Revalidated,
+        CacheOutcome::Fetched,
+    ];
+    assert_eq!(
+        outcomes.map(CacheOutcome::as_str),
+        ["fresh hit", "revalidated", "fetched"]
+    );
+}
@@ -0,0 +1,173 @@
+mod mocks;
+
+use std::time::Duration;
+
+use mocks::http_server::{HttpServer, Reply};
+use tokio::time::Instant;
+use wapp::rules::{
+    apply, dispatch, field_value, Rule, RuleAction, RuleEngine, RuleExit, RuleOutcome,
+};
+use wapp::webhook::{use_webhook, Webhook};
+
+const WEATHERAPI: &str = r#"{"current":{"temp_c":-3.0,"humidity":80,"wind_kph":12.0}}"#;
+const OPENWEATHER: &str = r#"{"main":{"temp":270.1,"humidity":64},"clouds":{"all":40}}"#;
+
+fn rule(name: &str, expr: &str, action: RuleAction, cooldown: Option<u64>) -> Rule {
+    Rule {
+        name: name.into(),
+        expr: expr.into(),
+        action,
+        url: None,
+        cooldown_secs: cooldown,
+    }
+}
+
+fn fired(outcomes: &[RuleOutcome]) -> Vec<String> {
+    outcomes
+        .iter()
+        .filter_map(|o| match o {
+            RuleOutcome::Fired(rule) => Some(rule.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_rule_config_format() {
+    let rule: Rule = serde_json::from_str(
+        r#"{"name":"frost","expr":"temp_c < 0","action":"exit-code","cooldown_secs":60}"#,
+    )
+    .unwrap();
+
+    assert_eq!(rule.action, RuleAction::ExitCode);
+    assert_eq!(rule.cooldown_secs, Some(60));
+}
+
+#[test]
+fn test_field_aliases_and_paths() {
+    let wa: serde_json::Value = serde_json::from_str(WEATHERAPI).unwrap();
+    let owm: serde_json::Value = serde_json::from_str(OPENWEATHER).unwrap();
+
+    assert_eq!(field_value(&wa, "temp_c"), Some(-3.0));
+    assert_eq!(field_value(&wa, "humidity"), Some(80.0));
+    assert_eq!(field_value(&owm, "humidity"), Some(64.0));
+    assert_eq!(field_value(&owm, "cloud"), Some(40.0));
+    assert_eq!(field_value(&owm, "main.temp"), Some(270.1));
+    assert_eq!(field_value(&owm, "temp_c"), None);
+
+    let list: serde_json::Value = serde_json::from_str(r#"{"list":[{"v":1},{"v":2}]}"#).unwrap();
+    assert_eq!(field_value(&list, "list.1.v"), Some(2.0));
+}
+
+#[test]
+fn test_cooldown_suppresses_repeat_firing() {
+    let mut engine = RuleEngine::new(vec![rule(
+        "frost",
+        "temp_c < 0",
+        RuleAction::Log,
+        Some(600),
+    )]);
+    let t0 = Instant::now();
+
+    assert_eq!(fired(&engine.evaluate(WEATHERAPI, t0)), vec!["frost"]);
+
+    let outcomes = engine.evaluate(WEATHERAPI, t0 + Duration::from_secs(300));
+    assert_eq!(outcomes, vec![RuleOutcome::CoolingDown("frost".into())]);
+
+    let outcomes = engine.evaluate(WEATHERAPI, t0 + Duration::from_secs(600));
+    assert_eq!(fired(&outcomes), vec!["frost"]);
+}
+
+#[test]
+fn test_no_cooldown_fires_every_time() {
+    let mut engine = RuleEngine::new(vec![rule("frost", "temp_c < 0", RuleAction::Log, None)]);
+    let t0 = Instant::now();
+
+    assert_eq!(fired(&engine.evaluate(WEATHERAPI, t0)).len(), 1);
+    assert_eq!(fired(&engine.evaluate(WEATHERAPI, t0)).len(), 1);
+}
+
+#[test]
+fn test_broken_rules_do_not_affect_others() {
+    let mut engine = RuleEngine::new(vec![
+        rule("syntax", "temp_c <", RuleAction::Log, None),
+        rule("missing", "uv > 5", RuleAction::Log, None),
+        rule("frost", "temp_c < 0", RuleAction::Log, None),
+        rule("heat", "temp_c > 30", RuleAction::Log, None),
+    ]);
+
+    let outcomes = engine.evaluate(WEATHERAPI, Instant::now());
+
+    assert!(matches!(&outcomes[0], RuleOutcome::Failed(name, _) if name == "syntax"));
+    assert!(matches!(&outcomes[1], RuleOutcome::Failed(name, _) if name == "missing"));
+    assert_eq!(fired(&outcomes), vec!["frost"]);
+    assert_eq!(outcomes[3], RuleOutcome::Clear("heat".into()));
+}
+
+#[tokio::test]
+async fn test_apply_exit_code_rule() {
+    let mut engine = RuleEngine::new(vec![
+        rule("log", "humidity > 50", RuleAction::Log, None),
+        rule("frost", "temp_c < 0", RuleAction::ExitCode, None),
+    ]);
+
+    let err = apply(&mut engine, WEATHERAPI, Instant::now())
+        .await
+        .unwrap_err();
+
+    assert_eq!(err.downcast_ref::<RuleExit>().unwrap().rule, "frost");
+}
+
+#[tokio::test]
+async fn test_failed_action_does_not_stop_other_rules() {
+    let mut engine = RuleEngine::new(vec![
+        // Webhook without a url fails to dispatch.
+        rule("hook", "temp_c < 0", RuleAction::Webhook, None),
+        rule("frost", "temp_c < 0", RuleAction::ExitCode, None),
+    ]);
+
+    let err = apply(&mut engine, WEATHERAPI, Instant::now())
+        .await
+        .unwrap_err();
+
+    assert!(err.is::<RuleExit>());
+}
+
+#[tokio::test]
+async fn test_apply_without_matches_is_ok() {
+    let mut engine = RuleEngine::new(vec![rule(
+        "heat",
+        "temp_c > 30",
+        RuleAction::ExitCode,
+        None,
+    )]);
+
+    assert!(apply(&mut engine, WEATHERAPI, Instant::now()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_webhook_rule_posts_like_get_webhook() {
+    let server = HttpServer::start_replies(vec![Reply {
+        status: 500,
+        ..Reply::ok("")
+    }])
+    .await;
+    use_webhook(Webhook {
+        url: "http://unused".into(),
+        headers: vec![("Authorization".into(), "Bearer TOKEN".into())],
+        strict: false,
+    });
+    let hook = Rule {
+        url: Some(format!("{}/hook?token=secret", server.base_url)),
+        ..rule("hook", "temp_c < 0", RuleAction::Webhook, None)
+    };
+
+    let err = dispatch(&hook, WEATHERAPI).await.unwrap_err().to_string();
+
+    assert_eq!(err, "webhook answered 500 Internal Server Error");
+    assert!(
+        server.received()[0].contains("authorization: bearer token"),
+        "{:?}",
+        server.received()
+    );
+}
@@ -0,0 +1,227 @@
+mod mocks;
+
+use std::time::Duration;
+
+use clap::Parser;
+use mocks::http_server::HttpServer;
+use wapp::cli::Cli;
+use wapp::exit::{exit_code, USAGE_EXIT_CODE};
+use wapp::runtime::{build, flavor_for, RuntimeFlavor, THREADS_ENV};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// What a run of wapp printed: its exit code, stdout and stderr, with the
+/// directory of the run written `<dir>`.
+type Run = (Option<i32>, String, String);
+
+/// Runs wapp with `args` in a directory of its own, against `base_url` for
+/// every provider, with [`THREADS_ENV`] set to `threads`.
+fn run(base_url: &str, threads: &str, args: &[&str]) -> Run {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{ "provider": "weatherapi" }"#,
+    )
+    .unwrap();
+    let output = mocks::cli::wapp(dir.path(), base_url)
+        .env_remove("RUST_BACKTRACE")
+        .env_remove("RUST_LIB_BACKTRACE")
+        .env("WTTR_BASE_URL", base_url)
+        .env(THREADS_ENV, threads)
+        .args(args)
+        .output()
+        .unwrap();
+    let text = |bytes: Vec<u8>| {
+        String::from_utf8(bytes)
+            .unwrap()
+            .replace(&dir.path().display().to_string(), "<dir>")
+    };
+    (
+        output.status.code(),
+        text(output.stdout),
+        text(output.stderr),
+    )
+}
+
+/// Runs `args` on one thread and on a pool of two, checks both runs print
+/// the same, and returns what they printed.
+fn under_both_flavors(base_url: &str, args: &[&str]) -> Run {
+    let single = run(base_url, "1", args);
+    let pool = run(base_url, "2", args);
+    assert_eq!(single, pool, "{:?}", args);
+    single
+}
+
+fn is_concurrent(args: &[&str]) -> bool {
+    Cli::parse_from([&["wapp"], args].concat()).is_concurrent()
+}
+
+#[test]
+fn test_single_requests_run_on_the_current_thread() {
+    assert_eq!(
+        flavor_for(false, None).unwrap(),
+        RuntimeFlavor::CurrentThread
+    );
+    assert_eq!(
+        flavor_for(true, None).unwrap(),
+        RuntimeFlavor::MultiThread { workers: None }
+    );
+    // An empty variable is not set.
+    assert_eq!(
+        flavor_for(true, Some(" ")).unwrap(),
+        RuntimeFlavor::MultiThread { workers: None }
+    );
+}
+
+#[test]
+fn test_threads_override_the_choice() {
+    assert_eq!(
+        flavor_for(true, Some("1")).unwrap(),
+        RuntimeFlavor::CurrentThread
+    );
+    assert_eq!(
+        flavor_for(false, Some("4")).unwrap(),
+        RuntimeFlavor::MultiThread { workers: Some(4) }
+    );
+    assert_eq!(
+        flavor_for(true, Some(" 2 ")).unwrap(),
+        RuntimeFlavor::MultiThread { workers: Some(2) }
+    );
+}
+
+#[test]
+fn test_invalid_threads_are_usage_errors() {
+    for invalid in ["0", "-1", "two", "1.5"] {
+        let err = flavor_for(false, Some(invalid)).unwrap_err();
+        assert_eq!(exit_code(&err), Some(USAGE_EXIT_CODE), "{}", invalid);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "invalid WAPP_THREADS '{}': expected a number of threads, at least 1",
+                invalid
+            )
+        );
+    }
+}
+
+#[test]
+fn test_concurrent_commands() {
+    assert!(is_concurrent(&["get", "--city", "Kyiv", "--city", "Lviv"]));
+    assert!(is_concurrent(&["get", "--cities-from", "cities.txt"]));
+    assert!(is_concurrent(&["get", "--city", "Kyiv", "--watch", "60"]));
+    assert!(is_concurrent(&[
+        "compare", "--city", "Kyiv", "--city", "Lviv"
+    ]));
+    assert!(is_concurrent(&["compare-providers", "--city", "Kyiv"]));
+    assert!(is_concurrent(&["monitor", "--city", "Miami", "--once"]));
+    assert!(is_concurrent(&[
+        "daemon", "--city", "Kyiv", "--output", "now.json"
+    ]));
+
+    assert!(!is_concurrent(&["get", "--city", "Kyiv"]));
+    assert!(!is_concurrent(&["now", "Kyiv"]));
+    assert!(!is_concurrent(&[
+        "check",
+        "--city",
+        "Kyiv",
+        "--condition",
+        "rain"
+    ]));
+    assert!(!is_concurrent(&["paths"]));
+}
+
+#[test]
+fn test_both_flavors_run_timers_and_tasks() {
+    let flavors = [
+        RuntimeFlavor::CurrentThread,
+        RuntimeFlavor::MultiThread { workers: Some(2) },
+        RuntimeFlavor::MultiThread { workers: None },
+    ];
+    for flavor in flavors {
+        let rt = build(flavor).unwrap();
+        let sum = rt.block_on(async {
+            let task = tokio::spawn(async {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                20
+            });
+            task.await.unwrap() + 1
+        });
+        assert_eq!(sum, 21, "{:?}", flavor);
+    }
+}
+
+#[test]
+fn test_local_commands_under_both_flavors() {
+    let commands: [&[&str]; 8] = [
+        &["paths"],
+        &["providers"],
+        &["config", "show"],
+        &["cache", "list"],
+        &["location", "list"],
+        &["profile", "list"],
+        &["completions", "bash"],
+        &["get", "--city", "Kyiv", "--dry-run"],
+    ];
+    for args in commands {
+        let (code, stdout, stderr) = under_both_flavors("http://127.0.0.1:9", args);
+        assert_eq!(code, Some(0), "{:?}: {}", args, stderr);
+        assert!(!stdout.is_empty(), "{:?}", args);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_requests_under_both_flavors() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let commands: [&[&str]; 5] = [
+        &["get", "--city", "Kyiv", "--output", "json"],
+        &[
+            "get", "--city", "Kyiv", "--city", "Lviv", "--output", "json",
+        ],
+        &["now", "Kyiv"],
+        &[
+            "compare", "--city", "Kyiv", "--city", "Lviv", "--data", "now",
+        ],
+        &["compare-providers", "--city", "Kyiv"],
+    ];
+    for args in commands {
+        let (code, stdout, stderr) = under_both_flavors(&server.base_url, args);
+        assert_eq!(code, Some(0), "{:?}: {}", args, stderr);
+        assert!(!stdout.is_empty(), "{:?}", args);
+    }
+    let (_, json, _) = run(&server.base_url, "1", commands[0]);
+    let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(json["temp_c"], 24.0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_failures_under_both_flavors() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+
+    // Nothing to check in the response, and no weather alerts to monitor.
+    let commands: [&[&str]; 3] = [
+        &["check", "--city", "Kyiv", "--condition", "rain"],
+        &["monitor", "--city", "Kyiv", "--once"],
+        &["--errors", "json", "get", "--no-auto-locate"],
+    ];
+    for args in commands {
+        let (code, _, stderr) = under_both_flavors(&server.base_url, args);
+        assert_ne!(code, Some(0), "{:?}", args);
+        assert!(!stderr.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_invalid_threads_fail_before_running() {
+    let (code, stdout, stderr) = run("http://127.0.0.1:9", "0", &["paths"]);
+    assert_eq!(code, Some(USAGE_EXIT_CODE));
+    assert_eq!(stdout, "");
+    assert_eq!(
+        stderr,
+        "Error: invalid WAPP_THREADS '0': expected a number of threads, at least 1\n"
+    );
+
+    let (code, _, stderr) = run("http://127.0.0.1:9", "many", &["--errors", "json", "paths"]);
+    assert_eq!(code, Some(USAGE_EXIT_CODE));
+    let json: serde_json::Value = serde_json::from_str(&stderr).unwrap();
+    assert_eq!(json["error"]["kind"], "usage");
+}
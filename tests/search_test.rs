@@ -0,0 +1,194 @@
+mod mocks;
+
+use clap::Parser;
+use mocks::http_server::HttpServer;
+use mocks::providers::weatherapi;
+use wapp::cli::{render_matches, Cli, Commands, ListFormat};
+use wapp::location::LocationMatch;
+use wapp::providers::{
+    search_locations, AccuWeatherProvider, ApiProvider, OpenMeteoProvider, OpenWeatherProvider,
+    WttrProvider,
+};
+
+const WEATHERAPI: &str = r#"[
+    {"id":2801268,"name":"London","region":"City of London, Greater London","country":"United Kingdom","lat":51.52,"lon":-0.11,"url":"london-city-of-london-greater-london-united-kingdom"},
+    {"id":315398,"name":"London","region":"Ontario","country":"Canada","lat":42.98,"lon":-81.25,"url":"london-ontario-canada"}
+]"#;
+
+const OPENWEATHER: &str = r#"[{"name":"London","local_names":{"uk":"Лондон"},"lat":51.5073219,"lon":-0.1276474,"country":"GB","state":"England"}]"#;
+
+const OPENMETEO: &str = r#"{"results":[{"id":2643743,"name":"London","latitude":51.50853,"longitude":-0.12574,"country_code":"GB","country":"United Kingdom","admin1":"England"}],"generationtime_ms":0.8}"#;
+
+const ACCUWEATHER: &str = r#"[{"Key":"328328","LocalizedName":"London","AdministrativeArea":{"LocalizedName":"London"},"Country":{"ID":"GB","LocalizedName":"United Kingdom"},"GeoPosition":{"Latitude":51.507,"Longitude":-0.127}}]"#;
+
+fn london(region: Option<&str>, country: Option<&str>) -> LocationMatch {
+    LocationMatch {
+        name: "London".into(),
+        region: region.map(String::from),
+        country: country.map(String::from),
+        lat: 51.5073,
+        lon: -0.1276,
+    }
+}
+
+#[test]
+fn test_search_urls() {
+    assert_eq!(
+        weatherapi("http://w").location_search_url("New York"),
+        Some("http://w/search.json?key=KEY&q=New%20York".into())
+    );
+    assert_eq!(
+        OpenWeatherProvider::new("KEY").location_search_url("London"),
+        Some("https://api.openweathermap.org/geo/1.0/direct?q=London&limit=5&appid=KEY".into())
+    );
+
+    let openmeteo = OpenMeteoProvider {
+        base_url: "http://f".into(),
+        geocoding_url: "http://g".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    assert_eq!(
+        openmeteo.location_search_url("London"),
+        Some("http://g/search?name=London&count=10&format=json".into())
+    );
+
+    let wttr = WttrProvider {
+        base_url: "http://t".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    assert_eq!(wttr.location_search_url("London"), None);
+}
+
+#[test]
+fn test_search_responses_are_read() {
+    let matches = weatherapi("http://w").location_matches(WEATHERAPI).unwrap();
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[1].region.as_deref(), Some("Ontario"));
+    assert_eq!(matches[1].country.as_deref(), Some("Canada"));
+    assert_eq!((matches[1].lat, matches[1].lon), (42.98, -81.25));
+
+    let matches = OpenWeatherProvider::new("KEY")
+        .location_matches(OPENWEATHER)
+        .unwrap();
+    assert_eq!(matches[0].region.as_deref(), Some("England"));
+    assert_eq!(matches[0].country.as_deref(), Some("GB"));
+
+    let openmeteo = OpenMeteoProvider {
+        base_url: "http://f".into(),
+        geocoding_url: "http://g".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let matches = openmeteo.location_matches(OPENMETEO).unwrap();
+    assert_eq!(matches[0].country.as_deref(), Some("United Kingdom"));
+    assert_eq!(
+        openmeteo.location_matches(r#"{"generationtime_ms":0.2}"#),
+        Some(Vec::new())
+    );
+
+    let accuweather = AccuWeatherProvider {
+        api_key: "KEY".into(),
+        base_url: "http://a".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let matches = accuweather.location_matches(ACCUWEATHER).unwrap();
+    assert_eq!(matches[0].region.as_deref(), Some("London"));
+    assert_eq!((matches[0].lat, matches[0].lon), (51.507, -0.127));
+
+    assert!(weatherapi("http://w").location_matches("<html>").is_none());
+}
+
+#[tokio::test]
+async fn test_search_locations_fetches_matches() {
+    let server = HttpServer::start(WEATHERAPI).await;
+    let provider = weatherapi(&server.base_url);
+
+    let matches = search_locations(&provider, "London").await.unwrap();
+
+    assert_eq!(matches.len(), 2);
+    let head = &server.received()[0];
+    assert!(head.starts_with("get /search.json?key=key&q=london"));
+    // Every request names the client and accepts compressed responses.
+    assert!(head.contains(&format!(
+        "user-agent: wapp/{}\r\n",
+        env!("CARGO_PKG_VERSION")
+    )));
+    let encodings = head
+        .lines()
+        .find_map(|line| line.strip_prefix("accept-encoding:"))
+        .unwrap_or_default();
+    assert!(
+        encodings.contains("gzip") && encodings.contains("br"),
+        "{}",
+        head
+    );
+}
+
+#[tokio::test]
+async fn test_search_is_not_supported_everywhere() {
+    let wttr = WttrProvider {
+        base_url: "http://127.0.0.1:9".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    let err = search_locations(&wttr, "London").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "location search is not supported by this provider (wttr)"
+    );
+}
+
+#[test]
+fn test_render_text() {
+    let matches = vec![
+        london(Some("England"), Some("GB")),
+        london(None, Some("United States")),
+    ];
+
+    assert_eq!(
+        render_matches("London", &matches, ListFormat::Text),
+        "1. London, England, GB (51.5073, -0.1276)\n2. London, United States (51.5073, -0.1276)\n"
+    );
+    assert_eq!(
+        render_matches("Atlantis", &[], ListFormat::Text),
+        "No places found for 'Atlantis'.\n"
+    );
+}
+
+#[test]
+fn test_render_json() {
+    let out = render_matches("London", &[london(None, Some("GB"))], ListFormat::Json);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    assert_eq!(json[0]["name"], "London");
+    assert_eq!(json[0]["region"], serde_json::Value::Null);
+    assert_eq!(json[0]["country"], "GB");
+    assert_eq!(json[0]["lat"], 51.5073);
+    assert_eq!(render_matches("x", &[], ListFormat::Json), "[]\n");
+}
+
+#[test]
+fn test_parse_search() {
+    let cli = Cli::try_parse_from(vec!["wapp", "search", "Paris", "--output", "json"]).unwrap();
+    match cli.cmd {
+        Commands::Search {
+            query,
+            provider,
+            output,
+        } => {
+            assert_eq!(query, "Paris");
+            assert_eq!(provider, None);
+            assert_eq!(output, ListFormat::Json);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
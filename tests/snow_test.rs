@@ -0,0 +1,144 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use mocks::providers::weatherapi;
+use wapp::cli::{Cli, Commands};
+use wapp::location::Location;
+use wapp::output::{self, OutputFormat};
+use wapp::providers::{validate_kind, ApiProvider, DataKind, TomorrowIoProvider};
+
+/// Chamonix: heavy snow on 2024-12-10, light snow on the 11th, none on the
+/// 12th.
+const WEATHERAPI_SNOW: &str = include_str!("fixtures/weatherapi_snow.json");
+
+#[test]
+fn test_weatherapi_snow_url_is_the_forecast() {
+    let p = weatherapi("http://w");
+
+    assert_eq!(
+        p.build_url(&Location::from("Chamonix"), DataKind::Snow, Some(5))
+            .unwrap(),
+        "http://w/forecast.json?key=KEY&q=Chamonix&days=5"
+    );
+    assert_eq!(
+        p.build_url(&Location::from("Chamonix"), DataKind::Snow, None)
+            .unwrap(),
+        "http://w/forecast.json?key=KEY&q=Chamonix&days=3"
+    );
+}
+
+#[test]
+fn test_weatherapi_snow_is_normalized() {
+    let days = weatherapi("http://w").snow(WEATHERAPI_SNOW).unwrap();
+
+    assert_eq!(days.len(), 3);
+    let day = &days[0];
+    assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 12, 10).unwrap());
+    assert_eq!(day.snow_cm, Some(12.5));
+    assert_eq!(day.snow_chance, Some(85.0));
+    assert_eq!(day.will_snow, Some(true));
+    assert_eq!(day.min_c, Some(-9.0));
+    assert_eq!(day.max_wind_kph, Some(25.2));
+    assert_eq!(day.condition.as_deref(), Some("Heavy snow"));
+
+    // The night ends at 06:00: the colder hour at 06:00 is not part of it.
+    assert_eq!(day.night_low_c, Some(-8.2));
+    assert_eq!(days[1].night_low_c, Some(-11.7));
+    assert_eq!(days[2].will_snow, Some(false));
+    assert_eq!(days[2].night_low_c, None);
+}
+
+#[test]
+fn test_text_puts_the_night_low_first() {
+    let text = output::summarize(
+        &weatherapi("http://w"),
+        "Chamonix",
+        "snow",
+        WEATHERAPI_SNOW,
+        OutputFormat::Text,
+    );
+
+    assert_eq!(
+        text,
+        "Chamonix\n\
+         \x20 2024-12-10   Night low -8.2 °C, min -9.0 °C, snow 12.5 cm (85%), wind up to 25.2 km/h  Heavy snow\n\
+         \x20 2024-12-11   Night low -11.7 °C, min -12.4 °C, snow 3.0 cm (62%), wind up to 14.8 km/h  Patchy light snow\n\
+         \x20 2024-12-12   Min -10.8 °C, snow 0.0 cm (0%), wind up to 9.4 km/h  Sunny\n"
+    );
+}
+
+#[test]
+fn test_table_has_a_row_per_day() {
+    let table = output::summarize(
+        &weatherapi("http://w"),
+        "Chamonix",
+        "snow",
+        WEATHERAPI_SNOW,
+        OutputFormat::Table,
+    );
+    let lines: Vec<&str> = table.lines().collect();
+
+    assert_eq!(lines.len(), 5, "{table}");
+    assert_eq!(
+        lines[1],
+        "Date        Night °C  Min °C  Snow cm  Snow %  Wind       Condition"
+    );
+    assert_eq!(
+        lines[2],
+        "2024-12-10      -8.2    -9.0     12.5      85  25.2 km/h  Heavy snow"
+    );
+    assert_eq!(
+        lines[4],
+        "2024-12-12         -   -10.8      0.0       0  9.4 km/h   Sunny"
+    );
+}
+
+#[test]
+fn test_json_carries_the_days() {
+    let json: serde_json::Value = serde_json::from_str(
+        &output::normalized_json(&weatherapi("http://w"), "snow", WEATHERAPI_SNOW).unwrap(),
+    )
+    .unwrap();
+    let day = &json["snow"][0];
+
+    assert_eq!(day["date"], "2024-12-10");
+    assert_eq!(day["snow_cm"], 12.5);
+    assert_eq!(day["snow_chance"], 85.0);
+    assert_eq!(day["will_snow"], true);
+    assert_eq!(day["night_low_c"], -8.2);
+    assert_eq!(json["snow"][2]["night_low_c"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_snow_only_from_weatherapi() {
+    assert!(validate_kind(&weatherapi("http://w"), DataKind::Snow).is_ok());
+
+    let tomorrow = TomorrowIoProvider {
+        api_key: "KEY".into(),
+        base_url: "http://t".into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let err = validate_kind(&tomorrow, DataKind::Snow)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.starts_with("data kind 'snow' is not supported by provider 'tomorrowio'"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_days_set_the_snow_range() {
+    let cli = Cli::parse_from([
+        "wapp", "get", "--city", "Chamonix", "--data", "snow", "--days", "5",
+    ]);
+    match cli.cmd {
+        Commands::Get { data, days, .. } => {
+            assert_eq!(data, vec![DataKind::Snow]);
+            assert_eq!(days, Some(5));
+        }
+        _ => panic!("wrong command parsed"),
+    }
+}
@@ -0,0 +1,125 @@
+use assert_cmd::Command;
+use clap::Parser;
+use wapp::cache::Cache;
+use wapp::cli::{Cli, Commands};
+use wapp::condition::Condition;
+use wapp::exit::CONFIG_EXIT_CODE;
+use wapp::output::status::{self, DEFAULT_STATUS_FORMAT};
+use wapp::output::Template;
+use wapp::weather::WeatherData;
+
+fn data() -> WeatherData {
+    WeatherData {
+        temp_c: Some(21.4),
+        condition: Some("Sunny".into()),
+        condition_kind: Some(Condition::Clear),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_default_status_line() {
+    let template = Template::parse(DEFAULT_STATUS_FORMAT).unwrap();
+    let line = status::render(&template, "London", &data());
+
+    assert_eq!(line, "☀️ 21°C London");
+    assert!(!line.contains('\x1b'));
+    assert!(!line.contains('\n'));
+}
+
+#[test]
+fn test_status_line_is_one_trimmed_line() {
+    let template = Template::parse("  {city}\n{condition}  ").unwrap();
+    assert_eq!(status::render(&template, "London", &data()), "London Sunny");
+
+    let missing = WeatherData::default();
+    let template = Template::parse(DEFAULT_STATUS_FORMAT).unwrap();
+    assert_eq!(
+        status::render(&template, "London", &missing),
+        "- -°C London"
+    );
+}
+
+#[test]
+fn test_last_known_response() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = Cache::new(dir.path());
+
+    assert_eq!(
+        status::last_known(&cache, "weatherapi", "now", "London"),
+        None
+    );
+
+    status::remember(&cache, "weatherapi", "now", "London", "{\"a\":1}", 100).unwrap();
+    status::remember(&cache, "weatherapi", "now", "London", "{\"a\":2}", 200).unwrap();
+
+    assert_eq!(
+        status::last_known(&cache, "weatherapi", "now", "london").as_deref(),
+        Some("{\"a\":2}")
+    );
+    assert_eq!(
+        status::last_known(&cache, "openweather", "now", "London"),
+        None
+    );
+    assert_eq!(status::stale_key("metno", "now", "Kyiv"), "metno:now:kyiv");
+}
+
+#[test]
+fn test_status_line_flags() {
+    let cli = Cli::parse_from([
+        "wapp",
+        "get",
+        "--city",
+        "London",
+        "--status-line",
+        "--status-format",
+        "{temp:.0}",
+        "--stale-fallback",
+    ]);
+    match cli.cmd {
+        Commands::Get {
+            status_line,
+            status_format,
+            stale_fallback,
+            ..
+        } => {
+            assert!(status_line);
+            assert_eq!(status_format, Some(Template::parse("{temp:.0}").unwrap()));
+            assert!(stale_fallback);
+        }
+        _ => panic!("expected get"),
+    }
+
+    let invalid = [
+        vec!["--status-line", "--output", "json"],
+        vec!["--status-line", "--fields", "temp"],
+        vec!["--status-format", "{city}"],
+        vec!["--stale-fallback"],
+        vec!["--status-line", "--status-format", "{nope}"],
+    ];
+    for args in invalid {
+        let argv = ["wapp", "get", "--city", "London"].into_iter().chain(args);
+        assert!(Cli::try_parse_from(argv).is_err());
+    }
+}
+
+#[test]
+fn test_status_line_reports_a_broken_config() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("config.json"), r#"{"provider": 42}"#).unwrap();
+
+    let output = Command::cargo_bin("wapp")
+        .unwrap()
+        .current_dir(dir.path())
+        .env("WAPP_CONFIG", dir.path().join("config.json"))
+        .env("WAPP_CACHE_DIR", dir.path().join("cache"))
+        .env("WAPP_LOG", "off")
+        .args(["get", "--city", "London", "--status-line"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(CONFIG_EXIT_CODE));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("config.json"), "{}", stderr);
+}
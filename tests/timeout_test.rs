@@ -0,0 +1,106 @@
+mod mocks;
+
+use std::time::{Duration, Instant};
+
+use clap::Parser;
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::weatherapi;
+use wapp::cli::{Cli, Commands};
+use wapp::config::AppConfig;
+use wapp::location::Location;
+use wapp::providers::{is_unreachable, ApiProvider, DataKind, WeatherApiProvider};
+use wapp::timeout::{describe_limit, timeouts_for, use_timeouts, Timeouts};
+
+const BODY: &str = r#"{"current":{"temp_c":1.0}}"#;
+
+/// WeatherAPI at `base_url`, giving up on its requests after 200 ms.
+fn impatient(base_url: &str) -> WeatherApiProvider {
+    use_timeouts(Timeouts {
+        connect: Duration::from_millis(200),
+        request: Duration::from_millis(200),
+    });
+    weatherapi(base_url)
+}
+
+#[tokio::test]
+async fn test_slow_provider_times_out() {
+    let server = HttpServer::start_delayed(vec![Reply::ok(BODY)], Duration::from_secs(2)).await;
+    let provider = impatient(&server.base_url);
+
+    let started = Instant::now();
+    let err = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(err.to_string(), "'weatherapi' did not answer within 0.2s");
+    assert!(is_unreachable(&err));
+}
+
+#[tokio::test]
+async fn test_answer_within_the_timeout() {
+    let server = HttpServer::start_delayed(vec![Reply::ok(BODY)], Duration::from_millis(20)).await;
+    let provider = impatient(&server.base_url);
+
+    let body = provider
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+    assert_eq!(body, BODY);
+}
+
+#[test]
+fn test_timeout_precedence() {
+    let secs = |t: Timeouts| t.request.as_secs();
+
+    assert_eq!(timeouts_for(None, None, None).unwrap(), Timeouts::default());
+    assert_eq!(secs(timeouts_for(None, None, Some(30)).unwrap()), 30);
+    assert_eq!(secs(timeouts_for(None, Some("20"), Some(30)).unwrap()), 20);
+    assert_eq!(
+        secs(timeouts_for(Some(3), Some("20"), Some(30)).unwrap()),
+        3
+    );
+    // An empty variable counts as unset.
+    assert_eq!(secs(timeouts_for(None, Some(" "), Some(30)).unwrap()), 30);
+
+    // Connecting never takes longer than the whole request.
+    assert_eq!(
+        timeouts_for(Some(3), None, None).unwrap().connect,
+        Duration::from_secs(3)
+    );
+    assert_eq!(
+        timeouts_for(Some(60), None, None).unwrap().connect,
+        Duration::from_secs(5)
+    );
+
+    assert_eq!(
+        timeouts_for(None, Some("0"), None).unwrap_err().to_string(),
+        "invalid WAPP_TIMEOUT '0': expected a number of seconds, at least 1"
+    );
+    assert!(timeouts_for(Some(3), Some("soon"), None).is_err());
+}
+
+#[test]
+fn test_describe_limit() {
+    assert_eq!(describe_limit(Duration::from_secs(15)), "15s");
+    assert_eq!(describe_limit(Duration::from_millis(2500)), "2.5s");
+}
+
+#[test]
+fn test_timeout_option_and_config() {
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--timeout", "5"]).unwrap();
+    match cli.cmd {
+        Commands::Get { timeout, .. } => assert_eq!(timeout, Some(5)),
+        _ => panic!("wrong command parsed"),
+    }
+    assert!(Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--timeout", "0"]).is_err());
+
+    let mut cfg = AppConfig::default();
+    cfg.set_key("timeout", "30").unwrap();
+    assert_eq!(cfg.timeout, Some(30));
+    assert_eq!(
+        cfg.set_key("timeout", "0").unwrap_err().to_string(),
+        "invalid value '0' for timeout: expected a number of seconds, at least 1"
+    );
+}
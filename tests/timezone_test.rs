@@ -0,0 +1,263 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::Parser;
+use wapp::cli::{Cli, Commands};
+use wapp::output::{self, Palette};
+use wapp::providers::{ApiProvider, OpenWeatherProvider, WeatherApiProvider};
+use wapp::timezone::{parse_time_format, TimeDisplay, Zone};
+use wapp::units::Units;
+
+/// Auckland on 10 June 2024, in winter: NZST, 12 hours ahead of UTC.
+const WEATHERAPI_AUCKLAND: &str = include_str!("fixtures/weatherapi_auckland.json");
+const OPENWEATHER_FORECAST: &str = include_str!("fixtures/openweather_auckland_forecast.json");
+const OPENWEATHER_CURRENT: &str = include_str!("fixtures/openweather_auckland_current.json");
+
+fn utc(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+fn auckland() -> Zone {
+    "Pacific/Auckland".parse().unwrap()
+}
+
+fn openweather() -> OpenWeatherProvider {
+    OpenWeatherProvider::new("KEY").with_units("metric")
+}
+
+#[test]
+fn test_zones_parse_and_print() {
+    assert_eq!(auckland().to_string(), "Pacific/Auckland");
+    assert_eq!(Zone::from_offset(43200).unwrap().to_string(), "UTC+12:00");
+    assert_eq!(Zone::from_offset(-12600).unwrap().to_string(), "UTC-03:30");
+
+    for offset in ["UTC+12:00", "UTC-03:30", "UTC+00:00"] {
+        assert_eq!(offset.parse::<Zone>().unwrap().to_string(), offset);
+    }
+    assert_eq!("UTC+5".parse::<Zone>().unwrap().to_string(), "UTC+05:00");
+    assert_eq!("UTC".parse::<Zone>().unwrap().to_string(), "UTC");
+
+    for invalid in ["Mars/Olympus", "UTC+25:00", "UTC*1", "UTC+1:75", ""] {
+        assert!(invalid.parse::<Zone>().is_err(), "{}", invalid);
+    }
+    assert!(Zone::from_offset(86_400).is_none());
+}
+
+#[test]
+fn test_wall_clock_follows_daylight_saving() {
+    let zone = auckland();
+
+    // NZST in June, NZDT in December
+    let winter = zone.local(utc("2024-06-09T21:00:00Z"));
+    assert_eq!(winter.to_string(), "2024-06-10 09:00:00");
+    let summer = zone.local(utc("2024-12-09T20:00:00Z"));
+    assert_eq!(summer.to_string(), "2024-12-10 09:00:00");
+    assert_eq!(zone.to_utc(winter), Some(utc("2024-06-09T21:00:00Z")));
+
+    // 02:30 happens twice on 7 April, when clocks go back, and not at all
+    // on 29 September, when they go forward.
+    let repeated = "2024-04-07T02:30:00".parse().unwrap();
+    assert_eq!(zone.to_utc(repeated), Some(utc("2024-04-06T13:30:00Z")));
+    let skipped = "2024-09-29T02:30:00".parse().unwrap();
+    assert_eq!(zone.to_utc(skipped), None);
+
+    let fixed = Zone::from_offset(43200).unwrap();
+    assert_eq!(
+        fixed.local(utc("2024-12-09T20:00:00Z")).to_string(),
+        "2024-12-10 08:00:00"
+    );
+}
+
+#[test]
+fn test_time_formats_are_checked() {
+    assert_eq!(parse_time_format("%H:%M").unwrap(), "%H:%M");
+    assert_eq!(
+        parse_time_format("%Y-%m-%d %H:%M %Z").unwrap(),
+        "%Y-%m-%d %H:%M %Z"
+    );
+
+    assert!(parse_time_format("").is_err());
+    let err = parse_time_format("%H:%Q").unwrap_err();
+    assert!(err.contains("invalid time format '%H:%Q'"), "{}", err);
+}
+
+#[test]
+fn test_times_show_in_the_location_zone() {
+    let time = utc("2024-06-09T21:00:00Z");
+    let zone = Some(auckland());
+
+    let local = TimeDisplay::default();
+    assert_eq!(local.show(time, zone, "%a %H:%M"), "Mon 09:00");
+    assert_eq!(
+        local.show(time, Zone::from_offset(43200), "%a %H:%M %Z"),
+        "Mon 09:00 +12:00"
+    );
+
+    let utc = TimeDisplay {
+        utc: true,
+        ..Default::default()
+    };
+    assert_eq!(utc.show(time, zone, "%a %H:%M"), "Sun 21:00");
+
+    let formatted = TimeDisplay {
+        utc: false,
+        format: Some("%Y-%m-%d %H:%M %Z".into()),
+    };
+    assert_eq!(
+        formatted.show(time, zone, "%a %H:%M"),
+        "2024-06-10 09:00 NZST"
+    );
+}
+
+#[test]
+fn test_providers_tell_the_zone() {
+    let weatherapi = WeatherApiProvider::new("KEY");
+    assert_eq!(weatherapi.timezone(WEATHERAPI_AUCKLAND), Some(auckland()));
+
+    let p = openweather();
+    let twelve = Zone::from_offset(12 * 3600);
+    assert_eq!(p.timezone(OPENWEATHER_CURRENT), twelve);
+    assert_eq!(p.timezone(OPENWEATHER_FORECAST), twelve);
+    assert_eq!(
+        p.timezone(r#"{"timezone":"Pacific/Auckland","timezone_offset":43200}"#),
+        Some(auckland())
+    );
+    assert_eq!(p.timezone("{}"), None);
+}
+
+#[test]
+fn test_weatherapi_times_are_local_to_auckland() {
+    let p = WeatherApiProvider::new("KEY");
+
+    let data = output::read(&p, "hourly", WEATHERAPI_AUCKLAND).unwrap();
+    assert_eq!(data.timezone, Some(auckland()));
+    assert_eq!(data.updated, Some(utc("2024-06-09T21:00:00Z")));
+
+    // The first hour of the local day is noon of the day before in UTC
+    let table = output::render_table_in("Auckland", &data, Palette::PLAIN, Units::Metric);
+    let times: Vec<&str> = table.lines().skip(2).map(|l| &l[..9]).collect();
+    assert_eq!(times, ["Mon 00:00", "Mon 06:00", "Mon 12:00", "Mon 18:00"]);
+
+    let data = output::read(&p, "now", WEATHERAPI_AUCKLAND).unwrap();
+    let text = output::render_text_in("Auckland", &data, Palette::PLAIN, Units::Metric);
+    assert!(
+        text.contains("  Updated:     Mon 10 Jun 09:00\n"),
+        "{}",
+        text
+    );
+
+    let json = data.to_json();
+    assert!(
+        json.contains(r#""updated": "2024-06-09T21:00:00Z""#),
+        "{}",
+        json
+    );
+    assert!(
+        json.contains(r#""timezone": "Pacific/Auckland""#),
+        "{}",
+        json
+    );
+}
+
+#[test]
+fn test_alerts_are_in_effect_in_auckland_time() {
+    let p = WeatherApiProvider::new("KEY");
+    let data = output::read(&p, "alerts", WEATHERAPI_AUCKLAND).unwrap();
+
+    let text = output::render_text_in("Auckland", &data, Palette::PLAIN, Units::Metric);
+    assert!(
+        text.contains("Mon 10 Jun 17:00 – Tue 11 Jun 08:00"),
+        "{}",
+        text
+    );
+
+    let table = output::render_table_in("Auckland", &data, Palette::PLAIN, Units::Metric);
+    assert!(
+        table.contains("Mon 10 Jun 17:00  Tue 11 Jun 08:00"),
+        "{}",
+        table
+    );
+}
+
+#[test]
+fn test_openweather_days_follow_auckland() {
+    let p = openweather();
+
+    // The first slot is 21:00 UTC on the 9th, already the 10th in Auckland
+    let dates: Vec<NaiveDate> = p
+        .daily(OPENWEATHER_FORECAST)
+        .unwrap()
+        .iter()
+        .map(|day| day.date)
+        .collect();
+    assert_eq!(
+        dates,
+        [
+            NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 6, 11).unwrap()
+        ]
+    );
+
+    // At 20:00 UTC on the 9th, tomorrow in Auckland is the 11th
+    let body = p
+        .keep_tomorrow(OPENWEATHER_FORECAST, utc("2024-06-09T20:00:00Z"))
+        .unwrap();
+    let days = p.daily(&body).unwrap();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].date.to_string(), "2024-06-11");
+
+    let data = output::read(&p, "hourly", OPENWEATHER_FORECAST).unwrap();
+    let table = output::render_table_in("Auckland", &data, Palette::PLAIN, Units::Metric);
+    let times: Vec<&str> = table.lines().skip(2).map(|l| &l[..9]).collect();
+    assert_eq!(
+        times,
+        [
+            "Mon 09:00",
+            "Mon 12:00",
+            "Mon 15:00",
+            "Mon 18:00",
+            "Mon 21:00",
+            "Tue 00:00",
+            "Tue 03:00"
+        ]
+    );
+}
+
+#[test]
+fn test_sun_times_are_local_to_auckland() {
+    let p = openweather();
+    let data = output::read(&p, "astro", OPENWEATHER_CURRENT).unwrap();
+
+    let text = output::render_text_in("Auckland", &data, Palette::PLAIN, Units::Metric);
+    assert!(
+        text.contains("  Date:        2024-06-10 (UTC+12:00)\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("  Sunrise:     07:30\n"), "{}", text);
+    assert!(text.contains("  Sunset:      17:00\n"), "{}", text);
+}
+
+#[test]
+fn test_time_options_are_parsed() {
+    let cli = Cli::try_parse_from([
+        "wapp",
+        "get",
+        "--city",
+        "Auckland",
+        "--utc",
+        "--time-format",
+        "%d.%m %H:%M",
+    ])
+    .unwrap();
+    assert!(cli.utc);
+    assert_eq!(cli.time_format.as_deref(), Some("%d.%m %H:%M"));
+    assert!(matches!(cli.cmd, Commands::Get { .. }));
+
+    let cli = Cli::try_parse_from(["wapp", "now", "Auckland"]).unwrap();
+    assert!(!cli.utc);
+    assert_eq!(cli.time_format, None);
+
+    let Err(err) = Cli::try_parse_from(["wapp", "get", "--time-format", "%H:%Q"]) else {
+        panic!("an invalid time format was accepted");
+    };
+    assert!(err.to_string().contains("invalid time format"), "{}", err);
+}
@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+
+use clap::Parser;
+use wapp::cli::{Cli, Commands};
+use wapp::config::AppConfig;
+use wapp::http::{client, read_ca_bundle, tls_for, use_tls, TlsSettings};
+
+const CA: &str = "tests/fixtures/ca.pem";
+
+#[test]
+fn test_ca_bundle_is_read() {
+    assert_eq!(read_ca_bundle(Path::new(CA)).unwrap().len(), 1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = dir.path().join("bundle.pem");
+    let pem = std::fs::read_to_string(CA).unwrap();
+    std::fs::write(&bundle, format!("{}{}", pem, pem)).unwrap();
+    assert_eq!(read_ca_bundle(&bundle).unwrap().len(), 2);
+}
+
+#[test]
+fn test_unusable_ca_bundles_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let missing = dir.path().join("missing.pem");
+    let err = read_ca_bundle(&missing).unwrap_err();
+    assert!(
+        format!("{:#}", err).starts_with(&format!("cannot read CA bundle {}: ", missing.display()))
+    );
+
+    let text = dir.path().join("notes.txt");
+    std::fs::write(&text, "not a certificate").unwrap();
+    assert_eq!(
+        read_ca_bundle(&text).unwrap_err().to_string(),
+        format!(
+            "CA bundle {} is not a PEM file of certificates (-----BEGIN CERTIFICATE-----)",
+            text.display()
+        )
+    );
+
+    let broken = dir.path().join("broken.pem");
+    std::fs::write(
+        &broken,
+        "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n",
+    )
+    .unwrap();
+    assert!(read_ca_bundle(&broken).is_err());
+
+    let mut cfg = AppConfig::default();
+    assert!(cfg
+        .set_key("ca_bundle", &text.display().to_string())
+        .is_err());
+    assert_eq!(cfg.ca_bundle, None);
+}
+
+#[test]
+fn test_client_fails_with_an_unusable_ca_bundle() {
+    let dir = tempfile::tempdir().unwrap();
+    let text = dir.path().join("notes.txt");
+    std::fs::write(&text, "not a certificate").unwrap();
+    use_tls(TlsSettings {
+        ca_bundle: Some(text.clone()),
+        insecure: false,
+    });
+
+    let err = client().unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with(&format!("CA bundle {} ", text.display())));
+    // Reported again, without reading the file again.
+    std::fs::remove_file(&text).unwrap();
+    assert!(client().unwrap_err().to_string().contains("not a PEM file"));
+}
+
+#[test]
+fn test_ca_bundle_precedence() {
+    let saved = || Some(PathBuf::from("/etc/saved.pem"));
+
+    assert_eq!(tls_for(false, None, None), TlsSettings::default());
+    assert_eq!(
+        tls_for(false, None, saved()).ca_bundle,
+        Some(PathBuf::from("/etc/saved.pem"))
+    );
+    assert_eq!(
+        tls_for(false, Some("/etc/env.pem"), saved()).ca_bundle,
+        Some(PathBuf::from("/etc/env.pem"))
+    );
+    assert_eq!(tls_for(false, Some(""), saved()).ca_bundle, saved());
+    assert!(tls_for(true, None, None).insecure);
+}
+
+#[test]
+fn test_parse_insecure() {
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv", "--insecure"]).unwrap();
+    assert!(matches!(cli.cmd, Commands::Get { insecure: true, .. }));
+    let cli = Cli::try_parse_from(vec!["wapp", "get", "--city", "Kyiv"]).unwrap();
+    assert!(matches!(
+        cli.cmd,
+        Commands::Get {
+            insecure: false,
+            ..
+        }
+    ));
+}
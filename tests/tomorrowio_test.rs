@@ -0,0 +1,123 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use mocks::http_server::HttpServer;
+use wapp::location::Location;
+use wapp::providers::{ApiProvider, DataKind, TomorrowIoProvider};
+
+const REALTIME: &str = r#"{"data":{"time":"2024-05-01T10:00:00Z","values":{"temperature":14.2,"temperatureApparent":13.1,"humidity":71,"pressureSurfaceLevel":1004.5,"windSpeed":5.0,"precipitationIntensity":0,"cloudCover":40,"weatherCode":1101}},"location":{"lat":50.45,"lon":30.52}}"#;
+
+const DAILY: &str = r#"{"timelines":{"daily":[{"time":"2024-05-01T04:00:00Z","values":{"temperatureMin":7.3,"temperatureMax":18.1,"rainAccumulationSum":0,"weatherCodeMax":1001}},{"time":"2024-05-02T04:00:00Z","values":{"temperatureMin":9.0,"temperatureMax":20.4,"rainAccumulationSum":2.6,"weatherCodeMax":4001}},{"time":"2024-05-03T04:00:00Z","values":{"temperatureMin":8.0,"temperatureMax":17.0,"rainAccumulationSum":0.4,"weatherCodeMax":4200}}]}}"#;
+
+fn provider(base_url: &str) -> TomorrowIoProvider {
+    TomorrowIoProvider {
+        api_key: "KEY".into(),
+        base_url: base_url.into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+fn url(kind: &str) -> String {
+    provider("https://api.tomorrow.io/v4")
+        .build_url(&Location::from("Paris, FR"), kind.parse().unwrap())
+        .unwrap()
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+#[test]
+fn test_now_url() {
+    assert_eq!(
+        url("now"),
+        "https://api.tomorrow.io/v4/weather/realtime?location=Paris%2C%20FR&units=metric&apikey=KEY"
+    );
+}
+
+#[test]
+fn test_forecast_url() {
+    assert_eq!(
+        url("forecast"),
+        "https://api.tomorrow.io/v4/weather/forecast?timesteps=1d&location=Paris%2C%20FR&units=metric&apikey=KEY"
+    );
+}
+
+#[test]
+fn test_tomorrow_url() {
+    assert_eq!(url("tomorrow"), url("forecast"));
+}
+
+#[test]
+fn test_hourly_url() {
+    assert_eq!(
+        url("hourly"),
+        "https://api.tomorrow.io/v4/weather/forecast?timesteps=1h&location=Paris%2C%20FR&units=metric&apikey=KEY"
+    );
+}
+
+#[test]
+fn test_unsupported_kind_is_rejected() {
+    let err = provider("http://x")
+        .build_url(&Location::from("Paris"), "2024-01-15".parse().unwrap())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "history is not supported by provider 'tomorrowio'"
+    );
+}
+
+#[test]
+fn test_missing_key_is_an_error() {
+    std::env::remove_var("TOMORROWIO_KEY");
+    std::env::remove_var("TOMORROWIO_KEY_FILE");
+
+    let err = TomorrowIoProvider::from_env().err().unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "TOMORROWIO_KEY (or TOMORROWIO_KEY_FILE) is not set"
+    );
+}
+
+#[test]
+fn test_realtime_and_daily_views() {
+    let p = provider("http://x");
+
+    let now = p.current(REALTIME).unwrap();
+    assert_eq!(now.temp_c, Some(14.2));
+    assert_eq!(now.wind_kph, Some(18.0));
+    assert_eq!(now.condition.as_deref(), Some("Partly cloudy"));
+
+    let days = p.daily(DAILY).unwrap();
+    assert_eq!(days.len(), 3);
+    assert_eq!(days[1].max_c, Some(20.4));
+    assert_eq!(days[1].condition.as_deref(), Some("Rain"));
+}
+
+#[tokio::test]
+async fn test_forecast_is_trimmed_to_days() {
+    let server = HttpServer::start(DAILY).await;
+    let p = provider(&server.base_url);
+
+    let body = p
+        .get_data(Location::from("Kyiv"), DataKind::Forecast, Some(2))
+        .await
+        .unwrap();
+
+    assert_eq!(p.forecast_dates(&body).unwrap(), vec![date(1), date(2)]);
+}
+
+#[tokio::test]
+async fn test_tomorrow_keeps_second_day() {
+    let server = HttpServer::start(DAILY).await;
+    let p = provider(&server.base_url);
+
+    let body = p
+        .get_data(Location::from("Kyiv"), DataKind::Tomorrow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(p.forecast_dates(&body).unwrap(), vec![date(2)]);
+}
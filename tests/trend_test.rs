@@ -0,0 +1,269 @@
+mod mocks;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+use mocks::cli::wapp_get;
+use mocks::http_server::HttpServer;
+use wapp::cli::Cli;
+use wapp::history::HistoryRecord;
+use wapp::series::SeriesPoint;
+use wapp::trend::{baseline, Change, Observations, Trend, MAX_OBSERVATIONS};
+use wapp::weather::WeatherData;
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+fn at(time: &str) -> DateTime<Utc> {
+    time.parse().unwrap()
+}
+
+fn observed(
+    city: &str,
+    time: DateTime<Utc>,
+    temp_c: Option<f64>,
+    pressure: Option<f64>,
+) -> HistoryRecord {
+    HistoryRecord {
+        point: SeriesPoint {
+            city: city.into(),
+            temp_c,
+            pressure_mb: pressure,
+            ..SeriesPoint::at(time)
+        },
+        provider: Some("weatherapi".into()),
+        kind: Some("now".into()),
+    }
+}
+
+fn wapp_trend(dir: &Path, base_url: &str) -> Command {
+    let mut cmd = wapp_get(dir, base_url);
+    cmd.arg("--trend");
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_baseline_is_the_latest_observation_older_than_the_gap() {
+    let now = at("2025-01-02T12:00:00Z");
+    let records = vec![
+        observed("Kyiv", at("2025-01-01T12:00:00Z"), Some(-3.0), None),
+        // Stored out of order, as by two processes.
+        observed("Kyiv", at("2025-01-02T10:00:00Z"), Some(1.0), Some(1013.0)),
+        observed("Kyiv", at("2025-01-02T09:00:00Z"), Some(0.0), None),
+        // Too recent with a one hour gap.
+        observed("Kyiv", at("2025-01-02T11:30:00Z"), Some(2.0), None),
+        observed("Lviv", at("2025-01-02T10:30:00Z"), Some(5.0), None),
+    ];
+
+    let chosen = baseline(&records, "kyiv", now, 3600).unwrap();
+    assert_eq!(chosen.point.time, at("2025-01-02T10:00:00Z"));
+
+    // A longer gap goes further back.
+    let chosen = baseline(&records, "Kyiv", now, 6 * 3600).unwrap();
+    assert_eq!(chosen.point.time, at("2025-01-01T12:00:00Z"));
+
+    // A gap of zero takes the latest, however recent.
+    let chosen = baseline(&records, "Kyiv", now, 0).unwrap();
+    assert_eq!(chosen.point.time, at("2025-01-02T11:30:00Z"));
+}
+
+#[test]
+fn test_baseline_without_prior_data_is_none() {
+    let now = at("2025-01-02T12:00:00Z");
+    assert!(baseline(&[], "Kyiv", now, 3600).is_none());
+
+    let records = vec![
+        observed("Lviv", at("2025-01-02T09:00:00Z"), Some(5.0), None),
+        observed("Kyiv", at("2025-01-02T11:59:00Z"), Some(2.0), None),
+        // Nothing to compare with.
+        observed("Kyiv", at("2025-01-02T08:00:00Z"), None, None),
+        // Observed after now, e.g. with the clock set back.
+        observed("Kyiv", at("2025-01-03T08:00:00Z"), Some(1.0), None),
+    ];
+    assert!(baseline(&records, "Kyiv", now, 3600).is_none());
+}
+
+#[test]
+fn test_trend_compares_what_both_observations_have() {
+    let earlier = observed("Kyiv", at("2025-01-02T10:00:00Z"), Some(27.0), None);
+    let data = WeatherData {
+        temp_c: Some(24.0),
+        pressure_mb: Some(1008.0),
+        ..Default::default()
+    };
+
+    let trend = Trend::between(&earlier, &data).unwrap();
+    assert_eq!(trend.since, at("2025-01-02T10:00:00Z"));
+    assert_eq!(
+        trend.temp_c,
+        Some(Change {
+            was: 27.0,
+            delta: -3.0
+        })
+    );
+    assert_eq!(trend.pressure_mb, None);
+
+    let no_temp = WeatherData {
+        pressure_mb: Some(1008.0),
+        ..Default::default()
+    };
+    assert_eq!(Trend::between(&earlier, &no_temp), None);
+}
+
+#[test]
+fn test_arrows_and_ages() {
+    let change = |delta| Change { was: 1013.0, delta };
+    assert_eq!(change(-5.0).arrow(1.0), "↓");
+    assert_eq!(change(2.0).arrow(1.0), "↑");
+    assert_eq!(change(-0.4).arrow(1.0), "→");
+
+    let trend = |since| Trend {
+        since: at(since),
+        temp_c: None,
+        pressure_mb: None,
+    };
+    let now = at("2025-01-02T12:00:00Z");
+    assert_eq!(trend("2025-01-02T10:00:00Z").ago(now), "2 hours ago");
+    assert_eq!(
+        trend("2025-01-01T12:30:00Z").ago(now),
+        "this time yesterday"
+    );
+    assert_eq!(trend("2024-12-30T12:00:00Z").ago(now), "3 days ago");
+}
+
+#[test]
+fn test_observations_keep_the_latest() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Observations::new(dir.path().join("data/observations.ndjson"));
+    assert!(store.all().unwrap().is_empty());
+
+    let start = at("2025-01-01T00:00:00Z");
+    for i in 0..MAX_OBSERVATIONS + 5 {
+        let time = start + Duration::minutes(i as i64);
+        store
+            .add(&observed("Kyiv", time, Some(i as f64), None))
+            .unwrap();
+    }
+
+    let kept = store.all().unwrap();
+    assert_eq!(kept.len(), MAX_OBSERVATIONS);
+    assert_eq!(kept[0].point.temp_c, Some(5.0));
+    assert_eq!(
+        kept.last().unwrap().point.temp_c,
+        Some((MAX_OBSERVATIONS + 4) as f64)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_first_query_has_no_annotations() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let text = stdout(wapp_trend(dir.path(), &server.base_url).args(["--output", "text"]));
+    assert!(text.contains("Temperature: 24.0 °C\n"), "{}", text);
+    assert!(!text.contains("Pressure"), "{}", text);
+
+    // The query is kept for later ones.
+    let store = Observations::new(dir.path().join("data/observations.ndjson"));
+    let kept = store.all().unwrap();
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].point.city, "Kyiv");
+    assert_eq!(kept[0].point.pressure_mb, Some(1017.0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_text_shows_the_change_since_the_earlier_query() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let store = Observations::new(dir.path().join("data/observations.ndjson"));
+    let now = Utc::now();
+    let two_hours = now - Duration::hours(2) - Duration::minutes(5);
+    store
+        .add(&observed("Kyiv", two_hours, Some(27.0), Some(1013.0)))
+        .unwrap();
+    // Within the gap, so not compared with.
+    store
+        .add(&observed(
+            "Kyiv",
+            now - Duration::minutes(10),
+            Some(20.0),
+            Some(1020.0),
+        ))
+        .unwrap();
+
+    let text = stdout(wapp_trend(dir.path(), &server.base_url).args(["--output", "text"]));
+    assert!(
+        text.contains("Temperature: 24.0 °C ↓ (3.0 °C colder than 2 hours ago)\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("Pressure:    1017 hPa ↑ (was 1013 2 hours ago)\n"),
+        "{}",
+        text
+    );
+
+    // In the detailed block, the pressure is shown once.
+    let text =
+        stdout(wapp_trend(dir.path(), &server.base_url).args(["--output", "text", "--detailed"]));
+    assert_eq!(text.matches("Pressure:").count(), 1, "{}", text);
+    assert!(
+        text.contains("1017 hPa ↑ (was 1013 2 hours ago)"),
+        "{}",
+        text
+    );
+
+    // With a shorter gap, the recent query is compared with.
+    let text = stdout(wapp_trend(dir.path(), &server.base_url).args([
+        "--output",
+        "text",
+        "--trend-gap",
+        "5m",
+    ]));
+    assert!(
+        text.contains("4.0 °C warmer than 10 minutes ago"),
+        "{}",
+        text
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_json_has_the_trend() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+    let dir = tempfile::tempdir().unwrap();
+    let store = Observations::new(dir.path().join("data/observations.ndjson"));
+    let since = Utc::now() - Duration::days(1);
+    store
+        .add(&observed("kyiv", since, Some(21.0), None))
+        .unwrap();
+
+    let json: serde_json::Value = serde_json::from_str(&stdout(
+        wapp_trend(dir.path(), &server.base_url).args(["--output", "json"]),
+    ))
+    .unwrap();
+    assert_eq!(json["temp_c"], 24.0);
+    assert_eq!(json["trend"]["temp_c"]["was"], 21.0);
+    assert_eq!(json["trend"]["temp_c"]["delta"], 3.0);
+    assert!(json["trend"].get("pressure_mb").is_none(), "{}", json);
+
+    // Without --trend, nothing changes.
+    let json: serde_json::Value = serde_json::from_str(&stdout(
+        wapp_get(dir.path(), &server.base_url).args(["--output", "json"]),
+    ))
+    .unwrap();
+    assert!(json.get("trend").is_none(), "{}", json);
+}
+
+#[test]
+fn test_trend_gap_takes_an_age_and_needs_trend() {
+    assert!(Cli::try_parse_from(["wapp", "get", "--trend", "--trend-gap", "30m"]).is_ok());
+    assert!(Cli::try_parse_from(["wapp", "get", "--trend-gap", "30m"]).is_err());
+    assert!(Cli::try_parse_from(["wapp", "get", "--trend", "--trend-gap", "soon"]).is_err());
+}
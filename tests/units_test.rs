@@ -0,0 +1,333 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use mocks::http_server::HttpServer;
+use wapp::cli::{check_raw_units, Cli, Commands};
+use wapp::exit::USAGE_EXIT_CODE;
+use wapp::output::{self, Palette};
+use wapp::series::DailySummary;
+use wapp::units::{
+    c_to_f, c_to_k, compass_point, f_to_c, k_to_c, knots_to_kph, kph_to_knots, kph_to_mph,
+    kph_to_ms, mph_to_kph, ms_to_kph, round, units_for, Units, WindUnit,
+};
+use wapp::weather::WeatherData;
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+fn current() -> WeatherData {
+    WeatherData {
+        temp_c: Some(24.0),
+        feels_like_c: Some(-40.0),
+        humidity: Some(36.0),
+        wind_kph: Some(36.0),
+        wind_degree: Some(320.0),
+        condition: Some("Sunny".into()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_temperature_conversions() {
+    assert!(close(c_to_f(0.0), 32.0));
+    assert!(close(c_to_f(100.0), 212.0));
+    assert!(close(c_to_f(-40.0), -40.0));
+    assert!(close(f_to_c(212.0), 100.0));
+    assert!(close(c_to_k(0.0), 273.15));
+    assert!(close(k_to_c(0.0), -273.15));
+
+    for c in [-12.3, 0.0, 24.8, 41.1] {
+        assert!(close(f_to_c(c_to_f(c)), c));
+        assert!(close(k_to_c(c_to_k(c)), c));
+    }
+}
+
+#[test]
+fn test_speed_conversions() {
+    assert!(close(kph_to_ms(36.0), 10.0));
+    assert!(close(ms_to_kph(10.0), 36.0));
+    assert!(close(mph_to_kph(1.0), 1.609344));
+    assert!(close(kph_to_mph(mph_to_kph(25.0)), 25.0));
+}
+
+#[test]
+fn test_knot_conversions() {
+    assert!(close(kph_to_knots(1.852), 1.0));
+    assert!(close(knots_to_kph(10.0), 18.52));
+    assert!(close(kph_to_knots(knots_to_kph(17.5)), 17.5));
+}
+
+#[test]
+fn test_wind_units() {
+    let kph = 36.0;
+    assert!(close(WindUnit::Kph.speed(kph), 36.0));
+    assert!(close(WindUnit::Ms.speed(kph), 10.0));
+    assert!(close(WindUnit::Mph.speed(kph), kph_to_mph(36.0)));
+    assert!(close(WindUnit::Knots.speed(kph), 36.0 / 1.852));
+
+    let symbols: Vec<&str> = [WindUnit::Kph, WindUnit::Ms, WindUnit::Mph, WindUnit::Knots]
+        .iter()
+        .map(|unit| unit.symbol())
+        .collect();
+    assert_eq!(symbols, ["km/h", "m/s", "mph", "kn"]);
+
+    assert_eq!(Units::Metric.wind_unit(), WindUnit::Kph);
+    assert_eq!(Units::Imperial.wind_unit(), WindUnit::Mph);
+    assert_eq!(Units::Standard.wind_unit(), WindUnit::Ms);
+}
+
+#[test]
+fn test_compass_points() {
+    let points = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+
+    // Every point's center and both edges of its 22.5° bucket.
+    for (i, point) in points.iter().enumerate() {
+        let center = i as f64 * 22.5;
+        assert_eq!(compass_point(center), *point, "{center}");
+        assert_eq!(compass_point(center - 11.25), *point, "{center}");
+        assert_eq!(compass_point(center + 11.24), *point, "{center}");
+    }
+
+    // The buckets of north.
+    assert_eq!(compass_point(348.74), "NNW");
+    assert_eq!(compass_point(348.75), "N");
+    assert_eq!(compass_point(11.24), "N");
+    assert_eq!(compass_point(11.25), "NNE");
+
+    // Bearings beyond a turn.
+    assert_eq!(compass_point(360.0), "N");
+    assert_eq!(compass_point(-11.25), "N");
+    assert_eq!(compass_point(-90.0), "W");
+    assert_eq!(compass_point(765.0), "NE");
+}
+
+#[test]
+fn test_rounding_goes_half_away_from_zero() {
+    assert_eq!(round(70.25, 1), 70.3);
+    assert_eq!(round(-0.05, 1), -0.1);
+    assert_eq!(round(24.04, 1), 24.0);
+    assert_eq!(round(21.5, 0), 22.0);
+    assert_eq!(round(-21.5, 0), -22.0);
+
+    // 21.25 °C is 70.25 °F, which plain formatting would show as 70.2.
+    assert_eq!(format!("{:.1}", round(c_to_f(21.25), 1)), "70.3");
+}
+
+#[test]
+fn test_units_by_name_and_precedence() {
+    assert_eq!("Imperial".parse::<Units>().unwrap(), Units::Imperial);
+    assert_eq!(" standard ".parse::<Units>().unwrap(), Units::Standard);
+    assert!("kelvin".parse::<Units>().is_err());
+
+    assert_eq!(
+        units_for(Some("imperial"), Some("standard"), Some("metric")).unwrap(),
+        Units::Imperial
+    );
+    assert_eq!(
+        units_for(None, Some("standard"), Some("imperial")).unwrap(),
+        Units::Standard
+    );
+    assert_eq!(
+        units_for(None, Some(" "), Some("imperial")).unwrap(),
+        Units::Imperial
+    );
+    assert_eq!(units_for(None, None, None).unwrap(), Units::Metric);
+
+    let err = units_for(None, Some("kelvin"), None)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("invalid units 'kelvin'"), "{err}");
+}
+
+#[test]
+fn test_text_in_imperial_and_standard() {
+    let text = output::render_text_in("Kyiv", &current(), Palette::PLAIN, Units::Imperial);
+    assert_eq!(
+        text,
+        "Kyiv\n\
+         \x20 Condition:   Sunny\n\
+         \x20 Temperature: 75.2 °F\n\
+         \x20 Feels like:  -40.0 °F\n\
+         \x20 Humidity:    36%\n\
+         \x20 Wind:        22.4 mph NW\n"
+    );
+
+    let text = output::render_text_in("Kyiv", &current(), Palette::PLAIN, Units::Standard);
+    assert!(text.contains("Temperature: 297.2 K\n"), "{text}");
+    assert!(text.contains("Wind:        10.0 m/s NW\n"), "{text}");
+}
+
+#[test]
+fn test_wind_line_has_gusts() {
+    let data = WeatherData {
+        wind_degree: Some(22.5),
+        gust_kph: Some(54.0),
+        ..current()
+    };
+
+    let text = output::render_text_in("Kyiv", &data, Palette::PLAIN, Units::Metric);
+    assert!(
+        text.contains("Wind:        36.0 km/h NNE, gusts 54.0 km/h\n"),
+        "{text}"
+    );
+
+    let text = output::render_text_in("Kyiv", &data, Palette::PLAIN, Units::Standard);
+    assert!(
+        text.contains("Wind:        10.0 m/s NNE, gusts 15.0 m/s\n"),
+        "{text}"
+    );
+
+    // Without a direction the gusts still follow the speed.
+    let data = WeatherData {
+        wind_degree: None,
+        ..data
+    };
+    let table = output::render_table_in("Kyiv", &data, Palette::PLAIN, Units::Metric);
+    assert!(table.contains("36.0 km/h, gusts 54.0 km/h"), "{table}");
+}
+
+#[test]
+fn test_table_headers_follow_the_units() {
+    let data = WeatherData {
+        forecast: vec![DailySummary {
+            date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            min_c: Some(10.0),
+            max_c: Some(30.0),
+            precip_mm: Some(0.4),
+            precip_chance: None,
+            max_wind_kph: None,
+            avg_wind_kph: None,
+            condition: Some("Sunny".into()),
+            partial: false,
+        }],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        output::render_table_in("Kyiv", &data, Palette::PLAIN, Units::Imperial),
+        "Kyiv\n\
+         ┌───────────┬────────────┐\n\
+         │ Date      │ 2024-06-10 │\n\
+         ├───────────┼────────────┤\n\
+         │ Condition │ Sunny      │\n\
+         │ High °F   │       86.0 │\n\
+         │ Low °F    │       50.0 │\n\
+         │ Rain %    │          - │\n\
+         │ Wind      │          - │\n\
+         └───────────┴────────────┘\n"
+    );
+
+    // Colors follow the temperature in °C: 86 °F is hot, 50 °F is not cold.
+    let colored = output::render_table_in("Kyiv", &data, Palette::ANSI, Units::Imperial);
+    assert!(colored.contains("\u{1b}[31m86.0\u{1b}[0m"), "{colored}");
+    assert!(!colored.contains("\u{1b}[34m"), "{colored}");
+}
+
+#[test]
+fn test_metric_output_is_unchanged() {
+    assert_eq!(
+        output::render_text_in("Kyiv", &current(), Palette::PLAIN, Units::Metric),
+        output::render_text("Kyiv", &current())
+    );
+}
+
+#[test]
+fn test_get_parses_units() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--units", "Imperial"]);
+    match cli.cmd {
+        Commands::Get { units, .. } => assert_eq!(units.as_deref(), Some("imperial")),
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(["wapp", "get", "--units", "kelvin"]).is_err());
+}
+
+#[test]
+fn test_wind_unit_flag() {
+    for (value, unit) in [
+        ("knots", WindUnit::Knots),
+        ("kn", WindUnit::Knots),
+        ("m/s", WindUnit::Ms),
+        ("km/h", WindUnit::Kph),
+        ("kph", WindUnit::Kph),
+        ("mph", WindUnit::Mph),
+    ] {
+        let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--wind-unit", value]);
+        match cli.cmd {
+            Commands::Get { wind_unit, .. } => assert_eq!(wind_unit, Some(unit), "{value}"),
+            _ => panic!("wrong command parsed"),
+        }
+    }
+
+    let cli = Cli::parse_from(["wapp", "now", "Kyiv", "--wind-unit", "mph"]);
+    assert!(matches!(
+        cli.cmd,
+        Commands::Now {
+            wind_unit: Some(WindUnit::Mph),
+            ..
+        }
+    ));
+
+    assert!(Cli::try_parse_from(["wapp", "get", "--wind-unit", "furlongs"]).is_err());
+}
+
+/// Runs `wapp now Kyiv` with `args` against a WeatherAPI at `base_url`.
+fn now(base_url: &str, args: &[&str]) -> std::process::Output {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("config.json"),
+        r#"{ "provider": "weatherapi" }"#,
+    )
+    .unwrap();
+    mocks::cli::wapp(dir.path(), base_url)
+        .env_remove("WAPP_UNITS")
+        .args(["now", "Kyiv"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_raw_json_units_only_for_openweather() {
+    assert!(check_raw_units("openweather", Some("imperial")).is_ok());
+    assert!(check_raw_units("weatherapi", None).is_ok());
+    assert!(check_raw_units("weatherapi", Some("metric")).is_ok());
+
+    let err = check_raw_units("weatherapi", Some("imperial")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "--units imperial does not change the JSON of 'weatherapi'; use --format text or table"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_now_units_with_weatherapi() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+
+    // The provider's JSON cannot be converted, so the flag is refused.
+    let output = now(
+        &server.base_url,
+        &["--units", "imperial", "--format", "json"],
+    );
+    assert_eq!(output.status.code(), Some(USAGE_EXIT_CODE));
+    assert_eq!(output.stdout, b"");
+    assert_eq!(server.requests(), 0);
+
+    // Text is the default, and converts.
+    let output = now(&server.base_url, &["--units", "imperial"]);
+    assert!(output.status.success(), "{:?}", output);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("Temperature: 75.2 °F\n"), "{}", text);
+
+    let output = now(&server.base_url, &["--units", "imperial", "--minimal"]);
+    assert!(output.status.success(), "{:?}", output);
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(text, "Kyiv: 75.2 °F, Sunny\n");
+}
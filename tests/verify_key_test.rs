@@ -0,0 +1,126 @@
+mod mocks;
+
+use mocks::http_server::{HttpServer, Reply};
+use mocks::providers::{openweather, weatherapi};
+use wapp::providers::{
+    is_unavailable, verify_key, AccuWeatherProvider, ApiProvider, KeyRejected, TomorrowIoProvider,
+    VisualCrossingProvider, WttrProvider,
+};
+
+fn status(status: u16) -> Reply {
+    Reply {
+        status,
+        headers: Vec::new(),
+        body: r#"{"error":{"code":2006,"message":"API key is invalid."}}"#,
+    }
+}
+
+#[test]
+fn test_verify_urls() {
+    assert_eq!(
+        weatherapi("http://w").verify_url(),
+        Some("http://w/current.json?key=KEY&q=51.5074,-0.1278".into())
+    );
+
+    assert!(openweather("http://o")
+        .verify_url()
+        .unwrap()
+        .starts_with("http://o/weather?lat=51.5074&lon=-0.1278&appid=KEY"));
+
+    let accuweather = AccuWeatherProvider {
+        api_key: "KEY".into(),
+        base_url: "http://a".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    assert!(accuweather
+        .verify_url()
+        .unwrap()
+        .starts_with("http://a/locations/v1/cities/geoposition/search?apikey=KEY"));
+
+    let tomorrowio = TomorrowIoProvider {
+        api_key: "KEY".into(),
+        base_url: "http://t".into(),
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    assert!(tomorrowio
+        .verify_url()
+        .unwrap()
+        .starts_with("http://t/weather/realtime?location=51.5074,-0.1278"));
+
+    let visualcrossing = VisualCrossingProvider {
+        api_key: "KEY".into(),
+        base_url: "http://v".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    assert!(visualcrossing.verify_url().unwrap().contains("key=KEY"));
+
+    let wttr = WttrProvider {
+        base_url: "http://w".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    assert_eq!(wttr.verify_url(), None);
+}
+
+#[tokio::test]
+async fn test_accepted_key() {
+    let server = HttpServer::start(r#"{"current":{}}"#).await;
+
+    verify_key(&weatherapi(&server.base_url)).await.unwrap();
+    assert_eq!(server.requests(), 1);
+    assert!(server.received()[0].starts_with("get /current.json?key=key&q=51.5074,-0.1278"));
+}
+
+#[tokio::test]
+async fn test_rejected_key() {
+    for code in [401, 403] {
+        let server = HttpServer::start_replies(vec![status(code)]).await;
+
+        let err = verify_key(&weatherapi(&server.base_url)).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<KeyRejected>().unwrap().status, code);
+        assert_eq!(
+            err.to_string(),
+            format!("'weatherapi' rejected the API key (HTTP {})", code)
+        );
+        assert!(!is_unavailable(&err));
+    }
+}
+
+#[tokio::test]
+async fn test_unverifiable_key() {
+    // Nothing listens on the discard port.
+    let err = verify_key(&weatherapi("http://127.0.0.1:9"))
+        .await
+        .unwrap_err();
+    assert!(is_unavailable(&err));
+
+    let server = HttpServer::start_replies(vec![status(503)]).await;
+    let err = verify_key(&weatherapi(&server.base_url)).await.unwrap_err();
+    assert!(is_unavailable(&err));
+
+    let server = HttpServer::start_replies(vec![status(400)]).await;
+    let err = verify_key(&weatherapi(&server.base_url)).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "'weatherapi' answered HTTP 400 to the key check"
+    );
+    assert!(err.downcast_ref::<KeyRejected>().is_none());
+}
+
+#[tokio::test]
+async fn test_keyless_provider_is_not_checked() {
+    let wttr = WttrProvider {
+        base_url: "http://127.0.0.1:9".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+
+    verify_key(&wttr).await.unwrap();
+}
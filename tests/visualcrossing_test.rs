@@ -0,0 +1,143 @@
+mod mocks;
+
+use mocks::http_server::HttpServer;
+use mocks::scripted_provider::ScriptedProvider;
+use wapp::location::{CityName, Location};
+use wapp::providers::{validate_kind, ApiProvider, DataKind, VisualCrossingProvider};
+use wapp::request::{execute, WeatherRequest};
+
+const BASE: &str =
+    "https://weather.visualcrossing.com/VisualCrossingWebServices/rest/services/timeline";
+
+const DAY: &str = r#"{"resolvedAddress":"Kyiv, Ukraine","days":[{"datetime":"2024-01-15","tempmax":-2.1,"tempmin":-8.4,"precip":0.3,"conditions":"Snow, Overcast"}],"currentConditions":{"datetimeEpoch":1705312800,"temp":-4.0,"feelslike":-9.2,"humidity":88,"pressure":1021,"windspeed":14.4,"precip":0,"cloudcover":100,"conditions":"Overcast"}}"#;
+
+fn provider() -> VisualCrossingProvider {
+    VisualCrossingProvider {
+        api_key: "KEY".into(),
+        base_url: BASE.into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+fn url(kind: &str, days: Option<u32>) -> String {
+    provider()
+        .build_url(&Location::from("Kyiv"), kind.parse().unwrap(), days)
+        .unwrap()
+}
+
+#[test]
+fn test_now_url() {
+    assert_eq!(
+        url("now", None),
+        format!("{BASE}/Kyiv/today?unitGroup=metric&include=current&key=KEY&contentType=json")
+    );
+}
+
+#[test]
+fn test_forecast_urls() {
+    assert_eq!(
+        url("forecast", None),
+        format!("{BASE}/Kyiv?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+    assert_eq!(
+        url("forecast", Some(7)),
+        format!("{BASE}/Kyiv/next6days?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+    assert_eq!(
+        url("forecast", Some(1)),
+        format!("{BASE}/Kyiv/today?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+    // Zero days must not underflow into `next4294967295days`.
+    assert_eq!(
+        url("forecast", Some(0)),
+        format!("{BASE}/Kyiv/today?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+    assert_eq!(
+        url("weekend", Some(0)),
+        format!("{BASE}/Kyiv/today?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+}
+
+#[test]
+fn test_tomorrow_url() {
+    assert_eq!(
+        url("tomorrow", None),
+        format!("{BASE}/Kyiv/tomorrow?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+}
+
+#[test]
+fn test_date_is_passed_through_as_history() {
+    assert_eq!(
+        url("2024-01-15", None),
+        format!("{BASE}/Kyiv/2024-01-15?unitGroup=metric&include=days&key=KEY&contentType=json")
+    );
+}
+
+#[test]
+fn test_location_is_encoded() {
+    let url = provider()
+        .build_url(&Location::from("New York, NY"), DataKind::Now, None)
+        .unwrap();
+
+    assert!(url.starts_with(&format!("{BASE}/New%20York%2C%20NY/today?")));
+}
+
+#[test]
+fn test_unknown_kind_and_bad_date_are_rejected() {
+    assert!(provider()
+        .build_url(&Location::from("Kyiv"), DataKind::Hourly, None)
+        .is_err());
+    assert!("2024-13-40".parse::<DataKind>().is_err());
+}
+
+#[test]
+fn test_only_history_providers_accept_dates() {
+    assert!(validate_kind(&provider(), "2024-01-15".parse().unwrap()).is_ok());
+
+    let err = validate_kind(
+        &ScriptedProvider::new(vec![]),
+        "2024-01-15".parse().unwrap(),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("not supported"));
+
+    let err = validate_kind(&provider(), DataKind::Hourly).unwrap_err();
+    assert!(err.to_string().ends_with("or a date as YYYY-MM-DD)"));
+}
+
+#[test]
+fn test_current_and_daily_views() {
+    let p = provider();
+
+    let now = p.current(DAY).unwrap();
+    assert_eq!(now.temp_c, Some(-4.0));
+    assert_eq!(now.condition.as_deref(), Some("Overcast"));
+
+    let days = p.daily(DAY).unwrap();
+    assert_eq!(days.len(), 1);
+    assert_eq!(days[0].min_c, Some(-8.4));
+    assert_eq!(days[0].condition.as_deref(), Some("Snow, Overcast"));
+}
+
+#[tokio::test]
+async fn test_history_request_is_executed() {
+    let server = HttpServer::start(DAY).await;
+    let provider = VisualCrossingProvider {
+        base_url: server.base_url.clone(),
+        ..provider()
+    };
+    let city = CityName::new("Kyiv").unwrap();
+
+    let body = execute(
+        &provider,
+        WeatherRequest::new(&city, "2024-01-15".parse().unwrap(), None),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(body, DAY);
+    assert_eq!(server.requests(), 1);
+}
@@ -0,0 +1,129 @@
+use chrono::{Local, TimeZone};
+use clap::Parser;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use wapp::cli::{Cli, Commands};
+use wapp::error::WappError;
+use wapp::watch::{interval, render_frame, run, WatchOptions, MIN_INTERVAL_SECS};
+
+fn options(clear: bool) -> WatchOptions {
+    WatchOptions {
+        interval: Duration::from_secs(60),
+        clear,
+    }
+}
+
+/// Returns a token cancelled after `secs` of (paused) time.
+fn cancel_after(secs: u64) -> CancellationToken {
+    let token = CancellationToken::new();
+    let trigger = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        trigger.cancel();
+    });
+    token
+}
+
+/// Runs the watch loop with time paused for `secs`, answering the n-th
+/// refresh with `script[n]`, and returns what was written.
+async fn watch_for(script: Vec<Result<&str, &str>>, clear: bool, secs: u64) -> String {
+    let calls = AtomicUsize::new(0);
+    let mut out = Vec::new();
+
+    run(&options(clear), &cancel_after(secs), &mut out, || {
+        let step = script[calls.fetch_add(1, Ordering::SeqCst)];
+        async move {
+            step.map(|body| body.as_bytes().to_vec()).map_err(|reason| {
+                anyhow::Error::new(WappError::Network {
+                    provider: "mock".into(),
+                    reason: reason.into(),
+                    unreachable: true,
+                })
+            })
+        }
+    })
+    .await
+    .unwrap();
+
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_interval_below_the_minimum_needs_force() {
+    let err = interval(5, false).unwrap_err().to_string();
+    assert!(err.contains("--force"), "{}", err);
+
+    assert_eq!(interval(5, true).unwrap(), Duration::from_secs(5));
+    assert_eq!(
+        interval(MIN_INTERVAL_SECS, false).unwrap(),
+        Duration::from_secs(MIN_INTERVAL_SECS)
+    );
+}
+
+#[test]
+fn test_frame_shows_when_the_data_was_updated() {
+    let updated = Local.with_ymd_and_hms(2025, 6, 1, 9, 5, 0).unwrap();
+
+    assert_eq!(
+        render_frame("Kyiv: 21°C\n", updated, None),
+        "Last updated: 2025-06-01 09:05:00\n\nKyiv: 21°C\n"
+    );
+
+    let stale = render_frame("Kyiv: 21°C\n", updated, Some("timed out"));
+    assert!(stale.starts_with("Warning: refresh failed: timed out;"));
+    assert!(stale.ends_with("Last updated: 2025-06-01 09:05:00\n\nKyiv: 21°C\n"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_every_tick_redraws_the_screen() {
+    // Ticks at t=0 and t=60; shut down before t=120.
+    let out = watch_for(vec![Ok("one\n"), Ok("two\n")], true, 90).await;
+
+    assert_eq!(out.matches("\x1b[2J").count(), 2);
+    assert!(out.ends_with("two\n"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_refresh_keeps_the_previous_data() {
+    let out = watch_for(vec![Ok("one\n"), Err("network down")], false, 90).await;
+
+    let last = out.split("\n\nWarning").nth(1).expect("a stale frame");
+    assert!(last.contains("network down"), "{}", out);
+    assert!(last.ends_with("one\n"), "{}", out);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_unavailable_provider_is_retried_on_the_next_tick() {
+    let out = watch_for(vec![Err("network down"), Ok("one\n")], false, 90).await;
+
+    assert!(out.starts_with("Warning: refresh failed"), "{}", out);
+    assert!(out.ends_with("one\n"), "{}", out);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_first_refresh_failing_otherwise_ends_the_watch() {
+    let mut out = Vec::new();
+    let result = run(&options(false), &cancel_after(90), &mut out, || async {
+        Err(anyhow::anyhow!("missing API key"))
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_parse_get_watch() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--watch", "300"]);
+    match cli.cmd {
+        Commands::Get { watch, force, .. } => {
+            assert_eq!(watch, Some(300));
+            assert!(!force);
+        }
+        _ => panic!("wrong command parsed"),
+    }
+
+    assert!(Cli::try_parse_from(["wapp", "get", "--force"]).is_err());
+    assert!(Cli::try_parse_from(["wapp", "get", "--watch", "60", "--dry-run"]).is_err());
+}
@@ -0,0 +1,208 @@
+mod mocks;
+
+use chrono::NaiveDate;
+use clap::Parser;
+use mocks::http_server::HttpServer;
+use mocks::providers::weatherapi;
+use wapp::cli::{Cli, Commands};
+use wapp::location::Location;
+use wapp::providers::openweather::OpenWeatherApi;
+use wapp::providers::{ApiProvider, DataKind, OpenWeatherProvider};
+use wapp::weather::UvCategory;
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+const WEATHERAPI_FORECAST: &str = include_str!("fixtures/weatherapi_forecast.json");
+const OPENWEATHER_CURRENT: &str = include_str!("fixtures/openweather_current.json");
+const OPENWEATHER_FORECAST: &str = include_str!("fixtures/openweather_forecast.json");
+const OPENWEATHER_ONECALL: &str = include_str!("fixtures/openweather_onecall.json");
+
+fn openweather(units: &str) -> OpenWeatherProvider {
+    mocks::providers::openweather("http://localhost").with_units(units)
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+}
+
+#[test]
+fn test_weatherapi_now() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_CURRENT)
+        .unwrap();
+
+    assert_eq!(data.temp_c, Some(24.0));
+    assert_eq!(data.feels_like_c, Some(24.8));
+    assert_eq!(data.humidity, Some(36.0));
+    assert_eq!(data.wind_kph, Some(11.2));
+    assert_eq!(data.wind_degree, Some(320.0));
+    assert_eq!(data.condition.as_deref(), Some("Sunny"));
+    assert!(data.forecast.is_empty());
+}
+
+#[test]
+fn test_weatherapi_forecast() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_FORECAST)
+        .unwrap();
+
+    assert_eq!(data.temp_c, Some(17.0));
+    assert_eq!(data.wind_degree, Some(250.0));
+
+    let days: Vec<NaiveDate> = data.forecast.iter().map(|d| d.date).collect();
+    assert_eq!(days, vec![date(10), date(11), date(12)]);
+    assert_eq!(data.forecast[1].min_c, Some(11.4));
+    assert_eq!(data.forecast[1].max_c, Some(16.8));
+    assert_eq!(
+        data.forecast[1].condition.as_deref(),
+        Some("Patchy rain nearby")
+    );
+}
+
+#[test]
+fn test_openweather_now() {
+    let data = openweather("metric").weather(OPENWEATHER_CURRENT).unwrap();
+
+    assert_eq!(data.temp_c, Some(23.46));
+    assert_eq!(data.feels_like_c, Some(23.12));
+    assert_eq!(data.humidity, Some(46.0));
+    assert_eq!(data.wind_kph, Some(14.4));
+    assert_eq!(data.wind_degree, Some(310.0));
+    assert_eq!(data.condition.as_deref(), Some("few clouds"));
+    assert!(data.forecast.is_empty());
+}
+
+#[test]
+fn test_openweather_forecast() {
+    let data = openweather("metric").weather(OPENWEATHER_FORECAST).unwrap();
+
+    assert_eq!(data.temp_c, None);
+    assert_eq!(data.wind_degree, None);
+    assert_eq!(data.forecast.first().map(|d| d.date), Some(date(10)));
+    assert_eq!(data.forecast.last().map(|d| d.date), Some(date(15)));
+    assert!(data
+        .forecast
+        .iter()
+        .all(|d| d.min_c.is_some() && d.min_c <= d.max_c));
+}
+
+#[test]
+fn test_uv_index_and_feels_like_of_both_providers() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_CURRENT)
+        .unwrap();
+    assert_eq!(data.feels_like_c, Some(24.8));
+    assert_eq!(data.uv_index, Some(7.0));
+    assert_eq!(data.uv_category, Some(UvCategory::High));
+
+    let onecall = openweather("metric").with_api(OpenWeatherApi::OneCall);
+    let data = onecall.weather(OPENWEATHER_ONECALL).unwrap();
+    assert_eq!(data.feels_like_c, Some(22.1));
+    assert_eq!(data.uv_index, Some(6.2));
+    assert_eq!(data.uv_category, Some(UvCategory::High));
+
+    // `/weather` of the 2.5 API has no UV index.
+    let data = openweather("metric").weather(OPENWEATHER_CURRENT).unwrap();
+    assert_eq!(data.feels_like_c, Some(23.12));
+    assert_eq!(data.uv_index, None);
+    assert_eq!(data.uv_category, None);
+}
+
+#[test]
+fn test_uv_category_thresholds() {
+    let category = |index| UvCategory::from_index(index);
+
+    assert_eq!(category(0.0), UvCategory::Low);
+    assert_eq!(category(2.4), UvCategory::Low);
+    assert_eq!(category(2.6), UvCategory::Moderate);
+    assert_eq!(category(5.0), UvCategory::Moderate);
+    assert_eq!(category(6.0), UvCategory::High);
+    assert_eq!(category(7.0), UvCategory::High);
+    assert_eq!(category(8.0), UvCategory::VeryHigh);
+    assert_eq!(category(10.4), UvCategory::VeryHigh);
+    assert_eq!(category(11.0), UvCategory::Extreme);
+    assert_eq!(category(14.0), UvCategory::Extreme);
+    assert_eq!(UvCategory::VeryHigh.label(), "Very High");
+}
+
+#[test]
+fn test_chance_of_precipitation_of_each_day() {
+    let chances = |days: &[wapp::series::DailySummary]| -> Vec<Option<f64>> {
+        days.iter()
+            .map(|d| d.precip_chance.map(|p| p.round()))
+            .collect()
+    };
+
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_FORECAST)
+        .unwrap();
+    assert_eq!(chances(&data.forecast), [Some(12.0), Some(78.0), Some(4.0)]);
+
+    // The day's highest `pop` of the 3-hourly steps, in the city's time
+    let data = openweather("metric").weather(OPENWEATHER_FORECAST).unwrap();
+    assert_eq!(chances(&data.forecast[..2]), [Some(5.0), Some(65.0)]);
+
+    let onecall = openweather("metric").with_api(OpenWeatherApi::OneCall);
+    let data = onecall.weather(OPENWEATHER_ONECALL).unwrap();
+    assert_eq!(chances(&data.forecast), [Some(35.0), Some(0.0), Some(80.0)]);
+
+    // Days without the chances of rain and snow have none, not 0%
+    let body = WEATHERAPI_FORECAST.replace("daily_chance_of_", "unknown_chance_of_");
+    let data = weatherapi("http://localhost").weather(&body).unwrap();
+    assert_eq!(chances(&data.forecast), [None, None, None]);
+}
+
+#[test]
+fn test_openweather_values_are_metric_in_any_units() {
+    let metric = openweather("metric").weather(OPENWEATHER_CURRENT).unwrap();
+    let imperial_body = OPENWEATHER_CURRENT.replace("23.46", "74.228");
+    let imperial = openweather("imperial").weather(&imperial_body).unwrap();
+
+    assert!((imperial.temp_c.unwrap() - metric.temp_c.unwrap()).abs() < 1e-9);
+}
+
+#[test]
+fn test_json_dates_and_missing_values() {
+    let data = weatherapi("http://localhost")
+        .weather(WEATHERAPI_FORECAST)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&data.to_json()).unwrap();
+
+    assert_eq!(json["forecast"][0]["date"], "2024-06-10");
+    assert_eq!(json["temp_c"], 17.0);
+
+    let json: serde_json::Value = serde_json::from_str(
+        &openweather("metric")
+            .weather(OPENWEATHER_FORECAST)
+            .unwrap()
+            .to_json(),
+    )
+    .unwrap();
+    assert!(json["temp_c"].is_null());
+}
+
+#[tokio::test]
+async fn test_get_weather_fetches_and_maps() {
+    let server = HttpServer::start(WEATHERAPI_CURRENT).await;
+
+    let data = weatherapi(&server.base_url)
+        .get_weather(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap();
+
+    assert_eq!(data.condition.as_deref(), Some("Sunny"));
+}
+
+#[test]
+fn test_get_raw_flag() {
+    let cli = Cli::parse_from(vec!["wapp", "get", "--city", "Kyiv", "--raw"]);
+    match cli.cmd {
+        Commands::Get { raw, .. } => assert!(raw),
+        _ => panic!("Expected Get command"),
+    }
+
+    let cli = Cli::parse_from(vec!["wapp", "get", "--city", "Kyiv"]);
+    match cli.cmd {
+        Commands::Get { raw, .. } => assert!(!raw),
+        _ => panic!("Expected Get command"),
+    }
+}
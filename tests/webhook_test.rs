@@ -0,0 +1,194 @@
+mod mocks;
+
+use std::path::Path;
+
+use assert_cmd::Command;
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use wapp::output;
+use wapp::providers::WeatherApiProvider;
+use wapp::webhook::{parse_header, payload, post, Webhook};
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const WEATHERAPI_CURRENT: &str = include_str!("fixtures/weatherapi_current.json");
+
+/// A server answering WeatherAPI's `/current.json` and posts to `/hook`
+/// with `hook_status`.
+async fn serve(hook_status: u16) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/current.json"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(WEATHERAPI_CURRENT))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(hook_status))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// `wapp get --city Kyiv` against WeatherAPI at `server`, posting to its
+/// `/hook`, with config, cache and data below `dir`.
+fn wapp_get(dir: &Path, server: &MockServer) -> Command {
+    let mut cmd = mocks::cli::wapp(dir, &server.uri());
+    cmd.args(["get", "--city", "Kyiv", "--provider", "weatherapi"])
+        .args(["--no-cache", "--output", "json"])
+        .arg("--webhook")
+        .arg(format!("{}/hook", server.uri()));
+    cmd
+}
+
+async fn posted(server: &MockServer) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_str() == "POST")
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_headers_are_parsed() {
+    assert_eq!(
+        parse_header("Authorization: Bearer a:b").unwrap(),
+        ("Authorization".into(), "Bearer a:b".into())
+    );
+    assert_eq!(
+        parse_header(" X-Source :wapp ").unwrap(),
+        ("X-Source".into(), "wapp".into())
+    );
+
+    for invalid in ["Authorization", ": value", "Bad Name: x", "X-Line: a\nb"] {
+        let err = parse_header(invalid).unwrap_err();
+        assert!(err.contains("invalid header"), "{}", err);
+    }
+}
+
+#[test]
+fn test_payload_adds_the_source_of_the_data() {
+    let p = WeatherApiProvider::new("KEY");
+    let json = output::normalized_json(&p, "now", WEATHERAPI_CURRENT).unwrap();
+    let fetched_at: DateTime<Utc> = "2024-06-10T09:30:00Z".parse().unwrap();
+
+    let body = payload(&json, "weatherapi", "Kyiv", "now", fetched_at).unwrap();
+    assert_eq!(body["provider"], "weatherapi");
+    assert_eq!(body["location"], "Kyiv");
+    assert_eq!(body["kind"], "now");
+    assert_eq!(body["fetched_at"], "2024-06-10T09:30:00Z");
+    assert!(body["temp_c"].is_number(), "{}", body);
+    assert!(body["forecast"].is_array(), "{}", body);
+
+    assert!(payload("[1]", "weatherapi", "Kyiv", "now", fetched_at).is_err());
+}
+
+#[tokio::test]
+async fn test_post_sends_json_with_the_headers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .and(header("authorization", "Bearer TOKEN"))
+        .and(header("content-type", "application/json"))
+        .and(body_partial_json(json!({ "location": "Kyiv" })))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let webhook = Webhook {
+        url: format!("{}/hook", server.uri()),
+        headers: vec![("Authorization".into(), "Bearer TOKEN".into())],
+        strict: true,
+    };
+
+    post(&webhook, &json!({ "location": "Kyiv", "temp_c": 21.5 }))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_post_reports_a_failed_status() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let webhook = Webhook {
+        url: format!("{}/hook", server.uri()),
+        headers: Vec::new(),
+        strict: false,
+    };
+
+    let err = post(&webhook, &json!({})).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "webhook answered 500 Internal Server Error"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_posts_the_fetched_weather() {
+    let server = serve(200).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server)
+        .args(["--webhook-header", "Authorization: Bearer TOKEN"])
+        .args(["--webhook-header", "X-Source: wapp"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let stdout: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let bodies = posted(&server).await;
+    assert_eq!(bodies.len(), 1);
+    let body = &bodies[0];
+    assert_eq!(body["provider"], "weatherapi");
+    assert_eq!(body["location"], "Kyiv");
+    assert_eq!(body["kind"], "now");
+    assert!(body["fetched_at"]
+        .as_str()
+        .unwrap()
+        .parse::<DateTime<Utc>>()
+        .is_ok());
+    assert_eq!(body["temp_c"], stdout["temp_c"]);
+    assert_eq!(body["condition"], stdout["condition"]);
+
+    let requests = server.received_requests().await.unwrap();
+    let hook = requests
+        .iter()
+        .find(|r| r.method.as_str() == "POST")
+        .unwrap();
+    assert_eq!(hook.headers["authorization"], "Bearer TOKEN");
+    assert_eq!(hook.headers["x-source"], "wapp");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_failed_webhook_keeps_the_output() {
+    let server = serve(503).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = wapp_get(dir.path(), &server).output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"temp_c\""));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr,
+        "Warning: webhook answered 503 Service Unavailable\n"
+    );
+
+    let output = wapp_get(dir.path(), &server)
+        .arg("--webhook-strict")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("\"temp_c\""));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("webhook answered 503 Service Unavailable"),
+        "{}",
+        stderr
+    );
+}
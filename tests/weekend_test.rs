@@ -0,0 +1,333 @@
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use clap::Parser;
+use wapp::cli::{Cli, Commands};
+use wapp::location::CityName;
+use wapp::output::color::Palette;
+use wapp::output::render_text_in;
+use wapp::output::report::render_markdown_in;
+use wapp::providers::{DataKind, WeatherApiProvider};
+use wapp::request::{validate_request, DateWindow, WeatherRequest};
+use wapp::series::{DailySummary, SeriesPoint};
+use wapp::timezone::Zone;
+use wapp::units::Units;
+use wapp::weather::WeatherData;
+use wapp::weekend::{fetch_days, summarize, today, verdict, window};
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+fn utc(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+}
+
+/// A dry forecast day of `min_c` to `max_c`.
+fn day(date: &str, min_c: f64, max_c: f64, chance: f64) -> DailySummary {
+    DailySummary {
+        date: self::date(date),
+        min_c: Some(min_c),
+        max_c: Some(max_c),
+        precip_mm: Some(0.0),
+        precip_chance: Some(chance),
+        max_wind_kph: Some(14.0),
+        avg_wind_kph: None,
+        condition: Some("Sunny".into()),
+        partial: false,
+    }
+}
+
+/// An hour from `time` with a `chance` of precipitation.
+fn hour(time: &str, chance: f64) -> SeriesPoint {
+    SeriesPoint {
+        precip_chance: Some(chance),
+        ..SeriesPoint::at(utc(time))
+    }
+}
+
+/// Thursday 2024-06-13 to Monday 2024-06-17 in UTC.
+fn five_days() -> WeatherData {
+    WeatherData {
+        temp_c: Some(21.0),
+        forecast: vec![
+            day("2024-06-13", 11.0, 19.0, 80.0),
+            day("2024-06-14", 12.0, 20.0, 10.0),
+            day("2024-06-15", 13.6, 21.5, 5.0),
+            day("2024-06-16", 14.0, 22.4, 20.0),
+            day("2024-06-17", 9.0, 18.0, 90.0),
+        ],
+        timezone: Some(Zone::Fixed(FixedOffset::east_opt(0).unwrap())),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_weekdays_look_ahead_to_saturday_and_sunday() {
+    let weekend = DateWindow {
+        from: date("2024-06-15"),
+        to: date("2024-06-16"),
+    };
+
+    // Monday through Friday.
+    for today in date("2024-06-10").iter_days().take(5) {
+        assert_eq!(window(today), (weekend, false), "{}", today);
+    }
+}
+
+#[test]
+fn test_the_weekend_under_way_keeps_what_is_left() {
+    assert_eq!(
+        window(date("2024-06-15")),
+        (
+            DateWindow {
+                from: date("2024-06-15"),
+                to: date("2024-06-16"),
+            },
+            true
+        )
+    );
+    assert_eq!(
+        window(date("2024-06-16")),
+        (
+            DateWindow {
+                from: date("2024-06-16"),
+                to: date("2024-06-16"),
+            },
+            true
+        )
+    );
+}
+
+#[test]
+fn test_today_is_the_date_of_the_location() {
+    // Late on Friday in UTC is Saturday in Auckland.
+    let now = utc("2024-06-14T23:30:00Z");
+    let auckland = Zone::Named("Pacific/Auckland".parse().unwrap());
+    assert_eq!(today(now, Some(auckland)), date("2024-06-15"));
+    assert!(window(today(now, Some(auckland))).1);
+
+    // Early on Saturday in UTC is still Friday in Los Angeles.
+    let now = utc("2024-06-15T03:00:00Z");
+    let los_angeles = Zone::Named("America/Los_Angeles".parse().unwrap());
+    assert_eq!(today(now, Some(los_angeles)), date("2024-06-14"));
+    assert!(!window(today(now, Some(los_angeles))).1);
+
+    let east = Zone::Fixed(FixedOffset::east_opt(5 * 3600).unwrap());
+    assert_eq!(
+        today(utc("2024-06-15T20:00:00Z"), Some(east)),
+        date("2024-06-16")
+    );
+}
+
+#[test]
+fn test_fetch_days_reach_the_end_of_the_weekend_in_any_zone() {
+    // Monday noon: Sunday is 6 days away everywhere.
+    assert_eq!(fetch_days(utc("2024-06-10T12:00:00Z")), 7);
+    // Friday noon: Saturday already began east of UTC+12.
+    assert_eq!(fetch_days(utc("2024-06-14T12:00:00Z")), 3);
+    // Sunday noon: Monday already began east of UTC+12, and its weekend
+    // is the next one.
+    assert_eq!(fetch_days(utc("2024-06-16T12:00:00Z")), 8);
+    // Saturday 23:00 is Saturday west of UTC-11.
+    assert_eq!(fetch_days(utc("2024-06-15T23:00:00Z")), 2);
+}
+
+#[test]
+fn test_rain_is_placed_in_the_part_of_the_day() {
+    let utc = Some(Zone::Fixed(FixedOffset::east_opt(0).unwrap()));
+    let days = [
+        day("2024-06-15", 13.0, 21.0, 20.0),
+        day("2024-06-16", 14.0, 22.0, 75.0),
+    ];
+    let mut hours: Vec<SeriesPoint> = (0..48)
+        .map(|h| {
+            let time = self::utc("2024-06-15T00:00:00Z") + Duration::hours(h);
+            SeriesPoint {
+                precip_chance: Some(10.0),
+                ..SeriesPoint::at(time)
+            }
+        })
+        .collect();
+    hours[24 + 14] = hour("2024-06-16T14:00:00Z", 75.0);
+
+    assert_eq!(
+        verdict(&days, &hours, utc, Units::Metric),
+        "Rain likely Sunday afternoon"
+    );
+
+    hours[6] = hour("2024-06-15T06:00:00Z", 40.0);
+    hours[20] = hour("2024-06-15T20:00:00Z", 45.0);
+    hours[24 + 14] = hour("2024-06-16T14:00:00Z", 50.0);
+    assert_eq!(
+        verdict(&days, &hours, utc, Units::Metric),
+        "Showers possible Saturday morning and evening and Sunday afternoon"
+    );
+
+    // The hours of the location's day, not of UTC: 22:00 UTC on Saturday
+    // is Sunday morning at UTC+10.
+    let east = Some(Zone::Fixed(FixedOffset::east_opt(10 * 3600).unwrap()));
+    let hours = [
+        hour("2024-06-15T22:00:00Z", 90.0),
+        hour("2024-06-16T04:00:00Z", 10.0),
+    ];
+    assert_eq!(
+        verdict(&days, &hours, east, Units::Metric),
+        "Rain likely Sunday morning"
+    );
+}
+
+#[test]
+fn test_days_without_hours_are_named_whole() {
+    let wet = [
+        day("2024-06-15", 13.0, 21.0, 70.0),
+        day("2024-06-16", 14.0, 22.0, 65.0),
+    ];
+    assert_eq!(
+        verdict(&wet, &[], None, Units::Metric),
+        "Rain likely Saturday and Sunday"
+    );
+
+    let showery = [
+        day("2024-06-15", 13.0, 21.0, 30.0),
+        day("2024-06-16", 14.0, 22.0, 10.0),
+    ];
+    assert_eq!(
+        verdict(&showery, &[], None, Units::Metric),
+        "Showers possible Saturday"
+    );
+
+    // Without a chance, a wet day's rain only makes showers possible.
+    let mut no_chance = day("2024-06-16", 14.0, 22.0, 0.0);
+    no_chance.precip_chance = None;
+    no_chance.precip_mm = Some(3.2);
+    assert_eq!(
+        verdict(&[no_chance], &[], None, Units::Metric),
+        "Showers possible Sunday"
+    );
+}
+
+#[test]
+fn test_dry_weekends_by_temperature() {
+    let days = |high: f64| {
+        [
+            day("2024-06-15", high - 8.4, high - 1.0, 10.0),
+            day("2024-06-16", high - 6.0, high, 0.0),
+        ]
+    };
+
+    assert_eq!(
+        verdict(&days(22.4), &[], None, Units::Metric),
+        "Good for hiking: dry, 14–22 °C"
+    );
+    assert_eq!(
+        verdict(&days(22.4), &[], None, Units::Imperial),
+        "Good for hiking: dry, 57–72 °F"
+    );
+    assert_eq!(
+        verdict(&days(31.6), &[], None, Units::Metric),
+        "Dry and hot: up to 32 °C"
+    );
+    assert_eq!(
+        verdict(&days(6.0), &[], None, Units::Metric),
+        "Dry but cold: -2–6 °C"
+    );
+    assert_eq!(
+        verdict(&[], &[], None, Units::Metric),
+        "No forecast for the weekend yet"
+    );
+}
+
+#[test]
+fn test_summary_keeps_the_weekend_and_its_hours_ahead() {
+    // Friday: the days before and after the weekend go.
+    let data = summarize(five_days(), &[], utc("2024-06-14T09:00:00Z"), Units::Metric);
+    let dates: Vec<NaiveDate> = data.forecast.iter().map(|day| day.date).collect();
+    assert_eq!(dates, [date("2024-06-15"), date("2024-06-16")]);
+    assert_eq!(data.temp_c, None);
+
+    assert_eq!(
+        render_text_in("Kyiv", &data, Palette::PLAIN, Units::Metric),
+        "Kyiv\n\
+         \x20 Verdict:     Good for hiking: dry, 14–22 °C\n\
+         \x20 2024-06-15   13.6 .. 21.5 °C  Sunny  Rain: 5% (0.0 mm)\n\
+         \x20 2024-06-16   14.0 .. 22.4 °C  Sunny  Rain: 20% (0.0 mm)\n"
+    );
+
+    // Sunday afternoon: the morning's rain is over.
+    let hours = [
+        hour("2024-06-16T08:00:00Z", 90.0),
+        hour("2024-06-16T17:00:00Z", 5.0),
+    ];
+    let data = summarize(
+        five_days(),
+        &hours,
+        utc("2024-06-16T15:20:00Z"),
+        Units::Metric,
+    );
+    let weekend = data.weekend.clone().unwrap();
+    assert!(weekend.under_way);
+    assert_eq!(weekend.verdict, "Good for hiking: dry, 14–22 °C");
+    assert_eq!(
+        render_text_in("Kyiv", &data, Palette::PLAIN, Units::Metric),
+        "Kyiv\n\
+         \x20 Verdict:     Good for hiking: dry, 14–22 °C\n\
+         \x20 2024-06-16   14.0 .. 22.4 °C  Sunny  Rain: 20% (0.0 mm)\n\
+         \x20 The weekend is under way: only the rest of it is shown\n"
+    );
+}
+
+#[test]
+fn test_verdict_in_json_and_markdown() {
+    let data = summarize(five_days(), &[], utc("2024-06-12T09:00:00Z"), Units::Metric);
+
+    let json: serde_json::Value = serde_json::from_str(&data.to_json()).unwrap();
+    assert_eq!(json["weekend"]["under_way"], false);
+    assert_eq!(json["weekend"]["verdict"], "Good for hiking: dry, 14–22 °C");
+    assert_eq!(json["forecast"].as_array().unwrap().len(), 2);
+
+    let markdown = render_markdown_in("Kyiv", &data, Units::Metric);
+    assert!(
+        markdown.starts_with("## Kyiv\n\n- **Verdict:** Good for hiking: dry, 14–22 °C\n| Date |"),
+        "{}",
+        markdown
+    );
+}
+
+#[test]
+fn test_parse_weekend_and_reject_days() {
+    let cli = Cli::parse_from(["wapp", "get", "--city", "Kyiv", "--data", "weekend"]);
+    match cli.cmd {
+        Commands::Get { data, .. } => assert_eq!(data, vec![DataKind::Weekend]),
+        _ => panic!("wrong command parsed"),
+    }
+
+    let provider = WeatherApiProvider {
+        api_key: "KEY".into(),
+        base_url: "http://w".into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    };
+    let city = CityName::new("Kyiv").unwrap();
+    assert!(validate_request(
+        &provider,
+        &WeatherRequest::new(&city, DataKind::Weekend, None)
+    )
+    .is_ok());
+
+    let request = WeatherRequest::new(&city, DataKind::Weekend, Some(2));
+    let err = validate_request(&provider, &request)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("--days does not apply to --data weekend"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_weekend_is_fetched_as_a_forecast() {
+    assert_eq!(DataKind::Weekend.fetched(), DataKind::Forecast);
+    assert_eq!(DataKind::Hourly.fetched(), DataKind::Hourly);
+    assert_eq!("weekend".parse::<DataKind>().unwrap(), DataKind::Weekend);
+    assert_eq!(DataKind::Weekend.to_string(), "weekend");
+}
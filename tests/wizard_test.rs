@@ -0,0 +1,81 @@
+use wapp::config::AppConfig;
+use wapp::providers::ProviderKind;
+use wapp::wizard::{ask, Answers};
+
+fn run(input: &str) -> (anyhow::Result<Answers>, String) {
+    let mut output = Vec::new();
+    let answers = ask(&mut input.as_bytes(), &mut output, false);
+    (answers, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn test_wizard_asks_until_answers_are_valid() {
+    let (answers, output) = run("9\nnope\n2\nKEY\nkelvin\nMetric\n\n");
+
+    assert_eq!(
+        answers.unwrap(),
+        Answers {
+            provider: ProviderKind::OpenWeather,
+            api_key: Some("KEY".into()),
+            units: Some("metric".into()),
+            lang: None,
+        }
+    );
+    assert!(output.starts_with("Supported providers:\n  1. weatherapi "));
+    assert!(output.contains("  8. wttr "));
+    assert!(output.contains("pick a number from 1 to 8\n"));
+    assert!(output.contains("provider 'nope' is not supported"));
+    assert!(output.contains("API key (empty to use OPENWEATHER_KEY): "));
+    assert!(output.contains("invalid units 'kelvin'"));
+}
+
+#[test]
+fn test_wizard_skips_key_for_keyless_providers() {
+    let (answers, output) = run("wttr.in\n\nuk\n");
+
+    assert_eq!(
+        answers.unwrap(),
+        Answers {
+            provider: ProviderKind::Wttr,
+            api_key: None,
+            units: None,
+            lang: Some("uk".into()),
+        }
+    );
+    assert!(!output.contains("API key (empty to use "));
+}
+
+#[test]
+fn test_wizard_stops_at_end_of_input() {
+    let (answers, _) = run("1\n");
+
+    assert_eq!(
+        answers.unwrap_err().to_string(),
+        "configure cancelled: no answer to 'API key (empty to use WEATHERAPI_KEY):'"
+    );
+}
+
+#[test]
+fn test_answers_replace_provider_settings() {
+    let mut cfg = AppConfig {
+        provider: ProviderKind::WeatherApi,
+        fallback_providers: vec![ProviderKind::Wttr],
+        api_keys: [("weatherapi".to_string(), "old".to_string())].into(),
+        default_city: Some("Kyiv".into()),
+        ..Default::default()
+    };
+
+    Answers {
+        provider: ProviderKind::OpenMeteo,
+        api_key: None,
+        units: Some("imperial".into()),
+        lang: None,
+    }
+    .apply(&mut cfg);
+
+    assert_eq!(cfg.provider, ProviderKind::OpenMeteo);
+    assert!(cfg.fallback_providers.is_empty());
+    assert_eq!(cfg.api_keys["weatherapi"], "old");
+    assert_eq!(cfg.units.as_deref(), Some("imperial"));
+    assert_eq!(cfg.default_city.as_deref(), Some("Kyiv"));
+}
@@ -0,0 +1,142 @@
+mod mocks;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use mocks::http_server::HttpServer;
+use wapp::location::Location;
+use wapp::providers::{ApiProvider, DataKind, WttrProvider};
+
+const J1: &str = r#"{
+    "current_condition": [{
+        "FeelsLikeC": "13", "cloudcover": "40", "humidity": "71",
+        "localObsDateTime": "2024-05-02 01:30 AM", "observation_time": "10:30 PM",
+        "precipMM": "0.0", "pressure": "1012", "temp_C": "14",
+        "weatherDesc": [{"value": "Partly cloudy"}], "windspeedKmph": "11"
+    }],
+    "weather": [
+        {"date": "2024-05-01", "maxtempC": "18", "mintempC": "7", "hourly": [
+            {"precipMM": "0.0", "weatherDesc": [{"value": "Sunny "}]},
+            {"precipMM": "0.4", "weatherDesc": [{"value": "Light rain"}]},
+            {"precipMM": "0.0", "weatherDesc": [{"value": "Sunny"}]}
+        ]},
+        {"date": "2024-05-02", "maxtempC": "20", "mintempC": "9", "hourly": []},
+        {"date": "2024-05-03", "maxtempC": "17", "mintempC": "8", "hourly": []}
+    ]
+}"#;
+
+const HTML_ERROR: &str = "<html><head><title>wttr.in</title></head><body><p>Sorry, we are running out of queries to the weather service at the moment.</p></body></html>";
+
+fn provider(base_url: &str) -> WttrProvider {
+    WttrProvider {
+        base_url: base_url.into(),
+        lang: None,
+        extra_params: Vec::new(),
+        kind_overrides: Default::default(),
+    }
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+}
+
+#[test]
+fn test_url() {
+    assert_eq!(
+        provider("https://wttr.in").build_url(&Location::from("Paris, FR")),
+        "https://wttr.in/Paris%2C%20FR?format=j1"
+    );
+    assert_eq!(
+        provider("https://wttr.in").build_url(&Location::iata("MUC").unwrap()),
+        "https://wttr.in/muc?format=j1"
+    );
+    assert_eq!(
+        provider("https://wttr.in").build_url(&Location::zip("94107").unwrap()),
+        "https://wttr.in/94107?format=j1"
+    );
+}
+
+#[test]
+fn test_current_resolves_utc_date() {
+    let now = provider("http://x").current(J1).unwrap();
+
+    // 22:30 UTC is on the previous day of local 01:30.
+    assert_eq!(
+        now.time,
+        Utc.with_ymd_and_hms(2024, 5, 1, 22, 30, 0).unwrap()
+    );
+    assert_eq!(now.temp_c, Some(14.0));
+    assert_eq!(now.condition.as_deref(), Some("Partly cloudy"));
+}
+
+#[test]
+fn test_daily_aggregates_hourly_entries() {
+    let days = provider("http://x").daily(J1).unwrap();
+
+    assert_eq!(days.len(), 3);
+    assert_eq!((days[0].min_c, days[0].max_c), (Some(7.0), Some(18.0)));
+    assert_eq!(days[0].precip_mm, Some(0.4));
+    assert_eq!(days[0].condition.as_deref(), Some("Sunny"));
+}
+
+#[test]
+fn test_html_page_is_a_readable_error() {
+    let err = WttrProvider::check_json(&Location::from("Kyiv"), HTML_ERROR).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "wttr.in returned no weather data for 'Kyiv': wttr.in Sorry, we are running out of queries to the weather service at the moment."
+    );
+}
+
+#[tokio::test]
+async fn test_forecast_slices_days() {
+    let server = HttpServer::start(J1).await;
+    let p = provider(&server.base_url);
+
+    let body = p
+        .get_data(Location::from("Kyiv"), DataKind::Forecast, Some(2))
+        .await
+        .unwrap();
+
+    assert_eq!(p.forecast_dates(&body).unwrap(), vec![date(1), date(2)]);
+}
+
+#[tokio::test]
+async fn test_tomorrow_keeps_second_day() {
+    let server = HttpServer::start(J1).await;
+    let p = provider(&server.base_url);
+
+    let body = p
+        .get_data(Location::from("Kyiv"), DataKind::Tomorrow, None)
+        .await
+        .unwrap();
+
+    assert_eq!(p.forecast_dates(&body).unwrap(), vec![date(2)]);
+}
+
+#[tokio::test]
+async fn test_html_response_fails_get_data() {
+    let server = HttpServer::start(HTML_ERROR).await;
+
+    let err = provider(&server.base_url)
+        .get_data(Location::from("Kyiv"), DataKind::Now, None)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("running out of queries"));
+}
+
+#[tokio::test]
+async fn test_unsupported_kind_fails_before_any_request() {
+    let server = HttpServer::start(J1).await;
+
+    let err = provider(&server.base_url)
+        .get_data(Location::from("Kyiv"), DataKind::Hourly, None)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "data kind 'hourly' is not supported by provider 'wttr'"
+    );
+    assert_eq!(server.requests(), 0);
+}